@@ -0,0 +1,52 @@
+//! Static libraries (`.a`, `.lib` and `.rlib`), archives of the relocatable objects they're
+//! linked from.
+
+use object::read::archive::ArchiveFile;
+
+pub struct Member<'data> {
+    pub name: String,
+    pub data: &'data [u8],
+}
+
+/// Whether `data` is an archive rather than an object.
+pub fn is_archive(data: &[u8]) -> bool {
+    ArchiveFile::parse(data).is_ok()
+}
+
+/// Objects in the archive, in the order they were added. Other files, like the metadata of a
+/// Rust library, are left out.
+pub fn members<'data>(data: &'data [u8]) -> Result<Vec<Member<'data>>, String> {
+    let archive =
+        ArchiveFile::parse(data).map_err(|err| format!("Failed to parse archive: {err}."))?;
+
+    let mut members = Vec::new();
+    for member in archive.members() {
+        let member = member.map_err(|err| format!("Failed to read archive member: {err}."))?;
+        let Ok(member_data) = member.data(data) else {
+            continue;
+        };
+
+        if object::File::parse(member_data).is_err() {
+            continue;
+        }
+
+        members.push(Member {
+            name: String::from_utf8_lossy(member.name()).into_owned(),
+            data: member_data,
+        });
+    }
+
+    Ok(members)
+}
+
+/// Object in the archive named `name`.
+pub fn member<'data>(data: &'data [u8], name: &str) -> Result<&'data [u8], String> {
+    let members = members(data)?;
+    match members.iter().find(|member| member.name == name) {
+        Some(member) => Ok(member.data),
+        None => {
+            let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+            Err(format!("Archive has no member '{name}', it has {}.", names.join(", ")))
+        }
+    }
+}
@@ -0,0 +1,355 @@
+//! Code signature of Mach-O objects, the blob `LC_CODE_SIGNATURE` points to.
+//!
+//! The signature is a superblob of big-endian blobs, of which the code directory holds the
+//! identifier, team and flags, and the CMS blob the certificates of the signing identity.
+
+use object::macho::{self, LinkeditDataCommand};
+use object::read::macho::{MachHeader, MachOFile};
+use object::Endianness;
+
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade0cc0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade0c02;
+const CSMAGIC_EMBEDDED_ENTITLEMENTS: u32 = 0xfade7171;
+const CSMAGIC_BLOBWRAPPER: u32 = 0xfade0b01;
+
+const CSSLOT_CODEDIRECTORY: u32 = 0;
+const CSSLOT_ENTITLEMENTS: u32 = 5;
+const CSSLOT_SIGNATURESLOT: u32 = 0x10000;
+
+/// Version of the code directory that added the team identifier.
+const CS_SUPPORTSTEAMID: u32 = 0x20200;
+
+/// Flags of the code directory, named like `codesign -d` does.
+const FLAGS: [(u32, &str); 10] = [
+    (0x1, "host"),
+    (0x2, "adhoc"),
+    (0x100, "hard"),
+    (0x200, "kill"),
+    (0x400, "expires"),
+    (0x800, "restrict"),
+    (0x1000, "enforcement"),
+    (0x2000, "library-validation"),
+    (0x10000, "runtime"),
+    (0x20000, "linker-signed"),
+];
+
+/// DER encoding of the object identifier of a certificate's common name, 2.5.4.3.
+const OID_COMMON_NAME: [u8; 5] = [0x06, 0x03, 0x55, 0x04, 0x03];
+
+pub struct CodeSignature {
+    /// Identifier the object was signed with, usually the bundle identifier.
+    pub identifier: String,
+    /// Team of the developer certificate, absent for ad-hoc signatures.
+    pub team: Option<String>,
+    pub flags: u32,
+    /// Algorithm the pages are hashed with.
+    pub hash: &'static str,
+    /// Number of hashed pages.
+    pub pages: u32,
+    /// Common names of the certificates, starting at the signing identity.
+    pub authorities: Vec<String>,
+    /// XML property list of entitlements.
+    pub entitlements: Option<String>,
+}
+
+impl CodeSignature {
+    /// Names of the set flags.
+    pub fn flag_names(&self) -> Vec<&'static str> {
+        FLAGS
+            .iter()
+            .filter(|(flag, _)| self.flags & flag != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Nul-terminated string at `offset`.
+fn read_str(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let len = bytes.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+}
+
+/// Blob at `offset` of the superblob if it has the expected magic, without its header.
+fn blob(data: &[u8], offset: usize, magic: u32) -> Option<&[u8]> {
+    if read_u32(data, offset)? != magic {
+        return None;
+    }
+
+    let len = read_u32(data, offset + 4)? as usize;
+    data.get(offset + 8..offset + len.max(8))
+}
+
+/// Common names of the certificate chain of a CMS signature, from the signing identity up to
+/// the root authority.
+///
+/// Rather than decoding the ASN.1, this looks for the common name attribute and takes the
+/// string that follows it. Every certificate names its issuer before its subject.
+fn common_names(cms: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut idx = 0;
+
+    while let Some(pos) = cms[idx..].windows(5).position(|w| w == OID_COMMON_NAME) {
+        idx += pos + OID_COMMON_NAME.len();
+
+        // Followed by a short UTF8String, PrintableString or IA5String.
+        let (Some(&tag), Some(&len)) = (cms.get(idx), cms.get(idx + 1)) else {
+            break;
+        };
+
+        if matches!(tag, 0x0c | 0x13 | 0x16) && len < 0x80 {
+            if let Some(name) = cms.get(idx + 2..idx + 2 + len as usize) {
+                names.push(String::from_utf8_lossy(name).into_owned());
+            }
+        }
+    }
+
+    // Any name left over is the issuer of the signer info, following the certificates.
+    let certs: Vec<(&String, &String)> =
+        names.chunks_exact(2).map(|pair| (&pair[0], &pair[1])).collect();
+
+    // The signing identity is the only certificate that didn't issue another one.
+    let leaf = certs.iter().find(|(_, subject)| {
+        !certs.iter().any(|(issuer, other)| issuer == subject && other != subject)
+    });
+
+    let mut chain: Vec<String> = Vec::new();
+    let mut current = leaf.map(|(_, subject)| *subject);
+    while let Some(name) = current.filter(|name| !chain.contains(name)) {
+        chain.push(name.clone());
+        current = certs.iter().find(|(_, subject)| *subject == name).map(|(issuer, _)| *issuer);
+    }
+
+    chain
+}
+
+fn code_directory(cd: &[u8], signature: &mut CodeSignature) -> Option<()> {
+    // Offsets within the code directory are from the start of its header.
+    let version = read_u32(cd, 0)?;
+    signature.flags = read_u32(cd, 4)?;
+    signature.identifier = read_str(cd, (read_u32(cd, 12)? as usize).checked_sub(8)?)?;
+    signature.pages = read_u32(cd, 20)?;
+    signature.hash = match cd.get(29)? {
+        1 => "sha1",
+        2 => "sha256",
+        3 => "sha256-truncated",
+        4 => "sha384",
+        _ => "unknown",
+    };
+
+    if version >= CS_SUPPORTSTEAMID {
+        let team = read_u32(cd, 40)? as usize;
+        if let Some(team) = team.checked_sub(8) {
+            signature.team = read_str(cd, team);
+        }
+    }
+
+    Some(())
+}
+
+fn parse<Mach: MachHeader<Endian = Endianness>>(
+    obj: &MachOFile<Mach>,
+) -> Result<Option<CodeSignature>, String> {
+    let header = obj.raw_header();
+    let endian = obj.endian();
+    let data = obj.data();
+
+    let malformed = |err: object::Error| format!("Failed to read load commands: {err}.");
+
+    let mut cmd = None;
+    let mut load_cmds_iter = header.load_commands(endian, data, 0).map_err(malformed)?;
+    while let Some(lcmd) = load_cmds_iter.next().map_err(malformed)? {
+        if lcmd.cmd() == macho::LC_CODE_SIGNATURE {
+            cmd = Some(lcmd.data::<LinkeditDataCommand<Endianness>>().map_err(malformed)?);
+        }
+    }
+
+    let Some(cmd) = cmd else {
+        return Ok(None);
+    };
+
+    let offset = cmd.dataoff.get(endian) as usize;
+    let size = cmd.datasize.get(endian) as usize;
+    let data = data
+        .get(offset..offset + size)
+        .ok_or_else(|| "Code signature lies outside of the object.".to_string())?;
+
+    parse_superblob(data).map(Some)
+}
+
+/// Parse the superblob of an embedded signature.
+fn parse_superblob(data: &[u8]) -> Result<CodeSignature, String> {
+    if read_u32(data, 0) != Some(CSMAGIC_EMBEDDED_SIGNATURE) {
+        return Err("Code signature has an unknown format.".to_string());
+    }
+
+    let mut signature = CodeSignature {
+        identifier: String::new(),
+        team: None,
+        flags: 0,
+        hash: "unknown",
+        pages: 0,
+        authorities: Vec::new(),
+        entitlements: None,
+    };
+
+    let count = read_u32(data, 8).unwrap_or(0) as usize;
+    for idx in 0..count {
+        let (Some(slot), Some(offset)) =
+            (read_u32(data, 12 + idx * 8), read_u32(data, 16 + idx * 8))
+        else {
+            break;
+        };
+
+        let offset = offset as usize;
+        match slot {
+            CSSLOT_CODEDIRECTORY => {
+                if let Some(cd) = blob(data, offset, CSMAGIC_CODEDIRECTORY) {
+                    code_directory(cd, &mut signature)
+                        .ok_or_else(|| "Code directory is truncated.".to_string())?;
+                }
+            }
+            CSSLOT_ENTITLEMENTS => {
+                if let Some(plist) = blob(data, offset, CSMAGIC_EMBEDDED_ENTITLEMENTS) {
+                    signature.entitlements = Some(String::from_utf8_lossy(plist).into_owned());
+                }
+            }
+            CSSLOT_SIGNATURESLOT => {
+                if let Some(cms) = blob(data, offset, CSMAGIC_BLOBWRAPPER) {
+                    signature.authorities = common_names(cms);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(signature)
+}
+
+/// Code signature of a Mach-O object, `None` for other objects or if it isn't signed.
+pub fn code_signature(obj: &object::File) -> Result<Option<CodeSignature>, String> {
+    match obj {
+        object::File::MachO32(macho) => parse(macho),
+        object::File::MachO64(macho) => parse(macho),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrap(magic: u32, payload: &[u8]) -> Vec<u8> {
+        let mut blob = magic.to_be_bytes().to_vec();
+        blob.extend((payload.len() as u32 + 8).to_be_bytes());
+        blob.extend(payload);
+        blob
+    }
+
+    fn directory(flags: u32, identifier: &str, team: &str) -> Vec<u8> {
+        let ident_offset = 52u32;
+        let team_offset = ident_offset + identifier.len() as u32 + 1;
+
+        let mut cd = Vec::new();
+        for word in [CS_SUPPORTSTEAMID, flags, 0, ident_offset, 0, 3, 0x4000] {
+            cd.extend(word.to_be_bytes());
+        }
+        // hash size, sha256, platform, log2 of the page size
+        cd.extend([32, 2, 0, 12]);
+        for word in [0, 0, team_offset] {
+            cd.extend(word.to_be_bytes());
+        }
+        cd.extend(identifier.as_bytes());
+        cd.push(0);
+        cd.extend(team.as_bytes());
+        cd.push(0);
+        wrap(CSMAGIC_CODEDIRECTORY, &cd)
+    }
+
+    /// Certificates given by their issuer and subject, as they appear in a CMS signature.
+    fn cms(names: &[&str]) -> Vec<u8> {
+        let mut cms = Vec::new();
+        for name in names {
+            cms.extend([0x30, 0x0b, 0x31, 0x09]);
+            cms.extend(OID_COMMON_NAME);
+            cms.extend([0x0c, name.len() as u8]);
+            cms.extend(name.as_bytes());
+        }
+        wrap(CSMAGIC_BLOBWRAPPER, &cms)
+    }
+
+    fn superblob(blobs: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut index = Vec::new();
+        let mut contents: Vec<u8> = Vec::new();
+        let mut offset = 12 + blobs.len() * 8;
+        for (slot, blob) in blobs {
+            index.extend(slot.to_be_bytes());
+            index.extend((offset as u32).to_be_bytes());
+            contents.extend(blob);
+            offset += blob.len();
+        }
+
+        let mut data = CSMAGIC_EMBEDDED_SIGNATURE.to_be_bytes().to_vec();
+        data.extend((offset as u32).to_be_bytes());
+        data.extend((blobs.len() as u32).to_be_bytes());
+        data.extend(index);
+        data.extend(contents);
+        data
+    }
+
+    #[test]
+    fn developer_id() {
+        let plist = "<plist><dict><key>com.apple.security.get-task-allow</key><true/></dict>";
+        let data = superblob(&[
+            (CSSLOT_CODEDIRECTORY, directory(0x10000, "com.example.app", "ABCDE12345")),
+            (CSSLOT_ENTITLEMENTS, wrap(CSMAGIC_EMBEDDED_ENTITLEMENTS, plist.as_bytes())),
+            (
+                CSSLOT_SIGNATURESLOT,
+                cms(&[
+                    "Apple Root CA",
+                    "Developer ID Certification Authority",
+                    "Developer ID Certification Authority",
+                    "Developer ID Application: Example (ABCDE12345)",
+                    "Apple Root CA",
+                    "Apple Root CA",
+                    // issuer of the signer info
+                    "Developer ID Certification Authority",
+                ]),
+            ),
+        ]);
+
+        let signature = parse_superblob(&data).unwrap();
+        assert_eq!(signature.identifier, "com.example.app");
+        assert_eq!(signature.team.as_deref(), Some("ABCDE12345"));
+        assert_eq!(signature.flag_names(), ["runtime"]);
+        assert_eq!((signature.hash, signature.pages), ("sha256", 3));
+        assert_eq!(signature.entitlements.as_deref(), Some(plist));
+        assert_eq!(
+            signature.authorities,
+            [
+                "Developer ID Application: Example (ABCDE12345)",
+                "Developer ID Certification Authority",
+                "Apple Root CA",
+            ]
+        );
+    }
+
+    #[test]
+    fn adhoc() {
+        let data = superblob(&[(CSSLOT_CODEDIRECTORY, directory(0x2, "a.out", ""))]);
+        let signature = parse_superblob(&data).unwrap();
+        assert_eq!(signature.flag_names(), ["adhoc"]);
+        assert!(signature.authorities.is_empty());
+    }
+
+    #[test]
+    fn unknown_format() {
+        let err = parse_superblob(&[0; 12]).err().unwrap();
+        assert_eq!(err, "Code signature has an unknown format.");
+    }
+}
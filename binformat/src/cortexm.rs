@@ -0,0 +1,131 @@
+//! Vector table of Cortex-M firmware, which sits at the start of the image.
+//!
+//! The first word is the initial stack pointer, followed by the addresses of the exception
+//! handlers and then those of the interrupts. Cortex-M cores only execute Thumb code, so every
+//! handler has its lowest bit set.
+
+/// Exceptions of the ARMv7-M and ARMv8-M architecture, by their index in the table.
+const EXCEPTIONS: [Option<&str>; 16] = [
+    None,
+    Some("Reset_Handler"),
+    Some("NMI_Handler"),
+    Some("HardFault_Handler"),
+    Some("MemManage_Handler"),
+    Some("BusFault_Handler"),
+    Some("UsageFault_Handler"),
+    Some("SecureFault_Handler"),
+    None,
+    None,
+    None,
+    Some("SVC_Handler"),
+    Some("DebugMon_Handler"),
+    None,
+    Some("PendSV_Handler"),
+    Some("SysTick_Handler"),
+];
+
+pub struct Handler {
+    pub name: String,
+    /// Address of the handler, without the Thumb bit.
+    pub addr: u64,
+}
+
+pub struct VectorTable {
+    /// Initial value of the stack pointer.
+    pub stack: u64,
+    /// Size of the table in bytes.
+    pub size: usize,
+    /// Every distinct handler, a handler shared by several vectors is named `Default_Handler`.
+    pub handlers: Vec<Handler>,
+}
+
+/// Vector table of the image in `data` loaded at `base`, if it starts with one.
+///
+/// A table is only accepted if its stack pointer is word aligned and its reset handler is a
+/// Thumb address inside the image, which rules out images that start with code. The table ends
+/// at the first vector that isn't either empty or a handler inside the image.
+pub fn vector_table(data: &[u8], base: u64) -> Option<VectorTable> {
+    let end = base + data.len() as u64;
+    let words: Vec<u64> = data
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()) as u64)
+        .collect();
+
+    let is_handler = |word: u64| word & 1 == 1 && (base..end).contains(&(word & !1));
+    let (&stack, &reset) = (words.first()?, words.get(1)?);
+    if stack == 0 || stack % 4 != 0 || !is_handler(reset) {
+        return None;
+    }
+
+    let count = 1 + words[1..]
+        .iter()
+        .take_while(|&&word| word == 0 || is_handler(word))
+        .count();
+
+    let mut handlers: Vec<(u64, String, usize)> = Vec::new();
+    for (idx, &word) in words[..count].iter().enumerate().skip(1) {
+        if word == 0 {
+            continue;
+        }
+
+        let name = match EXCEPTIONS.get(idx) {
+            Some(Some(name)) => name.to_string(),
+            Some(None) => format!("Vector{idx}_Handler"),
+            None => format!("IRQ{}_Handler", idx - EXCEPTIONS.len()),
+        };
+
+        match handlers.iter_mut().find(|(addr, ..)| *addr == word & !1) {
+            Some((_, _, uses)) => *uses += 1,
+            None => handlers.push((word & !1, name, 1)),
+        }
+    }
+
+    let handlers = handlers
+        .into_iter()
+        .map(|(addr, name, uses)| Handler {
+            // The reset handler keeps its name, as it's also the entry point.
+            name: if uses > 1 && name != "Reset_Handler" {
+                "Default_Handler".to_string()
+            } else {
+                name
+            },
+            addr,
+        })
+        .collect();
+
+    Some(VectorTable {
+        stack,
+        size: count * 4,
+        handlers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(words: &[u32]) -> Vec<u8> {
+        let mut data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        data.resize(0x100, 0);
+        data
+    }
+
+    #[test]
+    fn handlers() {
+        let data = image(&[0x2000_8000, 0x0800_0041, 0x0800_0061, 0x0800_0061, 0, 0xffff_ffff]);
+        let table = vector_table(&data, 0x0800_0000).unwrap();
+        assert_eq!(table.stack, 0x2000_8000);
+        assert_eq!(table.size, 20);
+        assert_eq!(table.handlers.len(), 2);
+        assert_eq!(table.handlers[0].name, "Reset_Handler");
+        assert_eq!(table.handlers[0].addr, 0x0800_0040);
+        assert_eq!(table.handlers[1].name, "Default_Handler");
+    }
+
+    #[test]
+    fn arm_code() {
+        // ARM mode branches at the start of a bootloader.
+        let data = image(&[0xea00_0006, 0xe59f_f014]);
+        assert!(vector_table(&data, 0).is_none());
+    }
+}
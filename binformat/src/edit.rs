@@ -0,0 +1,402 @@
+//! Rewriting the headers of an object, e.g. to add a section to it, or patching its contents.
+//!
+//! Nothing that's already in the object is moved, new contents and headers are appended to it
+//! instead, such that no other offsets have to be fixed up.
+
+use object::read::elf::{ElfFile, FileHeader, SectionHeader};
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::{elf, pe, Endian, Endianness, LittleEndian as LE, Object, ObjectKind, ObjectSection};
+
+/// Overwrite a 32 or 64-bit field of a header.
+pub(crate) fn patch_word(
+    bytes: &mut [u8],
+    offset: usize,
+    value: u64,
+    endian: Endianness,
+    is_64: bool,
+) {
+    if is_64 {
+        bytes[offset..offset + 8].copy_from_slice(&endian.write_u64_bytes(value));
+    } else {
+        bytes[offset..offset + 4].copy_from_slice(&endian.write_u32_bytes(value as u32));
+    }
+}
+
+pub(crate) fn patch_u32(bytes: &mut [u8], offset: usize, value: u32, endian: Endianness) {
+    bytes[offset..offset + 4].copy_from_slice(&endian.write_u32_bytes(value));
+}
+
+pub(crate) fn patch_u16(bytes: &mut [u8], offset: usize, value: u16, endian: Endianness) {
+    bytes[offset..offset + 2].copy_from_slice(&endian.write_u16_bytes(value));
+}
+
+pub(crate) fn align(data: &mut Vec<u8>, align: u64) {
+    let align = align.max(1) as usize;
+    data.resize(data.len().next_multiple_of(align), 0);
+}
+
+/// Append a section that isn't loaded, together with a copy of the section names and section
+/// header table that include it.
+fn add_elf_section<Elf: FileHeader<Endian = Endianness>>(
+    obj: &ElfFile<Elf>,
+    name: &str,
+    contents: &[u8],
+) -> Result<Vec<u8>, String> {
+    let endian = obj.endian();
+    let data = obj.data();
+    let header = obj.raw_header();
+    let is_64 = header.is_type_64();
+
+    let sections = header
+        .sections(endian, data)
+        .map_err(|err| format!("Failed to read section headers: {err}."))?;
+    let shstrndx = header
+        .shstrndx(endian, data)
+        .map_err(|err| format!("Failed to read section headers: {err}."))?;
+
+    if sections.is_empty() || sections.len() >= elf::SHN_LORESERVE as usize - 1 {
+        return Err("Object doesn't have a section header table to add to.".to_string());
+    }
+
+    let names = sections
+        .section(object::SectionIndex(shstrndx as usize))
+        .and_then(|section| section.data(endian, data))
+        .map_err(|err| format!("Failed to read section names: {err}."))?;
+
+    let mut out = data.to_vec();
+
+    let name_offset = names.len() as u32;
+    let names_offset = out.len() as u64;
+    out.extend_from_slice(names);
+    out.extend_from_slice(name.as_bytes());
+    out.push(0);
+
+    let contents_offset = out.len() as u64;
+    out.extend_from_slice(contents);
+
+    let (offset_at, size_at, align_at) = if is_64 {
+        (0x18, 0x20, 0x30)
+    } else {
+        (0x10, 0x14, 0x20)
+    };
+    let mut headers = Vec::new();
+    for (idx, section) in sections.iter().enumerate() {
+        let mut raw = object::bytes_of(section).to_vec();
+        if idx == shstrndx as usize {
+            patch_word(&mut raw, offset_at, names_offset, endian, is_64);
+            patch_word(
+                &mut raw,
+                size_at,
+                names.len() as u64 + name.len() as u64 + 1,
+                endian,
+                is_64,
+            );
+        }
+        headers.extend_from_slice(&raw);
+    }
+
+    let mut raw = vec![0; std::mem::size_of::<Elf::SectionHeader>()];
+    patch_u32(&mut raw, 0, name_offset, endian);
+    patch_u32(&mut raw, 4, elf::SHT_PROGBITS, endian);
+    patch_word(&mut raw, offset_at, contents_offset, endian, is_64);
+    patch_word(&mut raw, size_at, contents.len() as u64, endian, is_64);
+    patch_word(&mut raw, align_at, 1, endian, is_64);
+    headers.extend_from_slice(&raw);
+
+    align(&mut out, if is_64 { 8 } else { 4 });
+    let shoff = out.len() as u64;
+    out.extend_from_slice(&headers);
+
+    let (shoff_at, shnum_at) = if is_64 { (0x28, 0x3c) } else { (0x20, 0x30) };
+    patch_word(&mut out, shoff_at, shoff, endian, is_64);
+    patch_u16(&mut out, shnum_at, sections.len() as u16 + 1, endian);
+
+    Ok(out)
+}
+
+/// Checksum of an image as stored in its optional header, which skips the field itself.
+fn pe_checksum(data: &[u8], checksum_at: usize) -> u32 {
+    let mut sum = 0u64;
+    for (idx, chunk) in data.chunks(2).enumerate() {
+        if idx * 2 == checksum_at || idx * 2 == checksum_at + 2 {
+            continue;
+        }
+
+        let word = match *chunk {
+            [lo, hi] => u16::from_le_bytes([lo, hi]),
+            [lo] => lo as u16,
+            _ => unreachable!(),
+        };
+
+        sum += word as u64;
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    sum = (sum & 0xffff) + (sum >> 16);
+    sum as u32 + data.len() as u32
+}
+
+/// Append a read-only data section and map it after the last section.
+fn add_pe_section<Pe: ImageNtHeaders>(
+    obj: &PeFile<Pe>,
+    name: &str,
+    contents: &[u8],
+) -> Result<Vec<u8>, String> {
+    // Longer names are stored in the COFF string table, which images don't have.
+    if name.len() > 8 {
+        return Err("Sections of images can't have a name longer than 8 bytes.".to_string());
+    }
+
+    let data = obj.data();
+    let optional = obj.nt_headers().optional_header();
+    let section_alignment = optional.section_alignment().max(1);
+    let file_alignment = optional.file_alignment().max(1);
+
+    let coff_at = obj.dos_header().nt_headers_offset() as usize + 4;
+    let optional_at = coff_at + std::mem::size_of::<pe::ImageFileHeader>();
+    let table_at =
+        optional_at + obj.nt_headers().file_header().size_of_optional_header.get(LE) as usize;
+    let sections = obj.section_table();
+    let header_at = table_at + sections.len() * std::mem::size_of::<pe::ImageSectionHeader>();
+
+    // The new section header has to fit in front of the contents of the first section.
+    let first_contents = sections
+        .iter()
+        .map(|section| section.pointer_to_raw_data.get(LE))
+        .filter(|&offset| offset != 0)
+        .min()
+        .unwrap_or(u32::MAX);
+    let headers_end = optional.size_of_headers().min(first_contents) as usize;
+    if header_at + std::mem::size_of::<pe::ImageSectionHeader>() > headers_end {
+        return Err("Object doesn't have room for another section header.".to_string());
+    }
+
+    let image_end = sections
+        .iter()
+        .map(|section| {
+            let size = section.virtual_size.get(LE).max(section.size_of_raw_data.get(LE));
+            section.virtual_address.get(LE) + size
+        })
+        .max()
+        .unwrap_or(optional.size_of_headers());
+
+    let virtual_address = image_end.next_multiple_of(section_alignment);
+    let raw_size = (contents.len() as u32).next_multiple_of(file_alignment);
+
+    let mut out = data.to_vec();
+    align(&mut out, file_alignment as u64);
+    let raw_offset = out.len() as u32;
+    out.extend_from_slice(contents);
+    align(&mut out, file_alignment as u64);
+
+    let mut section_name = [0; 8];
+    section_name[..name.len()].copy_from_slice(name.as_bytes());
+
+    let header = &mut out[header_at..header_at + std::mem::size_of::<pe::ImageSectionHeader>()];
+    header[..8].copy_from_slice(&section_name);
+    patch_u32(header, 8, contents.len() as u32, Endianness::Little);
+    patch_u32(header, 12, virtual_address, Endianness::Little);
+    patch_u32(header, 16, raw_size, Endianness::Little);
+    patch_u32(header, 20, raw_offset, Endianness::Little);
+    let characteristics = pe::IMAGE_SCN_CNT_INITIALIZED_DATA | pe::IMAGE_SCN_MEM_READ;
+    patch_u32(header, 36, characteristics, Endianness::Little);
+
+    let size_of_image =
+        (virtual_address + contents.len() as u32).next_multiple_of(section_alignment);
+    patch_u16(
+        &mut out,
+        coff_at + 2,
+        sections.len() as u16 + 1,
+        Endianness::Little,
+    );
+    patch_u32(
+        &mut out,
+        optional_at + 56,
+        size_of_image,
+        Endianness::Little,
+    );
+
+    // Drivers and system libraries are rejected if the checksum doesn't match.
+    let checksum_at = optional_at + 64;
+    if optional.check_sum() != 0 {
+        let checksum = pe_checksum(&out, checksum_at);
+        patch_u32(&mut out, checksum_at, checksum, Endianness::Little);
+    }
+
+    Ok(out)
+}
+
+/// Copy of the object with a section of the given name and contents added to it.
+pub fn add_section(obj: &object::File, name: &str, contents: &[u8]) -> Result<Vec<u8>, String> {
+    if obj.section_by_name(name).is_some() {
+        return Err(format!("Object already has a section named '{name}'."));
+    }
+
+    match obj {
+        object::File::Elf32(elf) => add_elf_section(elf, name, contents),
+        object::File::Elf64(elf) => add_elf_section(elf, name, contents),
+        object::File::Pe32(pe) => add_pe_section(pe, name, contents),
+        object::File::Pe64(pe) => add_pe_section(pe, name, contents),
+        _ => Err("Sections can only be added to ELF and PE objects.".to_string()),
+    }
+}
+
+pub struct Patch {
+    /// Section the patched bytes are in.
+    pub section: String,
+    /// Offset of the patched bytes into the file.
+    pub offset: usize,
+    /// Bytes that were overwritten.
+    pub old: Vec<u8>,
+    /// Checksum of a PE image before and after it was fixed, if it was.
+    pub checksum: Option<(u32, u32)>,
+    /// Copy of the object with the bytes written to it.
+    pub data: Vec<u8>,
+}
+
+/// Recompute the checksum of a PE image in `out`, returning the old and new checksum.
+fn fix_pe_checksum<Pe: ImageNtHeaders>(obj: &PeFile<Pe>, out: &mut [u8]) -> (u32, u32) {
+    let optional_at = obj.dos_header().nt_headers_offset() as usize
+        + 4
+        + std::mem::size_of::<pe::ImageFileHeader>();
+    let checksum_at = optional_at + 64;
+    let checksum = pe_checksum(out, checksum_at);
+    patch_u32(out, checksum_at, checksum, Endianness::Little);
+    (obj.nt_headers().optional_header().check_sum(), checksum)
+}
+
+/// Copy of the object in `data` with `bytes` written at the virtual address `addr`.
+///
+/// The address is translated to an offset into the file through the section it's in, which has
+/// to hold every patched byte in the file, e.g. nothing can be written to `.bss`. With
+/// `fix_checksum`, the checksum of a PE image is updated to match its new contents.
+pub fn patch(data: &[u8], addr: u64, bytes: &[u8], fix_checksum: bool) -> Result<Patch, String> {
+    let obj = object::File::parse(data)
+        .map_err(|_| "Object doesn't have a header to find the address in.".to_string())?;
+
+    // Every section of a relocatable object starts at zero.
+    if obj.kind() == ObjectKind::Relocatable {
+        return Err("Relocatable objects aren't loaded at an address to patch.".to_string());
+    }
+
+    let section = obj
+        .sections()
+        .find(|section| (section.address()..section.address() + section.size()).contains(&addr))
+        .ok_or_else(|| format!("No section contains the address {addr:#x}."))?;
+
+    let name = section.name().unwrap_or("?").to_string();
+    let (file_offset, file_size) = section
+        .file_range()
+        .ok_or_else(|| format!("Section '{name}' has no contents in the object."))?;
+
+    let delta = addr - section.address();
+    if delta + bytes.len() as u64 > file_size {
+        return Err(format!("Patch runs past the contents of section '{name}' in the object."));
+    }
+
+    let offset = (file_offset + delta) as usize;
+    let mut out = data.to_vec();
+    let old = out[offset..offset + bytes.len()].to_vec();
+    out[offset..offset + bytes.len()].copy_from_slice(bytes);
+
+    let checksum = match obj {
+        _ if !fix_checksum => None,
+        object::File::Pe32(ref pe) => Some(fix_pe_checksum(pe, &mut out)),
+        object::File::Pe64(ref pe) => Some(fix_pe_checksum(pe, &mut out)),
+        _ => return Err("Only PE images have a checksum to fix.".to_string()),
+    };
+
+    Ok(Patch {
+        section: name,
+        offset,
+        old,
+        checksum,
+        data: out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::pe::{NtHeaders, Writer};
+
+    const IMAGE_BASE: u64 = 0x1_4000_0000;
+
+    /// Executable with 16 bytes of `.text` at 0x1000 and a `.bss` after it.
+    fn image() -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Writer::new(true, 0x1000, 0x200, &mut data);
+        writer.reserve_dos_header();
+        writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+        writer.reserve_section_headers(2);
+        let text = writer.reserve_text_section(16);
+        writer.reserve_bss_section(0x100);
+
+        writer.write_empty_dos_header().unwrap();
+        writer.write_nt_headers(NtHeaders {
+            machine: pe::IMAGE_FILE_MACHINE_AMD64,
+            time_date_stamp: 0,
+            characteristics: pe::IMAGE_FILE_EXECUTABLE_IMAGE,
+            major_linker_version: 14,
+            minor_linker_version: 0,
+            address_of_entry_point: text.virtual_address,
+            image_base: IMAGE_BASE,
+            major_operating_system_version: 6,
+            minor_operating_system_version: 0,
+            major_image_version: 0,
+            minor_image_version: 0,
+            major_subsystem_version: 6,
+            minor_subsystem_version: 0,
+            subsystem: pe::IMAGE_SUBSYSTEM_WINDOWS_CUI,
+            dll_characteristics: 0,
+            size_of_stack_reserve: 0x10_0000,
+            size_of_stack_commit: 0x1000,
+            size_of_heap_reserve: 0x10_0000,
+            size_of_heap_commit: 0x1000,
+        });
+        writer.write_section_headers();
+        writer.write_section(text.file_offset, &[0xcc; 16]);
+        data
+    }
+
+    #[test]
+    fn patch_text() {
+        let data = image();
+        let obj = object::File::parse(&*data).unwrap();
+        let (file_offset, _) = obj.section_by_name(".text").unwrap().file_range().unwrap();
+
+        let patched = patch(&data, IMAGE_BASE + 0x1004, &[0x90, 0x90], true).unwrap();
+        assert_eq!(patched.section, ".text");
+        assert_eq!(patched.offset, file_offset as usize + 4);
+        assert_eq!(patched.old, [0xcc, 0xcc]);
+        assert_eq!(patched.data[patched.offset..patched.offset + 3], [0x90, 0x90, 0xcc]);
+
+        // the new checksum is the one stored in the image.
+        let (old, new) = patched.checksum.unwrap();
+        assert_eq!(old, 0);
+        let repatched = patch(&patched.data, IMAGE_BASE + 0x1004, &[0x90], true).unwrap();
+        assert_eq!(repatched.checksum, Some((new, new)));
+
+        let unchanged = patch(&data, IMAGE_BASE + 0x1004, &[0x90], false).unwrap();
+        assert_eq!(unchanged.checksum, None);
+    }
+
+    #[test]
+    fn outside_contents() {
+        let data = image();
+        let err = |addr, bytes: &[u8]| patch(&data, IMAGE_BASE + addr, bytes, false).err();
+
+        assert_eq!(
+            err(0x100e, &[0x90; 4]).as_deref(),
+            Some("Patch runs past the contents of section '.text' in the object.")
+        );
+        assert_eq!(
+            err(0x2000, &[0]).as_deref(),
+            Some("Section '.bss' has no contents in the object.")
+        );
+        assert_eq!(
+            err(0x8000, &[0]),
+            Some(format!("No section contains the address {:#x}.", IMAGE_BASE + 0x8000))
+        );
+    }
+}
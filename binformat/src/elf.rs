@@ -2,12 +2,39 @@ use std::fmt;
 use crate::{datastructure, RawSymbol};
 use processor_shared::{AddressMap, Addressed, Section, SectionKind};
 use object::elf;
-use object::read::elf::{ElfFile, FileHeader, SectionHeader};
+use object::read::elf::{Dyn, ElfFile, FileHeader, ProgramHeader as _, SectionHeader};
+use object::read::StringTable;
 use object::{
-    Endian, Object, ObjectSection, ObjectSymbol, ObjectSymbolTable, RelocationKind,
-    RelocationTarget,
+    Architecture, Object, ObjectSection, ObjectSymbol, ObjectSymbolTable, RelocationKind,
+    RelocationTarget, SymbolKind,
 };
 
+/// Kind of content found at an address, as described by ELF mapping symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mapping {
+    /// Instructions of the default instruction set (`$a` on ARM, `$x` on AArch64/RISC-V).
+    Code,
+    /// Thumb instructions (`$t`).
+    Thumb,
+    /// Literal pools and other data embedded in code (`$d`).
+    Data,
+}
+
+/// Recognize mapping symbols, these can optionally have a suffix e.g. `$t.0`.
+fn parse_mapping_symbol(name: &str) -> Option<Mapping> {
+    let kind = match name.split_once('.') {
+        Some((kind, _)) => kind,
+        None => name,
+    };
+
+    match kind {
+        "$a" | "$x" => Some(Mapping::Code),
+        "$t" => Some(Mapping::Thumb),
+        "$d" => Some(Mapping::Data),
+        _ => None,
+    }
+}
+
 pub struct ElfDebugInfo<'data, Elf: FileHeader> {
     /// Parsed ELF header.
     obj: &'data ElfFile<'data, Elf>,
@@ -15,6 +42,10 @@ pub struct ElfDebugInfo<'data, Elf: FileHeader> {
     pub sections: Vec<Section>,
     /// Any parsed but not yet relocated symbols.
     pub syms: AddressMap<RawSymbol<'data>>,
+    /// Changes in instruction set or data regions, sorted by address.
+    pub mappings: AddressMap<Mapping>,
+    /// Names of the procedure linkage table stubs, which aren't in the object itself.
+    pub stubs: Vec<Addressed<String>>,
 }
 
 impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
@@ -23,6 +54,8 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
             obj,
             syms: AddressMap::default(),
             sections: Vec::new(),
+            mappings: AddressMap::default(),
+            stubs: Vec::new(),
         };
         this.sections = parse_sections(obj);
         this.parse_symbols();
@@ -31,6 +64,14 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
     }
 
     pub fn parse_imports(&mut self) {
+        // Stubs get an `@plt` suffix, like objdump does, to tell them apart from the GOT slot.
+        for (addr, name) in plt_entries(self.obj) {
+            self.stubs.push(Addressed {
+                addr: addr as usize,
+                item: format!("{name}@plt"),
+            });
+        }
+
         let relocations = match self.obj.dynamic_relocations() {
             Some(relocations) => relocations,
             None => return,
@@ -41,20 +82,21 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
             None => return,
         };
 
+        let versions = symbol_versions(self.obj);
+
         for (r_offset, reloc) in relocations {
             if let RelocationTarget::Symbol(idx) = reloc.target() {
-                let opt_section = self.obj.sections().find(|section| {
+                let in_section = self.obj.sections().any(|section| {
                     (section.address()..section.address() + section.size()).contains(&r_offset)
                 });
 
-                let section = match opt_section {
-                    Some(section) => section,
-                    None => continue,
-                };
+                if !in_section {
+                    continue;
+                }
 
                 if let Ok(sym) = dyn_syms.symbol_by_index(idx) {
                     let name = match sym.name() {
-                        Ok(name) => name,
+                        Ok(name) => versioned(name, versions.get(idx.0), false),
                         Err(..) => continue,
                     };
 
@@ -64,23 +106,7 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
                         RelocationKind::Elf(elf::R_X86_64_GLOB_DAT) => r_offset as usize,
                         RelocationKind::Elf(elf::R_X86_64_COPY) => r_offset as usize,
                         // address in .got.plt section which contains an address to the function
-                        RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) => {
-                            let width = if self.obj.is_64() { 8 } else { 4 };
-
-                            let bytes = match section.data_range(r_offset, width) {
-                                Ok(Some(bytes)) => bytes,
-                                _ => continue,
-                            };
-
-                            let phys_addr = if self.obj.is_64() {
-                                self.obj.endian().read_u64_bytes(bytes.try_into().unwrap()) as usize
-                            } else {
-                                self.obj.endian().read_u32_bytes(bytes.try_into().unwrap()) as usize
-                            };
-
-                            // idk why we need this
-                            phys_addr.saturating_sub(6)
-                        }
+                        RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) => r_offset as usize,
                         _ => continue,
                     };
 
@@ -95,14 +121,96 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
     }
 
     pub fn parse_symbols(&mut self) {
-        self.syms.extend(crate::parse_symbol_table(self.obj));
+        let is_arm = self.obj.architecture() == Architecture::Arm;
+
+        // On ARM the lowest bit of a function's address tells whether it's a thumb function.
+        // These are only used when the object doesn't contain any mapping symbols.
+        let mut func_mappings = AddressMap::default();
+        let thumb_bit = |addr: usize, func_mappings: &mut AddressMap<Mapping>| {
+            if !is_arm {
+                return addr;
+            }
+
+            let item = if addr & 1 == 1 { Mapping::Thumb } else { Mapping::Code };
+            func_mappings.push(Addressed { addr: addr & !1, item });
+            addr & !1
+        };
+
+        // Symbols of relocatable objects are relative to their section.
+        let layout = crate::relocatable_layout(self.obj);
+
+        for sym in self.obj.symbols() {
+            let name = match sym.name() {
+                Ok(name) => name,
+                Err(err) => {
+                    log::complex!(
+                        w "[elf::parse_symbols] ",
+                        y err.to_string(),
+                        y "."
+                    );
+                    continue;
+                }
+            };
+
+            let mut addr = sym.address() as usize;
+            if let Some(base) = sym.section_index().and_then(|idx| layout.get(idx.0)) {
+                addr += *base as usize;
+            }
+
+            if let Some(mapping) = parse_mapping_symbol(name) {
+                self.mappings.push(Addressed { addr, item: mapping });
+                continue;
+            }
+
+            if sym.kind() == SymbolKind::Text {
+                addr = thumb_bit(addr, &mut func_mappings);
+            }
+
+            self.syms.push(Addressed {
+                addr,
+                item: RawSymbol { name, module: None },
+            });
+        }
+
+        // Stripped shared libraries still name the functions they export.
+        if self.obj.symbol_table().is_none() {
+            let versions = symbol_versions(self.obj);
+            for sym in self.obj.dynamic_symbols() {
+                if sym.is_undefined() || sym.kind() != SymbolKind::Text {
+                    continue;
+                }
+
+                let Ok(name) = sym.name() else {
+                    continue;
+                };
+
+                let addr = thumb_bit(sym.address() as usize, &mut func_mappings);
+                self.syms.push(Addressed {
+                    addr,
+                    item: RawSymbol {
+                        name: versioned(name, versions.get(sym.index().0), true),
+                        module: None,
+                    },
+                });
+            }
+        }
+
+        let entry = thumb_bit(self.obj.entry() as usize, &mut func_mappings);
         self.syms.push(Addressed {
-            addr: self.obj.entry() as usize,
+            addr: entry,
             item: RawSymbol {
                 name: "entry",
                 module: None,
             },
         });
+
+        if self.mappings.is_empty() {
+            self.mappings = func_mappings;
+        }
+
+        // Only keep one mapping per address.
+        self.mappings.sort_by_key(|mapping| mapping.addr);
+        self.mappings.dedup_by_key(|mapping| mapping.addr);
     }
 }
 
@@ -134,10 +242,14 @@ fn parse_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Ve
     let mut sections = Vec::new();
     let endian = obj.endian();
     let section_headers = obj.raw_header().sections(endian, obj.data()).unwrap();
+    let layout = crate::relocatable_layout(obj);
 
     for (header, section) in section_headers.iter().zip(obj.sections()) {
         let sh_flags = header.sh_flags(endian).into();
-        let (name, bytes, start, end) = crate::parse_section_generics(&section);
+        let (name, bytes, mut start, mut end) = crate::parse_section_generics(&section);
+        if let Some(base) = layout.get(section.index().0) {
+            (start, end) = (*base as usize, *base as usize + (end - start));
+        }
 
         let (mut kind, ident) = match header.sh_type(endian) {
             // Section header table entry is unused.
@@ -257,9 +369,373 @@ fn parse_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Ve
         ));
     }
 
+    // Objects processed by `sstrip` or a packer don't have any section headers left.
+    if !sections.iter().any(|section| section.kind != SectionKind::Unloaded) {
+        sections.extend(segment_sections(obj));
+    }
+
     sections
 }
 
+/// Sections derived from the loadable segments, for objects without section headers.
+fn segment_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Vec<Section> {
+    let endian = obj.endian();
+    let mut sections = Vec::new();
+    let loadable = obj.raw_segments().iter().filter(|segment| {
+        segment.p_type(endian) == elf::PT_LOAD && Into::<u64>::into(segment.p_filesz(endian)) != 0
+    });
+
+    for (idx, segment) in loadable.enumerate() {
+        let bytes: &'static [u8] = match segment.data(endian, obj.data()) {
+            // The file is memory mapped so only the bytes are of lifetime &'static [u8].
+            Ok(data) => unsafe { std::mem::transmute::<&[u8], &'static [u8]>(data) },
+            Err(..) => continue,
+        };
+
+        let kind = if segment.p_flags(endian) & elf::PF_X != 0 {
+            SectionKind::Code
+        } else {
+            SectionKind::Raw
+        };
+
+        let start = Into::<u64>::into(segment.p_vaddr(endian)) as usize;
+        sections.push(Section::new(
+            format!("LOAD{idx}"),
+            "LOAD",
+            kind,
+            bytes,
+            start,
+            start + bytes.len(),
+        ));
+    }
+
+    sections
+}
+
+/// Entries of the `.dynamic` section together with the string table they refer to.
+pub fn dynamic_entries<'data, Elf: FileHeader>(
+    obj: &ElfFile<'data, Elf>,
+) -> Option<(&'data [Elf::Dyn], StringTable<'data>)> {
+    let endian = obj.endian();
+    let sections = obj.raw_header().sections(endian, obj.data()).ok()?;
+    let (entries, link) = sections.dynamic(endian, obj.data()).ok()??;
+    let strings = sections.strings(endian, obj.data(), link).ok()?;
+    Some((entries, strings))
+}
+
+/// Version of a dynamic symbol, e.g. `GLIBC_2.14`.
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolVersion<'data> {
+    pub name: &'data str,
+    /// Whether the version isn't the default of a defined symbol.
+    pub hidden: bool,
+}
+
+/// Versions of the dynamic symbols by symbol index, as given by `.gnu.version` and the versions
+/// it refers to in `.gnu.version_r` and `.gnu.version_d`.
+pub fn symbol_versions<'data, Elf: FileHeader>(
+    obj: &ElfFile<'data, Elf>,
+) -> Vec<Option<SymbolVersion<'data>>> {
+    let endian = obj.endian();
+    let versions = obj
+        .raw_header()
+        .sections(endian, obj.data())
+        .and_then(|sections| sections.versions(endian, obj.data()))
+        .ok()
+        .flatten();
+
+    let Some(versions) = versions else {
+        return Vec::new();
+    };
+
+    let mut by_index = Vec::new();
+    for sym in obj.dynamic_symbols() {
+        let idx = versions.version_index(endian, sym.index().0);
+        let version = versions
+            .version(idx)
+            .ok()
+            .flatten()
+            .and_then(|version| std::str::from_utf8(version.name()).ok())
+            .map(|name| SymbolVersion {
+                name,
+                hidden: idx.is_hidden(),
+            });
+
+        // The null symbol isn't iterated over, so indices start at one.
+        if by_index.len() <= sym.index().0 {
+            by_index.resize(sym.index().0 + 1, None);
+        }
+        by_index[sym.index().0] = version;
+    }
+
+    by_index
+}
+
+/// Name of a dynamic symbol together with its version, like `memcpy@GLIBC_2.14`. The default
+/// version of a defined symbol is written as `memcpy@@GLIBC_2.14`.
+fn versioned<'data>(
+    name: &'data str,
+    version: Option<&Option<SymbolVersion>>,
+    defined: bool,
+) -> &'data str {
+    match version {
+        Some(Some(version)) if defined && !version.hidden => {
+            Box::leak(format!("{name}@@{}", version.name).into_boxed_str())
+        }
+        Some(Some(version)) => Box::leak(format!("{name}@{}", version.name).into_boxed_str()),
+        _ => name,
+    }
+}
+
+/// Layout of a procedure linkage table: where the first entry starts and the size of an entry.
+fn plt_layout<Elf: FileHeader>(obj: &ElfFile<Elf>) -> Option<(&'static str, u64, u64)> {
+    match obj.architecture() {
+        // Indirect branch tracking moves the entries to a second table without a header.
+        Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386 => {
+            match obj.section_by_name(".plt.sec") {
+                Some(_) => Some((".plt.sec", 0, 16)),
+                None => Some((".plt", 16, 16)),
+            }
+        }
+        Architecture::Aarch64 => Some((".plt", 32, 16)),
+        Architecture::Arm => Some((".plt", 20, 12)),
+        _ => None,
+    }
+}
+
+/// Stubs in the procedure linkage table and the import they jump to.
+///
+/// Entries of `.plt` are in the same order as the `JUMP_SLOT` relocations of `.rela.plt`, stubs
+/// in `.plt.got` jump through a GOT slot that is relocated with `GLOB_DAT` (x86_64 only).
+pub fn plt_entries<'data, Elf: FileHeader>(obj: &ElfFile<'data, Elf>) -> Vec<(u64, &'data str)> {
+    let mut entries = Vec::new();
+
+    let (relocations, dyn_syms) = match (obj.dynamic_relocations(), obj.dynamic_symbol_table()) {
+        (Some(relocations), Some(dyn_syms)) => (relocations, dyn_syms),
+        _ => return entries,
+    };
+
+    let mut jump_slots = Vec::new();
+    let mut got_slots = Vec::new();
+    for (r_offset, reloc) in relocations {
+        let name = match reloc.target() {
+            RelocationTarget::Symbol(idx) => match dyn_syms.symbol_by_index(idx) {
+                Ok(sym) => sym.name().unwrap_or(""),
+                Err(..) => continue,
+            },
+            _ => continue,
+        };
+
+        match reloc.kind() {
+            RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) if obj.is_64() => jump_slots.push(name),
+            RelocationKind::Elf(elf::R_386_JMP_SLOT) if !obj.is_64() => jump_slots.push(name),
+            RelocationKind::Elf(elf::R_AARCH64_JUMP_SLOT | elf::R_ARM_JUMP_SLOT) => {
+                jump_slots.push(name)
+            }
+            RelocationKind::Elf(elf::R_X86_64_GLOB_DAT) => got_slots.push((r_offset, name)),
+            _ => {}
+        }
+    }
+
+    if let Some((section, header, stride)) = plt_layout(obj) {
+        if let Some(plt) = obj.section_by_name(section) {
+            for (idx, name) in jump_slots.into_iter().enumerate() {
+                let offset = header + stride * idx as u64;
+                if offset + stride <= plt.size() && !name.is_empty() {
+                    entries.push((plt.address() + offset, name));
+                }
+            }
+        }
+    }
+
+    let plt_got = match obj.section_by_name(".plt.got") {
+        Some(plt_got) if obj.architecture() == Architecture::X86_64 => plt_got,
+        _ => return entries,
+    };
+
+    let data = plt_got.data().unwrap_or_default();
+    let mut offset = 0;
+    while offset < data.len() {
+        // Entries are either `jmp [rip + slot]` or `endbr64; bnd jmp [rip + slot]`, padded.
+        let (jmp, stride) = match data[offset..] {
+            [0xf3, 0x0f, 0x1e, 0xfa, 0xf2, 0xff, 0x25, ..] => (offset + 5, 16),
+            [0xff, 0x25, ..] => (offset, 8),
+            _ => break,
+        };
+
+        let disp = match data.get(jmp + 2..jmp + 6) {
+            Some(disp) => i32::from_le_bytes(disp.try_into().unwrap()),
+            None => break,
+        };
+
+        let slot = (plt_got.address() + jmp as u64 + 6).wrapping_add_signed(disp as i64);
+        if let Some((_, name)) = got_slots.iter().find(|(r_offset, _)| *r_offset == slot) {
+            entries.push((plt_got.address() + offset as u64, *name));
+        }
+
+        offset += stride;
+    }
+
+    entries
+}
+
+/// Shared libraries the object depends on (`DT_NEEDED`).
+pub fn needed_libraries<'data, Elf: FileHeader>(obj: &ElfFile<'data, Elf>) -> Vec<&'data str> {
+    dynamic(obj).map(|dynamic| dynamic.needed).unwrap_or_default()
+}
+
+/// Entry of the program header table.
+#[derive(Debug, Clone)]
+pub struct ProgramHeader {
+    pub kind: String,
+    pub offset: u64,
+    pub vaddr: u64,
+    pub file_size: u64,
+    pub mem_size: u64,
+    pub align: u64,
+    /// Combination of `R`, `W` and `X`.
+    pub flags: String,
+}
+
+/// Name of a segment type as used by `readelf`.
+fn segment_kind(p_type: u32) -> String {
+    let name = match p_type {
+        elf::PT_NULL => "NULL",
+        elf::PT_LOAD => "LOAD",
+        elf::PT_DYNAMIC => "DYNAMIC",
+        elf::PT_INTERP => "INTERP",
+        elf::PT_NOTE => "NOTE",
+        elf::PT_SHLIB => "SHLIB",
+        elf::PT_PHDR => "PHDR",
+        elf::PT_TLS => "TLS",
+        elf::PT_GNU_EH_FRAME => "GNU_EH_FRAME",
+        elf::PT_GNU_STACK => "GNU_STACK",
+        elf::PT_GNU_RELRO => "GNU_RELRO",
+        elf::PT_GNU_PROPERTY => "GNU_PROPERTY",
+        _ => return format!("{p_type:#x}"),
+    };
+
+    name.to_string()
+}
+
+pub fn program_headers<Elf: FileHeader>(obj: &ElfFile<Elf>) -> Vec<ProgramHeader> {
+    let endian = obj.endian();
+    obj.raw_segments()
+        .iter()
+        .map(|segment| {
+            let p_flags = segment.p_flags(endian);
+            let flag = |bit, c| if p_flags & bit != 0 { c } else { ' ' };
+
+            ProgramHeader {
+                kind: segment_kind(segment.p_type(endian)),
+                offset: segment.p_offset(endian).into(),
+                vaddr: segment.p_vaddr(endian).into(),
+                file_size: segment.p_filesz(endian).into(),
+                mem_size: segment.p_memsz(endian).into(),
+                align: segment.p_align(endian).into(),
+                flags: [flag(elf::PF_R, 'R'), flag(elf::PF_W, 'W'), flag(elf::PF_X, 'X')]
+                    .iter()
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Thread-local variable, at an offset into the TLS block of the module that defines it.
+pub struct TlsVariable<'data> {
+    pub name: &'data str,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Thread-local variables defined by the object, sorted by their offset.
+pub fn tls_variables<'data>(obj: &object::File<'data>) -> Vec<TlsVariable<'data>> {
+    let mut variables: Vec<TlsVariable> = obj
+        .symbols()
+        .chain(obj.dynamic_symbols())
+        .filter(|sym| sym.kind() == SymbolKind::Tls && !sym.is_undefined())
+        .filter_map(|sym| {
+            Some(TlsVariable {
+                name: sym.name().ok().filter(|name| !name.is_empty())?,
+                offset: sym.address(),
+                size: sym.size(),
+            })
+        })
+        .collect();
+
+    // Exported variables are in both symbol tables.
+    variables.sort_unstable_by(|a, b| (a.offset, a.name).cmp(&(b.offset, b.name)));
+    variables.dedup_by(|a, b| a.offset == b.offset && a.name == b.name);
+    variables
+}
+
+/// Commonly inspected entries of the `.dynamic` section.
+#[derive(Debug, Default)]
+pub struct Dynamic<'data> {
+    /// Shared libraries the object depends on.
+    pub needed: Vec<&'data str>,
+    /// Name the object is linked against by others.
+    pub soname: Option<&'data str>,
+    /// Library search paths, searched before `LD_LIBRARY_PATH`.
+    pub rpath: Vec<&'data str>,
+    /// Library search paths, searched after `LD_LIBRARY_PATH`.
+    pub runpath: Vec<&'data str>,
+    pub init: Option<u64>,
+    pub fini: Option<u64>,
+    /// Address and size in bytes of the arrays of constructors and destructors.
+    pub preinit_array: Option<(u64, u64)>,
+    pub init_array: Option<(u64, u64)>,
+    pub fini_array: Option<(u64, u64)>,
+    /// `DT_FLAGS` and `DT_FLAGS_1`.
+    pub flags: u64,
+    pub flags_1: u64,
+}
+
+pub fn dynamic<'data, Elf: FileHeader>(obj: &ElfFile<'data, Elf>) -> Option<Dynamic<'data>> {
+    let endian = obj.endian();
+    let (entries, strings) = dynamic_entries(obj)?;
+    let mut dynamic = Dynamic::default();
+
+    let mut preinit_array = (None, None);
+    let mut init_array = (None, None);
+    let mut fini_array = (None, None);
+
+    for entry in entries {
+        let val: u64 = entry.d_val(endian).into();
+        let string = || {
+            entry.string(endian, strings).ok().and_then(|s| std::str::from_utf8(s).ok())
+        };
+
+        match entry.tag32(endian) {
+            Some(elf::DT_NULL) => break,
+            Some(elf::DT_NEEDED) => dynamic.needed.extend(string()),
+            Some(elf::DT_SONAME) => dynamic.soname = string(),
+            Some(elf::DT_RPATH) => dynamic.rpath.extend(string().iter().flat_map(|s| s.split(':'))),
+            Some(elf::DT_RUNPATH) => {
+                dynamic.runpath.extend(string().iter().flat_map(|s| s.split(':')))
+            }
+            Some(elf::DT_INIT) => dynamic.init = Some(val),
+            Some(elf::DT_FINI) => dynamic.fini = Some(val),
+            Some(elf::DT_PREINIT_ARRAY) => preinit_array.0 = Some(val),
+            Some(elf::DT_PREINIT_ARRAYSZ) => preinit_array.1 = Some(val),
+            Some(elf::DT_INIT_ARRAY) => init_array.0 = Some(val),
+            Some(elf::DT_INIT_ARRAYSZ) => init_array.1 = Some(val),
+            Some(elf::DT_FINI_ARRAY) => fini_array.0 = Some(val),
+            Some(elf::DT_FINI_ARRAYSZ) => fini_array.1 = Some(val),
+            Some(elf::DT_FLAGS) => dynamic.flags = val,
+            Some(elf::DT_FLAGS_1) => dynamic.flags_1 = val,
+            _ => {}
+        }
+    }
+
+    let array = |(addr, size): (Option<u64>, Option<u64>)| Some((addr?, size.unwrap_or(0)));
+    dynamic.preinit_array = array(preinit_array);
+    dynamic.init_array = array(init_array);
+    dynamic.fini_array = array(fini_array);
+
+    Some(dynamic)
+}
+
 #[repr(u64)]
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Debug)]
@@ -386,3 +862,108 @@ datastructure! {
         d_val: u32,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::elf::{FileHeader, ProgramHeader, Writer};
+    use object::Endianness;
+
+    /// x86_64 shared library with a `.dynamic` section and segments that don't map anything.
+    fn library() -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Writer::new(Endianness::Little, true, &mut data);
+        writer.reserve_file_header();
+        writer.reserve_program_headers(2);
+        let libc = writer.add_dynamic_string(b"libc.so.6");
+        let libm = writer.add_dynamic_string(b"libm.so.6");
+        let soname = writer.add_dynamic_string(b"libfoo.so.1");
+        let runpath = writer.add_dynamic_string(b"$ORIGIN:/opt/lib");
+        writer.reserve_null_section_index();
+        writer.reserve_dynstr_section_index();
+        writer.reserve_dynamic_section_index();
+        writer.reserve_shstrtab_section_index();
+        writer.reserve_dynstr();
+        writer.reserve_dynamic(8);
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&FileHeader {
+                os_abi: 0,
+                abi_version: 0,
+                e_type: elf::ET_DYN,
+                e_machine: elf::EM_X86_64,
+                e_entry: 0,
+                e_flags: 0,
+            })
+            .unwrap();
+        writer.write_program_header(&ProgramHeader {
+            p_type: elf::PT_LOAD,
+            p_flags: elf::PF_R | elf::PF_X,
+            p_offset: 0,
+            p_vaddr: 0x1000,
+            p_paddr: 0x1000,
+            p_filesz: 0x40,
+            p_memsz: 0x80,
+            p_align: 0x1000,
+        });
+        writer.write_program_header(&ProgramHeader {
+            p_type: 0x6fff_0000,
+            p_flags: elf::PF_R,
+            p_offset: 0,
+            p_vaddr: 0,
+            p_paddr: 0,
+            p_filesz: 0,
+            p_memsz: 0,
+            p_align: 8,
+        });
+        writer.write_dynstr();
+        writer.write_align_dynamic();
+        writer.write_dynamic_string(elf::DT_NEEDED, libc);
+        writer.write_dynamic_string(elf::DT_NEEDED, libm);
+        writer.write_dynamic_string(elf::DT_SONAME, soname);
+        writer.write_dynamic_string(elf::DT_RUNPATH, runpath);
+        writer.write_dynamic(elf::DT_INIT_ARRAY, 0x3000);
+        writer.write_dynamic(elf::DT_INIT_ARRAYSZ, 16);
+        writer.write_dynamic(elf::DT_FLAGS_1, elf::DF_1_NOW as u64);
+        writer.write_dynamic(elf::DT_NULL, 0);
+        writer.write_shstrtab();
+        writer.write_null_section_header();
+        writer.write_dynstr_section_header(0);
+        writer.write_dynamic_section_header(0);
+        writer.write_shstrtab_section_header();
+        data
+    }
+
+    #[test]
+    fn headers() {
+        let data = library();
+        let obj = ElfFile::<elf::FileHeader64<Endianness>>::parse(&*data).unwrap();
+        let headers = program_headers(&obj);
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].kind, "LOAD");
+        assert_eq!(headers[0].flags, "R X");
+        assert_eq!(headers[0].vaddr, 0x1000);
+        assert_eq!((headers[0].file_size, headers[0].mem_size), (0x40, 0x80));
+        // Unknown types are printed as is.
+        assert_eq!(headers[1].kind, "0x6fff0000");
+    }
+
+    #[test]
+    fn dynamic_section() {
+        let data = library();
+        let obj = ElfFile::<elf::FileHeader64<Endianness>>::parse(&*data).unwrap();
+        let dynamic = dynamic(&obj).unwrap();
+
+        assert_eq!(dynamic.needed, ["libc.so.6", "libm.so.6"]);
+        assert_eq!(dynamic.soname, Some("libfoo.so.1"));
+        assert_eq!(dynamic.runpath, ["$ORIGIN", "/opt/lib"]);
+        assert!(dynamic.rpath.is_empty());
+        assert_eq!(dynamic.init_array, Some((0x3000, 16)));
+        assert_eq!(dynamic.fini_array, None);
+        assert_eq!(dynamic.flags_1, elf::DF_1_NOW as u64);
+        assert_eq!(needed_libraries(&obj), dynamic.needed);
+    }
+}
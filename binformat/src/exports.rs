@@ -0,0 +1,62 @@
+//! Symbols an object makes available to other objects.
+
+use object::read::elf::{ElfFile, FileHeader};
+use object::{Object, ObjectSymbol};
+
+/// An exported symbol, the version is only known for ELF objects using symbol versioning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export<'data> {
+    pub name: &'data str,
+    pub version: Option<&'data str>,
+}
+
+fn elf_exports<'data, Elf: FileHeader>(obj: &ElfFile<'data, Elf>) -> Vec<Export<'data>> {
+    let endian = obj.endian();
+    let versions = obj
+        .raw_header()
+        .sections(endian, obj.data())
+        .and_then(|sections| sections.versions(endian, obj.data()))
+        .ok()
+        .flatten();
+
+    let mut exports = Vec::new();
+    for sym in obj.dynamic_symbols() {
+        if !sym.is_definition() || !sym.is_global() {
+            continue;
+        }
+
+        let name = match sym.name() {
+            Ok(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+
+        let version = versions.as_ref().and_then(|versions| {
+            let idx = versions.version_index(endian, sym.index().0);
+            let version = versions.version(idx).ok()??;
+            std::str::from_utf8(version.name()).ok()
+        });
+
+        exports.push(Export { name, version });
+    }
+
+    exports
+}
+
+/// Exported symbols of the object, sorted by name.
+pub fn exports<'data>(obj: &object::File<'data>) -> Vec<Export<'data>> {
+    let mut exports = match obj {
+        object::File::Elf32(elf) => elf_exports(elf),
+        object::File::Elf64(elf) => elf_exports(elf),
+        _ => obj
+            .exports()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|export| std::str::from_utf8(export.name()).ok())
+            .map(|name| Export { name, version: None })
+            .collect(),
+    };
+
+    exports.sort_unstable_by(|a, b| a.name.cmp(b.name));
+    exports.dedup();
+    exports
+}
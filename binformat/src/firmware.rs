@@ -0,0 +1,212 @@
+//! Firmware images stored as text, Intel HEX (`.hex`) and Motorola S-records (`.srec`).
+//!
+//! Both are lines of records with an address and a few bytes each, which are put back together
+//! into the regions of memory they're loaded into.
+
+pub struct Region {
+    pub addr: u64,
+    pub bytes: Vec<u8>,
+}
+
+pub struct Image {
+    /// Loaded memory sorted by address, with adjacent records merged.
+    pub regions: Vec<Region>,
+    /// Where execution starts, if the image says so.
+    pub entry: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    IntelHex,
+    SRecord,
+}
+
+fn format(text: &str) -> Option<Format> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    match line.as_bytes() {
+        [b':', ..] => Some(Format::IntelHex),
+        [b'S', b'0'..=b'9', ..] => Some(Format::SRecord),
+        _ => None,
+    }
+}
+
+fn hex_bytes(hex: &str, line: usize) -> Result<Vec<u8>, String> {
+    if !hex.is_ascii() {
+        return Err(format!("Record on line {line} isn't hexadecimal."));
+    }
+
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("Record on line {line} has an odd number of digits."));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16))
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Record on line {line} isn't hexadecimal."))
+}
+
+fn be_number(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, &byte| acc << 8 | byte as u64)
+}
+
+/// Add the bytes of a record to the image, extending the previous region if they follow it.
+fn push(regions: &mut Vec<Region>, addr: u64, bytes: &[u8]) {
+    if let Some(last) = regions.last_mut() {
+        if last.addr + last.bytes.len() as u64 == addr {
+            last.bytes.extend_from_slice(bytes);
+            return;
+        }
+    }
+
+    regions.push(Region {
+        addr,
+        bytes: bytes.to_vec(),
+    });
+}
+
+fn intel_hex(text: &str) -> Result<Image, String> {
+    let mut regions = Vec::new();
+    let mut entry = None;
+    let mut base = 0u64;
+
+    for (idx, line) in text.lines().enumerate().map(|(idx, line)| (idx + 1, line.trim())) {
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(record) = line.strip_prefix(':') else {
+            return Err(format!("Line {idx} isn't an Intel HEX record."));
+        };
+
+        let record = hex_bytes(record, idx)?;
+        if record.len() < 5 || record.len() != 5 + record[0] as usize {
+            return Err(format!("Record on line {idx} has the wrong length."));
+        }
+
+        if record.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+            return Err(format!("Record on line {idx} has an invalid checksum."));
+        }
+
+        let addr = be_number(&record[1..3]);
+        let data = &record[4..record.len() - 1];
+        match record[3] {
+            0x00 => push(&mut regions, base + addr, data),
+            0x01 => break,
+            // Extended segment address, in 16 byte paragraphs.
+            0x02 => base = be_number(data) << 4,
+            // Start segment address, CS:IP.
+            0x03 if data.len() == 4 => {
+                entry = Some((be_number(&data[..2]) << 4) + be_number(&data[2..]))
+            }
+            0x04 => base = be_number(data) << 16,
+            0x05 => entry = Some(be_number(data)),
+            kind => return Err(format!("Record on line {idx} has unknown type {kind:#x}.")),
+        }
+    }
+
+    Ok(Image { regions, entry })
+}
+
+fn s_record(text: &str) -> Result<Image, String> {
+    let mut regions = Vec::new();
+    let mut entry = None;
+
+    for (idx, line) in text.lines().enumerate().map(|(idx, line)| (idx + 1, line.trim())) {
+        if line.is_empty() {
+            continue;
+        }
+
+        let (Some(b'S'), Some(&kind)) = (line.as_bytes().first(), line.as_bytes().get(1)) else {
+            return Err(format!("Line {idx} isn't an S-record."));
+        };
+
+        let addr_len = match kind {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            kind => {
+                return Err(format!("Record on line {idx} has unknown type S{}.", kind as char))
+            }
+        };
+
+        let record = hex_bytes(&line[2..], idx)?;
+        if record.is_empty() || record.len() != 1 + record[0] as usize {
+            return Err(format!("Record on line {idx} has the wrong length."));
+        }
+
+        if record.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0xff {
+            return Err(format!("Record on line {idx} has an invalid checksum."));
+        }
+
+        let Some(data) = record.get(1 + addr_len..record.len() - 1) else {
+            return Err(format!("Record on line {idx} has the wrong length."));
+        };
+
+        let addr = be_number(&record[1..1 + addr_len]);
+        match kind {
+            b'1' | b'2' | b'3' => push(&mut regions, addr, data),
+            b'7' | b'8' | b'9' => entry = Some(addr),
+            // Header and record counts.
+            _ => {}
+        }
+    }
+
+    Ok(Image { regions, entry })
+}
+
+/// Parse `data` if it's an Intel HEX or S-record image, `None` if it's neither.
+pub fn parse(data: &[u8]) -> Option<Result<Image, String>> {
+    let text = std::str::from_utf8(data).ok()?;
+    let mut image = match format(text)? {
+        Format::IntelHex => intel_hex(text),
+        Format::SRecord => s_record(text),
+    };
+
+    if let Ok(ref mut image) = image {
+        image.regions.sort_by_key(|region| region.addr);
+    }
+
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intel_hex() {
+        let text = ":020000040800F2\n\
+                    :0400000001020304F2\n\
+                    :0400040005060708DE\n\
+                    :0400000508000101ED\n\
+                    :00000001FF\n";
+
+        let image = parse(text.as_bytes()).unwrap().unwrap();
+        assert_eq!(image.regions.len(), 1);
+        assert_eq!(image.regions[0].addr, 0x0800_0000);
+        assert_eq!(image.regions[0].bytes, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(image.entry, Some(0x0800_0101));
+    }
+
+    #[test]
+    fn s_record() {
+        let text = "S00600004844521B\n\
+                    S1070100AABBCCDDE9\n\
+                    S1050200EEFF0B\n\
+                    S9030100FB\n";
+
+        let image = parse(text.as_bytes()).unwrap().unwrap();
+        assert_eq!(image.regions.len(), 2);
+        assert_eq!(image.regions[0].addr, 0x100);
+        assert_eq!(image.regions[0].bytes, [0xaa, 0xbb, 0xcc, 0xdd]);
+        assert_eq!(image.regions[1].addr, 0x200);
+        assert_eq!(image.entry, Some(0x100));
+    }
+
+    #[test]
+    fn checksum() {
+        assert!(parse(b":0400000001020304F3\n").unwrap().is_err());
+        assert!(parse(b"\x7fELF").is_none());
+    }
+}
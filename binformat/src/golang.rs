@@ -0,0 +1,325 @@
+//! Function table the Go runtime uses for stack traces, `runtime.pclntab`.
+//!
+//! It's kept in every Go binary even after stripping, as the runtime can't unwind without it. It
+//! has the name of every function, and tables that map each of its instructions to a file and
+//! line. The layout changed with Go 1.16, 1.18 and 1.20. Binaries built before Go 1.16 only have
+//! their function names recovered.
+
+use crate::RawSymbol;
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind};
+use processor_shared::{AddressMap, Addressed};
+
+const MAGIC_12: u32 = 0xfffffffb;
+const MAGIC_116: u32 = 0xfffffffa;
+const MAGIC_118: u32 = 0xfffffff0;
+const MAGIC_120: u32 = 0xfffffff1;
+
+/// Row of the line table, the instructions starting at `addr` up to the next row were compiled
+/// from this line.
+pub struct GoLine<'data> {
+    pub addr: u64,
+    pub file: &'data str,
+    pub line: u32,
+}
+
+pub struct Pclntab<'data> {
+    /// Release of the Go toolchain that introduced the table's layout, e.g. `1.18`.
+    pub version: &'static str,
+    pub funcs: AddressMap<RawSymbol<'data>>,
+    /// Sorted by address.
+    pub lines: Vec<GoLine<'data>>,
+}
+
+struct Header<'data> {
+    data: &'data [u8],
+    magic: u32,
+    /// Instruction size quantum, pc deltas of the tables are a multiple of it.
+    quantum: u64,
+    ptr_size: usize,
+    little_endian: bool,
+}
+
+impl<'data> Header<'data> {
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match self.little_endian {
+            true => u32::from_le_bytes(bytes),
+            false => u32::from_be_bytes(bytes),
+        })
+    }
+
+    fn uintptr(&self, offset: usize) -> Option<u64> {
+        if self.ptr_size == 4 {
+            return self.u32(offset).map(u64::from);
+        }
+
+        let bytes: [u8; 8] = self.data.get(offset..offset + 8)?.try_into().ok()?;
+        Some(match self.little_endian {
+            true => u64::from_le_bytes(bytes),
+            false => u64::from_be_bytes(bytes),
+        })
+    }
+
+    /// Header field `idx`, following the magic and instruction quantum.
+    fn field(&self, idx: usize) -> Option<usize> {
+        self.uintptr(8 + idx * self.ptr_size).map(|value| value as usize)
+    }
+
+    fn str(&self, offset: usize) -> Option<&'data str> {
+        let bytes = self.data.get(offset..)?;
+        let len = bytes.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&bytes[..len]).ok()
+    }
+}
+
+/// Decodes a `pcvalue` table into the address each value starts at.
+fn pcvalue(data: &[u8], entry: u64, quantum: u64) -> Vec<(u64, i32)> {
+    let mut values = Vec::new();
+    let mut bytes = data.iter();
+    let mut varint = || -> Option<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let b = *bytes.next()?;
+            value |= ((b & 0x7f) as u32) << shift;
+            if b & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    };
+
+    let mut value = -1i32;
+    let mut pc = entry;
+    while let Some(uvdelta) = varint() {
+        // A zero delta ends the table, except for the first value.
+        if uvdelta == 0 && !values.is_empty() {
+            break;
+        }
+
+        let vdelta = match uvdelta & 1 {
+            0 => (uvdelta >> 1) as i32,
+            _ => !(uvdelta >> 1) as i32,
+        };
+        let Some(pcdelta) = varint() else {
+            break;
+        };
+
+        value = value.wrapping_add(vdelta);
+        values.push((pc, value));
+        pc += pcdelta as u64 * quantum;
+    }
+
+    values
+}
+
+/// Finds the table by its section, symbol or else by scanning read-only data for a valid
+/// header, as external linking or a PE object doesn't keep the section.
+fn locate<'data>(obj: &object::File<'data>) -> Option<&'data [u8]> {
+    for section in obj.sections() {
+        if let Ok(".gopclntab" | "__gopclntab") = section.name() {
+            return section.data().ok();
+        }
+    }
+
+    let symbol = obj.symbols().find(|sym| sym.name() == Ok("runtime.pclntab"));
+    if let Some(symbol) = symbol {
+        for section in obj.sections() {
+            let range = section.address()..section.address() + section.size();
+            if range.contains(&symbol.address()) {
+                let data = section.data().ok()?;
+                return data.get((symbol.address() - section.address()) as usize..);
+            }
+        }
+    }
+
+    let little_endian = obj.is_little_endian();
+    for section in obj.sections().filter(|section| section.kind() == SectionKind::ReadOnlyData) {
+        let Ok(data) = section.data() else {
+            continue;
+        };
+
+        for offset in (0..data.len().saturating_sub(8)).step_by(4) {
+            let magic: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+            let magic = match little_endian {
+                true => u32::from_le_bytes(magic),
+                false => u32::from_be_bytes(magic),
+            };
+
+            let valid = matches!(magic, MAGIC_116 | MAGIC_118 | MAGIC_120)
+                && data[offset + 4..offset + 6] == [0, 0]
+                && matches!(data[offset + 6], 1 | 2 | 4)
+                && matches!(data[offset + 7], 4 | 8);
+
+            if valid {
+                return Some(&data[offset..]);
+            }
+        }
+    }
+
+    None
+}
+
+/// Function names of tables from before Go 1.16, with the function table following the header.
+fn parse_12<'data>(header: &Header<'data>) -> Option<Pclntab<'data>> {
+    let nfunc = header.field(0)?;
+    let functab = 8 + header.ptr_size;
+
+    let mut funcs = AddressMap::default();
+    for idx in 0..nfunc {
+        let entry = header.uintptr(functab + idx * 2 * header.ptr_size)?;
+        let func = header.uintptr(functab + (idx * 2 + 1) * header.ptr_size)? as usize;
+        let name = header.u32(func + header.ptr_size)? as usize;
+        if let Some(name) = header.str(name) {
+            funcs.push(Addressed {
+                addr: entry as usize,
+                item: RawSymbol { name, module: None },
+            });
+        }
+    }
+
+    Some(Pclntab {
+        version: "1.2",
+        funcs,
+        lines: Vec::new(),
+    })
+}
+
+fn parse_116<'data>(header: &Header<'data>) -> Option<Pclntab<'data>> {
+    // Go 1.18 added the start of the text, which function entries are relative to.
+    let (text_start, fields) = match header.magic {
+        MAGIC_116 => (0, 2),
+        _ => (header.field(2)? as u64, 3),
+    };
+
+    let nfunc = header.field(0)?;
+    let funcnametab = header.field(fields)?;
+    let cutab = header.field(fields + 1)?;
+    let filetab = header.field(fields + 2)?;
+    let pctab = header.field(fields + 3)?;
+    let functab = header.field(fields + 4)?;
+
+    let mut funcs = AddressMap::default();
+    let mut lines = Vec::new();
+
+    for idx in 0..nfunc {
+        // Go 1.18 halved the function table to 32-bit offsets.
+        let (entry, func) = match header.magic {
+            MAGIC_116 => {
+                let entry = header.uintptr(functab + idx * 2 * header.ptr_size)?;
+                let func = header.uintptr(functab + (idx * 2 + 1) * header.ptr_size)?;
+                (entry, func as usize)
+            }
+            _ => {
+                let entry = header.u32(functab + idx * 8)? as u64;
+                let func = header.u32(functab + idx * 8 + 4)? as usize;
+                (text_start + entry, func)
+            }
+        };
+
+        // _func { entry, nameoff, args, deferreturn, pcsp, pcfile, pcln, npcdata, cu_offset }
+        let func = functab + func;
+        let fields = match header.magic {
+            MAGIC_116 => func + header.ptr_size,
+            _ => func + 4,
+        };
+
+        let name = header.u32(fields)? as usize;
+        let Some(name) = header.str(funcnametab + name) else {
+            continue;
+        };
+
+        funcs.push(Addressed {
+            addr: entry as usize,
+            item: RawSymbol { name, module: None },
+        });
+
+        let (Some(pcfile), Some(pcln), Some(cu)) = (
+            header.u32(fields + 16),
+            header.u32(fields + 20),
+            header.u32(fields + 28),
+        ) else {
+            continue;
+        };
+
+        let table = |offset: u32| {
+            let data = header.data.get(pctab + offset as usize..).unwrap_or(&[]);
+            pcvalue(data, entry, header.quantum)
+        };
+
+        let files = table(pcfile);
+        for (addr, line) in table(pcln) {
+            let file_idx = match files.partition_point(|&(file_addr, _)| file_addr <= addr) {
+                0 => continue,
+                idx => files[idx - 1].1,
+            };
+
+            let file = header
+                .u32(cutab + (cu as usize + file_idx as usize) * 4)
+                .and_then(|offset| header.str(filetab + offset as usize));
+
+            if let (Some(file), Ok(line)) = (file, u32::try_from(line)) {
+                lines.push(GoLine { addr, file, line });
+            }
+        }
+    }
+
+    lines.sort_by_key(|line| line.addr);
+
+    Some(Pclntab {
+        version: match header.magic {
+            MAGIC_116 => "1.16",
+            MAGIC_118 => "1.18",
+            _ => "1.20",
+        },
+        funcs,
+        lines,
+    })
+}
+
+/// Function names and line table of a Go binary, `None` if it wasn't built by Go.
+pub fn pclntab<'data>(obj: &object::File<'data>) -> Option<Pclntab<'data>> {
+    let data = locate(obj)?;
+    let little_endian = obj.is_little_endian();
+
+    let magic: [u8; 4] = data.get(..4)?.try_into().ok()?;
+    let header = Header {
+        data,
+        magic: match little_endian {
+            true => u32::from_le_bytes(magic),
+            false => u32::from_be_bytes(magic),
+        },
+        quantum: *data.get(6)? as u64,
+        ptr_size: *data.get(7)? as usize,
+        little_endian,
+    };
+
+    if !matches!(header.ptr_size, 4 | 8) {
+        return None;
+    }
+
+    match header.magic {
+        MAGIC_12 => parse_12(&header),
+        MAGIC_116 | MAGIC_118 | MAGIC_120 => parse_116(&header),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcvalue_table() {
+        // Line 10 for 4 bytes, line 12 for 8 bytes and line 11 for 2 bytes.
+        let data = [0x16, 0x04, 0x04, 0x08, 0x01, 0x02, 0x00];
+        let values = pcvalue(&data, 0x1000, 1);
+        assert_eq!(values, vec![(0x1000, 10), (0x1004, 12), (0x100c, 11)]);
+    }
+
+    #[test]
+    fn pcvalue_quantum() {
+        let data = [0x02, 0x01, 0x02, 0x02, 0x00];
+        let values = pcvalue(&data, 0x1000, 4);
+        assert_eq!(values, vec![(0x1000, 0), (0x1004, 1)]);
+    }
+}
@@ -0,0 +1,299 @@
+//! GPU code embedded in host objects, either as CUDA fat binaries or as SPIR-V modules.
+//!
+//! A fat binary holds the same kernels compiled for several GPU architectures, each entry being
+//! either PTX source or a cubin (an ELF object). SPIR-V modules are usually embedded as is.
+
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+
+const FATBIN_MAGIC: u32 = 0xBA55ED50;
+const SPIRV_MAGIC: u32 = 0x07230203;
+
+/// Size of the header preceding the entries of a fat binary.
+const FATBIN_HEADER_SIZE: usize = 16;
+
+/// Size of the header preceding the payload of a fat binary entry.
+const ENTRY_HEADER_SIZE: usize = 64;
+
+const ENTRY_KIND_PTX: u16 = 1;
+const ENTRY_KIND_CUBIN: u16 = 2;
+const ENTRY_FLAG_COMPRESSED: u64 = 0x2000;
+
+const SPIRV_OP_ENTRY_POINT: u32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ptx,
+    Cubin,
+    SpirV,
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Format::Ptx => "ptx",
+            Format::Cubin => "cubin",
+            Format::SpirV => "spir-v",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Blob<'data> {
+    pub format: Format,
+    /// Section of the host object the blob is embedded in.
+    pub section: String,
+    /// Offset of the blob in the host object.
+    pub offset: u64,
+    pub data: &'data [u8],
+    /// Target architecture, e.g. `sm_80`, or the SPIR-V version.
+    pub target: String,
+    /// Compressed blobs can be extracted, but their kernels can't be listed.
+    pub compressed: bool,
+    /// Mangled names of the kernels in the blob.
+    pub kernels: Vec<String>,
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+/// Kernels declared with `.entry` in PTX source.
+fn ptx_kernels(data: &[u8]) -> Vec<String> {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    let source = String::from_utf8_lossy(&data[..len]);
+
+    source
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(".entry")?;
+            let name = rest.trim_start().split(|c: char| c == '(' || c.is_whitespace()).next()?;
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Kernels of a cubin, each is placed in a `.text.<kernel>` section.
+fn cubin_kernels(data: &[u8]) -> Vec<String> {
+    let obj = match object::File::parse(data) {
+        Ok(obj) => obj,
+        Err(..) => return Vec::new(),
+    };
+
+    obj.symbols()
+        .filter(|sym| sym.kind() == SymbolKind::Text && sym.is_global())
+        .filter_map(|sym| {
+            let name = sym.name().ok()?;
+            let section = obj.section_by_index(sym.section_index()?).ok()?;
+            (section.name().ok()? == format!(".text.{name}")).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Entries of the fat binary at the start of `data`, and the size of the fat binary.
+fn fatbin_entries<'data>(
+    data: &'data [u8],
+    section: &str,
+    base: u64,
+) -> (Vec<Blob<'data>>, usize) {
+    let mut blobs = Vec::new();
+
+    let (header_size, fat_size) = match (u16_at(data, 6), u64_at(data, 8)) {
+        (Some(header_size), Some(fat_size)) => (header_size as usize, fat_size as usize),
+        _ => return (blobs, FATBIN_HEADER_SIZE),
+    };
+
+    let end = header_size.saturating_add(fat_size).min(data.len());
+    let mut offset = header_size.max(FATBIN_HEADER_SIZE);
+
+    while offset + ENTRY_HEADER_SIZE <= end {
+        let entry = &data[offset..end];
+        let (Some(kind), Some(entry_header_size), Some(size)) =
+            (u16_at(entry, 0), u32_at(entry, 4), u64_at(entry, 8))
+        else {
+            break;
+        };
+
+        let start = entry_header_size as usize;
+        let payload = match entry.get(start..start.saturating_add(size as usize)) {
+            Some(payload) if start >= ENTRY_HEADER_SIZE => payload,
+            _ => break,
+        };
+
+        let arch = u32_at(entry, 28).unwrap_or(0);
+        let compressed = u64_at(entry, 40).unwrap_or(0) & ENTRY_FLAG_COMPRESSED != 0;
+
+        let format = match kind {
+            ENTRY_KIND_PTX => Some(Format::Ptx),
+            ENTRY_KIND_CUBIN => Some(Format::Cubin),
+            _ => None,
+        };
+
+        if let Some(format) = format {
+            let kernels = match format {
+                _ if compressed => Vec::new(),
+                Format::Ptx => ptx_kernels(payload),
+                _ => cubin_kernels(payload),
+            };
+
+            blobs.push(Blob {
+                format,
+                section: section.to_string(),
+                offset: base + (offset + start) as u64,
+                data: payload,
+                target: format!("sm_{arch}"),
+                compressed,
+                kernels,
+            });
+        }
+
+        offset += start + payload.len();
+    }
+
+    (blobs, end)
+}
+
+/// The SPIR-V module at the start of `data`, if the header is valid.
+fn spirv_module<'data>(data: &'data [u8], section: &str, base: u64) -> Option<Blob<'data>> {
+    let version = u32_at(data, 4)?;
+    let (major, minor) = ((version >> 16) & 0xff, (version >> 8) & 0xff);
+    if version & 0xff0000ff != 0 || major != 1 || u32_at(data, 16)? != 0 {
+        return None;
+    }
+
+    let mut kernels = Vec::new();
+    let mut offset = 20;
+    while let Some(word) = u32_at(data, offset) {
+        let (count, opcode) = ((word >> 16) as usize, word & 0xffff);
+        let operands = match data.get(offset + 4..offset + count * 4) {
+            Some(operands) if count > 0 => operands,
+            _ => break,
+        };
+
+        // Execution model, function id and a nul-terminated name.
+        if opcode == SPIRV_OP_ENTRY_POINT && operands.len() > 8 {
+            let name = &operands[8..];
+            let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            kernels.push(String::from_utf8_lossy(&name[..len]).into_owned());
+        }
+
+        offset += count * 4;
+    }
+
+    // Anything that isn't followed by a single instruction is unlikely to be a module.
+    if offset == 20 {
+        return None;
+    }
+
+    Some(Blob {
+        format: Format::SpirV,
+        section: section.to_string(),
+        offset: base,
+        data: &data[..offset],
+        target: format!("{major}.{minor}"),
+        compressed: false,
+        kernels,
+    })
+}
+
+/// All GPU code embedded in the sections of `obj`.
+pub fn blobs<'data>(obj: &object::File<'data>) -> Vec<Blob<'data>> {
+    let mut blobs = Vec::new();
+
+    for section in obj.sections() {
+        let (data, (base, _)) = match (section.data(), section.file_range()) {
+            (Ok(data), Some(range)) => (data, range),
+            _ => continue,
+        };
+
+        let name = section.name().unwrap_or("unknown");
+        let mut offset = 0;
+
+        while offset + 4 <= data.len() {
+            let start = base + offset as u64;
+            match u32_at(data, offset) {
+                Some(FATBIN_MAGIC) => {
+                    let (entries, size) = fatbin_entries(&data[offset..], name, start);
+                    blobs.extend(entries);
+                    offset += size.max(4);
+                    offset = (offset + 3) & !3;
+                }
+                Some(SPIRV_MAGIC) => match spirv_module(&data[offset..], name, start) {
+                    Some(module) => {
+                        offset += module.data.len();
+                        blobs.push(module);
+                    }
+                    None => offset += 4,
+                },
+                _ => offset += 4,
+            }
+        }
+    }
+
+    blobs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn ptx() {
+        let source = b".version 7.0\n.visible .entry _Z3addPi(\n.param .u64 a\n)\n{ ret; }\n\0";
+        assert_eq!(ptx_kernels(source), ["_Z3addPi"]);
+    }
+
+    #[test]
+    fn spirv() {
+        let mut data = words(&[SPIRV_MAGIC, 0x00010500, 0, 8, 0]);
+        // OpCapability Kernel
+        data.extend(words(&[2 << 16 | 17, 6]));
+        // OpEntryPoint Kernel %1 "add"
+        data.extend(words(&[4 << 16 | SPIRV_OP_ENTRY_POINT, 6, 1]));
+        data.extend(b"add\0");
+        let len = data.len();
+        // Trailing data that doesn't belong to the module.
+        data.extend(words(&[0, 0]));
+
+        let module = spirv_module(&data, ".rodata", 0x100).unwrap();
+        assert_eq!(module.kernels, ["add"]);
+        assert_eq!(module.target, "1.5");
+        assert_eq!(module.data.len(), len);
+    }
+
+    #[test]
+    fn fatbin() {
+        let ptx = b".visible .entry kernel()\n\0\0\0\0\0\0\0";
+        let mut entry = vec![0; ENTRY_HEADER_SIZE];
+        entry[0..2].copy_from_slice(&ENTRY_KIND_PTX.to_le_bytes());
+        entry[4..8].copy_from_slice(&(ENTRY_HEADER_SIZE as u32).to_le_bytes());
+        entry[8..16].copy_from_slice(&(ptx.len() as u64).to_le_bytes());
+        entry[28..32].copy_from_slice(&80u32.to_le_bytes());
+        entry.extend(ptx);
+
+        let mut data = FATBIN_MAGIC.to_le_bytes().to_vec();
+        data.extend(1u16.to_le_bytes());
+        data.extend((FATBIN_HEADER_SIZE as u16).to_le_bytes());
+        data.extend((entry.len() as u64).to_le_bytes());
+        data.extend(entry);
+
+        let (blobs, size) = fatbin_entries(&data, ".nv_fatbin", 0);
+        assert_eq!(size, data.len());
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].format, Format::Ptx);
+        assert_eq!(blobs[0].target, "sm_80");
+        assert_eq!(blobs[0].offset, (FATBIN_HEADER_SIZE + ENTRY_HEADER_SIZE) as u64);
+        assert_eq!(blobs[0].kernels, ["kernel"]);
+    }
+}
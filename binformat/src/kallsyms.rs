@@ -0,0 +1,201 @@
+//! Symbol maps produced by the Linux kernel, either `/proc/kallsyms` or a `System.map`.
+//!
+//! Each line has the form `<address> <type> <name> [module]`, e.g.
+//! `ffffffff81000000 T _stext` or `ffffffffc0a01000 t ext4_fill_super [ext4]`.
+//!
+//! With KASLR the kernel is loaded at a random offset from where it was linked, which
+//! `/proc/kallsyms` shows. The offset is taken from `_text` or `_stext` and undone for every
+//! symbol, except for those of modules as they're loaded independently of the kernel.
+
+use crate::RawSymbol;
+use processor_shared::{AddressMap, Addressed};
+
+/// Parse a symbol map, with the kernel's symbols moved to where `image` has them linked.
+///
+/// `image` looks up the address of a symbol in the kernel image, if it's known.
+pub fn parse<'data>(
+    map: &'data str,
+    image: impl Fn(&str) -> Option<usize>,
+) -> AddressMap<RawSymbol<'data>> {
+    let mut syms = AddressMap::default();
+    let mut hidden = 0;
+
+    for (idx, line) in map.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        let (addr, kind, name) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(addr), Some(kind), Some(name)) => (addr, kind, name),
+            (None, ..) => continue,
+            _ => {
+                log::complex!(
+                    w "[kallsyms::parse] malformed line ",
+                    g (idx + 1).to_string(),
+                    w ".",
+                );
+                continue;
+            }
+        };
+
+        // symbols of loaded modules are followed by the module's name in brackets
+        let module = match parts.next() {
+            Some(module) => match module.strip_prefix('[').and_then(|m| m.strip_suffix(']')) {
+                Some(module) => Some(module),
+                None => {
+                    log::complex!(
+                        w "[kallsyms::parse] malformed module on line ",
+                        g (idx + 1).to_string(),
+                        w ".",
+                    );
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        let addr = match usize::from_str_radix(addr, 16) {
+            Ok(addr) => addr,
+            Err(_) => {
+                log::complex!(
+                    w "[kallsyms::parse] invalid address on line ",
+                    g (idx + 1).to_string(),
+                    w ".",
+                );
+                continue;
+            }
+        };
+
+        // undefined symbols don't have an address
+        if kind.eq_ignore_ascii_case("u") {
+            continue;
+        }
+
+        // `/proc/kallsyms` zeroes out all addresses when read without the right privileges
+        if addr == 0 {
+            hidden += 1;
+            continue;
+        }
+
+        syms.push(Addressed {
+            addr,
+            item: RawSymbol { name, module },
+        });
+    }
+
+    if let Some(slide) = slide(&syms, image) {
+        log::complex!(
+            w "[kallsyms::parse] kernel is loaded ",
+            g format!("{slide:#x}"),
+            w " bytes from where it was linked.",
+        );
+
+        for sym in syms.iter_mut().filter(|sym| sym.item.module.is_none()) {
+            sym.addr = sym.addr.wrapping_sub(slide);
+        }
+    }
+
+    if hidden > 0 {
+        log::complex!(
+            w "[kallsyms::parse] ",
+            y format!("skipped {hidden} symbols without an address"),
+            y ", was the map read as root?",
+        );
+    }
+
+    syms
+}
+
+/// Offset of the kernel in the map from where `image` has it linked, if it's moved.
+fn slide(syms: &[Addressed<RawSymbol>], image: impl Fn(&str) -> Option<usize>) -> Option<usize> {
+    ["_text", "_stext"].into_iter().find_map(|anchor| {
+        let loaded = syms
+            .iter()
+            .find(|sym| sym.item.module.is_none() && sym.item.name == anchor)?;
+        let slide = loaded.addr.wrapping_sub(image(anchor)?);
+        (slide != 0).then_some(slide)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYSTEM_MAP: &str = "\
+ffffffff81000000 T _text
+ffffffff81000000 T _stext
+ffffffff81001000 t do_one_initcall
+                 U __this_is_undefined
+ffffffff82000000 D jiffies
+";
+
+    fn names<'a>(syms: &AddressMap<RawSymbol<'a>>) -> Vec<(usize, &'a str, Option<&'a str>)> {
+        syms.iter().map(|sym| (sym.addr, sym.item.name, sym.item.module)).collect()
+    }
+
+    #[test]
+    fn system_map() {
+        let syms = parse(SYSTEM_MAP, |_| None);
+        assert_eq!(
+            names(&syms),
+            [
+                (0xffffffff81000000, "_text", None),
+                (0xffffffff81000000, "_stext", None),
+                (0xffffffff81001000, "do_one_initcall", None),
+                (0xffffffff82000000, "jiffies", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unprivileged() {
+        let map = "0000000000000000 T _text\n0000000000000000 T do_one_initcall\n";
+        assert!(parse(map, |_| Some(0xffffffff81000000)).is_empty());
+    }
+
+    #[test]
+    fn modules() {
+        let map = "\
+ffffffff81000000 T _text
+ffffffffc0a01000 t ext4_fill_super\t[ext4]
+ffffffffc0a02000 t ext4_iget [ext4
+";
+        let syms = parse(map, |_| None);
+        assert_eq!(
+            names(&syms),
+            [
+                (0xffffffff81000000, "_text", None),
+                (0xffffffffc0a01000, "ext4_fill_super", Some("ext4")),
+            ]
+        );
+    }
+
+    #[test]
+    fn kaslr() {
+        let map = "\
+ffffffff9a200000 T _text
+ffffffff9a201000 t do_one_initcall
+ffffffffc0a01000 t ext4_fill_super [ext4]
+";
+        let syms = parse(map, |name| (name == "_text").then_some(0xffffffff81000000));
+        assert_eq!(
+            names(&syms),
+            [
+                (0xffffffff81000000, "_text", None),
+                (0xffffffff81001000, "do_one_initcall", None),
+                (0xffffffffc0a01000, "ext4_fill_super", Some("ext4")),
+            ]
+        );
+    }
+
+    #[test]
+    fn kaslr_stext() {
+        // arm64 images that are stripped only tell where `.text` starts, which is `_stext`.
+        let map = "ffff9c8a08010000 T _stext\nffff9c8a08011000 t do_one_initcall\n";
+        let syms = parse(map, |name| (name == "_stext").then_some(0xffff800008010000));
+        assert_eq!(
+            names(&syms),
+            [
+                (0xffff800008010000, "_stext", None),
+                (0xffff800008011000, "do_one_initcall", None),
+            ]
+        );
+    }
+}
@@ -1,15 +1,138 @@
-use object::{Object, ObjectSection, ObjectSymbol};
+use object::{Object, ObjectKind, ObjectSection, ObjectSymbol, SectionFlags};
 use processor_shared::{AddressMap, Addressed};
 
+pub mod archive;
+pub mod codesign;
+pub mod cortexm;
+pub mod edit;
 pub mod elf;
+pub mod exports;
+pub mod firmware;
+pub mod golang;
+pub mod gpu;
+pub mod kallsyms;
 pub mod macho;
+pub mod objc;
 pub mod pe;
+pub mod relocs;
+pub mod security;
+pub mod strip;
+pub mod switch;
+pub mod symbol_map;
+pub mod uefi;
+pub mod unwind;
 
 pub struct RawSymbol<'data> {
     pub name: &'data str,
     pub module: Option<&'data str>,
 }
 
+/// Shared libraries the object links against, in the order they are loaded.
+pub fn libraries<'data>(obj: &object::File<'data>) -> Vec<&'data str> {
+    match obj {
+        object::File::Elf32(elf) => elf::needed_libraries(elf),
+        object::File::Elf64(elf) => elf::needed_libraries(elf),
+        _ => {
+            let mut libs = Vec::new();
+            for import in obj.imports().unwrap_or_default() {
+                if let Ok(lib) = std::str::from_utf8(import.library()) {
+                    if !lib.is_empty() && !libs.contains(&lib) {
+                        libs.push(lib);
+                    }
+                }
+            }
+            libs
+        }
+    }
+}
+
+/// Path that reads the object from stdin.
+pub const STDIN: &str = "-";
+
+/// Maps the file at `path` into memory, rather than reading all of it.
+///
+/// Only the pages of the headers and sections that are accessed are ever loaded, which keeps
+/// large debug builds from being copied into memory as a whole. The object parsed from the map
+/// borrows from it, just like it would from a buffer.
+///
+/// A `path` of [`STDIN`] reads the object from stdin instead, which can't be mapped. It's read
+/// once and copied into an anonymous map for every call.
+pub fn map<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<memmap2::Mmap> {
+    if path.as_ref() == std::path::Path::new(STDIN) {
+        return map_stdin();
+    }
+
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the file is assumed not to be modified while it's mapped, as with any object.
+    unsafe { memmap2::Mmap::map(&file) }
+}
+
+fn map_stdin() -> std::io::Result<memmap2::Mmap> {
+    static INPUT: std::sync::OnceLock<Vec<u8>> = std::sync::OnceLock::new();
+
+    let input = match INPUT.get() {
+        Some(input) => input,
+        None => {
+            let mut input = Vec::new();
+            std::io::Read::read_to_end(&mut std::io::stdin().lock(), &mut input)?;
+            INPUT.get_or_init(|| input)
+        }
+    };
+
+    if input.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "Nothing was written to stdin",
+        ));
+    }
+
+    let mut map = memmap2::MmapMut::map_anon(input.len())?;
+    map.copy_from_slice(input);
+    map.make_read_only()
+}
+
+/// Where the sections of a relocatable object are placed. The first page is left empty, as
+/// nothing is expected to be at address zero.
+const RELOCATABLE_BASE: u64 = 0x1000;
+
+/// Addresses of the sections of a relocatable object, by section index.
+///
+/// Every section of a relocatable ELF object starts at zero until it's linked, so the sections
+/// that are loaded are placed one after another instead, like a linker would. This way their
+/// code and symbols don't overlap. Empty for objects that aren't relocatable.
+pub fn relocatable_layout<'data: 'file, 'file, Obj: Object<'data, 'file>>(
+    obj: &'file Obj,
+) -> Vec<u64> {
+    let mut addrs = Vec::new();
+    if obj.kind() != ObjectKind::Relocatable {
+        return addrs;
+    }
+
+    let mut cursor = RELOCATABLE_BASE;
+    for section in obj.sections() {
+        let idx = section.index().0;
+        if addrs.len() <= idx {
+            addrs.resize(idx + 1, 0);
+        }
+
+        let alloc = match section.flags() {
+            SectionFlags::Elf { sh_flags } => sh_flags & object::elf::SHF_ALLOC as u64 != 0,
+            _ => false,
+        };
+
+        addrs[idx] = if alloc {
+            let align = section.align().max(1);
+            cursor = cursor.div_ceil(align) * align;
+            cursor += section.size();
+            cursor - section.size()
+        } else {
+            section.address()
+        };
+    }
+
+    addrs
+}
+
 fn parse_symbol_table<'data, Obj: Object<'data, 'data>>(
     obj: &'data Obj,
 ) -> AddressMap<RawSymbol<'data>> {
@@ -49,7 +172,7 @@ fn parse_section_generics<'data, Obj: ObjectSection<'data>>(
 
     let bytes: &'static [u8] = match section.data() {
         // The file is memory mapped so only the bytes are of lifetime &'static [u8].
-        Ok(data) => unsafe { std::mem::transmute(data) },
+        Ok(data) => unsafe { std::mem::transmute::<&[u8], &'static [u8]>(data) },
         Err(..) => {
             log::complex!(
                 w "[macho::parse_sections] ",
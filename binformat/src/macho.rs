@@ -101,12 +101,59 @@ impl ChainedFixupPointerGeneric {
         match self {
             Self::Generic32 => ((ptr >> 31) != 0, (ptr >> 21) & 0x3FF),
             Self::Generic64 => ((ptr >> 63) != 0, (ptr >> 51) & 0xFFF),
-            Self::GenericArm64e => ((ptr >> 63) != 0, (ptr >> 52) & 0x7FF),
+            Self::GenericArm64e => ((ptr >> 62) & 1 != 0, (ptr >> 51) & 0x7FF),
             Self::Firmware32 => (false, (ptr >> 26) & 0x3F),
         }
     }
 }
 
+/// Key a pointer is signed with on arm64e.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacKey {
+    IA,
+    IB,
+    DA,
+    DB,
+}
+
+/// How a pointer in data is signed once it's fixed up on arm64e.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerAuth {
+    pub key: PacKey,
+    /// Constant the signature is diversified with.
+    pub diversity: u16,
+    /// Whether the signature is also diversified with the address of the pointer.
+    pub addr_diversity: bool,
+}
+
+/// Pointer in data that gets fixed up by dyld when the object is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainedPointer {
+    /// Address it points to, `None` if it's bound to an import.
+    pub target: Option<u64>,
+    pub auth: Option<PointerAuth>,
+}
+
+/// Signing of an arm64e auth rebase or bind.
+fn pointer_auth(ptr: u64) -> Option<PointerAuth> {
+    if ptr >> 63 == 0 {
+        return None;
+    }
+
+    let key = match (ptr >> 49) & 0b11 {
+        0 => PacKey::IA,
+        1 => PacKey::IB,
+        2 => PacKey::DA,
+        _ => PacKey::DB,
+    };
+
+    Some(PointerAuth {
+        key,
+        diversity: (ptr >> 32) as u16,
+        addr_diversity: (ptr >> 48) & 1 != 0,
+    })
+}
+
 pub struct MachoDebugInfo<'data, Mach: MachHeader> {
     /// Parsed Mach-O header.
     obj: &'data MachOFile<'data, Mach>,
@@ -118,8 +165,11 @@ pub struct MachoDebugInfo<'data, Mach: MachHeader> {
     pub syms: AddressMap<RawSymbol<'data>>,
     /// Parsed sections with extra metadata.
     pub sections: Vec<Section>,
+    /// Pointers fixed up through chained fixups, by their address.
+    pub pointers: AddressMap<ChainedPointer>,
     // ---- Required load commands ----
     chained_fixups: Option<&'data LinkeditDataCommand<Mach::Endian>>,
+    exports_trie: Option<&'data LinkeditDataCommand<Mach::Endian>>,
     symtab: Option<SymbolTable<'data, Mach>>,
     dysymtab: Option<&'data DysymtabCommand<Mach::Endian>>,
     dylid_info: Option<&'data DyldInfoCommand<Mach::Endian>>,
@@ -133,8 +183,10 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             base_addr: obj.segments().next().map(|seg| seg.address()).unwrap_or(0),
             syms: AddressMap::default(),
             sections: Vec::new(),
+            pointers: AddressMap::default(),
             dylibs: Vec::new(),
             chained_fixups: None,
+            exports_trie: None,
             symtab: None,
             dysymtab: None,
             dylid_info: None,
@@ -147,6 +199,7 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             parse_chained_fixups::<Mach>(
                 this.base_addr,
                 &mut this.syms,
+                &mut this.pointers,
                 &this.dylibs,
                 chained_fixups,
                 this.obj.data(),
@@ -154,6 +207,7 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             );
         }
         this.parse_dylid_info()?;
+        this.parse_exports();
         Ok(this)
     }
 
@@ -185,6 +239,9 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             if lcmd.cmd() == macho::LC_DYLD_CHAINED_FIXUPS {
                 self.chained_fixups = Some(lcmd.data()?);
             }
+            if lcmd.cmd() == macho::LC_DYLD_EXPORTS_TRIE {
+                self.exports_trie = Some(lcmd.data()?);
+            }
         }
 
         Ok(())
@@ -197,45 +254,72 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             None => return Ok(()),
         };
 
-        let rebase_off = dylib_info.rebase_off.get(endian) as u64;
-        let rebase_size = dylib_info.rebase_size.get(endian) as u64;
-        match self.obj.data().read_bytes_at(rebase_off, rebase_size) {
-            Ok(bytes) => parse_dynamic_table(bytes, &mut self.syms)?,
-            Err(()) => log::complex!(
-                w "[macho::parse_dylid_info] ",
-                y "Failed to read rebase when parsing import at offset ",
-                g format!("{rebase_off:#x}"),
-                y "."
-            ),
-        }
-
-        let bind_off = dylib_info.bind_off.get(endian) as u64;
-        let bind_size = dylib_info.bind_size.get(endian) as u64;
-        match self.obj.data().read_bytes_at(bind_off, bind_size) {
-            Ok(bytes) => parse_dynamic_table(bytes, &mut self.syms)?,
-            Err(()) => log::complex!(
-                w "[macho::parse_dylid_info] ",
-                y "Failed to read bind when parsing import at offset ",
-                g format!("{rebase_off:#x}"),
-                y "."
-            ),
-        }
+        // Rebases only slide pointers that already hold their target, so they don't name
+        // anything. Binds are where imports are written to.
+        let segments: Vec<u64> = self.obj.segments().map(|segment| segment.address()).collect();
+        let ptr_size = if self.obj.is_64() { 8 } else { 4 };
+        let tables = [
+            ("bind", dylib_info.bind_off, dylib_info.bind_size),
+            ("weak bind", dylib_info.weak_bind_off, dylib_info.weak_bind_size),
+            ("lazy bind", dylib_info.lazy_bind_off, dylib_info.lazy_bind_size),
+        ];
 
-        let lazy_bind_off = dylib_info.lazy_bind_off.get(endian) as u64;
-        let lazy_bind_size = dylib_info.lazy_bind_size.get(endian) as u64;
-        match self.obj.data().read_bytes_at(lazy_bind_off, lazy_bind_size) {
-            Ok(bytes) => parse_dynamic_table(bytes, &mut self.syms)?,
-            Err(()) => log::complex!(
-                w "[macho::parse_dylid_info] ",
-                y "Failed to read lazy bind when parsing import at offset ",
-                g format!("{rebase_off:#x}"),
-                y "."
-            ),
+        for (table, off, size) in tables {
+            let off = off.get(endian) as u64;
+            let size = size.get(endian) as u64;
+            match self.obj.data().read_bytes_at(off, size) {
+                Ok(bytes) => parse_bind_opcodes(
+                    bytes,
+                    &segments,
+                    ptr_size,
+                    &self.dylibs,
+                    &mut self.syms,
+                ),
+                Err(()) => log::complex!(
+                    w "[macho::parse_dylid_info] ",
+                    y format!("Failed to read {table} opcodes at offset "),
+                    g format!("{off:#x}"),
+                    y "."
+                ),
+            }
         }
 
         Ok(())
     }
 
+    /// Exported symbols, which are kept in the export trie even when the object is stripped.
+    fn parse_exports(&mut self) {
+        let endian = self.obj.endian();
+        let (off, size) = match (self.exports_trie, self.dylid_info) {
+            (Some(trie), _) => (trie.dataoff.get(endian), trie.datasize.get(endian)),
+            (None, Some(info)) => (info.export_off.get(endian), info.export_size.get(endian)),
+            (None, None) => return,
+        };
+
+        let trie = match self.obj.data().read_bytes_at(off as u64, size as u64) {
+            Ok(trie) => trie,
+            Err(()) => {
+                log::complex!(
+                    w "[macho::parse_exports] ",
+                    y "Failed to read export trie at offset ",
+                    g format!("{off:#x}"),
+                    y "."
+                );
+                return;
+            }
+        };
+
+        for (name, offset) in parse_export_trie(trie) {
+            self.syms.push(Addressed {
+                addr: (self.base_addr + offset) as usize,
+                item: RawSymbol {
+                    name: Box::leak(name.into_boxed_str()),
+                    module: None,
+                },
+            });
+        }
+    }
+
     fn parse_base_addr(&mut self) -> Result<(), object::Error> {
         // Macho addresses are relative to the __TEXT segment.
         for segment in self.obj.segments() {
@@ -401,16 +485,202 @@ fn parse_sections<'data, Mach: MachHeader>(obj: &'data MachOFile<'data, Mach>) -
 }
 
 #[allow(dead_code)]
-fn parse_dynamic_table<'data>(
-    _bytes: &'data [u8],
-    _symbols: &mut AddressMap<RawSymbol<'data>>,
-) -> Result<(), object::Error> {
-    log::complex!(
-        w "[macho::parse_dynamic_table] ",
-        y "Missing an implementation",
-        w ".",
-    );
-    Ok(())
+fn read_uleb(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        if shift < 64 {
+            value |= ((byte & 0x7f) as u64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+}
+
+fn read_sleb(bytes: &[u8], pos: &mut usize) -> Option<i64> {
+    let mut value = 0i64;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        if shift < 64 {
+            value |= ((byte & 0x7f) as i64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= -1 << shift;
+            }
+            return Some(value);
+        }
+    }
+}
+
+fn read_cstr<'data>(bytes: &'data [u8], pos: &mut usize) -> Option<&'data str> {
+    let len = bytes.get(*pos..)?.iter().position(|&b| b == 0)?;
+    let name = std::str::from_utf8(&bytes[*pos..*pos + len]).ok()?;
+    *pos += len + 1;
+    Some(name)
+}
+
+/// Name of a dylib without its path.
+fn dylib_name<'data>(dylibs: &[&'data str], ordinal: i64) -> Option<&'data str> {
+    let lib = dylibs.get(usize::try_from(ordinal).ok()?)?;
+    Some(lib.rsplit_once('/').map(|x| x.1).filter(|x| !x.is_empty()).unwrap_or(lib))
+}
+
+/// Run the bind opcodes of `LC_DYLD_INFO`, naming every pointer that's bound to an import.
+///
+/// Pointers are addressed by a segment index and offset, `segments` has the address of each.
+fn parse_bind_opcodes<'data>(
+    bytes: &'data [u8],
+    segments: &[u64],
+    ptr_size: u64,
+    dylibs: &[&'data str],
+    symbols: &mut AddressMap<RawSymbol<'data>>,
+) {
+    let mut pos = 0;
+    let mut ordinal = 0i64;
+    let mut name = None;
+    let mut segment = None;
+    let mut offset = 0u64;
+
+    let mut bind = |name: Option<&'data str>, segment: Option<u64>, offset: u64, ordinal| {
+        if let (Some(name), Some(segment)) = (name, segment) {
+            symbols.push(Addressed {
+                addr: segment.wrapping_add(offset) as usize,
+                item: RawSymbol {
+                    name,
+                    module: dylib_name(dylibs, ordinal),
+                },
+            });
+        }
+    };
+
+    while pos < bytes.len() {
+        let opcode = bytes[pos] & macho::BIND_OPCODE_MASK;
+        let imm = bytes[pos] & macho::BIND_IMMEDIATE_MASK;
+        pos += 1;
+
+        let ok = match opcode {
+            // Lazy binds are separated by `DONE`, so it doesn't end the table.
+            macho::BIND_OPCODE_DONE => Some(()),
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => {
+                ordinal = imm as i64;
+                Some(())
+            }
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                read_uleb(bytes, &mut pos).map(|uleb| ordinal = uleb as i64)
+            }
+            // Self, the main executable or a flat lookup, which aren't a library.
+            macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                ordinal = if imm == 0 { 0 } else { (imm | 0xf0) as i8 as i64 };
+                Some(())
+            }
+            macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                read_cstr(bytes, &mut pos).map(|sym| name = Some(sym))
+            }
+            macho::BIND_OPCODE_SET_TYPE_IMM => Some(()),
+            macho::BIND_OPCODE_SET_ADDEND_SLEB => read_sleb(bytes, &mut pos).map(|_| ()),
+            macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                segment = segments.get(imm as usize).copied();
+                read_uleb(bytes, &mut pos).map(|uleb| offset = uleb)
+            }
+            macho::BIND_OPCODE_ADD_ADDR_ULEB => {
+                read_uleb(bytes, &mut pos).map(|uleb| offset = offset.wrapping_add(uleb))
+            }
+            macho::BIND_OPCODE_DO_BIND => {
+                bind(name, segment, offset, ordinal);
+                offset = offset.wrapping_add(ptr_size);
+                Some(())
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => read_uleb(bytes, &mut pos).map(|uleb| {
+                bind(name, segment, offset, ordinal);
+                offset = offset.wrapping_add(uleb).wrapping_add(ptr_size);
+            }),
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => {
+                bind(name, segment, offset, ordinal);
+                offset = offset.wrapping_add(imm as u64 * ptr_size + ptr_size);
+                Some(())
+            }
+            macho::BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = read_uleb(bytes, &mut pos);
+                let skip = read_uleb(bytes, &mut pos);
+                count.zip(skip).map(|(count, skip)| {
+                    for _ in 0..count.min(bytes.len() as u64 * 64) {
+                        bind(name, segment, offset, ordinal);
+                        offset = offset.wrapping_add(skip).wrapping_add(ptr_size);
+                    }
+                })
+            }
+            _ => None,
+        };
+
+        if ok.is_none() {
+            log::complex!(
+                w "[macho::parse_bind_opcodes] ",
+                y "Unsupported or truncated opcode ",
+                g format!("{opcode:#x}"),
+                y "."
+            );
+            return;
+        }
+    }
+}
+
+/// Names and offsets from the start of the object of every symbol in an export trie.
+///
+/// Every node of the trie holds a piece of a name, and the terminal nodes an export. Re-exports
+/// from another library don't have an address of their own and are left out.
+fn parse_export_trie(trie: &[u8]) -> Vec<(String, u64)> {
+    let mut exports = Vec::new();
+    let mut visited = std::collections::HashSet::new();
+    let mut stack = vec![(0usize, String::new())];
+
+    while let Some((node, prefix)) = stack.pop() {
+        // A malformed trie could link back to a node it already went through.
+        if !visited.insert(node) {
+            continue;
+        }
+
+        let mut pos = node;
+        let Some(terminal_size) = read_uleb(trie, &mut pos) else {
+            continue;
+        };
+
+        let children = pos + terminal_size as usize;
+        if terminal_size != 0 {
+            let flags = read_uleb(trie, &mut pos).unwrap_or(0);
+            if flags & macho::EXPORT_SYMBOL_FLAGS_REEXPORT as u64 == 0 {
+                if let Some(offset) = read_uleb(trie, &mut pos) {
+                    exports.push((prefix.clone(), offset));
+                }
+            }
+        }
+
+        let mut pos = children;
+        let Some(&child_count) = trie.get(pos) else {
+            continue;
+        };
+        pos += 1;
+
+        for _ in 0..child_count {
+            let (Some(edge), Some(child)) =
+                (read_cstr(trie, &mut pos), read_uleb(trie, &mut pos))
+            else {
+                break;
+            };
+
+            stack.push((child as usize, format!("{prefix}{edge}")));
+        }
+    }
+
+    exports.sort_unstable_by_key(|(_, offset)| *offset);
+    exports
 }
 
 struct ImportEntry<'data> {
@@ -572,6 +842,7 @@ fn parse_page_starts_table_starts(page_starts: u64, page_count: u64, data: &[u8]
 fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
     base_addr: u64,
     syms: &mut AddressMap<RawSymbol<'data>>,
+    pointers: &mut AddressMap<ChainedPointer>,
     dylibs: &[&'data str],
     chained_fixups: &LinkeditDataCommand<Mach::Endian>,
     data: &'data [u8],
@@ -738,6 +1009,7 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
                             DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => ptr & 0xFFFFF,
                             DYLD_CHAINED_PTR_ARM64E
                             | DYLD_CHAINED_PTR_ARM64E_KERNEL
+                            | DYLD_CHAINED_PTR_ARM64E_USERLAND
                             | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
                                 if starts.pointer_format == DYLD_CHAINED_PTR_ARM64E_USERLAND24 {
                                     ptr & 0xFFFFFF
@@ -761,6 +1033,16 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
                             }
                         };
 
+                        if let ChainedFixupPointerGeneric::GenericArm64e = format {
+                            pointers.push(Addressed {
+                                addr: (base_addr + chain_entry_addr) as usize,
+                                item: ChainedPointer {
+                                    target: None,
+                                    auth: pointer_auth(ptr),
+                                },
+                            });
+                        }
+
                         if let Some(entry) = imports.get(ordinal as usize) {
                             let target_addr = base_addr + chain_entry_addr;
 
@@ -798,13 +1080,19 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
                             );
                         }
                     } else {
-                        let _entry_addr = match starts.pointer_format {
+                        let entry_addr = match starts.pointer_format {
                             DYLD_CHAINED_PTR_ARM64E
                             | DYLD_CHAINED_PTR_ARM64E_KERNEL
                             | DYLD_CHAINED_PTR_ARM64E_USERLAND
                             | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
-                                let auth = ptr & 1 != 0;
-                                let mut entry_addr = if auth { ptr & 0xFFFF } else { ptr & 0xFFFA };
+                                // Signed pointers only keep the low 32 bits of the target, the
+                                // signature itself is computed by dyld and never stored.
+                                let auth = ptr >> 63 != 0;
+                                let mut entry_addr = if auth {
+                                    ptr & 0xFFFFFFFF
+                                } else {
+                                    (ptr & 0x7FFFFFFFFFF) | ((ptr >> 43) & 0xFF) << 56
+                                };
                                 if starts.pointer_format != DYLD_CHAINED_PTR_ARM64E || auth {
                                     entry_addr += base_addr;
                                 }
@@ -832,9 +1120,16 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
                             }
                         };
 
-                        let _reloc_addr = base_addr + chain_entry_addr;
-                        // FIXME: we just don't handle relocations.
-                        // Relocation from {reloc_addr:#x} to {entry_addr:#x}.
+                        pointers.push(Addressed {
+                            addr: (base_addr + chain_entry_addr) as usize,
+                            item: ChainedPointer {
+                                target: Some(entry_addr),
+                                auth: match format {
+                                    ChainedFixupPointerGeneric::GenericArm64e => pointer_auth(ptr),
+                                    _ => None,
+                                },
+                            },
+                        });
                     }
 
                     chain_entry_addr += next_entry_stride_count * stride_size;
@@ -857,3 +1152,71 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arm64e_auth_rebase() {
+        // auth rebase of 0x3f10 with key DA, diversity 0x1234, address diversity and a stride
+        // of 2 to the next fixup.
+        let ptr = 1 << 63 | 2 << 51 | 2 << 49 | 1 << 48 | 0x1234 << 32 | 0x3f10;
+        let format = ChainedFixupPointerGeneric::GenericArm64e;
+
+        assert_eq!(format.bind_and_stride(ptr), (false, 2));
+        assert_eq!(
+            pointer_auth(ptr),
+            Some(PointerAuth {
+                key: PacKey::DA,
+                diversity: 0x1234,
+                addr_diversity: true,
+            })
+        );
+    }
+
+    #[test]
+    fn bind_opcodes() {
+        // Bind _malloc from the first dylib to segment 2, offset 0x10, then _free right after it.
+        let mut bytes = vec![0x11, 0x40];
+        bytes.extend_from_slice(b"_malloc\0");
+        bytes.extend_from_slice(&[0x72, 0x10, 0x90, 0x40]);
+        bytes.extend_from_slice(b"_free\0");
+        bytes.extend_from_slice(&[0x90, 0x00]);
+
+        let mut symbols = AddressMap::default();
+        let dylibs = ["", "/usr/lib/libSystem.B.dylib"];
+        parse_bind_opcodes(&bytes, &[0, 0x1000, 0x4000], 8, &dylibs, &mut symbols);
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].addr, 0x4010);
+        assert_eq!(symbols[0].item.name, "_malloc");
+        assert_eq!(symbols[0].item.module, Some("libSystem.B.dylib"));
+        assert_eq!(symbols[1].addr, 0x4018);
+        assert_eq!(symbols[1].item.name, "_free");
+    }
+
+    #[test]
+    fn export_trie() {
+        // Root with the edge "_ma" to a node with the children "in" and "lloc".
+        let trie = [
+            0x00, 0x01, b'_', b'm', b'a', 0x00, 0x07, // root
+            0x00, 0x02, b'i', b'n', 0x00, 0x15, b'l', b'l', b'o', b'c', 0x00, 0x1a, // _ma
+            0x00, 0x00, // padding
+            0x03, 0x00, 0x80, 0x20, 0x00, // _main at 0x1000
+            0x03, 0x00, 0x90, 0x20, 0x00, // _malloc at 0x1010
+        ];
+
+        let exports = parse_export_trie(&trie);
+        assert_eq!(exports, [("_main".to_string(), 0x1000), ("_malloc".to_string(), 0x1010)]);
+    }
+
+    #[test]
+    fn arm64e_plain_bind() {
+        let ptr = 1 << 62 | 5;
+        let format = ChainedFixupPointerGeneric::GenericArm64e;
+
+        assert_eq!(format.bind_and_stride(ptr), (true, 0));
+        assert_eq!(pointer_auth(ptr), None);
+    }
+}
@@ -0,0 +1,424 @@
+//! Objective-C runtime metadata of 64-bit Mach-O objects, as the runtime finds it through
+//! `__objc_classlist`, `__objc_protolist` and `__objc_selrefs`.
+//!
+//! Pointers in these sections are rebased or bound by dyld, so they are read through the fixups
+//! of the object rather than taken as is.
+
+use crate::macho::MachoDebugInfo;
+use object::read::macho::{MachHeader, MachOFile};
+use object::{Endianness, Object, ObjectSection, ObjectSegment};
+use std::collections::HashMap;
+
+/// Bits of `class_t::data` that point to the `class_ro_t`, the others are used by Swift.
+const FAST_DATA_MASK: u64 = 0x0000_7fff_ffff_fff8;
+
+/// Flag of a method list whose entries are 32-bit offsets rather than pointers.
+const METHOD_LIST_RELATIVE: u32 = 0x8000_0000;
+
+/// Flag of a `class_ro_t` that belongs to a metaclass.
+const RO_META: u32 = 0x1;
+
+pub struct Method {
+    pub selector: String,
+    /// Type encoding of the return value and arguments, e.g. `v16@0:8`.
+    pub types: String,
+}
+
+pub struct Class {
+    pub addr: u64,
+    pub name: String,
+    /// Name of the superclass, either defined in the object or imported.
+    pub superclass: Option<String>,
+    pub instance_methods: Vec<Method>,
+    pub class_methods: Vec<Method>,
+    pub protocols: Vec<String>,
+}
+
+pub struct Protocol {
+    pub name: String,
+    pub protocols: Vec<String>,
+    pub methods: Vec<Method>,
+    pub optional_methods: Vec<Method>,
+}
+
+pub struct ObjcMetadata {
+    pub classes: Vec<Class>,
+    pub protocols: Vec<Protocol>,
+    /// Selectors the code refers to through `__objc_selrefs`.
+    pub selectors: Vec<String>,
+}
+
+struct Image<'data> {
+    /// Sections by address, with their contents.
+    sections: Vec<(u64, &'data [u8])>,
+    /// Targets of rebased pointers by their address.
+    rebases: HashMap<u64, u64>,
+    /// Imported symbols of bound pointers by their address.
+    binds: HashMap<u64, &'data str>,
+    /// First address of the object, which offsets of rebases are relative to.
+    base: u64,
+}
+
+impl<'data> Image<'data> {
+    fn bytes(&self, addr: u64, len: usize) -> Option<&'data [u8]> {
+        let (start, data) = self
+            .sections
+            .iter()
+            .find(|(start, data)| (*start..*start + data.len() as u64).contains(&addr))?;
+        let offset = (addr - start) as usize;
+        data.get(offset..offset + len)
+    }
+
+    fn u32(&self, addr: u64) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(addr, 4)?.try_into().ok()?))
+    }
+
+    fn i32(&self, addr: u64) -> Option<i32> {
+        self.u32(addr).map(|value| value as i32)
+    }
+
+    /// Pointer at `addr` as it is once dyld rebased it, `None` if null or bound to an import.
+    fn ptr(&self, addr: u64) -> Option<u64> {
+        if self.binds.contains_key(&addr) {
+            return None;
+        }
+
+        if let Some(&target) = self.rebases.get(&addr) {
+            return Some(target);
+        }
+
+        let raw = u64::from_le_bytes(self.bytes(addr, 8)?.try_into().ok()?);
+        match raw {
+            0 => None,
+            // Chained fixups that weren't parsed keep their target in the low bits.
+            raw if raw >> 48 != 0 => {
+                let target = raw & 0xf_ffff_ffff;
+                Some(if target < self.base {
+                    target + self.base
+                } else {
+                    target
+                })
+            }
+            raw => Some(raw),
+        }
+    }
+
+    /// Nul-terminated string at `addr`.
+    fn str(&self, addr: u64) -> Option<String> {
+        let (start, data) = self
+            .sections
+            .iter()
+            .find(|(start, data)| (*start..*start + data.len() as u64).contains(&addr))?;
+        let bytes = &data[(addr - start) as usize..];
+        let len = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    fn str_at_ptr(&self, addr: u64) -> Option<String> {
+        self.str(self.ptr(addr)?)
+    }
+
+    fn methods(&self, list: Option<u64>) -> Vec<Method> {
+        let mut methods = Vec::new();
+        let Some(list) = list else {
+            return methods;
+        };
+
+        let (Some(entsize_and_flags), Some(count)) = (self.u32(list), self.u32(list + 4)) else {
+            return methods;
+        };
+
+        let relative = entsize_and_flags & METHOD_LIST_RELATIVE != 0;
+        let entsize = (entsize_and_flags & 0xfffc) as u64;
+
+        for idx in 0..count as u64 {
+            let entry = list + 8 + idx * entsize;
+            let method = if relative {
+                // Offsets from each field, the name's pointing to a selector reference.
+                let name = self.i32(entry).map(|off| entry.wrapping_add_signed(off as i64));
+                let types =
+                    self.i32(entry + 4).map(|off| (entry + 4).wrapping_add_signed(off as i64));
+                (
+                    name.and_then(|name| self.str_at_ptr(name)),
+                    types.and_then(|ty| self.str(ty)),
+                )
+            } else {
+                (self.str_at_ptr(entry), self.str_at_ptr(entry + 8))
+            };
+
+            if let (Some(selector), types) = method {
+                methods.push(Method {
+                    selector,
+                    types: types.unwrap_or_default(),
+                });
+            }
+        }
+
+        methods
+    }
+
+    fn protocol_names(&self, list: Option<u64>) -> Vec<String> {
+        let Some(list) = list else {
+            return Vec::new();
+        };
+
+        let count = self.bytes(list, 8).map_or(0, |bytes| {
+            u64::from_le_bytes(bytes.try_into().unwrap_or_default())
+        });
+
+        (0..count)
+            .filter_map(|idx| self.ptr(list + 8 + idx * 8))
+            .filter_map(|protocol| self.str_at_ptr(protocol + 8))
+            .collect()
+    }
+
+    /// Name and methods of a `class_ro_t`, given the address of the `class_t`.
+    fn class_ro(&self, class: u64) -> Option<(u32, String, Vec<Method>, Vec<String>)> {
+        // class_t { isa, superclass, cache, vtable, data }
+        let ro = self.ptr(class + 32)? & FAST_DATA_MASK;
+
+        // class_ro_t { flags, instance_start, instance_size, reserved, ivar_layout, name,
+        //              base_methods, base_protocols, .. }
+        let flags = self.u32(ro)?;
+        let name = self.str_at_ptr(ro + 24)?;
+        let methods = self.methods(self.ptr(ro + 32));
+        let protocols = self.protocol_names(self.ptr(ro + 40));
+        Some((flags, name, methods, protocols))
+    }
+
+    fn superclass(&self, class: u64) -> Option<String> {
+        if let Some(name) = self.binds.get(&(class + 8)) {
+            return Some(name.trim_start_matches("_OBJC_CLASS_$_").to_string());
+        }
+
+        let (_, name, ..) = self.class_ro(self.ptr(class + 8)?)?;
+        Some(name)
+    }
+
+    fn class(&self, addr: u64) -> Option<Class> {
+        let (flags, name, instance_methods, protocols) = self.class_ro(addr)?;
+        if flags & RO_META != 0 {
+            return None;
+        }
+
+        let class_methods = match self.ptr(addr).and_then(|meta| self.class_ro(meta)) {
+            Some((_, _, methods, _)) => methods,
+            None => Vec::new(),
+        };
+
+        Some(Class {
+            addr,
+            name,
+            superclass: self.superclass(addr),
+            instance_methods,
+            class_methods,
+            protocols,
+        })
+    }
+
+    fn protocol(&self, addr: u64) -> Option<Protocol> {
+        // protocol_t { isa, name, protocols, instance_methods, class_methods,
+        //              optional_instance_methods, .. }
+        Some(Protocol {
+            name: self.str_at_ptr(addr + 8)?,
+            protocols: self.protocol_names(self.ptr(addr + 16)),
+            methods: self.methods(self.ptr(addr + 24)),
+            optional_methods: self.methods(self.ptr(addr + 40)),
+        })
+    }
+}
+
+fn parse<'data, Mach: MachHeader<Endian = Endianness>>(
+    obj: &'data MachOFile<'data, Mach>,
+) -> Result<ObjcMetadata, String> {
+    let debug_info = MachoDebugInfo::parse(obj)
+        .map_err(|err| format!("Failed to read the object's fixups: {err}."))?;
+
+    let image = Image {
+        sections: obj
+            .sections()
+            .filter_map(|section| Some((section.address(), section.data().ok()?)))
+            .collect(),
+        rebases: debug_info
+            .pointers
+            .iter()
+            .filter_map(|ptr| Some((ptr.addr as u64, ptr.item.target?)))
+            .collect(),
+        binds: debug_info.syms.iter().map(|sym| (sym.addr as u64, sym.item.name)).collect(),
+        base: obj.segments().map(|seg| seg.address()).min().unwrap_or(0),
+    };
+
+    // Lists of pointers, each section is found in either `__DATA` or `__DATA_CONST`.
+    let list = |name: &str| -> Vec<u64> {
+        let Some(section) = obj.sections().find(|section| section.name().is_ok_and(|n| n == name))
+        else {
+            return Vec::new();
+        };
+
+        (0..section.size() / 8)
+            .filter_map(|idx| image.ptr(section.address() + idx * 8))
+            .collect()
+    };
+
+    let mut selectors: Vec<String> =
+        list("__objc_selrefs").into_iter().filter_map(|addr| image.str(addr)).collect();
+    selectors.sort();
+    selectors.dedup();
+
+    Ok(ObjcMetadata {
+        classes: list("__objc_classlist")
+            .into_iter()
+            .filter_map(|cls| image.class(cls))
+            .collect(),
+        protocols: list("__objc_protolist")
+            .into_iter()
+            .filter_map(|protocol| image.protocol(protocol))
+            .collect(),
+        selectors,
+    })
+}
+
+/// Objective-C classes, protocols and selectors of a Mach-O object.
+pub fn metadata(obj: &object::File) -> Result<ObjcMetadata, String> {
+    match obj {
+        object::File::MachO64(macho) => parse(macho),
+        object::File::MachO32(_) => {
+            Err("Only the Objective-C metadata of 64-bit objects can be read.".to_string())
+        }
+        _ => Err("Only Mach-O objects have Objective-C metadata.".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: u64 = 0x1000;
+
+    fn put(data: &mut [u8], addr: u64, bytes: &[u8]) {
+        let offset = (addr - BASE) as usize;
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn put_u64(data: &mut [u8], addr: u64, value: u64) {
+        put(data, addr, &value.to_le_bytes());
+    }
+
+    fn put_u32(data: &mut [u8], addr: u64, value: u32) {
+        put(data, addr, &value.to_le_bytes());
+    }
+
+    /// A class with an imported superclass, a metaclass with a relative method list and a
+    /// protocol, all in one section.
+    fn data() -> Vec<u8> {
+        let mut data = vec![0; 0x400];
+
+        for (addr, str) in [
+            (0x1300, "Foo"),
+            (0x1310, "init"),
+            (0x1320, "v16@0:8"),
+            (0x1330, "alloc"),
+            (0x1340, "NSCopying"),
+            (0x1360, "@16@0:8"),
+        ] {
+            put(&mut data, addr, str.as_bytes());
+        }
+
+        // class_t and its metaclass, the data pointer has flags in the low bits.
+        put_u64(&mut data, 0x1000, 0x1040);
+        put_u64(&mut data, 0x1020, 0x1101);
+        put_u64(&mut data, 0x1060, 0x1140);
+
+        // class_ro_t of the class and of the metaclass.
+        put_u64(&mut data, 0x1118, 0x1300);
+        put_u64(&mut data, 0x1120, 0x1180);
+        put_u64(&mut data, 0x1128, 0x1200);
+        put_u32(&mut data, 0x1140, RO_META);
+        put_u64(&mut data, 0x1158, 0x1300);
+        put_u64(&mut data, 0x1160, 0x11c0);
+
+        // method list of pointers.
+        put_u32(&mut data, 0x1180, 24);
+        put_u32(&mut data, 0x1184, 1);
+        put_u64(&mut data, 0x1188, 0x1310);
+        put_u64(&mut data, 0x1190, 0x1320);
+
+        // method list of offsets, the name's going through a selector reference.
+        put_u32(&mut data, 0x11c0, 12 | METHOD_LIST_RELATIVE);
+        put_u32(&mut data, 0x11c4, 1);
+        put_u32(&mut data, 0x11c8, (0x1280 - 0x11c8) as u32);
+        put_u32(&mut data, 0x11cc, (0x1360 - 0x11cc) as u32);
+        put_u64(&mut data, 0x1280, 0x1330);
+
+        // protocol list and protocol_t.
+        put_u64(&mut data, 0x1200, 1);
+        put_u64(&mut data, 0x1208, 0x1240);
+        put_u64(&mut data, 0x1248, 0x1340);
+        put_u64(&mut data, 0x1258, 0x1180);
+
+        data
+    }
+
+    fn image(data: &[u8]) -> Image<'_> {
+        Image {
+            sections: vec![(BASE, data)],
+            rebases: HashMap::new(),
+            binds: HashMap::from([(0x1008, "_OBJC_CLASS_$_NSObject")]),
+            base: BASE,
+        }
+    }
+
+    #[test]
+    fn pointers() {
+        let mut data = data();
+        // chained fixup with a target relative to the first address of the object.
+        put_u64(&mut data, 0x1290, 1 << 51 | 0x300);
+        let mut image = image(&data);
+        image.rebases.insert(0x1298, 0x1310);
+
+        assert_eq!(image.ptr(0x1290), Some(0x1300));
+        assert_eq!(image.ptr(0x1298), Some(0x1310));
+        assert_eq!(image.ptr(0x1008), None);
+        assert_eq!(image.ptr(0x1010), None);
+        assert_eq!(image.ptr(0x1800), None);
+        assert_eq!(image.str_at_ptr(0x1290).as_deref(), Some("Foo"));
+    }
+
+    #[test]
+    fn class() {
+        let data = data();
+        let image = image(&data);
+        let class = image.class(0x1000).unwrap();
+
+        assert_eq!(class.name, "Foo");
+        assert_eq!(class.superclass.as_deref(), Some("NSObject"));
+        assert_eq!(class.protocols, ["NSCopying"]);
+
+        let methods = |methods: &[Method]| -> Vec<(String, String)> {
+            methods.iter().map(|m| (m.selector.clone(), m.types.clone())).collect()
+        };
+        assert_eq!(
+            methods(&class.instance_methods),
+            [("init".to_string(), "v16@0:8".to_string())]
+        );
+        assert_eq!(
+            methods(&class.class_methods),
+            [("alloc".to_string(), "@16@0:8".to_string())]
+        );
+
+        // metaclasses aren't listed as classes of their own.
+        assert!(image.class(0x1040).is_none());
+    }
+
+    #[test]
+    fn protocol() {
+        let data = data();
+        let protocol = image(&data).protocol(0x1240).unwrap();
+
+        assert_eq!(protocol.name, "NSCopying");
+        assert!(protocol.protocols.is_empty());
+        assert_eq!(protocol.methods.len(), 1);
+        assert_eq!(protocol.methods[0].selector, "init");
+        assert!(protocol.optional_methods.is_empty());
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{datastructure, RawSymbol};
 use processor_shared::{AddressMap, Addressed, Section, SectionKind};
+use md5::{Digest, Md5};
 use object::pe;
-use object::read::pe::{ImageNtHeaders, ImageThunkData, PeFile};
+use object::read::pe::{
+    ExportTarget, ImageNtHeaders, Import, ImageThunkData, PeFile, RichHeaderInfo,
+};
 use object::LittleEndian as LE;
 use object::Object;
 use std::mem::size_of;
@@ -103,6 +106,18 @@ impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
 
     pub fn parse_symbols(&mut self) {
         self.syms.extend(crate::parse_symbol_table(self.obj));
+
+        // Callbacks run before the entry point, so they're functions of their own.
+        for (idx, addr) in callbacks(self.obj).into_iter().enumerate() {
+            self.syms.push(Addressed {
+                addr: addr as usize,
+                item: RawSymbol {
+                    name: Box::leak(format!("tls_callback_{idx}").into_boxed_str()),
+                    module: None,
+                },
+            });
+        }
+
         self.syms.push(Addressed {
             addr: self.obj.entry() as usize,
             item: RawSymbol {
@@ -153,8 +168,8 @@ fn parse_sections<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> V
         let characteristics = header.characteristics.get(LE);
         let (mut kind, ident) = (SectionKind::Raw, "UNKNOWN");
 
-        // Section contains code.
-        if characteristics & pe::IMAGE_SCN_CNT_CODE != 0 {
+        // Section contains code. Firmware toolchains sometimes only mark it as executable.
+        if characteristics & (pe::IMAGE_SCN_CNT_CODE | pe::IMAGE_SCN_MEM_EXECUTE) != 0 {
             kind = SectionKind::Code;
         }
 
@@ -180,3 +195,513 @@ fn parse_sections<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> V
 
     sections
 }
+
+/// Addresses of the TLS callbacks, which the loader calls whenever a thread starts or exits.
+///
+/// The first thread is started before the entry point is called, which makes them a common
+/// place to hide anti-debugging checks.
+fn callbacks<Pe: ImageNtHeaders>(obj: &PeFile<Pe>) -> Vec<u64> {
+    let mut callbacks = Vec::new();
+    let data = obj.data();
+    let sections = obj.section_table();
+    let tls = match obj.data_directory(pe::IMAGE_DIRECTORY_ENTRY_TLS) {
+        Some(dir) => dir.data(data, &sections).unwrap_or_default(),
+        None => return callbacks,
+    };
+
+    let ptr_size = if obj.is_64() { 8 } else { 4 };
+    let read_ptr = |bytes: &[u8], offset: usize| {
+        let bytes = bytes.get(offset..offset + ptr_size)?;
+        Some(match ptr_size {
+            8 => u64::from_le_bytes(bytes.try_into().unwrap()),
+            _ => u32::from_le_bytes(bytes.try_into().unwrap()) as u64,
+        })
+    };
+
+    // `AddressOfCallBacks` follows the raw data range and the address of the index.
+    let array = read_ptr(tls, 3 * ptr_size)
+        .and_then(|va| va.checked_sub(obj.relative_address_base()))
+        .and_then(|rva| sections.pe_data_at(data, rva as u32));
+
+    if let Some(array) = array {
+        while let Some(va) = read_ptr(array, callbacks.len() * ptr_size).filter(|&va| va != 0) {
+            callbacks.push(va);
+        }
+    }
+
+    callbacks
+}
+
+/// TLS callbacks of a PE object, empty for any other object.
+pub fn tls_callbacks(obj: &object::File) -> Vec<u64> {
+    match obj {
+        object::File::Pe32(pe) => callbacks(pe),
+        object::File::Pe64(pe) => callbacks(pe),
+        _ => Vec::new(),
+    }
+}
+
+/// Tool of the toolchain that contributed to the object, as recorded by the linker.
+pub struct RichEntry {
+    /// Identifies the tool and its version, e.g. the C++ compiler of Visual Studio 2019.
+    pub product: u16,
+    /// Build number of the tool.
+    pub build: u16,
+    /// Number of objects the tool produced.
+    pub count: u32,
+}
+
+/// Undocumented header written by Microsoft's linker between the DOS stub and PE header.
+pub struct RichHeader {
+    /// Offset of the header in the object.
+    pub offset: usize,
+    /// Key the entries are masked with.
+    pub key: u32,
+    /// Checksum of the DOS header and entries, which matches the key unless it was tampered with.
+    pub checksum: u32,
+    pub entries: Vec<RichEntry>,
+}
+
+/// Checksum over the DOS header and stub, skipping `e_lfanew`, and the entries of the header.
+fn rich_checksum(data: &[u8], info: &RichHeaderInfo) -> u32 {
+    let mut checksum = info.offset as u32;
+    for (idx, &b) in data.iter().take(info.offset).enumerate() {
+        if !(0x3c..0x40).contains(&idx) {
+            checksum = checksum.wrapping_add((b as u32).rotate_left(idx as u32));
+        }
+    }
+
+    for entry in info.unmasked_entries() {
+        checksum = checksum.wrapping_add(entry.comp_id.rotate_left(entry.count));
+    }
+
+    checksum
+}
+
+/// The Rich header of a PE object, if it was linked by Microsoft's linker.
+pub fn rich_header(obj: &object::File) -> Option<RichHeader> {
+    let (data, info) = match obj {
+        object::File::Pe32(pe) => (pe.data(), pe.rich_header_info()?),
+        object::File::Pe64(pe) => (pe.data(), pe.rich_header_info()?),
+        _ => return None,
+    };
+
+    Some(parse_rich_header(data, &info))
+}
+
+fn parse_rich_header(data: &[u8], info: &RichHeaderInfo) -> RichHeader {
+    let entries = info
+        .unmasked_entries()
+        .map(|entry| RichEntry {
+            product: (entry.comp_id >> 16) as u16,
+            build: entry.comp_id as u16,
+            count: entry.count,
+        })
+        .collect();
+
+    RichHeader {
+        offset: info.offset,
+        key: info.xor_key,
+        checksum: rich_checksum(data, info),
+        entries,
+    }
+}
+
+/// Name of a library as written by imphash, lowercase without a `.dll`, `.ocx` or `.sys` extension.
+fn imphash_library(name: &[u8]) -> String {
+    let library = String::from_utf8_lossy(name).to_lowercase();
+    match library.rsplit_once('.') {
+        Some((stem, "dll" | "ocx" | "sys")) => stem.to_string(),
+        _ => library,
+    }
+}
+
+/// Imported functions as `library.function`, normalized the way imphash does.
+fn import_names<'data, Pe: ImageNtHeaders>(
+    obj: &PeFile<'data, Pe>,
+) -> Result<Vec<String>, object::Error> {
+    let mut names = Vec::new();
+    let import_table = match obj.import_table()? {
+        Some(table) => table,
+        None => return Ok(names),
+    };
+
+    let mut import_descs = import_table.descriptors()?;
+    while let Some(import_desc) = import_descs.next()? {
+        let library = imphash_library(import_table.name(import_desc.name.get(LE))?);
+
+        let mut thunk = import_desc.original_first_thunk.get(LE);
+        if thunk == 0 {
+            thunk = import_desc.first_thunk.get(LE);
+        }
+
+        let mut thunks = import_table.thunks(thunk)?;
+        while let Some(thunk) = thunks.next::<Pe>()? {
+            // pefile resolves the ordinals of a few libraries like ws2_32 to names, these are
+            // left as ordinals.
+            let func = match import_table.import::<Pe>(thunk)? {
+                Import::Ordinal(ordinal) => format!("ord{ordinal}"),
+                Import::Name(_, name) => String::from_utf8_lossy(name).to_lowercase(),
+            };
+
+            names.push(format!("{library}.{func}"));
+        }
+    }
+
+    Ok(names)
+}
+
+/// MD5 of the imported functions in the order they're imported, as introduced by Mandiant.
+///
+/// Objects built from the same source and toolchain tend to share an imphash, even when their
+/// code is packed. `None` for objects that aren't PE or don't import anything.
+pub fn imphash(obj: &object::File) -> Result<Option<String>, object::Error> {
+    let names = match obj {
+        object::File::Pe32(pe) => import_names(pe)?,
+        object::File::Pe64(pe) => import_names(pe)?,
+        _ => return Ok(None),
+    };
+
+    Ok(hash_imports(&names))
+}
+
+fn hash_imports(names: &[String]) -> Option<String> {
+    if names.is_empty() {
+        return None;
+    }
+
+    let digest = Md5::digest(names.join(",").as_bytes());
+    Some(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Function imported through the import address table (IAT).
+pub struct PeImport<'data> {
+    pub library: &'data str,
+    /// Name of the function, `None` if it's imported by ordinal.
+    pub name: Option<&'data str>,
+    pub ordinal: Option<u16>,
+    /// Address of the IAT slot the loader writes the function's address to.
+    pub slot: u64,
+}
+
+fn pe_imports<'data, Pe: ImageNtHeaders>(
+    obj: &PeFile<'data, Pe>,
+) -> Result<Vec<PeImport<'data>>, object::Error> {
+    let mut imports = Vec::new();
+    let import_table = match obj.import_table()? {
+        Some(table) => table,
+        None => return Ok(imports),
+    };
+
+    let mut import_descs = import_table.descriptors()?;
+    while let Some(import_desc) = import_descs.next()? {
+        let library = import_table.name(import_desc.name.get(LE))?;
+        let library = std::str::from_utf8(library).unwrap_or("");
+        let first_thunk = import_desc.first_thunk.get(LE);
+
+        // The IAT itself is overwritten once bound, the lookup table keeps the names.
+        let mut thunk = import_desc.original_first_thunk.get(LE);
+        if thunk == 0 {
+            thunk = first_thunk;
+        }
+
+        let mut slot = obj.relative_address_base() + first_thunk as u64;
+        let mut thunks = import_table.thunks(thunk)?;
+        while let Some(thunk) = thunks.next::<Pe>()? {
+            let (name, ordinal) = match import_table.import::<Pe>(thunk)? {
+                Import::Ordinal(ordinal) => (None, Some(ordinal)),
+                Import::Name(_, name) => (std::str::from_utf8(name).ok(), None),
+            };
+
+            imports.push(PeImport {
+                library,
+                name,
+                ordinal,
+                slot,
+            });
+            slot += size_of::<Pe::ImageThunkData>() as u64;
+        }
+    }
+
+    Ok(imports)
+}
+
+/// Functions imported by a PE object, in the order of its import directory.
+pub fn imports<'data>(obj: &object::File<'data>) -> Result<Vec<PeImport<'data>>, object::Error> {
+    match obj {
+        object::File::Pe32(pe) => pe_imports(pe),
+        object::File::Pe64(pe) => pe_imports(pe),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Where an export of a PE object leads.
+pub enum PeExportTarget {
+    Address(u64),
+    /// Another library's export, which the loader resolves instead, e.g. `NTDLL.RtlAllocateHeap`.
+    Forward(String),
+}
+
+/// Entry of the export directory.
+pub struct PeExport<'data> {
+    pub ordinal: u32,
+    /// Name of the export, `None` if it's only exported by ordinal.
+    pub name: Option<&'data str>,
+    pub target: PeExportTarget,
+}
+
+fn pe_exports<'data, Pe: ImageNtHeaders>(
+    obj: &PeFile<'data, Pe>,
+) -> Result<Vec<PeExport<'data>>, object::Error> {
+    let export_table = match obj.export_table()? {
+        Some(table) => table,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut exports = Vec::new();
+    for export in export_table.exports()? {
+        let target = match export.target {
+            ExportTarget::Address(rva) => {
+                PeExportTarget::Address(obj.relative_address_base() + rva as u64)
+            }
+            ExportTarget::ForwardByName(library, name) => PeExportTarget::Forward(format!(
+                "{}.{}",
+                String::from_utf8_lossy(library),
+                String::from_utf8_lossy(name)
+            )),
+            ExportTarget::ForwardByOrdinal(library, ordinal) => PeExportTarget::Forward(format!(
+                "{}.#{ordinal}",
+                String::from_utf8_lossy(library)
+            )),
+        };
+
+        exports.push(PeExport {
+            ordinal: export.ordinal,
+            name: export.name.and_then(|name| std::str::from_utf8(name).ok()),
+            target,
+        });
+    }
+
+    exports.sort_unstable_by_key(|export| export.ordinal);
+    Ok(exports)
+}
+
+/// Export directory of a PE object, sorted by ordinal.
+pub fn exports<'data>(obj: &object::File<'data>) -> Result<Vec<PeExport<'data>>, object::Error> {
+    match obj {
+        object::File::Pe32(pe) => pe_exports(pe),
+        object::File::Pe64(pe) => pe_exports(pe),
+        _ => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::pe::{NtHeaders, Writer};
+
+    /// DOS header and stub followed by a Rich header with the entries `(comp_id, count)`.
+    fn dos_header(entries: &[(u32, u32)], key: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 0x80];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x40..0x4e].copy_from_slice(b"This program c");
+
+        data.extend((0x536e_6144 ^ key).to_le_bytes());
+        for _ in 0..3 {
+            data.extend(key.to_le_bytes());
+        }
+        for &(comp_id, count) in entries {
+            data.extend((comp_id ^ key).to_le_bytes());
+            data.extend((count ^ key).to_le_bytes());
+        }
+        data.extend(b"Rich");
+        data.extend(key.to_le_bytes());
+
+        let nt_offset = data.len() as u32;
+        data[0x3c..0x40].copy_from_slice(&nt_offset.to_le_bytes());
+        data
+    }
+
+    fn rich(data: &[u8]) -> RichHeader {
+        let info = RichHeaderInfo::parse(data, data.len() as u64).unwrap();
+        parse_rich_header(data, &info)
+    }
+
+    #[test]
+    fn rich_header() {
+        // Utc1900 C++ and the linker of Visual Studio 2019.
+        let entries = [(0x0104_6b74, 12), (0x0102_6b74, 1)];
+
+        // The linker uses the checksum as key, which doesn't depend on it.
+        let checksum = rich(&dos_header(&entries, 0)).checksum;
+        let data = dos_header(&entries, checksum);
+        let header = rich(&data);
+
+        assert_eq!(header.offset, 0x80);
+        assert_eq!(header.key, header.checksum);
+        assert_eq!(header.entries.len(), 2);
+        assert_eq!(header.entries[0].product, 0x0104);
+        assert_eq!(header.entries[0].build, 0x6b74);
+        assert_eq!(header.entries[0].count, 12);
+
+        // Anything changed in the stub shows up as a mismatch.
+        let mut tampered = data.clone();
+        tampered[0x41] = b'X';
+        assert_ne!(rich(&tampered).checksum, header.key);
+    }
+
+    #[test]
+    fn imphash() {
+        assert_eq!(imphash_library(b"KERNEL32.dll"), "kernel32");
+        assert_eq!(imphash_library(b"WS2_32.DLL"), "ws2_32");
+        assert_eq!(imphash_library(b"msvcr120.so"), "msvcr120.so");
+
+        let names = [
+            "kernel32.exitprocess",
+            "kernel32.getlasterror",
+            "ws2_32.ord23",
+            "msvcrt.printf",
+        ]
+        .map(String::from);
+        assert_eq!(
+            hash_imports(&names).unwrap(),
+            "075273b508fe581ac972694983edf067"
+        );
+        assert_eq!(hash_imports(&[]), None);
+    }
+
+    const IMAGE_BASE: u64 = 0x1_8000_0000;
+
+    fn put(data: &mut [u8], offset: u32, bytes: &[u8]) {
+        data[offset as usize..offset as usize + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Import directory of `KERNEL32.dll` with `ExitProcess` and `GetLastError` by name and
+    /// `WS2_32.dll` with ordinal 23, at `rva`.
+    fn idata(rva: u32) -> Vec<u8> {
+        let mut data = vec![0; 0x100];
+        for (offset, lookup, name, iat) in [(0x00, 0x40, 0xa0, 0x70), (0x14, 0x58, 0xb0, 0x88)] {
+            put(&mut data, offset, &(rva + lookup).to_le_bytes());
+            put(&mut data, offset + 12, &(rva + name).to_le_bytes());
+            put(&mut data, offset + 16, &(rva + iat).to_le_bytes());
+        }
+
+        let by_name = [(rva + 0xc0) as u64, (rva + 0xd0) as u64, 0];
+        let by_ordinal = [pe::IMAGE_ORDINAL_FLAG64 | 23, 0];
+        for (offset, thunks) in [(0x40, &by_name[..]), (0x70, &by_name), (0x58, &by_ordinal)] {
+            let thunks: Vec<u8> = thunks.iter().flat_map(|thunk| thunk.to_le_bytes()).collect();
+            put(&mut data, offset, &thunks);
+        }
+        put(&mut data, 0x88, &by_ordinal[0].to_le_bytes());
+
+        put(&mut data, 0xa0, b"KERNEL32.dll");
+        put(&mut data, 0xb0, b"WS2_32.dll");
+        put(&mut data, 0xc2, b"ExitProcess");
+        put(&mut data, 0xd2, b"GetLastError");
+        data
+    }
+
+    /// Export directory of `Alpha`, `Forward` to `NTDLL.RtlAllocateHeap` and an export by
+    /// ordinal only, at `rva`.
+    fn edata(rva: u32) -> Vec<u8> {
+        let mut data = vec![0; 0x100];
+        let fields = [rva + 0x80, 1, 3, 2, rva + 0x28, rva + 0x34, rva + 0x3c];
+        for (idx, field) in fields.into_iter().enumerate() {
+            put(&mut data, 12 + idx as u32 * 4, &field.to_le_bytes());
+        }
+
+        for (idx, func) in [0x1010, rva + 0x90, 0x1020].into_iter().enumerate() {
+            put(&mut data, 0x28 + idx as u32 * 4, &func.to_le_bytes());
+        }
+        put(&mut data, 0x34, &(rva + 0x50).to_le_bytes());
+        put(&mut data, 0x38, &(rva + 0x60).to_le_bytes());
+        put(&mut data, 0x3e, &1u16.to_le_bytes());
+
+        put(&mut data, 0x50, b"Alpha");
+        put(&mut data, 0x60, b"Forward");
+        put(&mut data, 0x80, b"test.dll");
+        put(&mut data, 0x90, b"NTDLL.RtlAllocateHeap");
+        data
+    }
+
+    fn library() -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Writer::new(true, 0x1000, 0x200, &mut data);
+        writer.reserve_dos_header();
+        writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+        writer.reserve_section_headers(2);
+        let idata_range = writer.reserve_idata_section(0x100);
+        let edata_range = writer.reserve_edata_section(0x100);
+
+        writer.write_empty_dos_header().unwrap();
+        writer.write_nt_headers(NtHeaders {
+            machine: pe::IMAGE_FILE_MACHINE_AMD64,
+            time_date_stamp: 0,
+            characteristics: pe::IMAGE_FILE_DLL | pe::IMAGE_FILE_EXECUTABLE_IMAGE,
+            major_linker_version: 14,
+            minor_linker_version: 0,
+            address_of_entry_point: 0,
+            image_base: IMAGE_BASE,
+            major_operating_system_version: 6,
+            minor_operating_system_version: 0,
+            major_image_version: 0,
+            minor_image_version: 0,
+            major_subsystem_version: 6,
+            minor_subsystem_version: 0,
+            subsystem: pe::IMAGE_SUBSYSTEM_WINDOWS_CUI,
+            dll_characteristics: 0,
+            size_of_stack_reserve: 0x10_0000,
+            size_of_stack_commit: 0x1000,
+            size_of_heap_reserve: 0x10_0000,
+            size_of_heap_commit: 0x1000,
+        });
+        writer.write_section_headers();
+        writer.write_section(idata_range.file_offset, &idata(idata_range.virtual_address));
+        writer.write_section(edata_range.file_offset, &edata(edata_range.virtual_address));
+        data
+    }
+
+    #[test]
+    fn import_directory() {
+        let data = library();
+        let obj = object::File::parse(&*data).unwrap();
+        let imports: Vec<_> = imports(&obj)
+            .unwrap()
+            .into_iter()
+            .map(|import| (import.library, import.name, import.ordinal, import.slot))
+            .collect();
+
+        // the directory is the first section, its IAT starts at 0x70.
+        let iat = IMAGE_BASE + 0x1070;
+        assert_eq!(
+            imports,
+            [
+                ("KERNEL32.dll", Some("ExitProcess"), None, iat),
+                ("KERNEL32.dll", Some("GetLastError"), None, iat + 8),
+                ("WS2_32.dll", None, Some(23), iat + 0x18),
+            ]
+        );
+    }
+
+    #[test]
+    fn export_directory() {
+        let data = library();
+        let obj = object::File::parse(&*data).unwrap();
+        let exports = exports(&obj).unwrap();
+
+        let names: Vec<_> = exports.iter().map(|export| (export.ordinal, export.name)).collect();
+        assert_eq!(names, [(1, Some("Alpha")), (2, Some("Forward")), (3, None)]);
+
+        let targets: Vec<_> = exports
+            .iter()
+            .map(|export| match &export.target {
+                PeExportTarget::Address(addr) => format!("{addr:#x}"),
+                PeExportTarget::Forward(name) => name.clone(),
+            })
+            .collect();
+        assert_eq!(
+            targets,
+            ["0x180001010", "NTDLL.RtlAllocateHeap", "0x180001020"]
+        );
+    }
+}
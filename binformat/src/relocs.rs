@@ -0,0 +1,142 @@
+//! Relocations of an object, both the ones applied by the static linker and by the loader.
+
+use object::{elf, pe};
+use object::read::pe::{ImageNtHeaders, PeFile};
+use object::{Object, ObjectSection, ObjectSymbol, ObjectSymbolTable, ReadRef};
+use object::{RelocationKind, RelocationTarget};
+
+pub struct Reloc {
+    /// Address of the relocated field.
+    pub addr: u64,
+    /// Section the relocated field lies in, or where the relocation is stored for the loader.
+    pub section: String,
+    pub kind: String,
+    /// Name of the symbol or section the field is pointed at, empty for absolute relocations.
+    pub target: String,
+    pub addend: i64,
+}
+
+/// Name of the relocations that aren't given a generic kind, mostly those used by the loader.
+fn kind_name(kind: RelocationKind) -> String {
+    let name = match kind {
+        RelocationKind::Elf(r_type) => match r_type {
+            elf::R_X86_64_COPY => "R_X86_64_COPY",
+            elf::R_X86_64_GLOB_DAT => "R_X86_64_GLOB_DAT",
+            elf::R_X86_64_JUMP_SLOT => "R_X86_64_JUMP_SLOT",
+            elf::R_X86_64_RELATIVE => "R_X86_64_RELATIVE",
+            elf::R_X86_64_DTPMOD64 => "R_X86_64_DTPMOD64",
+            elf::R_X86_64_DTPOFF64 => "R_X86_64_DTPOFF64",
+            elf::R_X86_64_TPOFF64 => "R_X86_64_TPOFF64",
+            elf::R_X86_64_IRELATIVE => "R_X86_64_IRELATIVE",
+            elf::R_AARCH64_COPY => "R_AARCH64_COPY",
+            elf::R_AARCH64_GLOB_DAT => "R_AARCH64_GLOB_DAT",
+            elf::R_AARCH64_JUMP_SLOT => "R_AARCH64_JUMP_SLOT",
+            elf::R_AARCH64_RELATIVE => "R_AARCH64_RELATIVE",
+            elf::R_AARCH64_IRELATIVE => "R_AARCH64_IRELATIVE",
+            _ => return format!("{kind:?}"),
+        },
+        _ => return format!("{kind:?}"),
+    };
+
+    name.to_string()
+}
+
+fn base_reloc_kind(typ: u16) -> String {
+    match typ {
+        pe::IMAGE_REL_BASED_HIGH => "HIGH".to_string(),
+        pe::IMAGE_REL_BASED_LOW => "LOW".to_string(),
+        pe::IMAGE_REL_BASED_HIGHLOW => "HIGHLOW".to_string(),
+        pe::IMAGE_REL_BASED_HIGHADJ => "HIGHADJ".to_string(),
+        pe::IMAGE_REL_BASED_DIR64 => "DIR64".to_string(),
+        _ => format!("Based({typ})"),
+    }
+}
+
+/// Base relocations, applied by the loader when the image isn't loaded at its preferred base.
+fn base_relocs<'data, Pe: ImageNtHeaders, R: ReadRef<'data>>(
+    obj: &PeFile<'data, Pe, R>,
+    relocs: &mut Vec<Reloc>,
+) {
+    let sections = obj.section_table();
+    let mut blocks = match obj.data_directories().relocation_blocks(obj.data(), &sections) {
+        Ok(Some(blocks)) => blocks,
+        _ => return,
+    };
+
+    while let Ok(Some(block)) = blocks.next() {
+        // Absolute entries only pad the block.
+        for reloc in block.filter(|reloc| reloc.typ != pe::IMAGE_REL_BASED_ABSOLUTE) {
+            relocs.push(Reloc {
+                addr: obj.relative_address_base() + reloc.virtual_address as u64,
+                section: ".reloc".to_string(),
+                kind: base_reloc_kind(reloc.typ),
+                target: String::new(),
+                addend: 0,
+            });
+        }
+    }
+}
+
+/// All relocations of `obj`, in the order they're stored.
+pub fn relocations(obj: &object::File) -> Vec<Reloc> {
+    let mut relocs = Vec::new();
+
+    let target_name = |target: RelocationTarget, dynamic: bool| -> String {
+        let name = match target {
+            RelocationTarget::Symbol(idx) if dynamic => obj
+                .dynamic_symbol_table()
+                .and_then(|table| table.symbol_by_index(idx).ok())
+                .and_then(|sym| sym.name().ok().map(str::to_string)),
+            RelocationTarget::Symbol(idx) => {
+                obj.symbol_by_index(idx).ok().and_then(|sym| match sym.name() {
+                    // Section symbols don't have a name of their own.
+                    Ok("") => sym
+                        .section_index()
+                        .and_then(|idx| obj.section_by_index(idx).ok())
+                        .and_then(|section| section.name().ok().map(str::to_string)),
+                    name => name.ok().map(str::to_string),
+                })
+            }
+            RelocationTarget::Section(idx) => obj
+                .section_by_index(idx)
+                .ok()
+                .and_then(|section| section.name().ok().map(str::to_string)),
+            _ => None,
+        };
+
+        name.unwrap_or_default()
+    };
+
+    let layout = crate::relocatable_layout(obj);
+    for section in obj.sections() {
+        let name = section.name().unwrap_or("unknown");
+        let base = layout.get(section.index().0).copied().unwrap_or(section.address());
+        for (offset, reloc) in section.relocations() {
+            relocs.push(Reloc {
+                addr: base + offset,
+                section: name.to_string(),
+                kind: kind_name(reloc.kind()),
+                target: target_name(reloc.target(), false),
+                addend: reloc.addend(),
+            });
+        }
+    }
+
+    for (addr, reloc) in obj.dynamic_relocations().into_iter().flatten() {
+        relocs.push(Reloc {
+            addr,
+            section: "dynamic".to_string(),
+            kind: kind_name(reloc.kind()),
+            target: target_name(reloc.target(), true),
+            addend: reloc.addend(),
+        });
+    }
+
+    match obj {
+        object::File::Pe32(pe) => base_relocs(pe, &mut relocs),
+        object::File::Pe64(pe) => base_relocs(pe, &mut relocs),
+        _ => {}
+    }
+
+    relocs
+}
@@ -0,0 +1,270 @@
+//! Exploit mitigations an object was built with, similar to `checksec`.
+
+use object::read::elf::{Dyn, ElfFile, FileHeader, ProgramHeader};
+use object::read::macho::{MachHeader, MachOFile};
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::{elf, macho, pe, LittleEndian, Object, ObjectSymbol};
+
+/// Whether a mitigation is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Enabled,
+    Partial,
+    Disabled,
+}
+
+/// A single mitigation, e.g. a non-executable stack.
+#[derive(Debug, Clone)]
+pub struct Mitigation {
+    pub name: &'static str,
+    pub status: Status,
+}
+
+fn mitigation(name: &'static str, enabled: bool) -> Mitigation {
+    let status = if enabled { Status::Enabled } else { Status::Disabled };
+    Mitigation { name, status }
+}
+
+/// Whether any (dynamic) symbol has the given name, used to detect stack protectors.
+fn has_symbol<'data: 'file, 'file, Obj: Object<'data, 'file>>(
+    obj: &'file Obj,
+    pred: impl Fn(&str) -> bool,
+) -> bool {
+    obj.symbols()
+        .chain(obj.dynamic_symbols())
+        .any(|sym| sym.name().is_ok_and(&pred))
+}
+
+fn elf_mitigations<Elf: FileHeader>(obj: &ElfFile<Elf>) -> Vec<Mitigation> {
+    let endian = obj.endian();
+    let segments = obj.raw_segments();
+
+    let pie = obj.raw_header().e_type(endian) == elf::ET_DYN
+        && segments.iter().any(|s| s.p_type(endian) == elf::PT_INTERP);
+
+    // Without a PT_GNU_STACK header the stack is executable.
+    let nx = segments
+        .iter()
+        .find(|s| s.p_type(endian) == elf::PT_GNU_STACK)
+        .is_some_and(|s| s.p_flags(endian) & elf::PF_X == 0);
+
+    let bind_now = crate::elf::dynamic_entries(obj).is_some_and(|(entries, _)| {
+        entries.iter().any(|entry| {
+            let val = entry.d_val(endian).into();
+            match entry.tag32(endian) {
+                Some(elf::DT_BIND_NOW) => true,
+                Some(elf::DT_FLAGS) => val & elf::DF_BIND_NOW as u64 != 0,
+                Some(elf::DT_FLAGS_1) => val & elf::DF_1_NOW as u64 != 0,
+                _ => false,
+            }
+        })
+    });
+
+    let relro = match segments.iter().any(|s| s.p_type(endian) == elf::PT_GNU_RELRO) {
+        true if bind_now => Status::Enabled,
+        true => Status::Partial,
+        false => Status::Disabled,
+    };
+
+    vec![
+        mitigation("PIE", pie),
+        mitigation("NX", nx),
+        Mitigation { name: "RELRO", status: relro },
+        mitigation("Stack canary", has_symbol(obj, |name| name.starts_with("__stack_chk"))),
+        mitigation("Fortify", has_symbol(obj, |name| name.ends_with("_chk"))),
+    ]
+}
+
+fn pe_mitigations<Pe: ImageNtHeaders>(obj: &PeFile<Pe>) -> Vec<Mitigation> {
+    let flags = obj.nt_headers().optional_header().dll_characteristics();
+
+    vec![
+        mitigation("ASLR", flags & pe::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0),
+        mitigation(
+            "High entropy ASLR",
+            flags & pe::IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA != 0,
+        ),
+        mitigation("NX", flags & pe::IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0),
+        mitigation("Control flow guard", flags & pe::IMAGE_DLLCHARACTERISTICS_GUARD_CF != 0),
+        mitigation("SEH", flags & pe::IMAGE_DLLCHARACTERISTICS_NO_SEH == 0),
+    ]
+}
+
+fn macho_mitigations<Mach: MachHeader>(obj: &MachOFile<Mach>) -> Vec<Mitigation> {
+    let flags = obj.raw_header().flags(obj.endian());
+
+    vec![
+        mitigation("PIE", flags & macho::MH_PIE != 0),
+        mitigation("NX", flags & macho::MH_ALLOW_STACK_EXECUTION == 0),
+        mitigation("Stack canary", has_symbol(obj, |name| name.starts_with("___stack_chk"))),
+    ]
+}
+
+/// Where the loader places an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// At the address it was linked at.
+    Fixed,
+    /// At a random address, as position independent executable.
+    Executable,
+    /// At a random address, as library.
+    Library,
+}
+
+fn elf_placement<Elf: FileHeader>(obj: &ElfFile<Elf>) -> Placement {
+    let endian = obj.endian();
+    if obj.raw_header().e_type(endian) != elf::ET_DYN {
+        return Placement::Fixed;
+    }
+
+    match obj.raw_segments().iter().any(|s| s.p_type(endian) == elf::PT_INTERP) {
+        true => Placement::Executable,
+        false => Placement::Library,
+    }
+}
+
+fn pe_placement<Pe: ImageNtHeaders>(obj: &PeFile<Pe>) -> Placement {
+    let flags = obj.nt_headers().optional_header().dll_characteristics();
+    let characteristics = obj.nt_headers().file_header().characteristics.get(LittleEndian);
+
+    if flags & pe::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE == 0 {
+        Placement::Fixed
+    } else if characteristics & pe::IMAGE_FILE_DLL != 0 {
+        Placement::Library
+    } else {
+        Placement::Executable
+    }
+}
+
+fn macho_placement<Mach: MachHeader>(obj: &MachOFile<Mach>) -> Placement {
+    let header = obj.raw_header();
+    match header.filetype(obj.endian()) {
+        macho::MH_DYLIB | macho::MH_BUNDLE => Placement::Library,
+        _ if header.flags(obj.endian()) & macho::MH_PIE != 0 => Placement::Executable,
+        _ => Placement::Fixed,
+    }
+}
+
+/// Whether the loader randomizes the address the object is placed at, and as what.
+pub fn placement(obj: &object::File) -> Placement {
+    match obj {
+        object::File::Elf32(elf) => elf_placement(elf),
+        object::File::Elf64(elf) => elf_placement(elf),
+        object::File::Pe32(pe) => pe_placement(pe),
+        object::File::Pe64(pe) => pe_placement(pe),
+        object::File::MachO32(macho) => macho_placement(macho),
+        object::File::MachO64(macho) => macho_placement(macho),
+        _ => Placement::Fixed,
+    }
+}
+
+/// Mitigations relevant to the object's format.
+pub fn mitigations(obj: &object::File) -> Vec<Mitigation> {
+    match obj {
+        object::File::Elf32(elf) => elf_mitigations(elf),
+        object::File::Elf64(elf) => elf_mitigations(elf),
+        object::File::Pe32(pe) => pe_mitigations(pe),
+        object::File::Pe64(pe) => pe_mitigations(pe),
+        object::File::MachO32(macho) => macho_mitigations(macho),
+        object::File::MachO64(macho) => macho_mitigations(macho),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::elf::{FileHeader, ProgramHeader, Writer};
+    use object::Endianness;
+
+    /// Minimal x86_64 ELF with a header for each segment type and a `.dynamic` section.
+    fn elf(e_type: u16, segments: &[u32], dynamic: &[(u32, u64)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Writer::new(Endianness::Little, true, &mut data);
+        writer.reserve_file_header();
+        writer.reserve_program_headers(segments.len() as u32);
+        writer.add_dynamic_string(b"libc.so.6");
+        writer.reserve_null_section_index();
+        writer.reserve_dynstr_section_index();
+        writer.reserve_dynamic_section_index();
+        writer.reserve_shstrtab_section_index();
+        writer.reserve_dynstr();
+        writer.reserve_dynamic(dynamic.len() + 1);
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&FileHeader {
+                os_abi: 0,
+                abi_version: 0,
+                e_type,
+                e_machine: elf::EM_X86_64,
+                e_entry: 0,
+                e_flags: 0,
+            })
+            .unwrap();
+        for &p_type in segments {
+            writer.write_program_header(&ProgramHeader {
+                p_type,
+                p_flags: elf::PF_R | elf::PF_W,
+                p_offset: 0,
+                p_vaddr: 0,
+                p_paddr: 0,
+                p_filesz: 0,
+                p_memsz: 0,
+                p_align: 1,
+            });
+        }
+        writer.write_dynstr();
+        writer.write_align_dynamic();
+        for &(tag, val) in dynamic {
+            writer.write_dynamic(tag, val);
+        }
+        writer.write_dynamic(elf::DT_NULL, 0);
+        writer.write_shstrtab();
+        writer.write_null_section_header();
+        writer.write_dynstr_section_header(0);
+        writer.write_dynamic_section_header(0);
+        writer.write_shstrtab_section_header();
+        data
+    }
+
+    fn statuses(data: &[u8]) -> Vec<(&'static str, Status)> {
+        let obj = object::File::parse(data).unwrap();
+        mitigations(&obj).into_iter().map(|m| (m.name, m.status)).collect()
+    }
+
+    #[test]
+    fn hardened_elf() {
+        let segments = [elf::PT_INTERP, elf::PT_GNU_STACK, elf::PT_GNU_RELRO];
+        // libc.so.6 is the first string after the empty one.
+        let dynamic = [(elf::DT_NEEDED, 1), (elf::DT_FLAGS, elf::DF_BIND_NOW as u64)];
+        let data = elf(elf::ET_DYN, &segments, &dynamic);
+        let obj = object::File::parse(&*data).unwrap();
+        assert_eq!(crate::libraries(&obj), ["libc.so.6"]);
+        assert_eq!(placement(&obj), Placement::Executable);
+        assert_eq!(
+            statuses(&data)[..3],
+            [("PIE", Status::Enabled), ("NX", Status::Enabled), ("RELRO", Status::Enabled)]
+        );
+    }
+
+    #[test]
+    fn lazy_binding() {
+        // Without immediate binding the GOT stays writable.
+        let data = elf(elf::ET_DYN, &[elf::PT_GNU_STACK, elf::PT_GNU_RELRO], &[]);
+        assert_eq!(placement(&object::File::parse(&*data).unwrap()), Placement::Library);
+        assert_eq!(statuses(&data)[2], ("RELRO", Status::Partial));
+    }
+
+    #[test]
+    fn executable_stack() {
+        // No PT_GNU_STACK means the stack is executable.
+        let data = elf(elf::ET_EXEC, &[], &[]);
+        assert_eq!(placement(&object::File::parse(&*data).unwrap()), Placement::Fixed);
+        assert_eq!(
+            statuses(&data)[..3],
+            [("PIE", Status::Disabled), ("NX", Status::Disabled), ("RELRO", Status::Disabled)]
+        );
+    }
+}
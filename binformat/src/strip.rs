@@ -0,0 +1,452 @@
+//! Removing the symbol table and debug info of executables and shared libraries, similar to
+//! `strip`, optionally keeping the symbols selected by the caller.
+//!
+//! Everything that's loaded at runtime is copied byte for byte, only the sections that aren't
+//! loaded are laid out again after it, followed by a new section header table. Relocatable
+//! objects aren't supported as their relocations refer to symbols by index.
+
+use crate::edit::{align, patch_u16, patch_u32, patch_word};
+use object::elf;
+use object::read::elf::{ElfFile, FileHeader, ProgramHeader, SectionHeader, Sym};
+use object::Endianness;
+use std::collections::HashMap;
+
+/// Which symbols of the symbol table are kept, the dynamic symbols are always kept.
+pub enum Symbols<'a> {
+    All,
+    None,
+    Matching(&'a dyn Fn(&str) -> bool),
+}
+
+/// What was removed from an object.
+pub struct Stripped {
+    pub data: Vec<u8>,
+    pub symbols_kept: usize,
+    pub symbols_total: usize,
+    pub sections_removed: usize,
+}
+
+fn is_debug_section(name: &[u8]) -> bool {
+    name.starts_with(b".debug") || name.starts_with(b".zdebug") || name == b".gdb_index"
+}
+
+/// Symbol table and string table containing only the symbols that are kept.
+struct SymbolTable {
+    symbols: Vec<u8>,
+    strings: Vec<u8>,
+    locals: u32,
+    kept: usize,
+    total: usize,
+}
+
+fn filter_symbols<Elf: FileHeader<Endian = Endianness>>(
+    obj: &ElfFile<Elf>,
+    symtab: &object::read::elf::SymbolTable<Elf>,
+    section_map: &[Option<u32>],
+    symbols: &Symbols,
+) -> Result<SymbolTable, String> {
+    let endian = obj.endian();
+    let is_64 = obj.raw_header().is_type_64();
+    let shndx_offset = if is_64 { 6 } else { 14 };
+
+    let mut table = SymbolTable {
+        symbols: Vec::new(),
+        strings: vec![0],
+        locals: 0,
+        kept: 0,
+        total: symtab.len().saturating_sub(1),
+    };
+
+    let mut offsets: HashMap<&[u8], u32> = HashMap::new();
+    for (idx, sym) in symtab.iter().enumerate() {
+        let mut raw = object::bytes_of(sym).to_vec();
+
+        if idx != 0 {
+            let name = sym.name(endian, symtab.strings()).unwrap_or_default();
+            let keep = match symbols {
+                Symbols::All => true,
+                Symbols::None => false,
+                Symbols::Matching(pred) => pred(&String::from_utf8_lossy(name)),
+            };
+
+            let shndx = sym.st_shndx(endian);
+            let shndx = if shndx != elf::SHN_UNDEF && shndx < elf::SHN_LORESERVE {
+                // Symbols of sections that are removed go with them.
+                match section_map.get(shndx as usize).copied().flatten() {
+                    Some(shndx) => shndx as u16,
+                    None => continue,
+                }
+            } else {
+                shndx
+            };
+
+            if !keep {
+                continue;
+            }
+
+            let offset = match offsets.get(name) {
+                Some(&offset) => offset,
+                None if name.is_empty() => 0,
+                None => {
+                    let offset = table.strings.len() as u32;
+                    table.strings.extend_from_slice(name);
+                    table.strings.push(0);
+                    offsets.insert(name, offset);
+                    offset
+                }
+            };
+
+            patch_u32(&mut raw, 0, offset, endian);
+            patch_u16(&mut raw, shndx_offset, shndx, endian);
+            table.kept += 1;
+        }
+
+        if sym.st_bind() == elf::STB_LOCAL {
+            if table.locals as usize * raw.len() != table.symbols.len() {
+                return Err("Local symbols must precede the global symbols.".to_string());
+            }
+            table.locals += 1;
+        }
+
+        table.symbols.extend_from_slice(&raw);
+    }
+
+    Ok(table)
+}
+
+fn strip_elf<Elf: FileHeader<Endian = Endianness>>(
+    obj: &ElfFile<Elf>,
+    symbols: Symbols,
+) -> Result<Stripped, String> {
+    let endian = obj.endian();
+    let data = obj.data();
+    let header = obj.raw_header();
+    let is_64 = header.is_type_64();
+
+    match header.e_type(endian) {
+        elf::ET_EXEC | elf::ET_DYN => {}
+        _ => return Err("Only executables and shared libraries can be stripped.".to_string()),
+    }
+
+    let sections = header
+        .sections(endian, data)
+        .map_err(|err| format!("Failed to read section headers: {err}."))?;
+    let shstrndx = header
+        .shstrndx(endian, data)
+        .map_err(|err| format!("Failed to read section headers: {err}."))?;
+
+    if sections.iter().any(|s| s.sh_type(endian) == elf::SHT_SYMTAB_SHNDX) {
+        return Err("Objects with extended section indices aren't supported.".to_string());
+    }
+
+    let symtab = sections
+        .symbols(endian, data, elf::SHT_SYMTAB)
+        .map_err(|err| format!("Failed to read symbol table: {err}."))?;
+    let symtab_idx = symtab.section().0;
+    let strtab_idx = symtab.string_section().0;
+    let has_symtab = symtab_idx != 0;
+
+    if has_symtab && strtab_idx == shstrndx as usize {
+        return Err("Symbol names shared with the section names aren't supported.".to_string());
+    }
+
+    let mut removed = vec![false; sections.len()];
+    for (idx, section) in sections.iter().enumerate() {
+        let name = sections.section_name(endian, section).unwrap_or_default();
+        removed[idx] = is_debug_section(name)
+            || (has_symtab
+                && matches!(symbols, Symbols::None)
+                && (idx == symtab_idx || idx == strtab_idx));
+    }
+
+    // The dynamic symbols refer to loaded sections by index, which therefore can't move.
+    let last_alloc = sections
+        .iter()
+        .rposition(|s| s.sh_flags(endian).into() & elf::SHF_ALLOC as u64 != 0)
+        .unwrap_or(0);
+    if removed[..last_alloc].iter().any(|&removed| removed) {
+        return Err("Sections that are removed must follow the loaded sections.".to_string());
+    }
+
+    let mut section_map = Vec::with_capacity(sections.len());
+    let mut next = 0;
+    for &removed in removed.iter() {
+        section_map.push((!removed).then_some(next));
+        next += !removed as u32;
+    }
+
+    let table = if has_symtab && !removed[symtab_idx] {
+        Some(filter_symbols(obj, &symtab, &section_map, &symbols)?)
+    } else {
+        None
+    };
+
+    // Everything that's loaded or part of the file header stays where it is.
+    let mut end = header.e_phoff(endian).into()
+        + header.e_phnum(endian) as u64 * header.e_phentsize(endian) as u64;
+    end = end.max(std::mem::size_of::<Elf>() as u64);
+    for segment in obj.raw_segments() {
+        end = end.max(segment.p_offset(endian).into() + segment.p_filesz(endian).into());
+    }
+    for section in sections.iter() {
+        let alloc = section.sh_flags(endian).into() & elf::SHF_ALLOC as u64 != 0;
+        if alloc && section.sh_type(endian) != elf::SHT_NOBITS {
+            end = end.max(section.sh_offset(endian).into() + section.sh_size(endian).into());
+        }
+    }
+
+    let mut out = data
+        .get(..end as usize)
+        .ok_or_else(|| "Segments extend past the end of the object.".to_string())?
+        .to_vec();
+
+    let (offset_at, size_at, link_at, info_at) = if is_64 {
+        (0x18, 0x20, 0x28, 0x2c)
+    } else {
+        (0x10, 0x14, 0x18, 0x1c)
+    };
+
+    let mut headers = Vec::new();
+    for (idx, section) in sections.iter().enumerate() {
+        if removed[idx] {
+            continue;
+        }
+
+        let mut raw = object::bytes_of(section).to_vec();
+        let flags = section.sh_flags(endian).into();
+        let kind = section.sh_type(endian);
+
+        if idx != 0 && flags & elf::SHF_ALLOC as u64 == 0 {
+            let contents = match (&table, kind) {
+                (Some(table), _) if idx == symtab_idx => &table.symbols[..],
+                (Some(table), _) if idx == strtab_idx => &table.strings[..],
+                (_, elf::SHT_NOBITS) => &[][..],
+                _ => section
+                    .data(endian, data)
+                    .map_err(|err| format!("Failed to read section #{idx}: {err}."))?,
+            };
+
+            align(&mut out, section.sh_addralign(endian).into());
+            patch_word(&mut raw, offset_at, out.len() as u64, endian, is_64);
+            if kind != elf::SHT_NOBITS {
+                patch_word(&mut raw, size_at, contents.len() as u64, endian, is_64);
+            }
+            out.extend_from_slice(contents);
+        }
+
+        let link = section.sh_link(endian) as usize;
+        patch_u32(
+            &mut raw,
+            link_at,
+            section_map.get(link).copied().flatten().unwrap_or(0),
+            endian,
+        );
+
+        let info = section.sh_info(endian);
+        if idx == symtab_idx {
+            if let Some(ref table) = table {
+                patch_u32(&mut raw, info_at, table.locals, endian);
+            }
+        } else if matches!(kind, elf::SHT_REL | elf::SHT_RELA)
+            || flags & elf::SHF_INFO_LINK as u64 != 0
+        {
+            let info = section_map.get(info as usize).copied().flatten().unwrap_or(0);
+            patch_u32(&mut raw, info_at, info, endian);
+        }
+
+        headers.extend_from_slice(&raw);
+    }
+
+    align(&mut out, if is_64 { 8 } else { 4 });
+    let shoff = out.len() as u64;
+    out.extend_from_slice(&headers);
+
+    let (shoff_at, shnum_at, shstrndx_at) = if is_64 {
+        (0x28, 0x3c, 0x3e)
+    } else {
+        (0x20, 0x30, 0x32)
+    };
+    let shstrndx = section_map.get(shstrndx as usize).copied().flatten().unwrap_or(0);
+    patch_word(&mut out, shoff_at, shoff, endian, is_64);
+    patch_u16(&mut out, shnum_at, next as u16, endian);
+    patch_u16(&mut out, shstrndx_at, shstrndx as u16, endian);
+
+    let (symbols_kept, symbols_total) = match table {
+        Some(table) => (table.kept, table.total),
+        None => (0, symtab.len().saturating_sub(1)),
+    };
+
+    Ok(Stripped {
+        data: out,
+        symbols_kept,
+        symbols_total,
+        sections_removed: removed.iter().filter(|&&removed| removed).count(),
+    })
+}
+
+/// Remove the debug sections of an ELF object, together with the symbols that aren't kept.
+pub fn strip(obj: &object::File, symbols: Symbols) -> Result<Stripped, String> {
+    match obj {
+        object::File::Elf32(elf) => strip_elf(elf, symbols),
+        object::File::Elf64(elf) => strip_elf(elf, symbols),
+        _ => Err("Only ELF objects can be stripped.".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::elf::{SectionHeader, Sym, Writer};
+    use object::{Object, ObjectSection, ObjectSymbol};
+
+    /// Executable with a loaded `.text`, debug info and the symbols `local`, `keep` and `drop`.
+    fn executable() -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Writer::new(Endianness::Little, true, &mut data);
+        writer.reserve_file_header();
+        writer.reserve_program_headers(1);
+
+        writer.reserve_null_section_index();
+        let text_name = writer.add_section_name(b".text");
+        let text = writer.reserve_section_index();
+        writer.reserve_symtab_section_index();
+        writer.reserve_strtab_section_index();
+        let debug_name = writer.add_section_name(b".debug_info");
+        writer.reserve_section_index();
+        writer.reserve_shstrtab_section_index();
+
+        let names = [b"local".as_slice(), b"keep", b"drop"].map(|name| writer.add_string(name));
+        writer.reserve_null_symbol_index();
+        for _ in names {
+            writer.reserve_symbol_index(Some(text));
+        }
+
+        let text_offset = writer.reserve(16, 16);
+        let debug_offset = writer.reserve(8, 1);
+        writer.reserve_symtab();
+        writer.reserve_strtab();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&object::write::elf::FileHeader {
+                os_abi: 0,
+                abi_version: 0,
+                e_type: elf::ET_EXEC,
+                e_machine: elf::EM_X86_64,
+                e_entry: 0x401000,
+                e_flags: 0,
+            })
+            .unwrap();
+        writer.write_program_header(&object::write::elf::ProgramHeader {
+            p_type: elf::PT_LOAD,
+            p_flags: elf::PF_R | elf::PF_X,
+            p_offset: text_offset as u64,
+            p_vaddr: 0x401000,
+            p_paddr: 0x401000,
+            p_filesz: 16,
+            p_memsz: 16,
+            p_align: 16,
+        });
+        writer.write_align(16);
+        writer.write(&[0xc3; 16]);
+        writer.write(&[0xde; 8]);
+
+        writer.write_null_symbol();
+        for (idx, name) in names.into_iter().enumerate() {
+            let bind = if idx == 0 { elf::STB_LOCAL } else { elf::STB_GLOBAL };
+            writer.write_symbol(&Sym {
+                name: Some(name),
+                section: Some(text),
+                st_info: bind << 4 | elf::STT_FUNC,
+                st_other: 0,
+                st_shndx: 0,
+                st_value: 0x401000 + idx as u64 * 4,
+                st_size: 4,
+            });
+        }
+        writer.write_strtab();
+        writer.write_shstrtab();
+
+        writer.write_null_section_header();
+        writer.write_section_header(&SectionHeader {
+            name: Some(text_name),
+            sh_type: elf::SHT_PROGBITS,
+            sh_flags: (elf::SHF_ALLOC | elf::SHF_EXECINSTR) as u64,
+            sh_addr: 0x401000,
+            sh_offset: text_offset as u64,
+            sh_size: 16,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 16,
+            sh_entsize: 0,
+        });
+        writer.write_symtab_section_header(2);
+        writer.write_strtab_section_header();
+        writer.write_section_header(&SectionHeader {
+            name: Some(debug_name),
+            sh_type: elf::SHT_PROGBITS,
+            sh_flags: 0,
+            sh_addr: 0,
+            sh_offset: debug_offset as u64,
+            sh_size: 8,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 1,
+            sh_entsize: 0,
+        });
+        writer.write_shstrtab_section_header();
+        data
+    }
+
+    fn symbols(data: &[u8]) -> Vec<String> {
+        let obj = object::File::parse(data).unwrap();
+        obj.symbols()
+            .map(|sym| sym.name().unwrap().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn keep_matching() {
+        let data = executable();
+        let obj = object::File::parse(&*data).unwrap();
+        let text_range = obj.section_by_name(".text").unwrap().file_range();
+        let keep = |name: &str| name == "local" || name == "keep";
+        let stripped = strip(&obj, Symbols::Matching(&keep)).unwrap();
+
+        assert_eq!(symbols(&data), ["local", "keep", "drop"]);
+        assert_eq!((stripped.symbols_kept, stripped.symbols_total), (2, 3));
+        assert_eq!(stripped.sections_removed, 1);
+        assert_eq!(symbols(&stripped.data), ["local", "keep"]);
+
+        // The loaded code stays byte for byte where it was.
+        let obj = object::File::parse(&*stripped.data).unwrap();
+        assert!(obj.section_by_name(".debug_info").is_none());
+        let text = obj.section_by_name(".text").unwrap();
+        assert_eq!(text.data().unwrap(), [0xc3; 16]);
+        assert_eq!(text.file_range(), text_range);
+    }
+
+    #[test]
+    fn strip_all() {
+        let data = executable();
+        let obj = object::File::parse(&*data).unwrap();
+        let stripped = strip(&obj, Symbols::None).unwrap();
+
+        assert_eq!(stripped.sections_removed, 3);
+        let obj = object::File::parse(&*stripped.data).unwrap();
+        assert!(obj.symbol_table().is_none());
+        assert_eq!(obj.entry(), 0x401000);
+    }
+
+    #[test]
+    fn relocatable() {
+        let mut data = executable();
+        // e_type of a relocatable object.
+        data[0x10] = elf::ET_REL as u8;
+        let obj = object::File::parse(&*data).unwrap();
+        let err = strip(&obj, Symbols::All).err().unwrap();
+        assert_eq!(err, "Only executables and shared libraries can be stripped.");
+    }
+}
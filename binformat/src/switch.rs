@@ -0,0 +1,328 @@
+//! Nintendo Switch executables, NSO for system modules and NRO for homebrew.
+//!
+//! Both hold the text, rodata and data segments of an AArch64 module together with its build-id.
+//! Segments of an NSO are usually LZ4 compressed, whereas an NRO is laid out in the file just
+//! like it is in memory. The dynamic symbol table is found through a pointer in the header into
+//! the rodata segment.
+
+const NSO_MAGIC: &[u8] = b"NSO0";
+const NRO_MAGIC: &[u8] = b"NRO0";
+
+/// Offset of the magic in an NRO, which starts with a branch to the module's code instead.
+const NRO_MAGIC_OFFSET: usize = 0x10;
+
+/// Size of an `Elf64_Sym`.
+const SYM_SIZE: usize = 24;
+
+const STT_FUNC: u8 = 2;
+
+/// Largest memory of an NSO that's loaded, far beyond any real module, such that the offsets
+/// and sizes of a crafted header can't make it allocate gigabytes.
+const MAX_MEMORY: usize = 512 << 20;
+
+pub struct Segment {
+    pub name: &'static str,
+    /// Offset of the segment into the module's memory, which is also its address.
+    pub offset: usize,
+    pub size: usize,
+    pub code: bool,
+}
+
+pub struct Symbol {
+    pub name: String,
+    pub addr: u64,
+}
+
+pub struct Module {
+    /// Either "NSO" or "NRO".
+    pub format: &'static str,
+    /// Identifies the build, as used to match crash reports and patches to it.
+    pub build_id: Vec<u8>,
+    /// Decompressed segments at their offsets, which starts with the entry point.
+    pub memory: Vec<u8>,
+    pub segments: Vec<Segment>,
+    /// Functions of the dynamic symbol table.
+    pub symbols: Vec<Symbol>,
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<usize, String> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+        .ok_or_else(|| "Header is truncated.".to_string())
+}
+
+/// Whether `data` is an NSO or NRO module.
+pub fn is_module(data: &[u8]) -> bool {
+    data.starts_with(NSO_MAGIC)
+        || data.get(NRO_MAGIC_OFFSET..NRO_MAGIC_OFFSET + 4) == Some(NRO_MAGIC)
+}
+
+/// Decompress an LZ4 block of `size` bytes, without the framing of the LZ4 file format.
+pub fn lz4_decompress(input: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    fn length(input: &[u8], pos: &mut usize, mut len: usize) -> Option<usize> {
+        if len == 15 {
+            loop {
+                let byte = *input.get(*pos)?;
+                *pos += 1;
+                len += byte as usize;
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Some(len)
+    }
+
+    let corrupt = || "Compressed segment is corrupt.".to_string();
+    let mut out = Vec::with_capacity(size);
+    let mut pos = 0;
+
+    while pos < input.len() {
+        let token = input[pos];
+        pos += 1;
+
+        let literals = length(input, &mut pos, (token >> 4) as usize).ok_or_else(corrupt)?;
+        let literals = input.get(pos..pos + literals).ok_or_else(corrupt)?;
+        if out.len() + literals.len() > size {
+            return Err(corrupt());
+        }
+        out.extend_from_slice(literals);
+        pos += literals.len();
+
+        // The last sequence only has literals.
+        if pos == input.len() {
+            break;
+        }
+
+        let offset = input.get(pos..pos + 2).ok_or_else(corrupt)?;
+        let offset = u16::from_le_bytes([offset[0], offset[1]]) as usize;
+        pos += 2;
+
+        let len = length(input, &mut pos, (token & 0xf) as usize).ok_or_else(corrupt)? + 4;
+        if offset == 0 || offset > out.len() || out.len() + len > size {
+            return Err(corrupt());
+        }
+
+        // Matches can overlap what they copy, so they're copied a byte at a time.
+        let start = out.len() - offset;
+        for idx in start..start + len {
+            out.push(out[idx]);
+        }
+    }
+
+    if out.len() != size {
+        return Err(corrupt());
+    }
+
+    Ok(out)
+}
+
+/// Functions in the `Elf64_Sym` entries of `dynsym`, named from `dynstr`.
+fn symbols(memory: &[u8], dynsym: (usize, usize), dynstr: (usize, usize)) -> Vec<Symbol> {
+    let (Some(dynsym), Some(dynstr)) = (
+        memory.get(dynsym.0..dynsym.0 + dynsym.1),
+        memory.get(dynstr.0..dynstr.0 + dynstr.1),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut symbols = Vec::new();
+    for sym in dynsym.chunks_exact(SYM_SIZE) {
+        let name = u32::from_le_bytes(sym[0..4].try_into().unwrap()) as usize;
+        let kind = sym[4] & 0xf;
+        let section = u16::from_le_bytes([sym[6], sym[7]]);
+        let addr = u64::from_le_bytes(sym[8..16].try_into().unwrap());
+
+        // Imports are undefined and resolved by the loader.
+        if kind != STT_FUNC || section == 0 {
+            continue;
+        }
+
+        let Some(name) = dynstr.get(name..).and_then(|name| name.split(|&b| b == 0).next())
+        else {
+            continue;
+        };
+
+        if let Ok(name) = std::str::from_utf8(name) {
+            symbols.push(Symbol {
+                name: name.to_string(),
+                addr,
+            });
+        }
+    }
+
+    symbols
+}
+
+fn build_id(id: &[u8]) -> Vec<u8> {
+    // The id has room for any hash, but only the first 20 bytes are used by a GNU build-id.
+    let len = id.iter().rposition(|&b| b != 0).map_or(0, |idx| idx + 1);
+    id[..len].to_vec()
+}
+
+fn nso(data: &[u8]) -> Result<Module, String> {
+    let flags = u32_at(data, 0xc)?;
+    let build_id = build_id(data.get(0x40..0x60).ok_or("Header is truncated.")?);
+
+    let mut memory = Vec::new();
+    let mut segments = Vec::new();
+    for (idx, name) in ["text", "rodata", "data"].into_iter().enumerate() {
+        let header = 0x10 + idx * 0x10;
+        let file_offset = u32_at(data, header)?;
+        let offset = u32_at(data, header + 4)?;
+        let size = u32_at(data, header + 8)?;
+        let file_size = u32_at(data, 0x60 + idx * 4)?;
+        if offset + size > MAX_MEMORY {
+            return Err(format!("Segment '{name}' is too large."));
+        }
+
+        let compressed = flags & (1 << idx) != 0;
+        let stored = if compressed { file_size } else { size };
+        let bytes = data
+            .get(file_offset..file_offset + stored)
+            .ok_or_else(|| format!("Segment '{name}' lies outside of the module."))?;
+
+        let bytes = if compressed {
+            lz4_decompress(bytes, size).map_err(|err| format!("Segment '{name}': {err}"))?
+        } else {
+            bytes.to_vec()
+        };
+
+        if memory.len() < offset + size {
+            memory.resize(offset + size, 0);
+        }
+        memory[offset..offset + size].copy_from_slice(&bytes);
+        segments.push(Segment {
+            name,
+            offset,
+            size,
+            code: idx == 0,
+        });
+    }
+
+    // Tables are relative to the start of rodata.
+    let rodata = segments[1].offset;
+    let dynstr = (rodata + u32_at(data, 0x90)?, u32_at(data, 0x94)?);
+    let dynsym = (rodata + u32_at(data, 0x98)?, u32_at(data, 0x9c)?);
+
+    Ok(Module {
+        format: "NSO",
+        build_id,
+        symbols: symbols(&memory, dynsym, dynstr),
+        memory,
+        segments,
+    })
+}
+
+fn nro(data: &[u8]) -> Result<Module, String> {
+    let size = u32_at(data, 0x18)?;
+    let memory = data.get(..size).ok_or("Module is truncated.")?.to_vec();
+    let build_id = build_id(data.get(0x40..0x60).ok_or("Header is truncated.")?);
+
+    let mut segments = Vec::new();
+    for (idx, name) in ["text", "rodata", "data"].into_iter().enumerate() {
+        let offset = u32_at(data, 0x20 + idx * 8)?;
+        let size = u32_at(data, 0x24 + idx * 8)?;
+        if offset + size > memory.len() {
+            return Err(format!("Segment '{name}' lies outside of the module."));
+        }
+
+        segments.push(Segment {
+            name,
+            offset,
+            size,
+            code: idx == 0,
+        });
+    }
+
+    let rodata = segments[1].offset;
+    let dynstr = (rodata + u32_at(data, 0x70)?, u32_at(data, 0x74)?);
+    let dynsym = (rodata + u32_at(data, 0x78)?, u32_at(data, 0x7c)?);
+
+    Ok(Module {
+        format: "NRO",
+        build_id,
+        symbols: symbols(&memory, dynsym, dynstr),
+        memory,
+        segments,
+    })
+}
+
+/// Parse an NSO or NRO module, decompressing its segments into memory.
+pub fn parse(data: &[u8]) -> Result<Module, String> {
+    if data.starts_with(NSO_MAGIC) {
+        nso(data)
+    } else if is_module(data) {
+        nro(data)
+    } else {
+        Err("Object isn't an NSO or NRO module.".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lz4() {
+        // Literals "abc", a match of 6 bytes at offset 3 and the trailing literal "d".
+        let input = [0x32, b'a', b'b', b'c', 0x03, 0x00, 0x10, b'd'];
+        assert_eq!(lz4_decompress(&input, 10).unwrap(), b"abcabcabcd");
+        assert!(lz4_decompress(&input, 11).is_err());
+        assert!(lz4_decompress(&[0x02, b'a', 0x05, 0x00], 6).is_err());
+
+        // A match far longer than the block.
+        let input = [0x1f, b'a', 0x01, 0x00, 0xff, 0xff, 0xff, 0x00];
+        assert!(lz4_decompress(&input, 16).is_err());
+    }
+
+    #[test]
+    fn nso_segments() {
+        let mut data = vec![0; 0x100];
+        data[..4].copy_from_slice(NSO_MAGIC);
+        // Only text is compressed.
+        data[0xc] = 1;
+        let segments = [(0x100u32, 0u32, 10u32, 8u32), (0x108, 0x10, 4, 4), (0x10c, 0x20, 4, 4)];
+        for (idx, (file_offset, offset, size, file_size)) in segments.into_iter().enumerate() {
+            let header = 0x10 + idx * 0x10;
+            data[header..header + 4].copy_from_slice(&file_offset.to_le_bytes());
+            data[header + 4..header + 8].copy_from_slice(&offset.to_le_bytes());
+            data[header + 8..header + 12].copy_from_slice(&size.to_le_bytes());
+            data[0x60 + idx * 4..0x64 + idx * 4].copy_from_slice(&file_size.to_le_bytes());
+        }
+        data[0x40] = 0xcd;
+        data.extend_from_slice(&[0x32, b'a', b'b', b'c', 0x03, 0x00, 0x10, b'd']);
+        data.extend_from_slice(b"rodtdata");
+
+        let module = parse(&data).unwrap();
+        assert_eq!(module.format, "NSO");
+        assert_eq!(module.build_id, [0xcd]);
+        assert_eq!(module.memory.len(), 0x24);
+        assert_eq!(&module.memory[..10], b"abcabcabcd");
+        assert_eq!(&module.memory[0x10..0x14], b"rodt");
+        assert_eq!(&module.memory[0x20..], b"data");
+
+        // Data placed gigabytes past the rest of the module.
+        data[0x34..0x38].copy_from_slice(&0xffff_0000u32.to_le_bytes());
+        assert!(parse(&data).is_err());
+    }
+
+    #[test]
+    fn nro_segments() {
+        let mut data = vec![0; 0x100];
+        data[0x10..0x14].copy_from_slice(NRO_MAGIC);
+        data[0x18..0x1c].copy_from_slice(&0x100u32.to_le_bytes());
+        for (idx, offset) in [0x80u32, 0xc0, 0xe0].into_iter().enumerate() {
+            data[0x20 + idx * 8..0x24 + idx * 8].copy_from_slice(&offset.to_le_bytes());
+            data[0x24 + idx * 8..0x28 + idx * 8].copy_from_slice(&0x20u32.to_le_bytes());
+        }
+        data[0x40] = 0xab;
+
+        assert!(is_module(&data));
+        let module = parse(&data).unwrap();
+        assert_eq!(module.format, "NRO");
+        assert_eq!(module.build_id, [0xab]);
+        assert_eq!(module.segments[1].offset, 0xc0);
+        assert!(module.segments[0].code);
+    }
+}
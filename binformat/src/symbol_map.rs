@@ -0,0 +1,184 @@
+//! Symbol maps naming the functions of a stripped object, as kept next to it by reverse engineers.
+//!
+//! Plain maps have a symbol per line, either as `<address> <name>` or as `<name> <address>` the
+//! way scripts exporting from Ghidra tend to write them. Lines starting with `#` or `;` are
+//! comments.
+//!
+//! `.map` files written by IDA or the MSVC linker are also understood. Only their "Publics by
+//! Value" part names symbols, by `<segment>:<offset>` where the segment is the 1-based index of a
+//! section, e.g. ` 0001:00000140       _main`. The linker follows that with the virtual address.
+
+/// Header of the part of a `.map` file that lists its symbols.
+const PUBLICS: &str = "Publics by Value";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MapAddr {
+    Absolute(u64),
+    /// Offset into the section with the given 1-based index.
+    Segment { segment: usize, offset: u64 },
+}
+
+pub struct MapSymbol<'data> {
+    pub addr: MapAddr,
+    pub name: &'data str,
+}
+
+/// Hex number with an optional `0x` prefix or `h` suffix.
+fn hex(word: &str) -> Option<u64> {
+    let digits = word
+        .strip_prefix("0x")
+        .or_else(|| word.strip_prefix("0X"))
+        .or_else(|| word.strip_suffix(['h', 'H']))
+        .unwrap_or(word);
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Address of the form `<segment>:<offset>`, both in hex.
+fn segmented(word: &str) -> Option<MapAddr> {
+    let (segment, offset) = word.split_once(':')?;
+    Some(MapAddr::Segment {
+        segment: usize::try_from(hex(segment)?).ok()?,
+        offset: hex(offset)?,
+    })
+}
+
+/// Symbol on a line of the "Publics by Value" part of a `.map` file.
+///
+/// IDA only writes the name, which may contain spaces when it's demangled. The MSVC linker writes
+/// a mangled name followed by the virtual address, a flag for functions and the defining object.
+fn public(line: &str) -> Option<MapSymbol<'_>> {
+    let (addr, rest) = line.split_once(char::is_whitespace)?;
+    let addr = segmented(addr)?;
+    let rest = rest.trim();
+
+    let mut words = rest.split_whitespace();
+    if let (Some(name), Some(vaddr)) = (words.next(), words.next()) {
+        if let (Some(vaddr), Some(_)) = (hex(vaddr), words.next()) {
+            return Some(MapSymbol {
+                addr: MapAddr::Absolute(vaddr),
+                name,
+            });
+        }
+    }
+
+    (!rest.is_empty()).then_some(MapSymbol { addr, name: rest })
+}
+
+/// Symbol on a line of a plain map, with the address either before or after the name.
+fn plain(line: &str) -> Option<MapSymbol<'_>> {
+    let (first, rest) = line.split_once(char::is_whitespace)?;
+    if let Some(addr) = hex(first).map(MapAddr::Absolute).or_else(|| segmented(first)) {
+        return Some(MapSymbol {
+            addr,
+            name: rest.trim(),
+        });
+    }
+
+    let (rest, last) = line.rsplit_once(char::is_whitespace)?;
+    let addr = hex(last).map(MapAddr::Absolute).or_else(|| segmented(last))?;
+    Some(MapSymbol {
+        addr,
+        name: rest.trim(),
+    })
+}
+
+pub fn parse(map: &str) -> Vec<MapSymbol<'_>> {
+    let is_map_file = map.lines().any(|line| line.contains(PUBLICS));
+    let mut in_publics = false;
+    let mut syms = Vec::new();
+
+    for (idx, line) in map.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if is_map_file {
+            // The segment table before the publics also starts its lines with an address.
+            if line.contains(PUBLICS) {
+                in_publics = true;
+                continue;
+            }
+
+            // Lines that aren't symbols, like the entry point, are skipped without a warning.
+            if let Some(sym) = public(line).filter(|_| in_publics) {
+                syms.push(sym);
+            }
+
+            continue;
+        }
+
+        match plain(line) {
+            Some(sym) if !sym.name.is_empty() => syms.push(sym),
+            _ => log::complex!(
+                w "[symbol_map::parse] malformed line ",
+                g (idx + 1).to_string(),
+                w ".",
+            ),
+        }
+    }
+
+    syms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_map() {
+        let map = "# exported from ghidra\n\
+                   0x401000 main\n\
+                   00401020 parse_args(int, char**)\n\
+                   FUN_00401100 00401100\n";
+
+        let syms = parse(map);
+        assert_eq!(syms.len(), 3);
+        assert_eq!(syms[0].addr, MapAddr::Absolute(0x401000));
+        assert_eq!(syms[0].name, "main");
+        assert_eq!(syms[1].name, "parse_args(int, char**)");
+        assert_eq!(syms[2].addr, MapAddr::Absolute(0x401100));
+        assert_eq!(syms[2].name, "FUN_00401100");
+    }
+
+    #[test]
+    fn ida_map() {
+        let map = " Start         Length     Name                   Class\n\
+                   \x20\x200001:00000000 000004000H .text                  CODE\n\
+                   \n\
+                   \x20\x20Address         Publics by Value\n\
+                   \n\
+                   \x200001:00000140       _main\n\
+                   \x200001:000002A0       Foo::bar(int)\n\
+                   \n\
+                   Program entry point at 0001:000012A0\n";
+
+        let syms = parse(map);
+        assert_eq!(syms.len(), 2);
+        assert_eq!(
+            syms[0].addr,
+            MapAddr::Segment {
+                segment: 1,
+                offset: 0x140
+            }
+        );
+        assert_eq!(syms[1].name, "Foo::bar(int)");
+    }
+
+    #[test]
+    fn msvc_map() {
+        let map = "  Address         Publics by Value              Rva+Base       Lib:Object\n\
+                   \n\
+                   \x200001:00000140       _main                      00401140 f   main.obj\n";
+
+        let syms = parse(map);
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].addr, MapAddr::Absolute(0x401140));
+        assert_eq!(syms[0].name, "_main");
+    }
+}
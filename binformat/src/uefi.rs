@@ -0,0 +1,174 @@
+//! UEFI drivers and applications, either as PE32(+) objects or as Terse Executables.
+//!
+//! A Terse Executable (TE) is a PE object with its DOS stub and most of the PE header stripped,
+//! as found in firmware volumes. What's left is a small header followed by the PE section
+//! headers, which still describe the object as it was before stripping.
+
+use object::pe;
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader};
+use object::LittleEndian as LE;
+use object::ReadRef;
+
+/// Signature of a TE header, "VZ".
+const TE_SIGNATURE: u16 = 0x5a56;
+
+/// Size of the TE header, which replaces the first `stripped_size` bytes of the PE object.
+const TE_HEADER_SIZE: usize = 40;
+
+/// Kind of UEFI image a PE object's subsystem marks it as, `None` if it isn't one.
+pub fn subsystem(obj: &object::File) -> Option<&'static str> {
+    let subsystem = match obj {
+        object::File::Pe32(pe) => pe.nt_headers().optional_header().subsystem(),
+        object::File::Pe64(pe) => pe.nt_headers().optional_header().subsystem(),
+        _ => return None,
+    };
+
+    subsystem_name(subsystem)
+}
+
+fn subsystem_name(subsystem: u16) -> Option<&'static str> {
+    Some(match subsystem {
+        pe::IMAGE_SUBSYSTEM_EFI_APPLICATION => "UEFI application",
+        pe::IMAGE_SUBSYSTEM_EFI_BOOT_SERVICE_DRIVER => "UEFI boot service driver",
+        pe::IMAGE_SUBSYSTEM_EFI_RUNTIME_DRIVER => "UEFI runtime driver",
+        pe::IMAGE_SUBSYSTEM_EFI_ROM => "UEFI option ROM",
+        _ => return None,
+    })
+}
+
+pub struct TeSection {
+    pub name: String,
+    /// Virtual address the section is loaded at.
+    pub addr: u64,
+    /// Offset of the section into the TE image.
+    pub offset: usize,
+    pub size: usize,
+    pub code: bool,
+}
+
+pub struct TeImage {
+    /// Architecture by the name `--arch` takes.
+    pub arch: &'static str,
+    /// Kind of UEFI image, e.g. a boot service driver.
+    pub subsystem: Option<&'static str>,
+    /// Virtual address of the entry point.
+    pub entry: u64,
+    pub sections: Vec<TeSection>,
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Whether `data` starts with a TE header.
+pub fn is_te(data: &[u8]) -> bool {
+    data.len() >= TE_HEADER_SIZE && u16_at(data, 0) == TE_SIGNATURE
+}
+
+/// Parse a TE image, placing its sections where they're loaded.
+///
+/// Offsets in the section headers are still those of the PE object, so they're moved by the
+/// difference between the stripped part and the TE header that took its place.
+pub fn parse_te(data: &[u8]) -> Result<TeImage, String> {
+    if !is_te(data) {
+        return Err("Object isn't a TE image.".to_string());
+    }
+
+    let arch = match u16_at(data, 2) {
+        pe::IMAGE_FILE_MACHINE_I386 => "x86",
+        pe::IMAGE_FILE_MACHINE_AMD64 => "x86_64",
+        pe::IMAGE_FILE_MACHINE_ARM
+        | pe::IMAGE_FILE_MACHINE_THUMB
+        | pe::IMAGE_FILE_MACHINE_ARMNT => "arm",
+        pe::IMAGE_FILE_MACHINE_ARM64 => "aarch64",
+        pe::IMAGE_FILE_MACHINE_RISCV32 => "riscv32",
+        pe::IMAGE_FILE_MACHINE_RISCV64 => "riscv64",
+        machine => return Err(format!("TE image has unsupported machine {machine:#x}.")),
+    };
+
+    let section_count = data[4] as usize;
+    let subsystem = subsystem_name(data[5] as u16);
+    let stripped_size = u16_at(data, 6) as usize;
+    let entry = u32_at(data, 8) as u64;
+    let image_base = u64_at(data, 16);
+
+    let headers: &[pe::ImageSectionHeader] = data
+        .read_slice_at(TE_HEADER_SIZE as u64, section_count)
+        .map_err(|_| "TE image is too small for its section headers.".to_string())?;
+
+    let mut sections = Vec::new();
+    for header in headers {
+        let name = String::from_utf8_lossy(header.raw_name()).into_owned();
+        let raw_size = header.size_of_raw_data.get(LE) as usize;
+        let size = match header.virtual_size.get(LE) as usize {
+            0 => raw_size,
+            virtual_size => virtual_size.min(raw_size),
+        };
+
+        let offset = (header.pointer_to_raw_data.get(LE) as usize + TE_HEADER_SIZE)
+            .checked_sub(stripped_size)
+            .filter(|offset| offset + size <= data.len())
+            .ok_or_else(|| format!("Section '{name}' lies outside of the TE image."))?;
+
+        let characteristics = header.characteristics.get(LE);
+        sections.push(TeSection {
+            name,
+            addr: image_base + header.virtual_address.get(LE) as u64,
+            offset,
+            size,
+            code: characteristics & (pe::IMAGE_SCN_CNT_CODE | pe::IMAGE_SCN_MEM_EXECUTE) != 0,
+        });
+    }
+
+    Ok(TeImage {
+        arch,
+        subsystem,
+        entry: image_base + entry,
+        sections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terse_executable() {
+        // PE header of 0x1c8 bytes stripped down to a TE header, with a single .text section
+        // at file offset 0x200 in the PE object.
+        let mut data = vec![0; 0x60 + 4];
+        data[0..2].copy_from_slice(b"VZ");
+        data[2..4].copy_from_slice(&pe::IMAGE_FILE_MACHINE_AMD64.to_le_bytes());
+        data[4] = 1;
+        data[5] = pe::IMAGE_SUBSYSTEM_EFI_BOOT_SERVICE_DRIVER as u8;
+        data[6..8].copy_from_slice(&0x1c8u16.to_le_bytes());
+        data[8..12].copy_from_slice(&0x200u32.to_le_bytes());
+        data[16..24].copy_from_slice(&0xfff0_0000u64.to_le_bytes());
+
+        let section = &mut data[40..80];
+        section[0..5].copy_from_slice(b".text");
+        section[8..12].copy_from_slice(&4u32.to_le_bytes());
+        section[12..16].copy_from_slice(&0x200u32.to_le_bytes());
+        section[16..20].copy_from_slice(&4u32.to_le_bytes());
+        section[20..24].copy_from_slice(&0x200u32.to_le_bytes());
+        section[36..40].copy_from_slice(&pe::IMAGE_SCN_CNT_CODE.to_le_bytes());
+
+        let image = parse_te(&data).unwrap();
+        assert_eq!(image.arch, "x86_64");
+        assert_eq!(image.subsystem, Some("UEFI boot service driver"));
+        assert_eq!(image.entry, 0xfff0_0200);
+        assert_eq!(image.sections.len(), 1);
+        assert_eq!(image.sections[0].name, ".text");
+        assert_eq!(image.sections[0].addr, 0xfff0_0200);
+        assert_eq!(image.sections[0].offset, 0x60);
+        assert!(image.sections[0].code);
+    }
+}
@@ -0,0 +1,368 @@
+//! Unwind tables, `.eh_frame` of ELF and Mach-O objects and `.pdata` of x86_64 PE objects.
+//!
+//! Every function that can be unwound through has an entry, which makes them a list of
+//! function boundaries that survives stripping.
+
+use gimli::{
+    BaseAddresses, CfaRule, CieOrFde, EhFrame, EndianSlice, Register, RegisterRule, RunTimeEndian,
+    UnwindContext, UnwindSection,
+};
+use object::{Architecture, Object, ObjectSection};
+
+/// Where the rules of a function change.
+pub struct UnwindRow {
+    pub addr: u64,
+    /// Rules in effect from here on, e.g. `cfa=rsp+16 rbp=[cfa-16] ra=[cfa-8]` for DWARF or
+    /// the operation of the prolog for PE, e.g. `push rbp`.
+    pub rules: String,
+}
+
+pub struct UnwindFunction {
+    pub start: u64,
+    pub end: u64,
+    pub rows: Vec<UnwindRow>,
+}
+
+type Reader<'data> = EndianSlice<'data, RunTimeEndian>;
+
+/// Registers of x86_64 by the number used by `.pdata`.
+const PE_REGISTERS: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+
+fn register_name(arch: Architecture, reg: Register) -> String {
+    let name = match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 => gimli::X86_64::register_name(reg),
+        Architecture::I386 => gimli::X86::register_name(reg),
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => gimli::AArch64::register_name(reg),
+        Architecture::Arm => gimli::Arm::register_name(reg),
+        Architecture::Riscv32 | Architecture::Riscv64 => gimli::RiscV::register_name(reg),
+        _ => None,
+    };
+
+    match name {
+        Some(name) => name.to_string(),
+        None => format!("r{}", reg.0),
+    }
+}
+
+fn offset(offset: i64) -> String {
+    match offset {
+        0 => String::new(),
+        offset if offset < 0 => format!("-{:#x}", offset.unsigned_abs()),
+        offset => format!("+{offset:#x}"),
+    }
+}
+
+fn cfa_rule(arch: Architecture, rule: &CfaRule<Reader>) -> String {
+    match rule {
+        CfaRule::RegisterAndOffset {
+            register,
+            offset: off,
+        } => {
+            format!("cfa={}{}", register_name(arch, *register), offset(*off))
+        }
+        CfaRule::Expression(_) => "cfa=<expr>".to_string(),
+    }
+}
+
+fn register_rule(rule: &RegisterRule<Reader>, arch: Architecture) -> Option<String> {
+    Some(match rule {
+        RegisterRule::Undefined => "undefined".to_string(),
+        RegisterRule::SameValue => "same".to_string(),
+        RegisterRule::Offset(off) => format!("[cfa{}]", offset(*off)),
+        RegisterRule::ValOffset(off) => format!("cfa{}", offset(*off)),
+        RegisterRule::Register(reg) => register_name(arch, *reg),
+        RegisterRule::Expression(_) => "[<expr>]".to_string(),
+        RegisterRule::ValExpression(_) => "<expr>".to_string(),
+        RegisterRule::Constant(value) => format!("{value:#x}"),
+        _ => return None,
+    })
+}
+
+fn eh_frame(obj: &object::File, with_rows: bool) -> Result<Vec<UnwindFunction>, gimli::Error> {
+    let mut funcs = Vec::new();
+    let Some(section) =
+        obj.section_by_name(".eh_frame").or_else(|| obj.section_by_name("__eh_frame"))
+    else {
+        return Ok(funcs);
+    };
+
+    let endian = match obj.is_little_endian() {
+        true => RunTimeEndian::Little,
+        false => RunTimeEndian::Big,
+    };
+
+    let data = section.data().unwrap_or(&[]);
+    let mut eh_frame = EhFrame::new(data, endian);
+    if let Some(size) = obj.architecture().address_size() {
+        eh_frame.set_address_size(size.bytes());
+    }
+
+    let mut bases = BaseAddresses::default().set_eh_frame(section.address());
+    if let Some(text) = obj.section_by_name(".text").or_else(|| obj.section_by_name("__text")) {
+        bases = bases.set_text(text.address());
+    }
+    if let Some(hdr) = obj.section_by_name(".eh_frame_hdr") {
+        bases = bases.set_eh_frame_hdr(hdr.address());
+    }
+
+    let arch = obj.architecture();
+    let mut ctx = Box::new(UnwindContext::new());
+    let mut entries = eh_frame.entries(&bases);
+
+    while let Some(entry) = entries.next()? {
+        let CieOrFde::Fde(partial) = entry else {
+            continue;
+        };
+
+        let fde = partial.parse(EhFrame::cie_from_offset)?;
+        let (start, len) = (fde.initial_address(), fde.len());
+        if len == 0 {
+            continue;
+        }
+
+        let mut rows = Vec::new();
+        if with_rows {
+            let ra = fde.cie().return_address_register();
+            let mut table = fde.rows(&eh_frame, &bases, &mut ctx)?;
+            while let Some(row) = table.next_row()? {
+                let mut rules = cfa_rule(arch, row.cfa());
+                for (reg, rule) in row.registers() {
+                    let Some(rule) = register_rule(rule, arch) else {
+                        continue;
+                    };
+
+                    let name = match *reg == ra {
+                        true => "ra".to_string(),
+                        false => register_name(arch, *reg),
+                    };
+                    rules += &format!(" {name}={rule}");
+                }
+
+                rows.push(UnwindRow {
+                    addr: row.start_address(),
+                    rules,
+                });
+            }
+        }
+
+        funcs.push(UnwindFunction {
+            start,
+            end: start + len,
+            rows,
+        });
+    }
+
+    Ok(funcs)
+}
+
+/// Decodes the prolog operations of an `UNWIND_INFO`, which are listed from last to first.
+fn pe_unwind_codes(info: &[u8], start: u64) -> Vec<UnwindRow> {
+    let mut rows = Vec::new();
+    let count = info.get(2).copied().unwrap_or(0) as usize;
+    let slots: Vec<u16> = (0..count)
+        .filter_map(|idx| info.get(4 + idx * 2..6 + idx * 2))
+        .map(|slot| u16::from_le_bytes([slot[0], slot[1]]))
+        .collect();
+
+    let frame = info.get(3).copied().unwrap_or(0);
+    let mut idx = 0;
+    while idx < slots.len() {
+        let [prolog_offset, op] = slots[idx].to_le_bytes();
+        let (code, op_info) = (op & 0xf, (op >> 4) as usize);
+        let slot = |n: usize| slots.get(idx + n).copied().unwrap_or(0) as u64;
+
+        let (rules, used) = match code {
+            0 => (format!("push {}", PE_REGISTERS[op_info]), 1),
+            1 if op_info == 0 => (format!("alloc {:#x}", slot(1) * 8), 2),
+            1 => (format!("alloc {:#x}", slot(1) | slot(2) << 16), 3),
+            2 => (format!("alloc {:#x}", op_info * 8 + 8), 1),
+            3 => {
+                let reg = PE_REGISTERS[(frame & 0xf) as usize];
+                (format!("{reg}=rsp+{:#x}", (frame >> 4) as u64 * 16), 1)
+            }
+            4 => (
+                format!("save {} at rsp+{:#x}", PE_REGISTERS[op_info], slot(1) * 8),
+                2,
+            ),
+            5 => (
+                format!(
+                    "save {} at rsp+{:#x}",
+                    PE_REGISTERS[op_info],
+                    slot(1) | slot(2) << 16
+                ),
+                3,
+            ),
+            8 => (format!("save xmm{op_info} at rsp+{:#x}", slot(1) * 16), 2),
+            9 => (
+                format!("save xmm{op_info} at rsp+{:#x}", slot(1) | slot(2) << 16),
+                3,
+            ),
+            10 => ("push machine frame".to_string(), 1),
+            _ => (format!("unknown operation {code}"), 1),
+        };
+
+        rows.push(UnwindRow {
+            addr: start + prolog_offset as u64,
+            rules,
+        });
+        idx += used;
+    }
+
+    rows.reverse();
+    rows
+}
+
+fn pdata(obj: &object::File, with_rows: bool) -> Vec<UnwindFunction> {
+    let mut funcs = Vec::new();
+    if obj.architecture() != Architecture::X86_64 {
+        return funcs;
+    }
+
+    let Some(pdata) = obj.section_by_name(".pdata").and_then(|s| s.data().ok()) else {
+        return funcs;
+    };
+
+    let base = obj.relative_address_base();
+    let read_rva = |rva: u64| {
+        let addr = base + rva;
+        let section =
+            obj.sections().find(|s| (s.address()..s.address() + s.size()).contains(&addr))?;
+        section.data().ok()?.get((addr - section.address()) as usize..)
+    };
+
+    // RUNTIME_FUNCTION { begin, end, unwind_info }
+    for entry in pdata.chunks_exact(12) {
+        let field =
+            |idx: usize| u32::from_le_bytes(entry[idx * 4..idx * 4 + 4].try_into().unwrap());
+        let (begin, end, info) = (field(0) as u64, field(1) as u64, field(2) as u64);
+        if begin == 0 || end <= begin {
+            continue;
+        }
+
+        let rows = match (with_rows, read_rva(info)) {
+            (true, Some(info)) => pe_unwind_codes(info, base + begin),
+            _ => Vec::new(),
+        };
+
+        funcs.push(UnwindFunction {
+            start: base + begin,
+            end: base + end,
+            rows,
+        });
+    }
+
+    funcs
+}
+
+/// Unwind rules of every function in the object, sorted by address.
+pub fn functions(obj: &object::File) -> Result<Vec<UnwindFunction>, String> {
+    let mut funcs =
+        eh_frame(obj, true).map_err(|err| format!("Failed to read .eh_frame: {err}."))?;
+    funcs.extend(pdata(obj, true));
+    funcs.sort_by_key(|func| func.start);
+    Ok(funcs)
+}
+
+/// Start of every function that has unwind rules, sorted and deduplicated.
+pub fn function_starts(obj: &object::File) -> Vec<u64> {
+    let mut starts: Vec<u64> =
+        eh_frame(obj, false).unwrap_or_default().iter().map(|f| f.start).collect();
+    starts.extend(pdata(obj, false).iter().map(|func| func.start));
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write;
+    use object::{BinaryFormat, Endianness, SectionKind};
+
+    /// Frame description entry of `len` bytes at `start`, `at` being its offset in `.eh_frame`.
+    fn fde(at: u32, start: u64, len: u64, insts: &[u8]) -> Vec<u8> {
+        let mut entry = Vec::new();
+        entry.extend((at + 4).to_le_bytes());
+        entry.extend(start.to_le_bytes());
+        entry.extend(len.to_le_bytes());
+        entry.push(0);
+        entry.extend(insts);
+        entry.resize(entry.len().next_multiple_of(4), 0);
+
+        let mut fde = (entry.len() as u32).to_le_bytes().to_vec();
+        fde.extend(entry);
+        fde
+    }
+
+    /// Object with an `.eh_frame` of absolute pointers, a CIE as emitted for x86_64 and FDEs of
+    /// `(start, len)`.
+    fn object(funcs: &[(u64, u64)]) -> Vec<u8> {
+        // "zR", code alignment 1, data alignment -8, ra in r16, absolute pointers
+        // cfa=rsp+8 ra=[cfa-8]
+        let mut eh_frame = vec![20, 0, 0, 0, 0, 0, 0, 0, 1, b'z', b'R', 0, 1, 0x78, 16, 1, 0];
+        eh_frame.extend([0x0c, 7, 8, 0x90, 1, 0, 0]);
+
+        for &(start, len) in funcs {
+            // advance 1, cfa=rsp+16 rbp=[cfa-16]
+            let insts = [0x41, 0x0e, 0x10, 0x86, 0x02];
+            eh_frame.extend(fde(eh_frame.len() as u32, start, len, &insts));
+        }
+        eh_frame.extend([0; 4]);
+
+        let mut obj =
+            write::Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let section = obj.add_section(Vec::new(), b".eh_frame".to_vec(), SectionKind::ReadOnlyData);
+        obj.append_section_data(section, &eh_frame, 8);
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn eh_frame_rows() {
+        let data = object(&[(0x2000, 0x20), (0x1000, 0x10)]);
+        let obj = object::File::parse(&*data).unwrap();
+        let funcs = functions(&obj).unwrap();
+
+        let bounds: Vec<_> = funcs.iter().map(|func| (func.start, func.end)).collect();
+        assert_eq!(bounds, [(0x1000, 0x1010), (0x2000, 0x2020)]);
+
+        let rows: Vec<_> = funcs[0].rows.iter().map(|row| (row.addr, row.rules.as_str())).collect();
+        assert_eq!(
+            rows,
+            [
+                (0x1000, "cfa=rsp+0x8 ra=[cfa-0x8]"),
+                (0x1001, "cfa=rsp+0x10 ra=[cfa-0x8] rbp=[cfa-0x10]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn starts() {
+        let data = object(&[(0x2000, 0x20), (0x1000, 0x10), (0x3000, 0), (0x1000, 0x10)]);
+        let obj = object::File::parse(&*data).unwrap();
+        assert_eq!(function_starts(&obj), [0x1000, 0x2000]);
+    }
+
+    #[test]
+    fn pe_prolog() {
+        // push rbp; sub rsp, 0x20; lea rbp, [rsp]; sub rsp, 0x1000 (listed last to first)
+        let info = [
+            1, 15, 5, 0x05, 15, 0x01, 0x00, 0x02, 8, 0x03, 5, 0x32, 1, 0x50,
+        ];
+        let rows: Vec<_> = pe_unwind_codes(&info, 0x1000)
+            .into_iter()
+            .map(|row| (row.addr, row.rules))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (0x1001, "push rbp".to_string()),
+                (0x1005, "alloc 0x20".to_string()),
+                (0x1008, "rbp=rsp+0x0".to_string()),
+                (0x100f, "alloc 0x1000".to_string()),
+            ]
+        );
+    }
+}
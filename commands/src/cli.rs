@@ -1,4 +1,7 @@
+use debugvault::replace::{Level, Pack, Simplify};
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use tokenizing::Kind;
 
 macro_rules! exit {
     ($code:expr => $($arg:tt)*) => {{
@@ -9,32 +12,852 @@ macro_rules! exit {
 
 const HELP: &str = "OVERVIEW: Debugger/Decompilation tool
 
-USAGE: bite [options] <OBJECT>
+USAGE: bite [options] <OBJECT>..
+       bite <URL> --names
+       bite tui [<OBJECT>] [options]
+       bite disasm <OBJECT> [options]
+       bite nm <OBJECT>.. [options]
+       bite libs <OBJECT>.. [--resolve] [options]
+       bite sections <OBJECT>.. [options]
+       bite functions <OBJECT>.. [--sort <KEY>] [--simplify] [--fold [--expand]] [options]
+       bite imports <OBJECT>.. [options]
+       bite exports <OBJECT>.. [options]
+       bite extract <OBJECT> --section <NAME> -o <OUTPUT>
+       bite extract <OBJECT> --all-sections <DIRECTORY>
+       bite hex <OBJECT> [--section <NAME>|--start <ADDRESS> [--len <BYTES>]]
+       bite abi-check <OLD> <NEW>
+       bite decode --selftest <CORPUS>
+       bite demangle [help|--color <MODE>|-s..|--no-..|--json] < <INPUT>
+       bite demangle --selftest <CORPUS>..
+       bite strip <OBJECT> [--keep <REGEX>|--strip-debug] -o <OUTPUT>
+       bite patch <OBJECT> --at <ADDRESS> --bytes <HEX> [--fix-checksum] [--dry-run] [-o <OUTPUT>]
+       bite patch <OBJECT> --at <ADDRESS> --assemble <ASM> [options]
+       bite bloat <OBJECT> [--top <COUNT>]
+       bite bloat-diff <OLD> <NEW> [--top <COUNT>]
+       bite entropy <OBJECT> [--graph] [--window <BYTES>]
+       bite pe-info <OBJECT>
+       bite codesign <OBJECT>
+       bite objc <OBJECT>
+       bite id <OBJECT>
+       bite id <DIRECTORY> --by-id <ID>
+       bite serve [<ADDRESS>] [--root <DIRECTORY>]
 
 OPTIONS:
   -H, --help          Print usage information
   -D, --disassemble   Path to object you're disassembling
   -C, --config        Path to config used for disassembling
-  -B, --debug         Enable verbose internal info";
+  -B, --debug         Enable verbose internal info
+  --start-address     Only decode instructions at or after this address
+  --stop-address      Only decode instructions before this address
+  --len               Number of bytes dumped by 'bite hex' from --start-address
+  --kallsyms          Path to a kernel symbol map (/proc/kallsyms or System.map)
+  --symbol-map        Path to a map of names for a stripped object ('address name' or .map)
+  --dsym              Path to the .dSYM bundle of a Mach-O object, if it's not next to it
+  --dwp               Path to the package of split DWARF, if it's not next to the object
+  --build-dir         Directory the .dwo files of split DWARF are relative to
+  --member            Object of an archive (.a, .lib or .rlib) that's disassembled
+  --layout            Path to a JSON layout, treats the object as a flat memory dump
+  --raw               Treat the object as raw machine code without a header
+  --arch              Architecture of a raw object (x86, x86_64, arm, aarch64, riscv64, ..)
+  --base              Address at which a raw object is loaded
+  --bytes             Disassemble a string of hex bytes, e.g. 'f3 48 a5', using --arch
+  --dump-bytes        Write the machine code of a function (name or address) to --output
+  -o, --output        Path the extracted bytes, modified object or stripped object is written to,
+                      or else everything that's printed
+  --emit-object       Also write a linkable object containing just the dumped function to
+                      --output with '.o' appended
+  --names             Print the object's functions
+  --crate             Only print --names of the Rust functions in a crate
+  --fold              Print the instantiations of a template as one function, with their count
+  --expand            List the instantiations under each function folded by --fold
+  --sort              Order of 'bite functions', by 'address', 'size' (largest first) or 'name'
+  --simplify          Write types like in source, e.g. 'std::string', or only those of
+                      --simplify=std,boost,qt
+  -s, -ss, -sss       Simplify types, then also drop namespaces, then also parameter lists
+  --no-calling-conv   Leave out calling conventions like '__cdecl' from demangled MSVC names
+  --no-access         Leave out access specifiers like 'public:' from demangled MSVC names
+  --no-return-type    Leave out the return types of functions from demangled MSVC names
+  --no-cv             Leave out 'const' and 'volatile' from demangled MSVC names
+  --json              Print the symbols found by 'bite demangle' as JSON, with the AST of MSVC ones
+  --libs              Print the shared libraries the object depends on
+  --resolve           Print --libs as a tree of the files the dynamic linker would load
+  --security          Print the exploit mitigations the object was built with
+  --headers           Print the program headers and dynamic section of an ELF object
+  --relocs            Print the object's relocations
+  --unwind            Print the unwind rules of every function from .eh_frame or .pdata
+  --tls               Print the thread-local variables of an ELF object or TLS callbacks of a PE
+  --decode-strings    Annotate strings that are built or decrypted on the stack at runtime
+  --aslr-sim          Show addresses relative to the object next to an example runtime address
+  --source            Show the source lines above the instructions they were compiled from
+  --radix             Write operands as 'hex', 'dec' or 'auto', which writes small ones in decimal
+  --no-hex-prefix     Leave out the '0x' in front of hexadecimal numbers
+  --zero-pad          Pad hexadecimal numbers with zeros to 8 or 16 digits
+  --no-bytes          Leave out the column of encoded bytes next to each instruction
+  --no-pager          Print to the terminal directly instead of through $PAGER or less
+  --color             Color printed text 'always', 'never' or 'auto' when writing to a terminal
+  --filter            Print the functions whose (mangled) name contains a pattern
+  --signature         Print the functions whose parameter and return types match a signature
+  --search            Print where a pattern of hex bytes occurs, with '??' matching any byte
+  --gpu               Print the embedded CUDA and SPIR-V code and the kernels it contains
+  --extract-gpu       Write the n-th blob listed by --gpu to --output
+  --extract-section   Write the contents of a section (by name) to --output
+  --add-section       Write the object with a section added to --output, given as NAME=FILE
+  --section           Section written to --output by 'bite extract' or dumped by 'bite hex'
+  --all-sections      Directory 'bite extract' writes the contents of every section to
+  --clones            Print functions grouped with their compiler generated clones and inlines
+  --registers         Print how often each register is read and written, overall and per function
+  --trace             Print the functions an instruction trace went through with execution counts
+  --trace-base        Address the object was loaded at when it was traced
+  --callgraph         Print the call graph of the object as either 'dot' or 'json'
+  --callgraph-root    Only include the functions called from a function (name or address)
+  --format            Print the listing of every function as a page, only 'html' for now
+  --keep              Symbols kept by strip, matched against their mangled and demangled name
+  --strip-debug       Only remove the debug info with strip, keeping every symbol
+  --root              Directory 'bite serve' opens the modules of queries from by their path
+  --top               Number of crates, namespaces and functions listed by bloat (20 by default)
+  --graph             Draw the entropy profile as a graph instead of listing every window
+  --window            Number of bytes per window of the entropy profile (4096 by default)
+  --by-id             Build-id, UUID or PDB GUID of the object searched for by 'bite id'
+  --at                Virtual address 'bite patch' writes --bytes to
+  --assemble          Instructions written by 'bite patch' instead of --bytes, e.g. 'nop; ret'
+  --fix-checksum      Update the checksum of a PE image patched by 'bite patch'
+  --dry-run           Show what 'bite patch' would change without writing anything
 
-const ABBRV: &[&str] = &["-H", "-D", "-C", "-B"];
+  Any of --names, --libs, --security, --headers, --relocs, --unwind, --tls, --filter,
+  --signature, --search, --gpu, --clones, --registers, --trace, --callgraph and one of the
+  options writing to --output can be combined, the object is only parsed once. A --format page
+  is printed on its own. Given several objects (or a pattern like 'lib*.so'), they're parsed in
+  parallel and what's printed about each one follows a header with its path. Archives are
+  treated like the objects in them, unless one is selected with --member, which is required to
+  open one in the GUI.
+  Symbols are cached by build-id (or a hash of the object), so a --filter on an object that was
+  opened before doesn't parse it at all. The listing's blocks and the call graph are cached too.
+  Printed text is colored when writing to a terminal and NO_COLOR isn't set, unless --color
+  says otherwise.
+  The config is read from bite/config.yaml in the data directory unless --config is given, with
+  the .bite.yaml of the working directory on top of it. It picks a 'theme' (dark, light or
+  monochrome) and overrides any of its colors by token kind, either as '#rrggbb' or as an index
+  of the 256-color palette. Options that aren't given on the command line are taken from its
+  'defaults', which can set 'pager', 'arch', 'radix' and 'color'. It's 'replace' rules rewrite
+  the names printed with --simplify, e.g. '{from: absl::lts_*::, to: absl::}', where '*' matches
+  any type or part of a path and is written to 'to' as $1, $2, ..
+
+  A --signature is written like 'fn(&str, ..) -> Result<*, *>' or 'int (char const*, ..)',
+  where '*' matches any type and '..' any remaining parameters. Types match by the end of their
+  path, e.g. 'Result' matches 'core::result::Result<u64, E>'. The types are read from DWARF, or
+  else from the mangled names of C++ functions.
+
+  A trace has an executed address per line, e.g. as printed by 'perf script' for Intel PT or
+  by QEMU's exec log. Every instruction has to be logged, not just the start of each block.
+
+  UEFI drivers are read both as PE objects and as the Terse Executables (TE) of firmware
+  volumes, which are loaded at the image base in their header. Nintendo Switch modules (NSO
+  and NRO) are loaded at address zero with their segments decompressed.
+
+  Raw objects that are Intel HEX (.hex) or S-record (.srec) images are loaded at the addresses
+  of their records, moved by --base if it's given, and start at the entry point they specify.
+  ARM images that start with a Cortex-M vector table are decoded as Thumb from its handlers.
+
+  An object given as '-' is read from stdin, e.g. 'curl -s <URL> | bite nm -'.
+
+  Stripped objects use the symbols and debug info of their separate debug file, found by
+  build-id, .gnu_debuglink or downloaded from the debuginfod servers in DEBUGINFOD_URLS.
+  Mach-O objects use the .dSYM bundle next to them with the same UUID. Units compiled with
+  -gsplit-dwarf are read from the <OBJECT>.dwp package or else from their .dwo files, which are
+  relative to the directory they were compiled in unless --build-dir is given.
+  Functions of Go objects are named after the runtime's function table, which stripping keeps.
+  Other stripped objects have their functions found through the unwind table.
+
+  'bite tui' opens the GUI, with the object if one is given, which is also what happens without a
+  subcommand unless information is asked for. 'bite disasm' prints the listing of every function
+  of the object, while 'bite nm', 'bite libs' and 'bite sections' print the object's functions,
+  libraries or sections respectively. Each subcommand only takes its own options and the ones
+  that print information too, e.g. --color, --output or --libs.
+
+  --names groups the functions of Rust objects by crate and then by module, with the number of
+  functions in each. The functions that aren't Rust, like those of libc, are listed after them.
+
+  'bite functions' lists every function, named by its symbol or found through the unwind
+  table, with its address, size in bytes and section. Sizes run up to the next function or the
+  end of the section, without the padding in between.
+
+  --fold prints the instantiations of a template as one function, e.g. 'std::vector<…>::push_back
+  — 37 instantiations', which 'bite functions' lists with their combined size. With --expand
+  each instantiation is listed underneath.
+
+  --simplify rewrites the types of the standard library, Boost and Qt to how they're written in
+  source, such as their strings and smart pointers, or only those of the libraries it's given,
+  e.g. --simplify=std,qt. It's the same as -s, while -ss also shortens every path to its last
+  segment and -sss leaves out the parameters of functions too. Names written by 'bite demangle'
+  are simplified the same way.
+
+  --no-calling-conv, --no-access, --no-return-type and --no-cv make the MSVC names printed by
+  --names, 'bite functions' and 'bite demangle' shorter like llvm-undname's flags do, by leaving
+  out those parts of the name as it's demangled.
+
+  'bite imports' and 'bite exports' list the functions the object imports and exports, with
+  demangled names. For PE objects these are the IAT slots by library and name or ordinal, and
+  the export directory by ordinal together with the exports forwarded to other libraries.
+
+  'bite demangle' rewrites the mangled symbols found anywhere in the lines read from stdin,
+  like c++filt but for every scheme listed by 'bite demangle help' at once. With --selftest it
+  prints the pass rate on the test corpora of llvm-undname (ms-*.test) and libiberty
+  (demangle-expected) instead, per test file or mangling format. With --json it prints a line of
+  JSON for each symbol instead, with its mangled and demangled name and for MSVC symbols the
+  parsed AST, so tooling can query them by their parts, e.g. the type of the first parameter.
+
+  'bite serve' symbolizes the 'module,build-id,offset' frames of backtraces sent to
+  /symbolize over HTTP, either as lines of a POST or as 'q' parameters of a GET. It listens on
+  127.0.0.1:7878 unless another address is given. Modules are found by their build-id like
+  separate debug files, or by their path inside --root if it's given.
+
+  'bite strip' removes the symbol table and debug info of an ELF executable or shared library,
+  except for the symbols matching --keep. The dynamic symbols are never removed.
+
+  'bite bloat' attributes the bytes of the executable sections to the functions in them and
+  lists the largest, together with the crates of Rust functions and top-level namespaces of C++
+  functions they add up to. 'bite bloat-diff' lists how much those grew or shrunk between two
+  builds, matching functions by name.
+
+  'bite entropy' prints the entropy of each section and of every window of the file, in bits per
+  byte. Executable sections above 7.2 bits are flagged as they're likely packed or encrypted.
+
+  'bite pe-info' prints the imphash and imported libraries of a PE object, together with the
+  tools and their build numbers listed in its Rich header.
+
+  'bite codesign' prints the identifier, team, flags and certificate authorities of the code
+  signature of a Mach-O object, followed by its entitlements.
+
+  'bite extract' writes the raw contents of a section to a file like 'objcopy -O binary
+  --only-section' would, or with --all-sections those of every section to a file of its own.
+  Sections without contents in the object, such as .bss, are skipped.
+
+  'bite hex' prints a hex dump like 'xxd' of every section, of a single --section or of the bytes
+  from --start-address (or --start) up to --stop-address or for --len bytes. Lines are addressed
+  by virtual address and start over at each section. In the GUI, Ctrl+H shows the hex dump next to
+  the disassembly.
+
+  'bite patch' overwrites the bytes at a virtual address of an object with --bytes, at the offset
+  into the file given by the section the address is in. The object is changed in place after it's
+  copied to <OBJECT>.bak, unless it's written to --output instead. The bytes are printed before and
+  after patching, with --dry-run nothing is written. --assemble only supports x86_64's nop, int3,
+  ret, jmp, jcc, call and mov between registers or of an immediate, separated by ';'.
+
+  'bite objc' prints the Objective-C classes of a 64-bit Mach-O object with their methods and
+  type encodings, the protocols it defines and the selectors it references.
+
+  'bite id' prints what ties an object to its debug info: the build-id of an ELF object, the
+  UUID of a Mach-O object or the PDB GUID and age of a PE object, together with the keys a symbol
+  server stores a PE object and its PDB under. With --by-id, the objects in a directory and its
+  subdirectories are searched for the ones with that id, written with or without dashes.
+
+  A decoder self-test corpus has one instruction per line, given as 'arch | bytes | text',
+  e.g. 'x86_64 | 48 89 e5 | mov rbp, rsp'. Lines starting with '#' are ignored.";
+
+const SUBCOMMANDS: &[&str] = &[
+    "tui",
+    "disasm",
+    "nm",
+    "libs",
+    "sections",
+    "functions",
+    "imports",
+    "exports",
+    "extract",
+    "hex",
+    "abi-check",
+    "demangle",
+    "serve",
+    "strip",
+    "bloat",
+    "bloat-diff",
+    "entropy",
+    "pe-info",
+    "codesign",
+    "objc",
+    "id",
+    "patch",
+    "decode",
+];
+
+const ABBRV: &[&str] = &["-H", "-D", "-C", "-B", "-o"];
 const NAMES: &[&str] = &[
     "--help",
     "--disassemble",
     "--config",
     "--debug",
+    "--start-address",
+    "--stop-address",
+    "--start",
+    "--len",
+    "--kallsyms",
+    "--symbol-map",
+    "--dsym",
+    "--dwp",
+    "--member",
+    "--build-dir",
+    "--layout",
+    "--raw",
+    "--arch",
+    "--base",
+    "--bytes",
+    "--dump-bytes",
+    "--output",
+    "--emit-object",
+    "--names",
+    "--libs",
+    "--resolve",
+    "--security",
+    "--headers",
+    "--relocs",
+    "--unwind",
+    "--tls",
+    "--decode-strings",
+    "--aslr-sim",
+    "--source",
+    "--radix",
+    "--no-hex-prefix",
+    "--zero-pad",
+    "--no-bytes",
+    "--no-pager",
+    "--color",
+    "--filter",
+    "--signature",
+    "--search",
+    "--gpu",
+    "--extract-gpu",
+    "--extract-section",
+    "--add-section",
+    "--section",
+    "--all-sections",
+    "--clones",
+    "--registers",
+    "--trace",
+    "--trace-base",
+    "--callgraph",
+    "--callgraph-root",
+    "--format",
+    "--keep",
+    "--strip-debug",
+    "--root",
+    "--top",
+    "--graph",
+    "--window",
+    "--by-id",
+    "--at",
+    "--assemble",
+    "--fix-checksum",
+    "--dry-run",
+    "--sort",
+    "--simplify",
+    "--crate",
+    "--fold",
+    "--expand",
+    "--no-calling-conv",
+    "--no-access",
+    "--no-return-type",
+    "--no-cv",
+    "--json",
 ];
 
+/// Base immediates and addresses of operands are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Hex,
+    Decimal,
+    /// Small values in decimal and the rest in hex.
+    Auto,
+}
+
+/// Order functions are listed in by `bite functions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Address,
+    /// Largest first.
+    Size,
+    Name,
+}
+
+/// When printed text is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// When writing to a terminal and `NO_COLOR` isn't set.
+    Auto,
+    Always,
+    Never,
+}
+
+/// Format the whole listing is printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingFormat {
+    /// A page with an index of the functions and links to the ones that are called.
+    Html,
+}
+
+/// Format a call graph is printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz.
+    Dot,
+    Json,
+}
+
+/// What's done with the objects, or instead of opening any, together with the options that only
+/// apply to it.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum Subcommand {
+    /// Open the object in the GUI, or print the information asked for by options like --names.
+    #[default]
+    Tui,
+    /// Print the listing of every function.
+    Disasm,
+    /// Print the object's functions.
+    Nm,
+    /// Print the object's shared library dependencies.
+    Libs,
+    /// Print the object's sections.
+    Sections,
+    /// Print every function with its size and section.
+    Functions {
+        /// Order of the functions, by address if not given.
+        sort: Option<SortBy>,
+    },
+    /// Print the functions the object imports.
+    Imports,
+    /// Print the functions the object exports.
+    Exports,
+    /// Write the contents of sections to files.
+    Extract {
+        /// Section written to `output`.
+        section: Option<String>,
+        /// Directory every section is written to.
+        all_sections: Option<PathBuf>,
+    },
+    /// Print a hex dump of the object's sections.
+    Hex {
+        /// Only section that's dumped.
+        section: Option<String>,
+        /// Number of bytes dumped, starting at `start_addr`.
+        len: Option<usize>,
+    },
+    /// Compare the exported symbols of an old and new version of a library.
+    AbiCheck { old: PathBuf, new: PathBuf },
+    /// Demangle the symbols read from stdin.
+    Demangle {
+        /// Print the demangled symbols as JSON.
+        json: bool,
+    },
+    /// List the demangling schemes.
+    DemangleHelp,
+    /// Check the demanglers against the test corpora of other demanglers.
+    DemangleSelftest { corpora: Vec<PathBuf> },
+    /// Check the decoders against a corpus of instructions.
+    DecodeSelftest { corpus: PathBuf },
+    /// Serve symbolication requests on an address.
+    Serve {
+        addr: String,
+        /// Directory the modules of queries are opened from by their path.
+        root: Option<PathBuf>,
+    },
+    /// Remove the symbols and debug info of an object.
+    Strip {
+        object: PathBuf,
+        /// Pattern of the symbols that are kept.
+        keep: Option<String>,
+        /// Only remove debug info.
+        strip_debug: bool,
+    },
+    /// Attribute the size of the code of an object.
+    Bloat {
+        object: PathBuf,
+        /// Number of the largest functions and groups listed.
+        top: Option<usize>,
+    },
+    /// Compare the size of the code of an old and new build.
+    BloatDiff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Number of the largest functions and groups listed.
+        top: Option<usize>,
+    },
+    /// Print the entropy profile of an object.
+    Entropy {
+        object: PathBuf,
+        /// Draw the profile as a graph.
+        graph: bool,
+        /// Number of bytes per window.
+        window: Option<usize>,
+    },
+    /// Print the imphash and Rich header of a PE object.
+    PeInfo { object: PathBuf },
+    /// Print the code signature of a Mach-O object.
+    Codesign { object: PathBuf },
+    /// Print the Objective-C classes and protocols of a Mach-O object.
+    Objc { object: PathBuf },
+    /// Print the build-id, UUID or PDB GUID of an object, or search a directory for `by_id`.
+    Id {
+        path: PathBuf,
+        /// Id of the objects searched for.
+        by_id: Option<String>,
+    },
+    /// Write bytes into an object.
+    Patch {
+        object: PathBuf,
+        /// Virtual address the bytes are written to.
+        at: Option<usize>,
+        /// Instructions to assemble into the bytes written.
+        assemble: Option<String>,
+        /// Update the checksum of a patched PE image.
+        fix_checksum: bool,
+        /// Only show what patching would change.
+        dry_run: bool,
+    },
+}
+
+impl Subcommand {
+    /// Whether the subcommand prints information about the object it's given, such that it's
+    /// parsed just like it would be for the GUI.
+    pub fn reports(&self) -> bool {
+        matches!(
+            self,
+            Self::Disasm
+                | Self::Nm
+                | Self::Libs
+                | Self::Sections
+                | Self::Functions { .. }
+                | Self::Imports
+                | Self::Exports
+                | Self::Extract { .. }
+                | Self::Hex { .. }
+        )
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Cli {
+    /// What's done with the object.
+    pub command: Subcommand,
+
     /// Path to symbol being disassembled.
     pub path: PathBuf,
 
+    /// Objects after the first, which information is printed about too.
+    pub paths: Vec<PathBuf>,
+
     /// Optional path to config.
     pub config: Option<PathBuf>,
 
     /// Show egui debug overlay.
     pub debug: bool,
+
+    /// Virtual address at which decoding starts.
+    pub start_addr: Option<usize>,
+
+    /// Virtual address at which decoding stops.
+    pub stop_addr: Option<usize>,
+
+    /// Optional path to a kernel symbol map.
+    pub kallsyms: Option<PathBuf>,
+
+    /// Optional path to a map of names for the functions of a stripped object.
+    pub symbol_map: Option<PathBuf>,
+
+    /// Optional path to the dSYM bundle of a Mach-O object.
+    pub dsym: Option<PathBuf>,
+
+    /// Optional path to a package of split DWARF.
+    pub dwp: Option<PathBuf>,
+
+    /// Name of the object in an archive that's disassembled.
+    pub member: Option<String>,
+
+    /// Optional directory to look for `.dwo` files in.
+    pub build_dir: Option<PathBuf>,
+
+    /// Optional path to the layout of a memory dump.
+    pub layout: Option<PathBuf>,
+
+    /// Treat the object as raw machine code.
+    pub raw: bool,
+
+    /// Architecture of a raw object.
+    pub arch: Option<String>,
+
+    /// Address at which a raw object is loaded.
+    pub base: Option<usize>,
+
+    /// Bytes to disassemble instead of an object.
+    pub bytes: Option<Vec<u8>>,
+
+    /// Name or address of a function to write to `output`.
+    pub dump_bytes: Option<String>,
+
+    /// Path to write extracted bytes or else the printed information to.
+    pub output: Option<PathBuf>,
+
+    /// Also write a relocatable object containing the extracted function.
+    pub emit_object: bool,
+
+    /// Print the object's functions.
+    pub names: bool,
+
+    /// Print the object's shared library dependencies.
+    pub libs: bool,
+
+    /// Resolve the shared library dependencies to paths.
+    pub resolve: bool,
+
+    /// Print the object's exploit mitigations.
+    pub security: bool,
+
+    /// Print the object's program headers and dynamic section.
+    pub headers: bool,
+
+    /// Print the object's relocations.
+    pub relocs: bool,
+
+    /// Print the unwind rules of every function.
+    pub unwind: bool,
+
+    /// Print the object's thread-local variables or TLS callbacks.
+    pub tls: bool,
+
+    /// Emulate code that builds strings on the stack.
+    pub decode_strings: bool,
+
+    /// Show example runtime addresses of an object loaded at a random address.
+    pub aslr_sim: bool,
+
+    /// Interleave the listing with the source lines of its instructions.
+    pub source: bool,
+
+    /// Base operands are written in, the architecture's convention if not given.
+    pub radix: Option<Radix>,
+
+    /// Write hexadecimal numbers without a leading `0x`.
+    pub no_hex_prefix: bool,
+
+    /// Pad hexadecimal numbers with zeros.
+    pub zero_pad: bool,
+
+    /// Hide the encoded bytes of instructions.
+    pub no_bytes: bool,
+
+    /// Print to the terminal without a pager.
+    pub no_pager: bool,
+
+    /// When printed text is colored, [`ColorMode::Auto`] if not given.
+    pub color: Option<ColorMode>,
+
+    /// Pattern to search the object's functions for.
+    pub filter: Option<String>,
+
+    /// Signature to search the object's functions for.
+    pub signature: Option<String>,
+
+    /// Bytes to search the object's sections for, where `None` matches any byte.
+    pub search: Option<Vec<Option<u8>>>,
+
+    /// Print the object's embedded GPU code.
+    pub gpu: bool,
+
+    /// Index of an embedded GPU blob to write to `output`.
+    pub extract_gpu: Option<usize>,
+
+    /// Name of a section to write the contents of to `output`.
+    pub extract_section: Option<String>,
+
+    /// Name and contents of a section to add to the copy of the object written to `output`.
+    pub add_section: Option<(String, PathBuf)>,
+
+    /// Print functions grouped with their clones.
+    pub clones: bool,
+
+    /// Print the register usage of every function.
+    pub registers: bool,
+
+    /// Trace of executed instructions to print the functions of.
+    pub trace: Option<PathBuf>,
+
+    /// Address the traced object was loaded at.
+    pub trace_base: Option<usize>,
+
+    /// Format to print the call graph in.
+    pub callgraph: Option<GraphFormat>,
+
+    /// Function the call graph starts at.
+    pub callgraph_root: Option<String>,
+
+    /// Format to print the listing of every function in.
+    pub format: Option<ListingFormat>,
+
+    /// How demangled names are rewritten to be shorter, e.g. to how types are written in source.
+    pub simplify: Option<Simplify>,
+
+    /// Crate whose Rust functions are printed by `names`.
+    pub krate: Option<String>,
+
+    /// Print functions that only differ in their template arguments as one.
+    pub fold: bool,
+
+    /// List the functions that were folded together.
+    pub expand: bool,
+
+    /// Parts of demangled names that are left out, such as calling conventions.
+    pub omit: Kind,
+}
+
+/// Parses either a hexadecimal address prefixed with '0x' or a decimal address.
+fn parse_addr(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Whether `s` is a build-id, UUID or GUID, which may be written with dashes and braces.
+fn is_id(s: &str) -> bool {
+    s.contains(|c: char| c.is_ascii_hexdigit())
+        && s.chars().all(|c| c.is_ascii_hexdigit() || matches!(c, '-' | '{' | '}'))
+}
+
+/// Whether `name` matches `pattern`, where '*' matches any number of bytes and '?' any byte.
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Position of the last star and of the byte in `name` it's currently matched up to.
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&b) if b == b'?' || b == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            // Let the last star match one more byte and retry from there.
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+/// Paths matching wildcards in the file name of `path` like a shell would, for shells that don't.
+/// Paths without any or that don't match anything are returned as is.
+fn expand_glob(path: &str) -> Vec<PathBuf> {
+    let path = Path::new(path);
+    let Some(pattern) = path.file_name().and_then(|name| name.to_str()) else {
+        return vec![path.to_path_buf()];
+    };
+
+    if !pattern.contains(['*', '?']) {
+        return vec![path.to_path_buf()];
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if dir != Path::new("") => dir,
+        _ => Path::new("."),
+    };
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                // Hidden files are only matched explicitly.
+                (!name.starts_with('.') || pattern.starts_with('.'))
+                    && glob_matches(pattern.as_bytes(), name.as_bytes())
+            })
+        })
+        .map(|entry| path.with_file_name(entry.file_name()))
+        .collect();
+
+    paths.sort();
+    if paths.is_empty() {
+        paths.push(path.to_path_buf());
+    }
+    paths
+}
+
+fn parse_radix(s: &str) -> Option<Radix> {
+    match s {
+        "hex" => Some(Radix::Hex),
+        "dec" => Some(Radix::Decimal),
+        "auto" => Some(Radix::Auto),
+        _ => None,
+    }
+}
+
+fn parse_sort(s: &str) -> Option<SortBy> {
+    match s {
+        "address" => Some(SortBy::Address),
+        "size" => Some(SortBy::Size),
+        "name" => Some(SortBy::Name),
+        _ => None,
+    }
+}
+
+fn parse_color(s: &str) -> Option<ColorMode> {
+    match s {
+        "auto" => Some(ColorMode::Auto),
+        "always" => Some(ColorMode::Always),
+        "never" => Some(ColorMode::Never),
+        _ => None,
+    }
+}
+
+/// Parses hex bytes such as "f3 48 a5", "f348a5", "0xf3, 0x48" or "\\xf3\\x48".
+pub fn parse_hex_bytes(s: &str) -> Option<Vec<u8>> {
+    let mut digits = String::new();
+    for word in s.split(|c: char| c.is_whitespace() || c == ',') {
+        for chunk in word.split("\\x") {
+            let prefixed = chunk.strip_prefix("0x").or_else(|| chunk.strip_prefix("0X"));
+            digits.push_str(prefixed.unwrap_or(chunk));
+        }
+    }
+
+    if digits.is_empty() || digits.len() & 1 == 1 {
+        return None;
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(digits.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+/// Path given right after a subcommand, exiting with its usage if there isn't one.
+fn object_arg(args: &mut impl Iterator<Item = String>, usage: &str) -> PathBuf {
+    match args.next() {
+        Some(path) if !path.starts_with('-') => PathBuf::from(path),
+        _ => exit!(1 => "Usage: {usage}"),
+    }
+}
+
+/// Parses a pattern of hex bytes such as "48 8B ?? ?? E8", where "??" matches any byte.
+pub fn parse_pattern(s: &str) -> Option<Vec<Option<u8>>> {
+    let pattern: Option<Vec<Option<u8>>> = s
+        .split_whitespace()
+        .map(|byte| match byte {
+            "?" | "??" => Some(None),
+            byte if byte.len() == 2 => u8::from_str_radix(byte, 16).ok().map(Some),
+            _ => None,
+        })
+        .collect();
+
+    // A pattern of only wildcards matches everywhere.
+    pattern.filter(|pattern| pattern.iter().any(Option::is_some))
 }
 
 impl Cli {
@@ -42,6 +865,132 @@ impl Cli {
         let mut cli = Cli::default();
         let mut args = std::env::args().skip(1).peekable();
 
+        // The GUI is opened when no subcommand is given, unless information is asked for.
+        let mut tui = false;
+        let command = args.next_if(|arg| SUBCOMMANDS.contains(&arg.as_str()));
+        cli.command = match command.as_deref() {
+            None => Subcommand::Tui,
+            Some("tui") => {
+                if let Some(path) = args.next_if(|path| !path.starts_with('-') || path == "-") {
+                    cli.add_objects(&path);
+                }
+                tui = true;
+                Subcommand::Tui
+            }
+            Some("abi-check") => match (args.next(), args.next()) {
+                (Some(old), Some(new)) => Subcommand::AbiCheck {
+                    old: PathBuf::from(old),
+                    new: PathBuf::from(new),
+                },
+                _ => exit!(1 => "Usage: bite abi-check <OLD> <NEW>"),
+            },
+            Some("demangle") => {
+                // Demangling stdin only takes the color mode, simplification, the parts of names
+                // that are left out and --json, parsed like any other flag.
+                let flags = args.peek().is_some_and(|arg| {
+                    let known = ["--color", "--simplify", "-s", "--no-", "--json"];
+                    known.iter().any(|flag| arg.starts_with(flag))
+                });
+                match (args.next_if(|_| !flags).as_deref(), args.peek().is_some()) {
+                    (None, _) => Subcommand::Demangle { json: false },
+                    (Some("help"), false) => Subcommand::DemangleHelp,
+                    (Some("--selftest"), true) => Subcommand::DemangleSelftest {
+                        corpora: args.by_ref().map(PathBuf::from).collect(),
+                    },
+                    _ => exit!(1 => "Usage: bite demangle [help|--selftest <CORPUS>..|--color \
+                                     <MODE>]"),
+                }
+            }
+            Some("serve") => Subcommand::Serve {
+                addr: args
+                    .next_if(|addr| !addr.starts_with('-'))
+                    .unwrap_or_else(|| "127.0.0.1:7878".to_string()),
+                root: None,
+            },
+            Some("strip") => Subcommand::Strip {
+                object: object_arg(
+                    &mut args,
+                    "bite strip <OBJECT> [--keep <REGEX>|--strip-debug] -o <OUTPUT>",
+                ),
+                keep: None,
+                strip_debug: false,
+            },
+            Some("bloat") => Subcommand::Bloat {
+                object: object_arg(&mut args, "bite bloat <OBJECT> [--top <COUNT>]"),
+                top: None,
+            },
+            Some("bloat-diff") => match (args.next(), args.next()) {
+                (Some(old), Some(new)) => Subcommand::BloatDiff {
+                    old: PathBuf::from(old),
+                    new: PathBuf::from(new),
+                    top: None,
+                },
+                _ => exit!(1 => "Usage: bite bloat-diff <OLD> <NEW> [--top <COUNT>]"),
+            },
+            Some("entropy") => Subcommand::Entropy {
+                object: object_arg(
+                    &mut args,
+                    "bite entropy <OBJECT> [--graph] [--window <BYTES>]",
+                ),
+                graph: false,
+                window: None,
+            },
+            Some("pe-info") => Subcommand::PeInfo {
+                object: object_arg(&mut args, "bite pe-info <OBJECT>"),
+            },
+            Some("codesign") => Subcommand::Codesign {
+                object: object_arg(&mut args, "bite codesign <OBJECT>"),
+            },
+            Some("objc") => Subcommand::Objc {
+                object: object_arg(&mut args, "bite objc <OBJECT>"),
+            },
+            Some("id") => Subcommand::Id {
+                path: object_arg(
+                    &mut args,
+                    "bite id <OBJECT> or bite id <DIRECTORY> --by-id <ID>",
+                ),
+                by_id: None,
+            },
+            Some("patch") => Subcommand::Patch {
+                object: object_arg(
+                    &mut args,
+                    "bite patch <OBJECT> --at <ADDRESS> --bytes|--assemble <PATCH>",
+                ),
+                at: None,
+                assemble: None,
+                fix_checksum: false,
+                dry_run: false,
+            },
+            Some("decode") => match (args.next().as_deref(), args.next()) {
+                (Some("--selftest"), Some(corpus)) => Subcommand::DecodeSelftest {
+                    corpus: PathBuf::from(corpus),
+                },
+                _ => exit!(1 => "Usage: bite decode --selftest <CORPUS>"),
+            },
+            Some(command) => {
+                let Some(path) = args.next().filter(|path| !path.starts_with('-') || path == "-")
+                else {
+                    exit!(1 => "Usage: bite {command} <OBJECT>.. [options]");
+                };
+                cli.add_objects(&path);
+                match command {
+                    "disasm" => Subcommand::Disasm,
+                    "nm" => Subcommand::Nm,
+                    "libs" => Subcommand::Libs,
+                    "sections" => Subcommand::Sections,
+                    "functions" => Subcommand::Functions { sort: None },
+                    "imports" => Subcommand::Imports,
+                    "exports" => Subcommand::Exports,
+                    "extract" => Subcommand::Extract { section: None, all_sections: None },
+                    _ => Subcommand::Hex { section: None, len: None },
+                }
+            }
+        };
+
+        // Libraries and level of simplification, which can be given in any order.
+        let mut packs = None;
+        let mut level = None;
+
         while let Some(arg) = args.next() {
             match arg.as_str() {
                 "-H" | "--help" => exit!(0 => "{HELP}"),
@@ -71,45 +1020,1122 @@ impl Cli {
                     }
                     cli.debug = true
                 }
-                unknown => {
-                    let mut distance = u32::MAX;
-                    let mut best_guess = "";
-                    for name in NAMES {
-                        let d = triple_accel::levenshtein_exp(unknown.as_bytes(), name.as_bytes());
-                        if d < distance {
-                            distance = d;
-                            best_guess = name;
+                "--start-address" | "--start" => {
+                    if cli.start_addr.is_some() {
+                        exit!(1 => "Start address already given.");
+                    }
+                    match args.next().as_deref().map(parse_addr) {
+                        Some(Some(addr)) => cli.start_addr = Some(addr),
+                        _ => exit!(1 => "Start address must be a decimal or hexadecimal number."),
+                    }
+                }
+                "--stop-address" => {
+                    if cli.stop_addr.is_some() {
+                        exit!(1 => "Stop address already given.");
+                    }
+                    match args.next().as_deref().map(parse_addr) {
+                        Some(Some(addr)) => cli.stop_addr = Some(addr),
+                        _ => exit!(1 => "Stop address must be a decimal or hexadecimal number."),
+                    }
+                }
+                "--len" => {
+                    let Subcommand::Hex { ref mut len, .. } = cli.command else {
+                        exit!(1 => "--len can only be used with 'bite hex'.");
+                    };
+                    if len.is_some() {
+                        exit!(1 => "Number of bytes to dump already given.");
+                    }
+                    match args.next().as_deref().map(parse_addr) {
+                        Some(Some(bytes)) if bytes > 0 => *len = Some(bytes),
+                        _ => exit!(1 => "The number of bytes to dump must be a number."),
+                    }
+                }
+                "--kallsyms" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.kallsyms.is_some() {
+                                exit!(1 => "Path to symbol map already given.");
+                            }
+                            cli.kallsyms = Some(PathBuf::from(path));
                         }
                     }
-
-                    // A guess that's less than 3 `steps` away from a correct arg.
-                    if distance < 4 {
-                        exit!(1 => "Unknown cmd arg '{unknown}' did you mean '{best_guess}'?")
-                    } else {
-                        exit!(1 => "Unknown cmd arg '{unknown}' was entered.");
+                }
+                "--symbol-map" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.symbol_map.is_some() {
+                                exit!(1 => "Path to symbol map already given.");
+                            }
+                            cli.symbol_map = Some(PathBuf::from(path));
+                        }
                     }
                 }
-            }
-        }
-
-        cli.validate_args();
-        cli
-    }
-
-    fn validate_args(&mut self) {
-        if self.path == Path::new("") {
-            // exit!(1 => "You must provide a path to disassemble.");
-            return;
-        }
-
-        if !self.path.exists() {
-            exit!(1 => "Object {:?} does not exist.", self.path);
-        }
-
-        if let Some(ref cfg) = self.config {
-            if !cfg.exists() {
-                exit!(1 => "Config {cfg:?} does not exist.");
-            }
-        }
+                "--dsym" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.dsym.is_some() {
+                                exit!(1 => "Path to dSYM already given.");
+                            }
+                            cli.dsym = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--dwp" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.dwp.is_some() {
+                                exit!(1 => "Path to DWARF package already given.");
+                            }
+                            cli.dwp = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--member" => {
+                    if let Some(name) = args.next().as_deref() {
+                        if !NAMES.contains(&name) && !ABBRV.contains(&name) {
+                            if cli.member.is_some() {
+                                exit!(1 => "Archive member already given.");
+                            }
+                            cli.member = Some(name.to_string());
+                        }
+                    }
+                }
+                "--build-dir" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.build_dir.is_some() {
+                                exit!(1 => "Build directory already given.");
+                            }
+                            cli.build_dir = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--layout" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.layout.is_some() {
+                                exit!(1 => "Path to layout already given.");
+                            }
+                            cli.layout = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--raw" => {
+                    if cli.raw {
+                        exit!(1 => "Raw flag already set.");
+                    }
+                    cli.raw = true
+                }
+                "--arch" => {
+                    if let Some(arch) = args.next().as_deref() {
+                        if !NAMES.contains(&arch) && !ABBRV.contains(&arch) {
+                            if cli.arch.is_some() {
+                                exit!(1 => "Architecture already given.");
+                            }
+                            cli.arch = Some(arch.to_string());
+                        }
+                    }
+                }
+                "--base" => {
+                    if cli.base.is_some() {
+                        exit!(1 => "Base address already given.");
+                    }
+                    match args.next().as_deref().map(parse_addr) {
+                        Some(Some(addr)) => cli.base = Some(addr),
+                        _ => exit!(1 => "Base address must be a decimal or hexadecimal number."),
+                    }
+                }
+                "--bytes" => {
+                    if cli.bytes.is_some() {
+                        exit!(1 => "Bytes already given.");
+                    }
+                    match args.next().as_deref().map(parse_hex_bytes) {
+                        Some(Some(bytes)) => cli.bytes = Some(bytes),
+                        _ => exit!(1 => "Bytes must be given as a string of hexadecimal pairs."),
+                    }
+                }
+                "--dump-bytes" => {
+                    if let Some(symbol) = args.next().as_deref() {
+                        if !NAMES.contains(&symbol) && !ABBRV.contains(&symbol) {
+                            if cli.dump_bytes.is_some() {
+                                exit!(1 => "Function to dump already given.");
+                            }
+                            cli.dump_bytes = Some(symbol.to_string());
+                        }
+                    }
+                }
+                "-o" | "--output" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.output.is_some() {
+                                exit!(1 => "Path to output already given.");
+                            }
+                            cli.output = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--emit-object" => {
+                    if cli.emit_object {
+                        exit!(1 => "Emit object flag already set.");
+                    }
+                    cli.emit_object = true
+                }
+                "--names" => {
+                    if cli.names {
+                        exit!(1 => "Names flag already set.");
+                    }
+                    cli.names = true
+                }
+                "--libs" => {
+                    if cli.libs {
+                        exit!(1 => "Libs flag already set.");
+                    }
+                    cli.libs = true
+                }
+                "--resolve" => {
+                    if cli.resolve {
+                        exit!(1 => "Resolve flag already set.");
+                    }
+                    cli.resolve = true
+                }
+                "--security" => {
+                    if cli.security {
+                        exit!(1 => "Security flag already set.");
+                    }
+                    cli.security = true
+                }
+                "--filter" => {
+                    if let Some(pattern) = args.next().as_deref() {
+                        if !NAMES.contains(&pattern) && !ABBRV.contains(&pattern) {
+                            if cli.filter.is_some() {
+                                exit!(1 => "Filter pattern already given.");
+                            }
+                            cli.filter = Some(pattern.to_string());
+                        }
+                    }
+                }
+                "--signature" => {
+                    if let Some(signature) = args.next().as_deref() {
+                        if !NAMES.contains(&signature) && !ABBRV.contains(&signature) {
+                            if cli.signature.is_some() {
+                                exit!(1 => "Signature already given.");
+                            }
+                            cli.signature = Some(signature.to_string());
+                        }
+                    }
+                }
+                "--search" => {
+                    if cli.search.is_some() {
+                        exit!(1 => "Search pattern already given.");
+                    }
+                    match args.next().as_deref().map(parse_pattern) {
+                        Some(Some(pattern)) => cli.search = Some(pattern),
+                        _ => exit!(1 => "Search pattern must be hex bytes with '??' for any byte."),
+                    }
+                }
+                "--keep" => {
+                    let Subcommand::Strip { ref mut keep, .. } = cli.command else {
+                        exit!(1 => "--keep can only be used with 'bite strip'.");
+                    };
+                    if let Some(pattern) = args.next().as_deref() {
+                        if !NAMES.contains(&pattern) && !ABBRV.contains(&pattern) {
+                            if keep.is_some() {
+                                exit!(1 => "Pattern of kept symbols already given.");
+                            }
+                            *keep = Some(pattern.to_string());
+                        }
+                    }
+                }
+                "--top" => {
+                    let (Subcommand::Bloat { ref mut top, .. }
+                    | Subcommand::BloatDiff { ref mut top, .. }) = cli.command
+                    else {
+                        exit!(1 => "--top can only be used with 'bite bloat' and \
+                                    'bite bloat-diff'.");
+                    };
+                    if top.is_some() {
+                        exit!(1 => "Number of listed functions already given.");
+                    }
+                    match args.next().as_deref().map(str::parse) {
+                        Some(Ok(count)) => *top = Some(count),
+                        _ => exit!(1 => "The number of listed functions must be a number."),
+                    }
+                }
+                "--graph" => {
+                    let Subcommand::Entropy { ref mut graph, .. } = cli.command else {
+                        exit!(1 => "--graph can only be used with 'bite entropy'.");
+                    };
+                    if *graph {
+                        exit!(1 => "Graph flag already set.");
+                    }
+                    *graph = true
+                }
+                "--window" => {
+                    let Subcommand::Entropy { ref mut window, .. } = cli.command else {
+                        exit!(1 => "--window can only be used with 'bite entropy'.");
+                    };
+                    if window.is_some() {
+                        exit!(1 => "Window size already given.");
+                    }
+                    match args.next().as_deref().map(str::parse) {
+                        Some(Ok(bytes)) if bytes > 0 => *window = Some(bytes),
+                        _ => exit!(1 => "The window size must be a number of bytes."),
+                    }
+                }
+                "--by-id" => {
+                    let Subcommand::Id { ref mut by_id, .. } = cli.command else {
+                        exit!(1 => "--by-id can only be used with 'bite id'.");
+                    };
+                    if by_id.is_some() {
+                        exit!(1 => "Id already given.");
+                    }
+                    match args.next() {
+                        Some(id) if is_id(&id) => *by_id = Some(id),
+                        _ => exit!(1 => "The id must be hexadecimal, optionally with dashes."),
+                    }
+                }
+                "--at" => {
+                    let Subcommand::Patch { ref mut at, .. } = cli.command else {
+                        exit!(1 => "--at can only be used with 'bite patch'.");
+                    };
+                    if at.is_some() {
+                        exit!(1 => "Address to patch already given.");
+                    }
+                    match args.next().as_deref().map(parse_addr) {
+                        Some(Some(addr)) => *at = Some(addr),
+                        _ => exit!(1 => "Address to patch must be a number."),
+                    }
+                }
+                "--assemble" => {
+                    let Subcommand::Patch { ref mut assemble, .. } = cli.command else {
+                        exit!(1 => "--assemble can only be used with 'bite patch'.");
+                    };
+                    if assemble.is_some() {
+                        exit!(1 => "Instructions to assemble already given.");
+                    }
+                    match args.next() {
+                        Some(asm) => *assemble = Some(asm),
+                        None => exit!(1 => "Missing instructions to assemble."),
+                    }
+                }
+                "--fix-checksum" => {
+                    let Subcommand::Patch { ref mut fix_checksum, .. } = cli.command else {
+                        exit!(1 => "--fix-checksum can only be used with 'bite patch'.");
+                    };
+                    if *fix_checksum {
+                        exit!(1 => "Fix checksum flag already set.");
+                    }
+                    *fix_checksum = true
+                }
+                "--dry-run" => {
+                    let Subcommand::Patch { ref mut dry_run, .. } = cli.command else {
+                        exit!(1 => "--dry-run can only be used with 'bite patch'.");
+                    };
+                    if *dry_run {
+                        exit!(1 => "Dry run flag already set.");
+                    }
+                    *dry_run = true
+                }
+                "--root" => {
+                    let Subcommand::Serve { ref mut root, .. } = cli.command else {
+                        exit!(1 => "--root can only be used with 'bite serve'.");
+                    };
+                    if root.is_some() {
+                        exit!(1 => "Root directory already given.");
+                    }
+                    match args.next() {
+                        Some(dir) if !dir.starts_with('-') => *root = Some(PathBuf::from(dir)),
+                        _ => exit!(1 => "Missing the directory to open modules from."),
+                    }
+                }
+                "--sort" => {
+                    let Subcommand::Functions { ref mut sort } = cli.command else {
+                        exit!(1 => "--sort can only be used with 'bite functions'.");
+                    };
+                    if sort.is_some() {
+                        exit!(1 => "Sort order already given.");
+                    }
+                    match args.next().as_deref().and_then(parse_sort) {
+                        Some(order) => *sort = Some(order),
+                        None => exit!(1 => "Sort order must be 'address', 'size' or 'name'."),
+                    }
+                }
+                arg if arg == "--simplify" || arg.starts_with("--simplify=") => {
+                    if packs.is_some() {
+                        exit!(1 => "Libraries to simplify already given.");
+                    }
+                    let names = match arg.strip_prefix("--simplify=") {
+                        Some(names) => names
+                            .split(',')
+                            .map(|name| match Pack::from_name(name) {
+                                Some(pack) => pack,
+                                None => exit!(1 => "Library to simplify must be 'std', 'boost' or \
+                                                    'qt', not '{name}'."),
+                            })
+                            .collect(),
+                        None => Pack::ALL.to_vec(),
+                    };
+                    packs = Some(names);
+                }
+                "-s" | "-ss" | "-sss" => {
+                    if level.is_some() {
+                        exit!(1 => "Simplification level already given.");
+                    }
+                    level = match arg.as_str() {
+                        "-s" => Some(Level::Types),
+                        "-ss" => Some(Level::Namespaces),
+                        _ => Some(Level::Parameters),
+                    };
+                }
+                "--fold" => {
+                    if cli.fold {
+                        exit!(1 => "Fold flag already set.");
+                    }
+                    cli.fold = true
+                }
+                "--expand" => {
+                    if cli.expand {
+                        exit!(1 => "Expand flag already set.");
+                    }
+                    cli.expand = true
+                }
+                "--no-calling-conv" | "--no-access" | "--no-return-type" | "--no-cv" => {
+                    let kind = match arg.as_str() {
+                        "--no-calling-conv" => Kind::CALLING_CONV,
+                        "--no-access" => Kind::ACCESS,
+                        "--no-return-type" => Kind::RETURN_TYPE,
+                        _ => Kind::QUALIFIER,
+                    };
+                    if cli.omit.contains(kind) {
+                        exit!(1 => "Flag {arg} already set.");
+                    }
+                    cli.omit |= kind;
+                }
+                "--json" => {
+                    let Subcommand::Demangle { ref mut json } = cli.command else {
+                        exit!(1 => "--json can only be used with 'bite demangle'.");
+                    };
+                    if *json {
+                        exit!(1 => "JSON flag already set.");
+                    }
+                    *json = true
+                }
+                "--crate" => {
+                    if cli.krate.is_some() {
+                        exit!(1 => "Crate already given.");
+                    }
+                    match args.next() {
+                        Some(krate) => cli.krate = Some(krate),
+                        None => exit!(1 => "Missing name of crate."),
+                    }
+                }
+                "--strip-debug" => {
+                    let Subcommand::Strip { ref mut strip_debug, .. } = cli.command else {
+                        exit!(1 => "--strip-debug can only be used with 'bite strip'.");
+                    };
+                    if *strip_debug {
+                        exit!(1 => "Strip debug flag already set.");
+                    }
+                    *strip_debug = true
+                }
+                "--gpu" => {
+                    if cli.gpu {
+                        exit!(1 => "GPU flag already set.");
+                    }
+                    cli.gpu = true
+                }
+                "--extract-gpu" => {
+                    if cli.extract_gpu.is_some() {
+                        exit!(1 => "GPU blob to extract already given.");
+                    }
+                    match args.next().as_deref().map(str::parse) {
+                        Some(Ok(idx)) => cli.extract_gpu = Some(idx),
+                        _ => exit!(1 => "GPU blob to extract must be given by its index."),
+                    }
+                }
+                "--extract-section" => {
+                    if let Some(name) = args.next().as_deref() {
+                        if !NAMES.contains(&name) && !ABBRV.contains(&name) {
+                            if cli.extract_section.is_some() {
+                                exit!(1 => "Section to extract already given.");
+                            }
+                            cli.extract_section = Some(name.to_string());
+                        }
+                    }
+                }
+                "--add-section" => {
+                    if cli.add_section.is_some() {
+                        exit!(1 => "Section to add already given.");
+                    }
+                    match args.next().as_deref().and_then(|arg| arg.split_once('=')) {
+                        Some((name, path)) if !name.is_empty() => {
+                            cli.add_section = Some((name.to_string(), PathBuf::from(path)))
+                        }
+                        _ => exit!(1 => "Section to add must be given as NAME=FILE."),
+                    }
+                }
+                "--section" => {
+                    let (Subcommand::Extract { ref mut section, .. }
+                    | Subcommand::Hex { ref mut section, .. }) = cli.command
+                    else {
+                        exit!(1 => "--section can only be used with 'bite extract' and \
+                                    'bite hex'.");
+                    };
+                    if section.is_some() {
+                        exit!(1 => "Section already given.");
+                    }
+                    match args.next() {
+                        Some(name) if !name.starts_with('-') => *section = Some(name),
+                        _ => exit!(1 => "Missing the name of the section."),
+                    }
+                }
+                "--all-sections" => {
+                    let Subcommand::Extract { ref mut all_sections, .. } = cli.command else {
+                        exit!(1 => "--all-sections can only be used with 'bite extract'.");
+                    };
+                    if all_sections.is_some() {
+                        exit!(1 => "Directory to extract sections to already given.");
+                    }
+                    match args.next() {
+                        Some(dir) if !dir.starts_with('-') => {
+                            *all_sections = Some(PathBuf::from(dir))
+                        }
+                        _ => exit!(1 => "Missing the directory to extract sections to."),
+                    }
+                }
+                "--trace" => {
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            if cli.trace.is_some() {
+                                exit!(1 => "Path to trace already given.");
+                            }
+                            cli.trace = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--trace-base" => {
+                    if cli.trace_base.is_some() {
+                        exit!(1 => "Trace base address already given.");
+                    }
+                    match args.next().as_deref().map(parse_addr) {
+                        Some(Some(addr)) => cli.trace_base = Some(addr),
+                        _ => exit!(1 => "Trace base must be a decimal or hexadecimal address."),
+                    }
+                }
+                "--callgraph" => {
+                    if cli.callgraph.is_some() {
+                        exit!(1 => "Call graph format already given.");
+                    }
+                    match args.next().as_deref() {
+                        Some("dot") => cli.callgraph = Some(GraphFormat::Dot),
+                        Some("json") => cli.callgraph = Some(GraphFormat::Json),
+                        _ => exit!(1 => "Call graph format must be either 'dot' or 'json'."),
+                    }
+                }
+                "--format" => {
+                    if cli.format.is_some() {
+                        exit!(1 => "Listing format already given.");
+                    }
+                    match args.next().as_deref() {
+                        Some("html") => cli.format = Some(ListingFormat::Html),
+                        _ => exit!(1 => "Listing format must be 'html'."),
+                    }
+                }
+                "--callgraph-root" => {
+                    if let Some(symbol) = args.next().as_deref() {
+                        if !NAMES.contains(&symbol) && !ABBRV.contains(&symbol) {
+                            if cli.callgraph_root.is_some() {
+                                exit!(1 => "Call graph root already given.");
+                            }
+                            cli.callgraph_root = Some(symbol.to_string());
+                        }
+                    }
+                }
+                "--clones" => {
+                    if cli.clones {
+                        exit!(1 => "Clones flag already set.");
+                    }
+                    cli.clones = true
+                }
+                "--registers" => {
+                    if cli.registers {
+                        exit!(1 => "Registers flag already set.");
+                    }
+                    cli.registers = true
+                }
+                "--headers" => {
+                    if cli.headers {
+                        exit!(1 => "Headers flag already set.");
+                    }
+                    cli.headers = true
+                }
+                "--relocs" => {
+                    if cli.relocs {
+                        exit!(1 => "Relocs flag already set.");
+                    }
+                    cli.relocs = true
+                }
+                "--unwind" => {
+                    if cli.unwind {
+                        exit!(1 => "Unwind flag already set.");
+                    }
+                    cli.unwind = true
+                }
+                "--tls" => {
+                    if cli.tls {
+                        exit!(1 => "TLS flag already set.");
+                    }
+                    cli.tls = true
+                }
+                "--aslr-sim" => {
+                    if cli.aslr_sim {
+                        exit!(1 => "ASLR simulation flag already set.");
+                    }
+                    cli.aslr_sim = true
+                }
+                "--source" => {
+                    if cli.source {
+                        exit!(1 => "Source flag already set.");
+                    }
+                    cli.source = true
+                }
+                "--no-pager" => {
+                    if cli.no_pager {
+                        exit!(1 => "No pager flag already set.");
+                    }
+                    cli.no_pager = true
+                }
+                arg if arg == "--color" || arg.starts_with("--color=") => {
+                    if cli.color.is_some() {
+                        exit!(1 => "Color mode already given.");
+                    }
+                    let mode = match arg.strip_prefix("--color=") {
+                        Some(mode) => Some(mode.to_string()),
+                        None => args.next(),
+                    };
+                    match mode.as_deref().and_then(parse_color) {
+                        Some(mode) => cli.color = Some(mode),
+                        None => exit!(1 => "Color mode must be 'auto', 'always' or 'never'."),
+                    }
+                }
+                "--no-bytes" => {
+                    if cli.no_bytes {
+                        exit!(1 => "No bytes flag already set.");
+                    }
+                    cli.no_bytes = true
+                }
+                "--radix" => {
+                    if cli.radix.is_some() {
+                        exit!(1 => "Radix already given.");
+                    }
+                    match args.next().as_deref().and_then(parse_radix) {
+                        Some(radix) => cli.radix = Some(radix),
+                        None => exit!(1 => "Radix must be either 'hex', 'dec' or 'auto'."),
+                    }
+                }
+                "--no-hex-prefix" => {
+                    if cli.no_hex_prefix {
+                        exit!(1 => "No hex prefix flag already set.");
+                    }
+                    cli.no_hex_prefix = true
+                }
+                "--zero-pad" => {
+                    if cli.zero_pad {
+                        exit!(1 => "Zero pad flag already set.");
+                    }
+                    cli.zero_pad = true
+                }
+                "--decode-strings" => {
+                    if cli.decode_strings {
+                        exit!(1 => "Decode strings flag already set.");
+                    }
+                    cli.decode_strings = true
+                }
+                path if !path.starts_with('-') || path == "-" => cli.add_objects(path),
+                unknown => {
+                    let mut distance = u32::MAX;
+                    let mut best_guess = "";
+                    for name in NAMES {
+                        let d = triple_accel::levenshtein_exp(unknown.as_bytes(), name.as_bytes());
+                        if d < distance {
+                            distance = d;
+                            best_guess = name;
+                        }
+                    }
+
+                    // A guess that's less than 3 `steps` away from a correct arg.
+                    if distance < 4 {
+                        exit!(1 => "Unknown cmd arg '{unknown}' did you mean '{best_guess}'?")
+                    } else {
+                        exit!(1 => "Unknown cmd arg '{unknown}' was entered.");
+                    }
+                }
+            }
+        }
+
+        if packs.is_some() || level.is_some() {
+            cli.simplify = Some(Simplify {
+                packs: packs.unwrap_or_else(|| Pack::ALL.to_vec()),
+                level: level.unwrap_or(Level::Types),
+            });
+        }
+
+        if tui && cli.headless() {
+            exit!(1 => "'bite tui' opens the GUI, so it can't print any information.");
+        }
+
+        cli.apply_config();
+        cli.validate_args();
+        cli
+    }
+
+    /// Add the objects matching `path`, the first one being the one that's disassembled.
+    fn add_objects(&mut self, path: &str) {
+        for path in expand_glob(path) {
+            if self.path == Path::new("") {
+                self.path = path;
+            } else {
+                self.paths.push(path);
+            }
+        }
+    }
+
+    /// Every object information is printed about.
+    pub fn objects(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.path.as_path()).chain(self.paths.iter().map(PathBuf::as_path))
+    }
+
+    /// Fill in the options that weren't given with the defaults of the config.
+    fn apply_config(&mut self) {
+        if let Some(ref path) = self.config {
+            config::set_path(path.clone());
+        }
+
+        let defaults = &config::CONFIG.defaults;
+        if defaults.pager == Some(false) {
+            self.no_pager = true;
+        }
+
+        // Bytes that are patched in aren't disassembled.
+        let patch = matches!(self.command, Subcommand::Patch { .. });
+        if (self.raw || self.bytes.is_some()) && !patch && self.arch.is_none() {
+            self.arch = defaults.arch.clone();
+        }
+
+        if let (None, Some(radix)) = (self.radix, &defaults.radix) {
+            match parse_radix(radix) {
+                Some(radix) => self.radix = Some(radix),
+                None => exit!(1 => "Radix of the config must be either 'hex', 'dec' or 'auto'."),
+            }
+        }
+
+        if let (None, Some(color)) = (self.color, &defaults.color) {
+            match parse_color(color) {
+                Some(color) => self.color = Some(color),
+                None => exit!(1 => "Color mode of the config must be either 'auto', 'always' or \
+                                    'never'."),
+            }
+        }
+    }
+
+    /// Whether each kind of information that can be printed was asked for with a flag.
+    fn reports(&self) -> [bool; 20] {
+        [
+            self.names,
+            self.libs,
+            self.security,
+            self.headers,
+            self.relocs,
+            self.unwind,
+            self.tls,
+            self.filter.is_some(),
+            self.signature.is_some(),
+            self.search.is_some(),
+            self.gpu,
+            self.clones,
+            self.registers,
+            self.trace.is_some(),
+            self.callgraph.is_some(),
+            self.format.is_some(),
+            self.dump_bytes.is_some(),
+            self.extract_gpu.is_some(),
+            self.extract_section.is_some(),
+            self.add_section.is_some(),
+        ]
+    }
+
+    /// Whether any information is printed instead of opening the GUI.
+    pub fn headless(&self) -> bool {
+        self.command.reports() || self.reports().contains(&true)
+    }
+
+    /// Whether bytes or an object are written to `output`, rather than the printed information.
+    fn writes_object(&self) -> bool {
+        self.dump_bytes.is_some()
+            || self.extract_gpu.is_some()
+            || self.extract_section.is_some()
+            || self.add_section.is_some()
+            || matches!(self.command, Subcommand::Extract { .. } | Subcommand::Patch { .. })
+    }
+
+    /// File the printed information is written to instead of stdout.
+    pub fn text_output(&self) -> Option<&Path> {
+        let prints = self.headless() || self.bytes.is_some();
+        match prints && !self.writes_object() {
+            true => self.output.as_deref(),
+            false => None,
+        }
+    }
+
+    /// Whether the printed information is shown through a pager, only when printing to a
+    /// terminal.
+    pub fn paged(&self) -> bool {
+        let prints = self.headless() || self.bytes.is_some();
+        prints
+            && !self.no_pager
+            && !self.writes_object()
+            && self.output.is_none()
+            && std::io::stdout().is_terminal()
+    }
+
+    /// Whether printed text is colored, by default only on a terminal and if the `NO_COLOR`
+    /// environment variable isn't set.
+    pub fn colored(&self) -> bool {
+        match self.color.unwrap_or(ColorMode::Auto) {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|var| !var.is_empty());
+                !no_color && self.text_output().is_none() && std::io::stdout().is_terminal()
+            }
+        }
+    }
+
+    /// Whether the object is read from stdin, given as '-'.
+    pub fn is_stdin(&self) -> bool {
+        self.path == Path::new("-")
+    }
+
+    /// Whether the object is read over HTTP rather than from disk.
+    pub fn is_remote(&self) -> bool {
+        self.path
+            .to_str()
+            .is_some_and(|path| path.starts_with("http://") || path.starts_with("https://"))
+    }
+
+    fn validate_args(&mut self) {
+        if let (Some(start), Some(stop)) = (self.start_addr, self.stop_addr) {
+            if start >= stop {
+                exit!(1 => "Start address {start:#x} must come before stop address {stop:#x}.");
+            }
+        }
+
+        if self.raw && self.arch.is_none() {
+            exit!(1 => "Raw objects require an architecture to be given with --arch.");
+        }
+
+        let patch = matches!(self.command, Subcommand::Patch { .. });
+        if self.bytes.is_some() && !patch && self.arch.is_none() {
+            exit!(1 => "Disassembling bytes requires an architecture to be given with --arch.");
+        }
+
+        if !self.raw && self.bytes.is_none() && (self.arch.is_some() || self.base.is_some()) {
+            exit!(1 => "An architecture and base address can only be given for raw objects.");
+        }
+
+        if self.aslr_sim && (self.raw || self.layout.is_some()) {
+            exit!(1 => "ASLR can only be simulated for objects with a header.");
+        }
+
+        if self.raw && self.layout.is_some() {
+            exit!(1 => "An object can't be both raw and a memory dump.");
+        }
+
+        if self.member.is_some() && (self.raw || self.layout.is_some()) {
+            exit!(1 => "A member can only be selected from an archive.");
+        }
+
+        if self.dsym.is_some() && (self.raw || self.layout.is_some()) {
+            exit!(1 => "A dSYM can only be given for Mach-O objects.");
+        }
+
+        let outputs = [
+            self.dump_bytes.is_some(),
+            self.extract_gpu.is_some(),
+            self.extract_section.is_some(),
+            self.add_section.is_some(),
+        ];
+        let outputs = outputs.into_iter().filter(|&output| output).count();
+
+        if outputs > 1 {
+            exit!(1 => "Only one of --dump-bytes, --extract-gpu, --extract-section and \
+                        --add-section can be written to --output.");
+        }
+
+        match self.command {
+            Subcommand::Extract { ref section, ref all_sections } => {
+                match (section, all_sections) {
+                    (Some(_), None) if self.output.is_none() => {
+                        exit!(1 => "Extracting a section requires --output.")
+                    }
+                    (None, Some(_)) if self.output.is_some() => {
+                        exit!(1 => "--all-sections writes to a directory instead of --output.")
+                    }
+                    (Some(_), None) | (None, Some(_)) => {}
+                    _ => exit!(1 => "'bite extract' requires either --section or --all-sections."),
+                }
+            }
+            Subcommand::Hex { ref section, len } => {
+                if section.is_some() && (self.start_addr.is_some() || self.stop_addr.is_some()) {
+                    exit!(1 => "A section to dump can't be combined with addresses.");
+                }
+
+                if len.is_some() && self.stop_addr.is_some() {
+                    exit!(1 => "Only one of --len and --stop-address can be given.");
+                }
+            }
+            Subcommand::Strip { ref keep, strip_debug, .. } => {
+                if self.output.is_none() {
+                    exit!(1 => "Stripping an object requires --output.");
+                }
+
+                if keep.is_some() && strip_debug {
+                    exit!(1 => "Symbols can't be kept when only debug info is removed.");
+                }
+            }
+            Subcommand::Patch { at, ref assemble, dry_run, .. } => {
+                if at.is_none() || self.bytes.is_some() == assemble.is_some() {
+                    exit!(1 => "Patching an object requires --at and either --bytes or \
+                                --assemble.");
+                }
+
+                if dry_run && self.output.is_some() {
+                    exit!(1 => "A dry run doesn't write to --output.");
+                }
+            }
+            _ if outputs == 1 && self.output.is_none() => {
+                exit!(1 => "--dump-bytes, --extract-gpu, --extract-section and --add-section \
+                            require --output.");
+            }
+            _ if self.output.is_some() && !self.headless() && self.bytes.is_none() => {
+                exit!(1 => "Writing to --output requires information to print or one of \
+                            --dump-bytes, --extract-gpu, --extract-section or --add-section.");
+            }
+            _ => {}
+        }
+
+        let names = self.names || self.command == Subcommand::Nm;
+        let functions = matches!(self.command, Subcommand::Functions { .. });
+        let demangle = matches!(self.command, Subcommand::Demangle { .. });
+        if self.simplify.is_some() && !functions && !names && !demangle {
+            exit!(1 => "Only --names, 'bite functions' and 'bite demangle' simplify names.");
+        }
+
+        if !self.omit.is_empty() && !functions && !names && !demangle {
+            exit!(1 => "Only --names, 'bite functions' and 'bite demangle' omit parts of names.");
+        }
+
+        if self.krate.is_some() && !names {
+            exit!(1 => "--crate can only be used with --names.");
+        }
+
+        if self.fold && !functions && !names {
+            exit!(1 => "--fold can only be used with --names and 'bite functions'.");
+        }
+
+        if self.expand && !self.fold {
+            exit!(1 => "--expand lists the functions folded by --fold, which wasn't given.");
+        }
+
+        if self.emit_object && self.dump_bytes.is_none() {
+            exit!(1 => "An object can only be emitted when dumping a function.");
+        }
+
+        if self.callgraph_root.is_some() && self.callgraph.is_none() {
+            exit!(1 => "A call graph root requires a format given with --callgraph.");
+        }
+
+        if self.trace_base.is_some() && self.trace.is_none() {
+            exit!(1 => "A trace base address requires a trace given with --trace.");
+        }
+
+        if let Some(ref path) = self.trace {
+            if !path.exists() {
+                exit!(1 => "Trace {path:?} does not exist.");
+            }
+        }
+
+        if self.resolve && !self.libs && self.command != Subcommand::Libs {
+            exit!(1 => "Resolving libraries requires --libs.");
+        }
+
+        if self.headless() && self.path == Path::new("") {
+            exit!(1 => "Printing information requires an object given with --disassemble.");
+        }
+
+        if let Some((_, ref path)) = self.add_section {
+            if !path.exists() {
+                exit!(1 => "Contents of the section {path:?} do not exist.");
+            }
+        }
+
+        match self.command {
+            Subcommand::Patch { ref object, .. } if !object.is_file() => {
+                exit!(1 => "Object {object:?} does not exist.");
+            }
+            Subcommand::Id { ref path, by_id: Some(_) } if !path.is_dir() => {
+                exit!(1 => "Directory {path:?} does not exist.");
+            }
+            Subcommand::Strip { ref object, .. }
+            | Subcommand::Bloat { ref object, .. }
+            | Subcommand::Entropy { ref object, .. }
+            | Subcommand::PeInfo { ref object }
+            | Subcommand::Codesign { ref object }
+            | Subcommand::Objc { ref object }
+            | Subcommand::Id { path: ref object, .. }
+                if !object.exists() =>
+            {
+                exit!(1 => "Object {object:?} does not exist.");
+            }
+            Subcommand::AbiCheck { ref old, ref new }
+            | Subcommand::BloatDiff { ref old, ref new, .. } => {
+                for path in [old, new] {
+                    if !path.exists() {
+                        exit!(1 => "Object {path:?} does not exist.");
+                    }
+                }
+            }
+            Subcommand::Serve { root: Some(ref root), .. } if !root.is_dir() => {
+                exit!(1 => "Directory {root:?} does not exist.");
+            }
+            Subcommand::DecodeSelftest { ref corpus } if !corpus.exists() => {
+                exit!(1 => "Corpus {corpus:?} does not exist.");
+            }
+            Subcommand::DemangleSelftest { ref corpora } => {
+                for corpus in corpora {
+                    if !corpus.exists() {
+                        exit!(1 => "Corpus {corpus:?} does not exist.");
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if self.path == Path::new("") {
+            // exit!(1 => "You must provide a path to disassemble.");
+            return;
+        }
+
+        if !self.paths.is_empty() {
+            if !self.headless() || self.writes_object() || self.format.is_some() {
+                exit!(1 => "Several objects can only be given when printing information.");
+            }
+
+            if self.is_remote() {
+                exit!(1 => "Objects read over HTTP can only be given on their own.");
+            }
+
+            if self.objects().filter(|&path| path == Path::new("-")).count() > 1 {
+                exit!(1 => "Stdin can only be read once.");
+            }
+
+            for path in self.paths.iter() {
+                if path != Path::new("-") && !path.exists() {
+                    exit!(1 => "Object {path:?} does not exist.");
+                }
+            }
+        }
+
+        if self.is_remote() {
+            if !self.names {
+                exit!(1 => "Objects read over HTTP can only be listed with --names.");
+            }
+        } else if !self.is_stdin() && !self.path.exists() {
+            exit!(1 => "Object {:?} does not exist.", self.path);
+        }
+
+        if let Some(ref cfg) = self.config {
+            if !cfg.exists() {
+                exit!(1 => "Config {cfg:?} does not exist.");
+            }
+        }
+
+        if let Some(ref map) = self.kallsyms {
+            if !map.exists() {
+                exit!(1 => "Symbol map {map:?} does not exist.");
+            }
+        }
+
+        if let Some(ref map) = self.symbol_map {
+            if !map.exists() {
+                exit!(1 => "Symbol map {map:?} does not exist.");
+            }
+        }
+
+        if let Some(ref dsym) = self.dsym {
+            if !dsym.exists() {
+                exit!(1 => "dSYM {dsym:?} does not exist.");
+            }
+        }
+
+        if let Some(ref dwp) = self.dwp {
+            if !dwp.exists() {
+                exit!(1 => "DWARF package {dwp:?} does not exist.");
+            }
+        }
+
+        if let Some(ref dir) = self.build_dir {
+            if !dir.is_dir() {
+                exit!(1 => "Build directory {dir:?} does not exist.");
+            }
+        }
+
+        if let Some(ref layout) = self.layout {
+            if !layout.exists() {
+                exit!(1 => "Layout {layout:?} does not exist.");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_bytes() {
+        assert_eq!(parse_hex_bytes("f3 48 a5"), Some(vec![0xf3, 0x48, 0xa5]));
+        assert_eq!(parse_hex_bytes("f348a5"), Some(vec![0xf3, 0x48, 0xa5]));
+        assert_eq!(parse_hex_bytes("0xf3, 0x48,0xa5"), Some(vec![0xf3, 0x48, 0xa5]));
+        assert_eq!(parse_hex_bytes("\\xf3\\x48\\xa5"), Some(vec![0xf3, 0x48, 0xa5]));
+        assert_eq!(parse_hex_bytes("f3 4"), None);
+        assert_eq!(parse_hex_bytes("zz"), None);
+        assert_eq!(parse_hex_bytes(""), None);
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_matches(b"*.so", b"libfoo.so"));
+        assert!(glob_matches(b"lib?.so*", b"liba.so.1"));
+        assert!(glob_matches(b"*", b""));
+        assert!(!glob_matches(b"*.so", b"libfoo.a"));
+        assert!(!glob_matches(b"lib?.so", b"libab.so"));
+        assert!(glob_matches(b"*a*b*", b"xaxxbx"));
+        assert!(!glob_matches(b"*a*b", b"xaxxbx"));
+        // Would take exponential time to fail if stars were retried recursively.
+        let pattern = [b"a*".repeat(32), b"b".to_vec()].concat();
+        assert!(!glob_matches(&pattern, &[b'a'; 64]));
+    }
+
+    #[test]
+    fn pattern() {
+        let pattern = Some(vec![Some(0x48), Some(0x8b), None, None, Some(0xe8)]);
+        assert_eq!(parse_pattern("48 8B ?? ?? E8"), pattern);
+        let pattern = Some(vec![Some(0xe8), None, Some(0xc3)]);
+        assert_eq!(parse_pattern("e8 ? c3"), pattern);
+        assert_eq!(parse_pattern("?? ??"), None);
+        assert_eq!(parse_pattern("488b"), None);
+        assert_eq!(parse_pattern("zz"), None);
+        assert_eq!(parse_pattern(""), None);
     }
 }
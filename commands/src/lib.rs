@@ -2,7 +2,9 @@ mod cli;
 mod debug;
 mod gui;
 
-pub use cli::Cli;
+pub use cli::{
+    parse_hex_bytes, Cli, ColorMode, GraphFormat, ListingFormat, Radix, SortBy, Subcommand,
+};
 pub use gui::{Command, Error as CommandError, HELP as CMD_HELP};
 use once_cell::sync::Lazy;
 
@@ -1,16 +1,61 @@
 use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::sync::OnceLock;
 
 pub static CONFIG: Lazy<Config> = Lazy::new(Config::parse);
 
+/// Config given on the command line, read instead of the one in the data directory.
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Config of the project in the working directory, layered on top of the user's config.
+const PROJECT_CONFIG: &str = ".bite.yaml";
+
+/// Read the config at `path` instead of the one in the data directory. Has to be called before
+/// [`CONFIG`] is first used.
+pub fn set_path(path: PathBuf) {
+    let _ = PATH.set(path);
+}
+
 use egui::Color32;
 use serde::de::{self, Deserializer, Visitor};
 use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
 use std::fmt;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
+    /// Preset the colors are based on, see [`themes`].
+    #[serde(default)]
+    pub theme: Option<String>,
     #[serde(default = "defaults::colors")]
     pub colors: Colors,
+    /// Options used when they aren't given on the command line.
+    #[serde(default)]
+    pub defaults: Defaults,
+    /// Rewrites of demangled names done by `--simplify`, after the builtin ones.
+    #[serde(default)]
+    pub replace: Vec<Rewrite>,
+}
+
+/// Rewrite of a demangled name, e.g. `from: 'absl::lts_*::'` and `to: 'absl::'`.
+///
+/// A `*` in the pattern matches any type or part of a path, with its brackets balanced. What
+/// each of them matched is written to the replacement as `$1`, `$2` and so on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rewrite {
+    pub from: String,
+    pub to: String,
+}
+
+/// Options of the command line, written like their flags, e.g. `radix: dec` for `--radix dec`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Defaults {
+    /// Whether printed information is paged on a terminal.
+    pub pager: Option<bool>,
+    /// Architecture of raw objects and of `--bytes`.
+    pub arch: Option<String>,
+    pub radix: Option<String>,
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -138,6 +183,94 @@ impl Colors {
     }
 }
 
+/// Built-in color presets, selected with `theme: <name>` in the config.yaml.
+///
+/// Each one is written like the colors field of the config.yaml, which is layered on top of it so
+/// that a theme can be tweaked without repeating all of it. The field names are the kinds of
+/// tokens rather than their colors, so a theme works for every part of the program.
+pub mod themes {
+    /// The colors used when no theme is given, for a dark background.
+    pub const DARK: &str = "";
+
+    pub const LIGHT: &str = "
+src:
+  keyword: '#b23c00'
+  tipe: '#8a5a00'
+  field: '#1d6a99'
+  function: '#087a3c'
+  operator: '#a05f00'
+  string: '#087a3c'
+  variable: '#8e3b87'
+  constant: '#6a3591'
+  highlight: '#d04a00'
+asm:
+  section: '#1f6f8f'
+  opcode: '#202020'
+  component: '#b0304d'
+  register: '#b0304d'
+  label: '#1f6f8f'
+  segment: '#087a3c'
+  invalid: '#c81e1e'
+  pointer: '#c81e1e'
+  expr: '#5a5a5a'
+  immediate: '#1f6f8f'
+  annotation: '#1f6f8f'
+  primitive: '#2f7a6e'
+  string: '#94620b'
+comment: '#707070'
+address: '#a0a0a0'
+brackets: '#808080'
+bytes: '#087a3c'
+delimiter: '#707070'
+bg_primary: '#f5f5f5'
+bg_secondary: '#ebebeb'
+";
+
+    pub const MONOCHROME: &str = "
+src:
+  keyword: '#ffffff'
+  tipe: '#c8c8c8'
+  field: '#c8c8c8'
+  function: '#ffffff'
+  operator: '#c8c8c8'
+  string: '#a0a0a0'
+  variable: '#c8c8c8'
+  constant: '#c8c8c8'
+  highlight: '#ffffff'
+asm:
+  section: '#ffffff'
+  opcode: '#ffffff'
+  component: '#c8c8c8'
+  register: '#c8c8c8'
+  label: '#ffffff'
+  segment: '#c8c8c8'
+  invalid: '#ffffff'
+  pointer: '#c8c8c8'
+  expr: '#a0a0a0'
+  immediate: '#c8c8c8'
+  annotation: '#c8c8c8'
+  primitive: '#c8c8c8'
+  string: '#a0a0a0'
+comment: '#808080'
+address: '#606060'
+brackets: '#808080'
+bytes: '#a0a0a0'
+delimiter: '#808080'
+bg_primary: '#303030'
+bg_secondary: '#2d2d2d'
+";
+
+    /// Colors of the preset named `name`.
+    pub fn by_name(name: &str) -> Option<&'static str> {
+        match name {
+            "dark" => Some(DARK),
+            "light" => Some(LIGHT),
+            "monochrome" => Some(MONOCHROME),
+            _ => None,
+        }
+    }
+}
+
 /// Default color values when one is missing in the config.yaml's colors field.
 /// Important to note is that any update to these defaults should also be reflected in the
 /// config.yaml.
@@ -254,17 +387,19 @@ mod defaults {
 
 impl Config {
     pub fn parse() -> Self {
-        let path = match dirs::data_dir() {
-            Some(mut dir) => {
+        let path = match (PATH.get(), dirs::data_dir()) {
+            (Some(path), _) => path.clone(),
+            (None, Some(mut dir)) => {
                 dir.push("bite");
                 dir.push("config.yaml");
                 dir
             },
-            None => log::error!("You must have a data directory set."),
+            (None, None) => log::error!("You must have a data directory set."),
         };
 
         let raw = std::fs::read_to_string(path).unwrap_or_default();
-        match serde_yaml::from_str(&raw) {
+        let project = std::fs::read_to_string(PROJECT_CONFIG).unwrap_or_default();
+        match Self::parse_str(&raw, &project) {
             Ok(parsed) => parsed,
             Err(err) => {
                 log::warning!("Failed to parse config.\nError: {err}.");
@@ -274,6 +409,98 @@ impl Config {
             }
         }
     }
+
+    /// Parse a config with the one of the project on top of it, with its colors layered on top
+    /// of the ones of its theme.
+    fn parse_str(raw: &str, project: &str) -> Result<Self, String> {
+        let mut config = mapping(raw)?;
+        merge(&mut config, mapping(project)?);
+
+        let theme = match config.get("theme") {
+            Some(Value::String(name)) => themes::by_name(name).ok_or_else(|| {
+                format!("Unknown theme '{name}', expected dark, light or monochrome")
+            })?,
+            Some(_) => return Err("The theme must be the name of a preset".to_string()),
+            None => themes::DARK,
+        };
+
+        let mut colors: Value = serde_yaml::from_str(theme).map_err(|err| err.to_string())?;
+        if let Some(overrides) = config.get("colors") {
+            merge(&mut colors, overrides.clone());
+        }
+
+        if let Value::Mapping(ref mut config) = config {
+            if !colors.is_null() {
+                config.insert(Value::from("colors"), colors);
+            }
+        }
+
+        serde_yaml::from_value(config).map_err(|err| err.to_string())
+    }
+}
+
+/// Parse YAML that's either empty or a mapping.
+fn mapping(raw: &str) -> Result<Value, String> {
+    match serde_yaml::from_str(raw).map_err(|err| err.to_string())? {
+        Value::Null => Ok(Value::Mapping(Mapping::new())),
+        Value::Mapping(mapping) => Ok(Value::Mapping(mapping)),
+        _ => Err("A config must be a mapping of its fields".to_string()),
+    }
+}
+
+/// Replace the fields of `base` with the ones given in `overrides`, recursing into mappings.
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Mapping(base), Value::Mapping(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(field) => merge(field, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Color of the xterm 256-color palette at `idx`.
+fn xterm_color(idx: u8) -> Color32 {
+    const SYSTEM: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0x80, 0x00, 0x00),
+        (0x00, 0x80, 0x00),
+        (0x80, 0x80, 0x00),
+        (0x00, 0x00, 0x80),
+        (0x80, 0x00, 0x80),
+        (0x00, 0x80, 0x80),
+        (0xc0, 0xc0, 0xc0),
+        (0x80, 0x80, 0x80),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x00, 0x00, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+
+    match idx {
+        0..=15 => {
+            let (r, g, b) = SYSTEM[idx as usize];
+            Color32::from_rgb(r, g, b)
+        }
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let idx = idx - 16;
+            Color32::from_rgb(level(idx / 36), level(idx / 6 % 6), level(idx % 6))
+        }
+        232..=255 => {
+            let gray = 8 + (idx - 232) * 10;
+            Color32::from_rgb(gray, gray, gray)
+        }
+    }
 }
 
 fn color32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
@@ -282,13 +509,20 @@ fn color32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Err
         type Value = Color32;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("expected hex color values")
+            formatter.write_str("expected hex color values or an index of the 256-color palette")
         }
 
         fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
             Color32::from_hex(s).map_err(|err| E::custom(format!("{err:?}")))
         }
+
+        fn visit_u64<E: de::Error>(self, idx: u64) -> Result<Self::Value, E> {
+            match u8::try_from(idx) {
+                Ok(idx) => Ok(xterm_color(idx)),
+                Err(_) => Err(E::custom(format!("color {idx} isn't in the 256-color palette"))),
+            }
+        }
     }
 
-    deserializer.deserialize_str(ColorParsing)
+    deserializer.deserialize_any(ColorParsing)
 }
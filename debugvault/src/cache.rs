@@ -0,0 +1,404 @@
+//! Symbol index and analysis persisted across runs, such that searching the symbols of an object
+//! that was opened before doesn't require parsing and demangling it again.
+//!
+//! Entries are keyed by the build-id of the object, which also invalidates them once the object
+//! is rebuilt. Objects without a build-id are keyed by a hash of their headers and symbol tables.
+//! Stripping or patching an object keeps its build-id, so every entry also records the size and
+//! modification time of the file it was made from and is replaced once those change.
+
+use crate::Index;
+use object::{Object, ObjectSection, ObjectSymbol};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Identifies the layout of the cache files, bumped whenever it changes.
+const MAGIC: &[u8; 8] = b"BITEIDX2";
+
+/// Identifies the layout of the analysis files, bumped whenever it changes.
+const ANALYSIS_MAGIC: &[u8; 8] = b"BITEANA3";
+
+/// Result of analysing an object that's expensive to compute, stored as a list of numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Analysis {
+    /// Start of every block of the listing.
+    BlockBoundaries,
+    /// Calls between functions as the caller, callee and number of calls.
+    CallEdges,
+}
+
+impl Analysis {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::BlockBoundaries => "blocks",
+            Self::CallEdges => "calls",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedSymbol {
+    pub addr: usize,
+    /// Size in bytes, zero if it's unknown.
+    pub size: u64,
+    /// Name as it appears in the object, empty if it's unknown.
+    pub mangled: String,
+    pub demangled: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SymbolCache {
+    pub syms: Vec<CachedSymbol>,
+}
+
+/// Unique identifier of the build that produced the object.
+pub fn build_id(obj: &object::File) -> Option<Vec<u8>> {
+    if let Ok(Some(id)) = obj.build_id() {
+        return Some(id.to_vec());
+    }
+
+    if let Ok(Some(uuid)) = obj.mach_uuid() {
+        return Some(uuid.to_vec());
+    }
+
+    if let Ok(Some(pdb)) = obj.pdb_info() {
+        let mut id = pdb.guid().to_vec();
+        id.extend_from_slice(&pdb.age().to_le_bytes());
+        return Some(id);
+    }
+
+    None
+}
+
+/// FNV-1a, which unlike the hasher of the standard library is the same across releases.
+fn fnv1a(hash: u64, data: &[u8]) -> u64 {
+    data.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Hash of the sections and symbols of an object, without reading any of its contents.
+fn fingerprint(obj: &object::File) -> u64 {
+    let mut hash = fnv1a(0xcbf29ce484222325, &obj.entry().to_le_bytes());
+
+    for section in obj.sections() {
+        hash = fnv1a(hash, section.name_bytes().unwrap_or_default());
+        hash = fnv1a(hash, &section.address().to_le_bytes());
+        hash = fnv1a(hash, &section.size().to_le_bytes());
+    }
+
+    for sym in obj.symbols().chain(obj.dynamic_symbols()) {
+        hash = fnv1a(hash, sym.name_bytes().unwrap_or_default());
+        hash = fnv1a(hash, &sym.address().to_le_bytes());
+        hash = fnv1a(hash, &sym.size().to_le_bytes());
+    }
+
+    hash
+}
+
+/// Identifies the cache entries of an object and the version of the file they were made from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    /// Build-id of the object, or else a hash of its headers and symbol tables.
+    id: Vec<u8>,
+    /// Size and modification time of the file.
+    stamp: [u8; 16],
+}
+
+impl CacheKey {
+    pub fn new(obj: &object::File, path: &Path) -> Self {
+        let id = build_id(obj).unwrap_or_else(|| fingerprint(obj).to_le_bytes().to_vec());
+
+        let mut stamp = [0; 16];
+        if let Ok(metadata) = std::fs::metadata(path) {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .unwrap_or_default();
+
+            stamp[..8].copy_from_slice(&metadata.len().to_le_bytes());
+            stamp[8..].copy_from_slice(&(modified.as_nanos() as u64).to_le_bytes());
+        }
+
+        Self { id, stamp }
+    }
+
+    /// What's recorded in the header of an entry, to tell whether it's still current.
+    fn bytes(&self) -> Vec<u8> {
+        [&self.id[..], &self.stamp[..]].concat()
+    }
+}
+
+fn cache_path(key: &[u8], extension: &str) -> Option<PathBuf> {
+    let name: String = key.iter().map(|byte| format!("{byte:02x}")).collect();
+    Some(dirs::cache_dir()?.join("bite").join("symbols").join(name + "." + extension))
+}
+
+/// Write `data` to `path` through a temporary file, so concurrent runs never read a partial
+/// entry.
+fn write_entry(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Start of every entry, identifying its layout and the version of the object it was made from.
+fn header(magic: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(magic.len() + key.len() + 4);
+    out.extend_from_slice(magic);
+    out.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    out.extend_from_slice(key);
+    out
+}
+
+/// Whether the entry at `path` was made from the same version of the object, checked by only
+/// reading its header.
+fn is_current(path: &Path, magic: &[u8], key: &[u8]) -> bool {
+    let expected = header(magic, key);
+    let mut found = vec![0; expected.len()];
+    let read = std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut found));
+    read.is_ok() && found == expected
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).ok()
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+impl SymbolCache {
+    /// Collect the symbols of an index, taking their size and mangled name from the object.
+    pub fn build(obj: &object::File, index: &Index) -> Self {
+        let mut raw = HashMap::new();
+        for sym in obj.symbols().chain(obj.dynamic_symbols()) {
+            if let Ok(name) = sym.name() {
+                raw.entry(sym.address() as usize).or_insert((name, sym.size()));
+            }
+        }
+
+        let syms = index
+            .functions()
+            .map(|func| {
+                let (mangled, size) = raw.get(&func.addr).copied().unwrap_or(("", 0));
+                CachedSymbol {
+                    addr: func.addr,
+                    size,
+                    mangled: mangled.to_string(),
+                    demangled: func.item.as_str().to_string(),
+                }
+            })
+            .collect();
+
+        Self { syms }
+    }
+
+    fn encode(&self, key: &[u8]) -> Vec<u8> {
+        let mut out = header(MAGIC, key);
+        out.extend_from_slice(&(self.syms.len() as u32).to_le_bytes());
+
+        for sym in self.syms.iter() {
+            out.extend_from_slice(&(sym.addr as u64).to_le_bytes());
+            out.extend_from_slice(&sym.size.to_le_bytes());
+            write_str(&mut out, &sym.mangled);
+            write_str(&mut out, &sym.demangled);
+        }
+
+        out
+    }
+
+    /// Returns `None` if the data is corrupt or belongs to a different version of the object.
+    fn decode(data: &[u8], key: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data };
+
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return None;
+        }
+
+        let key_len = reader.u32()? as usize;
+        if reader.bytes(key_len)? != key {
+            return None;
+        }
+
+        let count = reader.u32()? as usize;
+        let mut syms = Vec::with_capacity(count.min(data.len()));
+        for _ in 0..count {
+            syms.push(CachedSymbol {
+                addr: reader.u64()? as usize,
+                size: reader.u64()?,
+                mangled: reader.str()?,
+                demangled: reader.str()?,
+            });
+        }
+
+        Some(Self { syms })
+    }
+
+    /// Cached symbols of an object, if this version of it was indexed before.
+    pub fn load(key: &CacheKey) -> Option<Self> {
+        let data = std::fs::read(cache_path(&key.id, "idx")?).ok()?;
+        Self::decode(&data, &key.bytes())
+    }
+
+    /// Persist the symbols of an object, unless they already are for this version of it.
+    pub fn store(key: &CacheKey, obj: &object::File, index: &Index) -> std::io::Result<()> {
+        let path = match cache_path(&key.id, "idx") {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if is_current(&path, MAGIC, &key.bytes()) {
+            return Ok(());
+        }
+
+        write_entry(&path, &Self::build(obj, index).encode(&key.bytes()))
+    }
+
+    /// Symbols of which either the mangled or demangled name contains `pattern`.
+    pub fn search<'a>(&'a self, pattern: &'a str) -> impl Iterator<Item = &'a CachedSymbol> {
+        self.syms
+            .iter()
+            .filter(move |sym| sym.demangled.contains(pattern) || sym.mangled.contains(pattern))
+    }
+}
+
+fn encode_analysis(key: &[u8], values: &[u64]) -> Vec<u8> {
+    let mut out = header(ANALYSIS_MAGIC, key);
+    out.reserve(values.len() * 8 + 8);
+    out.extend_from_slice(&(values.len() as u64).to_le_bytes());
+    for value in values {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+fn decode_analysis(data: &[u8], key: &[u8]) -> Option<Vec<u64>> {
+    let mut reader = Reader { data };
+
+    if reader.bytes(ANALYSIS_MAGIC.len())? != ANALYSIS_MAGIC {
+        return None;
+    }
+
+    let key_len = reader.u32()? as usize;
+    if reader.bytes(key_len)? != key {
+        return None;
+    }
+
+    let count = reader.u64()? as usize;
+    let mut values = Vec::with_capacity(count.min(data.len() / 8));
+    for _ in 0..count {
+        values.push(reader.u64()?);
+    }
+
+    Some(values)
+}
+
+/// Analysis of the object with the given key, if this version of it was analysed before.
+pub fn load_analysis(key: &CacheKey, analysis: Analysis) -> Option<Vec<u64>> {
+    let data = std::fs::read(cache_path(&key.id, analysis.extension())?).ok()?;
+    decode_analysis(&data, &key.bytes())
+}
+
+/// Persist the analysis of the object with the given key.
+pub fn store_analysis(key: &CacheKey, analysis: Analysis, values: &[u64]) -> std::io::Result<()> {
+    match cache_path(&key.id, analysis.extension()) {
+        Some(path) => write_entry(&path, &encode_analysis(&key.bytes(), values)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> SymbolCache {
+        SymbolCache {
+            syms: vec![
+                CachedSymbol {
+                    addr: 0x1000,
+                    size: 42,
+                    mangled: "_ZN3foo3barEv".to_string(),
+                    demangled: "foo::bar".to_string(),
+                },
+                CachedSymbol {
+                    addr: 0x2000,
+                    size: 0,
+                    mangled: String::new(),
+                    demangled: ".text".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let data = cache().encode(b"\x01\x02");
+        assert_eq!(SymbolCache::decode(&data, b"\x01\x02"), Some(cache()));
+    }
+
+    #[test]
+    fn different_build() {
+        let data = cache().encode(b"\x01\x02");
+        assert_eq!(SymbolCache::decode(&data, b"\x01\x03"), None);
+    }
+
+    #[test]
+    fn stale() {
+        let key = CacheKey { id: b"\x01\x02".to_vec(), stamp: [0; 16] };
+        let patched = CacheKey { stamp: [1; 16], ..key.clone() };
+        let data = cache().encode(&key.bytes());
+        assert_eq!(SymbolCache::decode(&data, &key.bytes()), Some(cache()));
+        assert_eq!(SymbolCache::decode(&data, &patched.bytes()), None);
+    }
+
+    #[test]
+    fn truncated() {
+        let data = cache().encode(b"\x01\x02");
+        assert_eq!(SymbolCache::decode(&data[..data.len() - 1], b"\x01\x02"), None);
+    }
+
+    #[test]
+    fn analysis_roundtrip() {
+        let values = [0x1000, 0x1010, u64::MAX];
+        let data = encode_analysis(b"\x01\x02", &values);
+        assert_eq!(decode_analysis(&data, b"\x01\x02"), Some(values.to_vec()));
+        assert_eq!(decode_analysis(&data, b"\x01\x03"), None);
+        assert_eq!(decode_analysis(&data[..data.len() - 1], b"\x01\x02"), None);
+    }
+
+    #[test]
+    fn search() {
+        let cache = cache();
+        let found: Vec<usize> = cache.search("3bar").map(|sym| sym.addr).collect();
+        assert_eq!(found, [0x1000]);
+        assert_eq!(cache.search("text").count(), 1);
+    }
+}
@@ -0,0 +1,61 @@
+//! Functions the compiler derived from another one, e.g. by specializing it for constant
+//! arguments (`.constprop`), dropping unused arguments (`.isra`) or splitting off rarely
+//! executed code (`.part`, `.cold`).
+
+/// Suffixes that are followed by a number.
+const NUMBERED: &[&str] = &["isra", "constprop", "part", "cold", "lto_priv", "llvm", "specialized"];
+
+/// Suffixes that stand on their own.
+const UNNUMBERED: &[&str] = &["cold", "localalias"];
+
+/// Split a symbol into the name of the function it was derived from and the clone suffix,
+/// e.g. `work.constprop.0.isra.0` into `work` and `.constprop.0.isra.0`. The suffix is empty
+/// if the symbol isn't a clone.
+pub fn split_clone_suffix(name: &str) -> (&str, &str) {
+    let mut base = name;
+
+    while let Some((rest, last)) = base.rsplit_once('.') {
+        if UNNUMBERED.contains(&last) {
+            base = rest;
+            continue;
+        }
+
+        if last.is_empty() || !last.bytes().all(|b| b.is_ascii_digit()) {
+            break;
+        }
+
+        match rest.rsplit_once('.') {
+            Some((rest, kind)) if NUMBERED.contains(&kind) => base = rest,
+            _ => break,
+        }
+    }
+
+    // Symbols consisting of just a suffix aren't clones.
+    if base.is_empty() {
+        return (name, "");
+    }
+
+    name.split_at(base.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones() {
+        assert_eq!(split_clone_suffix("work.constprop.0"), ("work", ".constprop.0"));
+        assert_eq!(split_clone_suffix("work.constprop.0.isra.1"), ("work", ".constprop.0.isra.1"));
+        assert_eq!(split_clone_suffix("_ZN2ns4workEv.part.0"), ("_ZN2ns4workEv", ".part.0"));
+        assert_eq!(split_clone_suffix("work.cold"), ("work", ".cold"));
+        assert_eq!(split_clone_suffix("work.localalias"), ("work", ".localalias"));
+    }
+
+    #[test]
+    fn not_clones() {
+        assert_eq!(split_clone_suffix("work"), ("work", ""));
+        assert_eq!(split_clone_suffix("work.1"), ("work.1", ""));
+        assert_eq!(split_clone_suffix("file.c"), ("file.c", ""));
+        assert_eq!(split_clone_suffix(".cold"), (".cold", ""));
+    }
+}
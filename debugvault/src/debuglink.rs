@@ -0,0 +1,273 @@
+//! Separate debug files of stripped objects, as shipped by most distributions.
+//!
+//! These are found the same way `gdb` finds them: by build-id under the global debug directory,
+//! by the name in the `.gnu_debuglink` section next to the object or, if `DEBUGINFOD_URLS` is
+//! set, by downloading them from a debuginfod server. Mach-O objects have theirs in a `.dSYM`
+//! bundle next to them instead, which is found by the object's UUID.
+
+use object::read::macho::{FatArch, FatHeader};
+use object::{FileKind, Object};
+use std::path::{Path, PathBuf};
+
+/// Where distributions install debug files.
+const GLOBAL_DEBUG_DIR: &str = "/usr/lib/debug";
+
+/// CRC-32 as used by `.gnu_debuglink`, the same one as used by zlib.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let mut crc = idx as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[idx] = crc;
+        idx += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn by_build_id(build_id: &[u8]) -> Option<PathBuf> {
+    let (first, rest) = build_id.split_first()?;
+    let path = Path::new(GLOBAL_DEBUG_DIR)
+        .join(".build-id")
+        .join(hex(&[*first]))
+        .join(hex(rest) + ".debug");
+
+    path.is_file().then_some(path)
+}
+
+fn by_debuglink(path: &Path, name: &[u8], crc: u32) -> Option<PathBuf> {
+    let name = std::str::from_utf8(name).ok()?;
+    let object = path.canonicalize().ok()?;
+    let dir = object.parent()?;
+
+    let candidates = [
+        dir.join(name),
+        dir.join(".debug").join(name),
+        Path::new(GLOBAL_DEBUG_DIR)
+            .join(dir.strip_prefix("/").unwrap_or(dir))
+            .join(name),
+    ];
+
+    candidates.into_iter().find(|candidate| {
+        // An object can link to a file of the same name in the same directory as itself.
+        if *candidate == object || !candidate.is_file() {
+            return false;
+        }
+
+        binformat::map(candidate).is_ok_and(|data| crc32(&data) == crc)
+    })
+}
+
+/// Objects in a universal file, or just `data` if it isn't one.
+pub fn macho_slices(data: &[u8]) -> Vec<&[u8]> {
+    match FileKind::parse(data) {
+        Ok(FileKind::MachOFat32) => FatHeader::parse_arch32(data)
+            .into_iter()
+            .flatten()
+            .filter_map(|arch| arch.data(data).ok())
+            .collect(),
+        Ok(FileKind::MachOFat64) => FatHeader::parse_arch64(data)
+            .into_iter()
+            .flatten()
+            .filter_map(|arch| arch.data(data).ok())
+            .collect(),
+        _ => vec![data],
+    }
+}
+
+/// The object in `data` with the given UUID, which for a universal file is one of its slices.
+pub fn macho_slice(data: &[u8], uuid: [u8; 16]) -> Option<&[u8]> {
+    let matches = |data: &[u8]| {
+        object::File::parse(data).is_ok_and(|obj| obj.mach_uuid().ok().flatten() == Some(uuid))
+    };
+
+    macho_slices(data).into_iter().find(|slice| matches(slice))
+}
+
+/// The DWARF file of a `.dSYM` bundle, or `path` itself if it's not a bundle.
+pub fn dsym_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    // The file inside is named after the object, which is the name of the bundle without the
+    // extension, unless the bundle was renamed.
+    let dir = path.join("Contents/Resources/DWARF");
+    let named = path.file_stem().map(|name| dir.join(name));
+    if let Some(named) = named.filter(|named| named.is_file()) {
+        return Some(named);
+    }
+
+    let entries = std::fs::read_dir(dir).ok()?;
+    entries.flatten().map(|entry| entry.path()).find(|path| path.is_file())
+}
+
+/// `.dSYM` bundle next to the object with the same UUID, preferring the one named after it.
+fn by_dsym(path: &Path, uuid: [u8; 16]) -> Option<PathBuf> {
+    let matches = |bundle: &Path| -> Option<PathBuf> {
+        let file = dsym_file(bundle)?;
+        let data = binformat::map(&file).ok()?;
+        macho_slice(&data, uuid).is_some().then_some(file)
+    };
+
+    let mut named = path.as_os_str().to_owned();
+    named.push(".dSYM");
+    if let Some(file) = matches(Path::new(&named)) {
+        return Some(file);
+    }
+
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let entries = std::fs::read_dir(dir).ok()?;
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|bundle| bundle.extension().is_some_and(|ext| ext == "dSYM"))
+        .find_map(|bundle| matches(&bundle))
+}
+
+/// Download a debug file from the servers in `DEBUGINFOD_URLS`, or take it from the cache
+/// shared with other debuginfod clients.
+fn by_debuginfod(build_id: &[u8]) -> Option<PathBuf> {
+    let urls = std::env::var("DEBUGINFOD_URLS").ok()?;
+    let cache = match std::env::var_os("DEBUGINFOD_CACHE_PATH") {
+        Some(path) => PathBuf::from(path),
+        None => dirs::cache_dir()?.join("debuginfod_client"),
+    };
+
+    let dir = cache.join(hex(build_id));
+    let path = dir.join("debuginfo");
+    if path.is_file() {
+        return Some(path);
+    }
+
+    std::fs::create_dir_all(&dir).ok()?;
+    let tmp = dir.join("debuginfo.tmp");
+
+    for url in urls.split_whitespace() {
+        let url = format!(
+            "{}/buildid/{}/debuginfo",
+            url.trim_end_matches('/'),
+            hex(build_id)
+        );
+        log::PROGRESS.set("Downloading debug info.", 1);
+        let status = std::process::Command::new("curl")
+            .args([
+                "--fail",
+                "--silent",
+                "--location",
+                "--max-time",
+                "120",
+                "--output",
+            ])
+            .arg(&tmp)
+            .arg(&url)
+            .status();
+        log::PROGRESS.step();
+
+        if status.is_ok_and(|status| status.success()) && std::fs::rename(&tmp, &path).is_ok() {
+            return Some(path);
+        }
+    }
+
+    let _ = std::fs::remove_file(&tmp);
+    None
+}
+
+/// Path to the debug file with the given build-id, either installed or from a debuginfod server.
+pub fn find(build_id: &[u8]) -> Option<PathBuf> {
+    by_build_id(build_id).or_else(|| by_debuginfod(build_id))
+}
+
+/// Path to the separate debug file of an object, if it was stripped of its debug info.
+pub fn locate(obj: &object::File, path: &Path) -> Option<PathBuf> {
+    if obj.section_by_name(".debug_info").is_some() {
+        return None;
+    }
+
+    if let Ok(Some(uuid)) = obj.mach_uuid() {
+        return by_dsym(path, uuid);
+    }
+
+    let build_id = obj.build_id().ok().flatten().filter(|id| !id.is_empty());
+
+    if let Some(path) = build_id.and_then(by_build_id) {
+        return Some(path);
+    }
+
+    if let Ok(Some((name, crc))) = obj.gnu_debuglink() {
+        if let Some(path) = by_debuglink(path, name, crc) {
+            return Some(path);
+        }
+    }
+
+    build_id.and_then(by_debuginfod)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    }
+
+    /// 64-bit Mach-O header with nothing but a UUID.
+    fn macho(cputype: u32, uuid: [u8; 16]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for field in [0xfeedfacf, cputype, 3, 2, 1, 24, 0, 0, 0x1b, 24] {
+            data.extend_from_slice(&u32::to_le_bytes(field));
+        }
+        data.extend_from_slice(&uuid);
+        data
+    }
+
+    #[test]
+    fn universal_slice() {
+        let x86_64 = macho(0x01000007, [1; 16]);
+        let arm64 = macho(0x0100000c, [2; 16]);
+
+        let mut fat = Vec::new();
+        for field in [0xcafebabe, 2] {
+            fat.extend_from_slice(&u32::to_be_bytes(field));
+        }
+        for (cputype, offset, slice) in [(0x01000007, 64, &x86_64), (0x0100000c, 128, &arm64)] {
+            for field in [cputype, 3, offset, slice.len() as u32, 0] {
+                fat.extend_from_slice(&u32::to_be_bytes(field));
+            }
+        }
+        fat.resize(64, 0);
+        fat.extend_from_slice(&x86_64);
+        fat.resize(128, 0);
+        fat.extend_from_slice(&arm64);
+
+        assert_eq!(macho_slices(&fat), [&x86_64[..], &arm64[..]]);
+        assert_eq!(macho_slices(&x86_64), [&x86_64[..]]);
+        assert_eq!(macho_slice(&fat, [2; 16]), Some(&arm64[..]));
+        assert_eq!(macho_slice(&fat, [1; 16]), Some(&x86_64[..]));
+        assert_eq!(macho_slice(&fat, [3; 16]), None);
+        assert_eq!(macho_slice(&x86_64, [1; 16]), Some(&x86_64[..]));
+    }
+}
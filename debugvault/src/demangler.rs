@@ -1,7 +1,8 @@
 //! Symbol demangler for common mangling schemes.
 
-use tokenizing::{Token, Color32};
+use tokenizing::{Color32, Kind, Token};
 use config::CONFIG;
+use std::sync::RwLock;
 
 pub fn parse(s: &str) -> TokenStream {
     // symbols without leading underscores are accepted as
@@ -11,30 +12,202 @@ pub fn parse(s: &str) -> TokenStream {
     let s = s.strip_suffix("$plt").unwrap_or(s);
     let s = s.strip_suffix("$pltgot").unwrap_or(s);
 
-    // parse rust symbols
-    if let Some(s) = crate::rust_legacy::parse(s) {
-        return s;
+    // stubs in the procedure linkage table
+    if let Some(s) = s.strip_suffix("@plt") {
+        let mut stream = parse(s);
+        stream.push("@plt", CONFIG.colors.comment);
+        return stream;
     }
 
-    // parse gnu/llvm/C/C++ symbols
-    if let Some(s) = crate::itanium::parse(s) {
-        return s;
+    // versioned ELF symbols, e.g. `memcpy@GLIBC_2.14`, msvc names being the others with an '@'
+    if let Some(idx) = s.find('@').filter(|&idx| idx > 0 && !s.starts_with('?')) {
+        let mut stream = parse(&s[..idx]);
+        stream.push_string(s[idx..].to_string(), CONFIG.colors.comment);
+        return stream;
     }
 
-    // parse rust symbols that match the v0 mangling scheme
-    if let Some(s) = crate::rust::parse(s) {
-        return s;
-    }
-
-    // parse windows msvc C/C++ symbols
-    if let Some(s) = crate::msvc::parse(s) {
-        return s;
+    // try every scheme, as the prefix identifying it might have been stripped
+    let found = with_backends(|backends| backends.iter().find_map(|backend| (backend.parse)(s).ok()));
+    if let Some(stream) = found {
+        return stream;
     }
 
     // return the original mangled symbol on failure
     TokenStream::simple(s)
 }
 
+/// Longest symbol the builtin demanglers accept, so a single adversarial symbol can't hold up
+/// demangling all the others.
+pub(crate) const MAX_LEN: usize = 1 << 16;
+
+/// Mangling scheme a symbol was recognized as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Itanium,
+    Msvc,
+    RustLegacy,
+    RustV0,
+    /// Scheme of a backend that was registered by a library consumer.
+    Other(&'static str),
+}
+
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Scheme::Itanium => "itanium",
+            Scheme::Msvc => "msvc",
+            Scheme::RustLegacy => "rust legacy",
+            Scheme::RustV0 => "rust v0",
+            Scheme::Other(name) => name,
+        })
+    }
+}
+
+/// A demangler for a single mangling scheme.
+#[derive(Debug, Clone, Copy)]
+pub struct Backend {
+    pub scheme: Scheme,
+    /// Languages or toolchains producing the scheme, shown when listing the backends.
+    pub description: &'static str,
+    /// Whether a symbol is mangled using this scheme, judging by its prefix alone. The extra
+    /// leading underscore of macOS symbols is stripped before matching.
+    pub matches: fn(&str) -> bool,
+    /// Demangle a symbol, or return the byte offset at which it stopped being valid.
+    pub parse: fn(&str) -> Result<TokenStream, usize>,
+}
+
+/// Builtin backends, in the order they're tried.
+const BUILTIN: &[Backend] = &[
+    Backend {
+        scheme: Scheme::RustLegacy,
+        description: "Rust before v0, '_ZN' paths ending in a hash",
+        matches: is_rust_legacy,
+        parse: crate::rust_legacy::parse_detailed,
+    },
+    Backend {
+        scheme: Scheme::Itanium,
+        description: "C++ built by gcc and clang, '_Z'",
+        matches: |s| s.starts_with("_Z"),
+        parse: crate::itanium::parse_detailed,
+    },
+    Backend {
+        scheme: Scheme::RustV0,
+        description: "Rust v0, '_R'",
+        matches: |s| s.starts_with("_R"),
+        parse: crate::rust::parse_detailed,
+    },
+    Backend {
+        scheme: Scheme::Msvc,
+        description: "C++ built by msvc, '?'",
+        matches: |s| s.starts_with('?') || s.starts_with(".?"),
+        parse: crate::msvc::parse_detailed,
+    },
+];
+
+/// Backends registered by library consumers, tried before the builtin ones.
+static REGISTERED: RwLock<Vec<Backend>> = RwLock::new(Vec::new());
+
+/// Add a backend for another mangling scheme. Backends are tried in the reverse order they're
+/// registered in, before any of the builtin ones.
+pub fn register(backend: Backend) {
+    REGISTERED.write().unwrap().insert(0, backend);
+}
+
+/// All backends, in the order they're tried.
+pub fn backends() -> Vec<Backend> {
+    with_backends(|backends| backends.to_vec())
+}
+
+fn with_backends<T>(f: impl FnOnce(&[Backend]) -> T) -> T {
+    let registered = REGISTERED.read().unwrap();
+    if registered.is_empty() {
+        return f(BUILTIN);
+    }
+
+    let mut backends = registered.clone();
+    backends.extend_from_slice(BUILTIN);
+    f(&backends)
+}
+
+/// Legacy rust symbols end in a path component holding a hash, e.g. '17h0123456789abcdefE'.
+fn is_rust_legacy(s: &str) -> bool {
+    let hash = s
+        .strip_prefix("_ZN")
+        .and_then(|path| path.strip_suffix('E'))
+        .and_then(|path| path.get(path.len().checked_sub(19)?..));
+
+    hash.is_some_and(|hash| hash.starts_with("17h"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Demangled {
+    pub scheme: Scheme,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemangleError {
+    /// Scheme the symbol looked like, `None` if it doesn't look mangled at all.
+    pub scheme: Option<Scheme>,
+    /// Byte offset in the symbol at which it stopped being valid.
+    pub offset: usize,
+}
+
+impl std::fmt::Display for DemangleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.scheme {
+            Some(scheme) => write!(f, "invalid {scheme} symbol at byte {}", self.offset),
+            None => f.write_str("not a mangled symbol"),
+        }
+    }
+}
+
+impl std::error::Error for DemangleError {}
+
+/// Scheme a symbol is mangled with, judging by its prefix alone.
+pub fn scheme(s: &str) -> Option<Scheme> {
+    let prefixed = s.strip_prefix('_').filter(|s| s.starts_with('_')).unwrap_or(s);
+    with_backends(|backends| {
+        backends.iter().find(|backend| (backend.matches)(prefixed)).map(|backend| backend.scheme)
+    })
+}
+
+/// Demangle a symbol of a scheme recognized by its prefix, unlike [`parse`] this doesn't
+/// guess at symbols that had their prefix stripped.
+pub fn parse_strict(s: &str) -> Result<(Scheme, TokenStream), DemangleError> {
+    let s = s.strip_suffix("$got").unwrap_or(s);
+    let s = s.strip_suffix("$plt").unwrap_or(s);
+    let s = s.strip_suffix("$pltgot").unwrap_or(s);
+
+    // macOS prefixes symbols with an extra underscore
+    let prefixed = s.strip_prefix('_').filter(|s| s.starts_with('_')).unwrap_or(s);
+
+    let backend = with_backends(|backends| {
+        backends.iter().find(|backend| (backend.matches)(prefixed)).copied()
+    });
+
+    let backend = backend.ok_or(DemangleError {
+        scheme: None,
+        offset: 0,
+    })?;
+
+    match (backend.parse)(s) {
+        Ok(stream) => Ok((backend.scheme, stream)),
+        Err(offset) => Err(DemangleError {
+            scheme: Some(backend.scheme),
+            offset,
+        }),
+    }
+}
+
+/// Like [`parse_strict`], but only the demangled name is kept.
+pub fn parse_detailed(s: &str) -> Result<Demangled, DemangleError> {
+    parse_strict(s).map(|(scheme, stream)| Demangled {
+        scheme,
+        name: stream.to_string(),
+    })
+}
+
 #[derive(Debug)]
 pub struct TokenStream {
     /// Unmovable string which the [Token]'s have a pointer to.
@@ -78,14 +251,126 @@ impl TokenStream {
         self.tokens.push(Token::from_string(text, color));
     }
 
+    /// Mark the tokens pushed after the first `start` as (part of) a `kind`.
+    pub fn mark(&mut self, start: usize, kind: Kind) {
+        for token in &mut self.tokens[start..] {
+            token.kind |= kind;
+        }
+    }
+
     #[inline]
     pub fn tokens(&self) -> &[Token] {
         self.tokens.as_slice()
     }
 }
 
+impl std::fmt::Display for TokenStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tokens().iter().try_for_each(|token| f.write_str(&token.text))
+    }
+}
+
 impl PartialEq for TokenStream {
     fn eq(&self, other: &Self) -> bool {
         self.inner == other.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemes() {
+        let scheme = |s| parse_detailed(s).map(|demangled| demangled.scheme);
+        assert_eq!(scheme("_ZN3foo3barEv"), Ok(Scheme::Itanium));
+        assert_eq!(scheme("_ZN3foo3bar17h05af221e174051e9E"), Ok(Scheme::RustLegacy));
+        assert_eq!(scheme("_RNvCs1234_7mycrate3foo"), Ok(Scheme::RustV0));
+        assert_eq!(scheme("?x@@3HA"), Ok(Scheme::Msvc));
+    }
+
+    #[test]
+    fn unmangled() {
+        let err = parse_detailed("main").unwrap_err();
+        assert_eq!(err, DemangleError { scheme: None, offset: 0 });
+    }
+
+    #[test]
+    fn offset() {
+        let err = parse_detailed("_ZN3foo3barEvXX").unwrap_err();
+        assert_eq!(err.scheme, Some(Scheme::Itanium));
+        assert_eq!(err.offset, 13);
+
+        let err = parse_detailed("_ZN3foo").unwrap_err();
+        assert_eq!(err.offset, 7);
+    }
+
+    #[test]
+    fn registered() {
+        register(Backend {
+            scheme: Scheme::Other("test"),
+            description: "",
+            matches: |s| s.starts_with("$test$"),
+            parse: |s| match s.strip_prefix("$test$") {
+                Some(name) if !name.is_empty() => Ok(TokenStream::simple(name)),
+                _ => Err(6),
+            },
+        });
+
+        let demangled = parse_detailed("$test$foo").unwrap();
+        assert_eq!(demangled.scheme, Scheme::Other("test"));
+        assert_eq!(demangled.name, "foo");
+        assert_eq!(parse_detailed("$test$").unwrap_err().offset, 6);
+        assert_eq!(parse_detailed("_ZN3foo3barEv").unwrap().scheme, Scheme::Itanium);
+    }
+
+    #[test]
+    fn line() {
+        let line = "  call   401000 <_ZN3foo3barEv@plt>; see _ZN3foo3bazEv.";
+        let found: Vec<_> = crate::demangle_line(line, None, Kind::empty())
+            .into_iter()
+            .map(|(range, symbol)| (&line[range], symbol.as_str().to_string()))
+            .collect();
+
+        assert_eq!(
+            found,
+            [("_ZN3foo3barEv", "foo::bar".to_string()), ("_ZN3foo3bazEv", "foo::baz".to_string())]
+        );
+    }
+
+    #[test]
+    fn line_anywhere() {
+        let line = "sym=_Z3fooi,.L_ZN3foo3barEv+0x10 (?x@@3HA) _ZN3foo3bazEvXX";
+        let found: Vec<_> = crate::demangle_line(line, None, Kind::empty())
+            .into_iter()
+            .map(|(range, _)| &line[range])
+            .collect();
+
+        assert_eq!(found, ["_Z3fooi", "_ZN3foo3barEv", "?x@@3HA", "_ZN3foo3bazEv"]);
+    }
+
+    #[test]
+    fn signature() {
+        let signature = |s| crate::demangle_signature(s).map(|sig| (sig.params, sig.ret));
+        let params = |params: &[&str]| params.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(signature("_Z5countPKci"), Some((params(&["char const*", "int"]), None)));
+        assert_eq!(signature("_ZN3foo3barEv"), Some((params(&[]), None)));
+        assert_eq!(
+            signature("_Z3maxIiET_S0_S0_"),
+            Some((params(&["int", "int"]), Some("int".to_string())))
+        );
+        assert_eq!(
+            signature("_Z4nameB5cxx11RKSt6vectorIiSaIiEEz").map(|(params, _)| params.len()),
+            Some(2)
+        );
+        assert_eq!(signature("_ZN3foo3bar17h05af221e174051e9E"), None);
+    }
+
+    #[test]
+    fn batch() {
+        let names = crate::demangle_batch(&["_ZN3foo3barEv", "main"]);
+        assert_eq!(names[0].as_ref().unwrap().name, "foo::bar");
+        assert!(names[1].is_err());
+    }
+}
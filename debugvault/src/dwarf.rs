@@ -1,5 +1,6 @@
 use crate::intern::InternMap;
-use crate::{AddressMap, Addressed, FileAttr};
+use crate::{AddressMap, Addressed, FileAttr, ParamKind, Signature};
+use memmap2::Mmap;
 use object::{Object, ObjectSection, ObjectSymbol};
 use rustc_hash::FxHasher;
 use std::borrow::Cow;
@@ -36,31 +37,144 @@ impl From<object::Error> for Error {
     }
 }
 
+/// Where the entries of units compiled with `-gsplit-dwarf` are looked for.
+#[derive(Debug, Default, Clone)]
+pub struct SplitDwarf {
+    /// Directory the `.dwo` files are relative to, instead of where each unit was compiled.
+    pub build_dir: Option<PathBuf>,
+    /// Package of the `.dwo` files, as created by `dwp`.
+    pub dwp: Option<PathBuf>,
+}
+
 pub struct Dwarf {
     /// Mapping from addresses starting at the header base to source files.
     pub file_attrs: AddressMap<FileAttr>,
+    /// Mapping from function entries to the kind of each of their parameters.
+    pub params: AddressMap<Vec<ParamKind>>,
+    /// Mapping from function entries to the types of their parameters and return value.
+    pub signatures: AddressMap<Signature>,
+    /// Mapping from out-of-line instances of a function, e.g. specialized clones, to the name
+    /// of the function they're derived from.
+    pub origins: AddressMap<String>,
+    /// Mapping from the start of inlined code to the name of the function that was inlined.
+    pub inlined: AddressMap<String>,
 }
 
 impl Dwarf {
-    pub fn parse(obj: &object::File) -> Result<Self> {
+    pub fn parse(obj: &object::File, split: &SplitDwarf) -> Result<Self> {
         let endian = if obj.is_little_endian() {
             gimli::RunTimeEndian::Little
         } else {
             gimli::RunTimeEndian::Big
         };
 
+        let files = Arena::new();
         let arena_data = Arena::new();
         let arena_relocations = Arena::new();
 
         let mut load_section = |id: gimli::SectionId| {
-            load_file_section(id, obj, endian, &arena_data, &arena_relocations)
+            let name = section_name(id, obj);
+            load_file_section(name, obj, endian, &arena_data, &arena_relocations)
         };
 
         let mut dwarf = gimli::Dwarf::load(&mut load_section)?;
         dwarf.populate_abbreviations_cache(gimli::AbbreviationsCacheStrategy::All);
         let file_attrs = dump_line(&dwarf)?;
+        let mut entries = dump_entries(&dwarf);
+
+        // Units compiled with `-gsplit-dwarf` only leave a skeleton with the line table behind,
+        // their entries are in a `.dwo` file per unit or in a package of them.
+        let package = match split.dwp.as_deref().and_then(|path| map_file(&files, path)) {
+            Some(file) => {
+                let empty =
+                    load_file_section(None, &file, endian, &arena_data, &arena_relocations)?;
+                let package = gimli::DwarfPackage::load(
+                    |id| {
+                        load_file_section(
+                            id.dwo_name(),
+                            &file,
+                            endian,
+                            &arena_data,
+                            &arena_relocations,
+                        )
+                    },
+                    empty,
+                )?;
+                Some(package)
+            }
+            None => None,
+        };
+
+        let mut iter = dwarf.units();
+        let (mut found, mut missing) = (0, 0);
+        while let Some(header) = iter.next()? {
+            let Ok(skeleton) = dwarf.unit(header) else {
+                continue;
+            };
+            let Some(dwo_id) = skeleton.dwo_id else {
+                continue;
+            };
+
+            let dwo = match package {
+                Some(ref package) => package.find_cu(dwo_id, &dwarf)?,
+                None => match dwo_path(&skeleton, &dwarf, split)
+                    .and_then(|path| map_file(&files, &path))
+                {
+                    Some(file) => {
+                        let mut dwo = gimli::Dwarf::load(|id| {
+                            load_file_section(
+                                id.dwo_name(),
+                                &file,
+                                endian,
+                                &arena_data,
+                                &arena_relocations,
+                            )
+                        })?;
+                        dwo.make_dwo(&dwarf);
+                        Some(dwo)
+                    }
+                    None => None,
+                },
+            };
+
+            let Some(dwo) = dwo else {
+                missing += 1;
+                continue;
+            };
+
+            // A `.dwo` file can also contain type units, besides the unit of the skeleton.
+            let mut dwo_iter = dwo.units();
+            while let Some(header) = dwo_iter.next()? {
+                let mut unit = dwo.unit(header)?;
+                if unit.dwo_id != Some(dwo_id) {
+                    continue;
+                }
 
-        Ok(Dwarf { file_attrs })
+                unit.copy_relocated_attributes(&skeleton);
+                dump_unit(&unit, &dwo, &mut entries);
+                found += 1;
+                break;
+            }
+        }
+
+        if missing > 0 {
+            log::complex!(
+                w "[dwarf::parse] ",
+                y "Couldn't find the split DWARF of ",
+                g missing.to_string(),
+                y " of ",
+                g (found + missing).to_string(),
+                y " units.",
+            );
+        }
+
+        Ok(Dwarf {
+            file_attrs,
+            params: entries.params,
+            signatures: entries.signatures,
+            origins: entries.origins,
+            inlined: entries.inlined,
+        })
     }
 
     #[allow(dead_code)]
@@ -68,12 +182,15 @@ impl Dwarf {
         let file = std::fs::File::open(path)?;
         let mmap = unsafe { memmap2::Mmap::map(&file)? };
         let obj = object::File::parse(&*mmap)?;
-        Self::parse(&obj)
+        Self::parse(&obj, &SplitDwarf::default())
     }
 
-    #[allow(dead_code)]
     pub fn merge(&mut self, other: Self) {
         self.file_attrs.extend(other.file_attrs);
+        self.params.extend(other.params);
+        self.signatures.extend(other.signatures);
+        self.origins.extend(other.origins);
+        self.inlined.extend(other.inlined);
     }
 }
 
@@ -288,20 +405,54 @@ impl<'a, R: gimli::Reader<Offset = usize>> gimli::Reader for Relocate<'a, R> {
 
 impl<'a, R: Reader> Reader for Relocate<'a, R> {}
 
-fn load_file_section<'input, 'arena, Endian: gimli::Endianity>(
-    id: gimli::SectionId,
-    file: &object::File<'input>,
+fn section_name(id: gimli::SectionId, file: &object::File) -> Option<&'static str> {
+    if file.format() == object::BinaryFormat::Xcoff {
+        id.xcoff_name()
+    } else {
+        Some(id.name())
+    }
+}
+
+/// Map a file holding split DWARF for as long as the sections loaded from it are used.
+fn map_file<'a>(files: &'a Arena<Mmap>, path: &Path) -> Option<object::File<'a>> {
+    let file = std::fs::File::open(path).ok()?;
+    let mmap = unsafe { Mmap::map(&file).ok()? };
+    object::File::parse(&files.alloc(mmap)[..]).ok()
+}
+
+/// Path of the `.dwo` file of a skeleton unit.
+fn dwo_path<R: Reader>(
+    unit: &gimli::Unit<R>,
+    dwarf: &gimli::Dwarf<R>,
+    split: &SplitDwarf,
+) -> Option<PathBuf> {
+    let name = dwarf.attr_string(unit, unit.dwo_name().ok()??).ok()?;
+    let name = PathBuf::from(&*name.to_string_lossy().ok()?);
+    let comp_dir = unit
+        .comp_dir
+        .as_ref()
+        .and_then(|dir| dir.to_string_lossy().ok())
+        .map(|dir| PathBuf::from(&*dir));
+
+    Some(locate_dwo(&name, comp_dir.as_deref(), split))
+}
+
+/// Where the `.dwo` file `name` of a unit compiled in `comp_dir` is.
+fn locate_dwo(name: &Path, comp_dir: Option<&Path>, split: &SplitDwarf) -> PathBuf {
+    let dir = split.build_dir.as_deref().or(comp_dir).unwrap_or(Path::new(""));
+
+    // Absolute names replace the directory.
+    dir.join(name)
+}
+
+fn load_file_section<'input, 'file: 'input, 'arena, Endian: gimli::Endianity>(
+    name: Option<&str>,
+    file: &object::File<'file>,
     endian: Endian,
     arena_data: &'arena Arena<Cow<'input, [u8]>>,
     arena_relocations: &'arena Arena<RelocationMap>,
 ) -> Result<Relocate<'arena, gimli::EndianSlice<'arena, Endian>>> {
     let mut relocations = RelocationMap::default();
-    let name = if file.format() == object::BinaryFormat::Xcoff {
-        id.xcoff_name()
-    } else {
-        Some(id.name())
-    };
-
     let data = match name.and_then(|name| file.section_by_name(name)) {
         Some(ref section) => {
             add_relocations(&mut relocations, file, section);
@@ -393,7 +544,7 @@ fn dump_line_program<R: Reader>(
             };
 
             // Try to use cached path if possible, prevents extra allocations.
-            // A path is uniquely identified by it's comp_dir, directory id and it's file id.
+            // A path is uniquely identified by its comp_dir, directory id and its file id.
             let key = id << 48 | row.file_index() << 24 | file.directory_index();
             let path = match path_cache.get(&key) {
                 Some(cached) => cached,
@@ -428,3 +579,339 @@ fn dump_line_program<R: Reader>(
 
     Ok(())
 }
+
+/// What's read from the entries of the units, in a single walk over each of them.
+#[derive(Default)]
+struct Entries {
+    params: AddressMap<Vec<ParamKind>>,
+    signatures: AddressMap<Signature>,
+    origins: AddressMap<String>,
+    inlined: AddressMap<String>,
+}
+
+fn dump_entries<R: Reader>(dwarf: &gimli::Dwarf<R>) -> Entries {
+    let mut iter = dwarf.units();
+    let mut entries = Entries::default();
+
+    while let Ok(Some(header)) = iter.next() {
+        let unit = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(err) => {
+                log::complex!(
+                    w "[dwarf::dump_entries] ",
+                    y "Failed to parse unit root entry for dump_entries: ",
+                    y format!("{err:?}."),
+                );
+                continue;
+            }
+        };
+
+        dump_unit(&unit, dwarf, &mut entries);
+    }
+
+    entries
+}
+
+/// Read the entries of a unit, leaving out the rest of the unit if it's malformed.
+fn dump_unit<R: Reader>(unit: &gimli::Unit<R>, dwarf: &gimli::Dwarf<R>, entries: &mut Entries) {
+    let mut walk = || -> Result<()> {
+        let mut tree = unit.entries_tree(None)?;
+        dump_node(tree.root()?, unit, dwarf, entries)
+    };
+
+    if let Err(err) = walk() {
+        log::complex!(
+            w "[dwarf::dump_unit] ",
+            y "Failed to walk unit entries for dump_entries: ",
+            y format!("{err:?}."),
+        );
+    }
+}
+
+/// Walk a tree of entries looking for function definitions, and for instances of other functions
+/// either out-of-line or inlined.
+fn dump_node<R: Reader>(
+    node: gimli::EntriesTreeNode<R>,
+    unit: &gimli::Unit<R>,
+    dwarf: &gimli::Dwarf<R>,
+    entries: &mut Entries,
+) -> Result<()> {
+    let entry = node.entry();
+    let instances = match entry.tag() {
+        gimli::DW_TAG_subprogram => Some(&mut entries.origins),
+        gimli::DW_TAG_inlined_subroutine => Some(&mut entries.inlined),
+        _ => None,
+    };
+
+    if let Some(instances) = instances {
+        if let Some(name) = origin_name(entry, unit, dwarf) {
+            // Inlined code can be split into multiple ranges, the first one is where it starts.
+            let addr = dwarf.die_ranges(unit, entry)?.next()?.map(|range| range.begin);
+            if let Some(addr @ 1..) = addr {
+                instances.push(Addressed {
+                    addr: addr as usize,
+                    item: name,
+                });
+            }
+        }
+    }
+
+    let low_pc = match entry.attr_value(gimli::DW_AT_low_pc)? {
+        Some(attr) if entry.tag() == gimli::DW_TAG_subprogram => dwarf.attr_address(unit, attr)?,
+        _ => None,
+    };
+
+    let ret = low_pc.and_then(|_| type_of(entry, unit)).map(|ty| type_name(ty, unit, dwarf));
+
+    let mut kinds = Some(Vec::new());
+    let mut params = Vec::new();
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let param = child.entry();
+        if low_pc.is_some() && param.tag() == gimli::DW_TAG_formal_parameter {
+            let kind = param_kind(param, unit);
+            kinds = kinds.zip(kind).map(|(mut kinds, kind)| {
+                kinds.push(kind);
+                kinds
+            });
+
+            // The implicit `this` of member functions isn't written in their signature.
+            let is_artificial = matches!(
+                param.attr_value(gimli::DW_AT_artificial)?,
+                Some(gimli::AttributeValue::Flag(true))
+            );
+
+            if !is_artificial {
+                params.push(match type_of(param, unit) {
+                    Some(ty) => type_name(ty, unit, dwarf),
+                    None => "?".to_string(),
+                });
+            }
+        }
+
+        if low_pc.is_some() && param.tag() == gimli::DW_TAG_unspecified_parameters {
+            params.push("...".to_string());
+        }
+
+        dump_node(child, unit, dwarf, entries)?;
+    }
+
+    if let Some(addr @ 1..) = low_pc {
+        // Functions with any parameters that aren't passed in registers don't get their kinds.
+        if let Some(kinds) = kinds {
+            entries.params.push(Addressed {
+                addr: addr as usize,
+                item: kinds,
+            });
+        }
+
+        entries.signatures.push(Addressed {
+            addr: addr as usize,
+            item: Signature { params, ret },
+        });
+    }
+
+    Ok(())
+}
+
+/// How a parameter is passed, by looking through its type.
+fn param_kind<R: Reader>(
+    entry: &gimli::DebuggingInformationEntry<R>,
+    unit: &gimli::Unit<R>,
+) -> Option<ParamKind> {
+    let mut offset = match entry.attr_value(gimli::DW_AT_type).ok()? {
+        Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+        // Parameters without a type are assumed to fit in a register.
+        _ => return Some(ParamKind::Integer),
+    };
+
+    // Limit the number of indirections in case of malformed type chains.
+    for _ in 0..16 {
+        let tipe = unit.entry(offset).ok()?;
+        match tipe.tag() {
+            gimli::DW_TAG_base_type => {
+                let size = match tipe.attr_value(gimli::DW_AT_byte_size).ok()? {
+                    Some(size) => size.udata_value()?,
+                    None => 0,
+                };
+
+                if size > 8 {
+                    return None;
+                }
+
+                return match tipe.attr_value(gimli::DW_AT_encoding).ok()? {
+                    Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_float)) => {
+                        Some(ParamKind::Float)
+                    }
+                    _ => Some(ParamKind::Integer),
+                };
+            }
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type
+            | gimli::DW_TAG_ptr_to_member_type
+            | gimli::DW_TAG_enumeration_type => return Some(ParamKind::Integer),
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => {
+                offset = match tipe.attr_value(gimli::DW_AT_type).ok()? {
+                    Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+                    // `const void` and friends.
+                    _ => return Some(ParamKind::Integer),
+                };
+            }
+            // Aggregates have complicated passing rules, so don't make any assumptions.
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Type of an entry, or of the declaration or abstract instance it completes.
+fn type_of<R: Reader>(
+    entry: &gimli::DebuggingInformationEntry<R>,
+    unit: &gimli::Unit<R>,
+) -> Option<gimli::UnitOffset> {
+    let mut entry = entry.clone();
+
+    // Limit the number of indirections in case of malformed chains.
+    for _ in 0..4 {
+        if let Some(gimli::AttributeValue::UnitRef(offset)) =
+            entry.attr_value(gimli::DW_AT_type).ok()?
+        {
+            return Some(offset);
+        }
+
+        let offset = match entry.attr_value(gimli::DW_AT_abstract_origin).ok()? {
+            Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+            _ => match entry.attr_value(gimli::DW_AT_specification).ok()? {
+                Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+                _ => return None,
+            },
+        };
+
+        entry = unit.entry(offset).ok()?;
+    }
+
+    None
+}
+
+/// Name of a type as it's written in the source, as far as the debug info tells.
+fn type_name<R: Reader>(
+    mut offset: gimli::UnitOffset,
+    unit: &gimli::Unit<R>,
+    dwarf: &gimli::Dwarf<R>,
+) -> String {
+    // Pointers and references without a name of their own are written after their pointee.
+    let mut suffix = String::new();
+
+    // Limit the number of indirections in case of malformed type chains.
+    for _ in 0..16 {
+        let Ok(tipe) = unit.entry(offset) else {
+            break;
+        };
+
+        let name = tipe
+            .attr_value(gimli::DW_AT_name)
+            .ok()
+            .flatten()
+            .and_then(|name| dwarf.attr_string(unit, name).ok())
+            .and_then(|name| name.to_string_lossy().ok().map(Cow::into_owned));
+
+        if let Some(name) = name {
+            return name + &suffix;
+        }
+
+        let modifier = match tipe.tag() {
+            gimli::DW_TAG_pointer_type => "*",
+            gimli::DW_TAG_reference_type => "&",
+            gimli::DW_TAG_rvalue_reference_type => "&&",
+            gimli::DW_TAG_array_type => "[]",
+            gimli::DW_TAG_subroutine_type => return "fn".to_string() + &suffix,
+            gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type
+            | gimli::DW_TAG_typedef => "",
+            // e.g. anonymous structs.
+            _ => return "?".to_string() + &suffix,
+        };
+
+        suffix.insert_str(0, modifier);
+        offset = match tipe.attr_value(gimli::DW_AT_type).ok().flatten() {
+            Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+            // Pointers to `void` don't have a pointee.
+            _ => return "void".to_string() + &suffix,
+        };
+    }
+
+    "?".to_string()
+}
+
+/// Name of the function an entry is an instance of, preferring its linkage name.
+fn origin_name<R: Reader>(
+    entry: &gimli::DebuggingInformationEntry<R>,
+    unit: &gimli::Unit<R>,
+    dwarf: &gimli::Dwarf<R>,
+) -> Option<String> {
+    let mut offset = match entry.attr_value(gimli::DW_AT_abstract_origin).ok()? {
+        Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+        _ => return None,
+    };
+
+    let attr_string = |entry: &gimli::DebuggingInformationEntry<R>, name| {
+        let value = entry.attr_value(name).ok()??;
+        let name = dwarf.attr_string(unit, value).ok()?;
+        name.to_string_lossy().ok().map(Cow::into_owned)
+    };
+
+    // The origin can in turn refer to a declaration, e.g. of a member function. Limit the
+    // number of indirections in case of malformed chains.
+    let mut name = None;
+    for _ in 0..4 {
+        let origin = unit.entry(offset).ok()?;
+        let linkage_name = attr_string(&origin, gimli::DW_AT_linkage_name)
+            .or_else(|| attr_string(&origin, gimli::DW_AT_MIPS_linkage_name));
+
+        if linkage_name.is_some() {
+            return linkage_name;
+        }
+
+        name = name.or_else(|| attr_string(&origin, gimli::DW_AT_name));
+        offset = match origin.attr_value(gimli::DW_AT_specification).ok()? {
+            Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+            _ => match origin.attr_value(gimli::DW_AT_abstract_origin).ok()? {
+                Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+                _ => break,
+            },
+        };
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dwo_location() {
+        let split = SplitDwarf::default();
+        let comp_dir = Some(Path::new("/build/obj"));
+        let name = Path::new("foo.dwo");
+        assert_eq!(locate_dwo(name, comp_dir, &split), Path::new("/build/obj/foo.dwo"));
+        assert_eq!(locate_dwo(name, None, &split), Path::new("foo.dwo"));
+
+        // The build directory is moved, e.g. when debugging on another machine.
+        let split = SplitDwarf { build_dir: Some(PathBuf::from("/home/me/obj")), dwp: None };
+        assert_eq!(
+            locate_dwo(Path::new("src/foo.dwo"), comp_dir, &split),
+            Path::new("/home/me/obj/src/foo.dwo")
+        );
+        let name = Path::new("/abs/foo.dwo");
+        assert_eq!(locate_dwo(name, comp_dir, &split), name);
+    }
+}
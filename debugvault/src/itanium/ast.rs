@@ -21,6 +21,10 @@ macro_rules! try_begin_parse {
     ($ctx:expr) => {
         let _auto_check_recursion = AutoParseRecursion::new($ctx)?;
     };
+    ($ctx:expr, $input:expr) => {
+        try_begin_parse!($ctx);
+        $ctx.reached($input.index());
+    };
 }
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -30,6 +34,8 @@ struct ParseContextState {
     recursion_level: u32,
     // Whether or not we are currently parsing a conversion operator.
     in_conversion: bool,
+    // Furthest offset in the input any production started parsing at.
+    furthest: usize,
 }
 
 /// Common context needed when parsing.
@@ -73,6 +79,18 @@ impl ParseContext {
         self.state.set(state);
     }
 
+    #[inline]
+    pub(crate) fn reached(&self, offset: usize) {
+        let mut state = self.state.get();
+        state.furthest = state.furthest.max(offset);
+        self.state.set(state);
+    }
+
+    /// Furthest offset in the input that was reached, where parsing failed if it did.
+    pub(crate) fn furthest(&self) -> usize {
+        self.state.get().furthest
+    }
+
     #[inline]
     fn in_conversion(&self) -> bool {
         self.state.get().in_conversion
@@ -338,8 +356,15 @@ pub(crate) struct DemangleContext<'a> {
 
     /// Whether to show types of expression literals.
     show_expression_literal_types: bool,
+
+    /// How many substitutions and template parameters are being demangled within one another.
+    indirections: u32,
 }
 
+/// Substitutions and template parameters can refer to themselves in malformed symbols, which
+/// would otherwise recurse until the stack overflows.
+const MAX_INDIRECTIONS: u32 = 256;
+
 impl<'a> DemangleContext<'a> {
     /// Construct a new `DemangleContext`.
     pub(crate) fn new(subs: &'a SubstitutionTable, input: &'a str) -> Self {
@@ -355,9 +380,27 @@ impl<'a> DemangleContext<'a> {
             is_template_prefix_in_nested_name: false,
             is_template_argument_pack: false,
             show_expression_literal_types: false,
+            indirections: 0,
         }
     }
 
+    /// Demangle a node referred to by a substitution or template parameter, bailing out with an
+    /// error token when references are nested too deep.
+    fn demangle_indirect<'prev>(
+        &mut self,
+        node: &'a impl Demangle<'a>,
+        scope: Option<ArgScopeStack<'prev, 'a>>,
+    ) {
+        if self.indirections >= MAX_INDIRECTIONS {
+            self.push("{recursion limit}", CONFIG.colors.comment);
+            return;
+        }
+
+        self.indirections += 1;
+        node.demangle(self, scope);
+        self.indirections -= 1;
+    }
+
     #[inline]
     fn ensure(&mut self, ch: char) {
         if self.last_char_written != Some(ch) {
@@ -767,7 +810,7 @@ macro_rules! define_handle {
                                      {
                 match *self {
                     $typename::WellKnown(ref comp) => comp.demangle(ctx, scope),
-                    $typename::BackReference(idx) => ctx.subs[idx].demangle(ctx, scope),
+                    $typename::BackReference(idx) => ctx.demangle_indirect(&ctx.subs[idx], scope),
                     $(
                         $typename::$extra_variant(ref extra) => extra.demangle(ctx, scope),
                     )*
@@ -803,7 +846,7 @@ impl<'subs> Demangle<'subs> for NonSubstitution {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.subs.non_substitution(self.0).demangle(ctx, scope)
+        ctx.demangle_indirect(ctx.subs.non_substitution(self.0), scope)
     }
 }
 
@@ -845,7 +888,7 @@ macro_rules! define_vocabulary {
                              _subs: &'a mut SubstitutionTable,
                              input: IndexStr<'b>)
                              -> Result<($typename, IndexStr<'b>)> {
-                try_begin_parse!(ctx);
+                try_begin_parse!(ctx, input);
 
                 let mut found_prefix = false;
                 $(
@@ -959,7 +1002,7 @@ impl Parse for MangledName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(MangledName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok(tail) = consume(b"_Z", input).or_else(|_| consume(b"__Z", input)) {
             let (encoding, tail) = Encoding::parse(ctx, subs, tail)?;
@@ -1041,7 +1084,7 @@ impl Parse for Encoding {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Encoding, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((name, tail)) = Name::parse(ctx, subs, input) {
             if let Ok((ty, tail)) = BareFunctionType::parse(ctx, subs, tail) {
@@ -1117,6 +1160,59 @@ impl<'subs> Demangle<'subs> for Encoding {
     }
 }
 
+impl Encoding {
+    /// Demangled types of a function's parameters and of its return value, which is only
+    /// mangled for templates.
+    pub(crate) fn signature<'subs>(
+        &'subs self,
+        subs: &'subs SubstitutionTable,
+        input: &'subs str,
+    ) -> Option<(Vec<String>, Option<String>)> {
+        let Encoding::Function(ref name, ref fun_ty) = *self else {
+            return None;
+        };
+
+        let root = None;
+        let scope = match name.get_leaf_name(subs) {
+            Some(LeafName::SourceName(leaf)) => root.push(leaf),
+            Some(LeafName::WellKnownComponent(leaf)) => root.push(leaf),
+            Some(LeafName::Closure(leaf)) => root.push(leaf),
+            Some(LeafName::UnnamedType(leaf)) => root.push(leaf),
+            None => root,
+        };
+
+        let template_args = name.get_template_args(subs);
+        let scope = match template_args {
+            Some(template_args) => scope.push(template_args),
+            None => scope,
+        };
+
+        let demangle = |ty: &'subs TypeHandle| {
+            let mut ctx = DemangleContext::new(subs, input);
+            ty.demangle(&mut ctx, scope);
+            ctx.stream.to_string()
+        };
+
+        let (ret, params) = if template_args.is_some() && !name.is_ctor_dtor_conversion(subs) {
+            (Some(demangle(fun_ty.ret())), &fun_ty.0[1..])
+        } else {
+            (None, &fun_ty.0[..])
+        };
+
+        // ABI tags trailing the name, e.g. `B5cxx11`, are parsed as the first parameters.
+        let mut params: Vec<String> = params.iter().map(demangle).collect();
+        let tags = params.iter().take_while(|param| param.starts_with("[abi:")).count();
+        params.drain(..tags);
+
+        // A function without parameters takes `void`.
+        if params == ["void"] {
+            params.clear();
+        }
+
+        Some((params, ret.filter(|ret| ret != "void")))
+    }
+}
+
 impl<'subs> DemangleAsInner<'subs> for Encoding {
     fn demangle_as_inner<'prev, 'ctx>(
         &'subs self,
@@ -1151,7 +1247,7 @@ impl Parse for CloneSuffix {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(CloneSuffix, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b".", input)?;
         let (identifier, mut tail) = CloneTypeIdentifier::parse(ctx, subs, tail)?;
@@ -1199,7 +1295,7 @@ impl Parse for GlobalCtorDtor {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(GlobalCtorDtor, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = match input.next_or(error::Error::UnexpectedEnd)? {
             (b'_', t) | (b'.', t) | (b'$', t) => t,
@@ -1272,7 +1368,7 @@ impl Parse for Name {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Name, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((name, tail)) = NestedName::parse(ctx, subs, input) {
             return Ok((Name::Nested(name), tail));
@@ -1372,7 +1468,7 @@ impl Parse for UnscopedName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnscopedName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok(tail) = consume(b"St", input) {
             let (name, tail) = UnqualifiedName::parse(ctx, subs, tail)?;
@@ -1445,7 +1541,7 @@ impl Parse for UnscopedTemplateNameHandle {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnscopedTemplateNameHandle, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((name, tail)) = UnscopedName::parse(ctx, subs, input) {
             let name = UnscopedTemplateName(name);
@@ -1511,7 +1607,7 @@ impl Parse for NestedName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(NestedName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"N", input)?;
 
@@ -1694,7 +1790,7 @@ impl Parse for PrefixHandle {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(PrefixHandle, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         #[inline]
         fn save(
@@ -1720,7 +1816,7 @@ impl Parse for PrefixHandle {
         let mut current = None;
 
         loop {
-            try_begin_parse!(ctx);
+            try_begin_parse!(ctx, input);
 
             match tail.peek() {
                 Some(b'E') | None => {
@@ -1989,7 +2085,7 @@ impl Parse for UnqualifiedName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnqualifiedName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((op, tail)) = OperatorName::parse(ctx, subs, input) {
             return Ok((UnqualifiedName::Operator(op), tail));
@@ -2117,7 +2213,7 @@ impl Parse for SourceName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(SourceName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (source_name_len, input) = parse_number(10, false, input)?;
         debug_assert!(source_name_len >= 0);
@@ -2135,6 +2231,9 @@ impl Parse for SourceName {
             return Err(error::Error::UnexpectedText);
         }
 
+        // A complete name is progress, even if nothing else is parsed after it.
+        ctx.reached(tail.index());
+
         let source_name = SourceName(identifier);
         Ok((source_name, tail))
     }
@@ -2185,7 +2284,7 @@ impl Parse for TaggedName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(TaggedName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"B", input)?;
         let (source_name, tail) = SourceName::parse(ctx, subs, tail)?;
@@ -2238,7 +2337,7 @@ impl Parse for Identifier {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Identifier, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if input.is_empty() {
             return Err(error::Error::UnexpectedEnd);
@@ -2319,7 +2418,7 @@ impl Parse for CloneTypeIdentifier {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(CloneTypeIdentifier, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if input.is_empty() {
             return Err(error::Error::UnexpectedEnd);
@@ -2376,7 +2475,7 @@ impl Parse for Number {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(isize, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
         parse_number(10, true, input)
     }
 }
@@ -2395,7 +2494,7 @@ impl Parse for SeqId {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(SeqId, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         parse_number(36, false, input).map(|(num, tail)| (SeqId(num as _), tail))
     }
@@ -2475,7 +2574,7 @@ impl OperatorName {
         input: IndexStr<'b>,
         from_expr: bool,
     ) -> Result<(OperatorName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((simple, tail)) = SimpleOperatorName::parse(ctx, subs, input) {
             return Ok((OperatorName::Simple(simple), tail));
@@ -2647,7 +2746,7 @@ impl Parse for CallOffset {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(CallOffset, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if input.is_empty() {
             return Err(error::Error::UnexpectedEnd);
@@ -2703,7 +2802,7 @@ impl Parse for NvOffset {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(NvOffset, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         Number::parse(ctx, subs, input).map(|(num, tail)| (NvOffset(num), tail))
     }
@@ -2723,7 +2822,7 @@ impl Parse for VOffset {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(VOffset, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (offset, tail) = Number::parse(ctx, subs, input)?;
         let tail = consume(b"_", tail)?;
@@ -2793,7 +2892,7 @@ impl Parse for CtorDtorName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(CtorDtorName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         match input.peek() {
             Some(b'C') => {
@@ -2980,7 +3079,7 @@ impl Parse for TypeHandle {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(TypeHandle, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         /// Insert the given type into the substitution table, and return a
         /// handle referencing the index in the table where it ended up.
@@ -3369,7 +3468,7 @@ impl Parse for CvQualifiers {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(CvQualifiers, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (restrict, tail) = if let Ok(tail) = consume(b"r", input) {
             (true, tail)
@@ -3530,7 +3629,7 @@ impl Parse for BuiltinType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(BuiltinType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((ty, tail)) = StandardBuiltinType::parse(ctx, subs, input) {
             return Ok((BuiltinType::Standard(ty), tail));
@@ -3609,7 +3708,7 @@ impl Parse for ExceptionSpec {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(ExceptionSpec, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok(tail) = consume(b"Do", input) {
             return Ok((ExceptionSpec::NoExcept, tail));
@@ -3661,7 +3760,7 @@ impl Parse for FunctionType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(FunctionType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (cv_qualifiers, tail) =
             if let Ok((cv_qualifiers, tail)) = CvQualifiers::parse(ctx, subs, input) {
@@ -3792,7 +3891,7 @@ impl Parse for BareFunctionType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(BareFunctionType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (types, tail) = one_or_more::<TypeHandle>(ctx, subs, input)?;
         Ok((BareFunctionType(types), tail))
@@ -3847,7 +3946,7 @@ impl Parse for Decltype {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Decltype, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"D", input)?;
 
@@ -3911,7 +4010,7 @@ impl Parse for ClassEnumType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(ClassEnumType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((name, tail)) = Name::parse(ctx, subs, input) {
             return Ok((ClassEnumType::Named(name), tail));
@@ -3987,7 +4086,7 @@ impl Parse for UnnamedTypeName {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnnamedTypeName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let input = consume(b"Ut", input)?;
         let (number, input) = match parse_number(10, false, input) {
@@ -4069,7 +4168,7 @@ impl Parse for ArrayType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(ArrayType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"A", input)?;
 
@@ -4208,7 +4307,7 @@ impl Parse for VectorType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(VectorType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"Dv", input)?;
 
@@ -4283,7 +4382,7 @@ impl Parse for PointerToMemberType {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(PointerToMemberType, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"M", input)?;
         let (ty1, tail) = TypeHandle::parse(ctx, subs, tail)?;
@@ -4340,7 +4439,7 @@ impl Parse for TemplateParam {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(TemplateParam, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let input = consume(b"T", input)?;
         let (number, input) = match parse_number(10, false, input) {
@@ -4365,7 +4464,7 @@ impl<'subs> Demangle<'subs> for TemplateParam {
             ctx.push_owned((self.0 + 1).to_string(), CONFIG.colors.asm.immediate);
         } else {
             let arg = self.resolve(scope);
-            arg.demangle(ctx, scope)
+            ctx.demangle_indirect(arg, scope)
         }
     }
 }
@@ -4410,7 +4509,7 @@ impl Parse for TemplateTemplateParamHandle {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(TemplateTemplateParamHandle, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((sub, tail)) = Substitution::parse(ctx, subs, input) {
             match sub {
@@ -4467,7 +4566,7 @@ impl Parse for FunctionParam {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(FunctionParam, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"f", input)?;
         if tail.is_empty() {
@@ -4523,7 +4622,7 @@ impl Parse for TemplateArgs {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(TemplateArgs, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"I", input)?;
 
@@ -4604,7 +4703,7 @@ impl Parse for TemplateArg {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(TemplateArg, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok(tail) = consume(b"X", input) {
             let (expr, tail) = Expression::parse(ctx, subs, tail)?;
@@ -4675,7 +4774,7 @@ impl Parse for MemberName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(MemberName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (name, tail) = UnqualifiedName::parse(ctx, subs, input)?;
         let name = UnscopedName::Unqualified(name);
@@ -4902,7 +5001,7 @@ impl Parse for Expression {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Expression, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok(tail) = consume(b"pp_", input) {
             let (expr, tail) = Expression::parse(ctx, subs, tail)?;
@@ -5601,7 +5700,7 @@ impl Parse for UnresolvedName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnresolvedName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok(tail) = consume(b"gs", input) {
             if let Ok((name, tail)) = BaseUnresolvedName::parse(ctx, subs, tail) {
@@ -5710,7 +5809,7 @@ impl Parse for UnresolvedTypeHandle {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnresolvedTypeHandle, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((param, tail)) = TemplateParam::parse(ctx, subs, input) {
             let (args, tail) = if let Ok((args, tail)) = TemplateArgs::parse(ctx, subs, tail) {
@@ -5782,7 +5881,7 @@ impl Parse for UnresolvedQualifierLevel {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(UnresolvedQualifierLevel, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (id, tail) = SimpleId::parse(ctx, subs, input)?;
         Ok((UnresolvedQualifierLevel(id), tail))
@@ -5814,7 +5913,7 @@ impl Parse for SimpleId {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(SimpleId, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (name, tail) = SourceName::parse(ctx, subs, input)?;
         let (args, tail) = if let Ok((args, tail)) = TemplateArgs::parse(ctx, subs, tail) {
@@ -5866,7 +5965,7 @@ impl Parse for BaseUnresolvedName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(BaseUnresolvedName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((name, tail)) = SimpleId::parse(ctx, subs, input) {
             return Ok((BaseUnresolvedName::Name(name), tail));
@@ -5928,7 +6027,7 @@ impl Parse for DestructorName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(DestructorName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((ty, tail)) = UnresolvedTypeHandle::parse(ctx, subs, input) {
             return Ok((DestructorName::Unresolved(ty), tail));
@@ -5979,7 +6078,7 @@ impl Parse for ExprPrimary {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(ExprPrimary, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"L", input)?;
 
@@ -6096,7 +6195,7 @@ impl Parse for Initializer {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Initializer, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"pi", input)?;
         let (exprs, tail) = zero_or_more::<Expression>(ctx, subs, tail)?;
@@ -6147,7 +6246,7 @@ impl Parse for LocalName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(LocalName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"Z", input)?;
         let (encoding, tail) = Encoding::parse(ctx, subs, tail)?;
@@ -6250,7 +6349,7 @@ impl Parse for Discriminator {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Discriminator, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"_", input)?;
 
@@ -6297,7 +6396,7 @@ impl Parse for ClosureTypeName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(ClosureTypeName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let tail = consume(b"Ul", input)?;
         let (sig, tail) = LambdaSig::parse(ctx, subs, tail)?;
@@ -6387,7 +6486,7 @@ impl Parse for LambdaSig {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(LambdaSig, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (types, tail) = if let Ok(tail) = consume(b"v", input) {
             (vec![], tail)
@@ -6424,7 +6523,7 @@ impl Parse for DataMemberPrefix {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(DataMemberPrefix, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (name, tail) = SourceName::parse(ctx, subs, input)?;
         let tail = consume(b"M", tail)?;
@@ -6490,7 +6589,7 @@ impl Parse for Substitution {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(Substitution, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if let Ok((well_known, tail)) = WellKnownComponent::parse(ctx, subs, input) {
             return Ok((Substitution::WellKnown(well_known), tail));
@@ -6670,7 +6769,7 @@ impl Parse for SpecialName {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(SpecialName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (head, tail) = match input.try_split_at(2) {
             None => return Err(error::Error::UnexpectedEnd),
@@ -6915,7 +7014,7 @@ impl Parse for ResourceName {
         _subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(ResourceName, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         if input.is_empty() {
             return Err(error::Error::UnexpectedEnd);
@@ -6999,7 +7098,7 @@ impl Parse for SubobjectExpr {
         subs: &'a mut SubstitutionTable,
         input: IndexStr<'b>,
     ) -> Result<(SubobjectExpr, IndexStr<'b>)> {
-        try_begin_parse!(ctx);
+        try_begin_parse!(ctx, input);
 
         let (ty, tail) = TypeHandle::parse(ctx, subs, input)?;
         let (expr, tail) = Expression::parse(ctx, subs, tail)?;
@@ -34,14 +34,39 @@ mod index_str;
 mod subs;
 mod tests;
 
+use crate::demangler::MAX_LEN;
 use crate::TokenStream;
 use ast::{Demangle, Parse, ParseContext};
 use error::{Error, Result};
 use index_str::IndexStr;
 
-pub fn parse(s: &str) -> Option<TokenStream> {
-    let sym = Symbol::new(s).ok()?;
-    Some(sym.demangle())
+/// Try to parse an itanium symbol, returning the offset it failed at otherwise.
+pub fn parse_detailed(s: &str) -> std::result::Result<TokenStream, usize> {
+    if s.len() > MAX_LEN {
+        return Err(MAX_LEN);
+    }
+
+    let ctx = ParseContext::new();
+    match Symbol::parse(s, &ctx) {
+        Ok(sym) => Ok(sym.demangle()),
+        Err(Error::UnexpectedEnd) => Err(s.len()),
+        Err(..) => Err(ctx.furthest()),
+    }
+}
+
+/// Types of the parameters and return value of a mangled function, which aren't part of its
+/// demangled name.
+pub fn parse_signature(s: &str) -> Option<(Vec<String>, Option<String>)> {
+    if s.len() > MAX_LEN {
+        return None;
+    }
+
+    let ctx = ParseContext::new();
+    let sym = Symbol::parse(s, &ctx).ok()?;
+    match sym.parsed {
+        ast::MangledName::Encoding(ref enc) => enc.signature(&sym.substitutions, sym.raw),
+        _ => None,
+    }
 }
 
 /// A mangled symbol that has been parsed into an AST.
@@ -59,18 +84,18 @@ impl Symbol<'_> {
     /// Given some raw storage, parse the mangled symbol from it with the default
     /// options.
     #[inline]
-    fn new(raw: &str) -> Result<Symbol> {
+    fn parse<'a>(raw: &'a str, ctx: &ParseContext) -> Result<Symbol<'a>> {
         let mut substitutions = subs::SubstitutionTable::new();
 
         let parsed = {
-            let ctx = ParseContext::new();
             let input = IndexStr::new(raw.as_bytes());
 
-            let (parsed, tail) = ast::MangledName::parse(&ctx, &mut substitutions, input)?;
+            let (parsed, tail) = ast::MangledName::parse(ctx, &mut substitutions, input)?;
 
             if tail.is_empty() {
                 parsed
             } else {
+                ctx.reached(tail.index());
                 return Err(Error::UnexpectedText);
             }
         };
@@ -1,13 +1,19 @@
 use binformat::RawSymbol;
-use demangler::TokenStream;
+pub use demangler::{Backend, DemangleError, Demangled, Scheme, TokenStream};
 use dwarf::Dwarf;
+pub use dwarf::SplitDwarf;
 use processor_shared::{AddressMap, Addressed};
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::fmt;
-use tokenizing::Token;
+use tokenizing::{Kind, Token};
 
+pub mod cache;
+pub mod clones;
+pub mod debuglink;
 pub mod prefix;
+pub mod replace;
 mod demangler;
 mod dwarf;
 mod error;
@@ -33,6 +39,23 @@ pub struct FileAttr {
     pub column_end: usize,
 }
 
+/// How a function parameter is passed according to its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// Integers, pointers and enums, passed in general purpose registers.
+    Integer,
+    /// Floating-point values, passed in vector registers.
+    Float,
+}
+
+/// Types of a function's parameters and of its return value, as named by the debug info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<String>,
+    /// `None` for functions that don't return anything.
+    pub ret: Option<String>,
+}
+
 pub struct Symbol {
     name: TokenStream,
     name_as_str: Arc<str>,
@@ -60,6 +83,127 @@ fn is_name_an_intrinsic(name: &str) -> bool {
     false
 }
 
+/// Demangle a symbol, returning the original name if it isn't mangled.
+pub fn demangle(name: &str) -> String {
+    demangler::parse(name).to_string()
+}
+
+/// AST of a MSVC symbol as JSON, `None` if it isn't one.
+pub fn demangle_ast(name: &str) -> Option<serde_json::Value> {
+    msvc::parse_json(name).ok()
+}
+
+/// Signature of a mangled C++ function, whose demangled name leaves out the parameters.
+pub fn demangle_signature(name: &str) -> Option<Signature> {
+    let (params, ret) = itanium::parse_signature(name)?;
+    Some(Signature { params, ret })
+}
+
+/// Add a demangler for another mangling scheme, it's used by everything that demangles.
+pub fn register_demangler(backend: Backend) {
+    demangler::register(backend)
+}
+
+/// All demanglers, in the order they're tried.
+pub fn demanglers() -> Vec<Backend> {
+    demangler::backends()
+}
+
+/// Demangle many symbols at once across all threads. Each symbol either demangles or reports
+/// which scheme it looked like and where it stopped being valid.
+pub fn demangle_batch(names: &[&str]) -> Vec<Result<Demangled, DemangleError>> {
+    let mut output = Vec::with_capacity(names.len());
+    parallel_compute(names.to_vec(), &mut output, |name| demangler::parse_detailed(name));
+    output
+}
+
+fn is_symbol_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"_$.@?".contains(&c)
+}
+
+/// Demangle the symbol at the start of `word`, returning how many bytes it spans.
+fn demangle_word(word: &str) -> Option<(usize, TokenStream)> {
+    // Punctuation ending a sentence isn't part of the symbol.
+    let mut word = word.trim_end_matches('.');
+
+    // Versions and stub suffixes such as `@GLIBCXX_3.4` and `@plt` are kept as is, except
+    // for MSVC symbols that use '@' as a separator.
+    if !word.starts_with('?') {
+        word = word.split('@').next().unwrap_or(word);
+    }
+
+    match demangler::parse_strict(word) {
+        Ok((_, name)) => Some((word.len(), name)),
+        // Whatever trails the symbol, e.g. `_Z3fooi:` or `_ZN3foo3barEv+0x10`, is only known
+        // once it fails to parse.
+        Err(DemangleError { scheme: Some(_), offset }) if offset > 0 && offset < word.len() => {
+            let (_, name) = demangler::parse_strict(&word[..offset]).ok()?;
+            Some((offset, name))
+        }
+        Err(..) => None,
+    }
+}
+
+/// Mangled symbols found anywhere in a line of text, e.g. the output of another tool, together
+/// with their byte range in the line. Words that don't demangle are left out, the ones that do
+/// are simplified if `simplify` is given. The parts of a name in `omit` are only left out here
+/// when simplifying, otherwise that's up to rendering.
+pub fn demangle_line(
+    line: &str,
+    simplify: Option<&replace::Simplify>,
+    omit: Kind,
+) -> Vec<(Range<usize>, Symbol)> {
+    let bytes = line.as_bytes();
+    let mut symbols = Vec::new();
+    let mut word_end = 0;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        if !is_symbol_char(bytes[idx]) {
+            idx += 1;
+            continue;
+        }
+
+        // Symbols can start anywhere in a word, e.g. in `sym=_Z3fooi` or `.L_ZN3foo3barEv`.
+        if word_end <= idx {
+            word_end = idx;
+            while word_end < bytes.len() && is_symbol_char(bytes[word_end]) {
+                word_end += 1;
+            }
+        }
+
+        let (len, name) = match demangle_word(&line[idx..word_end]) {
+            Some(demangled) => demangled,
+            None => {
+                idx += 1;
+                continue;
+            }
+        };
+
+        // Simplified names lose their highlighting, as the tokens don't line up anymore.
+        let name = match simplify {
+            Some(simplify) => {
+                let name = tokenizing::render_omitting(name.tokens(), false, omit);
+                TokenStream::simple(&replace::simplify(&name, simplify))
+            }
+            None => name,
+        };
+
+        let name_as_str = Arc::from(name.to_string());
+        let symbol = Symbol {
+            name,
+            name_as_str,
+            module: None,
+            is_intrinsics: false,
+        };
+
+        symbols.push((idx..idx + len, symbol));
+        idx += len;
+    }
+
+    symbols
+}
+
 impl Default for Symbol {
     fn default() -> Self {
         Self {
@@ -77,6 +221,17 @@ impl Symbol {
         self.name.tokens()
     }
 
+    /// Name as it appears in the object, before demangling.
+    #[inline]
+    pub fn mangled(&self) -> &str {
+        self.name.inner()
+    }
+
+    /// Scheme the symbol is mangled with, `None` if it isn't mangled.
+    pub fn scheme(&self) -> Option<Scheme> {
+        demangler::scheme(self.mangled())
+    }
+
     #[inline]
     pub fn module(&self) -> Option<&str> {
         self.module.as_deref()
@@ -120,6 +275,22 @@ pub struct Index {
     /// The addresses are sorted.
     pub file_attrs: AddressMap<FileAttr>,
 
+    /// Mapping from function entries to the kind of each of their parameters.
+    /// The addresses are sorted.
+    pub params: AddressMap<Vec<ParamKind>>,
+
+    /// Mapping from function entries to the types of their parameters and return value.
+    /// The addresses are sorted.
+    pub signatures: AddressMap<Signature>,
+
+    /// Mapping from out-of-line instances of a function, e.g. specialized clones, to the
+    /// (mangled) name of the function they're derived from. The addresses are sorted.
+    pub origins: AddressMap<String>,
+
+    /// Mapping from the start of inlined code to the (mangled) name of the inlined function.
+    /// The addresses are sorted.
+    pub inlined: AddressMap<String>,
+
     /// Efficient string match searcher.
     pub prefixes: prefix::PrefixMatcher,
 
@@ -128,22 +299,46 @@ pub struct Index {
 }
 
 impl Index {
+    /// Index the symbols and debug info of an object, together with the debug info of its
+    /// separate debug file if it was stripped.
     pub fn parse<'data>(
         obj: &object::File<'data>,
-        #[allow(unused_variables)]
+        debug: Option<&object::File>,
         path: &Path,
+        split: &SplitDwarf,
         mut syms: AddressMap<RawSymbol<'data>>,
     ) -> Result<Self, Error> {
         let mut this = Self::default();
 
+        // Like gdb, a package of split DWARF is looked for next to the object.
+        let mut dwp = path.as_os_str().to_owned();
+        dwp.push(".dwp");
+        let split = SplitDwarf {
+            dwp: split.dwp.clone().or_else(|| Some(PathBuf::from(dwp)).filter(|dwp| dwp.is_file())),
+            ..split.clone()
+        };
+
         let dwarf = match obj {
             #[cfg(target_os = "macos")]
-            object::File::MachO32(_) | object::File::MachO64(_) => macho_dwarf(obj, path),
-            _ => Dwarf::parse(obj),
+            object::File::MachO32(_) | object::File::MachO64(_) if debug.is_none() => {
+                macho_dwarf(obj, path)
+            }
+            _ => Dwarf::parse(obj, &split).and_then(|mut dwarf| {
+                if let Some(debug) = debug {
+                    dwarf.merge(Dwarf::parse(debug, &split)?);
+                }
+                Ok(dwarf)
+            }),
         };
 
         match dwarf {
-            Ok(dwarf) => this.file_attrs.extend(dwarf.file_attrs),
+            Ok(dwarf) => {
+                this.file_attrs.extend(dwarf.file_attrs);
+                this.params.extend(dwarf.params);
+                this.signatures.extend(dwarf.signatures);
+                this.origins.extend(dwarf.origins);
+                this.inlined.extend(dwarf.inlined);
+            }
             Err(err) => log::complex!(
                 w "[dwarf::parse] ",
                 y format!("Failed to parse dwarf: {err:?}"),
@@ -170,12 +365,23 @@ impl Index {
             syms.extend(std::mem::take(&mut pdb.syms));
         }
 
+        this.parse_symbols(syms);
+        Ok(this)
+    }
+
+    /// Build an index from symbols alone, for inputs that don't carry any debug info.
+    pub fn from_symbols(syms: AddressMap<RawSymbol>) -> Self {
+        let mut this = Self::default();
+        this.parse_symbols(syms);
+        this
+    }
+
+    fn parse_symbols(&mut self, syms: AddressMap<RawSymbol>) {
         log::PROGRESS.set("Parsing symbols.", syms.len());
-        parallel_compute(syms.mapping, &mut this.syms, |Addressed { addr, item }| {
+        parallel_compute(syms.mapping, &mut self.syms, |Addressed { addr, item }| {
             let demangled = demangler::parse(item.name);
             let is_intrinsics = is_name_an_intrinsic(item.name);
-            let name_as_str = String::from_iter(demangled.tokens().iter().map(|t| &t.text[..]));
-            let name_as_str = Arc::from(name_as_str);
+            let name_as_str = Arc::from(demangled.to_string());
             let symbol = Symbol {
                 name_as_str,
                 name: demangled,
@@ -190,16 +396,14 @@ impl Index {
             }
         });
 
-        this.sort_and_validate();
-        this.build_prefix_tree();
+        self.sort_and_validate();
+        self.build_prefix_tree();
 
         log::complex!(
             w "[index::parse] found ",
-            g this.syms.len().to_string(),
+            g self.syms.len().to_string(),
             w " functions."
         );
-
-        Ok(this)
     }
 
     fn sort_and_validate(&mut self) {
@@ -227,6 +431,15 @@ impl Index {
 
         // Keep file attrs sorted so it can be binary searched.
         self.file_attrs.sort_unstable();
+
+        // Keep signatures sorted so it can be binary searched.
+        self.params.sort_unstable();
+        self.params.dedup_by_key(|params| params.addr);
+        self.signatures.sort_unstable();
+        self.signatures.dedup_by_key(|signature| signature.addr);
+
+        self.origins.sort_unstable();
+        self.inlined.sort_unstable();
     }
 
     fn build_prefix_tree(&mut self) {
@@ -264,6 +477,22 @@ impl Index {
         }
     }
 
+    /// Parameters of a function, if they were recovered from debug info.
+    pub fn get_params_by_addr(&self, addr: usize) -> Option<&[ParamKind]> {
+        match self.params.search(addr) {
+            Ok(idx) => Some(&self.params[idx].item),
+            Err(..) => None,
+        }
+    }
+
+    /// Signature of a function, if it was recovered from debug info.
+    pub fn get_signature_by_addr(&self, addr: usize) -> Option<&Signature> {
+        match self.signatures.search(addr) {
+            Ok(idx) => Some(&self.signatures[idx].item),
+            Err(..) => None,
+        }
+    }
+
     pub fn get_func_by_name(&self, name: &str) -> Option<usize> {
         self.syms.iter().find(|func| func.item.as_str() == name).map(|func| func.addr)
     }
@@ -285,7 +514,7 @@ impl Index {
 
 #[cfg(target_os = "macos")]
 pub fn macho_dwarf(obj: &object::File, path: &Path) -> Result<Dwarf, dwarf::Error> {
-    let mut dwarf = Dwarf::parse(obj)?;
+    let mut dwarf = Dwarf::parse(obj, &SplitDwarf::default())?;
 
     let ext = if let Some(exist_ext) = path.extension().and_then(|ext| ext.to_str()) {
         exist_ext.to_string() + ".dSYM"
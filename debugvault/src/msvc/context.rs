@@ -1,22 +1,33 @@
 use super::{Literal, Modifiers, NestedPath, Scope, Type};
 
 use crate::TokenStream;
-use tokenizing::Color32;
+use tokenizing::{Color32, Kind};
 
-/// Max recursion depth
-const MAX_DEPTH: usize = 256;
+/// Max recursion depth, counting both paths and types. Each level takes up several frames, so
+/// it's kept low enough for an unoptimized build to stay within the 2 MiB stack of a thread.
+const MAX_DEPTH: usize = 64;
+
+/// Max number of nodes parsed, counting the nodes a back reference expands to every time it's
+/// used. References to types that themselves use references can otherwise grow exponentially.
+const MAX_NODES: usize = 1 << 16;
 
 #[derive(Debug)]
 pub(super) struct Backrefs {
     /// Up to 10 idents can be memorized for lookup using backref's: ?0, ?1, ..
     memorized: [NestedPath; 10],
 
+    /// Number of nodes each memorized ident expands to.
+    memorized_nodes: [usize; 10],
+
     /// Number of so far memorized idents.
     memorized_count: usize,
 
     /// A max of 10 function parameters is supported.
     params: [Type; 10],
 
+    /// Number of nodes each function parameter expands to.
+    param_nodes: [usize; 10],
+
     /// Number of so far encountered function parameters.
     param_count: usize,
 }
@@ -28,45 +39,53 @@ impl Backrefs {
 
         Self {
             memorized: [NO_PATH; 10],
+            memorized_nodes: [0; 10],
             memorized_count: 0,
             params: [NO_TYPE; 10],
+            param_nodes: [0; 10],
             param_count: 0,
         }
     }
 
-    pub fn memorize_path<'b>(&'b mut self, path: &'b NestedPath) {
+    /// Memorizes a path that took `nodes` to parse, as counted by [`Context::nodes`].
+    pub fn memorize_path<'b>(&'b mut self, path: &'b NestedPath, nodes: usize) {
         let memorized = &self.memorized[..self.memorized_count];
 
         if !memorized.contains(path) && self.memorized_count != 10 {
             self.memorized[self.memorized_count] = path.clone();
+            self.memorized_nodes[self.memorized_count] = nodes;
             self.memorized_count += 1;
         }
     }
 
-    pub fn get_memorized_path(&mut self, idx: usize) -> Option<NestedPath> {
+    pub fn get_memorized_path(&mut self, idx: usize, ctx: &mut Context) -> Option<NestedPath> {
         if idx >= self.memorized_count {
             return None;
         }
 
+        ctx.expand(self.memorized_nodes[idx])?;
         Some(self.memorized[idx].clone())
     }
 
+    /// Memorizes a type that took `nodes` to parse, as counted by [`Context::nodes`].
     // TODO: change interface to not be cloning a type
-    pub fn memorize_param<'b>(&'b mut self, tipe: &'b Type) {
+    pub fn memorize_param<'b>(&'b mut self, tipe: &'b Type, nodes: usize) {
         let memorized = &self.params[..self.param_count];
 
         if !memorized.contains(tipe) && self.param_count != 10 {
             self.params[self.param_count] = tipe.clone();
+            self.param_nodes[self.param_count] = nodes;
             self.param_count += 1;
         }
     }
 
     // TODO: change interface to not be cloning a type
-    pub fn get_memorized_param(&self, idx: usize) -> Option<Type> {
+    pub fn get_memorized_param(&self, idx: usize, ctx: &mut Context) -> Option<Type> {
         if idx >= self.param_count {
             return None;
         }
 
+        ctx.expand(self.param_nodes[idx])?;
         Some(self.params[idx].clone())
     }
 }
@@ -81,6 +100,7 @@ pub(super) struct Context<'a> {
     pub scope: &'a Scope,
     modifiers_in_use: Modifiers,
     depth: usize,
+    nodes: usize,
 }
 
 impl Context<'_> {
@@ -96,6 +116,7 @@ impl Context<'_> {
             scope: &NO_SCOPE,
             modifiers_in_use: Modifiers::empty(),
             depth: 0,
+            nodes: 0,
         }
     }
 
@@ -105,6 +126,13 @@ impl Context<'_> {
         self.stream.push(literal, color);
     }
 
+    /// Marks the tokens pushed by `f` as a `kind`, so that they can be left out when rendering.
+    pub fn marked(&mut self, kind: Kind, f: impl FnOnce(&mut Self)) {
+        let start = self.stream.tokens().len();
+        f(self);
+        self.stream.mark(start, kind);
+    }
+
     /// Create a reference to the underlying pinned string that holds the mangled symbol.
     #[inline]
     pub fn src<'b>(&self) -> &'b str {
@@ -254,6 +282,25 @@ impl Context<'_> {
             return None;
         }
 
+        self.expand(1)
+    }
+
+    /// Number of nodes parsed so far, including the ones back references expanded to.
+    #[inline]
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Counts `nodes` more as parsed, like the ones a back reference expands to, failing when
+    /// there are more than [`MAX_NODES`] in total.
+    #[inline]
+    pub fn expand(&mut self, nodes: usize) -> Option<()> {
+        self.nodes = self.nodes.saturating_add(nodes);
+
+        if self.nodes > MAX_NODES {
+            return None;
+        }
+
         Some(())
     }
 
@@ -67,23 +67,38 @@ mod tests;
 
 use bitflags::bitflags;
 use context::{Backrefs, Context};
-use tokenizing::colors;
+use crate::demangler::MAX_LEN;
+use serde::{Serialize, Serializer};
+use std::cell::RefCell;
+use tokenizing::{colors, Kind};
 use config::CONFIG;
 
+thread_local! {
+    /// Mangled symbol the [`Literal`]'s of an AST index into whilst it's serialized.
+    static SOURCE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
 #[cfg(test)]
-const PRINTING_SCOPE: bool = true;
+pub fn parse(s: &str) -> Option<crate::TokenStream> {
+    parse_detailed(s).ok()
+}
 
-#[cfg(not(test))]
-const PRINTING_SCOPE: bool = false;
+/// Try to parse a msvc symbol, returning the offset it failed at otherwise.
+pub fn parse_detailed(s: &str) -> Result<crate::TokenStream, usize> {
+    if s.len() > MAX_LEN {
+        return Err(MAX_LEN);
+    }
 
-pub fn parse(s: &str) -> Option<crate::TokenStream> {
     let mut ctx = Context::new(s);
     let mut backrefs = Backrefs::new();
 
     // llvm appears to generate a '.' prefix on some symbols
     ctx.eat(b'.');
 
-    let sym = Symbol::parse(&mut ctx, &mut backrefs)?;
+    let sym = match Symbol::parse(&mut ctx, &mut backrefs) {
+        Some(sym) => sym,
+        None => return Err(s.len() - ctx.src().len()),
+    };
     sym.demangle(&mut ctx, &mut backrefs);
 
     #[cfg(test)]
@@ -94,7 +109,33 @@ pub fn parse(s: &str) -> Option<crate::TokenStream> {
         }
     }
 
-    Some(ctx.stream)
+    Ok(ctx.stream)
+}
+
+/// Try to parse a msvc symbol into its AST as JSON, instead of flattening it to text, returning
+/// the offset it failed at otherwise.
+pub fn parse_json(s: &str) -> Result<serde_json::Value, usize> {
+    if s.len() > MAX_LEN {
+        return Err(MAX_LEN);
+    }
+
+    let mut ctx = Context::new(s);
+    let mut backrefs = Backrefs::new();
+
+    // llvm appears to generate a '.' prefix on some symbols
+    ctx.eat(b'.');
+
+    let sym = match Symbol::parse(&mut ctx, &mut backrefs) {
+        Some(sym) => sym,
+        None => return Err(s.len() - ctx.src().len()),
+    };
+
+    SOURCE.with(|src| *src.borrow_mut() = s.to_string());
+    let json = serde_json::to_value(&sym);
+    SOURCE.with(|src| src.borrow_mut().clear());
+
+    // serializing only fails for maps with keys that aren't strings, which the AST doesn't have
+    Ok(json.unwrap_or_default())
 }
 
 /// Converts an trivially printable node to a string.
@@ -134,7 +175,7 @@ trait Parse: Sized {
 #[derive(Default, Debug, Clone, PartialEq)]
 struct TypeRef(u32);
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
 enum Type {
     #[default]
     Unit,
@@ -184,6 +225,9 @@ enum Type {
     /// Renamed literal with additional modifiers.
     Typedef(Modifiers, Literal),
 
+    /// Type the compiler names itself, like `<auto>` for a deduced return type.
+    Custom(Modifiers, Literal),
+
     /// String encoded using a format we don't know.
     Encoded(EncodedIdent),
 
@@ -228,6 +272,16 @@ enum Type {
 
 impl Parse for Type {
     fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
+        ctx.descent()?;
+        let tipe = Type::parse_descended(ctx, backrefs);
+        ctx.ascent();
+        tipe
+    }
+}
+
+impl Type {
+    /// Parses a type after the depth is incremented, as types nest without going through a path.
+    fn parse_descended(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
         match ctx.peek_slice(..2) {
             Some(b"W4") => {
                 ctx.offset += 2;
@@ -344,13 +398,20 @@ impl Parse for Type {
             }
         }
 
+        if ctx.eat_slice(b"?<") {
+            ctx.offset -= 1;
+            let name = ctx.ident()?;
+            ctx.consume(b'@')?;
+            return Some(Type::Custom(ctx.pop_modifiers(), name));
+        }
+
         if ctx.eat(b'?') {
             let idx = ctx.number()?;
             return Some(Type::TemplateParameterIdx(idx));
         }
 
         if let Some(digit) = ctx.base10() {
-            return backrefs.get_memorized_param(digit);
+            return backrefs.get_memorized_param(digit, ctx);
         }
 
         let modi = ctx.pop_modifiers();
@@ -603,28 +664,36 @@ impl<'a> PositionalDemangle<'a> for Type {
             }
             Type::Function(func) => {
                 func.return_type.demangle_pre(ctx, backrefs);
-                func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.marked(Kind::CALLING_CONV, |ctx| {
+                    func.calling_conv.demangle(ctx, backrefs);
+                    ctx.stream.push(" ", colors::WHITE);
+                });
             }
             Type::MemberFunction(func) => {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
-                func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.marked(Kind::CALLING_CONV, |ctx| {
+                    func.calling_conv.demangle(ctx, backrefs);
+                    ctx.stream.push(" ", colors::WHITE);
+                });
             }
             Type::MemberFunctionPtr(func) => {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
-                func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.marked(Kind::CALLING_CONV, |ctx| {
+                    func.calling_conv.demangle(ctx, backrefs);
+                    ctx.stream.push(" ", colors::WHITE);
+                });
                 func.class_name.demangle(ctx, backrefs);
             }
             Type::Inherited(func) => {
                 ctx.stream.push("&", CONFIG.colors.asm.pointer);
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
-                func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.marked(Kind::CALLING_CONV, |ctx| {
+                    func.calling_conv.demangle(ctx, backrefs);
+                    ctx.stream.push(" ", colors::WHITE);
+                });
                 func.class_name.demangle(ctx, backrefs);
                 func.params.demangle(ctx, backrefs);
             }
@@ -642,6 +711,10 @@ impl<'a> PositionalDemangle<'a> for Type {
                 ctx.push_literal(name, CONFIG.colors.asm.component);
                 modi.demangle(ctx, backrefs);
             }
+            Type::Custom(modi, name) => {
+                ctx.push_literal(name, CONFIG.colors.asm.primitive);
+                modi.demangle(ctx, backrefs);
+            }
             Type::Variable(Variable {
                 storage,
                 tipe,
@@ -699,16 +772,19 @@ impl<'a> PositionalDemangle<'a> for Type {
             Type::Function(func) => {
                 func.params.demangle(ctx, backrefs);
                 func.quali.0.demangle(ctx, backrefs);
+                func.params.1.demangle(ctx, backrefs);
                 func.return_type.demangle_post(ctx, backrefs);
             }
             Type::MemberFunction(func) => {
                 func.params.demangle(ctx, backrefs);
                 func.qualifiers.0.demangle(ctx, backrefs);
+                func.params.1.demangle(ctx, backrefs);
                 func.return_type.demangle_post(ctx, backrefs);
             }
             Type::MemberFunctionPtr(func) => {
                 func.params.demangle(ctx, backrefs);
                 func.qualifiers.0.demangle(ctx, backrefs);
+                func.params.1.demangle(ctx, backrefs);
                 func.return_type.demangle_post(ctx, backrefs);
             }
             Type::Variable(Variable { tipe, .. }) => tipe.demangle_post(ctx, backrefs),
@@ -742,7 +818,7 @@ impl<'a> PositionalDemangle<'a> for Type {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct SymbolType(Type);
 
 impl Parse for SymbolType {
@@ -832,7 +908,7 @@ impl Parse for SymbolType {
 ///                 | 3 // global
 ///                 | 4 // static local
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct Variable {
     storage: StorageVariable,
     quali: PointeeQualifiers,
@@ -872,7 +948,7 @@ impl Parse for Variable {
 /// <function-type> = <pointee-cvr-qualifier> <calling-convention>
 ///                   <return-type> <parameters> <throw-spec>
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct Function {
     calling_conv: CallingConv,
     quali: PointeeQualifiers,
@@ -905,7 +981,7 @@ impl Parse for Function {
 ///                   | <storage-scope> <pointee-cvr-qualifier> <calling-convention>
 ///                     <return-type> <function-parameters>
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct MemberFunction {
     storage_scope: StorageScope,
     calling_conv: CallingConv,
@@ -949,7 +1025,7 @@ impl Parse for MemberFunction {
 /// <cvr-qualifier> = E <cvr-qualifier>       // ptr64
 ///                  | <pointee-cvr-qualifier> // ptr64 + pointee qualifiers
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct MemberFunctionPtr {
     storage_scope: StorageScope,
     class_name: Path,
@@ -999,7 +1075,7 @@ impl Parse for MemberFunctionPtr {
 /// <array> = <pointee-cvr-qualifier> <number>{dimensions} <type>
 /// <dimensions> = <number>
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct Arrays {
     modifiers: Modifiers,
     tipe: Box<Type>,
@@ -1033,7 +1109,7 @@ impl Parse for Arrays {
 /// <pointee> = <cvr-qualifier> <type>
 ///           | E <cvr-qualifier> <type> // ptr64 + pointee qualifiers
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct Pointee(Type);
 
 impl Parse for Pointee {
@@ -1051,14 +1127,16 @@ impl Parse for Pointee {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct FunctionReturnType(Type);
 
 impl Parse for FunctionReturnType {
     fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
         ctx.pop_modifiers();
 
-        if ctx.eat(b'?') {
+        // qualifiers, unless it's a custom type like `?<auto>@`
+        if ctx.peek_slice(..2).is_some_and(|s| s[0] == b'?' && s[1] != b'<') {
+            ctx.offset += 1;
             let modi = match ctx.take()? {
                 b'A' => Modifiers::empty(),
                 b'B' => Modifiers::CONST,
@@ -1080,19 +1158,21 @@ impl Parse for FunctionReturnType {
 
 impl<'a> PositionalDemangle<'a> for FunctionReturnType {
     fn demangle_pre(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
-        self.0.demangle_pre(ctx, backrefs);
-        if self.0 != Type::Unit {
-            ctx.stream.push(" ", colors::WHITE);
-        }
+        ctx.marked(Kind::RETURN_TYPE, |ctx| {
+            self.0.demangle_pre(ctx, backrefs);
+            if self.0 != Type::Unit {
+                ctx.stream.push(" ", colors::WHITE);
+            }
+        });
     }
 
     fn demangle_post(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
-        self.0.demangle_post(ctx, backrefs);
+        ctx.marked(Kind::RETURN_TYPE, |ctx| self.0.demangle_post(ctx, backrefs));
     }
 }
 
 /// Either a well known operator of a class or some C++ internal operator implementation.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 enum Intrinsics {
     Ctor,
     Dtor,
@@ -1148,6 +1228,7 @@ enum Intrinsics {
     DynamicInitializer(Box<Symbol>),
     DynamicAtExitDtor(Box<Symbol>),
     LocalStaticThreadGuard,
+    ThreadSafeStaticGuard(usize),
     Spaceship,
     CoAwait,
     VBaseDtor,
@@ -1176,7 +1257,8 @@ enum Intrinsics {
     LocalVftableCtorClosure,
     PlacementDeleteClosure,
     PlacementDeleteArrayClosure,
-    SourceName(Literal),
+    LiteralOperator(Literal),
+    StructuredBinding(usize),
 }
 
 impl Parse for Intrinsics {
@@ -1316,7 +1398,7 @@ impl Parse for Intrinsics {
                     }
                     b'J' => Intrinsics::LocalStaticThreadGuard,
                     b'M' => Intrinsics::Spaceship,
-                    b'K' => return ctx.ident().map(Intrinsics::SourceName),
+                    b'K' => return ctx.ident().map(Intrinsics::LiteralOperator),
                     _ => return None,
                 },
                 _ => return None,
@@ -1373,8 +1455,9 @@ impl<'a> Demangle<'a> for Intrinsics {
                 ctx.stream.push("''", CONFIG.colors.brackets);
                 return;
             }
-            Intrinsics::SourceName(ref src) => {
-                ctx.push_literal(src, CONFIG.colors.asm.component);
+            Intrinsics::LiteralOperator(ref suffix) => {
+                ctx.stream.push("operator \"\"", CONFIG.colors.asm.primitive);
+                ctx.push_literal(suffix, CONFIG.colors.asm.component);
                 return;
             }
             Intrinsics::RTTITypeDescriptor(_, ref tipe) => {
@@ -1407,6 +1490,20 @@ impl<'a> Demangle<'a> for Intrinsics {
                 ctx.stream.push(")'", CONFIG.colors.brackets);
                 return;
             }
+            Intrinsics::ThreadSafeStaticGuard(idx) => {
+                ctx.stream.push("`", CONFIG.colors.brackets);
+                ctx.stream.push("thread-safe static guard ", CONFIG.colors.asm.primitive);
+                ctx.stream.push_string(idx.to_string(), CONFIG.colors.asm.annotation);
+                ctx.stream.push("'", CONFIG.colors.brackets);
+                return;
+            }
+            Intrinsics::StructuredBinding(idx) => {
+                ctx.stream.push("`", CONFIG.colors.brackets);
+                ctx.stream.push("structured binding ", CONFIG.colors.asm.primitive);
+                ctx.stream.push_string(idx.to_string(), CONFIG.colors.asm.annotation);
+                ctx.stream.push("'", CONFIG.colors.brackets);
+                return;
+            }
             Intrinsics::RTTIBaseClassArray => {
                 ctx.stream.push("`", CONFIG.colors.brackets);
                 ctx.stream.push("RTTI Base Class Array", CONFIG.colors.asm.primitive);
@@ -1505,17 +1602,34 @@ impl<'a> Demangle<'a> for Intrinsics {
 ///              | <type>+ @
 ///              | <type>* Z // variable args
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct Parameters(Vec<Type>);
 
 impl Parse for Parameters {
     fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
+        Parameters::parse_list(ctx, backrefs, false)
+    }
+}
+
+impl Parameters {
+    /// Parses the types of a list, where argument lists end at the first `Z` as it's followed by
+    /// a throw specification.
+    fn parse_list(ctx: &mut Context, backrefs: &mut Backrefs, is_args: bool) -> Option<Self> {
         let mut types = Vec::new();
 
+        if is_args && ctx.eat(b'X') {
+            return Some(Parameters(vec![Type::Void(Modifiers::empty())]));
+        }
+
         loop {
+            if is_args && ctx.eat(b'Z') {
+                types.push(Type::Variadic);
+                break;
+            }
+
             // list ending is encountered or a variadic type.
             if ctx.eat(b'Z') {
-                // parameter lists can only have a variadic as it's last argument
+                // parameter lists can only have a variadic as its last argument
                 // therefore two Z's means a variadic type at the end which doesn't get memorized
                 if ctx.eat(b'Z') {
                     types.push(Type::Variadic);
@@ -1530,18 +1644,19 @@ impl Parse for Parameters {
             }
 
             if let Some(digit) = ctx.base10() {
-                types.push(backrefs.get_memorized_param(digit)?);
+                types.push(backrefs.get_memorized_param(digit, ctx)?);
                 continue;
             }
 
             let start = ctx.src().len();
+            let nodes = ctx.nodes();
             let tipe = Type::parse(ctx, backrefs)?;
             let end = ctx.src().len();
 
             // single-letter types are ignored for backref's because
             // memorizing them doesn't save anything.
             if start - end > 1 {
-                backrefs.memorize_param(&tipe);
+                backrefs.memorize_param(&tipe, ctx.nodes() - nodes);
             }
 
             types.push(tipe);
@@ -1572,19 +1687,19 @@ impl<'a> Demangle<'a> for Parameters {
 
 /// ```text
 /// <function-parameters> = <argument-list> <throw-spec>
+///
+/// <argument-list> = X // void
+///                 | <type>+ @
+///                 | <type>* Z // variable args
 /// ```
-#[derive(Debug, PartialEq, Clone)]
-struct FunctionParameters(Parameters);
+#[derive(Debug, PartialEq, Clone, Serialize)]
+struct FunctionParameters(Parameters, ThrowSpec);
 
 impl Parse for FunctionParameters {
     fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
-        let params = Parameters::parse(ctx, backrefs)?;
-
-        if !ctx.eat(b'Z') || !ctx.eat_slice(b"_E") {
-            // TODO: handle throw attribute
-        }
-
-        Some(FunctionParameters(params))
+        let params = Parameters::parse_list(ctx, backrefs, true)?;
+        let throws = ThrowSpec::parse(ctx, backrefs)?;
+        Some(FunctionParameters(params, throws))
     }
 }
 
@@ -1596,8 +1711,55 @@ impl<'a> Demangle<'a> for FunctionParameters {
     }
 }
 
+/// ```text
+/// <throw-spec> = Z               // none
+///              | _E              // noexcept
+///              | @               // throw()
+///              | <argument-list> // throw(<argument-list>)
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize)]
+enum ThrowSpec {
+    None,
+    Noexcept,
+    Throw(Parameters),
+}
+
+impl Parse for ThrowSpec {
+    fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
+        // some symbols are truncated right after their parameters
+        if ctx.eat(b'Z') || ctx.src().is_empty() {
+            return Some(ThrowSpec::None);
+        }
+
+        if ctx.eat_slice(b"_E") {
+            return Some(ThrowSpec::Noexcept);
+        }
+
+        if ctx.eat(b'@') {
+            return Some(ThrowSpec::Throw(Parameters(Vec::new())));
+        }
+
+        Parameters::parse_list(ctx, backrefs, true).map(ThrowSpec::Throw)
+    }
+}
+
+impl<'a> Demangle<'a> for ThrowSpec {
+    fn demangle(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
+        match self {
+            ThrowSpec::None => {}
+            ThrowSpec::Noexcept => ctx.stream.push(" noexcept", CONFIG.colors.asm.annotation),
+            ThrowSpec::Throw(types) => {
+                ctx.stream.push(" throw", CONFIG.colors.asm.annotation);
+                ctx.stream.push("(", CONFIG.colors.brackets);
+                types.demangle(ctx, backrefs);
+                ctx.stream.push(")", CONFIG.colors.brackets);
+            }
+        }
+    }
+}
+
 /// Calling conventions supported by MSVC
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
 enum CallingConv {
     Cdecl,
     Pascal,
@@ -1643,11 +1805,12 @@ impl<'a> Demangle<'a> for CallingConv {
             CallingConv::Anonymous => return,
         };
 
-        ctx.stream.push(literal, CONFIG.colors.asm.annotation);
+        let color = CONFIG.colors.asm.annotation;
+        ctx.marked(Kind::CALLING_CONV, |ctx| ctx.stream.push(literal, color));
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
 enum StorageVariable {
     PrivateStatic,
     ProtectedStatic,
@@ -1658,16 +1821,16 @@ enum StorageVariable {
 
 impl<'a> Demangle<'a> for StorageVariable {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
-        if PRINTING_SCOPE {
-            let literal = match self {
-                StorageVariable::PrivateStatic => "private: static ",
-                StorageVariable::ProtectedStatic => "protected: static ",
-                StorageVariable::PublicStatic => "public: static ",
-                StorageVariable::Global | StorageVariable::FunctionLocalStatic => return,
-            };
+        let access = match self {
+            StorageVariable::PrivateStatic => "private: ",
+            StorageVariable::ProtectedStatic => "protected: ",
+            StorageVariable::PublicStatic => "public: ",
+            StorageVariable::Global | StorageVariable::FunctionLocalStatic => return,
+        };
 
-            ctx.stream.push(literal, CONFIG.colors.asm.annotation);
-        }
+        let color = CONFIG.colors.asm.annotation;
+        ctx.marked(Kind::ACCESS, |ctx| ctx.stream.push(access, color));
+        ctx.stream.push("static ", color);
     }
 }
 
@@ -1695,7 +1858,7 @@ bitflags! {
     /// <global-function> = Y // global near
     ///                   | Z // global far
     /// ```
-    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
     struct StorageScope: u32 {
         const PUBLIC    = 1;
         const PRIVATE   = 1 << 1;
@@ -1746,7 +1909,7 @@ impl<'a> Demangle<'a> for StorageScope {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
         let color = CONFIG.colors.asm.annotation;
 
-        if PRINTING_SCOPE {
+        ctx.marked(Kind::ACCESS, |ctx| {
             if self.contains(StorageScope::PUBLIC) {
                 ctx.stream.push("public: ", color);
             }
@@ -1758,7 +1921,7 @@ impl<'a> Demangle<'a> for StorageScope {
             if self.contains(StorageScope::PROTECTED) {
                 ctx.stream.push("protected: ", color);
             }
-        }
+        });
 
         if self.contains(StorageScope::STATIC) {
             ctx.stream.push("static ", color);
@@ -1771,7 +1934,7 @@ impl<'a> Demangle<'a> for StorageScope {
 }
 
 bitflags! {
-    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
     struct Modifiers: u32 {
         const CONST     = 0b0000000000001;
         const VOLATILE  = 0b0000000000010;
@@ -1808,11 +1971,11 @@ impl<'a> Demangle<'a> for Modifiers {
         let color = CONFIG.colors.asm.annotation;
 
         if self.contains(Modifiers::CONST) {
-            ctx.stream.push(" const", color);
+            ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push(" const", color));
         }
 
         if self.contains(Modifiers::VOLATILE) {
-            ctx.stream.push(" volatile", color);
+            ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push(" volatile", color));
         }
     }
 }
@@ -1823,7 +1986,7 @@ impl<'a> Demangle<'a> for Modifiers {
 ///                 = D // const volatile
 ///                 = A // no qualifier
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct MemberReturnQualifiers(Modifiers);
 
 impl Parse for MemberReturnQualifiers {
@@ -1855,7 +2018,7 @@ impl Parse for MemberReturnQualifiers {
 ///                 = A // no qualifier
 ///                 = Q // no qualifier
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct Qualifiers(Modifiers);
 
 impl Parse for Qualifiers {
@@ -1878,11 +2041,11 @@ impl<'a> Demangle<'a> for Qualifiers {
         let color = CONFIG.colors.asm.annotation;
 
         if self.0.contains(Modifiers::CONST) {
-            ctx.stream.push("const ", color);
+            ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push("const ", color));
         }
 
         if self.0.contains(Modifiers::VOLATILE) {
-            ctx.stream.push("volatile ", color);
+            ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push("volatile ", color));
         }
 
         if self.0.contains(Modifiers::FAR) {
@@ -1916,7 +2079,7 @@ impl<'a> Demangle<'a> for Qualifiers {
 ///                         = G // &
 ///                         = H // &&
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct PointeeQualifiers(Modifiers);
 
 impl Parse for PointeeQualifiers {
@@ -1955,11 +2118,11 @@ impl<'a> Demangle<'a> for PointeeQualifiers {
         let color = CONFIG.colors.asm.annotation;
 
         if self.0.contains(Modifiers::CONST) {
-            ctx.stream.push(" const", color);
+            ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push(" const", color));
         }
 
         if self.0.contains(Modifiers::VOLATILE) {
-            ctx.stream.push(" volatile", color);
+            ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push(" volatile", color));
         }
 
         if self.0.contains(Modifiers::FAR) {
@@ -1997,7 +2160,7 @@ impl Parse for Literal {
     fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
         let ident = ctx.ident()?;
         if ctx.memorizing {
-            backrefs.memorize_path(&NestedPath::Literal(ident));
+            backrefs.memorize_path(&NestedPath::Literal(ident), 1);
         }
         Some(ident)
     }
@@ -2009,10 +2172,147 @@ impl Literal {
     }
 }
 
+impl Serialize for Literal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SOURCE.with(|src| {
+            let src = src.borrow();
+            serializer.serialize_str(src.get(self.start..self.end).unwrap_or(""))
+        })
+    }
+}
+
+/// Literal string constant, of which only the first 32 bytes are encoded.
+///
+/// ```text
+/// <string-literal> = <char-width> <number> <number> <encoded-char>* @
+///
+/// <char-width> = 0 // char
+///              | 1 // wchar_t
+///
+/// <encoded-char> = <ident-char>
+///                | ? <base-10-digit>      // one of ",/\\:. \n\t'-"
+///                | ? <lowercase-letter>   // 0xe1..=0xfa
+///                | ? <uppercase-letter>   // 0xc1..=0xda
+///                | ?$ <hex-digit>{2}      // A..=P for every nibble
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize)]
+struct StringLiteral {
+    is_wide: bool,
+    /// Length in bytes including the nul terminator, which may be more than was encoded.
+    len: usize,
+    crc: usize,
+    bytes: Vec<u8>,
+}
+
+impl StringLiteral {
+    fn encoded_char(ctx: &mut Context) -> Option<u8> {
+        if !ctx.eat(b'?') {
+            return ctx.take();
+        }
+
+        let chr = match ctx.take()? {
+            b'$' => {
+                let nibble = |chr| match chr {
+                    b'A'..=b'P' => Some(chr - b'A'),
+                    _ => None,
+                };
+
+                let high = nibble(ctx.take()?)?;
+                let low = nibble(ctx.take()?)?;
+                (high << 4) | low
+            }
+            chr @ b'0'..=b'9' => b",/\\:. \n\t'-"[(chr - b'0') as usize],
+            chr @ b'a'..=b'z' => chr - b'a' + 0xe1,
+            chr @ b'A'..=b'Z' => chr - b'A' + 0xc1,
+            _ => return None,
+        };
+
+        Some(chr)
+    }
+
+    /// Characters of the string, without a nul terminator if the whole string was encoded.
+    fn chars(&self) -> Vec<u32> {
+        let mut chars: Vec<u32> = if self.is_wide {
+            self.bytes.chunks(2).map(|c| c.iter().fold(0, |n, &b| (n << 8) | b as u32)).collect()
+        } else {
+            self.bytes.iter().map(|&b| b as u32).collect()
+        };
+
+        if !self.is_truncated() && chars.last() == Some(&0) {
+            chars.pop();
+        }
+
+        chars
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.bytes.len() < self.len
+    }
+}
+
+impl Parse for StringLiteral {
+    fn parse(ctx: &mut Context, _: &mut Backrefs) -> Option<Self> {
+        let is_wide = match ctx.take()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+
+        let len = usize::try_from(ctx.number()?).ok()?;
+        let crc = usize::try_from(ctx.number()?).ok()?;
+        let mut bytes = Vec::new();
+
+        while !ctx.eat(b'@') {
+            bytes.push(StringLiteral::encoded_char(ctx)?);
+        }
+
+        Some(StringLiteral {
+            is_wide,
+            len,
+            crc,
+            bytes,
+        })
+    }
+}
+
+impl<'a> Demangle<'a> for StringLiteral {
+    fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
+        let (tipe, prefix) = if self.is_wide { ("wchar_t", "L\"") } else { ("char", "\"") };
+        let color = CONFIG.colors.asm.annotation;
+
+        ctx.stream.push(tipe, CONFIG.colors.asm.primitive);
+        ctx.marked(Kind::QUALIFIER, |ctx| ctx.stream.push(" const", color));
+        ctx.stream.push(" *", CONFIG.colors.asm.pointer);
+        ctx.stream.push(" {", CONFIG.colors.brackets);
+        ctx.stream.push(prefix, CONFIG.colors.brackets);
+
+        let mut text = String::new();
+        for chr in self.chars() {
+            match char::from_u32(chr) {
+                Some('"') => text += "\\\"",
+                Some('\\') => text += "\\\\",
+                Some('\n') => text += "\\n",
+                Some('\t') => text += "\\t",
+                Some('\r') => text += "\\r",
+                Some('\0') => text += "\\0",
+                Some(chr) if !chr.is_control() => text.push(chr),
+                _ => text += &format!("\\x{chr:02x}"),
+            }
+        }
+
+        ctx.stream.push_string(text, CONFIG.colors.asm.string);
+        ctx.stream.push("\"", CONFIG.colors.brackets);
+        if self.is_truncated() {
+            ctx.stream.push("...", CONFIG.colors.delimiter);
+        }
+        ctx.stream.push("}", CONFIG.colors.brackets);
+    }
+}
+
 /// ```text
 /// <md5> = <base-16-digit>{32} @
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct MD5(Literal);
 
 impl Parse for MD5 {
@@ -2056,7 +2356,7 @@ impl<'a> Demangle<'a> for MD5 {
 /// ```text
 /// <scope> = {<nested-path>}* @
 /// ```
-#[derive(Debug, Default, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone, Serialize)]
 struct Scope(Vec<NestedPath>);
 
 impl Parse for Scope {
@@ -2096,7 +2396,7 @@ impl<'a> Demangle<'a> for Scope {
 /// ```text
 /// <path> = <unqualified-path> <scope>
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct Path {
     name: UnqualifiedPath,
     scope: Scope,
@@ -2131,8 +2431,9 @@ impl<'a> Demangle<'a> for Path {
 ///                | Q <nested-path> // interface
 ///                | I <nested-path> // interface
 ///                | <disambiguator>
+///                | <source-name>
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 enum NestedPath {
     Literal(Literal),
     Interface(Box<NestedPath>),
@@ -2141,6 +2442,9 @@ enum NestedPath {
     Symbol(Box<Symbol>),
     Disambiguator(isize),
     MD5(MD5),
+    StringLiteral(StringLiteral),
+    Lambda(Literal),
+    UnnamedType(Literal),
     Anonymous,
 }
 
@@ -2151,7 +2455,7 @@ impl Parse for NestedPath {
         // return memorized ident
         if let Some(digit) = ctx.base10() {
             ctx.ascent();
-            return backrefs.get_memorized_path(digit);
+            return backrefs.get_memorized_path(digit, ctx);
         }
 
         if ctx.eat(b'?') {
@@ -2161,8 +2465,9 @@ impl Parse for NestedPath {
                 b'$' => {
                     ctx.offset += 1;
 
+                    let nodes = ctx.nodes();
                     let template = Template::parse(ctx, backrefs).map(NestedPath::Template)?;
-                    backrefs.memorize_path(&template);
+                    backrefs.memorize_path(&template, ctx.nodes() - nodes);
                     Some(template)
                 }
                 b'A' => {
@@ -2177,10 +2482,11 @@ impl Parse for NestedPath {
                             ctx.offset += 1;
                         }
 
-                        backrefs.memorize_path(&NestedPath::Literal(Literal {
+                        let name = NestedPath::Literal(Literal {
                             start: ctx.offset,
                             end: ctx.offset + len,
-                        }));
+                        });
+                        backrefs.memorize_path(&name, 1);
                     }
 
                     ctx.consume(b'@')?;
@@ -2199,11 +2505,11 @@ impl Parse for NestedPath {
             };
         }
 
-        let ident = ctx.ident()?;
-        backrefs.memorize_path(&NestedPath::Literal(ident));
+        let name = source_name(ctx)?;
+        backrefs.memorize_path(&name, 1);
 
         ctx.ascent();
-        Some(NestedPath::Literal(ident))
+        Some(name)
     }
 }
 
@@ -2218,13 +2524,33 @@ impl<'a> Demangle<'a> for NestedPath {
             }
             NestedPath::Template(template) => template.demangle(ctx, backrefs),
             NestedPath::Intrinsics(int) => int.demangle(ctx, backrefs),
-            NestedPath::Symbol(inner) => inner.demangle(ctx, backrefs),
+            // symbols that scope a local, like a function's static variable
+            NestedPath::Symbol(inner) => {
+                let scope = ctx.scope;
+                ctx.stream.push("`", CONFIG.colors.brackets);
+                inner.demangle(ctx, backrefs);
+                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.scope = scope;
+            }
             NestedPath::Disambiguator(val) => {
                 ctx.stream.push("`", CONFIG.colors.brackets);
                 ctx.stream.push_string(val.to_string(), CONFIG.colors.asm.component);
                 ctx.stream.push("'", CONFIG.colors.brackets);
             }
             NestedPath::MD5(md5) => md5.demangle(ctx, backrefs),
+            NestedPath::StringLiteral(string) => string.demangle(ctx, backrefs),
+            NestedPath::Lambda(id) => {
+                ctx.stream.push("<", CONFIG.colors.brackets);
+                ctx.stream.push("lambda_", CONFIG.colors.asm.primitive);
+                ctx.push_literal(id, CONFIG.colors.asm.annotation);
+                ctx.stream.push(">", CONFIG.colors.brackets);
+            }
+            NestedPath::UnnamedType(name) => {
+                ctx.stream.push("<", CONFIG.colors.brackets);
+                ctx.stream.push("unnamed-type-", CONFIG.colors.asm.primitive);
+                ctx.push_literal(name, CONFIG.colors.asm.component);
+                ctx.stream.push(">", CONFIG.colors.brackets);
+            }
             NestedPath::Anonymous => {
                 ctx.stream.push("`", CONFIG.colors.brackets);
                 ctx.stream.push("anonymous namespace", CONFIG.colors.asm.component);
@@ -2239,7 +2565,7 @@ impl<'a> Demangle<'a> for NestedPath {
 ///                     | <intrinsic>
 ///                     | <source-name>
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct UnqualifiedPath(NestedPath);
 
 impl Parse for UnqualifiedPath {
@@ -2249,15 +2575,16 @@ impl Parse for UnqualifiedPath {
         // return memorized ident
         if let Some(digit) = ctx.base10() {
             ctx.ascent();
-            return backrefs.get_memorized_path(digit).map(UnqualifiedPath);
+            return backrefs.get_memorized_path(digit, ctx).map(UnqualifiedPath);
         }
 
         // special intrinsic
         if ctx.eat(b'?') {
             if ctx.eat(b'$') {
                 ctx.ascent();
+                let nodes = ctx.nodes();
                 let template = Template::parse(ctx, backrefs).map(NestedPath::Template)?;
-                backrefs.memorize_path(&template);
+                backrefs.memorize_path(&template, ctx.nodes() - nodes);
                 return Some(UnqualifiedPath(template));
             }
 
@@ -2267,13 +2594,47 @@ impl Parse for UnqualifiedPath {
                 .map(UnqualifiedPath);
         }
 
-        // parse source name as a literal
-        let name = ctx.ident()?;
-        backrefs.memorize_path(&NestedPath::Literal(name));
+        let name = source_name(ctx)?;
+        backrefs.memorize_path(&name, 1);
 
         ctx.ascent();
-        Some(UnqualifiedPath(NestedPath::Literal(name)))
+        Some(UnqualifiedPath(name))
+    }
+}
+
+/// Source name, of which the ones the compiler makes up for lambdas and unnamed types are told
+/// apart from a literal.
+///
+/// ```text
+/// <source-name>  = <lambda>
+///                | <unnamed-type>
+///                | <ident> @
+///
+/// <lambda>       = \<lambda_ <ident> > @
+/// <unnamed-type> = \<unnamed-type- <ident> > @
+/// ```
+fn source_name(ctx: &mut Context) -> Option<NestedPath> {
+    let ident = ctx.ident()?;
+    let text = &ctx.stream.inner()[ident.start..ident.end];
+
+    let inner = |prefix: &str| {
+        let inner = text.strip_prefix(prefix)?.strip_suffix('>')?;
+        let start = ident.start + prefix.len();
+        (!inner.is_empty()).then_some(Literal {
+            start,
+            end: start + inner.len(),
+        })
+    };
+
+    if let Some(id) = inner("<lambda_") {
+        return Some(NestedPath::Lambda(id));
+    }
+
+    if let Some(name) = inner("<unnamed-type-") {
+        return Some(NestedPath::UnnamedType(name));
     }
+
+    Some(NestedPath::Literal(ident))
 }
 
 /// ```text
@@ -2286,7 +2647,7 @@ impl Parse for UnqualifiedPath {
 ///             | _
 ///             | $
 /// ```
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
 struct EncodedIdent;
 
 impl Parse for EncodedIdent {
@@ -2328,7 +2689,7 @@ impl Parse for EncodedIdent {
 ///                | $ <constant-value>
 ///                | <template-arg>+
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct Template {
     name: Box<UnqualifiedPath>,
     params: Parameters,
@@ -2360,11 +2721,13 @@ impl<'a> Demangle<'a> for Template {
 ///
 /// ```text
 /// <symbol> = ??@ <md5>
+///          | ??_C@_ <string-literal>
 ///          | ?$TSS <static-guard>
+///          | ?$S <structured-binding>
 ///          | ?$ <template>
 ///          | <path> [<symbol-type>]
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 struct Symbol {
     path: Path,
     tipe: Type,
@@ -2384,22 +2747,34 @@ impl Parse for Symbol {
                 .map(Path::into);
         }
 
-        // scoped template instantiation?
-        if ctx.eat_slice(b"$TSS") {
-            let mut n = 0usize;
+        // string constant
+        if ctx.eat_slice(b"?_C@_") {
+            ctx.ascent();
+            return StringLiteral::parse(ctx, backrefs)
+                .map(NestedPath::StringLiteral)
+                .map(NestedPath::into)
+                .map(Path::into);
+        }
+
+        // thread-safe static guard or structured binding, unless it's a template that happens to
+        // be named like one
+        let numbered: Option<(&[u8], NumberedIntrinsic)> = match ctx.src().as_bytes() {
+            [b'$', b'T', b'S', b'S', ..] => Some((b"$TSS", Intrinsics::ThreadSafeStaticGuard)),
+            [b'$', b'S', ..] => Some((b"$S", Intrinsics::StructuredBinding)),
+            _ => None,
+        };
 
-            while !ctx.eat(b'@') {
-                let digit = ctx.base10()?;
+        if let Some((prefix, intrinsic)) = numbered {
+            let (offset, parsing_qualifiers) = (ctx.offset, ctx.parsing_qualifiers);
+            ctx.offset += prefix.len();
 
-                n = n.checked_mul(10)?;
-                n = n.checked_add(digit)?;
+            if let Some(variable) = numbered_variable(ctx, backrefs, intrinsic) {
+                ctx.ascent();
+                return Some(variable);
             }
 
-            ctx.ascent();
-            log::complex!(
-                w "[parse::parse] ",
-                y "TODO: return thread safe static guard"
-            );
+            ctx.offset = offset;
+            ctx.parsing_qualifiers = parsing_qualifiers;
         }
 
         // any other template instantiation
@@ -2435,8 +2810,10 @@ impl<'a> Demangle<'a> for Symbol {
         if let NestedPath::Intrinsics(Intrinsics::TypeCast) = self.path.name.0 {
             if let Type::MemberFunction(ref func) = self.tipe {
                 func.storage_scope.demangle(ctx, backrefs);
-                func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.marked(Kind::CALLING_CONV, |ctx| {
+                    func.calling_conv.demangle(ctx, backrefs);
+                    ctx.stream.push(" ", colors::WHITE);
+                });
                 self.path.scope.demangle(ctx, backrefs);
                 ctx.stream.push("::", CONFIG.colors.delimiter);
                 ctx.stream.push("operator ", CONFIG.colors.asm.primitive);
@@ -2451,8 +2828,10 @@ impl<'a> Demangle<'a> for Symbol {
             if let NestedPath::Intrinsics(Intrinsics::TypeCast) = template.name.0 {
                 if let Type::MemberFunction(ref func) = self.tipe {
                     func.storage_scope.demangle(ctx, backrefs);
-                    func.calling_conv.demangle(ctx, backrefs);
-                    ctx.stream.push(" ", colors::WHITE);
+                    ctx.marked(Kind::CALLING_CONV, |ctx| {
+                        func.calling_conv.demangle(ctx, backrefs);
+                        ctx.stream.push(" ", colors::WHITE);
+                    });
                     self.path.scope.demangle(ctx, backrefs);
                     ctx.stream.push("::", CONFIG.colors.delimiter);
                     ctx.stream.push("operator", CONFIG.colors.asm.primitive);
@@ -2473,6 +2852,48 @@ impl<'a> Demangle<'a> for Symbol {
     }
 }
 
+/// Intrinsic of a variable the compiler introduced, given its number.
+type NumberedIntrinsic = fn(usize) -> Intrinsics;
+
+/// Variable the compiler introduced, named by a number. These are either the guard of a function's
+/// local static that's initialized on first use or the object a structured binding decomposes.
+///
+/// ```text
+/// <static-guard>       = <base-10-digit>+ @ <scope> <variable>
+/// <structured-binding> = <base-10-digit>+ @ <scope> <variable>
+/// ```
+fn numbered_variable(
+    ctx: &mut Context,
+    backrefs: &mut Backrefs,
+    intrinsic: NumberedIntrinsic,
+) -> Option<Symbol> {
+    let mut n = 0usize;
+
+    while !ctx.eat(b'@') {
+        let digit = ctx.base10()?;
+
+        n = n.checked_mul(10)?;
+        n = n.checked_add(digit)?;
+    }
+
+    let name = NestedPath::Intrinsics(intrinsic(n));
+    let scope = Scope::parse(ctx, backrefs)?;
+
+    ctx.parsing_qualifiers = false;
+    let tipe = match SymbolType::parse(ctx, backrefs)?.0 {
+        tipe @ Type::Variable(..) => tipe,
+        _ => return None,
+    };
+
+    Some(Symbol {
+        path: Path {
+            name: UnqualifiedPath(name),
+            scope,
+        },
+        tipe,
+    })
+}
+
 impl From<Path> for Symbol {
     #[inline]
     fn from(path: Path) -> Symbol {
@@ -31,10 +31,13 @@ fn simple() {
                 calling_conv: CallingConv::Cdecl,
                 quali: PointeeQualifiers(Modifiers::empty()),
                 return_type: Box::new(FunctionReturnType(Type::Void(Modifiers::empty()))),
-                params: FunctionParameters(Parameters(vec![
-                    Type::Float(Modifiers::empty()),
-                    Type::Int(Modifiers::empty()),
-                ])),
+                params: FunctionParameters(
+                    Parameters(vec![
+                        Type::Float(Modifiers::empty()),
+                        Type::Int(Modifiers::empty())
+                    ]),
+                    ThrowSpec::None,
+                ),
             }),
         }
     );
@@ -58,6 +61,125 @@ fn complex_operator() {
         "class klass const & __cdecl operator>>(class klass const &, unsigned __int64)");
 }
 
+#[test]
+fn omitting() {
+    let omitted = |mangled: &str, omit: Kind| {
+        let symbol = parse(mangled).unwrap();
+        tokenizing::render_omitting(symbol.tokens(), false, omit)
+    };
+
+    let operator = "??5@YAAEBVklass@@AEBV0@_K@Z";
+    assert_eq!(
+        omitted(operator, Kind::CALLING_CONV),
+        "class klass const & operator>>(class klass const &, unsigned __int64)"
+    );
+    assert_eq!(
+        omitted(operator, Kind::RETURN_TYPE),
+        "__cdecl operator>>(class klass const &, unsigned __int64)"
+    );
+    assert_eq!(
+        omitted(operator, Kind::QUALIFIER),
+        "class klass & __cdecl operator>>(class klass &, unsigned __int64)"
+    );
+    assert_eq!(
+        omitted("??0klass@@QEAA@XZ", Kind::ACCESS | Kind::CALLING_CONV),
+        "klass::klass(void)"
+    );
+    assert_eq!(omitted("?x@@2HA", Kind::ACCESS), "static int x");
+}
+
+#[test]
+fn json() {
+    let ast = parse_json("?x@klass@@YAXMH@Z").unwrap();
+    let func = &ast["tipe"]["Function"];
+
+    assert_eq!(ast["path"]["name"]["Literal"], "x");
+    assert_eq!(ast["path"]["scope"][0]["Literal"], "klass");
+    assert_eq!(func["calling_conv"], "Cdecl");
+    assert!(func["return_type"].get("Void").is_some());
+    assert!(func["params"][0][0].get("Float").is_some());
+    assert!(func["params"][0][1].get("Int").is_some());
+    assert_eq!(func["params"][1], "None");
+    assert_eq!(parse_json("x"), Err(0));
+}
+
+#[test]
+fn limits() {
+    let nested = format!("?x@@3{}HA", "PEA".repeat(1024));
+    assert!(parse(&nested).is_none());
+
+    // Every function pointer takes nine of the previous one, expanding exponentially.
+    let mut expanding = String::from("?f@@YAXP6AXH@Z");
+    for idx in 0..9 {
+        expanding += &format!("P6AX{}@Z", idx.to_string().repeat(9));
+    }
+    expanding += "@Z";
+    assert!(parse(&expanding).is_none());
+
+    let long = format!("?x@@3HA{}", "A".repeat(crate::demangler::MAX_LEN));
+    assert!(parse_detailed(&long).is_err());
+}
+
+#[test]
+fn function_throw_a() {
+    eq!("?fnii@@YAHH@@" => "int __cdecl fnii(int) throw()");
+}
+
+#[test]
+fn function_throw_b() {
+    eq!("?fnii@@YAHH@HH@" => "int __cdecl fnii(int) throw(int, int)");
+}
+
+#[test]
+fn function_noexcept() {
+    eq!("?fnii@@YAHH@_E" => "int __cdecl fnii(int) noexcept");
+    eq!("?fnii@@YAXX_E" => "void __cdecl fnii(void) noexcept");
+    eq!("?fnii@@YAHHZ_E" => "int __cdecl fnii(int, ...) noexcept");
+    eq!("?fnii@@YAHHZZ" => "int __cdecl fnii(int, ...)");
+}
+
+#[test]
+fn string_literals() {
+    eq!("??_C@_00CNPNBAHC@?$AA@" => "char const * {\"\"}");
+    eq!("??_C@_07CONGLLKI@WPA_PSK?$AA@" => "char const * {\"WPA_PSK\"}");
+    eq!("??_C@_02BDIFHNNP@?1?9?$AA@" => "char const * {\"/-\"}");
+    eq!("??_C@_01EOFPKCAF@?$EA?$AA@" => "char const * {\"@\"}");
+    eq!("??_C@_03JHFLOMIE@a?6?$CC?$AA@" => "char const * {\"a\\n\\\"\"}");
+    eq!("??_C@_1G@BEFBAEHP@?$AAh?$AAe?$AA?$AA@" => "wchar_t const * {L\"he\"}");
+    eq!("??_C@_0MA@ABCDEFGH@abc@" => "char const * {\"abc\"...}");
+}
+
+#[test]
+fn literal_operator() {
+    eq!("??__K_deg@@YAHO@Z" => "int __cdecl operator \"\"_deg(long double)");
+}
+
+#[test]
+fn structured_binding() {
+    eq!("?$S1@@3UPair@@A" => "struct Pair `structured binding 1'");
+}
+
+#[test]
+fn autoclassinit() {
+    eq!("?__autoclassinit2@klass@@QEAAX_K@Z" =>
+        "public: void __cdecl klass::__autoclassinit2(unsigned __int64)");
+}
+
+#[test]
+fn lambda() {
+    eq!("??R<lambda_1>@?0??foo@@YAXXZ@QAE@XZ" =>
+        "public: __thiscall `void __cdecl foo(void)'::<lambda_1>::operator()(void)");
+    eq!("??R<lambda_1>@?0??foo@@YAXXZ@QAE?A?<auto>@@H@Z" =>
+        "public: <auto> __thiscall `void __cdecl foo(void)'::<lambda_1>::operator()(int)");
+}
+
+#[test]
+fn unnamed_type() {
+    eq!("?x@@3U<unnamed-type-x>@@A" => "struct <unnamed-type-x> x");
+    eq!("?y@?$klass@U<unnamed-type-x>@@@@2HA" =>
+        "public: static int klass<struct <unnamed-type-x>>::y");
+}
+
 #[test]
 fn operator_new_array() {
     eq!("??_U@YAPEAX_KAEAVklass@@@Z" =>
@@ -74,6 +196,13 @@ fn anonymous() {
     eq!("?A@?A0x43583946@@3VB@@B" => "class B const `anonymous namespace'::A");
 }
 
+#[test]
+fn thread_safe_static_guard() {
+    eq!("?$TSS0@?1??foo@@YAXXZ@4HA" =>
+        "int `void __cdecl foo(void)'::`thread-safe static guard 0'");
+    eq!("?$TSS0@HH" => "TSS0<int, int>");
+}
+
 #[test]
 fn triple_q0() {
     eq!("??__E??_7name0@name1@@6B@@@YMXXZ@?A0x647dec29@@$$FYMXXZ" =>
@@ -1794,16 +1923,6 @@ fn special_template_parameters_questionnumber() {
 // }
 //
 // #[test]
-// fn function_throw_a() {
-//     eq!("?fnii@@YAHH@@" => "int __cdecl fnii(int) throw()");
-// }
-//
-// #[test]
-// fn function_throw_b() {
-//     eq!("?fnii@@YAHH@HH@" => "int __cdecl fnii(int) throw(int, int)");
-// }
-//
-// #[test]
 // fn function_no_return_not_void() {
 //     eq!("?fnii@@YA@H@Z" => "__cdecl fnii(int)");
 // }
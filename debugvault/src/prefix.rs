@@ -29,7 +29,9 @@ impl PrefixMatcher {
 
     /// Sorts elements to allow for searching.
     pub fn reorder(&mut self) {
-        self.items.sort_unstable_by(|a, b| cmp(a.as_str(), b.as_str()));
+        // Sorting must use a total order, strings that share a prefix end up next to each other
+        // regardless.
+        self.items.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
         self.items.shrink_to_fit();
     }
 
@@ -124,4 +126,18 @@ mod test {
             assert_eq!(&x.as_str(), y, "Mismatch");
         }
     }
+
+    #[test]
+    fn reorder() {
+        let mut tree = PrefixMatcher::default();
+        for s in ["b", "ab", "a", "aa", "abc", "a"] {
+            tree.insert(&symbol(s));
+        }
+        tree.reorder();
+        let expected = ["a", "a", "aa", "ab", "abc", "b"];
+        assert_eq!(tree.items.len(), expected.len(), "Mismatched length");
+        for (x, y) in tree.items.iter().zip(expected.iter()) {
+            assert_eq!(&x.as_str(), y, "Mismatch");
+        }
+    }
 }
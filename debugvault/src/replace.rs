@@ -0,0 +1,752 @@
+//! Rewriting demangled names into the shorter form they're written as in source code.
+//!
+//! Demangled templates of the standard library spell out every default argument, e.g.
+//! `std::vector<int, std::allocator<int>>` for what's written as `std::vector<int>`, and strings
+//! are instantiations of `std::basic_string` several arguments deep. Arguments are compared
+//! without the `class` and `struct` keywords of MSVC and without whitespace, so both Itanium and
+//! MSVC names are simplified alike.
+//!
+//! Boost and Qt have rules of their own, e.g. for smart pointers and the shared data of strings.
+//! Each library is a [`Pack`] that can be left out, selected like `--simplify=std,boost`.
+//!
+//! The `replace` rules of the config are applied after that, to both C++ and Rust names. They
+//! can shorten a team's own namespaces or wrappers, e.g. `mycorp::util::Handle<*>` to
+//! `Handle<$1>`.
+
+use config::{Rewrite, CONFIG};
+
+/// Libraries whose types are simplified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pack {
+    /// Default template arguments, strings and inline namespaces of the standard library.
+    Std,
+    /// Smart pointers, `boost::bind` and the wrappers of thrown exceptions.
+    Boost,
+    /// Shared data of strings and byte arrays and the slot objects of connections.
+    Qt,
+}
+
+impl Pack {
+    /// Every pack, in the order they're applied.
+    pub const ALL: [Pack; 3] = [Pack::Std, Pack::Boost, Pack::Qt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pack::Std => "std",
+            Pack::Boost => "boost",
+            Pack::Qt => "qt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pack| pack.name() == name)
+    }
+}
+
+/// How much of a demangled name [`simplify`] leaves out, given as `-s`, `-ss` or `-sss`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Default template arguments and the other rules of the [`Pack`]s.
+    Types,
+    /// Namespaces too, leaving the last segment of each path.
+    Namespaces,
+    /// Parameter lists too.
+    Parameters,
+}
+
+/// How demangled names are simplified, by `--simplify` and `-s`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simplify {
+    pub packs: Vec<Pack>,
+    pub level: Level,
+}
+
+/// Rules of [`Pack::Boost`].
+const BOOST: [(&str, &str); 6] = [
+    ("boost::shared_ptr<*>", "shared_ptr<$1>"),
+    ("boost::weak_ptr<*>", "weak_ptr<$1>"),
+    ("boost::scoped_ptr<*>", "scoped_ptr<$1>"),
+    ("boost::intrusive_ptr<*>", "intrusive_ptr<$1>"),
+    // Exceptions thrown by `BOOST_THROW_EXCEPTION`, before it became `wrapexcept` in 1.73.
+    (
+        "boost::exception_detail::clone_impl<boost::exception_detail::error_info_injector<*>>",
+        "boost::wrapexcept<$1>",
+    ),
+    // The function of `boost::bind`, without its return type and list of bound arguments.
+    ("boost::_bi::bind_t<*, *, *>", "boost::bind<$2>"),
+];
+
+/// Rules of [`Pack::Qt`].
+const QT: [(&str, &str); 8] = [
+    // Qt 5 writes `ushort` for the characters of strings, Qt 6 `char16_t`.
+    ("QTypedArrayData<unsigned short>", "QString::Data"),
+    ("QTypedArrayData<char16_t>", "QString::Data"),
+    ("QTypedArrayData<char>", "QByteArray::Data"),
+    ("QArrayDataPointer<char16_t>", "QString::DataPointer"),
+    ("QArrayDataPointer<char>", "QByteArray::DataPointer"),
+    // Slots connected to a lambda or function, without their arguments and return type.
+    ("QtPrivate::QFunctorSlotObject<*, *, *, *>", "QFunctorSlotObject<$1>"),
+    ("QtPrivate::QSlotObject<*, *, *>", "QSlotObject<$1>"),
+    ("QtPrivate::QCallableObject<*, *, *>", "QCallableObject<$1>"),
+];
+
+/// Inline namespaces that version the standard library, which are never written out.
+const INLINE_NAMESPACES: [&str; 2] = ["std::__cxx11::", "std::__1::"];
+
+/// Character types of strings and streams, with the prefix of their typedefs.
+const CHARS: [(&str, &str); 5] = [
+    ("char", ""),
+    ("wchar_t", "w"),
+    ("char8_t", "u8"),
+    ("char16_t", "u16"),
+    ("char32_t", "u32"),
+];
+
+/// Templates over a character type that have a typedef for each of them, by whether they take an
+/// allocator after their character traits.
+const CHAR_TEMPLATES: [(&str, bool); 12] = [
+    ("basic_string", true),
+    ("basic_string_view", false),
+    ("basic_ostream", false),
+    ("basic_istream", false),
+    ("basic_iostream", false),
+    ("basic_streambuf", false),
+    ("basic_ostringstream", true),
+    ("basic_istringstream", true),
+    ("basic_stringstream", true),
+    ("basic_ofstream", false),
+    ("basic_ifstream", false),
+    ("basic_fstream", false),
+];
+
+/// Default arguments of templates, following the number of arguments that are always given.
+/// `$0` and `$1` stand for the first and second argument.
+const DEFAULTS: [(&str, usize, &[&str]); 13] = [
+    ("std::vector", 1, &["std::allocator<$0>"]),
+    ("std::deque", 1, &["std::allocator<$0>"]),
+    ("std::list", 1, &["std::allocator<$0>"]),
+    ("std::forward_list", 1, &["std::allocator<$0>"]),
+    ("std::set", 1, &["std::less<$0>", "std::allocator<$0>"]),
+    ("std::multiset", 1, &["std::less<$0>", "std::allocator<$0>"]),
+    ("std::unordered_set", 1, &["std::hash<$0>", "std::equal_to<$0>", "std::allocator<$0>"]),
+    ("std::map", 2, &["std::less<$0>", "std::allocator<std::pair<$0 const, $1>>"]),
+    ("std::multimap", 2, &["std::less<$0>", "std::allocator<std::pair<$0 const, $1>>"]),
+    (
+        "std::unordered_map",
+        2,
+        &["std::hash<$0>", "std::equal_to<$0>", "std::allocator<std::pair<$0 const, $1>>"],
+    ),
+    ("std::unique_ptr", 1, &["std::default_delete<$0>"]),
+    ("std::stack", 1, &["std::deque<$0>"]),
+    ("std::queue", 1, &["std::deque<$0>"]),
+];
+
+/// Name without the `class`, `struct`, `union` and `enum` keywords of MSVC and without any
+/// whitespace, such that two spellings of a type can be compared.
+fn squash(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut rest = name;
+
+    while let Some(c) = rest.chars().next() {
+        let at_word = !out.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        let keyword = ["class ", "struct ", "union ", "enum "]
+            .into_iter()
+            .find(|keyword| at_word && rest.starts_with(keyword));
+
+        match keyword {
+            Some(keyword) => rest = &rest[keyword.len()..],
+            None => {
+                if !c.is_whitespace() {
+                    out.push(c);
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    out
+}
+
+fn same_type(a: &str, b: &str) -> bool {
+    squash(a) == squash(b)
+}
+
+/// Index of the `>` closing the template arguments that start after the `<` at `start`.
+fn closing(name: &str, start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, c) in name[start..].char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return (c == '>').then_some(start + idx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Template arguments split at the commas that separate them.
+fn split_args(args: &str) -> Vec<&str> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut split = Vec::new();
+
+    for (idx, c) in args.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                split.push(args[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    split.push(args[start..].trim());
+    split
+}
+
+/// Length of the qualified name at the end of `out`, e.g. `std::vector` of `const std::vector`.
+fn qualified_name_len(out: &str) -> usize {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_' || c == ':' || c == '$';
+    out.len() - out.trim_end_matches(is_name).len()
+}
+
+/// Length of the name of an operator at the start of `rest`, including the brackets of `operator<`,
+/// `operator->` and the like.
+fn operator_len(rest: &str) -> Option<usize> {
+    let after = rest.strip_prefix("operator")?;
+    let op = after.len() - after.trim_start_matches(['<', '>', '=', '-']).len();
+    Some("operator".len() + op)
+}
+
+/// Start of the parameter list of a function, the last parentheses that aren't part of a path
+/// like `(anonymous namespace)::`.
+fn params_start(name: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut params = None;
+    let mut idx = 0;
+
+    while idx < name.len() {
+        let rest = &name[idx..];
+        if let Some(len) = operator_len(rest) {
+            idx += len;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        match c {
+            '(' if depth == 0 => {
+                params = Some(idx);
+                depth += 1;
+            }
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 => params = None,
+            _ => {}
+        }
+        idx += c.len_utf8();
+    }
+
+    params
+}
+
+/// Start of the path segment at the end of `out`, together with its template arguments.
+fn segment_start(out: &str) -> usize {
+    let mut depth = 0usize;
+    for (idx, c) in out.char_indices().rev() {
+        match c {
+            // MSVC quotes some names, like `anonymous namespace'.
+            '>' | ')' | ']' | '}' | '\'' => depth += 1,
+            '<' | '(' | '[' | '{' | '`' if depth == 0 => return idx + c.len_utf8(),
+            '<' | '(' | '[' | '{' | '`' => depth -= 1,
+            c if depth == 0 && !c.is_alphanumeric() && !"_$~.#".contains(c) => {
+                return idx + c.len_utf8()
+            }
+            _ => {}
+        }
+    }
+
+    0
+}
+
+/// Name with every path shortened to its last segment, e.g. `std::vector<std::string>::size()`
+/// to `size()`.
+fn last_segments(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut idx = 0;
+
+    while idx < name.len() {
+        let rest = &name[idx..];
+        if let Some(len) = operator_len(rest) {
+            out.push_str(&rest[..len]);
+            idx += len;
+            continue;
+        }
+
+        if rest.starts_with("::") {
+            out.truncate(segment_start(&out));
+            idx += 2;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        out.push(c);
+        idx += c.len_utf8();
+    }
+
+    out
+}
+
+/// Typedef of a template over a character type, e.g. `std::wstring`.
+fn char_typedef(name: &str, args: &[String]) -> Option<String> {
+    let template = name.strip_prefix("std::")?;
+    let &(_, allocator) = CHAR_TEMPLATES.iter().find(|(name, _)| *name == template)?;
+    let first = args.first()?;
+    let &(ch, prefix) = CHARS.iter().find(|(ch, _)| same_type(first, ch))?;
+
+    let mut expected = vec![ch.to_string(), format!("std::char_traits<{ch}>")];
+    if allocator {
+        expected.push(format!("std::allocator<{ch}>"));
+    }
+
+    let matches = args.len() == expected.len()
+        && args.iter().zip(&expected).all(|(arg, expected)| same_type(arg, expected));
+
+    matches.then(|| format!("std::{prefix}{}", &template["basic_".len()..]))
+}
+
+/// Instantiation of a template, without the trailing arguments that are the default.
+fn instantiate(name: &str, mut args: Vec<String>) -> String {
+    if let Some(&(_, given, defaults)) = DEFAULTS.iter().find(|(template, ..)| *template == name) {
+        let arg = |idx: usize| args.get(idx).map_or("", String::as_str);
+        let defaults: Vec<String> = defaults
+            .iter()
+            .map(|default| default.replace("$0", arg(0)).replace("$1", arg(1)))
+            .collect();
+
+        // Only the last arguments can be left out.
+        while args.len() > given
+            && defaults
+                .get(args.len() - 1 - given)
+                .is_some_and(|default| same_type(&args[args.len() - 1], default))
+        {
+            args.pop();
+        }
+    }
+
+    format!("{name}<{}>", args.join(", "))
+}
+
+/// Simplify every template in `name`, the innermost first.
+fn templates(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut idx = 0;
+
+    while idx < name.len() {
+        let rest = &name[idx..];
+
+        // The comparison operators aren't template arguments.
+        if let Some(len) = operator_len(rest) {
+            out.push_str(&rest[..len]);
+            idx += len;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        let end = match c {
+            '<' if qualified_name_len(&out) > 0 => closing(name, idx),
+            _ => None,
+        };
+
+        let Some(end) = end else {
+            out.push(c);
+            idx += c.len_utf8();
+            continue;
+        };
+
+        let args: Vec<String> =
+            split_args(&name[idx + 1..end]).into_iter().map(templates).collect();
+        let template_start = out.len() - qualified_name_len(&out);
+        let template = out.split_off(template_start);
+        match char_typedef(&template, &args) {
+            Some(typedef) => {
+                // A typedef isn't a class, so the keyword MSVC puts before classes goes as well.
+                for keyword in ["class ", "struct "] {
+                    let start = out.len().saturating_sub(keyword.len());
+                    if out.ends_with(keyword) && qualified_name_len(&out[..start]) == 0 {
+                        out.truncate(start);
+                    }
+                }
+                out.push_str(&typedef);
+            }
+            None => out.push_str(&instantiate(&template, args)),
+        }
+        idx = end + 1;
+    }
+
+    out
+}
+
+/// Length of the text at the start of `text` matching `pieces`, the literal parts of a pattern
+/// between its wildcards, together with the text each wildcard matched.
+fn match_pattern<'a>(pieces: &[&str], text: &'a str) -> Option<(usize, Vec<&'a str>)> {
+    let (first, rest) = pieces.split_first()?;
+    let after = text.strip_prefix(first)?;
+    if rest.is_empty() {
+        return Some((first.len(), Vec::new()));
+    }
+
+    // A wildcard matches the shortest balanced text after which the rest of the pattern matches,
+    // or up to the end of the type it's in if it ends the pattern.
+    let trailing = rest == [""];
+    let mut depth = 0usize;
+    let mut end = after.len();
+    for (idx, c) in after.char_indices() {
+        if idx > 0 && depth == 0 && !trailing {
+            if let Some((len, mut wildcards)) = match_pattern(rest, &after[idx..]) {
+                wildcards.insert(0, &after[..idx]);
+                return Some((first.len() + idx + len, wildcards));
+            }
+        }
+
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' | ',' if depth == 0 => {
+                end = idx;
+                break;
+            }
+            '>' | ')' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    (trailing && end > 0).then(|| (first.len() + end, vec![&after[..end]]))
+}
+
+/// Replacement of a rule with `$1`, `$2`, .. replaced by what the wildcards matched.
+fn substitute(to: &str, wildcards: &[&str]) -> String {
+    let mut out = String::with_capacity(to.len());
+    let mut chars = to.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let wildcard = chars.peek().and_then(|next| next.to_digit(10)).filter(|_| c == '$');
+        match wildcard {
+            Some(n) => {
+                chars.next();
+                let idx = (n as usize).wrapping_sub(1);
+                out.push_str(wildcards.get(idx).copied().unwrap_or_default());
+            }
+            None => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Replace every match of the pattern `from` in `name`, including those in what its wildcards
+/// matched.
+fn rewrite(name: &str, from: &str, to: &str) -> String {
+    if from.is_empty() {
+        return name.to_string();
+    }
+
+    let pieces: Vec<&str> = from.split('*').collect();
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    let starts_with_name = from.starts_with(is_name);
+
+    let mut out = String::with_capacity(name.len());
+    let mut idx = 0;
+    while idx < name.len() {
+        let rest = &name[idx..];
+
+        // A pattern starting with a name only matches a whole one, `std::` doesn't match the end
+        // of `mystd::`.
+        let at_name = !starts_with_name || !out.ends_with(is_name);
+        match match_pattern(&pieces, rest).filter(|_| at_name) {
+            Some((len, wildcards)) if len > 0 => {
+                let wildcards: Vec<String> =
+                    wildcards.into_iter().map(|text| rewrite(text, from, to)).collect();
+                let wildcards: Vec<&str> = wildcards.iter().map(String::as_str).collect();
+                out.push_str(&substitute(to, &wildcards));
+                idx += len;
+            }
+            _ => {
+                let c = rest.chars().next().unwrap();
+                out.push(c);
+                idx += c.len_utf8();
+            }
+        }
+    }
+
+    out
+}
+
+/// Apply rewrite rules to a demangled name, in order.
+pub fn apply_rules(name: &str, rules: &[Rewrite]) -> String {
+    rules.iter().fold(name.to_string(), |name, rule| rewrite(&name, &rule.from, &rule.to))
+}
+
+/// Rewrite the types of the libraries of `packs` in a demangled name to how they're written in
+/// source code, e.g. `std::__cxx11::basic_string<char, std::char_traits<char>,
+/// std::allocator<char>>` to `std::string`, followed by the `replace` rules of the config.
+pub fn simplify_type(name: &str, packs: &[Pack]) -> String {
+    let mut name = name.to_string();
+    for pack in Pack::ALL.into_iter().filter(|pack| packs.contains(pack)) {
+        let rules: &[(&str, &str)] = match pack {
+            Pack::Std => {
+                name = INLINE_NAMESPACES
+                    .iter()
+                    .fold(name, |name, namespace| name.replace(namespace, "std::"));
+                name = templates(&name);
+                continue;
+            }
+            Pack::Boost => &BOOST,
+            Pack::Qt => &QT,
+        };
+
+        for (from, to) in rules {
+            name = rewrite(&name, from, to);
+        }
+    }
+
+    apply_rules(&name, &CONFIG.replace)
+}
+
+/// Simplify a demangled name as far as the level of `options` goes.
+pub fn simplify(name: &str, options: &Simplify) -> String {
+    let mut name = simplify_type(name, &options.packs);
+    if options.level >= Level::Namespaces {
+        name = last_segments(&name);
+    }
+
+    if options.level >= Level::Parameters {
+        if let Some(params) = params_start(&name) {
+            name.truncate(params);
+        }
+    }
+
+    name
+}
+
+/// Name shared by every instantiation of a template, with its template arguments written as
+/// `<…>` and without a parameter list, e.g. `std::vector<…>::push_back`. `None` if the name
+/// doesn't have any template arguments.
+pub fn fold_templates(name: &str) -> Option<String> {
+    let mut out = String::with_capacity(name.len());
+    let mut folded = false;
+    let mut idx = 0;
+
+    while idx < name.len() {
+        let rest = &name[idx..];
+
+        if let Some(len) = operator_len(rest) {
+            out.push_str(&rest[..len]);
+            idx += len;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        let end = match c {
+            '<' if qualified_name_len(&out) > 0 => closing(name, idx),
+            _ => None,
+        };
+
+        match end {
+            Some(end) => {
+                out.push_str("<…>");
+                folded = true;
+                idx = end + 1;
+            }
+            None => {
+                out.push(c);
+                idx += c.len_utf8();
+            }
+        }
+    }
+
+    if !folded {
+        return None;
+    }
+
+    if let Some(params) = params_start(&out) {
+        out.truncate(params);
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simplify_std(name: &str) -> String {
+        simplify_type(name, &[Pack::Std])
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            simplify_std(
+                "std::__cxx11::basic_string<char, std::char_traits<char>, std::allocator<char>>\
+                 ::append(char const*)"
+            ),
+            "std::string::append(char const*)"
+        );
+        assert_eq!(
+            simplify_std(
+                "class std::basic_string<wchar_t, struct std::char_traits<wchar_t>, \
+                 class std::allocator<wchar_t> > const &"
+            ),
+            "std::wstring const &"
+        );
+        assert_eq!(
+            simplify_std("std::basic_ostream<char, std::char_traits<char>>& std::endl(int)"),
+            "std::ostream& std::endl(int)"
+        );
+    }
+
+    #[test]
+    fn default_arguments() {
+        assert_eq!(
+            simplify_std("std::vector<int, std::allocator<int>>::push_back(int const&)"),
+            "std::vector<int>::push_back(int const&)"
+        );
+        assert_eq!(
+            simplify_std(
+                "std::map<int, long, std::less<int>, std::allocator<std::pair<int const, long>>>"
+            ),
+            "std::map<int, long>"
+        );
+        assert_eq!(
+            simplify_std(
+                "std::map<int, long, std::greater<int>, std::allocator<std::pair<int const, long>>>"
+            ),
+            "std::map<int, long, std::greater<int>>"
+        );
+        assert_eq!(
+            simplify_std("std::__1::unique_ptr<Foo, std::__1::default_delete<Foo>>"),
+            "std::unique_ptr<Foo>"
+        );
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(
+            simplify_std("bool operator<<std::vector<int, std::allocator<int>>>(int)"),
+            "bool operator<<std::vector<int>>(int)"
+        );
+        assert_eq!(simplify_std("a<b>>(c)"), "a<b>>(c)");
+    }
+
+    #[test]
+    fn folding() {
+        assert_eq!(
+            fold_templates("std::vector<int, std::allocator<int>>::push_back(int const&)"),
+            Some("std::vector<…>::push_back".to_string())
+        );
+        assert_eq!(
+            fold_templates("<alloc::vec::Vec<u8> as core::ops::drop::Drop>::drop"),
+            Some("<alloc::vec::Vec<…> as core::ops::drop::Drop>::drop".to_string())
+        );
+        assert_eq!(
+            fold_templates("(anonymous namespace)::Foo<int>::bar() const"),
+            Some("(anonymous namespace)::Foo<…>::bar".to_string())
+        );
+        assert_eq!(fold_templates("operator<(int, int)"), None);
+        assert_eq!(fold_templates("main"), None);
+    }
+
+    fn rule(from: &str, to: &str) -> Rewrite {
+        Rewrite {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn rules() {
+        let rules = [
+            rule("absl::lts_*::", "absl::"),
+            rule("mycorp::util::Handle<*>", "Handle<$1>"),
+            rule("alloc::sync::Arc<*>", "Arc<$1>"),
+        ];
+
+        assert_eq!(
+            apply_rules("absl::lts_20230802::Cord::Append(absl::lts_20230802::Cord)", &rules),
+            "absl::Cord::Append(absl::Cord)"
+        );
+        assert_eq!(
+            apply_rules("mycorp::util::Handle<std::vector<int>>::get() const", &rules),
+            "Handle<std::vector<int>>::get() const"
+        );
+        assert_eq!(
+            apply_rules("<alloc::sync::Arc<foo::Bar> as core::ops::drop::Drop>::drop", &rules),
+            "<Arc<foo::Bar> as core::ops::drop::Drop>::drop"
+        );
+        assert_eq!(apply_rules("myabsl::lts_1::f", &rules), "myabsl::lts_1::f");
+    }
+
+    #[test]
+    fn wildcards() {
+        let swap = [rule("std::pair<*, *>", "($1, $2)")];
+        assert_eq!(
+            apply_rules("std::pair<int, std::map<int, long>>", &swap),
+            "(int, std::map<int, long>)"
+        );
+
+        let trailing = [rule("mycorp::detail::*", "$1")];
+        assert_eq!(apply_rules("f(mycorp::detail::Foo<int>, int)", &trailing), "f(Foo<int>, int)");
+    }
+
+    #[test]
+    fn packs() {
+        let name = "boost::shared_ptr<boost::shared_ptr<std::basic_string<char, \
+                    std::char_traits<char>, std::allocator<char>>>>";
+        assert_eq!(simplify_type(name, &Pack::ALL), "shared_ptr<shared_ptr<std::string>>");
+        assert_eq!(
+            simplify_type(name, &[Pack::Boost]),
+            "shared_ptr<shared_ptr<std::basic_string<char, std::char_traits<char>, \
+             std::allocator<char>>>>"
+        );
+        assert_eq!(
+            simplify_type("QTypedArrayData<unsigned short>::deallocate(QArrayData*)", &[Pack::Qt]),
+            "QString::Data::deallocate(QArrayData*)"
+        );
+        assert_eq!(Pack::from_name("boost"), Some(Pack::Boost));
+        assert_eq!(Pack::from_name("stl"), None);
+    }
+
+    #[test]
+    fn levels() {
+        let name = "std::vector<std::__cxx11::basic_string<char, std::char_traits<char>, \
+                    std::allocator<char>>, std::allocator<std::__cxx11::basic_string<char, \
+                    std::char_traits<char>, std::allocator<char>>>>::push_back(std::__cxx11::\
+                    basic_string<char, std::char_traits<char>, std::allocator<char>> const&)";
+        let level = |level| Simplify {
+            packs: vec![Pack::Std],
+            level,
+        };
+
+        assert_eq!(
+            simplify(name, &level(Level::Types)),
+            "std::vector<std::string>::push_back(std::string const&)"
+        );
+        assert_eq!(simplify(name, &level(Level::Namespaces)), "push_back(string const&)");
+        assert_eq!(simplify(name, &level(Level::Parameters)), "push_back");
+        assert_eq!(
+            simplify("(anonymous namespace)::Foo::operator<(int) const", &level(Level::Namespaces)),
+            "operator<(int) const"
+        );
+        assert_eq!(
+            simplify("void foo::bar<int>(foo::Baz, int)", &level(Level::Parameters)),
+            "void bar<int>"
+        );
+    }
+}
@@ -95,20 +95,29 @@ use tokenizing::{colors, Color32};
 /// Max recursion depth.
 const MAX_DEPTH: usize = 256;
 
+#[cfg(test)]
 /// Try to parse a rust v0 symbol.
 pub fn parse(s: &str) -> Option<TokenStream> {
+    parse_detailed(s).ok()
+}
+
+/// Try to parse a rust v0 symbol, returning the offset it failed at otherwise.
+pub fn parse_detailed(s: &str) -> Result<TokenStream, usize> {
     // macOS prefixes symbols with an extra underscore therefore '__R' is allowed
-    let s = s.strip_prefix('R').or(s.strip_prefix("_R")).or(s.strip_prefix("__R"))?;
+    let path = s.strip_prefix('R').or(s.strip_prefix("_R")).or(s.strip_prefix("__R"));
+    let path = path.ok_or(0usize)?;
+    let prefix = s.len() - path.len();
 
     // paths have to be ascii
-    if !s.bytes().all(|c| c.is_ascii()) {
-        return None;
+    if let Some(idx) = path.bytes().position(|c| !c.is_ascii()) {
+        return Err(prefix + idx);
     }
 
-    let mut parser = Parser::new(s);
-    parser.path()?;
-
-    Some(parser.stream)
+    let mut parser = Parser::new(path);
+    match parser.path() {
+        Some(_) => Ok(parser.stream),
+        None => Err(prefix + parser.offset),
+    }
 }
 
 /// State required to parse symbols.
@@ -3,12 +3,27 @@ use config::CONFIG;
 
 mod tests;
 
+#[cfg(test)]
 pub fn parse(s: &str) -> Option<TokenStream> {
+    parse_detailed(s).ok()
+}
+
+/// Try to parse a legacy rust symbol, returning the offset it failed at otherwise.
+pub fn parse_detailed(s: &str) -> Result<TokenStream, usize> {
+    let mut offset = 0;
+    demangle(s, &mut offset).ok_or(offset)
+}
+
+/// Keeps `offset` at the start of the path component that is being parsed.
+fn demangle(s: &str, offset: &mut usize) -> Option<TokenStream> {
     // macOS prefixes symbols with an extra underscore therefore '__S' is allowed
-    let s = s.strip_prefix("ZN").or(s.strip_prefix("_ZN")).or(s.strip_prefix("__ZN"))?;
+    let path = s.strip_prefix("ZN").or(s.strip_prefix("_ZN")).or(s.strip_prefix("__ZN"))?;
+    let prefix = s.len() - path.len();
+    let s = path;
 
     // paths have to be ascii
     if !s.bytes().all(|c| c.is_ascii()) {
+        *offset = prefix + s.bytes().position(|c| !c.is_ascii()).unwrap_or(0);
         return None;
     }
 
@@ -19,6 +34,8 @@ pub fn parse(s: &str) -> Option<TokenStream> {
     let mut in_first_part = true;
 
     loop {
+        *offset = prefix + s.len() - unparsed.len();
+
         // it's not valid to not have a closing 'E' character
         unparsed.as_bytes().first()?;
 
@@ -1838,7 +1838,7 @@ impl Operand {
 }
 
 /// a `armv7` or below instruction.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Instruction {
     /// the condition code for this instruction, defaults to `AL` if the instruction is
     /// unconditional.
@@ -2070,7 +2070,11 @@ fn format_reg_imm_mem(
 impl Decoded for Instruction {
     #[inline]
     fn width(&self) -> usize {
-        4
+        if self.thumb && !self.wide {
+            2
+        } else {
+            4
+        }
     }
 
     fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
@@ -2825,7 +2829,8 @@ impl Decodable for Decoder {
 
     fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
         let mut inst = Instruction::default();
-        read(self, reader, &mut inst).map_err(|err| Error::new(err, 4))?;
+        let size = if self.thumb { 2 } else { 4 };
+        read(self, reader, &mut inst).map_err(|err| Error::new(err, size))?;
         Ok(inst)
     }
 
@@ -811,17 +811,34 @@ impl ToTokens for Instruction {
                     (self.operands[0], self.operands[1])
                 {
                     let hint_num = (CRn << 3) | op2 as u16;
-                    match hint_num & 0b111111 {
+                    match hint_num {
                         0x0 => stream.push("nop", CONFIG.colors.asm.opcode),
                         0x1 => stream.push("yield", CONFIG.colors.asm.opcode),
                         0x2 => stream.push("wfe", CONFIG.colors.asm.opcode),
                         0x3 => stream.push("wfi", CONFIG.colors.asm.opcode),
                         0x4 => stream.push("sev", CONFIG.colors.asm.opcode),
+                        0x7 => stream.push("xpaclri", CONFIG.colors.asm.opcode),
+                        0x8 => stream.push("pacia1716", CONFIG.colors.asm.opcode),
+                        0xa => stream.push("pacib1716", CONFIG.colors.asm.opcode),
+                        0xc => stream.push("autia1716", CONFIG.colors.asm.opcode),
+                        0xe => stream.push("autib1716", CONFIG.colors.asm.opcode),
                         0x10 => stream.push("esb", CONFIG.colors.asm.opcode),
                         0x11 => stream.push("psb csync", CONFIG.colors.asm.opcode),
                         0x12 => stream.push("tsb csync", CONFIG.colors.asm.opcode),
                         0x14 => stream.push("csdb", CONFIG.colors.asm.opcode),
                         0x15 => stream.push("sevl", CONFIG.colors.asm.opcode),
+                        0x18 => stream.push("paciaz", CONFIG.colors.asm.opcode),
+                        0x19 => stream.push("paciasp", CONFIG.colors.asm.opcode),
+                        0x1a => stream.push("pacibz", CONFIG.colors.asm.opcode),
+                        0x1b => stream.push("pacibsp", CONFIG.colors.asm.opcode),
+                        0x1c => stream.push("autiaz", CONFIG.colors.asm.opcode),
+                        0x1d => stream.push("autiasp", CONFIG.colors.asm.opcode),
+                        0x1e => stream.push("autibz", CONFIG.colors.asm.opcode),
+                        0x1f => stream.push("autibsp", CONFIG.colors.asm.opcode),
+                        0x20 => stream.push("bti", CONFIG.colors.asm.opcode),
+                        0x22 => stream.push("bti c", CONFIG.colors.asm.opcode),
+                        0x24 => stream.push("bti j", CONFIG.colors.asm.opcode),
+                        0x26 => stream.push("bti jc", CONFIG.colors.asm.opcode),
                         _ => {
                             stream.push("hint #", CONFIG.colors.asm.opcode);
                             stream.push_owned(
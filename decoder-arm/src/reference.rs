@@ -0,0 +1,153 @@
+//! Compact ARM instruction set reference.
+
+/// Condition codes, flag setting and width qualifier suffixes.
+const SUFFIXES: &[&str] = &[
+    ".eq", ".ne", ".cs", ".hs", ".cc", ".lo", ".mi", ".pl", ".vs", ".vc", ".hi", ".ls", ".ge",
+    ".lt", ".gt", ".le", ".al", ".w", ".n", "eq", "ne", "cs", "hs", "cc", "lo", "mi", "pl", "vs",
+    "vc", "hi", "ls", "ge", "lt", "gt", "le", "al", "s",
+];
+
+#[rustfmt::skip]
+const ARMV7_REFERENCE: &decoder::Reference = &[
+    ("mov", "Copy a register or immediate into the destination. Flags: NZ(C) with 's'."),
+    ("mvn", "Copy the bitwise not of the operand. Flags: NZ(C) with 's'."),
+    ("add", "dst = op1 + op2. Flags: NZCV with 's'."),
+    ("adc", "dst = op1 + op2 + C. Flags: NZCV with 's'."),
+    ("sub", "dst = op1 - op2. Flags: NZCV with 's'."),
+    ("sbc", "dst = op1 - op2 - !C. Flags: NZCV with 's'."),
+    ("rsb", "dst = op2 - op1 (reverse subtract). Flags: NZCV with 's'."),
+    ("mul", "dst = op1 * op2, lower 32 bits. Flags: NZ with 's'."),
+    ("mla", "dst = op1 * op2 + acc. Flags: NZ with 's'."),
+    ("umull", "Unsigned 32x32 to 64-bit multiply. Flags: NZ with 's'."),
+    ("smull", "Signed 32x32 to 64-bit multiply. Flags: NZ with 's'."),
+    ("sdiv", "Signed divide. Flags: none."),
+    ("udiv", "Unsigned divide. Flags: none."),
+    ("and", "dst = op1 & op2. Flags: NZ(C) with 's'."),
+    ("orr", "dst = op1 | op2. Flags: NZ(C) with 's'."),
+    ("eor", "dst = op1 ^ op2. Flags: NZ(C) with 's'."),
+    ("bic", "dst = op1 & !op2 (bit clear). Flags: NZ(C) with 's'."),
+    ("lsl", "Logical shift left. Flags: NZC with 's'."),
+    ("lsr", "Logical shift right. Flags: NZC with 's'."),
+    ("asr", "Arithmetic shift right. Flags: NZC with 's'."),
+    ("ror", "Rotate right. Flags: NZC with 's'."),
+    ("cmp", "Compute op1 - op2 and discard the result. Flags: NZCV."),
+    ("cmn", "Compute op1 + op2 and discard the result. Flags: NZCV."),
+    ("tst", "Compute op1 & op2 and discard the result. Flags: NZ(C)."),
+    ("teq", "Compute op1 ^ op2 and discard the result. Flags: NZ(C)."),
+    ("b", "Branch to the target, optionally if the condition holds. Flags: none."),
+    ("bl", "Branch with link, lr = return address. Flags: none."),
+    ("blx", "Branch with link and possibly switch between ARM and Thumb state. Flags: none."),
+    ("bx", "Branch to a register and possibly switch between ARM and Thumb state. Flags: none."),
+    ("cbz", "Compare with zero and branch if zero (Thumb). Flags: none."),
+    ("cbnz", "Compare with zero and branch if not zero (Thumb). Flags: none."),
+    ("it", "If-then, make up to four following Thumb instructions conditional. Flags: none."),
+    ("ldr", "Load a word from memory. Flags: none."),
+    ("ldrb", "Load a byte from memory, zero-extended. Flags: none."),
+    ("ldrh", "Load a halfword from memory, zero-extended. Flags: none."),
+    ("ldrsb", "Load a byte from memory, sign-extended. Flags: none."),
+    ("ldrsh", "Load a halfword from memory, sign-extended. Flags: none."),
+    ("ldrd", "Load two words from memory. Flags: none."),
+    ("str", "Store a word to memory. Flags: none."),
+    ("strb", "Store a byte to memory. Flags: none."),
+    ("strh", "Store a halfword to memory. Flags: none."),
+    ("strd", "Store two words to memory. Flags: none."),
+    ("ldm", "Load multiple registers from consecutive memory. Flags: none."),
+    ("stm", "Store multiple registers to consecutive memory. Flags: none."),
+    ("push", "Store registers on the stack, decrementing sp. Flags: none."),
+    ("pop", "Load registers from the stack, incrementing sp. Loading pc returns. Flags: none."),
+    ("movw", "Move a 16-bit immediate into the lower halfword, zeroing the rest. Flags: none."),
+    ("movt", "Move a 16-bit immediate into the upper halfword. Flags: none."),
+    ("uxtb", "Zero-extend a byte. Flags: none."),
+    ("uxth", "Zero-extend a halfword. Flags: none."),
+    ("sxtb", "Sign-extend a byte. Flags: none."),
+    ("sxth", "Sign-extend a halfword. Flags: none."),
+    ("clz", "Count leading zero bits. Flags: none."),
+    ("svc", "Supervisor call (system call). Flags: none."),
+    ("bkpt", "Breakpoint. Flags: none."),
+    ("nop", "No operation. Flags: none."),
+    ("mrs", "Move a status register into a general purpose register. Flags: none."),
+    ("msr", "Move a general purpose register into a status register. Flags: written flags."),
+    ("dmb", "Data memory barrier. Flags: none."),
+    ("dsb", "Data synchronization barrier. Flags: none."),
+    ("isb", "Instruction synchronization barrier. Flags: none."),
+];
+
+#[rustfmt::skip]
+const A64_REFERENCE: &decoder::Reference = &[
+    ("mov", "Copy a register or immediate into the destination. Flags: none."),
+    ("movz", "Move a shifted 16-bit immediate, zeroing the other bits. Flags: none."),
+    ("movk", "Move a shifted 16-bit immediate, keeping the other bits. Flags: none."),
+    ("movn", "Move the inverse of a shifted 16-bit immediate. Flags: none."),
+    ("add", "dst = op1 + op2. Flags: NZCV with 's'."),
+    ("sub", "dst = op1 - op2. Flags: NZCV with 's'."),
+    ("adc", "dst = op1 + op2 + C. Flags: NZCV with 's'."),
+    ("sbc", "dst = op1 - op2 - !C. Flags: NZCV with 's'."),
+    ("neg", "dst = -op. Flags: NZCV with 's'."),
+    ("mul", "dst = op1 * op2. Flags: none."),
+    ("madd", "dst = acc + op1 * op2. Flags: none."),
+    ("msub", "dst = acc - op1 * op2. Flags: none."),
+    ("sdiv", "Signed divide. Flags: none."),
+    ("udiv", "Unsigned divide. Flags: none."),
+    ("and", "dst = op1 & op2. Flags: NZ with 's', CV cleared."),
+    ("orr", "dst = op1 | op2. Flags: none."),
+    ("eor", "dst = op1 ^ op2. Flags: none."),
+    ("bic", "dst = op1 & !op2 (bit clear). Flags: NZ with 's', CV cleared."),
+    ("lsl", "Logical shift left. Flags: none."),
+    ("lsr", "Logical shift right. Flags: none."),
+    ("asr", "Arithmetic shift right. Flags: none."),
+    ("ubfm", "Unsigned bitfield move. Flags: none."),
+    ("sbfm", "Signed bitfield move. Flags: none."),
+    ("cmp", "Compute op1 - op2 and discard the result. Flags: NZCV."),
+    ("cmn", "Compute op1 + op2 and discard the result. Flags: NZCV."),
+    ("tst", "Compute op1 & op2 and discard the result. Flags: NZ, CV cleared."),
+    ("ccmp", "Conditional compare, sets flags to op1 - op2 if the condition holds, otherwise to an immediate. Flags: NZCV."),
+    ("csel", "dst = cond ? op1 : op2. Flags: none."),
+    ("csinc", "dst = cond ? op1 : op2 + 1. Flags: none."),
+    ("cset", "dst = cond ? 1 : 0. Flags: none."),
+    ("b", "Branch to the target, optionally if the condition holds. Flags: none."),
+    ("bl", "Branch with link, x30 = return address. Flags: none."),
+    ("br", "Branch to a register. Flags: none."),
+    ("blr", "Branch with link to a register. Flags: none."),
+    ("ret", "Return to the address in x30 (or the given register). Flags: none."),
+    ("cbz", "Compare with zero and branch if zero. Flags: none."),
+    ("cbnz", "Compare with zero and branch if not zero. Flags: none."),
+    ("tbz", "Test a bit and branch if zero. Flags: none."),
+    ("tbnz", "Test a bit and branch if not zero. Flags: none."),
+    ("adr", "Compute a pc-relative address. Flags: none."),
+    ("adrp", "Compute the pc-relative address of a 4KB page. Flags: none."),
+    ("ldr", "Load a register from memory. Flags: none."),
+    ("ldrb", "Load a byte from memory, zero-extended. Flags: none."),
+    ("ldrh", "Load a halfword from memory, zero-extended. Flags: none."),
+    ("ldrsw", "Load a word from memory, sign-extended. Flags: none."),
+    ("ldp", "Load a pair of registers from memory. Flags: none."),
+    ("str", "Store a register to memory. Flags: none."),
+    ("strb", "Store a byte to memory. Flags: none."),
+    ("strh", "Store a halfword to memory. Flags: none."),
+    ("stp", "Store a pair of registers to memory. Flags: none."),
+    ("ldxr", "Load exclusive. Flags: none."),
+    ("stxr", "Store exclusive, status register is 0 on success. Flags: none."),
+    ("ldar", "Load-acquire. Flags: none."),
+    ("stlr", "Store-release. Flags: none."),
+    ("svc", "Supervisor call (system call). Flags: none."),
+    ("brk", "Breakpoint. Flags: none."),
+    ("nop", "No operation. Flags: none."),
+    ("mrs", "Read a system register. Flags: none."),
+    ("msr", "Write a system register. Flags: written flags."),
+    ("dmb", "Data memory barrier. Flags: none."),
+    ("dsb", "Data synchronization barrier. Flags: none."),
+    ("isb", "Instruction synchronization barrier. Flags: none."),
+    ("paciasp", "Sign the link register with key A and the stack pointer as modifier. Flags: none."),
+    ("autiasp", "Authenticate the link register with key A and the stack pointer as modifier. Flags: none."),
+    ("retaa", "Authenticate the link register with key A and return. Flags: none."),
+    ("retab", "Authenticate the link register with key B and return. Flags: none."),
+];
+
+/// Description of an ARMv7 (A32/T32) instruction, given its mnemonic.
+pub fn describe_armv7(mnemonic: &str) -> Option<&'static str> {
+    decoder::describe(ARMV7_REFERENCE, mnemonic, SUFFIXES)
+}
+
+/// Description of an ARMv8 (A64) instruction, given its mnemonic.
+pub fn describe_a64(mnemonic: &str) -> Option<&'static str> {
+    decoder::describe(A64_REFERENCE, mnemonic, SUFFIXES)
+}
@@ -4136,3 +4136,25 @@ fn test_decode_simd_32b_cases() {
         "vstmdb r3!, {s11, s12, s13, s14, s15, s16, s17, s18, s19, s20, s21, s22, s23, s24, s25, s26, s27, s28, s29, s30, s31}"
     );
 }
+
+#[test]
+fn test_decode_mixed_width_sequence() {
+    use decoder::Decoded;
+
+    let data = [0x00, 0xb5, 0xa3, 0xf1, 0x7e, 0x5a, 0x70, 0x47];
+    let decoder = InstDecoder::default_thumb();
+    let mut reader = Reader::new(&data[..]);
+    let mut decoded = Vec::new();
+    while let Ok(instr) = decoder.decode(&mut reader) {
+        decoded.push((instr.width(), format!("{}", instr)));
+    }
+
+    assert_eq!(
+        decoded,
+        [
+            (2, "push {lr}".to_string()),
+            (4, "sub.w r10, r3, 0x3f800000".to_string()),
+            (2, "bx lr".to_string()),
+        ]
+    );
+}
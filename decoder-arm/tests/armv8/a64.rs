@@ -4768,6 +4768,15 @@ fn test_pac() {
         ([0x00, 0x04, 0xf0, 0xf8], "ldrab x0, [x0, #-0x800]"),
         ([0x00, 0x14, 0x20, 0xf8], "ldraa x0, [x0, #0x8]"),
         ([0x00, 0x04, 0xa4, 0xf8], "ldrab x0, [x0, #0x200]"),
+        ([0x3f, 0x23, 0x03, 0xd5], "paciasp"),
+        ([0x7f, 0x23, 0x03, 0xd5], "pacibsp"),
+        ([0xbf, 0x23, 0x03, 0xd5], "autiasp"),
+        ([0xff, 0x23, 0x03, 0xd5], "autibsp"),
+        ([0xff, 0x20, 0x03, 0xd5], "xpaclri"),
+        ([0x1f, 0x21, 0x03, 0xd5], "pacia1716"),
+        ([0xff, 0x0b, 0x5f, 0xd6], "retaa"),
+        ([0xff, 0x0f, 0x5f, 0xd6], "retab"),
+        ([0x5f, 0x24, 0x03, 0xd5], "bti c"),
     ];
     let errs = run_tests(TESTS);
 
@@ -60,7 +60,6 @@ enum Format {
 
 struct TableInstruction {
     mnemomic: &'static str,
-    #[allow(dead_code)]
     desc: &'static str,
     format: &'static [usize],
 }
@@ -170,7 +169,7 @@ fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
                 };
 
                 if operand == shamt {
-                    operands[idx] = Cow::Owned(format!("0x{shamt:x}"));
+                    operands[idx] = Cow::Owned(decoder::encode_uhex(shamt as u64));
                 } else {
                     operands[idx] = Cow::Borrowed(REGISTERS[operand]);
                 }
@@ -195,7 +194,7 @@ fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
                 let (operands, operand_count) = operands![
                     Cow::Borrowed(REGISTERS[rt]),
                     Cow::Borrowed(REGISTERS[rs]),
-                    Cow::Owned(format!("{immediate:#x}")),
+                    Cow::Owned(decoder::encode_uhex(immediate as u64)),
                 ];
 
                 return Ok(Instruction {
@@ -219,7 +218,7 @@ fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
                 };
 
                 if operand == immediate {
-                    operands[idx] = Cow::Owned(format!("0x{immediate:x}"));
+                    operands[idx] = Cow::Owned(decoder::encode_uhex(immediate as u64));
                 } else {
                     operands[idx] = Cow::Borrowed(REGISTERS[operand]);
                 }
@@ -233,7 +232,7 @@ fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
         }
         Format::J => {
             let immediate = dword & 0b11111111_11111111_11111111;
-            let (operands, operand_count) = operands![Cow::Owned(format!("0x{immediate:x}"))];
+            let (operands, operand_count) = operands![Cow::Owned(decoder::encode_uhex(immediate as u64))];
 
             Ok(Instruction {
                 mnemomic: inst.mnemomic,
@@ -494,4 +493,15 @@ const R_TYPES: [TableInstruction; 44] = [
     mips!("sltu" : "If $rs is less then $rt, $rd is set to 1 otherwise to 0 (unsigned)", rd, rs, rt),
 ];
 
+/// Description of a MIPS instruction, given its mnemonic.
+pub fn describe(mnemonic: &str) -> Option<&'static str> {
+    let mnemonic = mnemonic.split_whitespace().last()?;
+    I_TYPES
+        .iter()
+        .chain(J_TYPES.iter())
+        .chain(R_TYPES.iter())
+        .find(|inst| !inst.mnemomic.is_empty() && inst.mnemomic == mnemonic)
+        .map(|inst| inst.desc)
+}
+
 const EMPTY_OPERAND: std::borrow::Cow<'static, str> = std::borrow::Cow::Borrowed("");
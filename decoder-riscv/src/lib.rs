@@ -1,6 +1,7 @@
 //! Riscv64gc/Riscv32gc disassembler.
 
 mod tests;
+pub mod reference;
 
 use decoder::{Error, ErrorKind, ToTokens};
 use debugvault::Index;
@@ -741,7 +742,10 @@ impl ToTokens for Operand {
                             stream.push_token(token.clone());
                         }
                     }
-                    None => stream.push_owned(imm.to_string(), CONFIG.colors.asm.immediate),
+                    None => {
+                        let imm = decoder::encode_number(*imm as i64, decoder::Radix::Decimal);
+                        stream.push_owned(imm, CONFIG.colors.asm.immediate)
+                    }
                 }
             }
             Self::Nothing => unreachable!("empty operand encountered"),
@@ -0,0 +1,97 @@
+//! Compact RISC-V instruction set reference.
+
+/// Atomic ordering and floating-point width suffixes.
+const SUFFIXES: &[&str] = &[".aqrl", ".aq", ".rl", ".w", ".d", ".s"];
+
+#[rustfmt::skip]
+const REFERENCE: &decoder::Reference = &[
+    ("lui", "Load a 20-bit immediate into the upper bits of rd. RISC-V has no flags."),
+    ("auipc", "rd = pc + (imm << 12). Used for pc-relative addressing."),
+    ("jal", "Jump and link, rd = pc + 4 and jump to the pc-relative target."),
+    ("jalr", "Jump and link register, rd = pc + 4 and jump to rs1 + imm."),
+    ("j", "Unconditional jump (jal with rd = zero)."),
+    ("jr", "Jump to a register (jalr with rd = zero)."),
+    ("ret", "Return to the address in ra (jalr zero, 0(ra))."),
+    ("call", "Call a function, ra = return address."),
+    ("tail", "Tail call a function without saving a return address."),
+    ("beq", "Branch if rs1 == rs2."),
+    ("bne", "Branch if rs1 != rs2."),
+    ("blt", "Branch if rs1 < rs2 (signed)."),
+    ("bge", "Branch if rs1 >= rs2 (signed)."),
+    ("bltu", "Branch if rs1 < rs2 (unsigned)."),
+    ("bgeu", "Branch if rs1 >= rs2 (unsigned)."),
+    ("beqz", "Branch if rs1 == 0."),
+    ("bnez", "Branch if rs1 != 0."),
+    ("lb", "Load a byte, sign-extended."),
+    ("lh", "Load a halfword, sign-extended."),
+    ("lw", "Load a word, sign-extended on RV64."),
+    ("ld", "Load a doubleword."),
+    ("lbu", "Load a byte, zero-extended."),
+    ("lhu", "Load a halfword, zero-extended."),
+    ("lwu", "Load a word, zero-extended."),
+    ("sb", "Store a byte."),
+    ("sh", "Store a halfword."),
+    ("sw", "Store a word."),
+    ("sd", "Store a doubleword."),
+    ("addi", "rd = rs1 + imm."),
+    ("addiw", "rd = sext((rs1 + imm)[31:0])."),
+    ("slti", "rd = rs1 < imm (signed) ? 1 : 0."),
+    ("sltiu", "rd = rs1 < imm (unsigned) ? 1 : 0."),
+    ("xori", "rd = rs1 ^ imm."),
+    ("ori", "rd = rs1 | imm."),
+    ("andi", "rd = rs1 & imm."),
+    ("slli", "rd = rs1 << imm."),
+    ("srli", "rd = rs1 >> imm (logical)."),
+    ("srai", "rd = rs1 >> imm (arithmetic)."),
+    ("add", "rd = rs1 + rs2."),
+    ("addw", "rd = sext((rs1 + rs2)[31:0])."),
+    ("sub", "rd = rs1 - rs2."),
+    ("subw", "rd = sext((rs1 - rs2)[31:0])."),
+    ("sll", "rd = rs1 << rs2."),
+    ("slt", "rd = rs1 < rs2 (signed) ? 1 : 0."),
+    ("sltu", "rd = rs1 < rs2 (unsigned) ? 1 : 0."),
+    ("xor", "rd = rs1 ^ rs2."),
+    ("srl", "rd = rs1 >> rs2 (logical)."),
+    ("sra", "rd = rs1 >> rs2 (arithmetic)."),
+    ("or", "rd = rs1 | rs2."),
+    ("and", "rd = rs1 & rs2."),
+    ("mv", "Copy rs1 into rd (addi rd, rs1, 0)."),
+    ("li", "Load an immediate into rd."),
+    ("la", "Load an address into rd."),
+    ("nop", "No operation (addi zero, zero, 0)."),
+    ("mul", "rd = (rs1 * rs2)[XLEN-1:0]."),
+    ("mulh", "Upper half of the signed product."),
+    ("mulhu", "Upper half of the unsigned product."),
+    ("div", "Signed division."),
+    ("divu", "Unsigned division."),
+    ("rem", "Signed remainder."),
+    ("remu", "Unsigned remainder."),
+    ("lr", "Load-reserved, used with sc for atomic read-modify-write."),
+    ("sc", "Store-conditional, rd = 0 on success."),
+    ("amoswap", "Atomically swap memory with rs2, rd = old value."),
+    ("amoadd", "Atomically add rs2 to memory, rd = old value."),
+    ("fence", "Order memory and I/O accesses."),
+    ("ecall", "Environment call (system call)."),
+    ("ebreak", "Breakpoint."),
+    ("csrrw", "Atomically swap a CSR with rs1."),
+    ("csrrs", "Atomically read a CSR and set the bits in rs1."),
+    ("csrrc", "Atomically read a CSR and clear the bits in rs1."),
+    ("flw", "Load a single-precision float."),
+    ("fld", "Load a double-precision float."),
+    ("fsw", "Store a single-precision float."),
+    ("fsd", "Store a double-precision float."),
+    ("fadd", "Floating-point addition."),
+    ("fsub", "Floating-point subtraction."),
+    ("fmul", "Floating-point multiplication."),
+    ("fdiv", "Floating-point division."),
+    ("fmv", "Move a floating-point value between registers."),
+    ("fcvt", "Convert between integer and floating-point formats."),
+    ("feq", "rd = rs1 == rs2 ? 1 : 0 (floating-point)."),
+    ("flt", "rd = rs1 < rs2 ? 1 : 0 (floating-point)."),
+    ("fle", "rd = rs1 <= rs2 ? 1 : 0 (floating-point)."),
+];
+
+/// Description of a RISC-V instruction, given its mnemonic.
+pub fn describe(mnemonic: &str) -> Option<&'static str> {
+    decoder::describe(REFERENCE, mnemonic, SUFFIXES)
+}
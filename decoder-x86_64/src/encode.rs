@@ -0,0 +1,315 @@
+//! Assembler for the handful of `x86_64` instructions that patches are usually made of.
+//!
+//! Only `nop`, `int3`, `ret`, `jmp`, `call`, conditional jumps and `mov` of a register or an
+//! immediate into a general purpose register are supported. Mnemonics and registers are looked
+//! up by the names the decoder prints them with, so what the disassembly shows for any of these
+//! assembles to the same instruction again.
+
+use crate::long_mode::{register_class, Opcode, RegSpec};
+
+/// Conditional jumps, in the order of the condition encoded in the low bits of their opcode.
+const JCC: [Opcode; 16] = [
+    Opcode::JO,
+    Opcode::JNO,
+    Opcode::JB,
+    Opcode::JNB,
+    Opcode::JZ,
+    Opcode::JNZ,
+    Opcode::JNA,
+    Opcode::JA,
+    Opcode::JS,
+    Opcode::JNS,
+    Opcode::JP,
+    Opcode::JNP,
+    Opcode::JL,
+    Opcode::JGE,
+    Opcode::JLE,
+    Opcode::JG,
+];
+
+/// Other mnemonics of conditional jumps, with the one the decoder uses.
+const JCC_ALIASES: [(&str, Opcode); 14] = [
+    ("je", Opcode::JZ),
+    ("jne", Opcode::JNZ),
+    ("jc", Opcode::JB),
+    ("jnae", Opcode::JB),
+    ("jae", Opcode::JNB),
+    ("jnc", Opcode::JNB),
+    ("jbe", Opcode::JNA),
+    ("jnbe", Opcode::JA),
+    ("jpe", Opcode::JP),
+    ("jpo", Opcode::JNP),
+    ("jnge", Opcode::JL),
+    ("jnl", Opcode::JGE),
+    ("jng", Opcode::JLE),
+    ("jnle", Opcode::JG),
+];
+
+/// Instructions other than conditional jumps that can be assembled.
+const OPCODES: [Opcode; 6] = [
+    Opcode::NOP,
+    Opcode::INT,
+    Opcode::RETURN,
+    Opcode::JMP,
+    Opcode::CALL,
+    Opcode::MOV,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    Reg(RegSpec),
+    Imm(i128),
+}
+
+fn opcode(mnemonic: &str) -> Option<Opcode> {
+    OPCODES
+        .into_iter()
+        .chain(JCC)
+        .find(|opcode| opcode.name() == mnemonic)
+        .or_else(|| JCC_ALIASES.iter().find(|(alias, _)| *alias == mnemonic).map(|(_, op)| *op))
+}
+
+/// General purpose register by name, from `rax` down to `al` and `ah`.
+fn register(name: &str) -> Option<RegSpec> {
+    let banks: [fn(u8) -> RegSpec; 4] = [RegSpec::q, RegSpec::d, RegSpec::w, RegSpec::rb];
+    banks
+        .into_iter()
+        .flat_map(|bank| (0..16).map(bank))
+        .chain((0..8).map(RegSpec::b))
+        .find(|reg| reg.name() == name)
+}
+
+/// Decimal or `0x` prefixed hexadecimal number, optionally negative.
+fn immediate(s: &str) -> Option<i128> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+
+    let value = match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => s.parse::<u64>().ok()?,
+    };
+
+    Some(if negative { -(value as i128) } else { value as i128 })
+}
+
+fn operand(s: &str) -> Result<Operand, String> {
+    register(s)
+        .map(Operand::Reg)
+        .or_else(|| immediate(s).map(Operand::Imm))
+        .ok_or_else(|| format!("Unknown operand '{s}'."))
+}
+
+/// REX prefix of an instruction, if it needs one.
+///
+/// `spl`, `bpl`, `sil` and `dil` can only be encoded with a REX prefix, whereas `ah`, `ch`, `dh`
+/// and `bh` are encoded the same and can only be used without one.
+fn rex(w: bool, r: RegSpec, b: RegSpec) -> Result<Option<u8>, String> {
+    let byte = |reg: RegSpec, class| reg.class() == class && (4..8).contains(&reg.num());
+    let rex = 0x40 | (w as u8) << 3 | (r.num() >> 3) << 2 | b.num() >> 3;
+    let required = rex != 0x40 || byte(r, register_class::RB) || byte(b, register_class::RB);
+
+    if required && (byte(r, register_class::B) || byte(b, register_class::B)) {
+        return Err("ah, ch, dh and bh can't be used together with a REX prefix.".to_string());
+    }
+
+    Ok(required.then_some(rex))
+}
+
+/// Displacement of a relative branch of `len` bytes at `addr` to `target`, if it fits in `T`.
+fn rel<T: TryFrom<i128>>(addr: u64, len: u64, target: i128) -> Option<T> {
+    T::try_from(target - (addr as i128 + len as i128)).ok()
+}
+
+fn branch(opcode: Opcode, addr: u64, target: i128) -> Result<Vec<u8>, String> {
+    let out_of_range = || format!("Target {target:#x} is out of range of a relative branch.");
+    let cond = JCC.iter().position(|&jcc| jcc == opcode).map(|cond| cond as u8);
+
+    if opcode != Opcode::CALL {
+        if let Some(disp) = rel::<i8>(addr, 2, target) {
+            let op = cond.map_or(0xeb, |cond| 0x70 | cond);
+            return Ok(vec![op, disp as u8]);
+        }
+    }
+
+    let (mut bytes, len) = match cond {
+        Some(cond) => (vec![0x0f, 0x80 | cond], 6),
+        None if opcode == Opcode::CALL => (vec![0xe8], 5),
+        None => (vec![0xe9], 5),
+    };
+
+    let disp: i32 = rel(addr, len, target).ok_or_else(out_of_range)?;
+    bytes.extend_from_slice(&disp.to_le_bytes());
+    Ok(bytes)
+}
+
+/// Indirect `jmp` or `call` through a 64-bit register.
+fn branch_reg(opcode: Opcode, reg: RegSpec) -> Result<Vec<u8>, String> {
+    if reg.class() != register_class::Q {
+        return Err(format!("Branches can only go through 64-bit registers, not {reg}."));
+    }
+
+    let ext = if opcode == Opcode::CALL { 2 } else { 4 };
+    let mut bytes = Vec::new();
+    bytes.extend(rex(false, RegSpec::q(0), reg)?);
+    bytes.extend_from_slice(&[0xff, 0xc0 | ext << 3 | (reg.num() & 7)]);
+    Ok(bytes)
+}
+
+fn mov_reg(dst: RegSpec, src: RegSpec) -> Result<Vec<u8>, String> {
+    if dst.width() != src.width() {
+        return Err(format!("{dst} and {src} aren't the same size."));
+    }
+
+    let mut bytes = Vec::new();
+    if dst.width() == 2 {
+        bytes.push(0x66);
+    }
+    bytes.extend(rex(dst.width() == 8, src, dst)?);
+    bytes.push(if dst.width() == 1 { 0x88 } else { 0x89 });
+    bytes.push(0xc0 | (src.num() & 7) << 3 | (dst.num() & 7));
+    Ok(bytes)
+}
+
+fn mov_imm(dst: RegSpec, imm: i128) -> Result<Vec<u8>, String> {
+    let width = dst.width() as u32;
+    let doesnt_fit = || format!("Immediate {imm:#x} doesn't fit in {dst}.");
+
+    // Immediates are encoded as either signed or unsigned, except for those of 64-bit registers
+    // which are sign extended unless they're given as all 64 bits.
+    let bits = width * 8;
+    let (min, max) = (-(1i128 << (bits - 1)), (1i128 << bits) - 1);
+    if !(min..=max).contains(&imm) {
+        return Err(doesnt_fit());
+    }
+
+    let mut bytes = Vec::new();
+    if width == 2 {
+        bytes.push(0x66);
+    }
+    bytes.extend(rex(width == 8, RegSpec::q(0), dst)?);
+
+    let reg = dst.num() & 7;
+    match width {
+        1 => {
+            bytes.push(0xb0 | reg);
+            bytes.push(imm as u8);
+        }
+        2 => {
+            bytes.push(0xb8 | reg);
+            bytes.extend_from_slice(&(imm as u16).to_le_bytes());
+        }
+        4 => {
+            bytes.push(0xb8 | reg);
+            bytes.extend_from_slice(&(imm as u32).to_le_bytes());
+        }
+        _ => match i32::try_from(imm) {
+            Ok(imm) => {
+                bytes.extend_from_slice(&[0xc7, 0xc0 | reg]);
+                bytes.extend_from_slice(&imm.to_le_bytes());
+            }
+            Err(_) => {
+                bytes.push(0xb8 | reg);
+                bytes.extend_from_slice(&(imm as u64).to_le_bytes());
+            }
+        },
+    }
+
+    Ok(bytes)
+}
+
+/// Assemble a single instruction as if it's placed at `addr`, which relative branches are
+/// encoded against, e.g. `jmp 0x401000` or `mov eax, 1`.
+pub fn assemble(inst: &str, addr: u64) -> Result<Vec<u8>, String> {
+    let inst = inst.trim().to_ascii_lowercase();
+    let (mnemonic, operands) = inst.split_once(char::is_whitespace).unwrap_or((inst.as_str(), ""));
+
+    let operands = match operands.trim() {
+        "" => Vec::new(),
+        operands => operands.split(',').map(|op| operand(op.trim())).collect::<Result<_, _>>()?,
+    };
+
+    let unsupported = || format!("Unsupported operands for '{mnemonic}'.");
+    let opcode = match mnemonic {
+        "int3" => return operands.is_empty().then(|| vec![0xcc]).ok_or_else(unsupported),
+        _ => opcode(mnemonic).ok_or_else(|| format!("Unsupported instruction '{mnemonic}'."))?,
+    };
+
+    match (opcode, &operands[..]) {
+        (Opcode::NOP, []) => Ok(vec![0x90]),
+        (Opcode::INT, [Operand::Imm(3)]) => Ok(vec![0xcc]),
+        (Opcode::INT, [Operand::Imm(imm @ 0..=0xff)]) => Ok(vec![0xcd, *imm as u8]),
+        (Opcode::RETURN, []) => Ok(vec![0xc3]),
+        (Opcode::RETURN, [Operand::Imm(imm @ 0..=0xffff)]) => {
+            let [lo, hi] = (*imm as u16).to_le_bytes();
+            Ok(vec![0xc2, lo, hi])
+        }
+        (Opcode::JMP | Opcode::CALL, [Operand::Reg(reg)]) => branch_reg(opcode, *reg),
+        (Opcode::JMP | Opcode::CALL, [Operand::Imm(target)]) => branch(opcode, addr, *target),
+        (jcc, [Operand::Imm(target)]) if JCC.contains(&jcc) => branch(jcc, addr, *target),
+        (Opcode::MOV, [Operand::Reg(dst), Operand::Reg(src)]) => mov_reg(*dst, *src),
+        (Opcode::MOV, [Operand::Reg(dst), Operand::Imm(imm)]) => mov_imm(*dst, *imm),
+        _ => Err(unsupported()),
+    }
+}
+
+/// Assemble instructions separated by newlines or `;`, placing each one after the other
+/// starting at `addr`.
+pub fn assemble_all(asm: &str, addr: u64) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for inst in asm.split(['\n', ';']).filter(|inst| !inst.trim().is_empty()) {
+        let inst = assemble(inst, addr + bytes.len() as u64)
+            .map_err(|err| format!("{}: {err}", inst.trim()))?;
+        bytes.extend(inst);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branches() {
+        assert_eq!(assemble("jmp 0x1010", 0x1000).unwrap(), [0xeb, 0x0e]);
+        assert_eq!(assemble("jmp 0x401000", 0x1000).unwrap(), [0xe9, 0xfb, 0xff, 0x3f, 0x00]);
+        assert_eq!(assemble("call 0x1000", 0x1000).unwrap(), [0xe8, 0xfb, 0xff, 0xff, 0xff]);
+        assert_eq!(assemble("je 0x1000", 0x1000).unwrap(), [0x74, 0xfe]);
+        assert_eq!(assemble("jnz 0x2000", 0x1000).unwrap(), [0x0f, 0x85, 0xfa, 0x0f, 0, 0]);
+        assert_eq!(assemble("jmp rax", 0).unwrap(), [0xff, 0xe0]);
+        assert_eq!(assemble("call r11", 0).unwrap(), [0x41, 0xff, 0xd3]);
+        assert!(assemble("jmp eax", 0).is_err());
+        assert!(assemble("jmp 0xffffffffffff", 0).is_err());
+    }
+
+    #[test]
+    fn moves() {
+        assert_eq!(assemble("mov eax, 1", 0).unwrap(), [0xb8, 1, 0, 0, 0]);
+        assert_eq!(
+            assemble("mov rax, -1", 0).unwrap(),
+            [0x48, 0xc7, 0xc0, 0xff, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(
+            assemble("mov r9, 0x1122334455667788", 0).unwrap(),
+            [0x49, 0xb9, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11]
+        );
+        assert_eq!(assemble("mov rbp, rsp", 0).unwrap(), [0x48, 0x89, 0xe5]);
+        assert_eq!(assemble("mov ax, 0xffff", 0).unwrap(), [0x66, 0xb8, 0xff, 0xff]);
+        assert_eq!(assemble("mov sil, 7", 0).unwrap(), [0x40, 0xb6, 7]);
+        assert_eq!(assemble("mov ah, 7", 0).unwrap(), [0xb4, 7]);
+        assert!(assemble("mov ah, r8b", 0).is_err());
+        assert!(assemble("mov eax, rax", 0).is_err());
+        assert!(assemble("mov al, 0x100", 0).is_err());
+    }
+
+    #[test]
+    fn sequence() {
+        let bytes = assemble_all("xor_is_missing: nop", 0);
+        assert!(bytes.is_err());
+
+        let bytes = assemble_all("mov eax, 1; ret\nint3", 0).unwrap();
+        assert_eq!(bytes, [0xb8, 1, 0, 0, 0, 0xc3, 0xcc]);
+    }
+}
@@ -7,8 +7,10 @@
 //! * `x86_64`/`amd64` decoding is under [`long_mode`]
 //! * `x86_32`/`x86` decoding is under [`protected_mode`]
 
+pub mod encode;
 pub mod long_mode;
 pub mod protected_mode;
+pub mod reference;
 mod safer_unchecked;
 
 use debugvault::Index;
@@ -2425,7 +2425,7 @@ const MNEMONICS: &[&str] = &[
 ];
 
 impl Opcode {
-    fn name(&self) -> &'static str {
+    pub(crate) fn name(&self) -> &'static str {
         unsafe { MNEMONICS.get_kinda_unchecked(*self as usize) }
     }
 }
@@ -2640,32 +2640,60 @@ impl Decoded for Instruction {
     }
 
     fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        // Only branches encode their immediate relative to the next instruction.
+        let relative = matches!(
+            self.opcode,
+            Opcode::JMP
+                | Opcode::CALL
+                | Opcode::JO
+                | Opcode::JNO
+                | Opcode::JB
+                | Opcode::JNB
+                | Opcode::JZ
+                | Opcode::JNZ
+                | Opcode::JA
+                | Opcode::JNA
+                | Opcode::JS
+                | Opcode::JNS
+                | Opcode::JP
+                | Opcode::JNP
+                | Opcode::JL
+                | Opcode::JGE
+                | Opcode::JLE
+                | Opcode::JG
+                | Opcode::LOOP
+                | Opcode::LOOPZ
+                | Opcode::LOOPNZ
+                | Opcode::JRCXZ
+                | Opcode::XBEGIN
+        );
+
         for idx in 0..self.operand_count as usize {
             let operand = Operand::from_spec(&self, self.operands[idx]);
             let addr = addr as u64;
             let addr = match operand {
-                Operand::ImmediateI8(imm) => {
+                Operand::ImmediateI8(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU8(imm) => {
+                Operand::ImmediateU8(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
-                Operand::ImmediateI16(imm) => {
+                Operand::ImmediateI16(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU16(imm) => {
+                Operand::ImmediateU16(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
-                Operand::ImmediateI32(imm) => {
+                Operand::ImmediateI32(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU32(imm) => {
+                Operand::ImmediateU32(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
-                Operand::ImmediateI64(imm) => {
+                Operand::ImmediateI64(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU64(imm) => {
+                Operand::ImmediateU64(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
                 Operand::DisplacementU32(imm) => addr.saturating_add(imm as u64),
@@ -4358,6 +4386,16 @@ impl Instruction {
         OperandSpec::Nothing != self.operands[i as usize]
     }
 
+    /// get the absolute address a relative operand refers to, such as the target of a
+    /// `call rel32`. only available once the instruction's relative addresses are resolved.
+    pub fn rel_addr(&self) -> Option<usize> {
+        if self.imm_override {
+            Some(self.imm as usize)
+        } else {
+            None
+        }
+    }
+
     /// get the memory access information for this instruction, if it accesses memory.
     ///
     /// the corresponding `MemoryAccessSize` may report that the size of accessed memory is
@@ -6394,6 +6394,29 @@ fn from_reports() {
     test_display(&[0xf3, 0x0f, 0x1e, 0x0f], "nop dword [rdi], ecx");
 }
 
+#[test]
+fn relative_immediates() {
+    let decode = |data: &[u8]| {
+        let mut reader = Reader::new(data);
+        let mut instr = Decoder::default().decode(&mut reader).expect("failed to decode");
+        instr.update_rel_addrs(0x1000, None);
+        instr
+    };
+
+    // branches are relative to the next instruction.
+    let jmp = decode(&[0xeb, 0x10]);
+    assert!(jmp.imm_override);
+    assert_eq!(jmp.imm, 0x1012);
+    let call = decode(&[0xe8, 0x20, 0x00, 0x00, 0x00]);
+    assert!(call.imm_override);
+    assert_eq!(call.imm, 0x1025);
+
+    // immediates of anything else are left alone.
+    assert!(!decode(&[0xb8, 0x10, 0x00, 0x00, 0x00]).imm_override);
+    assert!(!decode(&[0x83, 0xc0, 0x10]).imm_override);
+    assert!(!decode(&[0x6a, 0x10]).imm_override);
+}
+
 mod reg_masks {
     use crate::long_mode::RegSpec;
 
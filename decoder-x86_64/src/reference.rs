@@ -0,0 +1,101 @@
+//! Compact x86 instruction set reference.
+
+/// Condition codes used by `jcc`, `cmovcc` and `setcc`.
+const CONDITIONS: &[&str] = &[
+    "nae", "nbe", "nge", "nle", "ae", "be", "ge", "le", "na", "nb", "nc", "ne", "ng", "nl", "no",
+    "np", "ns", "nz", "pe", "po", "a", "b", "c", "e", "g", "l", "o", "p", "s", "z",
+];
+
+#[rustfmt::skip]
+const REFERENCE: &decoder::Reference = &[
+    ("mov", "Copy the source operand to the destination. Flags: none."),
+    ("movzx", "Copy and zero-extend the source to the size of the destination. Flags: none."),
+    ("movsx", "Copy and sign-extend the source to the size of the destination. Flags: none."),
+    ("movsxd", "Copy and sign-extend a doubleword to a quadword. Flags: none."),
+    ("movabs", "Move a 64-bit immediate or absolute address. Flags: none."),
+    ("lea", "Compute the effective address of the memory operand without accessing memory. Flags: none."),
+    ("xchg", "Swap the contents of both operands. Flags: none."),
+    ("push", "Decrement the stack pointer and store the operand on top of the stack. Flags: none."),
+    ("pop", "Load the top of the stack into the operand and increment the stack pointer. Flags: none."),
+    ("add", "dst = dst + src. Flags: OF SF ZF AF CF PF."),
+    ("adc", "dst = dst + src + CF. Flags: OF SF ZF AF CF PF."),
+    ("sub", "dst = dst - src. Flags: OF SF ZF AF CF PF."),
+    ("sbb", "dst = dst - (src + CF). Flags: OF SF ZF AF CF PF."),
+    ("inc", "dst = dst + 1. Flags: OF SF ZF AF PF (CF unaffected)."),
+    ("dec", "dst = dst - 1. Flags: OF SF ZF AF PF (CF unaffected)."),
+    ("neg", "dst = -dst (two's complement). Flags: CF set unless dst is 0, OF SF ZF AF PF."),
+    ("mul", "Unsigned multiply of the accumulator, result in rDX:rAX. Flags: OF CF."),
+    ("imul", "Signed multiply. Flags: OF CF set when the result is truncated."),
+    ("div", "Unsigned divide of rDX:rAX, quotient in rAX and remainder in rDX. Flags: undefined."),
+    ("idiv", "Signed divide of rDX:rAX, quotient in rAX and remainder in rDX. Flags: undefined."),
+    ("and", "dst = dst & src. Flags: OF CF cleared, SF ZF PF."),
+    ("or", "dst = dst | src. Flags: OF CF cleared, SF ZF PF."),
+    ("xor", "dst = dst ^ src, commonly used to zero a register. Flags: OF CF cleared, SF ZF PF."),
+    ("not", "dst = !dst (one's complement). Flags: none."),
+    ("shl", "Logical shift left. Flags: CF is the last bit shifted out, OF SF ZF PF."),
+    ("sal", "Arithmetic shift left, same as shl. Flags: CF OF SF ZF PF."),
+    ("shr", "Logical shift right, filling with zeroes. Flags: CF OF SF ZF PF."),
+    ("sar", "Arithmetic shift right, filling with the sign bit. Flags: CF OF SF ZF PF."),
+    ("rol", "Rotate left. Flags: CF OF."),
+    ("ror", "Rotate right. Flags: CF OF."),
+    ("cmp", "Compute dst - src and discard the result. Flags: OF SF ZF AF CF PF."),
+    ("test", "Compute dst & src and discard the result. Flags: OF CF cleared, SF ZF PF."),
+    ("jmp", "Unconditional jump to the target. Flags: none."),
+    ("j", "Conditional jump, taken if the condition holds on the current flags. Flags: none."),
+    ("jecxz", "Jump if ECX is zero. Flags: none."),
+    ("jrcxz", "Jump if RCX is zero. Flags: none."),
+    ("cmov", "Conditional move, copy src to dst if the condition holds. Flags: none."),
+    ("set", "Set the byte operand to 1 if the condition holds, otherwise 0. Flags: none."),
+    ("call", "Push the return address and jump to the target. Flags: none."),
+    ("ret", "Pop the return address off the stack and jump to it. Flags: none."),
+    ("leave", "Restore the caller's frame: rSP = rBP, then pop rBP. Flags: none."),
+    ("enter", "Create a stack frame for a procedure. Flags: none."),
+    ("nop", "No operation. Flags: none."),
+    ("int3", "Breakpoint trap. Flags: none."),
+    ("int", "Software interrupt. Flags: IF TF cleared."),
+    ("syscall", "Fast system call, RCX = RIP and R11 = RFLAGS. Flags: masked by IA32_FMASK."),
+    ("sysenter", "Fast system call into ring 0. Flags: VM IF cleared."),
+    ("hlt", "Halt the processor until an interrupt arrives. Flags: none."),
+    ("ud2", "Raise an invalid opcode exception, used to mark unreachable code. Flags: none."),
+    ("cdq", "Sign-extend EAX into EDX:EAX. Flags: none."),
+    ("cqo", "Sign-extend RAX into RDX:RAX. Flags: none."),
+    ("cdqe", "Sign-extend EAX into RAX. Flags: none."),
+    ("cwde", "Sign-extend AX into EAX. Flags: none."),
+    ("bt", "Copy the selected bit into CF. Flags: CF."),
+    ("bts", "Copy the selected bit into CF and set it. Flags: CF."),
+    ("btr", "Copy the selected bit into CF and clear it. Flags: CF."),
+    ("bsf", "Index of the least significant set bit. Flags: ZF set if src is 0."),
+    ("bsr", "Index of the most significant set bit. Flags: ZF set if src is 0."),
+    ("tzcnt", "Count trailing zero bits. Flags: CF set if src is 0, ZF set if the result is 0."),
+    ("lzcnt", "Count leading zero bits. Flags: CF set if src is 0, ZF set if the result is 0."),
+    ("popcnt", "Count set bits. Flags: ZF set if src is 0, others cleared."),
+    ("cmpxchg", "Compare rAX with dst, if equal store src in dst, otherwise load dst in rAX. Flags: ZF and arithmetic flags."),
+    ("xadd", "Exchange and add, dst = dst + src and src = old dst. Flags: OF SF ZF AF CF PF."),
+    ("movs", "Copy a string element from [rSI] to [rDI]. Flags: none."),
+    ("stos", "Store rAX to [rDI]. Flags: none."),
+    ("lods", "Load [rSI] into rAX. Flags: none."),
+    ("cmps", "Compare [rSI] with [rDI]. Flags: OF SF ZF AF CF PF."),
+    ("scas", "Compare rAX with [rDI]. Flags: OF SF ZF AF CF PF."),
+    ("cpuid", "Query processor identification and features. Flags: none."),
+    ("rdtsc", "Read the time-stamp counter into EDX:EAX. Flags: none."),
+    ("endbr64", "Indirect branch target marker for CET. Acts as a nop otherwise. Flags: none."),
+    ("endbr32", "Indirect branch target marker for CET. Acts as a nop otherwise. Flags: none."),
+    ("pause", "Spin-wait loop hint. Flags: none."),
+    ("movaps", "Move aligned packed single-precision values. Flags: none."),
+    ("movups", "Move unaligned packed single-precision values. Flags: none."),
+    ("movdqa", "Move aligned 128-bit integer data. Flags: none."),
+    ("movdqu", "Move unaligned 128-bit integer data. Flags: none."),
+    ("movq", "Move a quadword between general purpose and vector registers. Flags: none."),
+    ("movd", "Move a doubleword between general purpose and vector registers. Flags: none."),
+    ("pxor", "Bitwise xor of packed integers. Flags: none."),
+    ("xorps", "Bitwise xor of packed single-precision values. Flags: none."),
+    ("ucomisd", "Unordered compare of scalar doubles. Flags: ZF PF CF, OF SF AF cleared."),
+    ("comisd", "Ordered compare of scalar doubles. Flags: ZF PF CF, OF SF AF cleared."),
+    ("cvtsi2sd", "Convert an integer to a scalar double. Flags: none."),
+    ("cvttsd2si", "Convert a scalar double to an integer with truncation. Flags: none."),
+];
+
+/// Description of an x86 instruction, given its mnemonic.
+pub fn describe(mnemonic: &str) -> Option<&'static str> {
+    decoder::describe(REFERENCE, mnemonic, CONDITIONS)
+}
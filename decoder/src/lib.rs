@@ -77,6 +77,98 @@ pub trait Decodable {
     fn max_width(&self) -> usize;
 }
 
+/// Instructions decoded one at a time from a slice of bytes, each with its address.
+///
+/// Relative addresses are resolved against the address of the instruction, as if the bytes were
+/// loaded at `addr`. Failing to decode an instruction skips the bytes it consumed, and once too
+/// few bytes are left an [`ErrorKind::ExhaustedInput`] is yielded for the rest of them.
+pub struct InstructionStream<'data, D: Decodable> {
+    decoder: D,
+    reader: Reader<'data>,
+    len: usize,
+    /// Address of the next instruction.
+    addr: usize,
+    /// Offset of the next instruction.
+    offset: usize,
+    /// Previous instruction, if it decoded.
+    prev: Option<D::Instruction>,
+}
+
+impl<'data, D: Decodable> InstructionStream<'data, D>
+where
+    D::Instruction: Clone,
+{
+    pub fn new(decoder: D, bytes: &'data [u8], addr: usize) -> Self {
+        Self {
+            decoder,
+            reader: Reader::new(bytes),
+            len: bytes.len(),
+            addr,
+            offset: 0,
+            prev: None,
+        }
+    }
+
+    /// Address of the next instruction.
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+}
+
+impl<'data, D: Decodable> Iterator for InstructionStream<'data, D>
+where
+    D::Instruction: Clone,
+{
+    type Item = (usize, Result<D::Instruction, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.len {
+            return None;
+        }
+
+        // prefetch next cache line line
+        #[cfg(target_arch = "x86")]
+        unsafe {
+            core::arch::x86::_mm_prefetch(
+                self.reader.as_ptr() as *const i8,
+                core::arch::x86::_MM_HINT_NTA,
+            );
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            core::arch::x86_64::_mm_prefetch(
+                self.reader.as_ptr() as *const i8,
+                core::arch::x86_64::_MM_HINT_NTA,
+            );
+        }
+
+        let addr = self.addr;
+        let (width, decoded) = match self.decoder.decode(&mut self.reader) {
+            Ok(mut inst) => {
+                inst.update_rel_addrs(addr, self.prev.as_ref());
+                self.prev = Some(inst.clone());
+                (inst.width(), Ok(inst))
+            }
+            Err(err) if err.kind == ErrorKind::ExhaustedInput => {
+                let rest = self.len - self.offset;
+                self.offset = self.len;
+                self.addr += rest;
+                self.prev = None;
+                return Some((addr, Err(Error::new(ErrorKind::ExhaustedInput, rest))));
+            }
+            Err(err) => {
+                self.prev = None;
+                (err.size(), Err(err))
+            }
+        };
+
+        self.offset += width;
+        self.addr += width;
+        Some((addr, decoded))
+    }
+}
+
 pub struct Reader<'data> {
     start: *const u8,
     position: *const u8,
@@ -159,6 +251,79 @@ impl<'data> Reader<'data> {
 
 const HEX_NUGGET: [u8; 16] = *b"0123456789abcdef";
 
+/// Values below this are written in decimal with [`Radix::Auto`].
+pub const AUTO_DECIMAL_LIMIT: u64 = 0x100;
+
+/// Base immediates and addresses of operands are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Hex,
+    Decimal,
+    /// Small values in decimal and the rest in hex.
+    Auto,
+}
+
+/// How immediates and addresses of operands are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Base of every number, `None` keeps the convention of the architecture.
+    pub radix: Option<Radix>,
+    /// Whether hex is written with a leading `0x`.
+    pub prefix: bool,
+    /// Whether hex is padded with zeros to 8 digits, or 16 if it doesn't fit in 8.
+    pub padded: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            radix: None,
+            prefix: true,
+            padded: false,
+        }
+    }
+}
+
+static NUMBER_FORMAT: std::sync::OnceLock<NumberFormat> = std::sync::OnceLock::new();
+
+/// Set how numbers are written by every decoder, only the first call has any effect.
+pub fn set_number_format(format: NumberFormat) {
+    let _ = NUMBER_FORMAT.set(format);
+}
+
+fn custom_number_format() -> Option<NumberFormat> {
+    NUMBER_FORMAT.get().copied().filter(|format| *format != NumberFormat::default())
+}
+
+fn format_number(format: NumberFormat, negative: bool, abs: u64, native: Radix) -> String {
+    let decimal = match format.radix.unwrap_or(native) {
+        Radix::Hex => false,
+        Radix::Decimal => true,
+        Radix::Auto => abs < AUTO_DECIMAL_LIMIT,
+    };
+
+    let sign = if negative { "-" } else { "" };
+    if decimal {
+        return format!("{sign}{abs}");
+    }
+
+    let prefix = if format.prefix { "0x" } else { "" };
+    let width = match format.padded {
+        false => 0,
+        true if abs <= u32::MAX as u64 => 8,
+        true => 16,
+    };
+
+    format!("{sign}{prefix}{abs:0width$x}")
+}
+
+/// Encode 64-bit signed integer the way numbers are configured to be written, or else in the
+/// `native` radix of the architecture.
+pub fn encode_number(imm: i64, native: Radix) -> String {
+    let format = NUMBER_FORMAT.get().copied().unwrap_or_default();
+    format_number(format, imm.is_negative(), imm.unsigned_abs(), native)
+}
+
 #[inline]
 #[cold]
 fn cold() {}
@@ -179,8 +344,13 @@ fn unlikely(b: bool) -> bool {
     b
 }
 
-/// Encode 64-bit signed integer with a leading '0x' and in lowercase.
+/// Encode 64-bit signed integer with a leading '0x' and in lowercase, unless numbers are
+/// configured to be written otherwise.
 pub fn encode_hex(mut imm: i64) -> String {
+    if let Some(format) = custom_number_format() {
+        return format_number(format, imm.is_negative(), imm.unsigned_abs(), Radix::Hex);
+    }
+
     unsafe {
         let mut buffer = Vec::with_capacity(19);
         let slice = buffer.spare_capacity_mut();
@@ -224,8 +394,13 @@ pub fn encode_hex(mut imm: i64) -> String {
     }
 }
 
-/// Encode 64-bit unsigned integer with a leading '0x' and in lowercase.
+/// Encode 64-bit unsigned integer with a leading '0x' and in lowercase, unless numbers are
+/// configured to be written otherwise.
 pub fn encode_uhex(mut imm: u64) -> String {
+    if let Some(format) = custom_number_format() {
+        return format_number(format, false, imm, Radix::Hex);
+    }
+
     unsafe {
         let mut buffer = Vec::with_capacity(19);
         let slice = buffer.spare_capacity_mut();
@@ -263,8 +438,43 @@ pub fn encode_uhex(mut imm: u64) -> String {
     }
 }
 
+/// Compact instruction set reference, mapping a mnemonic to a one-line description.
+pub type Reference = [(&'static str, &'static str)];
+
+/// Look up the description of the mnemonic of an instruction.
+///
+/// Any prefixes (e.g. `lock`) are skipped and if there isn't an exact match, the `suffixes`
+/// are stripped of the mnemonic one at a time. This handles things like condition codes.
+pub fn describe(reference: &Reference, mnemonic: &str, suffixes: &[&str]) -> Option<&'static str> {
+    let mnemonic = mnemonic.split_whitespace().last()?.to_ascii_lowercase();
+    let lookup = |mnemonic: &str| {
+        reference.iter().find(|(name, _)| *name == mnemonic).map(|(_, desc)| *desc)
+    };
+
+    if let Some(desc) = lookup(&mnemonic) {
+        return Some(desc);
+    }
+
+    suffixes
+        .iter()
+        .filter_map(|suffix| mnemonic.strip_suffix(suffix))
+        .filter(|stripped| !stripped.is_empty())
+        .find_map(lookup)
+}
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn describe() {
+        const REFERENCE: &super::Reference = &[("b", "branch"), ("add", "addition")];
+
+        assert_eq!(super::describe(REFERENCE, "add", &[]), Some("addition"));
+        assert_eq!(super::describe(REFERENCE, "lock ADD", &[]), Some("addition"));
+        assert_eq!(super::describe(REFERENCE, "beq", &["eq", "ne"]), Some("branch"));
+        assert_eq!(super::describe(REFERENCE, "eq", &["eq"]), None);
+        assert_eq!(super::describe(REFERENCE, "sub", &[]), None);
+    }
+
     #[test]
     fn encode_hex() {
         assert_eq!(super::encode_hex(0x123123), "0x123123");
@@ -282,4 +492,88 @@ mod tests {
         assert_eq!(super::encode_uhex(0x0), "0x0");
         assert_eq!(super::encode_uhex(0x7fffffffffffffff), "0x7fffffffffffffff");
     }
+
+    #[test]
+    fn format_number() {
+        use super::{NumberFormat, Radix};
+
+        let hex = NumberFormat::default();
+        assert_eq!(super::format_number(hex, false, 0x10, Radix::Hex), "0x10");
+        assert_eq!(super::format_number(hex, true, 0x10, Radix::Hex), "-0x10");
+        assert_eq!(super::format_number(hex, false, 0x10, Radix::Decimal), "16");
+
+        let decimal = NumberFormat { radix: Some(Radix::Decimal), ..hex };
+        assert_eq!(super::format_number(decimal, true, 0x10, Radix::Hex), "-16");
+
+        let auto = NumberFormat { radix: Some(Radix::Auto), ..hex };
+        assert_eq!(super::format_number(auto, false, 0xff, Radix::Hex), "255");
+        assert_eq!(super::format_number(auto, false, 0x100, Radix::Hex), "0x100");
+
+        let bare = NumberFormat { prefix: false, padded: true, ..hex };
+        assert_eq!(super::format_number(bare, false, 0x10, Radix::Hex), "00000010");
+        assert_eq!(super::format_number(bare, false, 1 << 32, Radix::Hex), "0000000100000000");
+    }
+
+    /// Two byte instructions, of which the ones starting with `0xff` are invalid.
+    struct Pairs;
+
+    #[derive(Clone)]
+    struct Pair {
+        addr: usize,
+        prev: Option<usize>,
+    }
+
+    impl super::ToTokens for Pair {
+        fn tokenize(&self, _: &mut super::TokenStream, _: &super::Index) {}
+    }
+
+    impl super::Decoded for Pair {
+        fn width(&self) -> usize {
+            2
+        }
+
+        fn update_rel_addrs(&mut self, addr: usize, prev_inst: Option<&Self>) {
+            self.addr = addr;
+            self.prev = prev_inst.map(|prev| prev.addr);
+        }
+    }
+
+    impl super::Decodable for Pairs {
+        type Instruction = Pair;
+
+        fn decode(&self, reader: &mut super::Reader) -> Result<Pair, super::Error> {
+            let mut bytes = [0; 2];
+            if reader.next_n(&mut bytes).is_none() {
+                return Err(super::Error::new(super::ErrorKind::ExhaustedInput, 0));
+            }
+
+            match bytes[0] {
+                0xff => Err(super::Error::new(super::ErrorKind::InvalidOpcode, 2)),
+                _ => Ok(Pair { addr: 0, prev: None }),
+            }
+        }
+
+        fn max_width(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn instruction_stream() {
+        let bytes = [0x00, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0x00];
+        let decoded: Vec<_> = super::InstructionStream::new(Pairs, &bytes, 0x1000)
+            .map(|(addr, inst)| (addr, inst.map(|inst| (inst.addr, inst.prev))))
+            .collect();
+
+        assert_eq!(
+            decoded,
+            [
+                (0x1000, Ok((0x1000, None))),
+                (0x1002, Ok((0x1002, Some(0x1000)))),
+                (0x1004, Err(super::Error::new(super::ErrorKind::InvalidOpcode, 2))),
+                (0x1006, Ok((0x1006, None))),
+                (0x1008, Err(super::Error::new(super::ErrorKind::ExhaustedInput, 1))),
+            ]
+        );
+    }
 }
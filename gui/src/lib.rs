@@ -150,7 +150,7 @@ impl UI {
         let ui_queue = self.ui_queue.clone();
 
         std::thread::spawn(move || {
-            match processor::Processor::parse(&path) {
+            match processor::Processor::parse_from_args(&path) {
                 Ok(diss) => ui_queue.push(UIEvent::BinaryLoaded(diss)),
                 Err(err) => ui_queue.push(UIEvent::BinaryFailed(err)),
             };
@@ -174,6 +174,7 @@ impl UI {
                     self.panels.goto_window(panes::FUNCTIONS);
                     self.arch.bar.set_checked(panes::FUNCTIONS);
                 }
+                panes::HEX_DUMP => self.panels.toggle_hex_dump(),
                 panes::LOGGING => {
                     self.panels.goto_window(panes::LOGGING);
                     self.arch.bar.set_checked(panes::LOGGING);
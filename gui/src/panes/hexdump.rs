@@ -0,0 +1,56 @@
+use crate::common::*;
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::{Token, TokenStream};
+
+pub struct HexDump {
+    processor: Arc<Processor>,
+    lines: Vec<Vec<Token>>,
+    lines_count: usize,
+    min_row: usize,
+    max_row: usize,
+}
+
+impl HexDump {
+    pub fn new(processor: Arc<Processor>) -> Self {
+        let lines_count = processor.hex_lines(0, usize::MAX).count();
+
+        Self {
+            processor,
+            lines: Vec::new(),
+            lines_count,
+            min_row: 0,
+            max_row: 0,
+        }
+    }
+}
+
+fn tokenize_lines(processor: &Processor, range: std::ops::Range<usize>) -> Vec<Vec<Token>> {
+    let lines = processor.hex_lines(0, usize::MAX).skip(range.start).take(range.len());
+
+    lines
+        .map(|(_, addr, bytes)| {
+            let mut stream = TokenStream::new();
+            processor::tokenize_hex_line(&mut stream, addr, bytes, processor.aslr());
+            stream.inner
+        })
+        .collect()
+}
+
+impl Display for HexDump {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show_rows(ui, FONT.size, self.lines_count, |ui, row_range| {
+            if row_range != (self.min_row..self.max_row) {
+                self.lines = tokenize_lines(&self.processor, row_range.clone());
+                self.min_row = row_range.start;
+                self.max_row = row_range.end;
+            }
+
+            for line in self.lines.iter() {
+                ui.label(tokens_to_layoutjob(line.clone()));
+            }
+        });
+    }
+}
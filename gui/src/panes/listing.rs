@@ -201,7 +201,13 @@ fn draw_horizontal_line(ui: &mut egui::Ui) {
     ui.painter().extend(dashed_line);
 }
 
-fn draw_instruction(ui: &mut egui::Ui, tokens: Vec<Token>, index: &Index, ui_queue: &UiQueue) {
+fn draw_instruction(
+    ui: &mut egui::Ui,
+    tokens: Vec<Token>,
+    description: Option<&'static str>,
+    index: &Index,
+    ui_queue: &UiQueue,
+) {
     let (a, b, c) = split_instruction_by_label(tokens);
     let label = tokens_to_layoutjob(b);
     let label_text = label.text.clone();
@@ -209,7 +215,10 @@ fn draw_instruction(ui: &mut egui::Ui, tokens: Vec<Token>, index: &Index, ui_que
     ui.horizontal(|ui| {
         ui.style_mut().spacing.item_spacing.x = 0.0;
 
-        ui.label(tokens_to_layoutjob(a));
+        let response = ui.label(tokens_to_layoutjob(a));
+        if let Some(description) = description {
+            response.on_hover_text(description);
+        }
         if ui.link(label).clicked() {
             let label_without_arrows = &label_text[1..][..label_text.len() - 2];
             if let Some(addr) = index.get_func_by_name(label_without_arrows) {
@@ -243,11 +252,17 @@ impl Display for Listing {
                 }
 
                 let mut stream = TokenStream::new();
-                block.tokenize(&mut stream);
+                block.tokenize(&mut stream, self.processor.aslr());
 
                 match block.content {
-                    BlockContent::Instruction { .. } => {
-                        draw_instruction(ui, stream.inner, &self.processor.index, &self.ui_queue);
+                    BlockContent::Instruction { description, .. } => {
+                        draw_instruction(
+                            ui,
+                            stream.inner,
+                            description,
+                            &self.processor.index,
+                            &self.ui_queue,
+                        );
                     }
                     BlockContent::Label { .. } => {
                         if ui.link(tokens_to_layoutjob(stream.inner)).clicked() {
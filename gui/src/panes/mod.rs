@@ -1,4 +1,5 @@
 mod functions;
+mod hexdump;
 mod listing;
 mod source_code;
 
@@ -18,11 +19,13 @@ pub type Identifier = &'static str;
 pub const SOURCE: Identifier = crate::icon!(EMBED2, " Source");
 pub const DISASSEMBLY: Identifier = crate::icon!(PARAGRAPH_LEFT, " Disassembly");
 pub const FUNCTIONS: Identifier = crate::icon!(LIGATURE, " Functions");
+pub const HEX_DUMP: Identifier = crate::icon!(TABLE2, " Hex dump");
 pub const LOGGING: Identifier = crate::icon!(TERMINAL, " Logs");
 
 enum PanelKind {
     Disassembly(listing::Listing),
     Functions(functions::Functions),
+    HexDump(hexdump::HexDump),
     Source(source_code::Source),
     Logging,
 }
@@ -108,6 +111,7 @@ impl egui_tiles::Behavior<Identifier> for Tabs {
             match self.mapping.get_mut(pane) {
                 Some(PanelKind::Disassembly(disassembly)) => disassembly.show(ui),
                 Some(PanelKind::Functions(functions)) => functions.show(ui),
+                Some(PanelKind::HexDump(hex_dump)) => hex_dump.show(ui),
                 Some(PanelKind::Source(src)) => src.show(ui),
                 Some(PanelKind::Logging) => {
                     let area = egui::ScrollArea::vertical()
@@ -220,6 +224,11 @@ impl Panels {
             )),
         );
 
+        self.panes.mapping.insert(
+            HEX_DUMP,
+            PanelKind::HexDump(hexdump::HexDump::new(processor.clone())),
+        );
+
         self.panes.processor = Some(processor);
     }
 
@@ -276,6 +285,20 @@ impl Panels {
         }
     }
 
+    /// Show or hide the hex dump, split off to the right of the other windows.
+    pub fn toggle_hex_dump(&mut self) {
+        if let Some(id) = self.tree.tiles.find_pane(&HEX_DUMP) {
+            let visible = self.tree.tiles.is_visible(id);
+            self.tree.tiles.set_visible(id, !visible);
+            return;
+        }
+
+        let pane = self.tree.tiles.insert_pane(HEX_DUMP);
+        let root = self.tree.root.unwrap();
+        let split = self.tree.tiles.insert_horizontal_tile(vec![root, pane]);
+        self.tree.root = Some(split);
+    }
+
     /// Show some close/maximize/minimize buttons for the native window.
     #[cfg(any(target_family = "windows", target_os = "linux"))]
     fn top_bar_native(&mut self, ui: &mut egui::Ui) {
@@ -331,6 +354,11 @@ impl Panels {
                     ui.close_menu();
                 }
 
+                if ui.button(HEX_DUMP).clicked() {
+                    self.toggle_hex_dump();
+                    ui.close_menu();
+                }
+
                 if ui.button(SOURCE).clicked() {
                     self.goto_window(SOURCE);
                     ui.close_menu();
@@ -368,6 +396,10 @@ impl Panels {
             self.ask_for_binary();
         }
 
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::H)) {
+            self.toggle_hex_dump();
+        }
+
         // alt-tab'ing between tabs
         if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab)) {
             for id in self.tree.active_tiles() {
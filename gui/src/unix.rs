@@ -97,6 +97,13 @@ mod macos {
                 false,
                 None,
             ));
+            windows.push(CheckMenuItem::with_id(
+                panes::HEX_DUMP,
+                "Hex dump",
+                true,
+                false,
+                None,
+            ));
             windows.push(CheckMenuItem::with_id(
                 panes::LOGGING,
                 "Logging",
@@ -0,0 +1,78 @@
+//! Example runtime addresses of objects that are loaded at a random address, such that a
+//! listing can be correlated with what a debugger shows.
+//!
+//! The load addresses mimic the ranges used by the default loaders of Linux, Windows and macOS,
+//! they're only meant as an example of a single run.
+
+use binformat::security::{self, Placement};
+use object::{BinaryFormat, Object, ObjectSegment};
+use processor_shared::PhysAddr;
+use std::hash::{BuildHasher, Hasher};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aslr {
+    /// Address the object is linked at.
+    pub image_base: PhysAddr,
+    /// Address the object is loaded at in this example run.
+    pub load_base: PhysAddr,
+}
+
+/// Random number that differs between runs.
+fn random() -> u64 {
+    std::collections::hash_map::RandomState::new().build_hasher().finish()
+}
+
+/// Lowest address of the range the loader picks from, the number of slots in the range and the
+/// alignment of each slot.
+fn load_range(format: BinaryFormat, placement: Placement, is_64: bool) -> (u64, u64, u64) {
+    match (format, placement, is_64) {
+        (BinaryFormat::Pe, Placement::Library, true) => (0x7ff8_0000_0000, 1 << 17, 0x10000),
+        (BinaryFormat::Pe, _, true) => (0x7ff6_0000_0000, 1 << 17, 0x10000),
+        (BinaryFormat::Pe, Placement::Library, false) => (0x1000_0000, 1 << 8, 0x10000),
+        (BinaryFormat::Pe, _, false) => (0x40_0000, 1 << 8, 0x10000),
+        (BinaryFormat::MachO, Placement::Library, _) => (0x1_8000_0000, 1 << 16, 0x1000),
+        (BinaryFormat::MachO, _, _) => (0x1_0000_0000, 1 << 16, 0x1000),
+        (_, Placement::Library, true) => (0x7f00_0000_0000, 1 << 28, 0x1000),
+        (_, _, true) => (0x5500_0000_0000, 1 << 28, 0x1000),
+        (_, Placement::Library, false) => (0xf700_0000, 1 << 8, 0x1000),
+        (_, _, false) => (0x5650_0000, 1 << 8, 0x1000),
+    }
+}
+
+impl Aslr {
+    pub fn new(obj: &object::File) -> Self {
+        let image_base = match obj.format() {
+            BinaryFormat::Pe => obj.relative_address_base(),
+            // Segments that aren't backed by the file, e.g. `__PAGEZERO`, don't count.
+            _ => obj
+                .segments()
+                .filter(|segment| segment.file_range().1 > 0)
+                .map(|segment| segment.address())
+                .min()
+                .unwrap_or(0),
+        };
+
+        let load_base = match security::placement(obj) {
+            Placement::Fixed => image_base,
+            placement => {
+                let (start, slots, align) = load_range(obj.format(), placement, obj.is_64());
+                start + (random() % slots) * align
+            }
+        };
+
+        Self {
+            image_base: image_base as PhysAddr,
+            load_base: load_base as PhysAddr,
+        }
+    }
+
+    /// Offset of an address from the start of the object, as debuggers show for modules.
+    pub fn relative(&self, addr: PhysAddr) -> PhysAddr {
+        addr.wrapping_sub(self.image_base)
+    }
+
+    /// Address at which `addr` ends up in this example run.
+    pub fn runtime(&self, addr: PhysAddr) -> PhysAddr {
+        self.load_base.wrapping_add(self.relative(addr))
+    }
+}
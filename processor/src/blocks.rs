@@ -1,16 +1,20 @@
-use crate::Processor;
+use crate::{Aslr, Instruction, Processor, Thunk};
+use arm::armv8::a64::{Opcode, Operand};
 use binformat::elf::{Elf32Dyn, Elf32Sym, Elf64Dyn, Elf64Sym};
+use binformat::macho::PointerAuth;
 use binformat::pe::ExceptionDirectoryEntry;
 use binformat::ToData;
+use commands::ARGS;
 use config::CONFIG;
+use debugvault::cache::Analysis;
 use debugvault::Symbol;
 use object::Endian;
-use processor_shared::{encode_hex_bytes_truncated, Section, SectionKind};
+use processor_shared::{encode_hex_bytes_truncated, PhysAddr, Section, SectionKind};
 use std::mem::size_of;
 use std::sync::Arc;
 use tokenizing::{colors, Token, TokenStream};
 
-const BYTES_BLOCK_SIZE: usize = 256;
+pub(crate) const BYTES_BLOCK_SIZE: usize = 256;
 
 #[derive(Debug)]
 pub enum BlockContent {
@@ -23,9 +27,16 @@ pub enum BlockContent {
     Label {
         symbol: Arc<Symbol>,
     },
+    /// Line of source code the following instructions were compiled from.
+    Source {
+        line: usize,
+        text: String,
+    },
     Instruction {
         inst: Vec<Token>,
         bytes: String,
+        /// Short description of the mnemonic, if it's in the instruction set reference.
+        description: Option<&'static str>,
     },
     Error {
         err: decoder::ErrorKind,
@@ -41,17 +52,47 @@ pub enum BlockContent {
     Pointer {
         value: u64,
         symbol: Option<Arc<Symbol>>,
+        /// How the pointer is signed on arm64e.
+        auth: Option<PointerAuth>,
     },
     DataStructure {
         ident: &'static str,
         /// (addr, field, type, value).
         fields: Vec<(usize, &'static str, &'static str, String)>,
     },
+    /// Pointer in a data section to a symbol or into a section, e.g. `.quad foo+0x10`.
+    Reference {
+        size: usize,
+        target: PhysAddr,
+        /// Symbol or section followed by the offset into it.
+        name: Vec<Token>,
+    },
     Bytes {
         bytes: Vec<u8>,
     },
 }
 
+/// Address column of a line, alongside the example runtime address when simulating ASLR.
+pub(crate) fn push_addr(stream: &mut TokenStream, addr: usize, aslr: Option<&Aslr>) {
+    match aslr {
+        Some(aslr) => {
+            stream.push_owned(format!("{:0>10X} ", aslr.relative(addr)), CONFIG.colors.address);
+            stream.push_owned(format!("{:0>12X}  ", aslr.runtime(addr)), CONFIG.colors.comment);
+        }
+        None => stream.push_owned(format!("{addr:0>10X}  "), CONFIG.colors.address),
+    }
+}
+
+/// Encoded bytes of an instruction padded to the widest instruction of the architecture, so the
+/// instructions after them line up. Empty if the column is hidden with `--no-bytes`.
+pub(crate) fn bytes_column(bytes: &[u8], max_instruction_width: usize) -> String {
+    if ARGS.no_bytes {
+        return String::new();
+    }
+
+    encode_hex_bytes_truncated(bytes, max_instruction_width * 3 + 1, true)
+}
+
 #[derive(Debug)]
 pub struct Block {
     pub addr: usize,
@@ -65,23 +106,29 @@ impl Block {
             BlockContent::SectionStart { .. } => 2,
             BlockContent::SectionEnd { .. } => 2,
             BlockContent::Label { .. } => 2,
+            BlockContent::Source { .. } => 1,
             BlockContent::Instruction { .. } => 1,
             BlockContent::Error { .. } => 1,
             BlockContent::CString { bytes } => bytes.len() + 1,
             BlockContent::Pointer { .. } => 1,
+            BlockContent::Reference { .. } => 1,
             BlockContent::Got { .. } => 1,
             BlockContent::DataStructure { fields, .. } => 2 + fields.len(),
             BlockContent::Bytes { bytes } => (bytes.len() / 32) + 1,
         }
     }
 
-    pub fn tokenize(&self, stream: &mut TokenStream) {
+    pub fn tokenize(&self, stream: &mut TokenStream, aslr: Option<&Aslr>) {
         match &self.content {
             BlockContent::Label { symbol } => {
                 stream.push("\n<", CONFIG.colors.asm.label);
                 stream.inner.extend_from_slice(symbol.name());
                 stream.push(">", CONFIG.colors.asm.label);
             }
+            BlockContent::Source { line, text } => {
+                stream.push_owned(format!("{line:>10}  "), CONFIG.colors.comment);
+                stream.push_owned(text.clone(), CONFIG.colors.comment);
+            }
             BlockContent::SectionStart { section } => {
                 stream.push("section started", colors::WHITE);
                 stream.push_owned(format!(" {} ", section.name), CONFIG.colors.asm.section);
@@ -110,26 +157,26 @@ impl Block {
                 stream.push("-", CONFIG.colors.delimiter);
                 stream.push_owned(format!("{:x}", section.end), colors::GREEN);
             }
-            BlockContent::Instruction { inst, bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
+            BlockContent::Instruction { inst, bytes, .. } => {
+                push_addr(stream, self.addr, aslr);
                 stream.push_owned(bytes.clone(), CONFIG.colors.bytes);
                 stream.inner.extend_from_slice(&inst);
             }
             BlockContent::Error { err, bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
+                push_addr(stream, self.addr, aslr);
                 stream.push_owned(bytes.clone(), CONFIG.colors.bytes);
                 stream.push("<", CONFIG.colors.brackets);
                 stream.push_owned(format!("{err:?}"), CONFIG.colors.asm.invalid);
                 stream.push(">", CONFIG.colors.brackets);
             }
             BlockContent::CString { bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
+                push_addr(stream, self.addr, aslr);
                 let lossy_string = String::from_utf8_lossy(&bytes);
                 let escaped = format!("\"{}\"", lossy_string.escape_debug());
                 stream.push_owned(escaped, CONFIG.colors.asm.string);
             }
             BlockContent::Got { symbol, .. } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
+                push_addr(stream, self.addr, aslr);
                 stream.push("<", CONFIG.colors.asm.label);
                 let name = symbol.name();
                 if name.is_empty() {
@@ -146,12 +193,12 @@ impl Block {
                 // addr  }
                 let start_addr = fields[0].0;
                 let end_addr = fields[fields.len() - 1].0;
-                stream.push_owned(format!("{:0>10X}  ", start_addr), CONFIG.colors.address);
+                push_addr(stream, start_addr, aslr);
                 stream.push("struct ", CONFIG.colors.src.keyword);
                 stream.push(ident, CONFIG.colors.src.tipe);
                 stream.push(" {\n", CONFIG.colors.delimiter);
                 for (addr, name, tipe, value) in fields {
-                    stream.push_owned(format!("{:0>10X}  ", addr), CONFIG.colors.address);
+                    push_addr(stream, *addr, aslr);
                     stream.push("    ", colors::WHITE);
                     stream.push(name, CONFIG.colors.src.field);
                     stream.push(": ", colors::WHITE);
@@ -160,22 +207,39 @@ impl Block {
                     stream.push_owned(value.clone(), CONFIG.colors.src.constant);
                     stream.push("\n", colors::WHITE);
                 }
-                stream.push_owned(format!("{:0>10X}  ", end_addr), CONFIG.colors.address);
+                push_addr(stream, end_addr, aslr);
                 stream.push("}", CONFIG.colors.delimiter);
             }
-            BlockContent::Pointer { value, symbol, .. } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
+            BlockContent::Pointer { value, symbol, auth } => {
+                push_addr(stream, self.addr, aslr);
                 stream.push_owned(format!("{:#x}", value), CONFIG.colors.bytes);
                 if let Some(symbol) = symbol {
                     stream.push(" <", CONFIG.colors.asm.label);
                     stream.inner.extend_from_slice(symbol.name());
                     stream.push(">", CONFIG.colors.asm.label);
                 }
+                if let Some(auth) = auth {
+                    let mut comment = format!("  ; signed with key {:?}", auth.key);
+                    if auth.diversity != 0 {
+                        comment += &format!(", diversity {:#x}", auth.diversity);
+                    }
+                    if auth.addr_diversity {
+                        comment += ", address diversified";
+                    }
+                    stream.push_owned(comment, CONFIG.colors.comment);
+                }
+            }
+            BlockContent::Reference { size, target, name } => {
+                push_addr(stream, self.addr, aslr);
+                let directive = if *size == 4 { ".long " } else { ".quad " };
+                stream.push(directive, CONFIG.colors.asm.opcode);
+                stream.inner.extend_from_slice(name);
+                stream.push_owned(format!("  ; {target:#x}"), CONFIG.colors.comment);
             }
             BlockContent::Bytes { bytes } => {
                 let mut off = 0;
                 for chunk in bytes.chunks(32) {
-                    stream.push_owned(format!("{:0>10X}  ", self.addr + off), CONFIG.colors.address);
+                    push_addr(stream, self.addr + off, aslr);
                     let s = processor_shared::encode_hex_bytes_truncated(chunk, usize::MAX, false);
                     stream.push_owned(s, CONFIG.colors.bytes);
                     stream.push("\n", colors::WHITE);
@@ -203,6 +267,120 @@ impl Processor {
         self.index.get_sym_by_addr(addr)
     }
 
+    /// Where a branch to a thunk actually ends up.
+    fn thunk_comment(&self, target: PhysAddr) -> Option<String> {
+        // The name of a stub in the procedure linkage table already says where it goes, apart
+        // from the version of the import.
+        if let Some(stub) = self.index.get_sym_by_addr(target) {
+            if let Some(name) = stub.as_str().strip_suffix("@plt") {
+                let import = self.index.get_sym_by_addr(self.resolve_thunk(target))?;
+                let versioned = import.as_str().strip_prefix(name)?.starts_with('@');
+                return versioned.then(|| format!("  ; -> {}", import.as_str()));
+            }
+        }
+
+        match self.thunk_by_addr(target)? {
+            Thunk::Jump(_) => {
+                let target = self.resolve_thunk(target);
+                match self.index.get_sym_by_addr(target) {
+                    Some(symbol) => Some(format!("  ; -> {}", symbol.as_str())),
+                    None => Some(format!("  ; -> {target:#x}")),
+                }
+            }
+            Thunk::Indirect(reg) => Some(format!("  ; indirect through {reg}")),
+        }
+    }
+
+    /// What a pointer authentication instruction does with the pointer it signs, authenticates
+    /// or strips.
+    fn pac_comment(&self, inst: &Instruction) -> Option<&'static str> {
+        if self.arch != object::Architecture::Aarch64 {
+            return None;
+        }
+
+        let inst = unsafe { &*inst.aarch64 };
+        let comment = match inst.opcode {
+            Opcode::PACIA | Opcode::PACIZA => "  ; sign with key IA",
+            Opcode::PACIB | Opcode::PACIZB => "  ; sign with key IB",
+            Opcode::PACDA | Opcode::PACDZA => "  ; sign with key DA",
+            Opcode::PACDB | Opcode::PACDZB => "  ; sign with key DB",
+            Opcode::PACGA => "  ; generic signature with key GA",
+            Opcode::AUTIA | Opcode::AUTIZA => "  ; authenticate with key IA",
+            Opcode::AUTIB | Opcode::AUTIZB => "  ; authenticate with key IB",
+            Opcode::AUTDA | Opcode::AUTDZA => "  ; authenticate with key DA",
+            Opcode::AUTDB | Opcode::AUTDZB => "  ; authenticate with key DB",
+            Opcode::XPACI | Opcode::XPACD => "  ; strip signature",
+            Opcode::BRAA | Opcode::BRAAZ | Opcode::BLRAA | Opcode::BLRAAZ => {
+                "  ; authenticated branch with key IA"
+            }
+            Opcode::BRAB | Opcode::BRABZ | Opcode::BLRAB | Opcode::BLRABZ => {
+                "  ; authenticated branch with key IB"
+            }
+            Opcode::RETAA | Opcode::ERETAA => "  ; authenticate return address with key IA",
+            Opcode::RETAB | Opcode::ERETAB => "  ; authenticate return address with key IB",
+            Opcode::LDRAA => "  ; authenticated load with key DA",
+            Opcode::LDRAB => "  ; authenticated load with key DB",
+            Opcode::HINT => {
+                let (Operand::ControlReg(crm), Operand::Immediate(op2)) =
+                    (inst.operands[0], inst.operands[1])
+                else {
+                    return None;
+                };
+
+                match (crm << 3) | op2 as u16 {
+                    0x07 => "  ; strip signature of return address",
+                    0x08 => "  ; sign x17 with key IA",
+                    0x0a => "  ; sign x17 with key IB",
+                    0x0c => "  ; authenticate x17 with key IA",
+                    0x0e => "  ; authenticate x17 with key IB",
+                    0x18 | 0x19 => "  ; sign return address with key IA",
+                    0x1a | 0x1b => "  ; sign return address with key IB",
+                    0x1c | 0x1d => "  ; authenticate return address with key IA",
+                    0x1e | 0x1f => "  ; authenticate return address with key IB",
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        Some(comment)
+    }
+
+    /// Point the operand that gets filled in by a relocation at the relocation's target,
+    /// instead of at whatever placeholder address the field holds.
+    fn apply_reloc(
+        &self,
+        addr: PhysAddr,
+        width: usize,
+        target: Option<PhysAddr>,
+        inst: &mut Vec<Token>,
+    ) {
+        let name = match self.reloc_in(addr, width) {
+            Some(name) => debugvault::demangle(name),
+            None => return,
+        };
+
+        let placeholder = target.map(|target| decoder::encode_hex(target as i64));
+        let pos = inst.iter().position(|token| Some(&*token.text) == placeholder.as_deref());
+
+        match pos {
+            Some(pos) => {
+                inst.splice(
+                    pos..=pos,
+                    [
+                        Token::from_str("<", CONFIG.colors.asm.label),
+                        Token::from_string(name, CONFIG.colors.asm.label),
+                        Token::from_str(">", CONFIG.colors.asm.label),
+                    ],
+                );
+            }
+            None => {
+                let comment = format!("  ; reloc -> {name}");
+                inst.push(Token::from_string(comment, CONFIG.colors.comment));
+            }
+        }
+    }
+
     /// Parse blocks given an address boundary.
     pub fn parse_blocks(&self, addr: usize) -> Vec<Block> {
         let mut blocks = Vec::new();
@@ -266,6 +444,7 @@ impl Processor {
             SectionKind::Got32 => self.parse_got(addr, 4, section, &mut blocks),
             SectionKind::Got64 => self.parse_got(addr, 4, section, &mut blocks),
             SectionKind::CString => self.parse_cstring(addr, section, &mut blocks),
+            SectionKind::Raw => self.parse_data(addr, section, &mut blocks),
             SectionKind::ExceptionDirEntry => {
                 self.parse_datastructure::<ExceptionDirectoryEntry>(addr, section, &mut blocks);
             }
@@ -330,11 +509,26 @@ impl Processor {
             self.endianness.read_u64_bytes(bytes.try_into().unwrap())
         };
 
-        let symbol = self.get_symbol_by_addr(addr, section);
+        let mut symbol = self.get_symbol_by_addr(addr, section);
+
+        // Pointers fixed up by dyld hold an encoding of the fixup rather than an address.
+        let fixup = self.pointer_by_addr(addr);
+        let auth = fixup.and_then(|fixup| fixup.auth);
+        let value = match fixup.and_then(|fixup| fixup.target) {
+            Some(target) => {
+                symbol = symbol.or_else(|| self.index.get_sym_by_addr(target as PhysAddr));
+                target
+            }
+            None => value,
+        };
 
         blocks.push(Block {
             addr,
-            content: BlockContent::Pointer { value, symbol },
+            content: BlockContent::Pointer {
+                value,
+                symbol,
+                auth,
+            },
         });
     }
 
@@ -360,26 +554,67 @@ impl Processor {
                     content: BlockContent::Label { symbol },
                 })
             }
+
+            if let Some(source) = self.source_line_by_addr(addr) {
+                blocks.push(Block {
+                    addr,
+                    content: BlockContent::Source {
+                        line: source.line,
+                        text: source.text.clone(),
+                    },
+                })
+            }
         }
 
         if let Some(inst) = opt_inst {
             let width = self.instruction_width(&inst);
-            let inst = self.instruction_tokens(&inst, &self.index);
+            let target = self.branch_target(inst);
+            let pac_comment = self.pac_comment(inst);
+            let data_ref_comment = self.data_ref_comment(inst);
+            let mut inst = self.instruction_tokens(&inst, &self.index);
+            self.apply_reloc(addr, width, target, &mut inst);
+            if let Some(comment) = target.and_then(|target| self.thunk_comment(target)) {
+                inst.push(Token::from_string(comment, CONFIG.colors.comment));
+            }
+            if let Some(hint) = self.branch_hint_by_addr(addr) {
+                inst.push(Token::from_str(hint.comment(), CONFIG.colors.comment));
+            }
+            if let Some(comment) = pac_comment {
+                inst.push(Token::from_str(comment, CONFIG.colors.comment));
+            }
+            if let Some(comment) = data_ref_comment {
+                inst.push(Token::from_string(comment, CONFIG.colors.comment));
+            }
+            if let Some(string) = self.stack_string_by_addr(addr) {
+                inst.push(Token::from_string(format!("  ; {string:?}"), CONFIG.colors.comment));
+            }
+            if let Some(mismatch) = self.arg_mismatch_by_addr(addr) {
+                inst.push(Token::from_string(
+                    format!(
+                        "  ; only {} of {} arguments initialized",
+                        mismatch.initialized, mismatch.expected
+                    ),
+                    CONFIG.colors.comment,
+                ));
+            }
+            let description = inst.first().and_then(|t| self.instruction_description(&t.text));
             let bytes = section.bytes_by_addr(addr, width);
-            let bytes =
-                encode_hex_bytes_truncated(&bytes, self.max_instruction_width * 3 + 1, true);
+            let bytes = bytes_column(&bytes, self.max_instruction_width);
 
             blocks.push(Block {
                 addr,
-                content: BlockContent::Instruction { inst, bytes },
+                content: BlockContent::Instruction {
+                    inst,
+                    bytes,
+                    description,
+                },
             });
             return;
         }
 
         if let Some(err) = opt_err {
             let bytes = section.bytes_by_addr(addr, err.size());
-            let bytes =
-                encode_hex_bytes_truncated(&bytes, self.max_instruction_width * 3 + 1, true);
+            let bytes = bytes_column(&bytes, self.max_instruction_width);
 
             blocks.push(Block {
                 addr,
@@ -429,6 +664,10 @@ impl Processor {
 
     /// Only need to compute the start's of blocks.
     pub fn compute_block_boundaries(&self) -> Vec<usize> {
+        if let Some(cached) = self.cached(Analysis::BlockBoundaries) {
+            return cached.into_iter().map(|addr| addr as usize).collect();
+        }
+
         let mut boundaries = Vec::new();
         std::thread::scope(|s| {
             let threads: Vec<_> = self
@@ -443,6 +682,9 @@ impl Processor {
 
         boundaries.sort_unstable();
         boundaries.dedup();
+
+        let values: Vec<u64> = boundaries.iter().map(|&addr| addr as u64).collect();
+        self.cache(Analysis::BlockBoundaries, &values);
         boundaries
     }
 
@@ -466,6 +708,7 @@ impl Processor {
         match section.kind {
             SectionKind::Code => self.compute_code_boundaries(section, &mut boundaries),
             SectionKind::CString => self.compute_cstring_boundaries(section, &mut boundaries),
+            SectionKind::Raw => self.compute_data_boundaries(section, &mut boundaries),
             SectionKind::Ptr32 | SectionKind::Got32 => {
                 let mut addr = section.start;
                 while addr < section.end {
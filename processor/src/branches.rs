@@ -0,0 +1,185 @@
+//! Static prediction of conditional branches, such that the likely path through an unfamiliar
+//! function stands out.
+//!
+//! A branch back to an earlier instruction of the same function closes a loop and is likely
+//! taken. A branch to a path that ends in a call that doesn't return, like `abort` or a panic,
+//! leads to error handling and is unlikely taken, and likely taken if that path is the one it
+//! falls through to.
+
+use crate::{Instruction, Processor};
+use processor_shared::{AddressMap, Addressed, PhysAddr};
+use x86_64::long_mode::Opcode;
+
+/// How many instructions of a path are looked at for a call that doesn't return.
+const MAX_PATH: usize = 32;
+
+/// Functions that never return to their caller.
+const NORETURN: &[&str] = &[
+    "abort",
+    "exit",
+    "_exit",
+    "_Exit",
+    "quick_exit",
+    "err",
+    "errx",
+    "verr",
+    "verrx",
+    "longjmp",
+    "siglongjmp",
+    "__assert_fail",
+    "__assert_rtn",
+    "__stack_chk_fail",
+    "__fortify_fail",
+    "__chk_fail",
+    "__cxa_throw",
+    "__cxa_rethrow",
+    "__cxa_bad_cast",
+    "__cxa_bad_typeid",
+    "_Unwind_Resume",
+    "std::terminate",
+    "std::process::abort",
+    "std::process::exit",
+    "alloc::alloc::handle_alloc_error",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::result::unwrap_failed",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchHint {
+    /// Jumps back to the start of a loop.
+    Loop,
+    /// Jumps to a path that doesn't return.
+    ErrorPath,
+    /// Falls through to a path that doesn't return.
+    SkipsErrorPath,
+}
+
+impl BranchHint {
+    pub fn comment(&self) -> &'static str {
+        match self {
+            Self::Loop => "  ; likely taken, loop",
+            Self::ErrorPath => "  ; unlikely taken, error path",
+            Self::SkipsErrorPath => "  ; likely taken, skips error path",
+        }
+    }
+}
+
+fn is_noreturn(name: &str) -> bool {
+    let name = name.strip_suffix("@plt").unwrap_or(name);
+    NORETURN.contains(&name)
+        || name.starts_with("core::panicking::")
+        || name.starts_with("std::panicking::begin_panic")
+        || name.starts_with("core::slice::index::") && name.ends_with("_fail")
+}
+
+impl Processor {
+    /// Predict every conditional branch that one of the heuristics applies to (x86_64 only).
+    pub(crate) fn find_branch_hints(&self) -> AddressMap<BranchHint> {
+        let mut hints = AddressMap::default();
+
+        if self.arch != object::Architecture::X86_64 {
+            return hints;
+        }
+
+        for Addressed { addr, item } in self.instructions.iter() {
+            let inst = unsafe { &*item.x64 };
+            if !is_conditional(inst.opcode()) {
+                continue;
+            }
+
+            let Some(target) = inst.rel_addr() else {
+                continue;
+            };
+
+            let in_function = self.function_start(*addr).is_some_and(|start| target >= start);
+            let hint = if target <= *addr && in_function {
+                BranchHint::Loop
+            } else if self.is_error_path(target) {
+                BranchHint::ErrorPath
+            } else if self.is_error_path(*addr + self.instruction_width(item)) {
+                BranchHint::SkipsErrorPath
+            } else {
+                continue;
+            };
+
+            hints.push(Addressed {
+                addr: *addr,
+                item: hint,
+            });
+        }
+
+        hints
+    }
+
+    /// Whether the straight-line path starting at `addr` ends without returning.
+    fn is_error_path(&self, mut addr: PhysAddr) -> bool {
+        let Ok(idx) = self.instructions.search(addr) else {
+            return false;
+        };
+
+        for Addressed { addr: next, item } in self.instructions[idx..].iter().take(MAX_PATH) {
+            // The path ends at bytes that couldn't be decoded.
+            if *next != addr {
+                return false;
+            }
+
+            let inst = unsafe { &*item.x64 };
+            match inst.opcode() {
+                Opcode::UD2 | Opcode::HLT => return true,
+                Opcode::CALL if self.calls_noreturn(item) => return true,
+                Opcode::JMP => return self.calls_noreturn(item),
+                Opcode::RETURN | Opcode::RETF => return false,
+                opcode if is_conditional(opcode) => return false,
+                _ => {}
+            }
+
+            addr += self.instruction_width(item);
+        }
+
+        false
+    }
+
+    /// Whether a call or tail call goes to a function that doesn't return.
+    fn calls_noreturn(&self, inst: &Instruction) -> bool {
+        let Some(target) = self.branch_target(inst) else {
+            return false;
+        };
+
+        let target = self.resolve_thunk(target);
+        self.index.get_sym_by_addr(target).is_some_and(|sym| is_noreturn(sym.as_str()))
+    }
+
+    pub fn branch_hint_by_addr(&self, addr: PhysAddr) -> Option<BranchHint> {
+        match self.branch_hints.search(addr) {
+            Ok(idx) => Some(self.branch_hints[idx].item),
+            Err(..) => None,
+        }
+    }
+}
+
+fn is_conditional(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::JO
+            | Opcode::JNO
+            | Opcode::JB
+            | Opcode::JNB
+            | Opcode::JZ
+            | Opcode::JNZ
+            | Opcode::JA
+            | Opcode::JNA
+            | Opcode::JS
+            | Opcode::JNS
+            | Opcode::JP
+            | Opcode::JNP
+            | Opcode::JL
+            | Opcode::JGE
+            | Opcode::JLE
+            | Opcode::JG
+            | Opcode::LOOP
+            | Opcode::LOOPZ
+            | Opcode::LOOPNZ
+            | Opcode::JRCXZ
+    )
+}
@@ -0,0 +1,75 @@
+//! Direct calls between functions, from which a call graph of the whole object is built.
+//!
+//! Calls through thunks are attributed to the function that eventually gets called and jumps
+//! to the start of another function are counted as (tail) calls.
+
+use crate::Processor;
+use debugvault::cache::Analysis;
+use processor_shared::{Addressed, PhysAddr};
+use std::collections::BTreeMap;
+use x86_64::long_mode::Opcode;
+
+impl Processor {
+    /// Start of the function containing `addr`.
+    pub(crate) fn function_start(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        match self.index.syms.search(addr) {
+            Ok(idx) => Some(self.index.syms[idx].addr),
+            Err(0) => None,
+            Err(idx) => Some(self.index.syms[idx - 1].addr),
+        }
+    }
+
+    /// Number of calls from one function to another by the start of the caller and the
+    /// callee (x86_64 only).
+    pub fn call_edges(&self) -> BTreeMap<(PhysAddr, PhysAddr), usize> {
+        let mut edges = BTreeMap::new();
+
+        if self.arch != object::Architecture::X86_64 {
+            return edges;
+        }
+
+        if let Some(cached) = self.cached(Analysis::CallEdges) {
+            for edge in cached.chunks_exact(3) {
+                edges.insert((edge[0] as PhysAddr, edge[1] as PhysAddr), edge[2] as usize);
+            }
+            return edges;
+        }
+
+        for Addressed { addr, item } in self.instructions.iter() {
+            let inst = unsafe { &*item.x64 };
+            let is_call = match inst.opcode() {
+                Opcode::CALL => true,
+                Opcode::JMP => false,
+                _ => continue,
+            };
+
+            let (Some(caller), Some(target)) =
+                (self.function_start(*addr), self.branch_target(item))
+            else {
+                continue;
+            };
+
+            // Calls through a thunk are already attributed to the function it calls.
+            if self.thunk_by_addr(caller).is_some() {
+                continue;
+            }
+
+            let callee = self.resolve_thunk(target);
+            let is_function = self.index.get_sym_by_addr(callee).is_some();
+
+            // Any other jump stays within the function.
+            if !is_call && (!is_function || callee == caller) {
+                continue;
+            }
+
+            *edges.entry((caller, callee)).or_default() += 1;
+        }
+
+        let values: Vec<u64> = edges
+            .iter()
+            .flat_map(|(&(caller, callee), &count)| [caller as u64, callee as u64, count as u64])
+            .collect();
+        self.cache(Analysis::CallEdges, &values);
+        edges
+    }
+}
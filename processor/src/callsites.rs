@@ -0,0 +1,259 @@
+//! Sanity checking of call sites against the signatures recovered from debug info.
+//!
+//! A call that initializes fewer argument registers than the callee expects is a strong hint
+//! of a mismatched declaration, e.g. across an FFI boundary.
+//!
+//! Signatures are only recovered from DWARF, as PDB type records aren't parsed. PE binaries are
+//! therefore only checked when they're built with DWARF, e.g. by MinGW.
+
+use crate::Processor;
+use debugvault::ParamKind;
+use processor_shared::{AddressMap, Addressed, PhysAddr};
+use x86_64::long_mode::{register_class, Instruction, Opcode, Operand, RegSpec};
+
+/// How many instructions before a call are searched for argument setup.
+const MAX_LOOKBACK: usize = 32;
+
+/// Calling conventions that pass integer arguments in registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abi {
+    /// System V AMD64, used by ELF and Mach-O.
+    SysV,
+    /// Microsoft x64, used by PE.
+    Win64,
+}
+
+impl Abi {
+    /// Numbers of the general purpose registers that hold the integer parameters.
+    fn arg_regs(self, params: &[ParamKind]) -> Vec<u8> {
+        match self {
+            // rdi, rsi, rdx, rcx, r8, r9 are assigned in order to integer parameters.
+            Abi::SysV => params
+                .iter()
+                .filter(|&&kind| kind == ParamKind::Integer)
+                .zip([7, 6, 2, 1, 8, 9])
+                .map(|(_, reg)| reg)
+                .collect(),
+            // rcx, rdx, r8, r9 are assigned by position, floats take up a slot as well.
+            Abi::Win64 => params
+                .iter()
+                .zip([1, 2, 8, 9])
+                .filter(|(&kind, _)| kind == ParamKind::Integer)
+                .map(|(_, reg)| reg)
+                .collect(),
+        }
+    }
+}
+
+/// A call that initializes fewer argument registers than the callee expects.
+#[derive(Debug, Clone, Copy)]
+pub struct ArgMismatch {
+    /// Number of integer arguments the callee takes in registers.
+    pub expected: usize,
+    /// Number of those registers written before the call.
+    pub initialized: usize,
+}
+
+/// Number of the general purpose register, ignoring the width it's accessed with.
+pub(crate) fn gpr_num(reg: RegSpec) -> Option<u8> {
+    let class = reg.class();
+
+    if class == register_class::Q
+        || class == register_class::D
+        || class == register_class::W
+        || class == register_class::RB
+    {
+        return Some(reg.num());
+    }
+
+    // ah, ch, dh and bh share their number with spl, bpl, sil and dil.
+    if class == register_class::B && reg.num() < 4 {
+        return Some(reg.num());
+    }
+
+    None
+}
+
+/// Whether control flow continues after `inst`. Argument registers are clobbered by calls and
+/// control flow doesn't fall through jumps and returns.
+fn falls_through(inst: &Instruction) -> bool {
+    !matches!(
+        inst.opcode(),
+        Opcode::CALL
+            | Opcode::CALLF
+            | Opcode::JMP
+            | Opcode::JMPF
+            | Opcode::RETURN
+            | Opcode::RETF
+            | Opcode::UD2
+            | Opcode::HLT
+    )
+}
+
+/// Number of the general purpose register `inst` writes to, if any.
+fn written_gpr(inst: &Instruction) -> Option<u8> {
+    match inst.opcode() {
+        // Instructions that only read their first operand.
+        Opcode::CMP | Opcode::TEST | Opcode::PUSH | Opcode::BT => None,
+        _ if inst.operand_count() == 0 => None,
+        _ => match inst.operand(0) {
+            Operand::Register(reg) => gpr_num(reg),
+            _ => None,
+        },
+    }
+}
+
+impl Processor {
+    /// Check all direct calls to functions with a known signature (x86_64 only).
+    pub(crate) fn check_call_args(&self, abi: Abi) -> AddressMap<ArgMismatch> {
+        let mut mismatches = AddressMap::default();
+
+        if self.arch != object::Architecture::X86_64 {
+            return mismatches;
+        }
+
+        for (idx, Addressed { addr, item }) in self.instructions.iter().enumerate() {
+            let inst = unsafe { &*item.x64 };
+            if inst.opcode() != Opcode::CALL {
+                continue;
+            }
+
+            let target = match inst.rel_addr() {
+                Some(target) => self.resolve_thunk(target),
+                None => continue,
+            };
+
+            let expected = match self.index.get_params_by_addr(target) {
+                Some(params) => abi.arg_regs(params),
+                None => continue,
+            };
+
+            if expected.is_empty() {
+                continue;
+            }
+
+            let written = match self.written_arg_regs(idx, abi) {
+                Some(written) => written,
+                None => continue,
+            };
+            let initialized = expected.iter().filter(|reg| written.contains(reg)).count();
+            if initialized < expected.len() {
+                mismatches.push(Addressed {
+                    addr: *addr,
+                    item: ArgMismatch {
+                        expected: expected.len(),
+                        initialized,
+                    },
+                });
+            }
+        }
+
+        if !mismatches.is_empty() {
+            log::complex!(
+                w "[processor::check_call_args] found ",
+                y mismatches.len().to_string(),
+                w " calls with uninitialized arguments.",
+            );
+        }
+
+        mismatches
+    }
+
+    /// Collect the general purpose registers written in the straight-line code before the call
+    /// at index `idx`. Returns `None` if it can't be determined where that code starts.
+    fn written_arg_regs(&self, idx: usize, abi: Abi) -> Option<Vec<u8>> {
+        let mut written = Vec::new();
+        let mut next_addr: PhysAddr = self.instructions[idx].addr;
+
+        for Addressed { addr, item } in self.instructions[..idx].iter().rev().take(MAX_LOOKBACK) {
+            let inst = unsafe { &*item.x64 };
+
+            // Gaps in the code, e.g. data or a decoding error.
+            if *addr + self.instruction_width(item) != next_addr {
+                return None;
+            }
+
+            if !falls_through(inst) {
+                return Some(written);
+            }
+            written.extend(written_gpr(inst));
+
+            // The arguments of the caller are still live at the start of a function.
+            if self.index.get_sym_by_addr(*addr).is_some() {
+                let params = self.index.get_params_by_addr(*addr)?;
+                written.extend(abi.arg_regs(params));
+                return Some(written);
+            }
+
+            next_addr = *addr;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decoder::InstructionStream;
+    use x86_64::long_mode::Decoder;
+    use ParamKind::{Float, Integer};
+
+    /// Registers written in `code` leading up to its end, as if it were followed by a call.
+    fn written(code: &[u8]) -> Vec<u8> {
+        let insts: Vec<Instruction> = InstructionStream::new(Decoder::default(), code, 0)
+            .map(|(_, inst)| inst.unwrap())
+            .collect();
+
+        let mut written = Vec::new();
+        for inst in insts.iter().rev().take_while(|inst| falls_through(inst)) {
+            written.extend(written_gpr(inst));
+        }
+        written
+    }
+
+    #[test]
+    fn sysv_arg_regs() {
+        assert_eq!(Abi::SysV.arg_regs(&[Integer, Integer, Integer]), [7, 6, 2]);
+        // floats are passed in xmm registers without taking up an integer register.
+        assert_eq!(Abi::SysV.arg_regs(&[Float, Integer, Float, Integer]), [7, 6]);
+        // only the first six are passed in registers.
+        assert_eq!(Abi::SysV.arg_regs(&[Integer; 8]), [7, 6, 2, 1, 8, 9]);
+    }
+
+    #[test]
+    fn win64_arg_regs() {
+        assert_eq!(Abi::Win64.arg_regs(&[Integer, Integer, Integer]), [1, 2, 8]);
+        // floats take up the slot of the integer register at their position.
+        assert_eq!(Abi::Win64.arg_regs(&[Float, Integer, Float, Integer]), [2, 9]);
+        // only the first four are passed in registers.
+        assert_eq!(Abi::Win64.arg_regs(&[Integer; 6]), [1, 2, 8, 9]);
+    }
+
+    #[test]
+    fn written_regs() {
+        // mov edi, 1; mov rsi, rax; xor edx, edx; cmp rcx, 0
+        let code = [
+            0xbf, 0x01, 0x00, 0x00, 0x00, 0x48, 0x89, 0xc6, 0x31, 0xd2, 0x48, 0x83, 0xf9, 0x00,
+        ];
+        let mut regs = written(&code);
+        regs.sort();
+        assert_eq!(regs, [2, 6, 7]);
+
+        let sysv = Abi::SysV.arg_regs(&[Integer; 3]);
+        assert!(sysv.iter().all(|reg| regs.contains(reg)));
+        // rcx is only compared and r8 is never written.
+        let win64 = Abi::Win64.arg_regs(&[Integer; 3]);
+        assert_eq!(win64.iter().filter(|reg| regs.contains(reg)).count(), 1);
+    }
+
+    #[test]
+    fn written_before_call() {
+        // mov edi, 1; call 0; mov esi, 2
+        let code = [
+            0xbf, 0x01, 0x00, 0x00, 0x00, 0xe8, 0x00, 0x00, 0x00, 0x00, 0xbe, 0x02, 0x00, 0x00, 0x00,
+        ];
+        // rdi is clobbered by the call in between.
+        assert_eq!(written(&code), [6]);
+    }
+}
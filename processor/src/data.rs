@@ -0,0 +1,229 @@
+//! Contents of data sections that aren't described by the object, such as `.data.rel.ro` or
+//! `.rodata`. Pointers into loaded sections and strings are picked out of them, so they aren't
+//! hidden in a flat run of bytes.
+
+use crate::blocks::{Block, BlockContent, BYTES_BLOCK_SIZE};
+use crate::Processor;
+use config::CONFIG;
+use debugvault::Symbol;
+use object::Endian;
+use processor_shared::{PhysAddr, Section};
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Shortest run of printable characters that's shown as a string.
+const MIN_STRING: usize = 4;
+
+/// Lowest address that's taken for a pointer, small values are far more likely to be integers.
+const MIN_POINTER: PhysAddr = 0x1000;
+
+enum DataItem {
+    Reference { size: usize, target: PhysAddr },
+    /// String of `len` bytes, including the null terminator.
+    String { len: usize },
+    Bytes { len: usize },
+}
+
+/// Length of the null-terminated string at `addr`, including the terminator.
+fn string_at(section: &Section, addr: PhysAddr) -> Option<usize> {
+    // Strings only start right after the end of another one, as in a string table.
+    if addr != section.start && section.bytes_by_addr(addr - 1, 1) != [b'\0'] {
+        return None;
+    }
+
+    let bytes = section.bytes_by_addr(addr, usize::MAX);
+    let len = bytes.iter().position(|&b| b == b'\0')?;
+    let printable = |&b: &u8| b.is_ascii_graphic() || b.is_ascii_whitespace();
+    (len >= MIN_STRING && bytes[..len].iter().all(printable)).then_some(len + 1)
+}
+
+/// What's at `addr`, with `reference_at` giving the target of a pointer of `size` bytes. A run of
+/// bytes ends at the next pointer or string.
+fn data_item_at(
+    section: &Section,
+    addr: PhysAddr,
+    size: usize,
+    reference_at: impl Fn(PhysAddr) -> Option<PhysAddr>,
+) -> DataItem {
+    if let Some(target) = reference_at(addr) {
+        return DataItem::Reference { size, target };
+    }
+
+    if let Some(len) = string_at(section, addr) {
+        return DataItem::String { len };
+    }
+
+    let end = (section.start + section.bytes().len()).min(addr + BYTES_BLOCK_SIZE);
+    let mut next = addr + 1;
+    while next < end && reference_at(next).is_none() && string_at(section, next).is_none() {
+        next += 1;
+    }
+
+    DataItem::Bytes { len: next - addr }
+}
+
+impl Processor {
+    fn pointer_size(&self) -> usize {
+        self.arch.address_size().map_or(8, |size| size.bytes() as usize)
+    }
+
+    /// Symbol `addr` lies in, together with the offset into it. Symbols of another section
+    /// aren't considered, as `addr` can't be part of them.
+    pub(crate) fn symbol_containing(&self, addr: PhysAddr) -> Option<(&Arc<Symbol>, usize)> {
+        let idx = match self.index.syms.search(addr) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        let symbol = &self.index.syms[idx];
+        let section = self.section_by_addr(addr)?;
+        if symbol.addr < section.start {
+            return None;
+        }
+
+        Some((&symbol.item, addr - symbol.addr))
+    }
+
+    /// Target of an aligned, pointer-sized value at `addr` that points into a loaded section.
+    fn reference_at(&self, section: &Section, addr: PhysAddr) -> Option<PhysAddr> {
+        let size = self.pointer_size();
+        if !addr.is_multiple_of(size) {
+            return None;
+        }
+
+        let bytes = section.bytes_by_addr(addr, size);
+        let value = match bytes.len() {
+            4 => self.endianness.read_u32_bytes(bytes.try_into().unwrap()) as PhysAddr,
+            8 => self.endianness.read_u64_bytes(bytes.try_into().unwrap()) as PhysAddr,
+            _ => return None,
+        };
+
+        // Pointers fixed up by dyld hold an encoding of the fixup rather than an address.
+        let target = match self.pointer_by_addr(addr) {
+            Some(pointer) => pointer.target? as PhysAddr,
+            None => value,
+        };
+
+        if target < MIN_POINTER {
+            return None;
+        }
+
+        let loaded = self.sections().any(|section| (section.start..section.end).contains(&target));
+        loaded.then_some(target)
+    }
+
+    fn data_item_at(&self, section: &Section, addr: PhysAddr) -> DataItem {
+        let size = self.pointer_size();
+        data_item_at(section, addr, size, |addr| self.reference_at(section, addr))
+    }
+
+    /// Name of what a pointer refers to: the symbol it's in or otherwise the section, followed
+    /// by the offset into it.
+    fn reference_name(&self, target: PhysAddr) -> Vec<Token> {
+        let (mut name, offset) = match self.symbol_containing(target) {
+            Some((symbol, offset)) => (symbol.name().to_vec(), offset),
+            None => {
+                let section = self.section_by_addr(target).unwrap();
+                let name = Token::from_string(section.name.clone(), CONFIG.colors.asm.section);
+                (vec![name], target - section.start)
+            }
+        };
+
+        if offset != 0 {
+            name.push(Token::from_string(format!("+{offset:#x}"), CONFIG.colors.asm.expr));
+        }
+
+        name
+    }
+
+    pub(crate) fn parse_data(&self, addr: PhysAddr, section: &Section, blocks: &mut Vec<Block>) {
+        let content = match self.data_item_at(section, addr) {
+            DataItem::Reference { size, target } => BlockContent::Reference {
+                size,
+                target,
+                name: self.reference_name(target),
+            },
+            DataItem::String { len } => BlockContent::CString {
+                bytes: section.bytes_by_addr(addr, len - 1).to_vec(),
+            },
+            DataItem::Bytes { len } => BlockContent::Bytes {
+                bytes: section.bytes_by_addr(addr, len).to_vec(),
+            },
+        };
+
+        blocks.push(Block { addr, content });
+    }
+
+    pub(crate) fn compute_data_boundaries(&self, section: &Section, boundaries: &mut Vec<usize>) {
+        let end = section.start + section.bytes().len();
+        let mut addr = section.start;
+        while addr < end {
+            boundaries.push(addr);
+            addr += match self.data_item_at(section, addr) {
+                DataItem::Reference { size, .. } => size,
+                DataItem::String { len } | DataItem::Bytes { len } => len,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use processor_shared::SectionKind;
+
+    static BYTES: [u8; 32] = [
+        0x10, 0x30, 0, 0, 0, 0, 0, 0, b'h', b'e', b'l', b'l', b'o', 0, 0xff, 0xfe, //
+        0x20, 0x30, 0, 0, 0, 0, 0, 0, b'h', b'i', 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn section() -> Section {
+        Section::new(".data".to_string(), "data", SectionKind::Raw, &BYTES, 0x2000, 0x2020)
+    }
+
+    /// Aligned pointers into `0x3000..0x4000`.
+    fn reference_at(section: &Section, addr: PhysAddr) -> Option<PhysAddr> {
+        let bytes = section.bytes_by_addr(addr, 8).try_into().ok()?;
+        let target = u64::from_le_bytes(bytes) as PhysAddr;
+        (addr.is_multiple_of(8) && (0x3000..0x4000).contains(&target)).then_some(target)
+    }
+
+    #[test]
+    fn items() {
+        let section = section();
+        let mut items = Vec::new();
+        let mut addr = section.start;
+        while addr < section.end {
+            let item = data_item_at(&section, addr, 8, |addr| reference_at(&section, addr));
+            let (item, len) = match item {
+                DataItem::Reference { size, target } => (format!("{target:#x}"), size),
+                DataItem::String { len } => ("string".to_string(), len),
+                DataItem::Bytes { len } => ("bytes".to_string(), len),
+            };
+            items.push((addr, item, len));
+            addr += len;
+        }
+
+        let expected = [
+            (0x2000, "0x3010", 8),
+            (0x2008, "string", 6),
+            (0x200e, "bytes", 2),
+            (0x2010, "0x3020", 8),
+            // too short to be taken for a string.
+            (0x2018, "bytes", 8),
+        ]
+        .map(|(addr, item, len)| (addr, item.to_string(), len));
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn strings() {
+        let section = section();
+        assert_eq!(string_at(&section, 0x2008), Some(6));
+        // strings don't start in the middle of another one.
+        assert_eq!(string_at(&section, 0x2009), None);
+        assert_eq!(string_at(&section, 0x200e), None);
+        assert_eq!(string_at(&section, 0x2018), None);
+    }
+}
@@ -0,0 +1,72 @@
+//! What the RIP-relative operands of x86_64 instructions refer to, such that a reader doesn't
+//! have to look up the address of a string or the variable a field belongs to.
+
+use crate::{Instruction, Processor};
+use processor_shared::{PhysAddr, SectionKind};
+use x86_64::long_mode::{Operand, RegSpec};
+
+/// Longest string that's shown before it's cut off.
+const MAX_STRING: usize = 64;
+
+impl Processor {
+    /// Comment with the string or symbol a RIP-relative operand refers to (x86_64 only).
+    pub(crate) fn data_ref_comment(&self, inst: &Instruction) -> Option<String> {
+        if self.arch != object::Architecture::X86_64 {
+            return None;
+        }
+
+        let inst = unsafe { &*inst.x64 };
+        let is_rip_relative = (0..inst.operand_count()).any(|idx| {
+            matches!(
+                inst.operand(idx),
+                Operand::RegDisp(RegSpec::RIP, _) | Operand::RegDispMasked(RegSpec::RIP, ..)
+            )
+        });
+
+        if !is_rip_relative {
+            return None;
+        }
+
+        let target = inst.rel_addr()?;
+        if let Some(string) = self.string_by_addr(target) {
+            return Some(format!("  ; {string:?}"));
+        }
+
+        // Pointers fixed up by dyld hold an encoding of the fixup rather than their target.
+        if let Some(pointee) = self.pointer_by_addr(target).and_then(|pointer| pointer.target) {
+            if let Some(symbol) = self.index.get_sym_by_addr(pointee as PhysAddr) {
+                return Some(format!("  ; -> {}", symbol.as_str()));
+            }
+        }
+
+        // Operands that point at the start of a symbol already show it.
+        match self.symbol_containing(target)? {
+            (_, 0) => None,
+            (symbol, offset) => Some(format!("  ; {}+{offset:#x}", symbol.as_str())),
+        }
+    }
+
+    /// Null-terminated string of printable characters at `addr` in a data section.
+    fn string_by_addr(&self, addr: PhysAddr) -> Option<String> {
+        let section = self.section_by_addr(addr)?;
+        if section.kind == SectionKind::Code {
+            return None;
+        }
+
+        let bytes = section.bytes_by_addr(addr, usize::MAX);
+        let len = bytes.iter().position(|&b| b == b'\0')?;
+        let bytes = &bytes[..len];
+
+        let printable = |&b: &u8| b.is_ascii_graphic() || b.is_ascii_whitespace();
+        if bytes.is_empty() || !bytes.iter().all(printable) {
+            return None;
+        }
+
+        let mut string = String::from_utf8_lossy(&bytes[..len.min(MAX_STRING)]).into_owned();
+        if len > MAX_STRING {
+            string.push_str("..");
+        }
+
+        Some(string)
+    }
+}
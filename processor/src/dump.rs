@@ -0,0 +1,600 @@
+//! Analysis of flat memory dumps, e.g. firmware RAM dumps or hypervisor snapshots.
+//!
+//! A dump has no header describing it, so it comes paired with a JSON layout:
+//!
+//! ```json
+//! {
+//!     "arch": "aarch64",
+//!     "entry": "0x40080000",
+//!     "regions": [
+//!         { "name": "text", "base": "0x40080000", "offset": "0x0", "size": "0x20000" },
+//!         { "name": "data", "base": "0x400a0000", "offset": "0x20000", "size": "0x8000",
+//!           "kind": "data" }
+//!     ],
+//!     "symbols": ["vmlinux", "System.map"]
+//! }
+//! ```
+//!
+//! Symbol files are either objects with a symbol table or kernel style symbol maps.
+//! Relative paths are resolved against the directory of the layout.
+//!
+//! Terse Executables of UEFI firmware are loaded the same way, with a region per section, as are
+//! the segments of Nintendo Switch modules.
+//!
+//! Raw binaries without any header are treated as a dump with a single region of code, unless
+//! they're Intel HEX or S-record images, which have a region for every range they load.
+//!
+//! ARM code that starts with a Cortex-M vector table is decoded as Thumb, with its handlers
+//! named after the exceptions and interrupts they handle. Any other code is only decoded as
+//! Thumb if the layout's arch is "thumb".
+
+use crate::{apply_symbol_map, read_symbol_map, Backing, Error, Module, Processor};
+use binformat::elf::Mapping;
+use binformat::RawSymbol;
+use debugvault::Index;
+use object::{Architecture, Endianness, Object, ObjectSymbol};
+use processor_shared::{AddressMap, Addressed, PhysAddr, Section, SectionKind, Segment};
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct Layout {
+    /// Instruction set of all code regions.
+    arch: String,
+    /// Whether the dump is stored in big-endian byte order.
+    #[serde(default)]
+    big_endian: bool,
+    /// Where execution starts.
+    #[serde(default, deserialize_with = "opt_address")]
+    entry: Option<PhysAddr>,
+    /// Ranges of the dump and where they are mapped in the address space.
+    regions: Vec<Region>,
+    /// Files to read symbols from.
+    #[serde(default)]
+    symbols: Vec<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Region {
+    name: String,
+    /// Virtual address the region is mapped at.
+    #[serde(deserialize_with = "address")]
+    base: PhysAddr,
+    /// Offset of the region into the dump.
+    #[serde(default, deserialize_with = "address")]
+    offset: usize,
+    #[serde(deserialize_with = "address")]
+    size: usize,
+    #[serde(default)]
+    kind: RegionKind,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RegionKind {
+    #[default]
+    Code,
+    Data,
+}
+
+/// Addresses are given either as integers or as (hexadecimal) strings.
+fn address<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    struct AddressParsing;
+    impl<'de> Visitor<'de> for AddressParsing {
+        type Value = usize;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("expected an integer or a hexadecimal string")
+        }
+
+        fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+            Ok(n as usize)
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            let addr = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => usize::from_str_radix(hex, 16),
+                None => s.parse(),
+            };
+
+            addr.map_err(|_| E::custom(format!("invalid address '{s}'")))
+        }
+    }
+
+    deserializer.deserialize_any(AddressParsing)
+}
+
+fn opt_address<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    address(deserializer).map(Some)
+}
+
+/// Architecture by the name it's commonly referred to.
+pub fn parse_arch(name: &str) -> Option<Architecture> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "x86" | "i386" | "i686" => Architecture::I386,
+        "x86_64" | "x86-64" | "amd64" => Architecture::X86_64,
+        "arm" | "armv7" | "thumb" => Architecture::Arm,
+        "aarch64" | "arm64" | "armv8" => Architecture::Aarch64,
+        "riscv32" | "rv32" => Architecture::Riscv32,
+        "riscv64" | "rv64" => Architecture::Riscv64,
+        "mips" => Architecture::Mips,
+        "mips64" => Architecture::Mips64,
+        _ => return None,
+    })
+}
+
+/// Read symbols from either an object or a kernel style symbol map.
+fn parse_symbol_file<'data>(
+    path: &Path,
+    data: &'data [u8],
+) -> Result<AddressMap<RawSymbol<'data>>, Error> {
+    if let Ok(obj) = object::File::parse(data) {
+        let mut syms = AddressMap::default();
+        for sym in obj.symbols() {
+            if let Ok(name) = sym.name() {
+                syms.push(Addressed {
+                    addr: sym.address() as usize,
+                    item: RawSymbol { name, module: None },
+                });
+            }
+        }
+
+        return Ok(syms);
+    }
+
+    match std::str::from_utf8(data) {
+        // Dumps are of memory as it's loaded, so there's nothing to undo.
+        Ok(map) => Ok(binformat::kallsyms::parse(map, |_| None)),
+        Err(_) => Err(Error::Layout(format!("Unrecognized symbol file {path:?}."))),
+    }
+}
+
+/// Map every region to where it lies in the dump, sorted by address.
+fn map_regions(
+    regions: Vec<Region>,
+    binary: &'static [u8],
+) -> Result<(Vec<Section>, Vec<Segment>), Error> {
+    let mut sections = Vec::new();
+    let mut segments = Vec::new();
+    for region in regions {
+        let bytes = region
+            .offset
+            .checked_add(region.size)
+            .and_then(|end| binary.get(region.offset..end))
+            .ok_or_else(|| {
+                Error::Layout(format!("Region '{}' lies outside of the dump.", region.name))
+            })?;
+
+        let kind = match region.kind {
+            RegionKind::Code => SectionKind::Code,
+            RegionKind::Data => SectionKind::Raw,
+        };
+
+        let start = region.base;
+        let end = start.checked_add(region.size).ok_or_else(|| {
+            Error::Layout(format!("Region '{}' ends past the address space.", region.name))
+        })?;
+        segments.push(Segment {
+            name: region.name.clone(),
+            start,
+            end,
+        });
+        sections.push(Section::new(region.name, "LAYOUT", kind, bytes, start, end));
+    }
+
+    segments.sort_unstable_by_key(|s| s.start);
+    sections.sort_unstable_by_key(|s| s.start);
+
+    for pair in sections.windows(2) {
+        if pair[0].end > pair[1].start {
+            return Err(Error::Layout(format!(
+                "Regions '{}' and '{}' overlap.",
+                pair[0].name, pair[1].name
+            )));
+        }
+    }
+
+    Ok((sections, segments))
+}
+
+impl Processor {
+    /// Analyze a flat memory dump as the composite address space described by `layout`.
+    pub fn parse_dump<P: AsRef<Path>, L: AsRef<Path>>(path: P, layout: L) -> Result<Self, Error> {
+        let layout_path = layout.as_ref();
+        let layout = std::fs::read_to_string(layout_path).map_err(Error::IO)?;
+        let layout: Layout = serde_json::from_str(&layout)
+            .map_err(|err| Error::Layout(format!("Failed to parse {layout_path:?}: {err}.")))?;
+
+        let dir = layout_path.parent().unwrap_or(Path::new(""));
+        let backing = vec![Backing::map(path.as_ref())?];
+        // SAFETY: the map is moved into the processor along with everything borrowing from it.
+        let binary = unsafe { backing[0].bytes() };
+        Self::parse_layout(path.as_ref(), backing, binary, dir, layout, Vec::new())
+    }
+
+    /// Analyze a file without any object header, e.g. a bootloader or shellcode, as a single
+    /// region of code loaded at `base`.
+    ///
+    /// Intel HEX and S-record images are loaded at the addresses of their records instead, in
+    /// which case `base` only moves them.
+    pub fn parse_raw<P: AsRef<Path>>(path: P, arch: &str, base: PhysAddr) -> Result<Self, Error> {
+        let backing = vec![Backing::map(path.as_ref())?];
+        // SAFETY: the map is moved into the processor along with everything borrowing from it,
+        // or dropped after the image is copied out of it.
+        let binary = unsafe { backing[0].bytes() };
+
+        if let Some(image) = binformat::firmware::parse(binary) {
+            let image = image.map_err(Error::Layout)?;
+            return Self::parse_image(path.as_ref(), arch, base, image);
+        }
+
+        let layout = Layout {
+            arch: arch.to_string(),
+            big_endian: false,
+            entry: Some(base),
+            regions: vec![Region {
+                name: "raw".to_string(),
+                base,
+                offset: 0,
+                size: binary.len(),
+                kind: RegionKind::Code,
+            }],
+            symbols: Vec::new(),
+        };
+
+        let dir = Path::new("");
+        Self::parse_layout(path.as_ref(), backing, binary, dir, layout, Vec::new())
+    }
+
+    /// Analyze the memory of a firmware image, with a region of code for each range it loads.
+    fn parse_image(
+        path: &Path,
+        arch: &str,
+        base: PhysAddr,
+        image: binformat::firmware::Image,
+    ) -> Result<Self, Error> {
+        let mut memory = Vec::new();
+        let mut regions = Vec::new();
+        for region in image.regions {
+            regions.push(Region {
+                name: format!("load_{:x}", region.addr),
+                base: base + region.addr as PhysAddr,
+                offset: memory.len(),
+                size: region.bytes.len(),
+                kind: RegionKind::Code,
+            });
+            memory.extend(region.bytes);
+        }
+
+        if regions.is_empty() {
+            return Err(Error::Layout("The image doesn't load anything.".to_string()));
+        }
+
+        let layout = Layout {
+            arch: arch.to_string(),
+            big_endian: false,
+            entry: Some(image.entry.map_or(regions[0].base, |entry| base + entry as PhysAddr)),
+            regions,
+            symbols: Vec::new(),
+        };
+
+        let backing = vec![Backing::Buffer(memory.into_boxed_slice())];
+        // SAFETY: the buffer is moved into the processor along with everything borrowing from it.
+        let binary = unsafe { backing[0].bytes() };
+        Self::parse_layout(path, backing, binary, Path::new(""), layout, Vec::new())
+    }
+
+    /// Analyze a Terse Executable, the stripped down PE object of a UEFI driver in firmware.
+    pub(crate) fn parse_te(
+        path: &Path,
+        backing: Vec<Backing>,
+        binary: &'static [u8],
+    ) -> Result<Self, Error> {
+        let image = binformat::uefi::parse_te(binary).map_err(Error::Layout)?;
+        if let Some(subsystem) = image.subsystem {
+            log::complex!(
+                w "[processor::parse_te] image is a ",
+                g subsystem,
+                w ".",
+            );
+        }
+
+        let regions = image
+            .sections
+            .into_iter()
+            .map(|section| Region {
+                name: section.name,
+                base: section.addr as PhysAddr,
+                offset: section.offset,
+                size: section.size,
+                kind: if section.code {
+                    RegionKind::Code
+                } else {
+                    RegionKind::Data
+                },
+            })
+            .collect();
+
+        let layout = Layout {
+            arch: image.arch.to_string(),
+            big_endian: false,
+            entry: Some(image.entry as PhysAddr),
+            regions,
+            symbols: Vec::new(),
+        };
+
+        Self::parse_layout(path, backing, binary, Path::new(""), layout, Vec::new())
+    }
+
+    /// Analyze a Nintendo Switch NSO or NRO module, with its segments decompressed.
+    pub(crate) fn parse_switch(
+        path: &Path,
+        mut backing: Vec<Backing>,
+        binary: &'static [u8],
+    ) -> Result<Self, Error> {
+        let module = binformat::switch::parse(binary).map_err(Error::Layout)?;
+        log::complex!(
+            w "[processor::parse_switch] ",
+            g module.format,
+            w " module with build-id ",
+            g module.build_id.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+            w ".",
+        );
+
+        let regions = module
+            .segments
+            .iter()
+            .map(|segment| Region {
+                name: segment.name.to_string(),
+                base: segment.offset,
+                offset: segment.offset,
+                size: segment.size,
+                kind: if segment.code {
+                    RegionKind::Code
+                } else {
+                    RegionKind::Data
+                },
+            })
+            .collect();
+
+        let names = module
+            .symbols
+            .into_iter()
+            .map(|sym| Addressed {
+                addr: sym.addr as PhysAddr,
+                item: sym.name,
+            })
+            .collect();
+
+        let layout = Layout {
+            arch: "aarch64".to_string(),
+            big_endian: false,
+            entry: module.segments.first().map(|text| text.offset),
+            regions,
+            symbols: Vec::new(),
+        };
+
+        backing.push(Backing::Buffer(module.memory.into_boxed_slice()));
+        // SAFETY: the buffer is moved into the processor along with everything borrowing from it.
+        let memory = unsafe { backing[backing.len() - 1].bytes() };
+        Self::parse_layout(path, backing, memory, Path::new(""), layout, names)
+    }
+
+    /// Analyze a dump as described by `layout`, named by its symbol files and `names`.
+    fn parse_layout(
+        path: &Path,
+        backing: Vec<Backing>,
+        binary: &'static [u8],
+        dir: &Path,
+        layout: Layout,
+        mut names: Vec<Addressed<String>>,
+    ) -> Result<Self, Error> {
+        let path = path.to_path_buf();
+        let now = std::time::Instant::now();
+
+        let arch = parse_arch(&layout.arch)
+            .ok_or_else(|| Error::InvalidArchitecture(layout.arch.clone()))?;
+
+        let (sections, segments) = map_regions(layout.regions, binary)?;
+
+        // Cortex-M firmware starts with its vector table, which leads to all of its code.
+        let mut entry = layout.entry;
+        let mut mappings = AddressMap::default();
+        let table = match (arch, sections.first()) {
+            (Architecture::Arm, Some(first)) if first.kind == SectionKind::Code => {
+                binformat::cortexm::vector_table(first.bytes(), first.start as u64)
+                    .map(|table| (first.start, table))
+            }
+            _ => None,
+        };
+
+        if let Some((base, table)) = table {
+            log::complex!(
+                w "[processor::parse_layout] found a vector table with ",
+                g table.handlers.len().to_string(),
+                w " handlers and initial stack pointer ",
+                g format!("{:#x}", table.stack),
+                w ".",
+            );
+
+            // Only the first handler is the reset handler, if there is one.
+            entry = table.handlers.first().map(|reset| reset.addr as PhysAddr).or(entry);
+            names.extend(table.handlers.into_iter().map(|handler| Addressed {
+                addr: handler.addr as PhysAddr,
+                item: handler.name,
+            }));
+
+            mappings.push(Addressed { addr: base, item: Mapping::Data });
+            mappings.push(Addressed { addr: base + table.size, item: Mapping::Thumb });
+        } else if layout.arch.eq_ignore_ascii_case("thumb") {
+            for section in sections.iter().filter(|s| s.kind == SectionKind::Code) {
+                mappings.push(Addressed { addr: section.start, item: Mapping::Thumb });
+            }
+        }
+
+        // Symbols only borrow from their files until they're indexed.
+        let mut files = Vec::new();
+        for symbols in layout.symbols {
+            let path = dir.join(symbols);
+            let data = std::fs::read(&path).map_err(Error::IO)?;
+            files.push((path, data));
+        }
+
+        let mut syms = AddressMap::default();
+        for (path, data) in files.iter() {
+            syms.extend(parse_symbol_file(path, data)?);
+        }
+
+        for name in names.iter() {
+            syms.push(Addressed {
+                addr: name.addr,
+                item: RawSymbol {
+                    name: &name.item,
+                    module: None,
+                },
+            });
+        }
+
+        if let Some(entry) = entry {
+            syms.push(Addressed {
+                addr: entry,
+                item: RawSymbol {
+                    name: "entry",
+                    module: None,
+                },
+            });
+        }
+
+        for section in sections.iter() {
+            syms.push(Addressed {
+                addr: section.start,
+                item: RawSymbol { name: &section.name, module: None }
+            });
+        }
+
+        // Regions take the place of sections for the segmented addresses of `.map` files.
+        let symbol_map = read_symbol_map()?;
+        if let Some(ref map) = symbol_map {
+            apply_symbol_map(&mut syms, map, |segment| {
+                let section = sections.get(segment.checked_sub(1)?)?;
+                Some(section.start as u64)
+            });
+        }
+
+        let index = Index::from_symbols(syms);
+        let entrypoint = entry.unwrap_or(0);
+
+        let module = Module {
+            path,
+            binary,
+            index,
+            entrypoint,
+            sections,
+            segments,
+            mappings,
+            arch,
+            endianness: if layout.big_endian {
+                Endianness::Big
+            } else {
+                Endianness::Little
+            },
+            backing,
+        };
+
+        Self::from_module(module, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DUMP: [u8; 0x40] = [0; 0x40];
+
+    fn regions(json: &str) -> Result<(Vec<Section>, Vec<Segment>), Error> {
+        let layout: Layout = serde_json::from_str(json).unwrap();
+        map_regions(layout.regions, &DUMP)
+    }
+
+    fn message(result: Result<(Vec<Section>, Vec<Segment>), Error>) -> String {
+        match result {
+            Err(Error::Layout(msg)) => msg,
+            Err(_) => panic!("layout was rejected for the wrong reason"),
+            Ok(_) => panic!("layout was accepted"),
+        }
+    }
+
+    #[test]
+    fn sorted() {
+        let Ok((sections, segments)) = regions(
+            r#"{ "arch": "thumb", "regions": [
+                { "name": "data", "base": "0x2000", "offset": "0x20", "size": 32, "kind": "data" },
+                { "name": "text", "base": "0x1000", "size": "0x20" }
+            ] }"#,
+        ) else {
+            panic!("layout was rejected");
+        };
+
+        assert_eq!(sections[0].name, "text");
+        assert_eq!(sections[0].kind, SectionKind::Code);
+        assert_eq!((sections[1].start, sections[1].end), (0x2000, 0x2020));
+        assert_eq!(sections[1].kind, SectionKind::Raw);
+        assert_eq!(segments[0].name, "text");
+    }
+
+    #[test]
+    fn malformed() {
+        let json = r#"{ "arch": "arm", "regions": [{ "name": "a", "base": "0xzz", "size": 4 }] }"#;
+        assert!(serde_json::from_str::<Layout>(json).is_err());
+        assert!(serde_json::from_str::<Layout>(r#"{ "arch": "arm" }"#).is_err());
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let msg = message(regions(
+            r#"{ "arch": "arm", "regions": [
+                { "name": "text", "base": 0, "offset": 32, "size": 64 }
+            ] }"#,
+        ));
+        assert_eq!(msg, "Region 'text' lies outside of the dump.");
+
+        let msg = message(regions(&format!(
+            r#"{{ "arch": "arm", "regions": [
+                {{ "name": "text", "base": 0, "offset": {}, "size": 2 }}
+            ] }}"#,
+            usize::MAX
+        )));
+        assert_eq!(msg, "Region 'text' lies outside of the dump.");
+    }
+
+    #[test]
+    fn overflow() {
+        let msg = message(regions(&format!(
+            r#"{{ "arch": "arm", "regions": [{{ "name": "text", "base": {}, "size": 16 }}] }}"#,
+            usize::MAX - 8
+        )));
+        assert_eq!(msg, "Region 'text' ends past the address space.");
+    }
+
+    #[test]
+    fn overlap() {
+        let msg = message(regions(
+            r#"{ "arch": "arm", "regions": [
+                { "name": "high", "base": "0x1010", "offset": 16, "size": 16 },
+                { "name": "low", "base": "0x1000", "size": 32 }
+            ] }"#,
+        ));
+        assert_eq!(msg, "Regions 'low' and 'high' overlap.");
+
+        // Regions that only touch are fine.
+        assert!(regions(
+            r#"{ "arch": "arm", "regions": [
+                { "name": "high", "base": "0x1020", "offset": 32, "size": 32 },
+                { "name": "low", "base": "0x1000", "size": 32 }
+            ] }"#,
+        )
+        .is_ok());
+    }
+}
@@ -15,6 +15,14 @@ impl fmt::Debug for super::Error {
             Self::UnknownArchitecture(arch) => {
                 f.write_fmt(format_args!("Unsupported architecture: '{arch:?}'."))
             }
+            Self::InvalidArchitecture(arch) => {
+                f.write_fmt(format_args!("Unknown architecture: '{arch}'."))
+            }
+            Self::Layout(err) => f.write_str(err),
+            Self::MismatchedDsym(path) => {
+                f.write_fmt(format_args!("{path:?} doesn't contain the debug info of the object."))
+            }
+            Self::Archive(err) => f.write_str(err),
         }
     }
 }
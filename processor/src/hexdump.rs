@@ -0,0 +1,139 @@
+//! Hex dumps in the style of `xxd`, addressed by virtual address instead of file offset.
+
+use crate::blocks::push_addr;
+use crate::{Aslr, Processor};
+use config::CONFIG;
+use processor_shared::{PhysAddr, Section};
+use tokenizing::{colors, TokenStream};
+
+/// Bytes per line of a hex dump.
+pub const HEX_WIDTH: usize = 16;
+
+/// Line of a hex dump: the address, the bytes in pairs and the bytes as text. Printable ASCII is
+/// highlighted, anything else is written as a dimmed '.'.
+pub fn tokenize_hex_line(
+    stream: &mut TokenStream,
+    addr: PhysAddr,
+    bytes: &[u8],
+    aslr: Option<&Aslr>,
+) {
+    push_addr(stream, addr, aslr);
+
+    // Lines that don't start at a multiple of the width are padded, so the columns line up.
+    let offset = addr % HEX_WIDTH;
+    let mut hex = String::with_capacity(HEX_WIDTH * 5 / 2 + 1);
+    for idx in 0..HEX_WIDTH {
+        match idx.checked_sub(offset).and_then(|idx| bytes.get(idx)) {
+            Some(byte) => hex += &format!("{byte:02x}"),
+            None => hex += "  ",
+        }
+
+        if idx % 2 == 1 {
+            hex.push(' ');
+        }
+    }
+
+    stream.push_owned(hex, CONFIG.colors.bytes);
+    stream.push(" ", colors::WHITE);
+    if offset != 0 {
+        stream.push_owned(" ".repeat(offset), colors::WHITE);
+    }
+
+    // Runs of printable and unprintable bytes share a token.
+    for run in bytes.chunk_by(|a, b| is_printable(*a) == is_printable(*b)) {
+        if is_printable(run[0]) {
+            let text = run.iter().map(|&byte| byte as char).collect();
+            stream.push_owned(text, CONFIG.colors.src.string);
+        } else {
+            stream.push_owned(".".repeat(run.len()), colors::GRAY60);
+        }
+    }
+}
+
+fn is_printable(byte: u8) -> bool {
+    byte.is_ascii_graphic() || byte == b' '
+}
+
+/// Lines of a hex dump of `section` between `start` and `end`, see [`Processor::hex_lines`].
+fn section_lines(
+    section: &Section,
+    start: PhysAddr,
+    end: PhysAddr,
+) -> impl Iterator<Item = (PhysAddr, &[u8])> {
+    let mut addr = start.max(section.start);
+    let end = end.min(section.start + section.bytes().len());
+
+    std::iter::from_fn(move || {
+        if addr >= end {
+            return None;
+        }
+
+        let next = (addr / HEX_WIDTH + 1) * HEX_WIDTH;
+        let line = (addr, section.bytes_by_addr(addr, next.min(end) - addr));
+        addr = next;
+        Some(line)
+    })
+}
+
+impl Processor {
+    /// Lines of a hex dump of the loaded sections between `start` and `end`, together with the
+    /// section they're in.
+    ///
+    /// Lines start at a multiple of [`HEX_WIDTH`], except for the first line of a section or of
+    /// the range. Sections without contents in the object, such as `.bss`, are left out.
+    pub fn hex_lines(
+        &self,
+        start: PhysAddr,
+        end: PhysAddr,
+    ) -> impl Iterator<Item = (&Section, PhysAddr, &[u8])> + '_ {
+        self.sections().flat_map(move |section| {
+            section_lines(section, start, end).map(move |(addr, bytes)| (section, addr, bytes))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use processor_shared::SectionKind;
+
+    fn line(addr: PhysAddr, bytes: &[u8]) -> String {
+        let mut stream = TokenStream::new();
+        tokenize_hex_line(&mut stream, addr, bytes, None);
+        stream.to_string()
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            line(0x1000, b"Hello, world!\n\0\xff"),
+            "0000001000  4865 6c6c 6f2c 2077 6f72 6c64 210a 00ff  Hello, world!..."
+        );
+
+        // bytes of a line that doesn't start at a multiple of the width stay in their column.
+        assert_eq!(
+            line(0x100e, b"ab"),
+            format!("000000100E  {}6162  {}ab", " ".repeat(35), " ".repeat(14))
+        );
+    }
+
+    #[test]
+    fn lines_of_section() {
+        static BYTES: [u8; 40] = [0; 40];
+        let section = Section::new(
+            ".rodata".to_string(),
+            "rodata",
+            SectionKind::Raw,
+            &BYTES,
+            0x1008,
+            0x1030,
+        );
+
+        let lines = |start, end| -> Vec<_> {
+            section_lines(&section, start, end).map(|(addr, bytes)| (addr, bytes.len())).collect()
+        };
+        assert_eq!(lines(0, usize::MAX), [(0x1008, 8), (0x1010, 16), (0x1020, 16)]);
+        assert_eq!(lines(0x1014, 0x1024), [(0x1014, 12), (0x1020, 4)]);
+        assert!(lines(0x2000, 0x3000).is_empty());
+    }
+}
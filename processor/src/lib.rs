@@ -1,25 +1,55 @@
 mod fmt;
+mod aslr;
 mod blocks;
+mod branches;
+mod callgraph;
+mod callsites;
+mod data;
+mod datarefs;
+mod dump;
+mod hexdump;
+mod registers;
+mod snippet;
+mod source;
+mod stackstrings;
+mod thunks;
 
 use decoder::{Decodable, Decoded};
-use object::{Endianness, Object, ObjectSegment};
+use object::{Endianness, Object, ObjectSection, ObjectSegment, SectionIndex};
 use object::{Architecture, BinaryFormat};
 use object::read::File as ObjectFile;
 use processor_shared::{AddressMap, Addressed, PhysAddr, Section, SectionKind, Segment};
-use debugvault::Index;
+use debugvault::cache::{self, Analysis, SymbolCache};
+use debugvault::{FileAttr, Index, SplitDwarf};
 use tokenizing::Token;
 use binformat::{elf, macho, pe, RawSymbol};
+use binformat::symbol_map::MapAddr;
+use binformat::elf::Mapping;
+use callsites::Abi;
+use commands::ARGS;
 
-use memmap2::Mmap;
 use x86_64::long_mode as x64;
 use x86_64::protected_mode as x86;
 use arm::armv7 as armv7;
 use arm::armv8::a64 as aarch64;
 
-use std::fs::File;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
 use std::mem::ManuallyDrop;
 
+use memmap2::Mmap;
+
+pub use aslr::Aslr;
 pub use blocks::{BlockContent, Block};
+pub use branches::BranchHint;
+pub use callsites::ArgMismatch;
+pub use dump::parse_arch;
+pub use hexdump::{tokenize_hex_line, HEX_WIDTH};
+pub use registers::{RegisterUsage, RegisterUsages};
+pub use snippet::{assemble, decode_bytes, disassemble_bytes};
+pub use source::SourceLine;
+pub use thunks::Thunk;
 
 /// FIXME: This is way too large and way too broad.
 ///        Especially since these are being started for any address with a faulty decoding.
@@ -30,6 +60,10 @@ pub enum Error {
     NotAnExecutable,
     DecompressionFailed(object::Error),
     UnknownArchitecture(object::Architecture),
+    InvalidArchitecture(String),
+    Layout(String),
+    MismatchedDsym(std::path::PathBuf),
+    Archive(String),
 }
 
 pub union Instruction {
@@ -42,9 +76,9 @@ pub union Instruction {
 }
 
 macro_rules! impl_recursion {
-    ($symbols:expr, $errors:expr, $instructions:expr, $sections:expr,
-     $max_instruction_width:expr, $decoder:expr, $arch:ident) => {{
-        $max_instruction_width = $decoder.max_width();
+    ($symbols:expr, $errors:expr, $instructions:expr, $sections:expr, $window:expr,
+     $mappings:expr, $max_instruction_width:expr, $decoder:expr, $arch:ident) => {{
+        $max_instruction_width = $decoder(Mapping::Code).max_width();
 
         let width_guess = if $max_instruction_width == 4 {
             4
@@ -53,79 +87,183 @@ macro_rules! impl_recursion {
         };
 
         for section in $sections.iter().filter(|s| s.kind == SectionKind::Code) {
-            let mut prev_inst = None;
-            let mut reader = decoder::Reader::new(section.bytes());
-            let mut ip = section.start;
+            // only decode the part of the section that overlaps with the address window
+            let start = std::cmp::max(section.start, $window.start);
+            let end = std::cmp::min(section.end, $window.end);
+            if start >= end {
+                continue;
+            }
 
             log::complex!(
                 w "[processor::recurse] analyzing section ",
                 b &*section.name,
                 w " <",
-                g format!("{:x}", section.start),
+                g format!("{:x}", start),
                 w "..",
-                g format!("{:x}", section.end),
+                g format!("{:x}", end),
                 w ">.",
             );
 
             // guessing an average of 5 byte long instructions
-            log::PROGRESS.set("Decoding instructions", section.bytes().len() / width_guess);
-
-            loop {
-                // prefetch next cache line line
-                #[cfg(target_arch = "x86")]
-                unsafe {
-                    core::arch::x86::_mm_prefetch(
-                        reader.as_ptr() as *const i8,
-                        core::arch::x86::_MM_HINT_NTA
-                    );
-                }
-
-                #[cfg(target_arch = "x86_64")]
-                unsafe {
-                    core::arch::x86_64::_mm_prefetch(
-                        reader.as_ptr() as *const i8,
-                        core::arch::x86_64::_MM_HINT_NTA
-                    );
-                }
+            log::PROGRESS.set("Decoding instructions", (end - start) / width_guess);
 
-                match $decoder.decode(&mut reader) {
-                    Ok(mut instruction) => {
-                        instruction.update_rel_addrs(ip, prev_inst);
+            for (start, end, mapping) in code_regions(start, end, $mappings) {
+                let bytes = section.bytes_by_addr(start, end - start);
+                let stream = decoder::InstructionStream::new($decoder(mapping), bytes, start);
 
-                        let width = instruction.width();
-                        $instructions.push(Addressed {
-                            addr: ip,
-                            item: Instruction {
-                                $arch: std::mem::ManuallyDrop::new(instruction)
+                for (ip, decoded) in stream {
+                    match decoded {
+                        Ok(instruction) => {
+                            $instructions.push(Addressed {
+                                addr: ip,
+                                item: Instruction {
+                                    $arch: std::mem::ManuallyDrop::new(instruction)
+                                }
+                            });
+                        }
+                        Err(error) => {
+                            if error.kind == decoder::ErrorKind::ExhaustedInput {
+                                break;
                             }
-                        });
 
-                        prev_inst = $instructions.last().map(|inst| {
-                            unsafe { &*inst.item.$arch }
-                        });
-                        ip += width;
-                    }
-                    Err(error) => {
-                        if error.kind == decoder::ErrorKind::ExhaustedInput {
-                            break;
+                            $errors.push(Addressed {
+                                addr: ip,
+                                item: error
+                            });
                         }
-
-                        let width = error.size();
-                        $errors.push(Addressed {
-                            addr: ip,
-                            item: error
-                        });
-                        prev_inst = None;
-                        ip += width;
                     }
-                }
 
-                log::PROGRESS.step();
+                    log::PROGRESS.step();
+                }
             }
         }
     }};
 }
 
+/// Split up the range `start..end` into regions of code using the mapping symbols.
+/// Any regions that are marked as data are skipped.
+fn code_regions(
+    start: PhysAddr,
+    end: PhysAddr,
+    mappings: &AddressMap<Mapping>,
+) -> Vec<(PhysAddr, PhysAddr, Mapping)> {
+    let mut regions = Vec::new();
+
+    // find the mapping the range starts in, defaulting to code
+    let (mut mapping, first) = match mappings.search(start) {
+        Ok(idx) => (mappings[idx].item, idx + 1),
+        Err(0) => (Mapping::Code, 0),
+        Err(idx) => (mappings[idx - 1].item, idx),
+    };
+
+    let mut region_start = start;
+    for next in mappings[first..].iter().take_while(|next| next.addr < end) {
+        if next.item == mapping {
+            continue;
+        }
+
+        if mapping != Mapping::Data && region_start < next.addr {
+            regions.push((region_start, next.addr, mapping));
+        }
+
+        region_start = next.addr;
+        mapping = next.item;
+    }
+
+    if mapping != Mapping::Data && region_start < end {
+        regions.push((region_start, end, mapping));
+    }
+
+    regions
+}
+
+/// Memory that an object's sections and symbols borrow from.
+///
+/// The contents of a map or buffer stay in place when it's moved, so they're borrowed as
+/// `'static` for as long as the [`Processor`] owning them is alive. Everything that borrows them
+/// is owned by the same processor and declared before its backing, such that it's dropped first.
+enum Backing {
+    Map(Mmap),
+    Buffer(Box<[u8]>),
+}
+
+impl Backing {
+    fn map(path: &Path) -> Result<Self, Error> {
+        binformat::map(path).map(Self::Map).map_err(Error::IO)
+    }
+
+    /// # Safety
+    ///
+    /// The contents must not be borrowed beyond the lifetime of the backing, i.e. anything
+    /// holding a borrow has to be dropped before it.
+    unsafe fn bytes(&self) -> &'static [u8] {
+        let bytes = match self {
+            Self::Map(mmap) => &mmap[..],
+            Self::Buffer(buffer) => &buffer[..],
+        };
+
+        std::slice::from_raw_parts(bytes.as_ptr(), bytes.len())
+    }
+}
+
+/// Contents of the map given by `--symbol-map`, if any.
+fn read_symbol_map() -> Result<Option<String>, Error> {
+    ARGS.symbol_map.as_ref().map(std::fs::read_to_string).transpose().map_err(Error::IO)
+}
+
+/// Symbols of `map`, given by `--symbol-map`, which replace those at the same addresses.
+///
+/// `section_start` resolves the 1-based section indices used by the addresses of `.map` files.
+fn apply_symbol_map<'data>(
+    syms: &mut AddressMap<RawSymbol<'data>>,
+    map: &'data str,
+    section_start: impl Fn(usize) -> Option<u64>,
+) {
+    let mut named = AddressMap::default();
+    for sym in binformat::symbol_map::parse(map) {
+        let addr = match sym.addr {
+            MapAddr::Absolute(addr) => addr,
+            MapAddr::Segment { segment, offset } => match section_start(segment) {
+                Some(start) => start + offset,
+                None => {
+                    log::complex!(
+                        w "[processor::apply_symbol_map] ",
+                        y format!("no section {segment} for symbol '{}'", sym.name),
+                        w ".",
+                    );
+                    continue;
+                }
+            },
+        };
+
+        named.push(Addressed {
+            addr: addr as PhysAddr,
+            item: RawSymbol {
+                name: sym.name,
+                module: None,
+            },
+        });
+    }
+
+    let addrs: HashSet<PhysAddr> = named.iter().map(|sym| sym.addr).collect();
+    syms.retain(|sym| !addrs.contains(&sym.addr));
+    syms.extend(named);
+}
+
+/// Everything that's extracted from an input before it's decoded.
+struct Module {
+    path: std::path::PathBuf,
+    binary: &'static [u8],
+    index: Index,
+    entrypoint: PhysAddr,
+    sections: Vec<Section>,
+    segments: Vec<Segment>,
+    mappings: AddressMap<Mapping>,
+    arch: Architecture,
+    endianness: Endianness,
+    backing: Vec<Backing>,
+}
+
 /// Architecture agnostic analysis of a module.
 pub struct Processor {
     /// Where execution start. Might be zero in case of libraries.
@@ -137,11 +275,8 @@ pub struct Processor {
     /// Symbol lookup by physical address.
     pub index: Index,
 
-    /// File handle to binary,
-    _file: File,
-
-    /// A memory map of the binary.
-    _mmap: Mmap,
+    /// Contents of the binary, borrowed from its backing.
+    binary: &'static [u8],
 
     /// Object's sections sorted by address.
     sections: Vec<Section>,
@@ -157,6 +292,40 @@ pub struct Processor {
     /// Sorted by address.
     instructions: AddressMap<Instruction>,
 
+    /// Calls that don't initialize all the arguments of the callee.
+    /// Sorted by address.
+    arg_mismatches: AddressMap<ArgMismatch>,
+
+    /// Functions that only forward to another function.
+    /// Sorted by address.
+    thunks: AddressMap<Thunk>,
+
+    /// Predicted direction of conditional branches.
+    /// Sorted by address.
+    branch_hints: AddressMap<BranchHint>,
+
+    /// Strings constructed at runtime, by the address they're first written at.
+    /// Sorted by address.
+    stack_strings: AddressMap<String>,
+
+    /// Symbols that fields in code are relocated against, by the address of the field.
+    /// Sorted by address.
+    relocs: AddressMap<String>,
+
+    /// Pointers in data that are fixed up when the object is loaded, by their address.
+    /// Sorted by address.
+    pointers: AddressMap<macho::ChainedPointer>,
+
+    /// Example load address of the object, if ASLR is simulated.
+    aslr: Option<Aslr>,
+
+    /// Lines of source code by the address of the first instruction compiled from them.
+    /// Sorted by address.
+    source_lines: AddressMap<SourceLine>,
+
+    /// Key the analysis of the object is cached by, if it's the same every time it's opened.
+    cache_key: Option<cache::CacheKey>,
+
     /// How many bytes an instruction given the architecture.
     max_instruction_width: usize,
 
@@ -171,13 +340,41 @@ pub struct Processor {
 
     /// Target's endianness.
     endianness: Endianness,
+
+    /// Maps and buffers that the binary, sections and symbols borrow from.
+    /// Must be declared last, so that it's dropped after everything borrowing from it.
+    #[allow(dead_code)]
+    backing: Vec<Backing>,
 }
 
 impl Processor {
     pub fn parse<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
-        let file = std::fs::File::open(path.as_ref()).map_err(Error::IO)?;
-        let mmap = unsafe { Mmap::map(&file).map_err(Error::IO)? };
-        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+        Self::parse_member(path, ARGS.member.as_deref())
+    }
+
+    /// Parse an object, or the object named `member` if it's an archive.
+    pub fn parse_member<P: AsRef<std::path::Path>>(
+        path: P,
+        member: Option<&str>,
+    ) -> Result<Self, Error> {
+        let mut backing = vec![Backing::map(path.as_ref())?];
+        // SAFETY: the map is moved into the processor along with everything borrowing from it.
+        let mut binary = unsafe { backing[0].bytes() };
+        if binformat::archive::is_archive(binary) {
+            let Some(member) = member else {
+                return Err(Error::Archive(
+                    "The object is an archive, select one of its members with --member."
+                        .to_string(),
+                ));
+            };
+            binary = binformat::archive::member(binary, member).map_err(Error::Archive)?;
+        }
+        if binformat::uefi::is_te(binary) {
+            return Self::parse_te(path.as_ref(), backing, binary);
+        }
+        if binformat::switch::is_module(binary) {
+            return Self::parse_switch(path.as_ref(), backing, binary);
+        }
         let obj = ObjectFile::parse(binary)?;
 
         let path = path.as_ref().to_path_buf();
@@ -185,26 +382,35 @@ impl Processor {
 
         let mut syms = AddressMap::default();
         let mut sections = Vec::new();
+        let mut mappings = AddressMap::default();
+        let mut pointers = AddressMap::default();
+        let mut stubs = Vec::new();
         match &obj {
             object::File::MachO32(macho) => {
                 let debug_info = macho::MachoDebugInfo::parse(macho)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                pointers.extend(debug_info.pointers);
             }
             object::File::MachO64(macho) => {
                 let debug_info = macho::MachoDebugInfo::parse(macho)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                pointers.extend(debug_info.pointers);
             }
             object::File::Elf32(elf) => {
                 let debug_info = elf::ElfDebugInfo::parse(elf)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                mappings.extend(debug_info.mappings);
+                stubs.extend(debug_info.stubs);
             }
             object::File::Elf64(elf) => {
                 let debug_info = elf::ElfDebugInfo::parse(elf)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                mappings.extend(debug_info.mappings);
+                stubs.extend(debug_info.stubs);
             }
             object::File::Pe32(pe) => {
                 let debug_info = pe::PeDebugInfo::parse(pe)?;
@@ -219,14 +425,177 @@ impl Processor {
             _ => {}
         }
 
-        for section in sections.iter() {
+        for stub in stubs.iter() {
+            syms.push(Addressed {
+                addr: stub.addr,
+                item: RawSymbol { name: &stub.item, module: None }
+            });
+        }
+
+        if let Some(subsystem) = binformat::uefi::subsystem(&obj) {
+            log::complex!(
+                w "[processor::parse] object is a ",
+                g subsystem,
+                w ".",
+            );
+        }
+
+        // kernel images are usually stripped, so take the symbols from a separate map
+        let kallsyms = ARGS.kallsyms.as_ref().map(std::fs::read_to_string);
+        let kallsyms = kallsyms.transpose().map_err(Error::IO)?;
+        if let Some(ref map) = kallsyms {
+            // `_stext` is where `.text` starts, even if the image is stripped of its symbols.
+            let kernel_syms = binformat::kallsyms::parse(map, |name| {
+                syms.iter()
+                    .find(|sym| sym.item.name == name)
+                    .map(|sym| sym.addr)
+                    .or_else(|| match name {
+                        "_stext" => obj.section_by_name(".text").map(|s| s.address() as usize),
+                        _ => None,
+                    })
+            });
+            syms.extend(kernel_syms);
+        }
+
+        // Distributions ship their objects stripped, with the symbols and debug info in a
+        // separate file. It's mapped for as long as the processor is alive, like the object itself.
+        let debug_path = match ARGS.dsym {
+            Some(ref dsym) => debugvault::debuglink::dsym_file(dsym),
+            None => debugvault::debuglink::locate(&obj, &path),
+        };
+        let debug_obj = debug_path.and_then(|debug_path| {
+            backing.push(Backing::map(&debug_path).ok()?);
+            // SAFETY: the map is moved into the processor along with everything borrowing from it.
+            let data = unsafe { backing[backing.len() - 1].bytes() };
+            // A universal dSYM has the debug info of every architecture the object was built for.
+            let data = match obj.mach_uuid() {
+                Ok(Some(uuid)) => debugvault::debuglink::macho_slice(data, uuid)?,
+                _ => data,
+            };
+            let debug_obj = ObjectFile::parse(data).ok()?;
+            log::complex!(
+                w "[processor::parse] using debug info from ",
+                g debug_path.display().to_string(),
+                w ".",
+            );
+            Some(debug_obj)
+        });
+
+        if let (Some(dsym), None) = (&ARGS.dsym, &debug_obj) {
+            return Err(Error::MismatchedDsym(dsym.clone()));
+        }
+
+        match &debug_obj {
+            Some(object::File::MachO32(macho)) => {
+                syms.extend(macho::MachoDebugInfo::parse(macho)?.syms)
+            }
+            Some(object::File::MachO64(macho)) => {
+                syms.extend(macho::MachoDebugInfo::parse(macho)?.syms)
+            }
+            Some(object::File::Elf32(elf)) => syms.extend(elf::ElfDebugInfo::parse(elf)?.syms),
+            Some(object::File::Elf64(elf)) => syms.extend(elf::ElfDebugInfo::parse(elf)?.syms),
+            _ => {}
+        }
+
+        // Go keeps the names of its functions for the runtime, even in stripped objects.
+        let pclntab = binformat::golang::pclntab(&obj);
+        if let Some(ref pclntab) = pclntab {
+            log::complex!(
+                w "[processor::parse] found a Go ",
+                g pclntab.version,
+                w " function table.",
+            );
+
+            let named: HashSet<PhysAddr> = syms.iter().map(|sym| sym.addr).collect();
+            for func in pclntab.funcs.iter().filter(|func| !named.contains(&func.addr)) {
+                syms.push(Addressed {
+                    addr: func.addr,
+                    item: RawSymbol { name: func.item.name, module: None }
+                });
+            }
+        }
+
+        // Names the user gave to the functions take precedence over whatever the object or
+        // its debug info calls them.
+        let symbol_map = read_symbol_map()?;
+        if let Some(ref map) = symbol_map {
+            apply_symbol_map(&mut syms, map, |segment| {
+                obj.section_by_index(SectionIndex(segment)).ok().map(|section| section.address())
+            });
+        }
+
+        // Stripped objects still have unwind tables, that mark where each function starts.
+        let mut func_names = Vec::new();
+        if obj.symbol_table().is_none() && debug_obj.is_none() {
+            let named: HashSet<PhysAddr> = syms.iter().map(|sym| sym.addr).collect();
+            let starts = binformat::unwind::function_starts(&obj);
+            let starts: Vec<u64> =
+                starts.into_iter().filter(|addr| !named.contains(&(*addr as PhysAddr))).collect();
+
+            if !starts.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    g starts.len().to_string(),
+                    w " functions in the unwind table.",
+                );
+            }
+
+            func_names.extend(starts.into_iter().map(|addr| (addr, format!("func_{addr:x}"))));
+        }
+
+        for (addr, name) in func_names.iter() {
+            syms.push(Addressed {
+                addr: *addr as PhysAddr,
+                item: RawSymbol { name, module: None }
+            });
+        }
+
+        // Sections are only used as a name when nothing else starts there, e.g. stubs at the
+        // start of `.plt.got` keep their own name.
+        let named: HashSet<PhysAddr> = syms.iter().map(|sym| sym.addr).collect();
+        for section in sections.iter().filter(|section| !named.contains(&section.start)) {
             syms.push(Addressed {
                 addr: section.start,
                 item: RawSymbol { name: &section.name, module: None }
             });
         }
 
-        let index = Index::parse(&obj, &path, syms).map_err(Error::Debug)?;
+        let split = SplitDwarf {
+            build_dir: ARGS.build_dir.clone(),
+            dwp: ARGS.dwp.clone(),
+        };
+        let mut index =
+            Index::parse(&obj, debug_obj.as_ref(), &path, &split, syms).map_err(Error::Debug)?;
+
+        // Without DWARF, lines are taken from the function table of Go objects.
+        if let (Some(pclntab), true) = (pclntab, index.file_attrs.is_empty()) {
+            let mut paths: HashMap<&str, Arc<Path>> = HashMap::new();
+            for row in pclntab.lines {
+                let path = paths.entry(row.file).or_insert_with(|| Arc::from(Path::new(row.file)));
+                index.file_attrs.push(Addressed {
+                    addr: row.addr as PhysAddr,
+                    item: FileAttr {
+                        path: path.clone(),
+                        line: row.line as usize,
+                        column_start: 0,
+                        column_end: 0,
+                    },
+                });
+            }
+        }
+
+        // Makes searching the symbols of this object instantaneous next time. Symbols taken
+        // from a kernel or user map don't belong to the object, so those aren't cached.
+        let external = ARGS.kallsyms.is_some() || ARGS.symbol_map.is_some();
+        let key = cache::CacheKey::new(&obj, &path);
+        let cached = (!external).then(|| SymbolCache::store(&key, &obj, &index));
+        if let Some(Err(err)) = cached {
+            log::complex!(
+                w "[processor::parse] ",
+                y format!("Failed to cache symbols: {err}"),
+                w ".",
+            );
+        }
         let entrypoint = index.get_func_by_name("entry").unwrap_or(0);
 
         if entrypoint != 0 {
@@ -283,7 +652,55 @@ impl Processor {
             segments.push(segment);
         }
 
-        let arch = obj.architecture();
+        let module = Module {
+            path,
+            binary,
+            index,
+            entrypoint,
+            sections,
+            segments,
+            mappings,
+            arch: obj.architecture(),
+            endianness: obj.endianness(),
+            backing,
+        };
+
+        let abi = if obj.format() == BinaryFormat::Pe {
+            Abi::Win64
+        } else {
+            Abi::SysV
+        };
+
+        let mut processor = Self::from_module(module, now)?;
+        processor.arg_mismatches = processor.check_call_args(abi);
+        processor.relocs = processor.code_relocs(&obj);
+        processor.pointers = pointers;
+        processor.pointers.sort_unstable();
+        processor.aslr = ARGS.aslr_sim.then(|| Aslr::new(&obj));
+
+        // Analysis of part of the object, or with symbols from elsewhere, isn't reused.
+        let partial = ARGS.start_addr.is_some() || ARGS.stop_addr.is_some();
+        if !partial && ARGS.kallsyms.is_none() && ARGS.symbol_map.is_none() {
+            processor.cache_key = Some(key);
+        }
+        Ok(processor)
+    }
+
+    /// Decode all code sections of a module, regardless of the format it came from.
+    fn from_module(module: Module, now: std::time::Instant) -> Result<Self, Error> {
+        let Module {
+            path,
+            binary,
+            index,
+            entrypoint,
+            mut sections,
+            segments,
+            mappings,
+            arch,
+            endianness,
+            backing,
+        } = module;
+
         let (instruction_tokens, instruction_width) = unsafe {
             match arch {
                 Architecture::Riscv32 | Architecture::Riscv64 => (
@@ -318,6 +735,9 @@ impl Processor {
         let mut errors = AddressMap::default();
         let max_instruction_width;
 
+        // range of virtual addresses that get decoded
+        let window = ARGS.start_addr.unwrap_or(0)..ARGS.stop_addr.unwrap_or(usize::MAX);
+
         match arch {
             Architecture::Riscv32 => {
                 impl_recursion!(
@@ -325,8 +745,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    riscv::Decoder { is_64: false },
+                    |_| riscv::Decoder { is_64: false },
                     riscv
                 )
             }
@@ -336,8 +758,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    riscv::Decoder { is_64: true },
+                    |_| riscv::Decoder { is_64: true },
                     riscv
                 )
             }
@@ -347,8 +771,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    mips::Decoder::default(),
+                    |_| mips::Decoder::default(),
                     mips
                 )
             }
@@ -358,8 +784,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    x86::Decoder::default(),
+                    |_| x86::Decoder::default(),
                     x86
                 )
             }
@@ -369,8 +797,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    x64::Decoder::default(),
+                    |_| x64::Decoder::default(),
                     x64
                 )
             }
@@ -380,8 +810,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    armv7::Decoder::default(),
+                    |mapping| armv7::Decoder::default().with_thumb_mode(mapping == Mapping::Thumb),
                     armv7
                 )
             },
@@ -391,8 +823,10 @@ impl Processor {
                     &mut errors,
                     &mut instructions,
                     &mut sections,
+                    window,
+                    &mappings,
                     max_instruction_width,
-                    aarch64::Decoder::default(),
+                    |_| aarch64::Decoder::default(),
                     aarch64
                 )
             }
@@ -409,22 +843,51 @@ impl Processor {
             w format!("{path:?}.")
         );
 
-        Ok(Self {
+        let mut processor = Self {
             entrypoint,
             path,
             sections,
             segments,
             errors,
             instructions,
+            arg_mismatches: AddressMap::default(),
+            thunks: AddressMap::default(),
+            branch_hints: AddressMap::default(),
+            stack_strings: AddressMap::default(),
+            relocs: AddressMap::default(),
+            pointers: AddressMap::default(),
+            aslr: None,
+            source_lines: AddressMap::default(),
+            cache_key: None,
             index,
-            _file: file,
-            _mmap: mmap,
+            binary,
             max_instruction_width,
             instruction_tokens,
             instruction_width,
             arch,
-            endianness: obj.endianness(),
-        })
+            endianness,
+            backing,
+        };
+
+        processor.thunks = processor.find_thunks();
+        processor.branch_hints = processor.find_branch_hints();
+        if ARGS.decode_strings {
+            processor.stack_strings = processor.decode_stack_strings();
+        }
+        if ARGS.source {
+            processor.source_lines = processor.read_source_lines();
+        }
+        Ok(processor)
+    }
+
+    /// Parse an object the way it's described by the command line arguments, either as a
+    /// memory dump, raw machine code or a regular object.
+    pub fn parse_from_args<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        match (&ARGS.layout, &ARGS.arch) {
+            (Some(layout), _) => Self::parse_dump(path, layout),
+            (None, Some(arch)) if ARGS.raw => Self::parse_raw(path, arch, ARGS.base.unwrap_or(0)),
+            _ => Self::parse(path),
+        }
     }
 
     /// Relatively slow tokenization of an [`Instruction`].
@@ -437,6 +900,22 @@ impl Processor {
         (self.instruction_width)(instruction)
     }
 
+    /// One-line description of a mnemonic's semantics from the embedded instruction set reference.
+    pub fn instruction_description(&self, mnemonic: &str) -> Option<&'static str> {
+        match self.arch {
+            Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386 => {
+                x86_64::reference::describe(mnemonic)
+            }
+            Architecture::Arm => arm::reference::describe_armv7(mnemonic),
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+                arm::reference::describe_a64(mnemonic)
+            }
+            Architecture::Riscv32 | Architecture::Riscv64 => riscv::reference::describe(mnemonic),
+            Architecture::Mips | Architecture::Mips64 => mips::describe(mnemonic),
+            _ => None,
+        }
+    }
+
     pub fn error_by_addr(&self, addr: PhysAddr) -> Option<&decoder::Error> {
         match self.errors.search(addr) {
             Ok(idx) => Some(&self.errors[idx].item),
@@ -444,6 +923,67 @@ impl Processor {
         }
     }
 
+    pub fn arg_mismatch_by_addr(&self, addr: PhysAddr) -> Option<&ArgMismatch> {
+        match self.arg_mismatches.search(addr) {
+            Ok(idx) => Some(&self.arg_mismatches[idx].item),
+            Err(..) => None,
+        }
+    }
+
+    /// Relocations that patch code, only these are shown in the disassembly.
+    fn code_relocs(&self, obj: &ObjectFile) -> AddressMap<String> {
+        let mut relocs = AddressMap::default();
+        for reloc in binformat::relocs::relocations(obj) {
+            let in_code = self.sections().any(|section| {
+                section.name == reloc.section && section.kind == SectionKind::Code
+            });
+
+            if in_code && !reloc.target.is_empty() {
+                relocs.push(Addressed {
+                    addr: reloc.addr as PhysAddr,
+                    item: reloc.target,
+                });
+            }
+        }
+
+        relocs.sort_unstable();
+        relocs
+    }
+
+    /// Target of the first relocation that patches a field in `addr..addr + width`.
+    pub fn reloc_in(&self, addr: PhysAddr, width: usize) -> Option<&str> {
+        let idx = self.relocs.partition_point(|reloc| reloc.addr < addr);
+        self.relocs
+            .get(idx)
+            .filter(|reloc| reloc.addr < addr + width)
+            .map(|reloc| reloc.item.as_str())
+    }
+
+    /// Pointer in data at `addr` that's fixed up when the object is loaded.
+    pub fn pointer_by_addr(&self, addr: PhysAddr) -> Option<&macho::ChainedPointer> {
+        let idx = self.pointers.search(addr).ok()?;
+        Some(&self.pointers[idx].item)
+    }
+
+    /// Example load address of the object, if ASLR is simulated.
+    pub fn aslr(&self) -> Option<&Aslr> {
+        self.aslr.as_ref()
+    }
+
+    pub fn source_line_by_addr(&self, addr: PhysAddr) -> Option<&SourceLine> {
+        match self.source_lines.search(addr) {
+            Ok(idx) => Some(&self.source_lines[idx].item),
+            Err(..) => None,
+        }
+    }
+
+    pub fn stack_string_by_addr(&self, addr: PhysAddr) -> Option<&str> {
+        match self.stack_strings.search(addr) {
+            Ok(idx) => Some(&self.stack_strings[idx].item),
+            Err(..) => None,
+        }
+    }
+
     pub fn instruction_by_addr(&self, addr: PhysAddr) -> Option<&Instruction> {
         match self.instructions.search(addr) {
             Ok(idx) => Some(&self.instructions[idx].item),
@@ -451,6 +991,79 @@ impl Processor {
         }
     }
 
+    /// Contents of the file the processor was created from.
+    pub fn binary(&self) -> &[u8] {
+        self.binary
+    }
+
+    /// Analysis of the object stored by a previous run.
+    pub(crate) fn cached(&self, analysis: Analysis) -> Option<Vec<u64>> {
+        cache::load_analysis(self.cache_key.as_ref()?, analysis)
+    }
+
+    /// Store the analysis of the object, such that the next run can skip it.
+    pub(crate) fn cache(&self, analysis: Analysis, values: &[u64]) {
+        let Some(ref key) = self.cache_key else {
+            return;
+        };
+
+        if let Err(err) = cache::store_analysis(key, analysis, values) {
+            log::complex!(
+                w "[processor::cache] ",
+                y format!("Failed to cache {analysis:?}: {err}"),
+                w ".",
+            );
+        }
+    }
+
+    /// Target's instruction set.
+    pub fn arch(&self) -> Architecture {
+        self.arch
+    }
+
+    /// Target's endianness.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Machine code of the function starting at `addr`. The function is assumed to end at the
+    /// next symbol or the end of its section, without any trailing padding.
+    pub fn function_bytes(&self, addr: PhysAddr) -> Option<&[u8]> {
+        let section = self.section_by_addr(addr).filter(|s| s.kind == SectionKind::Code)?;
+
+        let next = match self.index.syms.search(addr) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx,
+        };
+
+        let mut end = self.index.syms[next..]
+            .iter()
+            .map(|sym| sym.addr)
+            .find(|&sym_addr| sym_addr > addr)
+            .map_or(section.end, |sym_addr| std::cmp::min(sym_addr, section.end));
+
+        // Strip padding between functions.
+        let mut idx = match self.instructions.search(end) {
+            Ok(idx) | Err(idx) => idx,
+        };
+
+        while idx > 0 {
+            idx -= 1;
+            let Addressed { addr: inst_addr, item } = &self.instructions[idx];
+            if *inst_addr < addr || *inst_addr + self.instruction_width(item) != end {
+                break;
+            }
+
+            let tokens = self.instruction_tokens(item, &self.index);
+            match tokens.first().map(|t| &t.text[..]) {
+                Some("nop" | "int3") => end = *inst_addr,
+                _ => break,
+            }
+        }
+
+        Some(section.bytes_by_addr(addr, end - addr))
+    }
+
     pub fn segments(&self) -> impl DoubleEndedIterator<Item = &Segment> {
         self.segments.iter()
     }
@@ -0,0 +1,280 @@
+//! How often every register is read and written by the operands of each function, such that
+//! register pressure and the registers a compiler prefers stand out.
+//!
+//! Only explicit operands are counted, the registers used implicitly, e.g. `rsp` by `push` or
+//! `rdx` by `div`, aren't. The registers addressing a memory operand are read.
+
+use crate::callsites::gpr_num;
+use crate::Processor;
+use processor_shared::{Addressed, PhysAddr};
+use std::collections::BTreeMap;
+use x86_64::long_mode::{register_class, Instruction, Opcode, Operand, RegSpec};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterUsage {
+    pub reads: usize,
+    pub writes: usize,
+}
+
+impl RegisterUsage {
+    pub fn total(&self) -> usize {
+        self.reads + self.writes
+    }
+}
+
+/// Usage by register name, where the partial general purpose registers like `eax` and `al`
+/// are counted as the full `rax`.
+pub type RegisterUsages = BTreeMap<&'static str, RegisterUsage>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+/// How the first operand is accessed.
+fn destination_access(inst: &Instruction) -> Access {
+    match inst.opcode() {
+        Opcode::CMP
+        | Opcode::TEST
+        | Opcode::BT
+        | Opcode::PUSH
+        | Opcode::CALL
+        | Opcode::JMP
+        | Opcode::COMISS
+        | Opcode::COMISD
+        | Opcode::UCOMISS
+        | Opcode::UCOMISD
+        | Opcode::PTEST
+        | Opcode::VPTEST => Access::Read,
+        Opcode::MOV
+        | Opcode::MOVZX
+        | Opcode::MOVSX
+        | Opcode::MOVSXD
+        | Opcode::MOVD
+        | Opcode::MOVQ
+        | Opcode::MOVAPS
+        | Opcode::MOVAPD
+        | Opcode::MOVUPS
+        | Opcode::MOVUPD
+        | Opcode::MOVDQA
+        | Opcode::MOVDQU
+        | Opcode::VMOVD
+        | Opcode::VMOVQ
+        | Opcode::VMOVAPS
+        | Opcode::VMOVAPD
+        | Opcode::VMOVUPS
+        | Opcode::VMOVUPD
+        | Opcode::VMOVDQA
+        | Opcode::VMOVDQU
+        | Opcode::VMOVDQA32
+        | Opcode::VMOVDQA64
+        | Opcode::VMOVDQU8
+        | Opcode::VMOVDQU16
+        | Opcode::VMOVDQU32
+        | Opcode::VMOVDQU64
+        | Opcode::LEA
+        | Opcode::POP
+        | Opcode::SETO
+        | Opcode::SETNO
+        | Opcode::SETB
+        | Opcode::SETAE
+        | Opcode::SETZ
+        | Opcode::SETNZ
+        | Opcode::SETBE
+        | Opcode::SETA
+        | Opcode::SETS
+        | Opcode::SETNS
+        | Opcode::SETP
+        | Opcode::SETNP
+        | Opcode::SETL
+        | Opcode::SETGE
+        | Opcode::SETLE
+        | Opcode::SETG => Access::Write,
+        // Double precision shifts have three operands, but still shift their destination.
+        Opcode::SHLD | Opcode::SHRD => Access::ReadWrite,
+        // Non-destructive forms such as `vaddps` and `imul r, r/m, imm` only write it, unlike
+        // fused multiply-adds which accumulate into it.
+        opcode if inst.operand_count() >= 3 => {
+            let name = opcode.to_string();
+            if name.starts_with("vfm") || name.starts_with("vfnm") {
+                Access::ReadWrite
+            } else {
+                Access::Write
+            }
+        }
+        _ => Access::ReadWrite,
+    }
+}
+
+/// Whether the instruction clears a register regardless of its value, e.g. `xor eax, eax`.
+fn is_zero_idiom(inst: &Instruction) -> bool {
+    let is_xor_or_sub = matches!(
+        inst.opcode(),
+        Opcode::XOR
+            | Opcode::SUB
+            | Opcode::PXOR
+            | Opcode::XORPS
+            | Opcode::XORPD
+            | Opcode::VPXOR
+            | Opcode::VPXORD
+            | Opcode::VPXORQ
+            | Opcode::VXORPS
+            | Opcode::VXORPD
+    );
+
+    let first = inst.operand(0);
+    is_xor_or_sub
+        && matches!(first, Operand::Register(_))
+        && (1..inst.operand_count()).all(|idx| inst.operand(idx) == first)
+}
+
+/// Registers an operand reads to address memory, and the one it names otherwise.
+fn operand_regs(operand: &Operand) -> (Option<RegSpec>, Vec<RegSpec>) {
+    match *operand {
+        Operand::Register(reg) => (Some(reg), Vec::new()),
+        Operand::RegisterMaskMerge(reg, mask, ..)
+        | Operand::RegisterMaskMergeSae(reg, mask, ..)
+        | Operand::RegisterMaskMergeSaeNoround(reg, mask, ..) => (Some(reg), vec![mask]),
+        Operand::RegDeref(reg)
+        | Operand::RegDisp(reg, _)
+        | Operand::RegScale(reg, _)
+        | Operand::RegScaleDisp(reg, ..) => (None, vec![reg]),
+        Operand::RegIndexBase(base, index)
+        | Operand::RegIndexBaseDisp(base, index, _)
+        | Operand::RegIndexBaseScale(base, index, _)
+        | Operand::RegIndexBaseScaleDisp(base, index, ..) => (None, vec![base, index]),
+        Operand::RegDerefMasked(reg, mask)
+        | Operand::RegDispMasked(reg, _, mask)
+        | Operand::RegScaleMasked(reg, _, mask)
+        | Operand::RegScaleDispMasked(reg, _, _, mask) => (None, vec![reg, mask]),
+        Operand::RegIndexBaseMasked(base, index, mask)
+        | Operand::RegIndexBaseDispMasked(base, index, _, mask)
+        | Operand::RegIndexBaseScaleMasked(base, index, _, mask)
+        | Operand::RegIndexBaseScaleDispMasked(base, index, _, _, mask) => {
+            (None, vec![base, index, mask])
+        }
+        _ => (None, Vec::new()),
+    }
+}
+
+/// Name a register is counted under, `None` for the instruction pointer.
+fn register_name(reg: RegSpec) -> Option<&'static str> {
+    if reg.class() == register_class::RIP || reg.class() == register_class::EIP {
+        return None;
+    }
+
+    Some(match gpr_num(reg) {
+        Some(num) => RegSpec::q(num).name(),
+        None => reg.name(),
+    })
+}
+
+fn record(usages: &mut RegisterUsages, inst: &Instruction) {
+    let mut count = |reg: RegSpec, access: Access| {
+        if let Some(name) = register_name(reg) {
+            let usage = usages.entry(name).or_default();
+            if access != Access::Write {
+                usage.reads += 1;
+            }
+            if access != Access::Read {
+                usage.writes += 1;
+            }
+        }
+    };
+
+    if is_zero_idiom(inst) {
+        if let Operand::Register(reg) = inst.operand(0) {
+            count(reg, Access::Write);
+        }
+        return;
+    }
+
+    for idx in 0..inst.operand_count() {
+        let (reg, address) = operand_regs(&inst.operand(idx));
+        if let Some(reg) = reg {
+            let access = if idx == 0 {
+                destination_access(inst)
+            } else {
+                Access::Read
+            };
+            count(reg, access);
+        }
+
+        for reg in address {
+            count(reg, Access::Read);
+        }
+    }
+}
+
+impl Processor {
+    /// Register reads and writes by the start of the function they're in (x86_64 only).
+    pub fn register_usage(&self) -> BTreeMap<PhysAddr, RegisterUsages> {
+        let mut functions: BTreeMap<PhysAddr, RegisterUsages> = BTreeMap::new();
+
+        if self.arch != object::Architecture::X86_64 {
+            return functions;
+        }
+
+        for Addressed { addr, item } in self.instructions.iter() {
+            if let Some(start) = self.function_start(*addr) {
+                let inst = unsafe { &*item.x64 };
+                record(functions.entry(start).or_default(), inst);
+            }
+        }
+
+        functions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decoder::InstructionStream;
+    use x86_64::long_mode::Decoder;
+
+    fn usages(code: &[u8]) -> RegisterUsages {
+        let mut usages = RegisterUsages::new();
+        for (_, inst) in InstructionStream::new(Decoder::default(), code, 0) {
+            record(&mut usages, &inst.unwrap());
+        }
+        usages
+    }
+
+    fn usage(reads: usize, writes: usize) -> RegisterUsage {
+        RegisterUsage { reads, writes }
+    }
+
+    #[test]
+    fn operands() {
+        // mov rcx, [rax + rbx * 8]; add ecx, edx; cmp rcx, 0
+        let code = [0x48, 0x8b, 0x0c, 0xd8, 0x01, 0xd1, 0x48, 0x83, 0xf9, 0x00];
+        let usages = usages(&code);
+
+        assert_eq!(usages["rax"], usage(1, 0));
+        assert_eq!(usages["rbx"], usage(1, 0));
+        // Partial registers are counted as the full register.
+        assert_eq!(usages["rcx"], usage(2, 2));
+        assert_eq!(usages["rdx"], usage(1, 0));
+    }
+
+    #[test]
+    fn zero_idiom() {
+        // xor eax, eax; sub ebx, ebx; xor ecx, edx
+        let code = [0x31, 0xc0, 0x29, 0xdb, 0x31, 0xd1];
+        let usages = usages(&code);
+
+        assert_eq!(usages["rax"], usage(0, 1));
+        assert_eq!(usages["rbx"], usage(0, 1));
+        assert_eq!(usages["rcx"], usage(1, 1));
+    }
+
+    #[test]
+    fn rip_relative() {
+        // lea rdi, [rip + 0x10]
+        let usages = usages(&[0x48, 0x8d, 0x3d, 0x10, 0x00, 0x00, 0x00]);
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages["rdi"], usage(0, 1));
+    }
+}
@@ -0,0 +1,114 @@
+//! Decoding of short byte sequences that aren't part of any object, similar to `rasm2 -d`, and
+//! assembling them for the few instructions that patches need.
+
+use crate::blocks::bytes_column;
+use crate::{parse_arch, Error};
+use debugvault::Index;
+use decoder::{Decodable, Decoded, InstructionStream};
+use object::Architecture;
+use processor_shared::PhysAddr;
+
+use arm::armv7;
+use arm::armv8::a64 as aarch64;
+use x86_64::long_mode as x64;
+use x86_64::protected_mode as x86;
+
+/// Width and text of each instruction, or of the error decoding it, together with the maximum
+/// width of an instruction.
+///
+/// Instructions are decoded in order, as each one's relative addresses depend on the one before
+/// it, but formatted across all threads.
+fn decode_all<D: Decodable>(
+    decoder: D,
+    bytes: &[u8],
+    base: PhysAddr,
+) -> (usize, Vec<(usize, String)>)
+where
+    D::Instruction: Clone + Send + Sync,
+{
+    let max_width = decoder.max_width();
+    let mut decoded: Vec<(usize, Result<D::Instruction, String>)> = Vec::new();
+
+    for (ip, inst) in InstructionStream::new(decoder, bytes, base) {
+        let offset = ip - base;
+        let (width, inst) = match inst {
+            Ok(inst) => (inst.width(), Ok(inst)),
+            Err(err) => (err.size(), Err(format!("<{:?}>", err.kind))),
+        };
+
+        decoded.push((std::cmp::min(width, bytes.len() - offset), inst));
+    }
+
+    let index = Index::default();
+    let mut insts = Vec::with_capacity(decoded.len());
+    debugvault::parallel_compute(decoded, &mut insts, |(width, inst)| {
+        let text = match inst {
+            Ok(inst) => inst.tokens(&index).iter().map(|t| &t.text[..]).collect::<String>(),
+            Err(err) => err.clone(),
+        };
+        (*width, text)
+    });
+
+    (max_width, insts)
+}
+
+fn decode(
+    arch: &str,
+    bytes: &[u8],
+    base: PhysAddr,
+) -> Result<(usize, Vec<(usize, String)>), Error> {
+    let decoded = match parse_arch(arch) {
+        Some(Architecture::I386) => decode_all(x86::Decoder::default(), bytes, base),
+        Some(Architecture::X86_64) => decode_all(x64::Decoder::default(), bytes, base),
+        Some(Architecture::Arm) => {
+            let thumb = arch.eq_ignore_ascii_case("thumb");
+            let decoder = armv7::Decoder::default().with_thumb_mode(thumb);
+            decode_all(decoder, bytes, base)
+        }
+        Some(Architecture::Aarch64) => decode_all(aarch64::Decoder::default(), bytes, base),
+        Some(Architecture::Riscv32) => decode_all(riscv::Decoder { is_64: false }, bytes, base),
+        Some(Architecture::Riscv64) => decode_all(riscv::Decoder { is_64: true }, bytes, base),
+        Some(Architecture::Mips | Architecture::Mips64) => {
+            decode_all(mips::Decoder, bytes, base)
+        }
+        Some(arch) => return Err(Error::UnknownArchitecture(arch)),
+        None => return Err(Error::InvalidArchitecture(arch.to_string())),
+    };
+
+    Ok(decoded)
+}
+
+/// Decode `bytes` as if they were loaded at `base`, returning the width and text of each
+/// instruction. Bytes that fail to decode are given as the error, e.g. `<InvalidOpcode>`.
+pub fn decode_bytes(
+    arch: &str,
+    bytes: &[u8],
+    base: PhysAddr,
+) -> Result<Vec<(usize, String)>, Error> {
+    decode(arch, bytes, base).map(|(_, insts)| insts)
+}
+
+/// Disassemble `bytes` as if they were loaded at `base`, returning one line per instruction.
+pub fn disassemble_bytes(arch: &str, bytes: &[u8], base: PhysAddr) -> Result<Vec<String>, Error> {
+    let (max_width, insts) = decode(arch, bytes, base)?;
+    let mut offset = 0;
+    let mut lines = Vec::new();
+
+    for (width, text) in insts {
+        let ip = base + offset;
+        let inst_bytes = bytes_column(&bytes[offset..][..width], max_width);
+        lines.push(format!("{ip:0>10X}  {inst_bytes}{text}"));
+        offset += width;
+    }
+
+    Ok(lines)
+}
+
+/// Assemble instructions separated by newlines or `;` as if they're placed at `addr`. Only
+/// a small subset of `x86_64` can be assembled for now.
+pub fn assemble(arch: Architecture, asm: &str, addr: PhysAddr) -> Result<Vec<u8>, String> {
+    match arch {
+        Architecture::X86_64 => x86_64::encode::assemble_all(asm, addr as u64),
+        arch => Err(format!("Instructions can't be assembled for {arch:?}.")),
+    }
+}
@@ -0,0 +1,60 @@
+//! Source lines interleaved with the instructions they were compiled from, like `objdump -S`.
+//!
+//! The line table of the debug info maps addresses to a file and line, only the rows where
+//! either of them changes are shown. Files that can't be read are skipped.
+
+use crate::Processor;
+use processor_shared::{AddressMap, Addressed};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct SourceLine {
+    /// Line number, starting at one.
+    pub line: usize,
+    /// Text of the line with tabs expanded.
+    pub text: String,
+}
+
+impl Processor {
+    /// Read the source lines of every row in the line table.
+    pub(crate) fn read_source_lines(&self) -> AddressMap<SourceLine> {
+        let mut files: HashMap<Arc<Path>, Option<Vec<String>>> = HashMap::new();
+        let mut lines: AddressMap<SourceLine> = AddressMap::default();
+        let mut prev: Option<(&Arc<Path>, usize)> = None;
+
+        for row in self.index.file_attrs.iter() {
+            let attr = &row.item;
+            if prev == Some((&attr.path, attr.line)) {
+                continue;
+            }
+            prev = Some((&attr.path, attr.line));
+
+            let file = files.entry(attr.path.clone()).or_insert_with(|| {
+                let src = std::fs::read_to_string(&attr.path).ok()?;
+                Some(src.lines().map(|line| line.trim_end().replace('\t', "    ")).collect())
+            });
+
+            let text = match file.as_ref().and_then(|file| file.get(attr.line.checked_sub(1)?)) {
+                Some(text) => text.clone(),
+                None => continue,
+            };
+
+            // Only the last row at an address is in effect.
+            if lines.last().is_some_and(|last| last.addr == row.addr) {
+                lines.pop();
+            }
+
+            lines.push(Addressed {
+                addr: row.addr,
+                item: SourceLine {
+                    line: attr.line,
+                    text,
+                },
+            });
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,312 @@
+//! Recovery of strings that are built at runtime instead of being stored in the object, a
+//! common way of hiding them from tools like `strings`.
+//!
+//! Straight-line code is emulated to track constants stored on the stack, either directly or
+//! through a register. Stores that are xor'ed with a constant key, byte by byte or in a loop,
+//! are decrypted. Whatever printable strings remain are attributed to their first store.
+
+use crate::callsites::gpr_num;
+use crate::Processor;
+use processor_shared::{AddressMap, Addressed, PhysAddr};
+use std::collections::BTreeMap;
+use x86_64::long_mode::{Instruction, Opcode, Operand, RegSpec};
+
+/// Strings shorter than this are too likely to be ordinary constants.
+const MIN_LEN: usize = 4;
+
+#[derive(Default)]
+struct Frame {
+    /// Bytes stored relative to a stack or frame pointer, and the instruction that stored them.
+    bytes: BTreeMap<(u8, i64), (u8, PhysAddr)>,
+    /// Offsets of stores that were decrypted in a loop.
+    decrypted: Vec<(u8, i64)>,
+    /// Known values of general purpose registers.
+    regs: [Option<u64>; 16],
+    /// Registers holding a byte loaded from a buffer, together with the key it's xor'ed with.
+    loaded: [Option<((u8, i64), u8)>; 16],
+    /// Registers pointing into the stack.
+    pointers: [Option<(u8, i64)>; 16],
+}
+
+/// Base register and displacement of a buffer on the stack that is indexed by a register.
+fn indexed_slot(operand: &Operand) -> Option<(u8, i64)> {
+    let (base, disp) = match *operand {
+        Operand::RegIndexBaseDisp(base, _, disp) => (base, disp),
+        Operand::RegIndexBaseScaleDisp(base, _, 1, disp) => (base, disp),
+        _ => return None,
+    };
+
+    if base == RegSpec::rsp() || base == RegSpec::rbp() {
+        Some((base.num(), disp as i64))
+    } else {
+        None
+    }
+}
+
+/// Value of an immediate operand, as it's stored in memory.
+fn immediate(operand: &Operand) -> Option<u64> {
+    Some(match *operand {
+        Operand::ImmediateI8(imm) => imm as i64 as u64,
+        Operand::ImmediateU8(imm) => imm as u64,
+        Operand::ImmediateI16(imm) => imm as i64 as u64,
+        Operand::ImmediateU16(imm) => imm as u64,
+        Operand::ImmediateI32(imm) => imm as i64 as u64,
+        Operand::ImmediateU32(imm) => imm as u64,
+        Operand::ImmediateI64(imm) => imm as u64,
+        Operand::ImmediateU64(imm) => imm,
+        _ => return None,
+    })
+}
+
+impl Frame {
+    /// Base register and displacement of a stack location.
+    fn stack_slot(&self, operand: &Operand) -> Option<(u8, i64)> {
+        let (base, disp) = match *operand {
+            Operand::RegDeref(base) => (base, 0),
+            Operand::RegDisp(base, disp) => (base, disp as i64),
+            _ => return None,
+        };
+
+        if base == RegSpec::rsp() || base == RegSpec::rbp() {
+            return Some((base.num(), disp));
+        }
+
+        let (base, offset) = self.pointers[gpr_num(base)? as usize]?;
+        Some((base, offset + disp))
+    }
+
+    fn value(&self, operand: &Operand) -> Option<u64> {
+        match *operand {
+            Operand::Register(reg) => self.regs[gpr_num(reg)? as usize],
+            _ => immediate(operand),
+        }
+    }
+
+    fn store(&mut self, (base, disp): (u8, i64), value: u64, size: u8, addr: PhysAddr) {
+        for (idx, byte) in value.to_le_bytes().into_iter().take(size as usize).enumerate() {
+            self.bytes.insert((base, disp + idx as i64), (byte, addr));
+        }
+    }
+
+    fn xor(&mut self, (base, disp): (u8, i64), key: u64, size: u8) {
+        for (idx, key) in key.to_le_bytes().into_iter().take(size as usize).enumerate() {
+            if let Some((byte, _)) = self.bytes.get_mut(&(base, disp + idx as i64)) {
+                *byte ^= key;
+            }
+        }
+    }
+
+    /// Xor the stores starting at `disp` up to a terminator, as done by a decryption loop.
+    fn xor_run(&mut self, (base, disp): (u8, i64), key: u8) {
+        let mut offset = disp;
+        while let Some((byte, _)) = self.bytes.get_mut(&(base, offset)) {
+            if *byte == 0 {
+                break;
+            }
+            *byte ^= key;
+            offset += 1;
+        }
+        self.decrypted.push((base, disp));
+    }
+
+    fn execute(&mut self, inst: &Instruction, addr: PhysAddr) {
+        let size = inst.mem_size().and_then(|size| size.bytes_size()).unwrap_or(0);
+
+        if inst.operand_count() != 2 {
+            if inst.operand_count() > 0 {
+                if let Operand::Register(reg) = inst.operand(0) {
+                    self.clobber(reg);
+                }
+            }
+            return;
+        }
+
+        let (dst, src) = (inst.operand(0), inst.operand(1));
+
+        if let Some(slot) = self.stack_slot(&dst) {
+            match (inst.opcode(), self.value(&src)) {
+                (Opcode::MOV, Some(value)) => self.store(slot, value, size, addr),
+                (Opcode::XOR, Some(key)) => self.xor(slot, key, size),
+                (Opcode::CMP | Opcode::TEST, _) => {}
+                _ => self.forget(slot, size),
+            }
+            return;
+        }
+
+        if let Some(slot) = indexed_slot(&dst) {
+            // Decryption loops either xor a buffer in place, or store a byte they loaded and
+            // xor'ed in a register.
+            let key = match (inst.opcode(), &src) {
+                (Opcode::XOR, _) => immediate(&src).map(|key| key as u8),
+                (Opcode::MOV, &Operand::Register(reg)) => gpr_num(reg)
+                    .and_then(|num| self.loaded[num as usize])
+                    .filter(|(loaded, _)| *loaded == slot)
+                    .map(|(_, key)| key),
+                _ => None,
+            };
+
+            if let (Some(key @ 1..), 1) = (key, size) {
+                self.xor_run(slot, key);
+            }
+            return;
+        }
+
+        let reg = match dst {
+            Operand::Register(reg) => reg,
+            _ => return,
+        };
+
+        let num = match gpr_num(reg) {
+            Some(num) => num as usize,
+            None => return,
+        };
+
+        match inst.opcode() {
+            Opcode::LEA => {
+                self.clobber(reg);
+                self.pointers[num] = self.stack_slot(&src);
+            }
+            Opcode::ADD | Opcode::SUB if self.pointers[num].is_some() => {
+                let offset = immediate(&src).map(|imm| imm as i64);
+                let offset = if inst.opcode() == Opcode::SUB { offset.map(|o| -o) } else { offset };
+                self.pointers[num] = self.pointers[num]
+                    .zip(offset)
+                    .map(|((base, disp), offset)| (base, disp + offset));
+            }
+            Opcode::MOV | Opcode::MOVZX | Opcode::MOVSX => {
+                self.regs[num] = self.value(&src).and_then(|val| match reg.width() {
+                    // Writes to a 32-bit register zero the upper half.
+                    4 => Some(val as u32 as u64),
+                    8 => Some(val),
+                    _ => None,
+                });
+
+                self.loaded[num] = match src {
+                    Operand::Register(src) => gpr_num(src).and_then(|n| self.loaded[n as usize]),
+                    _ => indexed_slot(&src).filter(|_| size == 1).map(|slot| (slot, 0)),
+                };
+
+                self.pointers[num] = match src {
+                    Operand::Register(src) => gpr_num(src).and_then(|n| self.pointers[n as usize]),
+                    _ => None,
+                };
+            }
+            Opcode::XOR => {
+                self.regs[num] = None;
+                self.loaded[num] = match (self.loaded[num], immediate(&src)) {
+                    (Some((slot, key)), Some(imm)) => Some((slot, key ^ imm as u8)),
+                    _ => None,
+                };
+            }
+            Opcode::CMP | Opcode::TEST => {}
+            _ => self.clobber(reg),
+        }
+    }
+
+    fn clobber(&mut self, reg: RegSpec) {
+        if let Some(num) = gpr_num(reg) {
+            self.regs[num as usize] = None;
+            self.loaded[num as usize] = None;
+            self.pointers[num as usize] = None;
+        }
+    }
+
+    fn forget(&mut self, (base, disp): (u8, i64), size: u8) {
+        for idx in 0..size as i64 {
+            self.bytes.remove(&(base, disp + idx));
+        }
+    }
+
+    /// Printable strings in the stored bytes, attributed to the first store.
+    fn strings(&mut self, strings: &mut AddressMap<String>) {
+        let bytes = std::mem::take(&mut self.bytes);
+        let decrypted = std::mem::take(&mut self.decrypted);
+        self.regs = [None; 16];
+        self.loaded = [None; 16];
+        self.pointers = [None; 16];
+
+        let mut run: Vec<(u8, PhysAddr)> = Vec::new();
+        let mut prev: Option<(u8, i64)> = None;
+        let mut start = (0, 0);
+
+        let mut flush = |run: &mut Vec<(u8, PhysAddr)>, start: (u8, i64)| {
+            let len = run.iter().position(|(byte, _)| *byte == 0).unwrap_or(run.len());
+            let text = &run[..len];
+
+            let mut sites: Vec<PhysAddr> = text.iter().map(|(_, addr)| *addr).collect();
+            sites.sort_unstable();
+            sites.dedup();
+
+            let printable =
+                text.iter().all(|(b, _)| b.is_ascii_graphic() || b" \t\r\n".contains(b));
+            let built = sites.len() > 1 || decrypted.contains(&start);
+            if len >= MIN_LEN && printable && built {
+                strings.push(Addressed {
+                    addr: sites[0],
+                    item: text.iter().map(|(byte, _)| *byte as char).collect(),
+                });
+            }
+
+            run.clear();
+        };
+
+        for ((base, offset), byte) in bytes {
+            if prev != Some((base, offset - 1)) {
+                flush(&mut run, start);
+                start = (base, offset);
+            }
+
+            run.push(byte);
+            prev = Some((base, offset));
+        }
+
+        flush(&mut run, start);
+    }
+}
+
+impl Processor {
+    /// Recover strings constructed on the stack (x86_64 only).
+    pub(crate) fn decode_stack_strings(&self) -> AddressMap<String> {
+        let mut strings = AddressMap::default();
+
+        if self.arch != object::Architecture::X86_64 {
+            return strings;
+        }
+
+        let mut frame = Frame::default();
+        let mut next_addr = 0;
+
+        for Addressed { addr, item } in self.instructions.iter() {
+            let inst = unsafe { &*item.x64 };
+
+            // Only straight-line code within a function is emulated.
+            if *addr != next_addr || self.index.get_sym_by_addr(*addr).is_some() {
+                frame.strings(&mut strings);
+            }
+
+            frame.execute(inst, *addr);
+            next_addr = *addr + self.instruction_width(item);
+
+            match inst.opcode() {
+                // Decryption loops are usually entered with a jump, so those don't end it.
+                Opcode::CALL | Opcode::RETURN | Opcode::UD2 | Opcode::HLT => {
+                    frame.strings(&mut strings)
+                }
+                _ => {}
+            }
+        }
+
+        frame.strings(&mut strings);
+        strings.sort_unstable();
+
+        if !strings.is_empty() {
+            log::complex!(
+                w "[processor::decode_stack_strings] found ",
+                y strings.len().to_string(),
+                w " strings constructed at runtime.",
+            );
+        }
+
+        strings
+    }
+}
@@ -0,0 +1,181 @@
+//! Compiler generated thunks, such that calls through them can be attributed to the function
+//! that actually gets called.
+//!
+//! Recognized are retpolines (`__x86_indirect_thunk_rax`), import thunks that jump through an
+//! import slot and trampolines that only tail-call another function.
+
+use crate::{Instruction, Processor};
+use processor_shared::{AddressMap, Addressed, PhysAddr};
+use x86_64::long_mode::{self as x64, Opcode, Operand, RegSpec};
+
+/// How many thunks are followed before giving up, in case they form a cycle.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Thunk {
+    /// Jumps straight to another function or import.
+    Jump(PhysAddr),
+    /// Retpoline that calls the function pointed to by a register.
+    Indirect(String),
+}
+
+/// Where a function starting at `addr` with the instructions `insts` jumps to, if it does nothing
+/// else. `is_import` tells whether a slot is known to hold an import.
+fn jump_target<'a>(
+    addr: PhysAddr,
+    mut insts: impl Iterator<Item = (PhysAddr, usize, &'a x64::Instruction)>,
+    is_import: impl Fn(PhysAddr) -> bool,
+) -> Option<PhysAddr> {
+    let (_, width, mut inst) = insts.next()?;
+
+    // Indirect branch tracking landing pad.
+    if inst.opcode() == Opcode::ENDBR64 || inst.opcode() == Opcode::ENDBR32 {
+        let (next, _, next_inst) = insts.next()?;
+        if next != addr + width {
+            return None;
+        }
+        inst = next_inst;
+    }
+
+    if inst.opcode() != Opcode::JMP || inst.operand_count() != 1 {
+        return None;
+    }
+
+    let target = inst.rel_addr()?;
+    match inst.operand(0) {
+        // Tail call trampoline.
+        Operand::ImmediateI8(_) | Operand::ImmediateI32(_) if target != addr => Some(target),
+        // Import thunk, only if the slot is known to hold an import.
+        Operand::RegDisp(RegSpec::RIP, _) if is_import(target) => Some(target),
+        _ => None,
+    }
+}
+
+impl Processor {
+    /// Find all functions that are thunks (x86_64 only).
+    pub(crate) fn find_thunks(&self) -> AddressMap<Thunk> {
+        let mut thunks = AddressMap::default();
+
+        if self.arch != object::Architecture::X86_64 {
+            return thunks;
+        }
+
+        for Addressed { addr, item: symbol } in self.index.syms.iter() {
+            if let Some(reg) = symbol.as_str().strip_prefix("__x86_indirect_thunk_") {
+                thunks.push(Addressed {
+                    addr: *addr,
+                    item: Thunk::Indirect(reg.to_string()),
+                });
+                continue;
+            }
+
+            if let Some(target) = self.thunk_target(*addr) {
+                thunks.push(Addressed {
+                    addr: *addr,
+                    item: Thunk::Jump(target),
+                });
+            }
+        }
+
+        thunks.dedup_by_key(|thunk| thunk.addr);
+
+        if !thunks.is_empty() {
+            log::complex!(
+                w "[processor::find_thunks] found ",
+                y thunks.len().to_string(),
+                w " thunks.",
+            );
+        }
+
+        thunks
+    }
+
+    /// Where the function at `addr` jumps to, if it does nothing else.
+    fn thunk_target(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let idx = self.instructions.search(addr).ok()?;
+        let insts = self.instructions[idx..].iter().map(|Addressed { addr, item }| {
+            (*addr, self.instruction_width(item), unsafe { &*item.x64 })
+        });
+
+        jump_target(addr, insts, |slot| self.index.get_sym_by_addr(slot).is_some())
+    }
+
+    pub fn thunk_by_addr(&self, addr: PhysAddr) -> Option<&Thunk> {
+        match self.thunks.search(addr) {
+            Ok(idx) => Some(&self.thunks[idx].item),
+            Err(..) => None,
+        }
+    }
+
+    /// Follow the thunks starting at `addr` to the function that is eventually called.
+    pub fn resolve_thunk(&self, mut addr: PhysAddr) -> PhysAddr {
+        for _ in 0..MAX_DEPTH {
+            match self.thunk_by_addr(addr) {
+                Some(Thunk::Jump(target)) => addr = *target,
+                _ => break,
+            }
+        }
+
+        addr
+    }
+
+    /// Target of a direct call or jump, or the import slot of an indirect one.
+    pub fn branch_target(&self, inst: &Instruction) -> Option<PhysAddr> {
+        if self.arch != object::Architecture::X86_64 {
+            return None;
+        }
+
+        let inst = unsafe { &*inst.x64 };
+        match inst.opcode() {
+            Opcode::CALL | Opcode::JMP => inst.rel_addr(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decoder::InstructionStream;
+
+    fn target(code: &[u8], imports: &[PhysAddr]) -> Option<PhysAddr> {
+        let insts: Vec<(PhysAddr, usize, x64::Instruction)> =
+            InstructionStream::new(x64::Decoder::default(), code, 0x1000)
+                .map(|(addr, inst)| {
+                    let inst = inst.unwrap();
+                    (addr, decoder::Decoded::width(&inst), inst)
+                })
+                .collect();
+
+        let insts = insts.iter().map(|(addr, width, inst)| (*addr, *width, inst));
+        jump_target(0x1000, insts, |slot| imports.contains(&slot))
+    }
+
+    #[test]
+    fn trampoline() {
+        // jmp 0x1100
+        assert_eq!(target(&[0xe9, 0xfb, 0x00, 0x00, 0x00], &[]), Some(0x1100));
+        // endbr64; jmp 0x1100
+        let code = [0xf3, 0x0f, 0x1e, 0xfa, 0xe9, 0xf7, 0x00, 0x00, 0x00];
+        assert_eq!(target(&code, &[]), Some(0x1100));
+        // jmp to itself is a loop and not a thunk.
+        assert_eq!(target(&[0xeb, 0xfe], &[]), None);
+    }
+
+    #[test]
+    fn import_thunk() {
+        // jmp qword ptr [rip + 0x2000]
+        let code = [0xff, 0x25, 0x00, 0x20, 0x00, 0x00];
+        assert_eq!(target(&code, &[0x3006]), Some(0x3006));
+        // Slots that aren't known to hold an import could be anything.
+        assert_eq!(target(&code, &[]), None);
+    }
+
+    #[test]
+    fn not_a_thunk() {
+        // push rbp; jmp 0x1100
+        assert_eq!(target(&[0x55, 0xe9, 0xfa, 0x00, 0x00, 0x00], &[]), None);
+        // jmp rax
+        assert_eq!(target(&[0xff, 0xe0], &[]), None);
+    }
+}
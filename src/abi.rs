@@ -0,0 +1,176 @@
+//! Comparison of the exported symbols of two versions of a library, for gating releases on
+//! backwards compatibility.
+
+use binformat::exports::{exports, Export};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Versions of each mangled symbol, the version is `None` for unversioned symbols.
+type Overloads = BTreeMap<String, BTreeSet<Option<String>>>;
+
+/// Characters an operator's name can consist of, e.g. `<<=` of `operator<<=`.
+const OPERATOR: &str = "<>=-+*/%^&|~!,[]";
+
+/// Qualified name of a demangled function without its return type, calling convention and
+/// parameters, e.g. `ns::Foo::bar` of `public: void __cdecl ns::Foo::bar(int)`.
+fn qualified(demangled: &str) -> &str {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut end = None;
+    let mut operator = false;
+    let mut idx = 0;
+
+    while idx < demangled.len() {
+        let rest = &demangled[idx..];
+        let boundary = demangled[..idx].ends_with([' ', ':']) || idx == 0;
+        if let Some(op) = rest.strip_prefix("operator").filter(|_| depth == 0 && boundary) {
+            let len = if op.starts_with("()") {
+                2
+            } else {
+                op.len() - op.trim_start_matches(|c| OPERATOR.contains(c)).len()
+            };
+            // Conversion operators contain spaces, e.g. `operator unsigned int`.
+            operator = true;
+            idx += "operator".len() + len;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        match c {
+            '(' if depth == 0 => {
+                end = end.or(Some(idx));
+                depth += 1;
+            }
+            '(' | '<' | '[' | '`' => depth += 1,
+            ')' | '>' | ']' | '\'' => depth = depth.saturating_sub(1),
+            // Parentheses that are part of a path, like `(anonymous namespace)::`.
+            ':' if depth == 0 => end = None,
+            ' ' if depth == 0 && end.is_none() && !operator => start = idx + 1,
+            _ => {}
+        }
+        idx += c.len_utf8();
+    }
+
+    &demangled[start..end.unwrap_or(demangled.len())]
+}
+
+/// Exports grouped by their qualified name, such that the overloads of a function share a group
+/// in every scheme. The mangled name encodes the parameter list.
+fn exported_functions(path: &Path) -> Result<BTreeMap<String, Overloads>, String> {
+    let data = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = object::File::parse(&data[..])
+        .map_err(|err| format!("Failed to parse {path:?}: {err}."))?;
+
+    let mut funcs: BTreeMap<String, Overloads> = BTreeMap::new();
+    for Export { name, version } in exports(&obj) {
+        funcs
+            .entry(qualified(&debugvault::demangle(name)).to_string())
+            .or_default()
+            .entry(name.to_string())
+            .or_default()
+            .insert(version.map(str::to_string));
+    }
+
+    Ok(funcs)
+}
+
+fn versioned(name: &str, version: &Option<String>) -> String {
+    match version {
+        Some(version) => format!("{name}@{version}"),
+        None => name.to_string(),
+    }
+}
+
+fn print_section(title: &str, symbols: &[String]) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    println!("{title} ({}):", symbols.len());
+    for symbol in symbols {
+        println!("  {symbol}");
+    }
+    println!();
+}
+
+/// Compare the exports of `old` and `new`, returns whether `new` is backwards compatible.
+pub fn check(old: &Path, new: &Path) -> Result<bool, String> {
+    let old = exported_functions(old)?;
+    let new = exported_functions(new)?;
+
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    let mut versions = Vec::new();
+    let mut added = Vec::new();
+
+    for (name, old_overloads) in old.iter() {
+        let new_overloads = match new.get(name) {
+            Some(new_overloads) => new_overloads,
+            None => {
+                removed.push(name.clone());
+                continue;
+            }
+        };
+
+        for (mangled, old_versions) in old_overloads {
+            let new_versions = match new_overloads.get(mangled) {
+                Some(new_versions) => new_versions,
+                None => {
+                    let now = new_overloads.keys().map(|name| debugvault::demangle(name));
+                    let now = now.collect::<Vec<_>>().join(", ");
+                    changed.push(format!("{} -> {now}", debugvault::demangle(mangled)));
+                    continue;
+                }
+            };
+
+            // Adding versions is backwards compatible, removing them isn't.
+            for version in old_versions.difference(new_versions) {
+                let now = new_versions.iter().map(|v| versioned(mangled, v));
+                let now = now.collect::<Vec<_>>().join(", ");
+                versions.push(format!("{} -> {now}", versioned(mangled, version)));
+            }
+        }
+    }
+
+    for name in new.keys() {
+        if !old.contains_key(name) {
+            added.push(name.clone());
+        }
+    }
+
+    print_section("Removed", &removed);
+    print_section("Changed signature", &changed);
+    print_section("Changed version", &versions);
+    print_section("Added", &added);
+
+    let compatible = removed.is_empty() && changed.is_empty() && versions.is_empty();
+    println!("ABI check {}.", if compatible { "passed" } else { "failed" });
+    Ok(compatible)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::qualified;
+
+    #[test]
+    fn overloads() {
+        let name = |mangled| qualified(&debugvault::demangle(mangled)).to_string();
+        assert_eq!(name("?x@klass@@YAXMH@Z"), "klass::x");
+        assert_eq!(name("?x@klass@@YAXH@Z"), "klass::x");
+        assert_eq!(name("??0klass@@QEAA@XZ"), "klass::klass");
+        assert_eq!(name("_ZN3foo3barEi"), "foo::bar");
+        assert_eq!(name("_ZN3foo3barEv"), "foo::bar");
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(
+            qualified("public: __cdecl name0::operator unsigned int(void)"),
+            "name0::operator unsigned int"
+        );
+        assert_eq!(qualified("bool operator<(foo const&, foo const&)"), "operator<");
+        assert_eq!(qualified("foo::operator()(int) const"), "foo::operator()");
+        assert_eq!(qualified("(anonymous namespace)::foo(int)"), "(anonymous namespace)::foo");
+        assert_eq!(qualified("`anonymous namespace'::foo"), "`anonymous namespace'::foo");
+    }
+}
@@ -0,0 +1,343 @@
+//! What takes up the code of an object, in the spirit of `cargo bloat`.
+//!
+//! Every byte of the executable sections is attributed to the function it's in, and functions
+//! are grouped by the crate of Rust functions or the top-level namespace of C++ functions.
+
+use commands::{Cli, Subcommand};
+use debugvault::Scheme;
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Number of functions and groups listed by default.
+const DEFAULT_TOP: usize = 20;
+
+struct Function {
+    addr: u64,
+    size: u64,
+    name: String,
+    group: String,
+}
+
+/// Start of a path, skipping the `<` of a qualified path like `<alloc::vec::Vec<T> as Drop>`.
+fn rust_crate(name: &str) -> Option<&str> {
+    let name = name.trim_start_matches('<');
+    let (krate, _) = name.split_once("::")?;
+    Some(krate)
+}
+
+/// Outermost namespace or class of a C++ name, skipping a template's return type.
+fn cpp_namespace(name: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut component = 0;
+    let mut namespace = None;
+    let bytes = name.as_bytes();
+
+    for (idx, &b) in bytes.iter().enumerate() {
+        match b {
+            // The parameters follow the name, unless a component starts with parentheses like
+            // `(anonymous namespace)` does.
+            b'(' if depth == 0 && idx > component => break,
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+            // Everything before was the return type.
+            b' ' if depth == 0 => {
+                start = idx + 1;
+                component = idx + 1;
+                namespace = None;
+            }
+            b':' if depth == 0 && bytes.get(idx + 1) == Some(&b':') => {
+                namespace = namespace.or(Some(&name[start..idx]));
+                component = idx + 2;
+                // Operators such as `operator delete` have spaces in their name.
+                if name[component..].starts_with("operator") {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    namespace
+}
+
+fn group_of(scheme: Option<Scheme>, name: &str) -> String {
+    let group = match scheme {
+        Some(Scheme::RustLegacy | Scheme::RustV0) => rust_crate(name),
+        Some(Scheme::Itanium | Scheme::Msvc) => cpp_namespace(name),
+        _ => return "[unmangled]".to_string(),
+    };
+
+    group.unwrap_or("[global]").to_string()
+}
+
+fn functions(obj: &object::File) -> Vec<Function> {
+    let mut syms: Vec<(u64, u64, &str)> = obj
+        .symbols()
+        .chain(obj.dynamic_symbols())
+        .filter(|sym| sym.kind() == SymbolKind::Text && !sym.is_undefined())
+        .filter_map(|sym| Some((sym.address(), sym.size(), sym.name().ok()?)))
+        .filter(|(_, _, name)| !name.is_empty())
+        .collect();
+
+    // Aliases of a function are only counted once.
+    syms.sort_by_key(|(addr, size, _)| (*addr, std::cmp::Reverse(*size)));
+    syms.dedup_by_key(|(addr, ..)| *addr);
+
+    let names: Vec<&str> = syms.iter().map(|(_, _, name)| *name).collect();
+    let demangled = debugvault::demangle_batch(&names);
+
+    let sections: Vec<(u64, u64)> = obj
+        .sections()
+        .filter(|section| section.kind() == SectionKind::Text)
+        .map(|section| (section.address(), section.address() + section.size()))
+        .collect();
+
+    let mut functions = Vec::with_capacity(syms.len());
+    for (idx, ((addr, size, name), demangled)) in syms.iter().zip(demangled).enumerate() {
+        let Some(&(_, section_end)) =
+            sections.iter().find(|(start, end)| (*start..*end).contains(addr))
+        else {
+            continue;
+        };
+
+        // Symbols without a size, like those of Mach-O, span up to the next one.
+        let size = match *size {
+            0 => match syms.get(idx + 1) {
+                Some(&(next, ..)) => next.min(section_end) - addr,
+                None => section_end - addr,
+            },
+            size => size,
+        };
+
+        let (scheme, name) = match demangled {
+            Ok(demangled) => (Some(demangled.scheme), demangled.name),
+            Err(_) => (None, name.to_string()),
+        };
+
+        functions.push(Function {
+            addr: *addr,
+            size,
+            group: group_of(scheme, &name),
+            name,
+        });
+    }
+
+    functions
+}
+
+fn print_row(size: u64, total: u64, name: &str) {
+    let percentage = size as f64 * 100.0 / total.max(1) as f64;
+    println!("{percentage:>6.2}%  {size:>10}  {name}");
+}
+
+/// Functions of the object at `path` and the size of its executable sections.
+fn load(path: &Path) -> Result<(Vec<Function>, u64), String> {
+    let binary = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = object::File::parse(&binary[..])
+        .map_err(|_| format!("{path:?} doesn't have a header to read the symbols of."))?;
+
+    let text: u64 = obj
+        .sections()
+        .filter(|section| section.kind() == SectionKind::Text)
+        .map(|section| section.size())
+        .sum();
+
+    let functions = functions(&obj);
+    if functions.is_empty() {
+        return Err(format!("{path:?} doesn't have any function symbols."));
+    }
+
+    Ok((functions, text))
+}
+
+/// Print the largest groups and functions of the object given by `bite bloat`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Bloat { object: ref path, top } = args.command else {
+        return Ok(());
+    };
+
+    let (mut functions, text) = load(path)?;
+    let top = top.unwrap_or(DEFAULT_TOP);
+    let attributed: u64 = functions.iter().map(|func| func.size).sum();
+
+    let mut groups: HashMap<&str, u64> = HashMap::new();
+    for func in functions.iter() {
+        *groups.entry(&func.group).or_default() += func.size;
+    }
+
+    let mut groups: Vec<(&str, u64)> = groups.into_iter().collect();
+    groups.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then(a_name.cmp(b_name)));
+
+    println!("  code%       bytes  crate or namespace");
+    for &(name, size) in groups.iter().take(top) {
+        print_row(size, text, name);
+    }
+    if groups.len() > top {
+        let rest = groups[top..].iter().map(|(_, size)| size).sum();
+        print_row(rest, text, &format!("and {} more", groups.len() - top));
+    }
+
+    functions.sort_by(|a, b| b.size.cmp(&a.size).then(a.addr.cmp(&b.addr)));
+
+    println!();
+    println!("  code%       bytes  function");
+    for func in functions.iter().take(top) {
+        print_row(func.size, text, &func.name);
+    }
+    if functions.len() > top {
+        let rest = functions[top..].iter().map(|func| func.size).sum();
+        print_row(rest, text, &format!("and {} more", functions.len() - top));
+    }
+
+    println!();
+    print_row(
+        attributed,
+        text,
+        &format!("in {} functions", functions.len()),
+    );
+    print_row(
+        text.saturating_sub(attributed),
+        text,
+        "not covered by a symbol",
+    );
+    print_row(text, text, "in the executable sections");
+
+    Ok(())
+}
+
+/// Sizes in the old and new object by name.
+type Sizes<'a> = HashMap<&'a str, (u64, u64)>;
+
+fn print_changes(changes: Sizes, top: usize, what: &str) {
+    let mut changes: Vec<(&str, (u64, u64))> =
+        changes.into_iter().filter(|(_, (old, new))| old != new).collect();
+
+    // Largest growth first.
+    let delta = |&(old, new): &(u64, u64)| new as i64 - old as i64;
+    changes.sort_by(|(a_name, a), (b_name, b)| delta(b).cmp(&delta(a)).then(a_name.cmp(b_name)));
+
+    println!("       old         new       delta  {what}");
+    for (name, sizes) in changes.iter().take(top) {
+        println!(
+            "{:>10}  {:>10}  {:>+10}  {name}",
+            sizes.0,
+            sizes.1,
+            delta(sizes)
+        );
+    }
+
+    if changes.len() > top {
+        let (old, new) = changes[top..].iter().fold((0, 0), |(old, new), (_, sizes)| {
+            (old + sizes.0, new + sizes.1)
+        });
+        let more = format!("and {} more changed", changes.len() - top);
+        println!("{old:>10}  {new:>10}  {:>+10}  {more}", delta(&(old, new)));
+    }
+}
+
+/// Print how the size of the groups and functions changed between the objects given by
+/// `bite bloat-diff`, largest growth first.
+pub fn diff(args: &Cli) -> Result<(), String> {
+    let Subcommand::BloatDiff { old: ref old_path, new: ref new_path, top } = args.command else {
+        return Ok(());
+    };
+
+    let (old, old_text) = load(old_path)?;
+    let (new, new_text) = load(new_path)?;
+    let top = top.unwrap_or(DEFAULT_TOP);
+
+    // Functions are matched by name, which for Rust no longer includes the hash that changes
+    // between builds. Functions sharing a name are added up.
+    let mut groups: Sizes = HashMap::new();
+    let mut functions: Sizes = HashMap::new();
+    for func in old.iter() {
+        groups.entry(&func.group).or_default().0 += func.size;
+        functions.entry(&func.name).or_default().0 += func.size;
+    }
+    for func in new.iter() {
+        groups.entry(&func.group).or_default().1 += func.size;
+        functions.entry(&func.name).or_default().1 += func.size;
+    }
+
+    print_changes(groups, top, "crate or namespace");
+    println!();
+    print_changes(functions, top, "function");
+
+    println!();
+    let delta = new_text as i64 - old_text as i64;
+    println!("{old_text:>10}  {new_text:>10}  {delta:>+10}  in the executable sections");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::{self, StandardSection, Symbol, SymbolSection};
+    use object::{Architecture, BinaryFormat, Endianness, SymbolFlags, SymbolScope};
+
+    #[test]
+    fn groups() {
+        assert_eq!(rust_crate("core::fmt::write"), Some("core"));
+        assert_eq!(rust_crate("<alloc::vec::Vec<T> as core::ops::Drop>::drop"), Some("alloc"));
+        assert_eq!(rust_crate("main"), None);
+
+        assert_eq!(cpp_namespace("std::vector<int>::push_back(int const&)"), Some("std"));
+        assert_eq!(cpp_namespace("foo(std::string)"), None);
+        // The return type of a template comes before its name.
+        assert_eq!(cpp_namespace("std::pair<a::b, c> ns::make<int>(int)"), Some("ns"));
+        let anonymous = "(anonymous namespace)::foo(int)";
+        assert_eq!(cpp_namespace(anonymous), Some("(anonymous namespace)"));
+        assert_eq!(cpp_namespace("ns::Foo::operator delete(void*)"), Some("ns"));
+        assert_eq!(cpp_namespace("operator<(foo const&, foo const&)"), None);
+
+        assert_eq!(group_of(None, "main"), "[unmangled]");
+        assert_eq!(group_of(Some(Scheme::Itanium), "foo(int)"), "[global]");
+    }
+
+    #[test]
+    fn function_sizes() {
+        let mut obj =
+            write::Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let text = obj.section_id(StandardSection::Text);
+        obj.append_section_data(text, &[0xc3; 0x40], 16);
+
+        // `bar` has no size and runs up to `baz`, which is an alias of `qux`.
+        for (name, value, size) in [
+            ("_ZN3foo3barEv", 0x10, 0),
+            ("_ZN3foo3bazEv", 0x20, 0x10),
+            ("_ZN3qux3quxEv", 0x20, 0),
+            ("main", 0, 0x10),
+        ] {
+            obj.add_symbol(Symbol {
+                name: name.as_bytes().to_vec(),
+                value,
+                size,
+                kind: SymbolKind::Text,
+                scope: SymbolScope::Linkage,
+                weak: false,
+                section: SymbolSection::Section(text),
+                flags: SymbolFlags::None,
+            });
+        }
+
+        let data = obj.write().unwrap();
+        let obj = object::File::parse(&*data).unwrap();
+        let functions = functions(&obj);
+        let sizes: Vec<(u64, u64, &str, &str)> = functions
+            .iter()
+            .map(|func| (func.addr, func.size, &*func.name, &*func.group))
+            .collect();
+
+        assert_eq!(
+            sizes,
+            [
+                (0, 0x10, "main", "[unmangled]"),
+                (0x10, 0x10, "foo::bar", "foo"),
+                (0x20, 0x10, "foo::baz", "foo"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Call graph of the whole object, or of everything reachable from a single function, in a
+//! format other tools can render or query.
+
+use crate::output::outln;
+use commands::{Cli, GraphFormat};
+use processor::Processor;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+struct Node {
+    name: String,
+    /// Bytes of machine code, zero for imports.
+    size: usize,
+}
+
+fn node(processor: &Processor, addr: usize) -> Node {
+    let name = match processor.index.get_sym_by_addr(addr) {
+        Some(sym) => sym.as_str().to_string(),
+        None => format!("func_{addr:x}"),
+    };
+
+    let size = processor.function_bytes(addr).map_or(0, <[u8]>::len);
+    Node { name, size }
+}
+
+/// Functions reachable from `root`, including itself.
+fn reachable(edges: &BTreeMap<(usize, usize), usize>, root: usize) -> BTreeSet<usize> {
+    let mut seen = BTreeSet::from([root]);
+    let mut queue = vec![root];
+
+    while let Some(caller) = queue.pop() {
+        for &(_, callee) in edges.range((caller, 0)..=(caller, usize::MAX)).map(|(edge, _)| edge) {
+            if seen.insert(callee) {
+                queue.push(callee);
+            }
+        }
+    }
+
+    seen
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn print_dot(nodes: &BTreeMap<usize, Node>, edges: &BTreeMap<(usize, usize), usize>) {
+    outln!("digraph callgraph {{");
+    outln!("    node [shape=box, fontname=monospace];");
+
+    for (addr, node) in nodes {
+        outln!(
+            "    \"{addr:x}\" [label=\"{}\\n{} bytes\"];",
+            escape(&node.name),
+            node.size
+        );
+    }
+
+    for (&(caller, callee), &count) in edges {
+        match count {
+            1 => outln!("    \"{caller:x}\" -> \"{callee:x}\";"),
+            count => outln!("    \"{caller:x}\" -> \"{callee:x}\" [label=\"{count}\"];"),
+        }
+    }
+
+    outln!("}}");
+}
+
+fn print_json(nodes: &BTreeMap<usize, Node>, edges: &BTreeMap<(usize, usize), usize>) {
+    let nodes: Vec<_> = nodes
+        .iter()
+        .map(|(addr, node)| json!({ "addr": addr, "name": node.name, "size": node.size }))
+        .collect();
+
+    let edges: Vec<_> = edges
+        .iter()
+        .map(|(&(caller, callee), &count)| {
+            json!({ "caller": caller, "callee": callee, "count": count })
+        })
+        .collect();
+
+    outln!("{}", json!({ "nodes": nodes, "edges": edges }));
+}
+
+/// Print the call graph asked for by `--callgraph`.
+pub fn print(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let Some(format) = args.callgraph else {
+        return Ok(());
+    };
+
+    let mut edges = processor.call_edges();
+    if edges.is_empty() {
+        return Err("No calls were found, call graphs are only built for x86_64.".to_string());
+    }
+
+    let mut nodes = BTreeMap::new();
+    if let Some(ref symbol) = args.callgraph_root {
+        let root = crate::extract::resolve(processor, symbol)
+            .ok_or_else(|| format!("Function '{symbol}' could not be found."))?;
+
+        let reachable = reachable(&edges, root);
+        edges.retain(|(caller, _), _| reachable.contains(caller));
+        nodes.insert(root, node(processor, root));
+    }
+
+    for &(caller, callee) in edges.keys() {
+        for addr in [caller, callee] {
+            nodes.entry(addr).or_insert_with(|| node(processor, addr));
+        }
+    }
+
+    match format {
+        GraphFormat::Dot => print_dot(&nodes, &edges),
+        GraphFormat::Json => print_json(&nodes, &edges),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reachable_from_root() {
+        // 1 -> 2 -> 3 -> 2 forms a cycle, 4 only calls into it.
+        let edges = BTreeMap::from([((1, 2), 1), ((2, 3), 2), ((3, 2), 1), ((4, 1), 1)]);
+        assert_eq!(reachable(&edges, 1), BTreeSet::from([1, 2, 3]));
+        assert_eq!(reachable(&edges, 3), BTreeSet::from([2, 3]));
+        assert_eq!(reachable(&edges, 4), BTreeSet::from([1, 2, 3, 4]));
+        // Leaves only reach themselves.
+        assert_eq!(reachable(&edges, 5), BTreeSet::from([5]));
+    }
+
+    #[test]
+    fn escaped_labels() {
+        assert_eq!(escape(r#"operator"" _km"#), r#"operator\"\" _km"#);
+        assert_eq!(escape(r"a\b"), r"a\\b");
+    }
+}
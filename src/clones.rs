@@ -0,0 +1,128 @@
+//! Functions grouped by the source-level function they were derived from, such that compiler
+//! generated clones and inlined copies are accounted to their original.
+
+use crate::output::outln;
+use commands::Cli;
+use debugvault::clones::split_clone_suffix;
+use object::{Object, ObjectSymbol, SymbolKind};
+use processor::Processor;
+use std::collections::{BTreeMap, HashSet};
+
+struct Instance {
+    addr: usize,
+    size: u64,
+    name: String,
+    /// Whether this is a clone rather than the original function.
+    clone: bool,
+}
+
+#[derive(Default)]
+struct Group {
+    instances: Vec<Instance>,
+    /// Functions the original was inlined into, and how many times.
+    inlined_into: BTreeMap<String, usize>,
+}
+
+impl Group {
+    fn size(&self) -> u64 {
+        self.instances.iter().map(|inst| inst.size).sum()
+    }
+}
+
+fn groups(processor: &Processor) -> Result<BTreeMap<String, Group>, String> {
+    let obj = object::File::parse(processor.binary())
+        .map_err(|_| "Object doesn't have a header to read this from.".to_string())?;
+
+    let index = &processor.index;
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+    let mut seen = HashSet::new();
+
+    for sym in obj.symbols().chain(obj.dynamic_symbols()) {
+        let addr = sym.address() as usize;
+        if sym.kind() != SymbolKind::Text || sym.is_undefined() || !seen.insert(addr) {
+            continue;
+        }
+
+        let name = match sym.name() {
+            Ok(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+
+        let (base, suffix) = split_clone_suffix(name);
+        let base = debugvault::demangle(base);
+
+        // Debug info knows the original of clones that aren't named after it, though it's
+        // name isn't always qualified.
+        let origin = match index.origins.search(addr) {
+            Ok(idx) if suffix.is_empty() => debugvault::demangle(&index.origins[idx].item),
+            _ => base.clone(),
+        };
+
+        let group = groups.entry(origin.clone()).or_default();
+        group.instances.push(Instance {
+            addr,
+            size: sym.size(),
+            clone: !suffix.is_empty() || origin != base,
+            name: base + suffix,
+        });
+    }
+
+    for inlined in index.inlined.iter() {
+        let caller = match index.syms.search(inlined.addr) {
+            Ok(idx) => idx,
+            Err(0) => continue,
+            Err(idx) => idx - 1,
+        };
+
+        let caller = index.syms[caller].item.as_str().to_string();
+        let group = groups.entry(debugvault::demangle(&inlined.item)).or_default();
+        *group.inlined_into.entry(caller).or_default() += 1;
+    }
+
+    Ok(groups)
+}
+
+/// Print the functions that were cloned or inlined, largest first.
+pub fn print(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let mut groups: Vec<(String, Group)> = groups(processor)?
+        .into_iter()
+        .filter(|(_, group)| {
+            group.instances.iter().any(|inst| inst.clone) || !group.inlined_into.is_empty()
+        })
+        .collect();
+
+    if groups.is_empty() {
+        outln!("There are no cloned or inlined functions.");
+        return Ok(());
+    }
+
+    groups.sort_by(|(a_name, a), (b_name, b)| b.size().cmp(&a.size()).then(a_name.cmp(b_name)));
+
+    for (name, mut group) in groups {
+        group.instances.sort_by_key(|inst| inst.addr);
+        match group.instances.len() {
+            0 => outln!("{name}  (only inlined)"),
+            1 => outln!("{name}  (1 instance, {} bytes)", group.size()),
+            count => outln!("{name}  ({count} instances, {} bytes)", group.size()),
+        }
+
+        for inst in group.instances.iter() {
+            outln!("  {:0>10X}  {:>8}  {}", inst.addr, inst.size, inst.name);
+        }
+
+        if !group.inlined_into.is_empty() {
+            let callers: Vec<String> = group
+                .inlined_into
+                .iter()
+                .map(|(caller, count)| match count {
+                    1 => caller.to_string(),
+                    count => format!("{caller} ({count}x)"),
+                })
+                .collect();
+
+            outln!("  inlined into {}", callers.join(", "));
+        }
+    }
+
+    Ok(())
+}
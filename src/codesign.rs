@@ -0,0 +1,49 @@
+//! Code signature of Mach-O objects, the part of `codesign -d` that's useful for reversing.
+
+use commands::{Cli, Subcommand};
+
+/// Print the signing identity, flags and entitlements of the object given by `bite codesign`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Codesign { object: ref path } = args.command else {
+        return Ok(());
+    };
+
+    let binary = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = match object::File::parse(&binary[..]) {
+        Ok(obj @ (object::File::MachO32(_) | object::File::MachO64(_))) => obj,
+        _ => return Err(format!("{path:?} isn't a Mach-O object.")),
+    };
+
+    let Some(signature) = binformat::codesign::code_signature(&obj)? else {
+        println!("The object isn't signed.");
+        return Ok(());
+    };
+
+    let flags = signature.flag_names();
+    let flags = if flags.is_empty() {
+        "none".to_string()
+    } else {
+        flags.join(", ")
+    };
+
+    println!("identifier  {}", signature.identifier);
+    println!(
+        "team        {}",
+        signature.team.as_deref().unwrap_or("not set")
+    );
+    println!("flags       {:#x} ({flags})", signature.flags);
+    println!(
+        "hashes      {} pages of {}",
+        signature.pages, signature.hash
+    );
+    for authority in signature.authorities.iter() {
+        println!("authority   {authority}");
+    }
+
+    match signature.entitlements {
+        Some(entitlements) => println!("\n{}", entitlements.trim_end()),
+        None => println!("\nThere are no entitlements."),
+    }
+
+    Ok(())
+}
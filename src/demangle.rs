@@ -0,0 +1,87 @@
+//! Filter rewriting mangled symbols anywhere in its input, similar to `c++filt` and `rustfilt`
+//! but for every mangling scheme at once.
+
+use commands::{Subcommand, ARGS};
+use debugvault::replace::Simplify;
+use serde_json::json;
+use std::io::{BufRead, Write};
+use tokenizing::Kind;
+
+fn filter(
+    input: impl BufRead,
+    mut out: impl Write,
+    colored: bool,
+    simplify: Option<&Simplify>,
+    omit: Kind,
+) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        let mut offset = 0;
+
+        for (range, symbol) in debugvault::demangle_line(&line, simplify, omit) {
+            out.write_all(&line.as_bytes()[offset..range.start])?;
+            out.write_all(tokenizing::render_omitting(symbol.name(), colored, omit).as_bytes())?;
+            offset = range.end;
+        }
+
+        out.write_all(&line.as_bytes()[offset..])?;
+        out.write_all(b"\n")?;
+    }
+
+    out.flush()
+}
+
+/// Write each symbol found in the input as a line of JSON, with the AST of MSVC symbols so that
+/// they can be queried by their parts, e.g. the type of their first parameter.
+fn filter_json(
+    input: impl BufRead,
+    mut out: impl Write,
+    simplify: Option<&Simplify>,
+    omit: Kind,
+) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+
+        for (range, symbol) in debugvault::demangle_line(&line, simplify, omit) {
+            let mangled = &line[range];
+            let json = json!({
+                "mangled": mangled,
+                "demangled": tokenizing::render_omitting(symbol.name(), false, omit),
+                "ast": debugvault::demangle_ast(mangled),
+            });
+            writeln!(out, "{json}")?;
+        }
+    }
+
+    out.flush()
+}
+
+/// Demangle the symbols in each line read from stdin, colored as given by `--color`, simplified
+/// as given by `--simplify` and `-s` and without the parts of names given by the `--no-*` flags.
+/// With `--json` each symbol is written as a line of JSON instead.
+pub fn run() -> Result<(), String> {
+    let stdout = std::io::stdout();
+    let colored = ARGS.colored();
+    let out = std::io::BufWriter::new(stdout.lock());
+
+    let input = std::io::stdin().lock();
+    let json = matches!(ARGS.command, Subcommand::Demangle { json: true });
+    let result = match json {
+        true => filter_json(input, out, ARGS.simplify.as_ref(), ARGS.omit),
+        false => filter(input, out, colored, ARGS.simplify.as_ref(), ARGS.omit),
+    };
+
+    match result {
+        // The reader went away, e.g. when piped into `head`.
+        Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        Err(err) => Err(format!("Failed to demangle stdin: {err}.")),
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Print the schemes that are demangled, in the order they're tried.
+pub fn list() {
+    for backend in debugvault::demanglers() {
+        println!("{:<14}{}", backend.scheme.to_string(), backend.description);
+    }
+}
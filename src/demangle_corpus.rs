@@ -0,0 +1,197 @@
+//! Pass rates of the demanglers on the test corpora of other demanglers, such that gaps in
+//! coverage can be tracked from release to release.
+//!
+//! Understood are the lit tests of `llvm-undname` (`llvm/test/Demangle/ms-*.test`), where every
+//! mangled name is followed by the `; CHECK:` lines it's expected to demangle to in the same
+//! order, and libiberty's `testsuite/demangle-expected`, where each case is an options line,
+//! the mangled name and what it's expected to demangle to.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// How many failures of a category are printed.
+const MAX_FAILURES: usize = 5;
+
+struct Case {
+    category: String,
+    line: usize,
+    mangled: String,
+    expected: String,
+}
+
+/// Whether the expected output is a demangler refusing the input, which should then be left
+/// as is.
+fn is_rejection(expected: &str) -> bool {
+    expected.starts_with("Invalid mangled name") || expected.starts_with("error:")
+}
+
+fn parse_llvm(corpus: &str, category: &str) -> Vec<Case> {
+    let mut cases = Vec::new();
+    let mut pending = VecDeque::new();
+
+    for (idx, line) in corpus.lines().enumerate() {
+        let line = line.trim();
+        let Some(comment) = line.strip_prefix(';') else {
+            if !line.is_empty() {
+                pending.push_back((idx + 1, line));
+            }
+            continue;
+        };
+
+        let comment = comment.trim_start();
+        let expected = comment
+            .strip_prefix("CHECK:")
+            .or_else(|| comment.strip_prefix("CHECK-NEXT:"))
+            .map(str::trim);
+
+        if let Some(expected) = expected {
+            // Checks that don't follow an input, e.g. of the output as a whole, are skipped.
+            if let Some((line, mangled)) = pending.pop_front() {
+                cases.push(Case {
+                    category: category.to_string(),
+                    line,
+                    mangled: mangled.to_string(),
+                    expected: expected.to_string(),
+                });
+            }
+        }
+    }
+
+    cases
+}
+
+fn parse_libiberty(corpus: &str) -> Vec<Case> {
+    let mut cases = Vec::new();
+    let mut lines = corpus.lines().enumerate().filter(|(_, line)| !line.starts_with('#'));
+
+    while let Some((_, options)) = lines.next() {
+        let options: Vec<&str> = options.split_whitespace().collect();
+        if options.is_empty() {
+            continue;
+        }
+
+        let (Some((idx, mangled)), Some((_, expected))) = (lines.next(), lines.next()) else {
+            break;
+        };
+
+        // Without parameters a case is expected to demangle once more.
+        if options.contains(&"--no-params") {
+            lines.next();
+        }
+
+        // Options that change how the result is printed have no equivalent.
+        let unsupported = [
+            "--is-v3-ctor",
+            "--is-v3-dtor",
+            "--ret-postfix",
+            "--ret-drop",
+        ];
+        if options.iter().any(|option| unsupported.contains(option)) {
+            continue;
+        }
+
+        let category = options
+            .iter()
+            .find_map(|option| option.strip_prefix("--format="))
+            .unwrap_or("auto");
+
+        cases.push(Case {
+            category: category.to_string(),
+            line: idx + 1,
+            mangled: mangled.trim().to_string(),
+            expected: expected.trim().to_string(),
+        });
+    }
+
+    cases
+}
+
+fn parse_corpus(path: &Path) -> Result<Vec<Case>, String> {
+    let corpus =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+
+    let is_llvm = corpus.lines().any(|line| line.trim_start().starts_with("; CHECK"));
+    if is_llvm {
+        let category = path.file_stem().map_or("llvm".into(), |stem| stem.to_string_lossy());
+        Ok(parse_llvm(&corpus, &category))
+    } else {
+        Ok(parse_libiberty(&corpus))
+    }
+}
+
+/// Collapse whitespace, such that the spacing of the corpus doesn't matter.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Default)]
+struct Category {
+    total: usize,
+    failures: Vec<(Case, String)>,
+}
+
+/// Demangle every case of the corpora at `paths`, returns whether all of them matched.
+pub fn run(paths: &[PathBuf]) -> Result<bool, String> {
+    let mut categories: BTreeMap<String, Category> = BTreeMap::new();
+
+    for path in paths {
+        for case in parse_corpus(path)? {
+            let demangled = debugvault::demangle(&case.mangled);
+            let expected = if is_rejection(&case.expected) {
+                &case.mangled
+            } else {
+                &case.expected
+            };
+
+            let passed = normalize(&demangled) == normalize(expected);
+            let category = categories.entry(case.category.clone()).or_default();
+            category.total += 1;
+            if !passed {
+                category.failures.push((case, demangled));
+            }
+        }
+    }
+
+    if categories.is_empty() {
+        return Err("The corpora don't contain any test cases.".to_string());
+    }
+
+    for (name, category) in categories.iter() {
+        for (case, demangled) in category.failures.iter().take(MAX_FAILURES) {
+            println!("{name} line {}: {}", case.line, case.mangled);
+            println!("  expected:  {}", case.expected);
+            println!("  demangled: {demangled}");
+        }
+
+        if category.failures.len() > MAX_FAILURES {
+            let more = category.failures.len() - MAX_FAILURES;
+            println!("  ... and {more} more failures in {name}");
+        }
+    }
+
+    let width = categories.keys().map(String::len).max().unwrap_or(0).max(5);
+    let (mut passed, mut total) = (0, 0);
+
+    if categories.values().any(|category| !category.failures.is_empty()) {
+        println!();
+    }
+
+    for (name, category) in categories.iter() {
+        let matched = category.total - category.failures.len();
+        let rate = matched as f64 * 100.0 / category.total as f64;
+        println!(
+            "{name:<width$}  {matched:>6} of {:<6}  {rate:>5.1}%",
+            category.total
+        );
+        passed += matched;
+        total += category.total;
+    }
+
+    let rate = passed as f64 * 100.0 / total as f64;
+    println!(
+        "{:<width$}  {passed:>6} of {total:<6}  {rate:>5.1}%",
+        "total"
+    );
+
+    Ok(passed == total)
+}
@@ -0,0 +1,193 @@
+//! Shannon entropy of an object, to tell whether it's worth disassembling at all.
+//!
+//! Compressed or encrypted data is close to 8 bits of entropy per byte, whereas machine code
+//! rarely exceeds 6.5. Executable sections above [`PACKED`] are most likely packed.
+
+use commands::{Cli, Subcommand};
+use object::{Object, ObjectSection, SectionKind};
+
+/// Bytes per window of the profile, unless given with `--window`.
+const DEFAULT_WINDOW: usize = 4096;
+
+/// Entropy above which a section is assumed to be compressed or encrypted.
+const PACKED: f64 = 7.2;
+
+/// Windows per line of the graph.
+const GRAPH_WIDTH: usize = 64;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Section with contents in the file, by file offset.
+struct Section {
+    name: String,
+    start: u64,
+    end: u64,
+    executable: bool,
+}
+
+/// Bits of entropy per byte, between 0 and 8.
+fn entropy(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count != 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Name of the section containing the file offset.
+fn section_at(sections: &[Section], offset: u64) -> &str {
+    sections
+        .iter()
+        .find(|section| (section.start..section.end).contains(&offset))
+        .map_or("", |section| section.name.as_str())
+}
+
+fn print_profile(data: &[u8], window: usize, sections: &[Section]) {
+    println!("{:>10}  {:>7}  SECTION", "OFFSET", "ENTROPY");
+    for (idx, chunk) in data.chunks(window).enumerate() {
+        let offset = idx * window;
+        println!(
+            "{offset:>#10x}  {:>7.3}  {}",
+            entropy(chunk),
+            section_at(sections, offset as u64)
+        );
+    }
+}
+
+fn print_graph(data: &[u8], window: usize) {
+    let bars: Vec<char> = data
+        .chunks(window)
+        .map(|chunk| {
+            let level = (entropy(chunk) / 8.0 * BARS.len() as f64) as usize;
+            BARS[level.min(BARS.len() - 1)]
+        })
+        .collect();
+
+    for (idx, line) in bars.chunks(GRAPH_WIDTH).enumerate() {
+        let offset = idx * GRAPH_WIDTH * window;
+        println!("{offset:>#10x}  {}", line.iter().collect::<String>());
+    }
+}
+
+/// Print the entropy of the sections and of every window of the object given by `bite entropy`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Entropy { object: ref path, graph, window } = args.command else {
+        return Ok(());
+    };
+
+    let data = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let window = window.unwrap_or(DEFAULT_WINDOW);
+
+    // Objects without a header, like firmware, only get a profile.
+    let mut sections = Vec::new();
+    if let Ok(obj) = object::File::parse(&data[..]) {
+        for section in obj.sections() {
+            let (Some((offset, size)), Ok(name)) = (section.file_range(), section.name()) else {
+                continue;
+            };
+
+            if size != 0 {
+                sections.push(Section {
+                    name: name.to_string(),
+                    start: offset,
+                    end: offset + size,
+                    executable: section.kind() == SectionKind::Text,
+                });
+            }
+        }
+    }
+
+    println!("{:<20} {:>10} {:>10}  ENTROPY", "SECTION", "OFFSET", "SIZE");
+    for section in sections.iter() {
+        let bytes = data.get(section.start as usize..section.end as usize).unwrap_or(&[]);
+        let entropy = entropy(bytes);
+        let packed = if section.executable && entropy > PACKED {
+            "  executable and likely packed or encrypted"
+        } else {
+            ""
+        };
+
+        println!(
+            "{:<20} {:>#10x} {:>#10x}  {entropy:.3}{packed}",
+            section.name,
+            section.start,
+            section.end - section.start
+        );
+    }
+    println!(
+        "{:<20} {:>#10x} {:>#10x}  {:.3}",
+        "[file]",
+        0,
+        data.len(),
+        entropy(&data)
+    );
+
+    println!();
+    if graph {
+        print_graph(&data, window);
+    } else {
+        print_profile(&data, window, &sections);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_per_byte() {
+        assert_eq!(entropy(&[]), 0.0);
+        assert_eq!(entropy(&[0x90; 64]), 0.0);
+        assert_eq!(entropy(&[0, 1, 0, 1]), 1.0);
+
+        let every_byte: Vec<u8> = (0..=255).collect();
+        assert_eq!(entropy(&every_byte), 8.0);
+    }
+
+    #[test]
+    fn packed() {
+        // Output of a linear congruential generator looks encrypted.
+        let mut state = 1u32;
+        let random: Vec<u8> = (0..4096)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state >> 24) as u8
+            })
+            .collect();
+        assert!(entropy(&random) > PACKED);
+
+        // push rbp; mov rbp, rsp; xor eax, eax; pop rbp; ret
+        let code = [0x55, 0x48, 0x89, 0xe5, 0x31, 0xc0, 0x5d, 0xc3].repeat(512);
+        assert!(entropy(&code) < PACKED);
+    }
+
+    #[test]
+    fn sections_by_offset() {
+        let section = |name: &str, start, end| Section {
+            name: name.to_string(),
+            start,
+            end,
+            executable: false,
+        };
+        let sections = [section(".text", 0x1000, 0x2000), section(".data", 0x2000, 0x2800)];
+
+        assert_eq!(section_at(&sections, 0x1fff), ".text");
+        assert_eq!(section_at(&sections, 0x2000), ".data");
+        assert_eq!(section_at(&sections, 0), "");
+    }
+}
@@ -0,0 +1,299 @@
+//! Extraction of a single function's machine code, e.g. for diffing or re-linking it, and of the
+//! raw contents of sections.
+
+use crate::output::outln;
+use commands::{Cli, Subcommand};
+use object::write::{Object, StandardSection, Symbol, SymbolSection};
+use object::{
+    BinaryFormat, Object as _, ObjectSection, Section, SymbolFlags, SymbolKind, SymbolScope,
+};
+use processor::Processor;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Parses either a hexadecimal address prefixed with '0x' or a symbol name.
+pub(crate) fn resolve(processor: &Processor, symbol: &str) -> Option<usize> {
+    match symbol.strip_prefix("0x").or_else(|| symbol.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => processor.index.get_func_by_name(symbol),
+    }
+}
+
+/// Relocatable ELF object with a single global function.
+///
+/// Relocations of the original object aren't preserved, so any absolute or rip-relative
+/// references to other code or data will have to be fixed up by hand.
+fn build_object(processor: &Processor, name: &str, bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut obj = Object::new(BinaryFormat::Elf, processor.arch(), processor.endianness());
+    let section = obj.section_id(StandardSection::Text);
+    let symbol = obj.add_symbol(Symbol {
+        name: name.as_bytes().to_vec(),
+        value: 0,
+        size: 0,
+        kind: SymbolKind::Text,
+        scope: SymbolScope::Linkage,
+        weak: false,
+        section: SymbolSection::Undefined,
+        flags: SymbolFlags::None,
+    });
+
+    obj.add_symbol_data(symbol, section, bytes, 16);
+    obj.write().map_err(|err| format!("Failed to write object: {err}."))
+}
+
+/// Write the function given by `--dump-bytes` to `--output`.
+pub fn dump_bytes(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let (Some(symbol), Some(output)) = (&args.dump_bytes, &args.output) else {
+        return Ok(());
+    };
+
+    let addr = resolve(processor, symbol)
+        .ok_or_else(|| format!("Function '{symbol}' could not be found."))?;
+    let bytes = processor
+        .function_bytes(addr)
+        .ok_or_else(|| format!("Address {addr:#x} doesn't lie in a code section."))?;
+
+    std::fs::write(output, bytes).map_err(|err| format!("Failed to write {output:?}: {err}."))?;
+    outln!("Wrote {} bytes of '{symbol}' to {output:?}.", bytes.len());
+
+    if args.emit_object {
+        // Replacing the extension could name the raw bytes themselves, e.g. `func.o`.
+        let mut path = output.clone().into_os_string();
+        path.push(".o");
+        let path = PathBuf::from(path);
+        let name = match processor.index.get_func_by_name(symbol) {
+            Some(_) => symbol.clone(),
+            None => format!("func_{addr:x}"),
+        };
+
+        let obj = build_object(processor, &name, bytes)?;
+        std::fs::write(&path, obj).map_err(|err| format!("Failed to write {path:?}: {err}."))?;
+        outln!("Wrote object containing '{name}' to {path:?}.");
+    }
+
+    Ok(())
+}
+
+/// Write the GPU blob given by `--extract-gpu` to `--output`.
+pub fn extract_gpu(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let (Some(idx), Some(output)) = (args.extract_gpu, &args.output) else {
+        return Ok(());
+    };
+
+    let obj = object::File::parse(processor.binary())
+        .map_err(|_| "Object doesn't have a header to find GPU code in.".to_string())?;
+    let blobs = binformat::gpu::blobs(&obj);
+    let blob = blobs.get(idx).ok_or_else(|| {
+        format!("There is no GPU blob #{idx}, the object contains {}.", blobs.len())
+    })?;
+
+    std::fs::write(output, blob.data)
+        .map_err(|err| format!("Failed to write {output:?}: {err}."))?;
+    outln!("Wrote {} bytes of {} to {output:?}.", blob.data.len(), blob.format);
+
+    Ok(())
+}
+
+fn sections_header<'data>(processor: &'data Processor) -> Result<object::File<'data>, String> {
+    object::File::parse(processor.binary())
+        .map_err(|_| "Object doesn't have a header to find sections in.".to_string())
+}
+
+/// Contents of a section in the object, `None` for sections such as `.bss` that only take up
+/// space once they're loaded.
+fn contents<'data>(
+    section: &Section<'data, '_>,
+    name: &str,
+) -> Result<Option<&'data [u8]>, String> {
+    match section.file_range() {
+        Some(_) => section
+            .data()
+            .map(Some)
+            .map_err(|err| format!("Failed to read '{name}': {err}.")),
+        None => Ok(None),
+    }
+}
+
+fn write_section(obj: &object::File, name: &str, output: &Path) -> Result<(), String> {
+    let section = obj
+        .section_by_name(name)
+        .ok_or_else(|| format!("Section '{name}' could not be found."))?;
+    let data = contents(&section, name)?
+        .ok_or_else(|| format!("Section '{name}' has no contents in the object."))?;
+
+    std::fs::write(output, data).map_err(|err| format!("Failed to write {output:?}: {err}."))?;
+    outln!("Wrote {} bytes of '{name}' to {output:?}.", data.len());
+
+    Ok(())
+}
+
+/// Name of the file a section is written to, e.g. `rodata` for `.rodata` and `__TEXT.__const` for
+/// the `__const` section of a Mach-O object's `__TEXT` segment.
+fn file_stem(segment: Option<&str>, name: &str) -> String {
+    let name = match segment {
+        Some(segment) if !segment.is_empty() => format!("{segment}.{name}"),
+        _ => name.to_string(),
+    };
+
+    let stem: String = name
+        .trim_start_matches('.')
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+
+    match stem.is_empty() {
+        true => "section".to_string(),
+        false => stem,
+    }
+}
+
+/// Write the contents of the section given by `--extract-section` to `--output`.
+pub fn extract_section(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let (Some(name), Some(output)) = (&args.extract_section, &args.output) else {
+        return Ok(());
+    };
+
+    write_section(&sections_header(processor)?, name, output)
+}
+
+/// Write the section given by `--section` to `--output`, or every section with contents to the
+/// directory given by `--all-sections`.
+pub fn extract(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let Subcommand::Extract { ref section, ref all_sections } = args.command else {
+        return Ok(());
+    };
+
+    let obj = sections_header(processor)?;
+    if let (Some(name), Some(output)) = (section, &args.output) {
+        return write_section(&obj, name, output);
+    }
+
+    let Some(dir) = all_sections else {
+        return Ok(());
+    };
+
+    write_sections(&obj, dir)
+}
+
+/// Write every section with contents to a file of its own in `dir`.
+fn write_sections(obj: &object::File, dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|err| format!("Failed to create {dir:?}: {err}."))?;
+
+    let mut files = HashSet::new();
+    for section in obj.sections() {
+        let Ok(name) = section.name() else {
+            continue;
+        };
+
+        let Some(data) = contents(&section, name)? else {
+            continue;
+        };
+
+        // Sections can share a name, e.g. the `__const` sections of different segments.
+        let stem = file_stem(section.segment_name().ok().flatten(), name);
+        let mut file = format!("{stem}.bin");
+        let mut idx = 1;
+        while !files.insert(file.clone()) {
+            file = format!("{stem}_{idx}.bin");
+            idx += 1;
+        }
+
+        let path = dir.join(file);
+        std::fs::write(&path, data).map_err(|err| format!("Failed to write {path:?}: {err}."))?;
+        outln!("Wrote {} bytes of '{name}' to {path:?}.", data.len());
+    }
+
+    if files.is_empty() {
+        return Err("Object doesn't have any sections with contents.".to_string());
+    }
+
+    Ok(())
+}
+
+/// Write the object with the section given by `--add-section` added to it to `--output`.
+pub fn add_section(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let (Some((name, path)), Some(output)) = (&args.add_section, &args.output) else {
+        return Ok(());
+    };
+
+    let contents = std::fs::read(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = object::File::parse(processor.binary())
+        .map_err(|_| "Object doesn't have a header to add a section to.".to_string())?;
+    let data = binformat::edit::add_section(&obj, name, &contents)?;
+
+    std::fs::write(output, data).map_err(|err| format!("Failed to write {output:?}: {err}."))?;
+    if let Ok(metadata) = std::fs::metadata(&args.path) {
+        let _ = std::fs::set_permissions(output, metadata.permissions());
+    }
+    outln!("Wrote object with {} bytes in '{name}' to {output:?}.", contents.len());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::{Architecture, Endianness, SectionKind};
+
+    /// Relocatable ELF object with `.text`, two sections named `.rodata` and a `.bss`.
+    fn object() -> Vec<u8> {
+        let mut obj = Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let text = obj.section_id(StandardSection::Text);
+        obj.append_section_data(text, &[0xc3], 16);
+        for data in [b"first".as_slice(), b"second"] {
+            let name = b".rodata".to_vec();
+            let rodata = obj.add_section(Vec::new(), name, SectionKind::ReadOnlyData);
+            obj.append_section_data(rodata, data, 1);
+        }
+        let bss = obj.section_id(StandardSection::UninitializedData);
+        obj.append_section_bss(bss, 64, 8);
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn stems() {
+        assert_eq!(file_stem(None, ".rodata"), "rodata");
+        assert_eq!(file_stem(Some(""), ".text"), "text");
+        assert_eq!(file_stem(Some("__TEXT"), "__const"), "__TEXT.__const");
+        assert_eq!(file_stem(None, ".note/gnu build"), "note_gnu_build");
+        assert_eq!(file_stem(None, "."), "section");
+    }
+
+    #[test]
+    fn all_sections() {
+        let data = object();
+        let obj = object::File::parse(&*data).unwrap();
+        let dir = std::env::temp_dir().join(format!("bite-extract-{}", std::process::id()));
+
+        let written = write_sections(&obj, &dir);
+        let read = |file: &str| std::fs::read(dir.join(file)).ok();
+        let (text, first, second, bss) =
+            (read("text.bin"), read("rodata.bin"), read("rodata_1.bin"), read("bss.bin"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(written.is_ok());
+        assert_eq!(text.as_deref(), Some(&[0xc3][..]));
+        assert_eq!(first.as_deref(), Some(&b"first"[..]));
+        assert_eq!(second.as_deref(), Some(&b"second"[..]));
+        assert_eq!(bss, None);
+    }
+
+    #[test]
+    fn without_contents() {
+        let data = object();
+        let obj = object::File::parse(&*data).unwrap();
+        let output = Path::new("/nonexistent/bss.bin");
+
+        assert_eq!(
+            write_section(&obj, ".bss", output).err().as_deref(),
+            Some("Section '.bss' has no contents in the object.")
+        );
+        assert_eq!(
+            write_section(&obj, ".data.rel", output).err().as_deref(),
+            Some("Section '.data.rel' could not be found.")
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Listing of every function as a single HTML page, e.g. to share a snapshot of an analysis.
+//!
+//! Tokens keep their colors, every function has an anchor and calls link to the function they
+//! end up in.
+
+use crate::output::outln;
+use commands::Cli;
+use config::CONFIG;
+use processor::{BlockContent, Processor};
+use tokenizing::{Color32, Token, TokenStream};
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn css(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn spans(tokens: &[Token]) -> String {
+    let mut html = String::new();
+    for token in tokens {
+        html += &format!(
+            "<span style=\"color:{}\">{}</span>",
+            css(token.color),
+            escape(&token.text)
+        );
+    }
+    html
+}
+
+fn anchor(addr: usize) -> String {
+    format!("f{addr:x}")
+}
+
+/// Lines of the function at `addr`, with the operands of calls to another function as a link.
+fn function(processor: &Processor, addr: usize) -> Vec<String> {
+    let end = addr + processor.function_bytes(addr).map_or(0, <[u8]>::len);
+    let mut lines = Vec::new();
+    let mut ip = addr;
+
+    while ip < end {
+        let Some(inst) = processor.instruction_by_addr(ip) else {
+            ip += 1;
+            continue;
+        };
+
+        let target = processor
+            .branch_target(inst)
+            .map(|target| processor.resolve_thunk(target))
+            .filter(|&target| processor.index.get_sym_by_addr(target).is_some());
+
+        for block in processor.parse_blocks(ip) {
+            let BlockContent::Instruction { ref inst, .. } = block.content else {
+                continue;
+            };
+
+            let mut stream = TokenStream::new();
+            block.tokenize(&mut stream, processor.aslr());
+
+            // The instruction's own tokens come after the address and bytes.
+            let operands = stream.inner.len() - inst.len() + 1;
+            let line = match target {
+                Some(target) if operands < stream.inner.len() => format!(
+                    "{}<a href=\"#{}\">{}</a>",
+                    spans(&stream.inner[..operands]),
+                    anchor(target),
+                    spans(&stream.inner[operands..])
+                ),
+                _ => spans(&stream.inner),
+            };
+            lines.push(line);
+        }
+
+        ip += processor.instruction_width(inst);
+    }
+
+    lines
+}
+
+/// Print the listing as given by `--format`.
+pub fn print(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let title = escape(&processor.path.display().to_string());
+    let functions: Vec<_> = processor
+        .index
+        .functions()
+        .filter(|func| processor.function_bytes(func.addr).is_some())
+        .collect();
+
+    outln!("<!DOCTYPE html>");
+    outln!("<html>");
+    outln!("<head>");
+    outln!("<meta charset=\"utf-8\">");
+    outln!("<title>{title}</title>");
+    outln!("<style>");
+    outln!(
+        "body {{ background: {}; color: {}; font-family: monospace; }}",
+        css(CONFIG.colors.bg_primary),
+        css(CONFIG.colors.comment)
+    );
+    outln!("a {{ text-decoration: none; }}");
+    outln!("a:hover {{ text-decoration: underline; }}");
+    outln!("h3 {{ font-weight: normal; margin-bottom: 0; }}");
+    outln!(
+        "nav {{ background: {}; padding: 0.5em; }}",
+        css(CONFIG.colors.bg_secondary)
+    );
+    outln!("</style>");
+    outln!("</head>");
+    outln!("<body>");
+    outln!("<h2>{title}</h2>");
+
+    outln!("<nav><details open>");
+    outln!("<summary>{} functions</summary>", functions.len());
+    outln!("<ul>");
+    for func in &functions {
+        outln!(
+            "<li><a href=\"#{}\">{}</a></li>",
+            anchor(func.addr),
+            spans(func.item.name())
+        );
+    }
+    outln!("</ul>");
+    outln!("</details></nav>");
+
+    for func in &functions {
+        outln!(
+            "<h3 id=\"{}\">&lt;{}&gt;</h3>",
+            anchor(func.addr),
+            spans(func.item.name())
+        );
+        outln!("<pre>");
+        for line in function(processor, func.addr) {
+            outln!("{line}");
+        }
+        outln!("</pre>");
+    }
+
+    outln!("</body>");
+    outln!("</html>");
+    Ok(())
+}
@@ -0,0 +1,268 @@
+//! Identifiers tying an object to its debug info, as used by symbol servers and debuginfod.
+//!
+//! ELF objects have a GNU build-id note, Mach-O objects an `LC_UUID` and PE objects the GUID and
+//! age of their PDB in the debug directory. Symbol servers also store PE objects themselves, by
+//! their timestamp and the size of their image.
+
+use commands::{Cli, Subcommand};
+use debugvault::debuglink;
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::LittleEndian as LE;
+use object::Object;
+use std::path::Path;
+
+struct Id {
+    kind: &'static str,
+    value: String,
+    /// Whether `--by-id` matches against it.
+    searchable: bool,
+}
+
+impl Id {
+    fn new(kind: &'static str, value: String) -> Self {
+        Self {
+            kind,
+            value,
+            searchable: true,
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+/// Lowercase hex digits of an id, without the dashes and braces it's usually written with.
+fn normalize(id: &str) -> String {
+    id.chars()
+        .filter(char::is_ascii_hexdigit)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// UUID as written by `dwarfdump --uuid`.
+fn uuid(bytes: [u8; 16]) -> String {
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex(&bytes[..4]),
+        hex(&bytes[4..6]),
+        hex(&bytes[6..8]),
+        hex(&bytes[8..10]),
+        hex(&bytes[10..])
+    )
+}
+
+/// GUID in the registry format, its first three fields are stored little-endian.
+fn guid(bytes: [u8; 16]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{}-{}",
+        u32::from_le_bytes(bytes[..4].try_into().unwrap()),
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        u16::from_le_bytes([bytes[6], bytes[7]]),
+        hex(&bytes[8..10]),
+        hex(&bytes[10..])
+    )
+}
+
+/// Key of a PE object on a symbol server, its timestamp followed by the size of its image.
+fn image_key<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> String {
+    let headers = pe.nt_headers();
+    format!(
+        "{:08X}{:x}",
+        headers.file_header().time_date_stamp.get(LE),
+        headers.optional_header().size_of_image()
+    )
+}
+
+fn ids(obj: &object::File) -> Vec<Id> {
+    let mut ids = Vec::new();
+
+    if let Ok(Some(build_id)) = obj.build_id() {
+        ids.push(Id::new("build-id", hex(build_id).to_lowercase()));
+    }
+
+    if let Ok(Some(bytes)) = obj.mach_uuid() {
+        ids.push(Id::new("uuid", uuid(bytes)));
+    }
+
+    if let Ok(Some(pdb)) = obj.pdb_info() {
+        ids.push(Id::new("guid", guid(pdb.guid())));
+        ids.push(Id {
+            kind: "age",
+            value: pdb.age().to_string(),
+            searchable: false,
+        });
+        ids.push(Id {
+            kind: "pdb",
+            value: String::from_utf8_lossy(pdb.path()).into_owned(),
+            searchable: false,
+        });
+        // The PDB is stored under its GUID without dashes, followed by the age in hex.
+        let key = format!("{}{:X}", guid(pdb.guid()).replace('-', ""), pdb.age());
+        ids.push(Id::new("pdb-key", key));
+    }
+
+    match obj {
+        object::File::Pe32(pe) => ids.push(Id::new("image-key", image_key(pe))),
+        object::File::Pe64(pe) => ids.push(Id::new("image-key", image_key(pe))),
+        _ => {}
+    }
+
+    ids
+}
+
+fn print(path: &Path) -> Result<(), String> {
+    let binary = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let slices = debuglink::macho_slices(&binary);
+
+    for (idx, slice) in slices.iter().enumerate() {
+        let obj = object::File::parse(*slice)
+            .map_err(|err| format!("Failed to parse {path:?}: {err}."))?;
+
+        // Each slice of a universal file has an id of its own.
+        if slices.len() > 1 {
+            if idx != 0 {
+                println!();
+            }
+            println!("{:?}:", obj.architecture());
+        }
+
+        let ids = ids(&obj);
+        if ids.is_empty() {
+            println!("The object has no build-id, UUID or PDB GUID.");
+        }
+
+        for id in ids {
+            println!("{:<10} {}", id.kind, id.value);
+        }
+    }
+
+    Ok(())
+}
+
+fn has_id(path: &Path, id: &str) -> bool {
+    let Ok(binary) = binformat::map(path) else {
+        return false;
+    };
+
+    debuglink::macho_slices(&binary)
+        .into_iter()
+        .filter_map(|slice| object::File::parse(slice).ok())
+        .any(|obj| {
+            ids(&obj)
+                .iter()
+                .any(|candidate| candidate.searchable && normalize(&candidate.value) == id)
+        })
+}
+
+/// Print every object below `dir` with the given id.
+fn search(dir: &Path, id: &str) -> Result<(), String> {
+    let id = normalize(id);
+    let mut found = false;
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+
+            // Symbolic links to directories aren't followed, as they might form a cycle.
+            let path = entry.path();
+            if kind.is_dir() {
+                dirs.push(path);
+            } else if path.is_file() && has_id(&path, &id) {
+                println!("{}", path.display());
+                found = true;
+            }
+        }
+    }
+
+    if !found {
+        return Err(format!("No object in {dir:?} has the id {id}."));
+    }
+
+    Ok(())
+}
+
+/// Print the ids of the object given by `bite id`, or search a directory with `--by-id`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Id { ref path, ref by_id } = args.command else {
+        return Ok(());
+    };
+
+    match by_id {
+        Some(id) => search(path, id),
+        None => print(path),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::{write, Architecture, BinaryFormat, Endianness, SectionKind};
+
+    const BYTES: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+    /// Relocatable ELF object with a GNU build-id note.
+    fn elf(build_id: &[u8]) -> Vec<u8> {
+        let mut note = Vec::new();
+        for field in [4, build_id.len() as u32, object::elf::NT_GNU_BUILD_ID] {
+            note.extend(field.to_le_bytes());
+        }
+        note.extend(b"GNU\0");
+        note.extend(build_id);
+
+        let mut obj =
+            write::Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let name = b".note.gnu.build-id".to_vec();
+        let section = obj.add_section(Vec::new(), name, SectionKind::Note);
+        obj.append_section_data(section, &note, 4);
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(uuid(BYTES), "00010203-0405-0607-0809-0A0B0C0D0E0F");
+        assert_eq!(guid(BYTES), "03020100-0504-0706-0809-0A0B0C0D0E0F");
+        assert_eq!(
+            normalize("{03020100-0504-0706-0809-0A0B0C0D0E0F}"),
+            "030201000504070608090a0b0c0d0e0f"
+        );
+    }
+
+    #[test]
+    fn build_id() {
+        let data = elf(&[0xde, 0xad, 0xbe, 0xef]);
+        let obj = object::File::parse(&*data).unwrap();
+        let ids: Vec<_> = ids(&obj).into_iter().map(|id| (id.kind, id.value)).collect();
+        assert_eq!(ids, [("build-id", "deadbeef".to_string())]);
+    }
+
+    #[test]
+    fn search_by_id() {
+        let dir = std::env::temp_dir().join(format!("bite-id-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("lib/a.so"), elf(&[0xde, 0xad, 0xbe, 0xef])).unwrap();
+        std::fs::write(dir.join("b.so"), elf(&[0x12, 0x34])).unwrap();
+
+        let found = has_id(&dir.join("lib/a.so"), &normalize("DEADBEEF"));
+        let missing = has_id(&dir.join("b.so"), &normalize("DEADBEEF"));
+        let searched = search(&dir, "de:ad:be:ef").is_ok();
+        let not_found = search(&dir, "cafe").err();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(found);
+        assert!(!missing);
+        assert!(searched);
+        assert_eq!(not_found, Some(format!("No object in {dir:?} has the id cafe.")));
+    }
+}
@@ -0,0 +1,198 @@
+//! Resolution of shared library dependencies the way the dynamic linker would, similar to `ldd`
+//! but without executing anything.
+
+use crate::output::outln;
+use binformat::elf::{self, Dynamic};
+use object::{Architecture, Object};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Directories searched after everything else.
+const DEFAULT_DIRS: &[&str] = &["/lib64", "/usr/lib64", "/lib", "/usr/lib"];
+
+/// What a library has to match to be loaded alongside the object.
+#[derive(Clone, Copy, PartialEq)]
+struct Target {
+    arch: Architecture,
+    is_64: bool,
+}
+
+struct Library {
+    needed: Vec<String>,
+    rpath: Vec<String>,
+    runpath: Vec<String>,
+    target: Target,
+}
+
+fn read_library(path: &Path) -> Option<Library> {
+    let data = binformat::map(path).ok()?;
+    let obj = object::File::parse(&data[..]).ok()?;
+
+    let dynamic = match obj {
+        object::File::Elf32(ref elf) => elf::dynamic(elf),
+        object::File::Elf64(ref elf) => elf::dynamic(elf),
+        _ => None,
+    };
+
+    let Dynamic { needed, rpath, runpath, .. } = dynamic.unwrap_or_default();
+    let owned = |strs: Vec<&str>| strs.into_iter().map(str::to_string).collect();
+
+    Some(Library {
+        needed: owned(needed),
+        rpath: owned(rpath),
+        runpath: owned(runpath),
+        target: Target {
+            arch: obj.architecture(),
+            is_64: obj.is_64(),
+        },
+    })
+}
+
+/// Directories listed in `ld.so.conf`, following includes.
+fn ld_so_conf(path: &Path, dirs: &mut Vec<PathBuf>) {
+    let conf = match std::fs::read_to_string(path) {
+        Ok(conf) => conf,
+        Err(..) => return,
+    };
+
+    for line in conf.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if let Some(pattern) = line.strip_prefix("include").map(str::trim) {
+            let pattern = Path::new(pattern);
+            let (dir, name) = match (pattern.parent(), pattern.file_name()) {
+                (Some(dir), Some(name)) => (dir, name.to_string_lossy()),
+                _ => continue,
+            };
+
+            // Only trailing wildcards, e.g. `/etc/ld.so.conf.d/*.conf`, are supported.
+            let (prefix, suffix) = name.split_once('*').unwrap_or((&name, ""));
+            let mut includes: Vec<PathBuf> = match std::fs::read_dir(dir) {
+                Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+                Err(..) => continue,
+            };
+
+            includes.sort();
+            for include in includes {
+                let file_name = include.file_name().unwrap_or_default().to_string_lossy();
+                if file_name.starts_with(prefix) && file_name.ends_with(suffix) {
+                    ld_so_conf(&include, dirs);
+                }
+            }
+        } else if !line.is_empty() {
+            dirs.push(PathBuf::from(line));
+        }
+    }
+}
+
+/// Expand `$ORIGIN`, the directory of the object requesting the library.
+fn expand_origin(path: &str, origin: &Path) -> PathBuf {
+    let origin = origin.to_string_lossy();
+    PathBuf::from(path.replace("${ORIGIN}", &origin).replace("$ORIGIN", &origin))
+}
+
+struct Resolver {
+    target: Target,
+    system_dirs: Vec<PathBuf>,
+    ld_library_path: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+    missing: usize,
+}
+
+impl Resolver {
+    /// Search order of glibc's dynamic linker: DT_RPATH of the loading objects (unless there's
+    /// a DT_RUNPATH), `LD_LIBRARY_PATH`, DT_RUNPATH, `ld.so.conf` and the default directories.
+    fn resolve(
+        &self,
+        name: &str,
+        origin: &Path,
+        rpaths: &[PathBuf],
+        lib: &Library,
+    ) -> Option<PathBuf> {
+        let matches = |path: &Path| {
+            path.is_file() && read_library(path).is_some_and(|lib| lib.target == self.target)
+        };
+
+        if name.contains('/') {
+            let path = expand_origin(name, origin);
+            return matches(&path).then_some(path);
+        }
+
+        let runpath: Vec<PathBuf> =
+            lib.runpath.iter().map(|dir| expand_origin(dir, origin)).collect();
+
+        let rpaths = if runpath.is_empty() { rpaths } else { &[] };
+
+        rpaths
+            .iter()
+            .chain(self.ld_library_path.iter())
+            .chain(runpath.iter())
+            .chain(self.system_dirs.iter())
+            .map(|dir| dir.join(name))
+            .find(|path| matches(path))
+    }
+
+    fn print(&mut self, path: &Path, lib: &Library, rpaths: &[PathBuf], depth: usize) {
+        let origin = path.parent().unwrap_or(Path::new(""));
+
+        // DT_RPATH is inherited by the libraries being loaded.
+        let mut rpaths = rpaths.to_vec();
+        rpaths.extend(lib.rpath.iter().map(|dir| expand_origin(dir, origin)));
+
+        for name in lib.needed.iter() {
+            let indent = "    ".repeat(depth);
+            let resolved = match self.resolve(name, origin, &rpaths, lib) {
+                Some(resolved) => resolved,
+                None => {
+                    outln!("{indent}{name} => not found");
+                    self.missing += 1;
+                    continue;
+                }
+            };
+
+            let canonical = resolved.canonicalize().unwrap_or(resolved.clone());
+            if !self.seen.insert(canonical) {
+                outln!("{indent}{name} => {} (already listed)", resolved.display());
+                continue;
+            }
+
+            outln!("{indent}{name} => {}", resolved.display());
+            if let Some(dep) = read_library(&resolved) {
+                self.print(&resolved, &dep, &rpaths, depth + 1);
+            }
+        }
+    }
+}
+
+/// Print the tree of libraries the object at `path` would load.
+pub fn print_tree(path: &Path) -> Result<(), String> {
+    let lib = read_library(path).ok_or_else(|| format!("Failed to parse {path:?}."))?;
+
+    let mut system_dirs = Vec::new();
+    ld_so_conf(Path::new("/etc/ld.so.conf"), &mut system_dirs);
+    system_dirs.extend(DEFAULT_DIRS.iter().map(PathBuf::from));
+
+    let ld_library_path = std::env::var("LD_LIBRARY_PATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect();
+
+    let mut resolver = Resolver {
+        target: lib.target,
+        system_dirs,
+        ld_library_path,
+        seen: HashSet::new(),
+        missing: 0,
+    };
+
+    let path = path.canonicalize().map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    resolver.print(&path, &lib, &[], 0);
+
+    if resolver.missing > 0 {
+        return Err(format!("{} libraries could not be found.", resolver.missing));
+    }
+
+    Ok(())
+}
@@ -6,10 +6,131 @@
 #[cfg(not(any(target_family = "windows", target_family = "unix")))]
 compile_error!("Bite can only be build for windows, macos and linux.");
 
+mod abi;
+mod bloat;
+mod callgraph;
+mod clones;
+mod codesign;
+mod demangle;
+mod demangle_corpus;
+mod entropy;
+mod extract;
+mod html;
+mod id;
+mod libs;
+mod objc;
+mod output;
+mod patch;
+mod pe_info;
+mod registers;
+mod remote;
+mod report;
+mod search;
+mod selftest;
+mod serve;
+mod signature;
+mod strip;
+mod trace;
 mod wayland;
-use commands::ARGS;
+use commands::{Subcommand, ARGS};
+
+/// Write numbers the way they were asked for on the command line.
+fn set_number_format() {
+    let radix = ARGS.radix.map(|radix| match radix {
+        commands::Radix::Hex => decoder::Radix::Hex,
+        commands::Radix::Decimal => decoder::Radix::Decimal,
+        commands::Radix::Auto => decoder::Radix::Auto,
+    });
+
+    decoder::set_number_format(decoder::NumberFormat {
+        radix,
+        prefix: !ARGS.no_hex_prefix,
+        padded: ARGS.zero_pad,
+    });
+}
 
 fn main() {
+    set_number_format();
+
+    if let Some(path) = ARGS.text_output() {
+        if let Err(err) = output::to_file(path) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    } else if ARGS.paged() {
+        output::to_pager();
+    }
+
+    let result = match ARGS.command {
+        Subcommand::Tui => match (&ARGS.bytes, &ARGS.arch) {
+            (Some(bytes), Some(arch)) => disassemble_bytes(arch, bytes),
+            _ if ARGS.headless() => report(),
+            _ => return open_gui(),
+        },
+        Subcommand::AbiCheck { ref old, ref new } => check(abi::check(old, new)),
+        Subcommand::Demangle { .. } => demangle::run(),
+        Subcommand::DemangleHelp => {
+            demangle::list();
+            Ok(())
+        }
+        Subcommand::DemangleSelftest { ref corpora } => check(demangle_corpus::run(corpora)),
+        Subcommand::DecodeSelftest { ref corpus } => check(selftest::run(corpus)),
+        Subcommand::Serve { ref addr, ref root } => serve::run(addr, root.as_deref()),
+        Subcommand::Strip { .. } => strip::run(&ARGS),
+        Subcommand::Patch { .. } => patch::run(&ARGS),
+        Subcommand::Bloat { .. } => bloat::run(&ARGS),
+        Subcommand::BloatDiff { .. } => bloat::diff(&ARGS),
+        Subcommand::Entropy { .. } => entropy::run(&ARGS),
+        Subcommand::PeInfo { .. } => pe_info::run(&ARGS),
+        Subcommand::Codesign { .. } => codesign::run(&ARGS),
+        Subcommand::Objc { .. } => objc::run(&ARGS),
+        Subcommand::Id { .. } => id::run(&ARGS),
+        Subcommand::Disasm
+        | Subcommand::Nm
+        | Subcommand::Libs
+        | Subcommand::Sections
+        | Subcommand::Functions { .. }
+        | Subcommand::Imports
+        | Subcommand::Exports
+        | Subcommand::Extract { .. }
+        | Subcommand::Hex { .. } => report(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+/// Print the instructions of the bytes given with --bytes.
+fn disassemble_bytes(arch: &str, bytes: &[u8]) -> Result<(), String> {
+    let lines = processor::disassemble_bytes(arch, bytes, ARGS.base.unwrap_or(0))
+        .map_err(|err| format!("{err:?}"))?;
+    lines.iter().for_each(|line| output::outln!("{line}"));
+    output::finish();
+    Ok(())
+}
+
+/// Print the information asked for about the object.
+fn report() -> Result<(), String> {
+    let result = report::run(&ARGS);
+    output::finish();
+    result
+}
+
+/// Exit with whether a check passed, or with 2 if it couldn't be run.
+fn check(result: Result<bool, String>) -> Result<(), String> {
+    match result {
+        Ok(passed) => std::process::exit(if passed { 0 } else { 1 }),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Open the GUI, with the object if one was given.
+fn open_gui() {
     #[cfg(target_os = "linux")]
     if nix::unistd::getuid() == 0.into() {
         wayland::set_env();
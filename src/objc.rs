@@ -0,0 +1,74 @@
+//! Objective-C classes and protocols of a Mach-O object, printed like the interfaces they were
+//! declared with.
+
+use binformat::objc::Method;
+use commands::{Cli, Subcommand};
+
+fn print_methods(prefix: char, methods: &[Method]) {
+    for method in methods {
+        println!("{prefix} {:<40} {}", method.selector, method.types);
+    }
+}
+
+fn conformance(protocols: &[String]) -> String {
+    match protocols.is_empty() {
+        true => String::new(),
+        false => format!(" <{}>", protocols.join(", ")),
+    }
+}
+
+/// Print the classes, protocols and referenced selectors of the object given by `bite objc`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Objc { object: ref path } = args.command else {
+        return Ok(());
+    };
+
+    let binary = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = object::File::parse(&binary[..])
+        .map_err(|_| format!("{path:?} doesn't have a header to read the classes of."))?;
+    let metadata = binformat::objc::metadata(&obj)?;
+
+    if metadata.classes.is_empty() && metadata.protocols.is_empty() {
+        println!("The object doesn't define any Objective-C classes or protocols.");
+    }
+
+    for class in metadata.classes.iter() {
+        let superclass = match class.superclass {
+            Some(ref superclass) => format!(" : {superclass}"),
+            None => String::new(),
+        };
+
+        println!(
+            "@interface {}{superclass}{}  // {:#x}",
+            class.name,
+            conformance(&class.protocols),
+            class.addr
+        );
+        print_methods('+', &class.class_methods);
+        print_methods('-', &class.instance_methods);
+        println!("@end\n");
+    }
+
+    for protocol in metadata.protocols.iter() {
+        println!(
+            "@protocol {}{}",
+            protocol.name,
+            conformance(&protocol.protocols)
+        );
+        print_methods('-', &protocol.methods);
+        if !protocol.optional_methods.is_empty() {
+            println!("@optional");
+            print_methods('-', &protocol.optional_methods);
+        }
+        println!("@end\n");
+    }
+
+    if !metadata.selectors.is_empty() {
+        println!("Referenced selectors:");
+        for selector in metadata.selectors.iter() {
+            println!("  {selector}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//! Text printed instead of opening the GUI, written through a single buffered writer.
+//!
+//! Lines are collected per thread and handed to the writer in batches, rather than taking the
+//! lock of stdout for every line. Text goes to stdout, or to `--output` when nothing else is
+//! written there. On a terminal it goes through a pager, unless `--no-pager` is given.
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Write as _;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+/// Bytes a thread collects before handing them to the writer.
+const BATCH_SIZE: usize = 64 * 1024;
+
+type Writer = BufWriter<Box<dyn Write + Send>>;
+
+static WRITER: Mutex<Option<Writer>> = Mutex::new(None);
+
+/// Pager the writer writes to, waited on once everything is written.
+static PAGER: Mutex<Option<Child>> = Mutex::new(None);
+
+/// Lines of a thread that weren't written yet, handed over when the thread exits.
+struct Batch(String);
+
+impl Drop for Batch {
+    fn drop(&mut self) {
+        write_batch(&mut self.0);
+    }
+}
+
+thread_local! {
+    static BATCH: RefCell<Batch> = const { RefCell::new(Batch(String::new())) };
+
+    /// Whether the thread's lines are kept in its batch, see [`capture`].
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Exit after a failed write. Like `head` closing the pipe, a reader that went away has nothing
+/// left to be told, but any other error means the output is incomplete.
+fn write_failed(err: io::Error) -> ! {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        std::process::exit(0);
+    }
+
+    eprintln!("Failed to write the output: {err}.");
+    std::process::exit(1);
+}
+
+fn write_batch(batch: &mut String) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let mut writer = WRITER.lock().unwrap_or_else(|err| err.into_inner());
+    let writer = writer.get_or_insert_with(|| BufWriter::new(Box::new(std::io::stdout())));
+
+    if let Err(err) = writer.write_all(batch.as_bytes()) {
+        write_failed(err);
+    }
+
+    batch.clear();
+}
+
+/// Write everything to the file at `path` instead of stdout.
+pub fn to_file(path: &Path) -> Result<(), String> {
+    let file = std::fs::File::create(path)
+        .map_err(|err| format!("Failed to create {path:?}: {err}."))?;
+
+    flush();
+    *WRITER.lock().unwrap_or_else(|err| err.into_inner()) =
+        Some(BufWriter::new(Box::new(file)));
+    Ok(())
+}
+
+/// Write everything through `$PAGER`, or `less` if it isn't set.
+///
+/// Like git, `less` is told to keep the colors and to quit right away if everything fits on one
+/// screen, unless `$LESS` says otherwise. Text is written to stdout if the pager can't be started.
+pub fn to_pager() {
+    let pager = std::env::var("PAGER").unwrap_or_default();
+    let mut words = pager.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("less"));
+    command.args(words).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    let Ok(mut child) = command.spawn() else {
+        return;
+    };
+
+    let Some(stdin) = child.stdin.take() else {
+        return;
+    };
+
+    flush();
+    *WRITER.lock().unwrap_or_else(|err| err.into_inner()) = Some(BufWriter::new(Box::new(stdin)));
+    *PAGER.lock().unwrap_or_else(|err| err.into_inner()) = Some(child);
+}
+
+/// Add a line to the calling thread's batch, see [`outln`].
+pub fn line(args: std::fmt::Arguments) {
+    BATCH.with(|batch| {
+        let batch = &mut batch.borrow_mut().0;
+        let _ = batch.write_fmt(args);
+        batch.push('\n');
+
+        if batch.len() >= BATCH_SIZE && !CAPTURING.get() {
+            write_batch(batch);
+        }
+    });
+}
+
+/// Run `f`, returning the lines it printed on the calling thread instead of writing them, e.g.
+/// to write the output of several threads in order.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, String) {
+    BATCH.with(|batch| write_batch(&mut batch.borrow_mut().0));
+
+    CAPTURING.set(true);
+    let result = f();
+    CAPTURING.set(false);
+
+    let text = BATCH.with(|batch| std::mem::take(&mut batch.borrow_mut().0));
+    (result, text)
+}
+
+/// Write text that was [`capture`]d.
+pub fn text(text: &str) {
+    BATCH.with(|batch| {
+        let batch = &mut batch.borrow_mut().0;
+        batch.push_str(text);
+
+        if batch.len() >= BATCH_SIZE {
+            write_batch(batch);
+        }
+    });
+}
+
+/// Write the calling thread's batch and everything buffered before it.
+pub fn flush() {
+    BATCH.with(|batch| write_batch(&mut batch.borrow_mut().0));
+
+    let mut writer = WRITER.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(writer) = writer.as_mut() {
+        if let Err(err) = writer.flush() {
+            write_failed(err);
+        }
+    }
+}
+
+/// Write everything and wait for the pager to be closed, if there is one.
+pub fn finish() {
+    flush();
+
+    // Closing the pager's input tells it there's nothing left to come.
+    WRITER.lock().unwrap_or_else(|err| err.into_inner()).take();
+    if let Some(mut pager) = PAGER.lock().unwrap_or_else(|err| err.into_inner()).take() {
+        let _ = pager.wait();
+    }
+}
+
+/// Like `println!`, but written through the shared writer.
+macro_rules! outln {
+    () => {
+        $crate::output::line(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::line(format_args!($($arg)*))
+    };
+}
+
+pub(crate) use outln;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured() {
+        let (value, text) = capture(|| {
+            outln!("{:x}", 0x10);
+            outln!();
+            5
+        });
+        assert_eq!(value, 5);
+        assert_eq!(text, "10\n\n");
+
+        // lines aren't handed to the writer while capturing, however many there are.
+        let line = "a".repeat(1023);
+        let ((), text) = capture(|| (0..BATCH_SIZE / 1024 * 2).for_each(|_| outln!("{line}")));
+        assert_eq!(text.len(), BATCH_SIZE * 2);
+    }
+
+    #[test]
+    fn threads_in_order() {
+        let path = std::env::temp_dir().join(format!("bite-output-{}", std::process::id()));
+        to_file(&path).unwrap();
+
+        let texts: Vec<String> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|idx| scope.spawn(move || capture(|| outln!("thread {idx}")).1))
+                .collect();
+            threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+        });
+
+        // a thread's batch is written once it exits.
+        std::thread::spawn(|| outln!("last")).join().unwrap();
+        texts.iter().rev().for_each(|captured| text(captured));
+        flush();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        *WRITER.lock().unwrap() = None;
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written, "last\nthread 3\nthread 2\nthread 1\nthread 0\n");
+    }
+}
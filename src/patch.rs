@@ -0,0 +1,92 @@
+//! Writing bytes into an object at a virtual address, e.g. to skip a check or stub a function.
+
+use commands::{Cli, Subcommand};
+use object::Object;
+use std::path::PathBuf;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(" ")
+}
+
+/// Print the bytes before and after patching, 16 to a line.
+fn print_diff(addr: usize, old: &[u8], new: &[u8]) {
+    for (idx, (old, new)) in old.chunks(16).zip(new.chunks(16)).enumerate() {
+        println!("{:#x}:", addr + idx * 16);
+        println!("- {}", hex(old));
+        println!("+ {}", hex(new));
+    }
+}
+
+/// Patch the object given by `bite patch`, either in place or into `--output`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Patch {
+        object: ref path,
+        at: Some(addr),
+        ref assemble,
+        fix_checksum,
+        dry_run,
+    } = args.command
+    else {
+        return Ok(());
+    };
+
+    let data = std::fs::read(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let bytes = match (&args.bytes, assemble) {
+        (Some(bytes), _) => bytes.clone(),
+        (None, Some(asm)) => {
+            let obj = object::File::parse(&data[..])
+                .map_err(|_| "Object doesn't have a header to find its architecture.")?;
+            processor::assemble(obj.architecture(), asm, addr)?
+        }
+        (None, None) => return Ok(()),
+    };
+
+    let patch = binformat::edit::patch(&data, addr as u64, &bytes, fix_checksum)?;
+
+    println!(
+        "Patching {} bytes of '{}' at {addr:#x} (offset {:#x}):",
+        bytes.len(),
+        patch.section,
+        patch.offset
+    );
+    print_diff(addr, &patch.old, &bytes);
+
+    if let Some((old, new)) = patch.checksum {
+        println!("Checksum {old:#010x} becomes {new:#010x}.");
+    }
+
+    if patch.old == bytes {
+        println!("The object already has these bytes.");
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    let output = match args.output {
+        Some(ref output) => output.clone(),
+        None => {
+            // An existing backup is kept, as it's the object before any of the patches.
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            let backup = PathBuf::from(backup);
+            if !backup.exists() {
+                std::fs::copy(path, &backup)
+                    .map_err(|err| format!("Failed to back up {path:?}: {err}."))?;
+                println!("Backed up the object to {backup:?}.");
+            }
+            path.clone()
+        }
+    };
+
+    std::fs::write(&output, &patch.data)
+        .map_err(|err| format!("Failed to write {output:?}: {err}."))?;
+
+    // Keep the object executable.
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = std::fs::set_permissions(&output, metadata.permissions());
+    }
+
+    println!("Wrote the patched object to {output:?}.");
+    Ok(())
+}
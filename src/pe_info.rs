@@ -0,0 +1,54 @@
+//! Indicators used to triage PE objects, which tie together samples built from the same source
+//! or with the same toolchain.
+
+use binformat::pe;
+use commands::{Cli, Subcommand};
+
+/// Print the imphash, imported libraries and Rich header of the object given by `bite pe-info`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::PeInfo { object: ref path } = args.command else {
+        return Ok(());
+    };
+
+    let binary = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = match object::File::parse(&binary[..]) {
+        Ok(obj @ (object::File::Pe32(_) | object::File::Pe64(_))) => obj,
+        _ => return Err(format!("{path:?} isn't a PE object.")),
+    };
+
+    let imphash = pe::imphash(&obj).map_err(|err| format!("Failed to read imports: {err}."))?;
+    println!(
+        "imphash   {}",
+        imphash.as_deref().unwrap_or("none, nothing is imported")
+    );
+
+    for lib in binformat::libraries(&obj) {
+        println!("import    {lib}");
+    }
+
+    let Some(rich) = pe::rich_header(&obj) else {
+        println!("\nThere is no Rich header, the object wasn't linked by Microsoft's linker.");
+        return Ok(());
+    };
+
+    println!();
+    println!("Rich header at {:#x}:", rich.offset);
+    if rich.key == rich.checksum {
+        println!("  key      {:#010x}", rich.key);
+    } else {
+        println!(
+            "  key      {:#010x} (doesn't match checksum {:#010x}, it was modified)",
+            rich.key, rich.checksum
+        );
+    }
+
+    println!("  {:>7} {:>7} {:>7}", "PRODUCT", "BUILD", "COUNT");
+    for entry in rich.entries {
+        println!(
+            "  {:>#7x} {:>7} {:>7}",
+            entry.product, entry.build, entry.count
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Register usage of the whole object and of each function, as a table of reads and writes
+//! with a bar showing how hot each register is.
+//!
+//! Functions are printed by name rather than address, such that the output of two builds can
+//! be diffed to compare the registers the compilers chose.
+
+use crate::output::outln;
+use commands::Cli;
+use processor::{Processor, RegisterUsages};
+
+/// Width of the bar of the most used register.
+const BAR_WIDTH: usize = 24;
+
+fn print_table(usages: &RegisterUsages, indent: &str) {
+    let mut usages: Vec<_> = usages.iter().collect();
+    usages.sort_by(|(a_name, a), (b_name, b)| b.total().cmp(&a.total()).then(a_name.cmp(b_name)));
+
+    let max = usages.first().map_or(0, |(_, usage)| usage.total());
+    for (name, usage) in usages {
+        let bar = "#".repeat((usage.total() * BAR_WIDTH).div_ceil(max.max(1)));
+        outln!(
+            "{indent}{name:<8} {:>8} {:>8}  {bar}",
+            usage.reads, usage.writes
+        );
+    }
+}
+
+/// Print the register usage of the whole object, followed by that of every function.
+pub fn print(processor: &Processor, _: &Cli) -> Result<(), String> {
+    if processor.arch() != object::Architecture::X86_64 {
+        return Err("Register usage is only counted for x86_64.".to_string());
+    }
+
+    let functions = processor.register_usage();
+
+    let mut total = RegisterUsages::new();
+    for usages in functions.values() {
+        for (name, usage) in usages {
+            let entry = total.entry(name).or_default();
+            entry.reads += usage.reads;
+            entry.writes += usage.writes;
+        }
+    }
+
+    outln!("{:<8} {:>8} {:>8}", "register", "reads", "writes");
+    print_table(&total, "");
+
+    let mut functions: Vec<(String, RegisterUsages)> = functions
+        .into_iter()
+        .map(|(addr, usages)| {
+            let name = match processor.index.get_sym_by_addr(addr) {
+                Some(sym) => sym.as_str().to_string(),
+                None => format!("func_{addr:x}"),
+            };
+            (name, usages)
+        })
+        .collect();
+
+    functions.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (name, usages) in functions {
+        // Stubs that only jump through memory don't use any registers.
+        if usages.is_empty() {
+            continue;
+        }
+
+        outln!();
+        outln!("{name}");
+        print_table(&usages, "  ");
+    }
+
+    Ok(())
+}
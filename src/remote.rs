@@ -0,0 +1,153 @@
+//! Objects read over HTTP, such as debug artifacts on a build server, without downloading all
+//! of them.
+//!
+//! Only the blocks of the object that are parsed are fetched using `Range` requests, which for
+//! listing the symbols of an object are the headers, the symbol tables and their strings.
+
+use crate::output::outln;
+use commands::Cli;
+use object::read::ReadCache;
+use object::{Object, ObjectSymbol, SymbolKind};
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Size of the aligned blocks that are fetched, such that parsing many small headers doesn't
+/// result in a request for each of them.
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Reader of a remote object that fetches blocks of it as they're read.
+struct RangeReader {
+    agent: ureq::Agent,
+    url: String,
+    len: u64,
+    pos: u64,
+    blocks: HashMap<u64, Vec<u8>>,
+    /// Number of bytes that were fetched.
+    fetched: u64,
+}
+
+impl RangeReader {
+    fn open(url: &str) -> Result<Self, String> {
+        let mut reader = Self {
+            agent: ureq::Agent::new(),
+            url: url.to_string(),
+            len: u64::MAX,
+            pos: 0,
+            blocks: HashMap::new(),
+            fetched: 0,
+        };
+
+        // The first block is needed for the header anyway, its response also has the length.
+        let (block, len) =
+            reader.fetch(0).map_err(|err| format!("Failed to read {url}: {err}."))?;
+        reader.blocks.insert(0, block);
+        reader.len = len;
+        Ok(reader)
+    }
+
+    /// Fetch the block at `idx`, returns its contents and the length of the whole object.
+    fn fetch(&mut self, idx: u64) -> io::Result<(Vec<u8>, u64)> {
+        let start = idx * BLOCK_SIZE;
+        let end = start.saturating_add(BLOCK_SIZE).min(self.len) - 1;
+
+        let response = self
+            .agent
+            .get(&self.url)
+            .set("Range", &format!("bytes={start}-{end}"))
+            .call()
+            .map_err(|err| match err {
+                ureq::Error::Status(code, _) => io::Error::other(format!("status code {code}")),
+                ureq::Error::Transport(err) => io::Error::other(err.kind().to_string()),
+            })?;
+
+        if response.status() != 206 {
+            return Err(io::Error::other("server doesn't support range requests"));
+        }
+
+        // e.g. `Content-Range: bytes 0-65535/1048576`.
+        let len = response
+            .header("Content-Range")
+            .and_then(|range| range.rsplit_once('/'))
+            .and_then(|(_, len)| len.parse().ok())
+            .ok_or_else(|| io::Error::other("response doesn't say how large the object is"))?;
+
+        let mut block = Vec::new();
+        response.into_reader().take(BLOCK_SIZE).read_to_end(&mut block)?;
+        self.fetched += block.len() as u64;
+        Ok((block, len))
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let idx = self.pos / BLOCK_SIZE;
+        if !self.blocks.contains_key(&idx) {
+            let (block, _) = self.fetch(idx)?;
+            self.blocks.insert(idx, block);
+        }
+
+        let block = &self.blocks[&idx];
+        let off = (self.pos % BLOCK_SIZE) as usize;
+        let len = buf.len().min(block.len().saturating_sub(off));
+        if len == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+
+        buf[..len].copy_from_slice(&block[off..off + len]);
+        self.pos += len as u64;
+        Ok(len)
+    }
+}
+
+impl Seek for RangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(off) => self.len.checked_add_signed(off),
+            SeekFrom::Current(off) => self.pos.checked_add_signed(off),
+        };
+
+        self.pos = pos.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        Ok(self.pos)
+    }
+}
+
+/// Print the functions of the object at the URL given as path, the only report that's
+/// supported for remote objects.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let url = args.path.to_string_lossy();
+    let cache = ReadCache::new(RangeReader::open(&url)?);
+    let obj = object::File::parse(&cache).map_err(|err| format!("{url}: {err}."))?;
+
+    let mut funcs: Vec<(u64, String)> = obj
+        .symbols()
+        .chain(obj.dynamic_symbols())
+        .filter(|sym| sym.kind() == SymbolKind::Text && !sym.is_undefined())
+        .filter_map(|sym| Some((sym.address(), debugvault::demangle(sym.name().ok()?))))
+        .collect();
+
+    funcs.sort_unstable();
+    funcs.dedup();
+
+    for (addr, name) in funcs {
+        match args.simplify {
+            Some(ref simplify) => {
+                outln!("{addr:0>10X}  {}", debugvault::replace::simplify(&name, simplify))
+            }
+            None => outln!("{addr:0>10X}  {name}"),
+        }
+    }
+
+    let reader = cache.into_inner();
+    log::complex!(
+        w "[remote::run] fetched ",
+        y format!("{} of {} bytes", reader.fetched, reader.len),
+        w " of the object.",
+    );
+
+    Ok(())
+}
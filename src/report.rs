@@ -0,0 +1,865 @@
+//! Information printed instead of opening the GUI.
+//!
+//! Any number of these can be requested at once, they all share a single parse of the object.
+
+use crate::output::{self, outln};
+use binformat::elf;
+use binformat::pe::{self, PeExportTarget};
+use binformat::security::{self, Status};
+use commands::{Cli, SortBy, Subcommand};
+use debugvault::cache::{CacheKey, SymbolCache};
+use debugvault::{Scheme, Symbol};
+use object::Object as _;
+use processor::{BlockContent, Processor};
+use processor_shared::Addressed;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use tokenizing::TokenStream;
+
+type Report = fn(&Processor, &Cli) -> Result<(), String>;
+
+fn header(processor: &Processor) -> Result<object::File<'_>, String> {
+    object::File::parse(processor.binary())
+        .map_err(|_| "Object doesn't have a header to read this from.".to_string())
+}
+
+/// Address of a function as listed by `--names`, next to the runtime address when simulating
+/// ASLR.
+fn addr_column(processor: &Processor, addr: usize) -> String {
+    match processor.aslr() {
+        Some(aslr) => format!("{:0>10X} {:0>12X}", aslr.relative(addr), aslr.runtime(addr)),
+        None => format!("{addr:0>10X}"),
+    }
+}
+
+/// Line of `--names` listing a function.
+fn name_line(processor: &Processor, args: &Cli, addr: usize, func: &Symbol) -> String {
+    let addr = addr_column(processor, addr);
+    let name = match args.simplify {
+        Some(ref simplify) => {
+            let name = tokenizing::render_omitting(func.name(), false, args.omit);
+            debugvault::replace::simplify(&name, simplify)
+        }
+        None => tokenizing::render_omitting(func.name(), args.colored(), args.omit),
+    };
+
+    match func.module() {
+        Some(module) => format!("{addr}  {name} ({module})"),
+        None => format!("{addr}  {name}"),
+    }
+}
+
+/// Module of a demangled Rust function, its path without the name and generic arguments.
+/// Methods of a trait impl like `<alloc::vec::Vec<T> as Drop>::drop` are in their type's module.
+fn rust_module(name: &str) -> Option<String> {
+    let name = name.trim_start_matches('<');
+    let bytes = name.as_bytes();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut segments = Vec::new();
+
+    for (idx, &b) in bytes.iter().enumerate() {
+        match b {
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+            b' ' if depth == 0 => break,
+            b':' if depth == 0 && idx >= start && bytes.get(idx + 1) == Some(&b':') => {
+                segments.push(name[start..idx].split('<').next().unwrap_or_default());
+                start = idx + 2;
+            }
+            _ => {}
+        }
+    }
+
+    (!segments.is_empty()).then(|| segments.join("::"))
+}
+
+/// Groups of functions whose names only differ in their template arguments, in the order of the
+/// first function of each. Groups of a single function don't have a folded name.
+fn fold<T>(funcs: Vec<T>, name: impl Fn(&T) -> String) -> Vec<(Option<String>, Vec<T>)> {
+    let mut groups: Vec<(Option<String>, Vec<T>)> = Vec::new();
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+
+    for func in funcs {
+        let Some(folded) = debugvault::replace::fold_templates(&name(&func)) else {
+            groups.push((None, vec![func]));
+            continue;
+        };
+
+        match by_name.get(&folded) {
+            Some(&idx) => groups[idx].1.push(func),
+            None => {
+                by_name.insert(folded.clone(), groups.len());
+                groups.push((Some(folded), vec![func]));
+            }
+        }
+    }
+
+    // A template that's only instantiated once is listed like any other function.
+    for (folded, funcs) in &mut groups {
+        if funcs.len() == 1 {
+            *folded = None;
+        }
+    }
+
+    groups
+}
+
+/// List functions by `--names`, with the instantiations of a template on a single line if they're
+/// folded.
+fn print_names(
+    processor: &Processor,
+    args: &Cli,
+    funcs: Vec<&Addressed<Arc<Symbol>>>,
+    indent: &str,
+) {
+    if !args.fold {
+        for func in funcs {
+            outln!("{indent}{}", name_line(processor, args, func.addr, &func.item));
+        }
+        return;
+    }
+
+    let omitted = |func: &&Addressed<Arc<Symbol>>| {
+        tokenizing::render_omitting(func.item.name(), false, args.omit)
+    };
+
+    for (folded, funcs) in fold(funcs, omitted) {
+        let Some(folded) = folded else {
+            for func in funcs {
+                outln!("{indent}{}", name_line(processor, args, func.addr, &func.item));
+            }
+            continue;
+        };
+
+        let addr = addr_column(processor, funcs[0].addr);
+        outln!("{indent}{addr}  {folded} — {} instantiations", funcs.len());
+        if args.expand {
+            for func in funcs {
+                outln!("{indent}  {}", name_line(processor, args, func.addr, &func.item));
+            }
+        }
+    }
+}
+
+fn names(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let is_rust =
+        |func: &Symbol| matches!(func.scheme(), Some(Scheme::RustLegacy | Scheme::RustV0));
+    if args.krate.is_none() && !processor.index.functions().any(|func| is_rust(&func.item)) {
+        print_names(processor, args, processor.index.functions().collect(), "");
+        return Ok(());
+    }
+
+    // Rust functions are grouped by crate and then by module, the others are listed after them.
+    let mut crates: BTreeMap<String, BTreeMap<String, Vec<_>>> = BTreeMap::new();
+    let mut other = Vec::new();
+    for func in processor.index.functions() {
+        let module = match is_rust(&func.item) {
+            true => rust_module(func.item.as_str()),
+            false => None,
+        };
+
+        let Some(module) = module else {
+            other.push(func);
+            continue;
+        };
+
+        let krate = module.split("::").next().unwrap_or_default().to_string();
+        if args.krate.as_ref().is_some_and(|name| *name != krate) {
+            continue;
+        }
+
+        crates.entry(krate).or_default().entry(module).or_default().push(func);
+    }
+
+    if let Some(ref krate) = args.krate {
+        if crates.is_empty() {
+            return Err(format!("There aren't any functions of crate '{krate}'."));
+        }
+    }
+
+    for (krate, modules) in crates {
+        let count: usize = modules.values().map(Vec::len).sum();
+        outln!("{krate} ({count} functions)");
+        for (module, funcs) in modules {
+            outln!("  {module} ({})", funcs.len());
+            print_names(processor, args, funcs, "    ");
+        }
+    }
+
+    if args.krate.is_none() && !other.is_empty() {
+        outln!("[other] ({} functions)", other.len());
+        print_names(processor, args, other, "  ");
+    }
+
+    Ok(())
+}
+
+/// Listing of every function of the object, like the GUI shows it.
+fn disasm(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let colored = args.colored();
+    for func in processor.index.functions().filter(|func| !func.item.imported()) {
+        let Some(bytes) = processor.function_bytes(func.addr) else {
+            continue;
+        };
+
+        outln!();
+        outln!("{:0>10X}  {}", func.addr, tokenizing::render(func.item.name(), colored));
+
+        let end = func.addr + bytes.len();
+        let mut addr = func.addr;
+        while addr < end {
+            let Some(inst) = processor.instruction_by_addr(addr) else {
+                addr += 1;
+                continue;
+            };
+
+            for block in processor.parse_blocks(addr) {
+                if let BlockContent::Instruction { .. } = block.content {
+                    let mut stream = TokenStream::new();
+                    block.tokenize(&mut stream, processor.aslr());
+                    outln!("{}", tokenizing::render(&stream.inner, colored));
+                }
+            }
+
+            addr += processor.instruction_width(inst);
+        }
+    }
+
+    Ok(())
+}
+
+fn functions(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let Subcommand::Functions { sort } = args.command else {
+        return Ok(());
+    };
+
+    // Imported functions don't have any code in the object.
+    let funcs: Vec<_> = processor
+        .index
+        .functions()
+        .filter(|func| !func.item.imported())
+        .map(|func| (func, processor.function_bytes(func.addr).map_or(0, <[u8]>::len)))
+        .collect();
+
+    // Without folding, every function is a group of its own.
+    let mut groups = match args.fold {
+        true => fold(funcs, |(func, _)| {
+            tokenizing::render_omitting(func.item.name(), false, args.omit)
+        }),
+        false => funcs.into_iter().map(|func| (None, vec![func])).collect(),
+    };
+
+    match sort.unwrap_or(SortBy::Address) {
+        SortBy::Address => {}
+        SortBy::Size => groups.sort_by_key(|(_, funcs)| {
+            std::cmp::Reverse(funcs.iter().map(|(_, size)| size).sum::<usize>())
+        }),
+        SortBy::Name => groups.sort_by_cached_key(|(folded, funcs)| {
+            folded.clone().unwrap_or_else(|| funcs[0].0.item.as_str().to_string())
+        }),
+    }
+
+    let width = groups
+        .iter()
+        .flat_map(|(_, funcs)| funcs)
+        .filter_map(|(func, _)| processor.section_name(func.addr))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+
+    let colored = args.colored();
+    let line = |func: &Addressed<Arc<Symbol>>, size: usize| {
+        let section = processor.section_name(func.addr).unwrap_or("");
+        let name = match args.simplify {
+            Some(ref simplify) => {
+                let name = tokenizing::render_omitting(func.item.name(), false, args.omit);
+                debugvault::replace::simplify(&name, simplify)
+            }
+            None => tokenizing::render_omitting(func.item.name(), colored, args.omit),
+        };
+
+        format!("{:0>10X}  {size:>8}  {section:<width$}  {name}", func.addr)
+    };
+
+    for (folded, funcs) in groups {
+        let Some(folded) = folded else {
+            let (func, size) = funcs[0];
+            outln!("{}", line(func, size));
+            continue;
+        };
+
+        let (first, _) = funcs[0];
+        let section = processor.section_name(first.addr).unwrap_or("");
+        let size: usize = funcs.iter().map(|(_, size)| size).sum();
+        outln!(
+            "{:0>10X}  {size:>8}  {section:<width$}  {folded} — {} instantiations",
+            first.addr,
+            funcs.len()
+        );
+
+        if args.expand {
+            for (func, size) in funcs {
+                outln!("  {}", line(func, size));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn libs(processor: &Processor, args: &Cli) -> Result<(), String> {
+    if args.resolve {
+        return crate::libs::print_tree(&processor.path);
+    }
+
+    for lib in binformat::libraries(&header(processor)?) {
+        outln!("{lib}");
+    }
+
+    Ok(())
+}
+
+fn security(processor: &Processor, _: &Cli) -> Result<(), String> {
+    for mitigation in security::mitigations(&header(processor)?) {
+        let status = match mitigation.status {
+            Status::Enabled => "enabled",
+            Status::Partial => "partial",
+            Status::Disabled => "disabled",
+        };
+
+        outln!("{:<20}{status}", mitigation.name);
+    }
+
+    Ok(())
+}
+
+fn headers(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let (segments, dynamic) = match header(processor)? {
+        object::File::Elf32(elf) => (elf::program_headers(&elf), elf::dynamic(&elf)),
+        object::File::Elf64(elf) => (elf::program_headers(&elf), elf::dynamic(&elf)),
+        _ => return Err("Only ELF objects have program headers.".to_string()),
+    };
+
+    outln!("Program headers:");
+    outln!(
+        "  {:<14} {:>10} {:>18} {:>10} {:>10} {:<5} {:>8}",
+        "TYPE", "OFFSET", "VIRTADDR", "FILESIZ", "MEMSIZ", "FLG", "ALIGN"
+    );
+    for s in segments {
+        outln!(
+            "  {:<14} {:>#10x} {:>#18x} {:>#10x} {:>#10x} {:<5} {:>#8x}",
+            s.kind, s.offset, s.vaddr, s.file_size, s.mem_size, s.flags, s.align
+        );
+    }
+
+    let dynamic = match dynamic {
+        Some(dynamic) => dynamic,
+        None => {
+            outln!("\nThere is no dynamic section.");
+            return Ok(());
+        }
+    };
+
+    outln!("\nDynamic section:");
+    for lib in dynamic.needed {
+        outln!("  {:<14} {lib}", "NEEDED");
+    }
+    if let Some(soname) = dynamic.soname {
+        outln!("  {:<14} {soname}", "SONAME");
+    }
+    for path in dynamic.rpath {
+        outln!("  {:<14} {path}", "RPATH");
+    }
+    for path in dynamic.runpath {
+        outln!("  {:<14} {path}", "RUNPATH");
+    }
+    if let Some(addr) = dynamic.init {
+        outln!("  {:<14} {addr:#x}", "INIT");
+    }
+    if let Some(addr) = dynamic.fini {
+        outln!("  {:<14} {addr:#x}", "FINI");
+    }
+
+    let width = processor.arch().address_size().map_or(8, |size| size.bytes() as u64);
+
+    let arrays = [
+        ("PREINIT_ARRAY", dynamic.preinit_array),
+        ("INIT_ARRAY", dynamic.init_array),
+        ("FINI_ARRAY", dynamic.fini_array),
+    ];
+
+    for (name, array) in arrays {
+        if let Some((addr, size)) = array {
+            outln!("  {name:<14} {addr:#x} ({} entries)", size / width);
+        }
+    }
+
+    if dynamic.flags != 0 {
+        outln!("  {:<14} {:#x}", "FLAGS", dynamic.flags);
+    }
+    if dynamic.flags_1 != 0 {
+        outln!("  {:<14} {:#x}", "FLAGS_1", dynamic.flags_1);
+    }
+
+    Ok(())
+}
+
+fn relocs(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let relocs = binformat::relocs::relocations(&header(processor)?);
+    if relocs.is_empty() {
+        outln!("There are no relocations.");
+        return Ok(());
+    }
+
+    outln!("{:<18} {:<20} {:<18} TARGET", "ADDRESS", "SECTION", "KIND");
+    for reloc in relocs {
+        let target = match reloc.addend {
+            addend if reloc.target.is_empty() => format!("{addend:#x}"),
+            0 => reloc.target,
+            addend if addend < 0 => format!("{}-{:#x}", reloc.target, addend.unsigned_abs()),
+            addend => format!("{}+{addend:#x}", reloc.target),
+        };
+
+        outln!("{:<#18x} {:<20} {:<18} {target}", reloc.addr, reloc.section, reloc.kind);
+    }
+
+    Ok(())
+}
+
+fn unwind(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let funcs = binformat::unwind::functions(&header(processor)?)?;
+    if funcs.is_empty() {
+        outln!("There is no unwind table.");
+    }
+
+    for func in funcs {
+        let name = match processor.index.get_sym_by_addr(func.start as usize) {
+            Some(sym) => sym.as_str().to_string(),
+            None => format!("func_{:x}", func.start),
+        };
+
+        outln!("{:0>10X}-{:0>10X}  {name}", func.start, func.end);
+        for row in func.rows {
+            outln!("  {:0>10X}  {}", row.addr, row.rules);
+        }
+    }
+
+    Ok(())
+}
+
+fn tls(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let obj = header(processor)?;
+    let segments = match obj {
+        object::File::Elf32(ref elf) => elf::program_headers(elf),
+        object::File::Elf64(ref elf) => elf::program_headers(elf),
+        object::File::Pe32(_) | object::File::Pe64(_) => {
+            let callbacks = pe::tls_callbacks(&obj);
+            if callbacks.is_empty() {
+                outln!("There are no TLS callbacks.");
+            }
+
+            for addr in callbacks {
+                match processor.index.get_sym_by_addr(addr as usize) {
+                    Some(symbol) => outln!("{addr:0>10X}  {}", symbol.as_str()),
+                    None => outln!("{addr:0>10X}"),
+                }
+            }
+            return Ok(());
+        }
+        _ => return Err("Only ELF and PE objects have thread-local storage.".to_string()),
+    };
+
+    if let Some(tls) = segments.iter().find(|segment| segment.kind == "TLS") {
+        outln!(
+            "TLS block of {:#x} bytes, initialized from {:#x}..{:#x}.",
+            tls.mem_size,
+            tls.vaddr,
+            tls.vaddr + tls.file_size
+        );
+    }
+
+    let variables = elf::tls_variables(&obj);
+    if variables.is_empty() {
+        outln!("There are no thread-local variables.");
+    }
+
+    for var in variables {
+        outln!("{:>#10x}  {:>8}  {}", var.offset, var.size, debugvault::demangle(var.name));
+    }
+
+    Ok(())
+}
+
+fn hex(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let (section, len) = match args.command {
+        Subcommand::Hex { ref section, len } => (section.as_deref(), len),
+        _ => (None, None),
+    };
+
+    let (start, end) = match section {
+        Some(name) => {
+            let section = processor
+                .sections()
+                .find(|section| section.name == *name)
+                .ok_or_else(|| format!("Section '{name}' could not be found."))?;
+            (section.start, section.end)
+        }
+        None => {
+            let start = args.start_addr.unwrap_or(0);
+            let end = match len {
+                Some(len) => start.saturating_add(len),
+                None => args.stop_addr.unwrap_or(usize::MAX),
+            };
+            (start, end)
+        }
+    };
+
+    let colored = args.colored();
+    let mut current = None;
+    for (section, addr, bytes) in processor.hex_lines(start, end) {
+        if current != Some(section.start) {
+            if current.is_some() {
+                outln!();
+            }
+            outln!("{}:", section.name);
+            current = Some(section.start);
+        }
+
+        let mut stream = TokenStream::new();
+        processor::tokenize_hex_line(&mut stream, addr, bytes, processor.aslr());
+        outln!("{}", tokenizing::render(&stream.inner, colored));
+    }
+
+    if current.is_none() {
+        return Err(format!("Nothing is loaded between {start:#x} and {end:#x}."));
+    }
+
+    Ok(())
+}
+
+fn sections(processor: &Processor, _: &Cli) -> Result<(), String> {
+    for section in processor.sections() {
+        let kind = format!("{:?}", section.kind);
+        outln!(
+            "{:0>10X}-{:0>10X}  {:>10}  {kind:<10}  {}",
+            section.start,
+            section.end,
+            section.end - section.start,
+            section.name
+        );
+    }
+
+    Ok(())
+}
+
+fn imports(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let obj = header(processor)?;
+    if !matches!(obj, object::File::Pe32(_) | object::File::Pe64(_)) {
+        for import in obj.imports().unwrap_or_default() {
+            let name = String::from_utf8_lossy(import.name());
+            let library = String::from_utf8_lossy(import.library());
+            outln!("{:<24}  {}", library, debugvault::demangle(&name));
+        }
+        return Ok(());
+    }
+
+    let imports = pe::imports(&obj).map_err(|err| format!("Failed to read imports: {err}."))?;
+    for import in imports {
+        let name = match (import.name, import.ordinal) {
+            (Some(name), _) => debugvault::demangle(name),
+            (None, Some(ordinal)) => format!("#{ordinal}"),
+            (None, None) => String::new(),
+        };
+
+        outln!("{:0>10X}  {:<24}  {name}", import.slot, import.library);
+    }
+
+    Ok(())
+}
+
+fn exports(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let obj = header(processor)?;
+    if !matches!(obj, object::File::Pe32(_) | object::File::Pe64(_)) {
+        for export in binformat::exports::exports(&obj) {
+            let name = debugvault::demangle(export.name);
+            match export.version {
+                Some(version) => outln!("{name}@{version}"),
+                None => outln!("{name}"),
+            }
+        }
+        return Ok(());
+    }
+
+    let exports = pe::exports(&obj).map_err(|err| format!("Failed to read exports: {err}."))?;
+    for export in exports {
+        let name = export.name.map(debugvault::demangle).unwrap_or_default();
+        match export.target {
+            PeExportTarget::Address(addr) => {
+                outln!("{addr:0>10X}  {:>5}  {name}", export.ordinal)
+            }
+            PeExportTarget::Forward(forward) => {
+                outln!("{:>10}  {:>5}  {name} -> {forward}", "", export.ordinal)
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn gpu(processor: &Processor, _: &Cli) -> Result<(), String> {
+    let blobs = binformat::gpu::blobs(&header(processor)?);
+    if blobs.is_empty() {
+        outln!("There is no embedded GPU code.");
+    }
+
+    for (idx, blob) in blobs.iter().enumerate() {
+        outln!(
+            "#{idx:<3} {:<6} {:<7} {:#x} in {} ({} bytes{})",
+            blob.format,
+            blob.target,
+            blob.offset,
+            blob.section,
+            blob.data.len(),
+            if blob.compressed { ", compressed" } else { "" }
+        );
+
+        for kernel in blob.kernels.iter() {
+            outln!("      {}", debugvault::demangle(kernel));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_matches(cache: &SymbolCache, pattern: &str) {
+    for sym in cache.search(pattern) {
+        outln!("{:0>10X}  {:>8}  {}", sym.addr, sym.size, sym.demangled);
+    }
+}
+
+fn filter(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let pattern = args.filter.as_deref().unwrap_or("");
+    print_matches(&SymbolCache::build(&header(processor)?, &processor.index), pattern);
+    Ok(())
+}
+
+/// Search the symbols cached by a previous run, without parsing the object.
+fn cached_filter(path: &Path, args: &Cli) -> Option<()> {
+    let data = binformat::map(path).ok()?;
+    let obj = object::File::parse(&data[..]).ok()?;
+    let cache = SymbolCache::load(&CacheKey::new(&obj, path))?;
+    print_matches(&cache, args.filter.as_deref()?);
+    Some(())
+}
+
+/// Parse the object once and print everything that was asked for, each in its own section.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let mut reports: Vec<(&str, Report)> = Vec::new();
+    match args.command {
+        Subcommand::Disasm => reports.push(("disasm", disasm)),
+        Subcommand::Sections => reports.push(("sections", sections)),
+        Subcommand::Functions { .. } => reports.push(("functions", functions)),
+        Subcommand::Imports => reports.push(("imports", imports)),
+        Subcommand::Exports => reports.push(("exports", exports)),
+        Subcommand::Extract { .. } => reports.push(("extract", crate::extract::extract)),
+        Subcommand::Hex { .. } => reports.push(("hex", hex)),
+        _ => {}
+    }
+    if args.names || args.command == Subcommand::Nm {
+        reports.push(("names", names));
+    }
+    if args.libs || args.command == Subcommand::Libs {
+        reports.push(("libs", libs));
+    }
+    if args.security {
+        reports.push(("security", security));
+    }
+    if args.headers {
+        reports.push(("headers", headers));
+    }
+    if args.relocs {
+        reports.push(("relocs", relocs));
+    }
+    if args.unwind {
+        reports.push(("unwind", unwind));
+    }
+    if args.tls {
+        reports.push(("tls", tls));
+    }
+    if args.filter.is_some() {
+        reports.push(("filter", filter));
+    }
+    if args.signature.is_some() {
+        reports.push(("signature", crate::signature::print));
+    }
+    if args.search.is_some() {
+        reports.push(("search", crate::search::print));
+    }
+    if args.gpu {
+        reports.push(("gpu", gpu));
+    }
+    if args.clones {
+        reports.push(("clones", crate::clones::print));
+    }
+    if args.registers {
+        reports.push(("registers", crate::registers::print));
+    }
+    if args.trace.is_some() {
+        reports.push(("trace", crate::trace::print));
+    }
+    if args.callgraph.is_some() {
+        reports.push(("callgraph", crate::callgraph::print));
+    }
+    if args.format.is_some() {
+        reports.push(("format", crate::html::print));
+    }
+    if args.dump_bytes.is_some() {
+        reports.push(("dump-bytes", crate::extract::dump_bytes));
+    }
+    if args.extract_gpu.is_some() {
+        reports.push(("extract-gpu", crate::extract::extract_gpu));
+    }
+    if args.extract_section.is_some() {
+        reports.push(("extract-section", crate::extract::extract_section));
+    }
+    if args.add_section.is_some() {
+        reports.push(("add-section", crate::extract::add_section));
+    }
+
+    if args.format.is_some() && reports.len() > 1 {
+        return Err("A page of the listing can't be combined with other information.".to_string());
+    }
+
+    if args.is_remote() {
+        if reports.len() > 1 {
+            return Err("Objects read over HTTP can only be listed with --names.".to_string());
+        }
+        return crate::remote::run(args);
+    }
+
+    let objects = objects(args);
+    match &objects[..] {
+        [] => return Err("The archive doesn't contain any objects.".to_string()),
+        [object] => return run_reports(object, args, &reports),
+        _ => {}
+    }
+
+    // Objects are processed in parallel, but each one's information is printed in order.
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut failed = 0;
+    for (chunk_idx, chunk) in objects.chunks(threads).enumerate() {
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|object| {
+                    let reports = &reports;
+                    scope.spawn(move || output::capture(|| run_reports(object, args, reports)))
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        for (idx, (object, (result, text))) in chunk.iter().zip(results).enumerate() {
+            if chunk_idx != 0 || idx != 0 {
+                outln!();
+            }
+            outln!("==> {} <==", object.name());
+            output::text(&text);
+
+            if let Err(err) = result {
+                eprintln!("{}: {err}", object.name());
+                failed += 1;
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(format!("Failed to print {failed} of {} objects.", objects.len())),
+    }
+}
+
+/// An object information is printed about, or a member of an archive.
+struct Object<'a> {
+    path: &'a Path,
+    member: Option<String>,
+}
+
+impl Object<'_> {
+    /// Path of the object, followed by the member in parentheses like `nm` does.
+    fn name(&self) -> String {
+        match self.member {
+            Some(ref member) => format!("{}({member})", self.path.display()),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+/// The objects that were given, with archives replaced by every object in them unless a member
+/// was selected with `--member`.
+fn objects(args: &Cli) -> Vec<Object<'_>> {
+    let flat = args.raw || args.layout.is_some();
+    let mut objects = Vec::new();
+
+    for path in args.objects() {
+        let members = match (flat, &args.member) {
+            (false, None) => binformat::map(path).ok().and_then(|data| {
+                let members = binformat::archive::members(&data).ok()?;
+                Some(members.into_iter().map(|member| member.name).collect::<Vec<_>>())
+            }),
+            _ => None,
+        };
+
+        match members {
+            Some(members) => objects.extend(members.into_iter().map(|member| Object {
+                path,
+                member: Some(member),
+            })),
+            None => objects.push(Object {
+                path,
+                member: args.member.clone(),
+            }),
+        }
+    }
+
+    objects
+}
+
+/// Print everything that was asked for about an object.
+fn run_reports(object: &Object, args: &Cli, reports: &[(&str, Report)]) -> Result<(), String> {
+    let flat = args.raw
+        || args.layout.is_some()
+        || args.kallsyms.is_some()
+        || args.symbol_map.is_some();
+    let path = object.path;
+    if reports.len() == 1
+        && args.filter.is_some()
+        && !flat
+        && object.member.is_none()
+        && cached_filter(path, args).is_some()
+    {
+        return Ok(());
+    }
+
+    let processor = match object.member {
+        Some(ref member) => Processor::parse_member(path, Some(member.as_str())),
+        None => Processor::parse_from_args(path),
+    };
+    let processor = processor.map_err(|err| format!("{err:?}"))?;
+
+    for (idx, (name, report)) in reports.iter().enumerate() {
+        if reports.len() > 1 {
+            if idx != 0 {
+                outln!();
+            }
+            outln!("==> {name} <==");
+        }
+
+        report(&processor, args)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Search for byte patterns with wildcards, e.g. to find the inlined copies of some code or
+//! check how unique a signature is.
+
+use crate::output::outln;
+use commands::Cli;
+use processor::{BlockContent, Processor};
+use processor_shared::{Section, SectionKind};
+use tokenizing::TokenStream;
+
+/// Maximum number of instructions printed after a match.
+const CONTEXT: usize = 4;
+
+/// Addresses in `section` at which `pattern` matches, where `None` matches any byte.
+fn matches(section: &Section, pattern: &[Option<u8>]) -> Vec<usize> {
+    let bytes = section.bytes();
+    if pattern.is_empty() || bytes.len() < pattern.len() {
+        return Vec::new();
+    }
+
+    // Anchor on the first fixed byte, so most windows are rejected by a single compare.
+    let anchor = pattern.iter().position(Option::is_some);
+
+    let mut found = Vec::new();
+    for offset in 0..=bytes.len() - pattern.len() {
+        if let Some(anchor) = anchor {
+            if Some(bytes[offset + anchor]) != pattern[anchor] {
+                continue;
+            }
+        }
+
+        let window = &bytes[offset..offset + pattern.len()];
+        let is_match = window.iter().zip(pattern).all(|(&b, p)| p.is_none() || *p == Some(b));
+        if is_match {
+            found.push(section.start + offset);
+        }
+    }
+
+    found
+}
+
+/// Symbol the address lies in, with the offset into it.
+fn enclosing_symbol(processor: &Processor, addr: usize) -> String {
+    let syms = &processor.index.syms;
+    let sym = match syms.search(addr) {
+        Ok(idx) => &syms[idx],
+        Err(0) => return "<unknown>".to_string(),
+        Err(idx) => &syms[idx - 1],
+    };
+
+    match addr - sym.addr {
+        0 => sym.item.as_str().to_string(),
+        offset => format!("{}+{offset:#x}", sym.item.as_str()),
+    }
+}
+
+/// Text of the instructions covering the `len` bytes at `addr`, limited to [`CONTEXT`].
+fn context(processor: &Processor, addr: usize, len: usize, colored: bool) -> Vec<(usize, String)> {
+    let mut insts = Vec::new();
+    let mut ip = addr;
+
+    while ip < addr + len && insts.len() < CONTEXT {
+        let Some(inst) = processor.instruction_by_addr(ip) else {
+            break;
+        };
+
+        for block in processor.parse_blocks(ip) {
+            if let BlockContent::Instruction { .. } = block.content {
+                let mut stream = TokenStream::new();
+                block.tokenize(&mut stream, processor.aslr());
+                insts.push((ip, tokenizing::render(&stream.inner, colored)));
+            }
+        }
+
+        ip += processor.instruction_width(inst);
+    }
+
+    insts
+}
+
+/// Print every match of the pattern given by `--search`, with the instructions at the match if
+/// it starts at an instruction.
+pub fn print(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let Some(ref pattern) = args.search else {
+        return Ok(());
+    };
+
+    let mut count = 0;
+    for section in processor.sections() {
+        for addr in matches(section, pattern) {
+            count += 1;
+            outln!(
+                "{addr:0>10X}  {:<20} {}",
+                section.name,
+                enclosing_symbol(processor, addr)
+            );
+
+            if section.kind == SectionKind::Code {
+                for (addr, text) in context(processor, addr, pattern.len(), args.colored()) {
+                    outln!("  {addr:0>10X}  {text}");
+                }
+            }
+        }
+    }
+
+    match count {
+        0 => outln!("The pattern doesn't occur in the object."),
+        1 => outln!("\n1 match."),
+        count => outln!("\n{count} matches."),
+    }
+
+    Ok(())
+}
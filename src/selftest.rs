@@ -0,0 +1,84 @@
+//! Checking the decoders against a corpus of instructions and the text they're expected to
+//! decode to, such that they can be validated against the instruction mix of a workload.
+
+use std::path::Path;
+
+struct Entry<'a> {
+    line: usize,
+    arch: &'a str,
+    bytes: Vec<u8>,
+    expected: &'a str,
+}
+
+/// Entries of a corpus with one `arch | bytes | text` entry per line.
+fn parse_corpus(corpus: &str) -> Result<Vec<Entry<'_>>, String> {
+    let mut entries = Vec::new();
+
+    for (idx, line) in corpus.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.splitn(3, '|').map(str::trim);
+        let (Some(arch), Some(bytes), Some(expected)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!(
+                "Line {} isn't of the form 'arch | bytes | text'.",
+                idx + 1
+            ));
+        };
+
+        let bytes = commands::parse_hex_bytes(bytes)
+            .ok_or_else(|| format!("Line {} has invalid bytes '{bytes}'.", idx + 1))?;
+
+        entries.push(Entry {
+            line: idx + 1,
+            arch,
+            bytes,
+            expected,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Collapse whitespace, such that the formatting of the corpus doesn't matter.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode every entry of the corpus at `path`, returns whether all of them matched.
+pub fn run(path: &Path) -> Result<bool, String> {
+    let corpus =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+
+    let entries = parse_corpus(&corpus)?;
+    let mut mismatches = 0;
+
+    for entry in entries.iter() {
+        let insts = processor::decode_bytes(entry.arch, &entry.bytes, 0)
+            .map_err(|err| format!("Line {}: {err:?}", entry.line))?;
+
+        // Bytes that decode to more than one instruction are shown as such.
+        let decoded = insts.into_iter().map(|(_, text)| text).collect::<Vec<_>>().join("; ");
+        if normalize(&decoded) == normalize(entry.expected) {
+            continue;
+        }
+
+        let bytes: Vec<String> = entry.bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        println!("line {}: {} | {}", entry.line, entry.arch, bytes.join(" "));
+        println!("  expected: {}", entry.expected);
+        println!("  decoded:  {decoded}");
+        mismatches += 1;
+    }
+
+    let matched = entries.len() - mismatches;
+    println!(
+        "{matched} of {} instructions decoded as expected.",
+        entries.len()
+    );
+
+    Ok(mismatches == 0)
+}
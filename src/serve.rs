@@ -0,0 +1,345 @@
+//! Symbolication service turning the `module,build-id,offset` frames of a backtrace into
+//! demangled function names and source locations.
+//!
+//! Queries are either sent as the body of a `POST /symbolize`, one per line, or as the `q`
+//! parameter of a `GET /symbolize`. Offsets are relative to where the module was loaded. A
+//! module is looked up by build-id like a separate debug file, or by its path inside the root
+//! directory if one is given, such that clients can't open any other file. The modules opened
+//! last stay in memory.
+
+use binformat::RawSymbol;
+use debugvault::{Index, SplitDwarf};
+use object::{Object, ObjectSymbol, SymbolKind};
+use processor_shared::{AddressMap, Addressed};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Largest request body that's accepted.
+const MAX_BODY: usize = 1 << 20;
+
+/// Most modules kept in memory, the ones opened first are dropped to make room.
+const MAX_MODULES: usize = 64;
+
+/// Longest request or header line that's accepted.
+const MAX_LINE: u64 = 8 << 10;
+
+/// Most headers of a request that are accepted.
+const MAX_HEADERS: usize = 64;
+
+/// Most requests handled at once, further connections wait until one of them is done.
+const MAX_CONNECTIONS: usize = 16;
+
+/// How long a client may take to send each part of its request.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Module {
+    index: Index,
+    /// Address the module is linked at, which offsets are relative to.
+    image_base: usize,
+}
+
+#[derive(Default)]
+struct Opened {
+    /// Modules by their build-id.
+    modules: HashMap<String, Arc<Module>>,
+    /// Keys of `modules`, in the order they were opened.
+    order: VecDeque<String>,
+}
+
+struct Modules {
+    /// Directory modules are opened from by their path, like a sysroot.
+    root: Option<PathBuf>,
+    opened: Mutex<Opened>,
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 == 1 {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(s.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+fn symbols<'data: 'file, 'file>(
+    obj: &'file object::File<'data>,
+) -> impl Iterator<Item = Addressed<RawSymbol<'data>>> + 'file {
+    obj.symbols().chain(obj.dynamic_symbols()).filter_map(|sym| {
+        if sym.kind() != SymbolKind::Text || sym.is_undefined() {
+            return None;
+        }
+
+        Some(Addressed {
+            addr: sym.address() as usize,
+            item: RawSymbol {
+                name: sym.name().ok()?,
+                module: None,
+            },
+        })
+    })
+}
+
+fn open(path: &Path, build_id: &[u8]) -> Result<Module, String> {
+    let data = binformat::map(path).map_err(|err| format!("failed to read {path:?}: {err}"))?;
+    let obj = object::File::parse(&data[..]).map_err(|err| format!("{path:?}: {err}"))?;
+
+    if !build_id.is_empty() && obj.build_id().ok().flatten() != Some(build_id) {
+        return Err(format!("{path:?} has a different build-id"));
+    }
+
+    let debug_data =
+        debugvault::debuglink::locate(&obj, path).and_then(|path| binformat::map(path).ok());
+    let debug_obj = debug_data.as_deref().and_then(|data| object::File::parse(data).ok());
+
+    let mut syms = AddressMap::default();
+    syms.mapping.extend(symbols(&obj));
+    if let Some(ref debug_obj) = debug_obj {
+        syms.mapping.extend(symbols(debug_obj));
+    }
+
+    let index = Index::parse(&obj, debug_obj.as_ref(), path, &SplitDwarf::default(), syms)
+        .map_err(|err| format!("{path:?}: {err}"))?;
+
+    Ok(Module {
+        index,
+        image_base: processor::Aslr::new(&obj).image_base,
+    })
+}
+
+/// File at `path` inside `root`, unless it leads out of it.
+fn within(root: &Path, path: &str) -> Option<PathBuf> {
+    let path = root.join(path.trim_start_matches('/')).canonicalize().ok()?;
+    (path.starts_with(root) && path.is_file()).then_some(path)
+}
+
+/// Module of a query, opened once per build-id.
+fn module(modules: &Modules, path: &str, build_id: &[u8]) -> Result<Arc<Module>, String> {
+    if build_id.is_empty() {
+        return Err("modules can only be found by their build-id".to_string());
+    }
+
+    let key: String = build_id.iter().map(|byte| format!("{byte:02x}")).collect();
+    if let Some(module) = modules.opened.lock().unwrap().modules.get(&key) {
+        return Ok(module.clone());
+    }
+
+    let path = modules
+        .root
+        .as_deref()
+        .and_then(|root| within(root, path))
+        .or_else(|| debugvault::debuglink::find(build_id))
+        .ok_or_else(|| format!("no module with build-id {key} could be found"))?;
+
+    let module = Arc::new(open(&path, build_id)?);
+    let mut opened = modules.opened.lock().unwrap();
+    if !opened.modules.contains_key(&key) {
+        if opened.order.len() >= MAX_MODULES {
+            let oldest = opened.order.pop_front().unwrap_or_default();
+            opened.modules.remove(&oldest);
+        }
+        opened.order.push_back(key.clone());
+    }
+    opened.modules.insert(key, module.clone());
+    Ok(module)
+}
+
+/// Function and source location of a `module,build-id,offset` query.
+fn symbolize(modules: &Modules, query: &str) -> Value {
+    let mut fields = query.trim().rsplitn(3, ',');
+    let (Some(offset), Some(build_id), Some(path)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return json!({ "query": query, "error": "expected module,build-id,offset" });
+    };
+
+    let offset = offset.trim_start_matches("0x");
+    let (Ok(offset), Some(build_id)) = (usize::from_str_radix(offset, 16), parse_hex(build_id))
+    else {
+        return json!({ "query": query, "error": "invalid build-id or offset" });
+    };
+
+    let module = match module(modules, path, &build_id) {
+        Ok(module) => module,
+        Err(err) => return json!({ "query": query, "error": err }),
+    };
+
+    let Some(addr) = module.image_base.checked_add(offset) else {
+        return json!({ "query": query, "error": "offset is out of range" });
+    };
+
+    let index = &module.index;
+    let mut result = json!({ "query": query });
+
+    let func = match index.syms.search(addr) {
+        Ok(idx) => Some(idx),
+        Err(0) => None,
+        Err(idx) => Some(idx - 1),
+    };
+
+    let func = func.map(|idx| &index.syms[idx]);
+    if let Some(func) = func {
+        result["function"] = json!(func.item.as_str());
+        result["function_offset"] = json!(addr - func.addr);
+    }
+
+    let attr = match index.file_attrs.search(addr) {
+        Ok(idx) => Some(idx),
+        Err(0) => None,
+        Err(idx) => Some(idx - 1),
+    };
+
+    // Line table rows before the start of the function belong to another one.
+    let attr = attr.map(|idx| &index.file_attrs[idx]);
+    if let Some(attr) = attr.filter(|attr| func.is_none_or(|func| attr.addr >= func.addr)) {
+        result["file"] = json!(attr.item.path.display().to_string());
+        result["line"] = json!(attr.item.line);
+    }
+
+    result
+}
+
+/// Decode the `+` and `%XX` escapes of a query parameter.
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut chars = s.bytes();
+
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [chars.next().unwrap_or(0), chars.next().unwrap_or(0)];
+                let hex = std::str::from_utf8(&hex).unwrap_or("");
+                bytes.push(u8::from_str_radix(hex, 16).unwrap_or(b'?'));
+            }
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Read a line of at most [`MAX_LINE`] bytes.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> std::io::Result<usize> {
+    let len = reader.by_ref().take(MAX_LINE).read_line(line)?;
+    if len as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "line is too long"));
+    }
+    Ok(len)
+}
+
+fn handle(mut stream: TcpStream, modules: &Modules) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    read_line(&mut reader, &mut request)?;
+
+    let mut content_length = 0;
+    for headers in 0.. {
+        let mut header = String::new();
+        if read_line(&mut reader, &mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        if headers == MAX_HEADERS {
+            return respond(
+                &mut stream,
+                "431 Request Header Fields Too Large",
+                r#"{"error":"too many headers"}"#,
+            );
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, params) = target.split_once('?').unwrap_or((target, ""));
+
+    if path != "/symbolize" {
+        return respond(&mut stream, "404 Not Found", r#"{"error":"not found"}"#);
+    }
+
+    let queries: Vec<String> = match method {
+        "GET" => params
+            .split('&')
+            .filter_map(|param| param.strip_prefix("q="))
+            .map(percent_decode)
+            .collect(),
+        "POST" if content_length <= MAX_BODY => {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            String::from_utf8_lossy(&body)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+        "POST" => {
+            return respond(
+                &mut stream,
+                "413 Payload Too Large",
+                r#"{"error":"too large"}"#,
+            )
+        }
+        _ => {
+            return respond(
+                &mut stream,
+                "405 Method Not Allowed",
+                r#"{"error":"not allowed"}"#,
+            )
+        }
+    };
+
+    let results: Vec<Value> = queries.iter().map(|query| symbolize(modules, query)).collect();
+    respond(&mut stream, "200 OK", &Value::Array(results).to_string())
+}
+
+/// Serve symbolication requests on `addr` until the process is killed, opening modules by their
+/// path only inside `root`.
+pub fn run(addr: &str, root: Option<&Path>) -> Result<(), String> {
+    let root = root
+        .map(|root| root.canonicalize().map_err(|err| format!("Failed to open {root:?}: {err}.")))
+        .transpose()?;
+
+    let listener =
+        TcpListener::bind(addr).map_err(|err| format!("Failed to listen on {addr}: {err}."))?;
+    let modules = Modules {
+        root,
+        opened: Mutex::default(),
+    };
+
+    println!("Symbolizing on http://{addr}/symbolize.");
+    std::thread::scope(|s| {
+        // Each worker accepts and handles one connection at a time.
+        for _ in 0..MAX_CONNECTIONS {
+            s.spawn(|| {
+                for stream in listener.incoming().flatten() {
+                    if let Err(err) = handle(stream, &modules) {
+                        eprintln!("Failed to handle request: {err}.");
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! Search for functions by the types of their parameters and return value, such as every
+//! function taking a `&str` and returning a `Result`.
+//!
+//! Queries and the types of a function are parsed into the same structure and compared by
+//! it, so `core::result::Result<u64, core::num::error::ParseIntError>` is matched by
+//! `Result<*, *>` and `char const*` by `const char *`. Lifetimes, mutability and `const` are
+//! ignored.
+
+use crate::output::outln;
+use commands::Cli;
+use debugvault::Signature;
+use processor::Processor;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Punct(char),
+    Arrow,
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let is_ident = |c: char| c.is_alphanumeric() || "_:$~.{}#".contains(c);
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '-' if chars.next_if_eq(&'>').is_some() => tokens.push(Token::Arrow),
+            '&' | '*' | '<' | '>' | ',' | '(' | ')' | '[' | ']' | ';' => {
+                tokens.push(Token::Punct(c))
+            }
+            // Lifetimes don't change which type is referred to.
+            '\'' => while chars.next_if(|&c| is_ident(c)).is_some() {},
+            c if is_ident(c) => {
+                let mut ident = String::from(c);
+                while let Some(c) = chars.next_if(|&c| is_ident(c)) {
+                    ident.push(c);
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return None,
+        }
+    }
+
+    Some(tokens)
+}
+
+#[derive(Debug, PartialEq)]
+enum Type {
+    /// `*` in a query, matches any type.
+    Any,
+    Ref(Box<Type>),
+    Ptr(Box<Type>),
+    /// Path of the type with its generic arguments, tuples are named `()` and slices and
+    /// arrays `[]`.
+    Named {
+        path: Vec<String>,
+        args: Vec<Type>,
+    },
+}
+
+impl Type {
+    fn named(name: &str, args: Vec<Type>) -> Self {
+        Self::Named {
+            path: name.split("::").map(String::from).collect(),
+            args,
+        }
+    }
+
+    /// Parse a type as named by debug info, types that can't be parsed only match themselves.
+    fn parse(text: &str) -> Self {
+        tokenize(text)
+            .and_then(|tokens| {
+                let mut parser = Parser { tokens, pos: 0 };
+                let ty = parser.parse_type()?;
+                parser.at_end().then_some(ty)
+            })
+            .unwrap_or_else(|| Self::named(text, Vec::new()))
+    }
+
+    fn is_unit(&self) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Named { path, args } => args.is_empty() && (path == &["()"] || path == &["void"]),
+            _ => false,
+        }
+    }
+
+    /// Whether `self` as part of a query matches the type `other`.
+    fn matches(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Self::Any, _) => true,
+            (Self::Ref(a), Self::Ref(b)) | (Self::Ptr(a), Self::Ptr(b)) => a.matches(b),
+            (
+                Self::Named { path, args },
+                Self::Named {
+                    path: opath,
+                    args: oargs,
+                },
+            ) => {
+                // Arguments that are left out match any, except for the unit type.
+                let args_match = if args.is_empty() {
+                    path != &["()"] || oargs.is_empty()
+                } else {
+                    args.len() <= oargs.len() && args.iter().zip(oargs).all(|(a, b)| a.matches(b))
+                };
+
+                opath.ends_with(path) && args_match
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Words that qualify a type without changing which type it is.
+const QUALIFIERS: &[&str] = &[
+    "const", "volatile", "mut", "struct", "class", "enum", "union",
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let matches = self.peek() == Some(token);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        self.eat(&Token::Ident(ident.to_string()))
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.tokens.len()
+    }
+
+    fn is_rest(&self) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == ".." || ident == "...")
+    }
+
+    fn parse_type(&mut self) -> Option<Type> {
+        let mut ty = match self.tokens.get(self.pos)?.clone() {
+            Token::Punct('&') => {
+                self.pos += 1;
+                self.eat_ident("mut");
+                Type::Ref(Box::new(self.parse_type()?))
+            }
+            Token::Punct('*') => {
+                self.pos += 1;
+                if self.eat_ident("const") || self.eat_ident("mut") {
+                    Type::Ptr(Box::new(self.parse_type()?))
+                } else {
+                    Type::Any
+                }
+            }
+            Token::Punct('(') => {
+                self.pos += 1;
+                let (args, _) = self.parse_list(')')?;
+                Type::named("()", args)
+            }
+            Token::Punct('[') => {
+                self.pos += 1;
+                let elem = self.parse_type()?;
+                // The length of an array isn't part of the comparison.
+                if self.eat(&Token::Punct(';')) {
+                    self.pos += 1;
+                }
+                if !self.eat(&Token::Punct(']')) {
+                    return None;
+                }
+                Type::named("[]", vec![elem])
+            }
+            Token::Ident(_) => self.parse_named()?,
+            _ => return None,
+        };
+
+        loop {
+            if self.eat(&Token::Punct('*')) {
+                ty = Type::Ptr(Box::new(ty));
+            } else if self.eat(&Token::Punct('&')) {
+                ty = Type::Ref(Box::new(ty));
+            } else if self.eat(&Token::Punct('[')) {
+                if matches!(self.peek(), Some(Token::Ident(_))) {
+                    self.pos += 1;
+                }
+                if !self.eat(&Token::Punct(']')) {
+                    return None;
+                }
+                ty = Type::named("[]", vec![ty]);
+            } else if !QUALIFIERS.iter().any(|qualifier| self.eat_ident(qualifier)) {
+                return Some(ty);
+            }
+        }
+    }
+
+    /// Type made of one or more words, such as `unsigned int` or `alloc::string::String`.
+    fn parse_named(&mut self) -> Option<Type> {
+        let mut words = Vec::new();
+        while let Some(Token::Ident(ident)) = self.peek() {
+            if self.is_rest() {
+                break;
+            }
+            if !QUALIFIERS.contains(&ident.as_str()) {
+                words.push(ident.clone());
+            }
+            self.pos += 1;
+        }
+
+        if words.is_empty() {
+            return None;
+        }
+
+        let name = words.join(" ");
+        let mut args = Vec::new();
+        if self.eat(&Token::Punct('<')) {
+            args = self.parse_list('>')?.0;
+        }
+
+        // Function pointers are only compared by being one.
+        if name == "fn" && self.eat(&Token::Punct('(')) {
+            self.parse_list(')')?;
+            if self.eat(&Token::Arrow) {
+                self.parse_type()?;
+            }
+        }
+
+        Some(Type::named(&name, args))
+    }
+
+    /// Types separated by commas up to `close`, and whether they end in `..`.
+    fn parse_list(&mut self, close: char) -> Option<(Vec<Type>, bool)> {
+        let mut types = Vec::new();
+        let mut rest = false;
+
+        while !self.eat(&Token::Punct(close)) {
+            // Nothing may follow `..`.
+            if rest || !types.is_empty() && !self.eat(&Token::Punct(',')) {
+                return None;
+            }
+
+            if self.is_rest() {
+                self.pos += 1;
+                rest = true;
+            } else {
+                types.push(self.parse_type()?);
+            }
+        }
+
+        Some((types, rest))
+    }
+}
+
+/// C's way of saying a function doesn't take any parameters.
+fn strip_void(params: &mut Vec<Type>) {
+    if matches!(params.as_slice(), [ty] if ty.is_unit()) && params[0] != Type::Any {
+        params.clear();
+    }
+}
+
+struct Query {
+    params: Vec<Type>,
+    /// Whether any parameters may follow `params`.
+    rest: bool,
+    /// `None` if any return type matches.
+    ret: Option<Type>,
+}
+
+impl Query {
+    /// Parse either `fn(params) -> ret` or `ret (params)`, where the return type is optional.
+    fn parse(text: &str) -> Option<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+
+        let is_rust = parser.tokens.starts_with(&[Token::Ident("fn".into()), Token::Punct('(')]);
+        let mut ret = None;
+        if is_rust {
+            parser.pos += 2;
+        } else {
+            if parser.peek() != Some(&Token::Punct('(')) {
+                ret = Some(parser.parse_type()?);
+            }
+            if !parser.eat(&Token::Punct('(')) {
+                return None;
+            }
+        }
+
+        let (mut params, rest) = parser.parse_list(')')?;
+        if is_rust && parser.eat(&Token::Arrow) {
+            ret = Some(parser.parse_type()?);
+        }
+
+        strip_void(&mut params);
+        parser.at_end().then_some(Self { params, rest, ret })
+    }
+
+    fn matches(&self, candidate: &Candidate) -> bool {
+        let params_match = if self.rest {
+            self.params.len() <= candidate.params.len()
+        } else {
+            self.params.len() == candidate.params.len()
+        };
+
+        let ret_match = match (&self.ret, &candidate.ret) {
+            (None, _) | (_, Ret::Unknown) => true,
+            (Some(ret), Ret::Unit) => ret.is_unit(),
+            (Some(ret), Ret::Type(ty)) => ret.matches(ty),
+        };
+
+        params_match
+            && ret_match
+            && self.params.iter().zip(&candidate.params).all(|(a, b)| a.matches(b))
+    }
+}
+
+enum Ret {
+    Unit,
+    Type(Type),
+    /// Mangled names don't include the return type of most functions.
+    Unknown,
+}
+
+struct Candidate {
+    params: Vec<Type>,
+    ret: Ret,
+}
+
+impl Candidate {
+    /// Candidate from a signature that was read from debug info or the mangled name, where
+    /// only the latter can lack a return type without it being `void`.
+    fn new(signature: &Signature, from_debug_info: bool) -> Self {
+        Self {
+            params: signature.params.iter().map(|param| Type::parse(param)).collect(),
+            ret: match signature.ret {
+                Some(ref ret) => Ret::Type(Type::parse(ret)),
+                None if from_debug_info => Ret::Unit,
+                None => Ret::Unknown,
+            },
+        }
+    }
+}
+
+/// Print the functions whose signature matches the `--signature` query.
+pub fn print(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let text = args.signature.as_deref().unwrap_or("");
+    let query = Query::parse(text).ok_or_else(|| {
+        format!(
+            "Signature '{text}' couldn't be parsed, expected e.g. 'fn(&str, ..) -> Result<*, *>'."
+        )
+    })?;
+
+    for func in processor.index.functions() {
+        let (signature, candidate) = match processor.index.get_signature_by_addr(func.addr) {
+            Some(signature) => (signature.clone(), Candidate::new(signature, true)),
+            None => match debugvault::demangle_signature(func.item.mangled()) {
+                Some(signature) => {
+                    let candidate = Candidate::new(&signature, false);
+                    (signature, candidate)
+                }
+                None => continue,
+            },
+        };
+
+        if !query.matches(&candidate) {
+            continue;
+        }
+
+        let name = func.item.as_str();
+        let params = signature.params.join(", ");
+        match signature.ret {
+            Some(ret) => outln!("{:0>10X}  {name}({params}) -> {ret}", func.addr),
+            None => outln!("{:0>10X}  {name}({params})", func.addr),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! Shipping slim objects that retain only selected symbols, e.g. the ones needed to symbolize
+//! crash reports.
+
+use binformat::strip::Symbols;
+use commands::{Cli, Subcommand};
+use regex::Regex;
+
+/// Strip the object given by `bite strip` and write it to `--output`.
+pub fn run(args: &Cli) -> Result<(), String> {
+    let Subcommand::Strip { object: ref path, ref keep, strip_debug } = args.command else {
+        return Ok(());
+    };
+    let Some(ref output) = args.output else {
+        return Ok(());
+    };
+
+    let keep = match keep {
+        Some(pattern) => {
+            Some(Regex::new(pattern).map_err(|err| format!("Invalid pattern to keep: {err}."))?)
+        }
+        None => None,
+    };
+
+    // Both the mangled and demangled name can be matched, the latter such that a pattern like
+    // `^ns::` selects an entire namespace.
+    let matches = |name: &str| {
+        keep.as_ref()
+            .is_some_and(|re| re.is_match(name) || re.is_match(&debugvault::demangle(name)))
+    };
+
+    let symbols = match (&keep, strip_debug) {
+        (Some(_), _) => Symbols::Matching(&matches),
+        (None, true) => Symbols::All,
+        (None, false) => Symbols::None,
+    };
+
+    let binary = binformat::map(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+    let obj = object::File::parse(&binary[..])
+        .map_err(|_| "Object doesn't have a header to strip.".to_string())?;
+    let stripped = binformat::strip::strip(&obj, symbols)?;
+
+    std::fs::write(output, &stripped.data)
+        .map_err(|err| format!("Failed to write {output:?}: {err}."))?;
+
+    // Keep the object executable.
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = std::fs::set_permissions(output, metadata.permissions());
+    }
+
+    println!(
+        "Kept {} of {} symbols and removed {} sections, {} bytes down to {}.",
+        stripped.symbols_kept,
+        stripped.symbols_total,
+        stripped.sections_removed,
+        binary.len(),
+        stripped.data.len()
+    );
+
+    Ok(())
+}
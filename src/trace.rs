@@ -0,0 +1,154 @@
+//! Listing of the functions an instruction trace went through, with the number of times each
+//! of their instructions was executed.
+//!
+//! A trace has an address per line, taken from the first field that is the address of an
+//! instruction. This covers `perf script` output of Intel PT traces decoded per instruction
+//! (`--itrace=i1i`) and QEMU exec logs, as long as every instruction is logged rather than just
+//! the start of each translation block (`-accel tcg,one-insn-per-tb=on`).
+
+use crate::output::outln;
+use commands::Cli;
+use processor::{BlockContent, Processor};
+use std::collections::HashMap;
+use tokenizing::TokenStream;
+
+/// Address of a line of a QEMU exec log, e.g. `Trace 0: 0x7f.. [00000000/0000000000401126/..]`.
+fn qemu_addr(line: &str) -> Option<usize> {
+    let (_, fields) = line.strip_prefix("Trace ")?.split_once('[')?;
+    let addr = fields.split('/').nth(1)?;
+    usize::from_str_radix(addr, 16).ok()
+}
+
+fn parse_hex(token: &str) -> Option<usize> {
+    let token = token.trim_end_matches(':');
+    let token = token.strip_prefix("0x").unwrap_or(token);
+    usize::from_str_radix(token, 16).ok()
+}
+
+/// Number of times each instruction was executed, by its address in the object.
+fn counts(processor: &Processor, trace: &str, offset: isize) -> (HashMap<usize, usize>, usize) {
+    let mut counts = HashMap::new();
+    let mut unmapped = 0;
+
+    let is_inst = |addr: usize| processor.instruction_by_addr(addr).is_some();
+    for line in trace.lines().filter(|line| !line.trim().is_empty()) {
+        let addr = match qemu_addr(line) {
+            Some(addr) => Some(addr.wrapping_add_signed(offset)).filter(|&addr| is_inst(addr)),
+            None => line
+                .split_whitespace()
+                .filter_map(parse_hex)
+                .map(|addr| addr.wrapping_add_signed(offset))
+                .find(|&addr| is_inst(addr)),
+        };
+
+        match addr {
+            Some(addr) => *counts.entry(addr).or_default() += 1,
+            None => unmapped += 1,
+        }
+    }
+
+    (counts, unmapped)
+}
+
+/// Instructions of the function containing `addr`, with their text.
+fn function(processor: &Processor, addr: usize, colored: bool) -> (usize, Vec<(usize, String)>) {
+    let syms = &processor.index.syms;
+    let (start, end) = match syms.search(addr) {
+        Ok(idx) => (syms[idx].addr, syms.get(idx + 1).map(|sym| sym.addr)),
+        Err(0) => (addr, syms.first().map(|sym| sym.addr)),
+        Err(idx) => (syms[idx - 1].addr, syms.get(idx).map(|sym| sym.addr)),
+    };
+
+    let section_end = processor.section_by_addr(start).map_or(start, |section| section.end);
+    let end = end.unwrap_or(section_end).min(section_end);
+
+    let mut insts = Vec::new();
+    let mut addr = start;
+    while addr < end {
+        let Some(inst) = processor.instruction_by_addr(addr) else {
+            addr += 1;
+            continue;
+        };
+
+        for block in processor.parse_blocks(addr) {
+            if let BlockContent::Instruction { .. } = block.content {
+                let mut stream = TokenStream::new();
+                block.tokenize(&mut stream, processor.aslr());
+                insts.push((addr, tokenizing::render(&stream.inner, colored)));
+            }
+        }
+
+        addr += processor.instruction_width(inst);
+    }
+
+    (start, insts)
+}
+
+/// Print the functions executed by the trace given by `--trace`.
+pub fn print(processor: &Processor, args: &Cli) -> Result<(), String> {
+    let Some(ref path) = args.trace else {
+        return Ok(());
+    };
+
+    let trace =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {path:?}: {err}."))?;
+
+    // Addresses of a trace are where the object was loaded, rather than where it's linked.
+    let offset = match args.trace_base {
+        Some(base) => {
+            let obj = object::File::parse(processor.binary()).map_err(|_| {
+                "Object doesn't have a header to rebase the trace with.".to_string()
+            })?;
+            processor::Aslr::new(&obj).image_base as isize - base as isize
+        }
+        None => 0,
+    };
+
+    let (counts, unmapped) = counts(processor, &trace, offset);
+    if counts.is_empty() {
+        return Err(
+            "None of the addresses in the trace are instructions of the object, it might \
+                    have to be rebased with --trace-base."
+                .to_string(),
+        );
+    }
+
+    let mut addrs: Vec<usize> = counts.keys().copied().collect();
+    addrs.sort_unstable();
+
+    let mut printed = Vec::new();
+    for addr in addrs {
+        if printed.last().is_some_and(|&(_, end)| addr < end) {
+            continue;
+        }
+
+        let (start, insts) = function(processor, addr, args.colored());
+        let end = insts.last().map_or(addr + 1, |&(addr, _)| addr + 1);
+        printed.push((start, end));
+
+        let executed = insts.iter().filter(|(addr, _)| counts.contains_key(addr)).count();
+        let name = processor.index.get_sym_by_addr(start);
+        let name = name.as_ref().map_or("<unknown>", |sym| sym.as_str());
+
+        if printed.len() > 1 {
+            outln!();
+        }
+        outln!(
+            "{name}  ({executed} of {} instructions executed)",
+            insts.len()
+        );
+
+        for (addr, text) in insts {
+            match counts.get(&addr) {
+                Some(count) => outln!("{count:>10}  {text}"),
+                None => outln!("{:>10}  {text}", ""),
+            }
+        }
+    }
+
+    if unmapped > 0 {
+        outln!("\n{unmapped} lines of the trace aren't instructions of the object.");
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 //! Colors used for rendering text in the GUI.
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 pub use egui::Color32;
 
@@ -64,10 +64,29 @@ impl Deref for MaybeStatic {
     }
 }
 
+bitflags::bitflags! {
+    /// Parts of a demangled name a token belongs to, so that they can be left out when rendering.
+    /// A token can belong to several, e.g. the calling convention of a returned function pointer.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct Kind: u8 {
+        const CALLING_CONV = 1;
+        const ACCESS       = 1 << 1;
+        const RETURN_TYPE  = 1 << 2;
+        const QUALIFIER    = 1 << 3;
+    }
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        Kind::empty()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub text: MaybeStatic,
     pub color: Color32,
+    pub kind: Kind,
 }
 
 impl Token {
@@ -76,6 +95,7 @@ impl Token {
         Self {
             text: MaybeStatic::Static(text),
             color,
+            kind: Kind::empty(),
         }
     }
 
@@ -84,6 +104,7 @@ impl Token {
         Self {
             text: MaybeStatic::Dynamic(Arc::from(text)),
             color,
+            kind: Kind::empty(),
         }
     }
 }
@@ -124,6 +145,54 @@ impl TokenStream {
     }
 }
 
+/// Whether the terminal says it supports 24-bit colors, otherwise the 256-color palette is used.
+fn truecolor() -> bool {
+    static TRUECOLOR: OnceLock<bool> = OnceLock::new();
+    *TRUECOLOR.get_or_init(|| {
+        matches!(std::env::var("COLORTERM").as_deref(), Ok("truecolor" | "24bit"))
+    })
+}
+
+/// Closest color of the 6x6x6 cube of the 256-color palette.
+fn xterm_index(color: Color32) -> u8 {
+    let level = |value: u8| match value {
+        0..=47 => 0,
+        48..=114 => 1,
+        value => (value - 35) / 40,
+    };
+
+    16 + 36 * level(color.r()) + 6 * level(color.g()) + level(color.b())
+}
+
+/// Text of the tokens, colored with the escape codes of a terminal if `colored`.
+pub fn render(tokens: &[Token], colored: bool) -> String {
+    render_omitting(tokens, colored, Kind::empty())
+}
+
+/// Text of the tokens like [`render`], leaving out those of any of the kinds in `omit`.
+pub fn render_omitting(tokens: &[Token], colored: bool, omit: Kind) -> String {
+    let tokens = tokens.iter().filter(|t| !t.kind.intersects(omit));
+    if !colored {
+        return tokens.map(|t| &t.text as &str).collect();
+    }
+
+    let truecolor = truecolor();
+    let mut text = String::new();
+    for token in tokens {
+        let color = token.color;
+        text += &match truecolor {
+            true => format!("\x1b[38;2;{};{};{}m", color.r(), color.g(), color.b()),
+            false => format!("\x1b[38;5;{}m", xterm_index(color)),
+        };
+        text += &token.text;
+    }
+
+    if !text.is_empty() {
+        text += "\x1b[0m";
+    }
+    text
+}
+
 impl ToString for TokenStream {
     fn to_string(&self) -> String {
         self.inner.iter().map(|t| &t.text as &str).collect()